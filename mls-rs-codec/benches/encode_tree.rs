@@ -0,0 +1,138 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use criterion::{BenchmarkId, Criterion};
+use mls_rs_codec::{MlsEncode, MlsSize};
+
+/// Allocator counting allocations and reallocations.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Node of a ratchet tree, with the field sizes of a tree using
+/// Curve25519 keys and basic credentials.
+#[derive(MlsSize, MlsEncode)]
+#[repr(u8)]
+enum Node {
+    Leaf(Leaf) = 1u8,
+    Parent(Parent) = 2u8,
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct Leaf {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    encryption_key: Vec<u8>,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    signature_key: Vec<u8>,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    credential: Vec<u8>,
+    capabilities: Vec<u16>,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    signature: Vec<u8>,
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct Parent {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    encryption_key: Vec<u8>,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    parent_hash: Vec<u8>,
+    unmerged_leaves: Vec<u32>,
+}
+
+fn tree(leaf_count: usize) -> Vec<Option<Node>> {
+    (0..2 * leaf_count - 1)
+        .map(|i| {
+            let node = if i % 2 == 0 {
+                Node::Leaf(Leaf {
+                    encryption_key: vec![1; 32],
+                    signature_key: vec![2; 32],
+                    credential: (i as u64).to_be_bytes().to_vec(),
+                    capabilities: vec![1, 2, 3, 4, 5],
+                    signature: vec![3; 64],
+                })
+            } else {
+                Node::Parent(Parent {
+                    encryption_key: vec![4; 32],
+                    parent_hash: vec![5; 32],
+                    unmerged_leaves: vec![],
+                })
+            };
+
+            Some(node)
+        })
+        .collect()
+}
+
+fn allocations<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let res = f();
+    let after = ALLOCATIONS.load(Ordering::Relaxed);
+    drop(res);
+
+    after - before
+}
+
+fn bench(c: &mut Criterion) {
+    let mut bench_group = c.benchmark_group("encode_tree");
+
+    for leaf_count in [100, 1000, 10000] {
+        let tree = tree(leaf_count);
+        let mut buffer = Vec::new();
+
+        println!(
+            "{leaf_count} leaves, {} bytes: {} allocations to a new vec, {} to a reused buffer",
+            tree.mls_encoded_len(),
+            allocations(|| tree.mls_encode_to_vec().unwrap()),
+            allocations(|| {
+                buffer.clear();
+                tree.mls_encode_to_writer(&mut buffer).unwrap()
+            }),
+        );
+
+        bench_group.bench_with_input(BenchmarkId::new("to_vec", leaf_count), &tree, |b, tree| {
+            b.iter(|| tree.mls_encode_to_vec().unwrap())
+        });
+
+        bench_group.bench_with_input(
+            BenchmarkId::new("to_reused_buffer", leaf_count),
+            &tree,
+            |b, tree| {
+                b.iter(|| {
+                    buffer.clear();
+                    tree.mls_encode_to_writer(&mut buffer).unwrap()
+                })
+            },
+        );
+    }
+
+    bench_group.finish();
+}
+
+criterion::criterion_group!(benches, bench);
+criterion::criterion_main!(benches);
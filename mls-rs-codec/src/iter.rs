@@ -17,7 +17,6 @@ where
     header_length + len
 }
 
-#[cfg(feature = "preallocate")]
 pub fn mls_encode<I>(iter: I, writer: &mut Vec<u8>) -> Result<(), crate::Error>
 where
     I: IntoIterator + Clone,
@@ -39,25 +38,6 @@ where
     Ok(())
 }
 
-#[cfg(not(feature = "preallocate"))]
-pub fn mls_encode<I>(iter: I, writer: &mut Vec<u8>) -> Result<(), crate::Error>
-where
-    I: IntoIterator + Clone,
-    I::Item: MlsEncode,
-{
-    let mut buffer = Vec::new();
-
-    iter.into_iter()
-        .try_for_each(|x| x.mls_encode(&mut buffer))?;
-
-    let len = VarInt::try_from(buffer.len())?;
-
-    len.mls_encode(writer)?;
-    writer.extend(buffer);
-
-    Ok(())
-}
-
 pub fn mls_decode_collection<T, F>(reader: &mut &[u8], item_decode: F) -> Result<T, crate::Error>
 where
     F: Fn(&mut &[u8]) -> Result<T, crate::Error>,
@@ -18,6 +18,16 @@ pub mod byte_vec;
 
 pub mod iter;
 
+/// Zero-copy decoding of byte fields into reference-counted [`bytes::Bytes`].
+///
+/// Compatible with derive macros by using `mls_codec(with = "mls_rs_codec::shared_bytes")`,
+/// which copies the data. Types implementing
+/// [`MlsDecodeShared`](shared_bytes::MlsDecodeShared) decode their fields
+/// with a [`DecodeContext`](shared_bytes::DecodeContext) instead, without
+/// copying. The wire format is the same as [`byte_vec`].
+#[cfg(feature = "bytes")]
+pub mod shared_bytes;
+
 mod cow;
 mod map;
 mod option;
@@ -26,8 +36,10 @@ mod string;
 mod tuple;
 mod varint;
 mod vec;
+mod writer;
 
 pub use varint::*;
+pub use writer::Writer;
 
 pub use mls_rs_codec_derive::*;
 
@@ -45,6 +57,8 @@ pub enum Error {
     UnexpectedEOF,
     #[cfg_attr(feature = "std", error("Option marker out of range: {0}"))]
     OptionOutOfRange(u8),
+    #[cfg_attr(feature = "std", error("Boolean value out of range: {0}"))]
+    BoolOutOfRange(u8),
     #[cfg_attr(feature = "std", error("Unsupported enum discriminant"))]
     UnsupportedEnumDiscriminant,
     #[cfg_attr(feature = "std", error("Expected UTF-8 string"))]
@@ -82,18 +96,35 @@ where
 pub trait MlsEncode: MlsSize {
     fn mls_encode(&self, writer: &mut Vec<u8>) -> Result<(), Error>;
 
+    /// Encode into a new buffer, allocated once with the size computed by
+    /// [`MlsSize::mls_encoded_len`].
     #[inline]
     fn mls_encode_to_vec(&self) -> Result<Vec<u8>, Error> {
-        #[cfg(feature = "preallocate")]
         let mut vec = Vec::with_capacity(self.mls_encoded_len());
-
-        #[cfg(not(feature = "preallocate"))]
-        let mut vec = Vec::new();
-
         self.mls_encode(&mut vec)?;
 
         Ok(vec)
     }
+
+    /// Encode into `writer`, e.g. a buffer of the size computed by
+    /// [`MlsSize::mls_encoded_len`].
+    ///
+    /// Writing to a `Vec<u8>` appends the encoding after reserving space for
+    /// it, without intermediate buffers. Encoders only write to vectors, so
+    /// other writers, such as `&mut [u8]`, receive the encoding in a single
+    /// write from a temporary `Vec<u8>` allocated with the size computed by
+    /// [`MlsSize::mls_encoded_len`]. Encode into a vector to avoid this
+    /// allocation and copy.
+    #[inline]
+    fn mls_encode_to_writer<W: Writer>(&self, mut writer: W) -> Result<(), Error> {
+        match writer.as_vec() {
+            Some(vec) => {
+                vec.reserve(self.mls_encoded_len());
+                self.mls_encode(vec)
+            }
+            None => writer.write(&self.mls_encode_to_vec()?),
+        }
+    }
 }
 
 impl<T> MlsEncode for &T
@@ -0,0 +1,152 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use bytes::Bytes;
+
+use alloc::vec::Vec;
+
+use crate::{byte_vec, iter::mls_decode_split_on_collection, Error};
+
+/// Encoded length of a [`Bytes`] field, identical to [`byte_vec`].
+pub fn mls_encoded_len(data: &Bytes) -> usize {
+    byte_vec::mls_encoded_len(data)
+}
+
+/// Encoding of a [`Bytes`] field, identical to [`byte_vec`].
+pub fn mls_encode(data: &Bytes, writer: &mut Vec<u8>) -> Result<(), Error> {
+    byte_vec::mls_encode(data, writer)
+}
+
+/// Decoding of a [`Bytes`] field into a copy of the data.
+///
+/// Use [`DecodeContext::mls_decode`] to decode the field into a slice of
+/// the buffer being decoded.
+pub fn mls_decode(reader: &mut &[u8]) -> Result<Bytes, Error> {
+    let (data, rest) = mls_decode_split_on_collection(reader)?;
+
+    *reader = rest;
+
+    Ok(Bytes::copy_from_slice(data))
+}
+
+/// Buffer a value is decoded from, passed to the decoders of its [`Bytes`]
+/// fields so that they are decoded into slices of the buffer instead of
+/// copies.
+#[derive(Clone, Copy, Debug)]
+pub struct DecodeContext<'a> {
+    source: &'a Bytes,
+}
+
+impl<'a> DecodeContext<'a> {
+    pub fn new(source: &'a Bytes) -> Self {
+        Self { source }
+    }
+
+    /// Reader over the whole source buffer.
+    pub fn reader(&self) -> &'a [u8] {
+        self.source
+    }
+
+    /// Decoding of a [`Bytes`] field, see [`mls_decode`].
+    ///
+    /// The result is a slice of the source buffer if `reader` reads from it,
+    /// which keeps the buffer alive. Otherwise the data is copied.
+    pub fn mls_decode(&self, reader: &mut &[u8]) -> Result<Bytes, Error> {
+        let (data, rest) = mls_decode_split_on_collection(reader)?;
+
+        *reader = rest;
+
+        Ok(self.share(data))
+    }
+
+    fn share(&self, data: &[u8]) -> Bytes {
+        if data.is_empty() {
+            Bytes::new()
+        } else if self.contains(data) {
+            self.source.slice_ref(data)
+        } else {
+            Bytes::copy_from_slice(data)
+        }
+    }
+
+    fn contains(&self, data: &[u8]) -> bool {
+        let start = self.source.as_ptr() as usize;
+        let data_start = data.as_ptr() as usize;
+
+        data_start >= start && data_start + data.len() <= start + self.source.len()
+    }
+}
+
+/// Trait to support deserializing a type with MLS encoding, decoding its
+/// [`Bytes`] fields into slices of the buffer being decoded.
+pub trait MlsDecodeShared: Sized {
+    fn mls_decode_shared(reader: &mut &[u8], context: &DecodeContext<'_>) -> Result<Self, Error>;
+}
+
+/// Decode `T` from `buf`, decoding the [`Bytes`] fields of `T` into slices
+/// of `buf` instead of copies.
+///
+/// The slices keep `buf` alive.
+pub fn decode_shared<T: MlsDecodeShared>(buf: &Bytes) -> Result<T, Error> {
+    let context = DecodeContext::new(buf);
+
+    T::mls_decode_shared(&mut context.reader(), &context)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use bytes::Bytes;
+
+    use crate::Error;
+
+    use super::{DecodeContext, MlsDecodeShared};
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    struct Payload(Bytes);
+
+    impl MlsDecodeShared for Payload {
+        fn mls_decode_shared(
+            reader: &mut &[u8],
+            context: &DecodeContext<'_>,
+        ) -> Result<Self, Error> {
+            context.mls_decode(reader).map(Self)
+        }
+    }
+
+    #[test]
+    fn encoding_matches_byte_vec() {
+        let data = Bytes::from_static(&[1, 2, 3]);
+
+        let mut encoded = Vec::new();
+        super::mls_encode(&data, &mut encoded).unwrap();
+
+        assert_eq!(encoded, [3, 1, 2, 3]);
+        assert_eq!(super::mls_encoded_len(&data), encoded.len());
+        assert_eq!(super::mls_decode(&mut &*encoded).unwrap(), data);
+    }
+
+    #[test]
+    fn shared_decode_does_not_copy() {
+        let buf = Bytes::from(alloc::vec![3, 1, 2, 3]);
+
+        let Payload(data) = super::decode_shared(&buf).unwrap();
+
+        assert_eq!(data, [1, 2, 3][..]);
+        assert_eq!(data.as_ptr(), buf[1..].as_ptr());
+
+        // Without a context, the data is copied.
+        let data = super::mls_decode(&mut &*buf).unwrap();
+        assert_ne!(data.as_ptr(), buf[1..].as_ptr());
+
+        // Data read from another buffer is copied as well.
+        let other = buf.to_vec();
+        let context = DecodeContext::new(&buf);
+        let data = context.mls_decode(&mut &*other).unwrap();
+        assert_eq!(data, [1, 2, 3][..]);
+        assert_ne!(data.as_ptr(), other[1..].as_ptr());
+    }
+}
@@ -34,14 +34,38 @@ impl_stdint!(u32);
 impl_stdint!(u64);
 impl_stdint!(u128);
 
+impl MlsSize for bool {
+    fn mls_encoded_len(&self) -> usize {
+        1
+    }
+}
+
+impl MlsEncode for bool {
+    fn mls_encode(&self, writer: &mut Vec<u8>) -> Result<(), crate::Error> {
+        writer.push(u8::from(*self));
+        Ok(())
+    }
+}
+
+impl MlsDecode for bool {
+    fn mls_decode(reader: &mut &[u8]) -> Result<Self, crate::Error> {
+        match u8::mls_decode(reader)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            n => Err(crate::Error::BoolOutOfRange(n)),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[cfg(target_arch = "wasm32")]
     use wasm_bindgen_test::wasm_bindgen_test as test;
 
-    use crate::{MlsDecode, MlsEncode};
+    use crate::{Error, MlsDecode, MlsEncode};
 
     use alloc::vec;
+    use assert_matches::assert_matches;
 
     #[test]
     fn u8_round_trip() {
@@ -95,4 +119,22 @@ mod tests {
 
         assert_eq!(recovered, 10000000000000000u128);
     }
+
+    #[test]
+    fn bool_round_trip() {
+        for value in [false, true] {
+            let serialized = value.mls_encode_to_vec().unwrap();
+            assert_eq!(serialized, vec![u8::from(value)]);
+
+            let recovered = bool::mls_decode(&mut &*serialized).unwrap();
+
+            assert_eq!(recovered, value);
+        }
+    }
+
+    #[test]
+    fn bool_out_of_range() {
+        let res = bool::mls_decode(&mut &*vec![2u8]);
+        assert_matches!(res, Err(Error::BoolOutOfRange(2)));
+    }
 }
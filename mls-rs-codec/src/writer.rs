@@ -6,8 +6,20 @@ use crate::Error;
 
 use alloc::vec::Vec;
 
+/// Destination of [`MlsEncode::mls_encode_to_writer`](crate::MlsEncode::mls_encode_to_writer).
+///
+/// Writers that don't expose a vector with [`Writer::as_vec`] receive the
+/// encoding from a temporary buffer, see
+/// [`MlsEncode::mls_encode_to_writer`](crate::MlsEncode::mls_encode_to_writer).
 pub trait Writer {
     fn write(&mut self, bytes: &[u8]) -> Result<(), Error>;
+
+    /// The underlying buffer, if the writer appends to a `Vec<u8>` that values
+    /// can be encoded into directly.
+    #[inline]
+    fn as_vec(&mut self) -> Option<&mut Vec<u8>> {
+        None
+    }
 }
 
 impl<T: Writer + ?Sized> Writer for &mut T {
@@ -15,6 +27,11 @@ impl<T: Writer + ?Sized> Writer for &mut T {
     fn write(&mut self, bytes: &[u8]) -> Result<(), Error> {
         (**self).write(bytes)
     }
+
+    #[inline]
+    fn as_vec(&mut self) -> Option<&mut Vec<u8>> {
+        (**self).as_vec()
+    }
 }
 
 impl Writer for &mut [u8] {
@@ -37,4 +54,46 @@ impl Writer for Vec<u8> {
         self.extend_from_slice(bytes);
         Ok(())
     }
+
+    #[inline]
+    fn as_vec(&mut self) -> Option<&mut Vec<u8>> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Error, MlsEncode};
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    #[test]
+    fn encoding_is_appended_to_vec() {
+        let mut buffer = vec![0u8];
+        vec![1u8, 2, 3].mls_encode_to_writer(&mut buffer).unwrap();
+
+        assert_eq!(buffer, vec![0, 3, 1, 2, 3]);
+    }
+
+    #[test]
+    fn encoding_is_written_to_slice() {
+        let value = vec![1u8, 2, 3];
+        let mut buffer = [0u8; 5];
+
+        let mut remaining = &mut buffer[..];
+        value.mls_encode_to_writer(&mut remaining).unwrap();
+        assert_eq!(remaining.len(), 1);
+
+        assert_eq!(buffer, [3, 1, 2, 3, 0]);
+
+        let mut small = [0u8; 3];
+
+        assert_matches!(
+            value.mls_encode_to_writer(&mut &mut small[..]),
+            Err(Error::UnexpectedEOF)
+        );
+    }
 }
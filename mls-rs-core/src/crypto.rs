@@ -500,4 +500,11 @@ pub trait CipherSuiteProvider: Send + Sync {
         signature: &[u8],
         data: &[u8],
     ) -> Result<(), Self::Error>;
+
+    /// Suffix appended to the label of every signature created or verified with
+    /// this provider by `SignWithLabel` and `VerifyWithLabel`. The default is
+    /// empty, as defined by RFC 9420.
+    fn signature_label_suffix(&self) -> &[u8] {
+        &[]
+    }
 }
@@ -59,6 +59,23 @@ impl ExtensionType {
     pub const fn is_default(&self) -> bool {
         self.0 <= 5
     }
+
+    /// Determines if this extension type is a GREASE value as defined in
+    /// [RFC 9420](https://www.rfc-editor.org/rfc/rfc9420.html#name-grease).
+    pub const fn is_grease(&self) -> bool {
+        self.0 & 0x0F0F == 0x0A0A && self.0 >> 12 == (self.0 >> 4) & 0x0F && self.0 != 0xFAFA
+    }
+
+    /// Determines if a client that does not support this extension type must
+    /// reject messages containing it.
+    ///
+    /// By the convention of this library, extension types with the most
+    /// significant bit set are critical, except for the default types and
+    /// GREASE values. Unknown extension types that are not critical are
+    /// ignored and preserved as is.
+    pub const fn is_critical(&self) -> bool {
+        self.0 & 0x8000 != 0 && !self.is_default() && !self.is_grease()
+    }
 }
 
 impl From<u16> for ExtensionType {
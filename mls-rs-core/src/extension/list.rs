@@ -9,6 +9,12 @@ use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
 
 /// A collection of MLS [Extensions](super::Extension).
 ///
+/// Extensions added with [`ExtensionList::set`] and the functions built on it
+/// are kept sorted by extension type, so that lists built by different
+/// implementations from the same extensions are encoded identically. A list
+/// decoded from a message keeps the order of the message, since it is covered
+/// by signatures and transcript hashes, and can be sorted with
+/// [`ExtensionList::canonicalize`].
 ///
 /// # Warning
 ///
@@ -128,7 +134,9 @@ impl ExtensionList {
     /// [Extension](super::Extension) value.
     ///
     /// If there is already an entry in the list for the same extension type,
-    /// then the prior value is removed as part of the insertion.
+    /// then the prior value is removed as part of the insertion. Otherwise,
+    /// the extension is inserted before the first entry with a greater
+    /// extension type.
     pub fn set(&mut self, ext: Extension) {
         let mut found = self
             .0
@@ -138,7 +146,13 @@ impl ExtensionList {
         if let Some(found) = found.take() {
             *found = ext;
         } else {
-            self.0.push(ext);
+            let position = self
+                .0
+                .iter()
+                .position(|e| e.extension_type > ext.extension_type)
+                .unwrap_or(self.0.len());
+
+            self.0.insert(position, ext);
         }
     }
 
@@ -162,7 +176,41 @@ impl ExtensionList {
     /// If there is already an entry in the list for the same extension type,
     /// then the existing value is removed.
     pub fn append(&mut self, others: Self) {
-        self.0.extend(others.0);
+        self.extend(others.0);
+    }
+
+    /// Sort the list by extension type.
+    pub fn canonicalize(&mut self) {
+        self.0.sort_by_key(|e| e.extension_type);
+    }
+
+    /// Determine if the list is sorted by extension type.
+    pub fn is_canonical(&self) -> bool {
+        self.0
+            .windows(2)
+            .all(|pair| pair[0].extension_type < pair[1].extension_type)
+    }
+
+    /// Extensions whose type is neither a default type, a GREASE value nor in
+    /// `known`.
+    ///
+    /// Unknown extensions keep the exact bytes they were decoded from.
+    pub fn unknown<'a>(
+        &'a self,
+        known: &'a [ExtensionType],
+    ) -> impl Iterator<Item = &'a Extension> + 'a {
+        self.0.iter().filter(move |ext| {
+            let ext_type = ext.extension_type;
+            !ext_type.is_default() && !ext_type.is_grease() && !known.contains(&ext_type)
+        })
+    }
+
+    /// The first [unknown](ExtensionList::unknown) extension type that is
+    /// [critical](ExtensionType::is_critical), if any.
+    pub fn unknown_critical(&self, known: &[ExtensionType]) -> Option<ExtensionType> {
+        self.unknown(known)
+            .map(|ext| ext.extension_type)
+            .find(ExtensionType::is_critical)
     }
 }
 
@@ -361,4 +409,96 @@ mod tests {
 
         assert_eq!(list, expected);
     }
+
+    #[test]
+    fn extension_list_is_kept_sorted_by_extension_type() {
+        let mut list = ExtensionList::new();
+        list.set_from(TestExtensionC(1)).unwrap();
+        list.set_from(TestExtensionA(2)).unwrap();
+        list.set_from(TestExtensionB(vec![3])).unwrap();
+
+        assert!(list.is_canonical());
+
+        let types = list.iter().map(|e| e.extension_type).collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            [ExtensionType(128), ExtensionType(129), ExtensionType(130)]
+        );
+    }
+
+    #[test]
+    fn extension_list_is_encoded_in_canonical_order() {
+        let extensions = [
+            TestExtensionC(1).into_extension().unwrap(),
+            TestExtensionA(2).into_extension().unwrap(),
+        ];
+
+        let a = extensions.iter().cloned().collect::<ExtensionList>();
+        let b = extensions.iter().rev().cloned().collect::<ExtensionList>();
+
+        assert_eq!(
+            a.mls_encode_to_vec().unwrap(),
+            b.mls_encode_to_vec().unwrap()
+        );
+    }
+
+    #[test]
+    fn decoded_extension_list_keeps_order_until_canonicalized() {
+        let extensions = ExtensionsVec(vec![
+            TestExtensionC(1).into_extension().unwrap(),
+            TestExtensionA(2).into_extension().unwrap(),
+        ]);
+
+        let serialized_extensions = extensions.mls_encode_to_vec().unwrap();
+        let mut list = ExtensionList::mls_decode(&mut &*serialized_extensions).unwrap();
+
+        assert!(!list.is_canonical());
+        assert_eq!(list.mls_encode_to_vec().unwrap(), serialized_extensions);
+
+        list.canonicalize();
+
+        assert!(list.is_canonical());
+        assert_eq!(list.first().unwrap().extension_type, ExtensionType(128));
+    }
+
+    #[test]
+    fn appending_extension_list_maintains_extension_uniqueness() {
+        let mut list = ExtensionList::new();
+        list.set_from(TestExtensionA(33)).unwrap();
+        list.set_from(TestExtensionC(34)).unwrap();
+
+        list.append(ExtensionList::from(vec![
+            TestExtensionA(35).into_extension().unwrap(),
+            TestExtensionB(vec![36]).into_extension().unwrap(),
+        ]));
+
+        let expected = ExtensionList(vec![
+            TestExtensionA(35).into_extension().unwrap(),
+            TestExtensionB(vec![36]).into_extension().unwrap(),
+            TestExtensionC(34).into_extension().unwrap(),
+        ]);
+
+        assert_eq!(list.len(), 3);
+        assert_eq!(list, expected);
+    }
+
+    #[test]
+    fn unknown_critical_extensions_are_found() {
+        let list = ExtensionList(vec![
+            Extension::new(ExtensionType::APPLICATION_ID, vec![0]),
+            Extension::new(ExtensionType(0x0042), vec![1]),
+            Extension::new(ExtensionType(0x8A8A), vec![2]),
+            Extension::new(ExtensionType(0x8042), vec![3]),
+        ]);
+
+        let unknown = list.unknown(&[]).map(|ext| ext.extension_type);
+
+        assert_eq!(
+            unknown.collect::<Vec<_>>(),
+            vec![ExtensionType(0x0042), ExtensionType(0x8042)]
+        );
+
+        assert_eq!(list.unknown_critical(&[]), Some(ExtensionType(0x8042)));
+        assert_eq!(list.unknown_critical(&[ExtensionType(0x8042)]), None);
+    }
 }
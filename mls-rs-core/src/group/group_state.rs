@@ -59,11 +59,23 @@ impl EpochRecord {
 ///
 /// # Cleaning up records
 ///
-/// Group state will not be purged when the local member is removed from the
-/// group. It is up to the implementer of this trait to provide a mechanism
-/// to delete records that can be used by an application.
+/// When a commit removing the local member from the group is processed,
+/// [`delete`](GroupStateStorage::delete) is called to purge the group state
+/// and all prior epochs. The default implementation of this function does
+/// nothing, in which case it is up to the implementer of this trait to
+/// provide a mechanism to delete records that can be used by an application.
 ///
-
+/// # Transactions
+///
+/// All writes and deletes caused by persisting a group, e.g. after
+/// processing a message, happen between a call to
+/// [`begin_transaction`](GroupStateStorage::begin_transaction) and a call to
+/// either [`commit_transaction`](GroupStateStorage::commit_transaction) or,
+/// if any of them failed,
+/// [`rollback_transaction`](GroupStateStorage::rollback_transaction).
+/// Implementations backed by slow or remote storage can use these hooks to
+/// buffer the writes and apply them at once, so that other readers never
+/// observe partially written state. The default implementations do nothing.
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
 pub trait GroupStateStorage: Send + Sync {
@@ -104,4 +116,27 @@ pub trait GroupStateStorage: Send + Sync {
     /// The [`EpochRecord::id`] value that is associated with a stored
     /// prior epoch for a particular group.
     async fn max_epoch_id(&self, group_id: &[u8]) -> Result<Option<u64>, Self::Error>;
+
+    /// Delete the group state and all prior epochs of a particular group.
+    async fn delete(&mut self, group_id: &[u8]) -> Result<(), Self::Error> {
+        let _ = group_id;
+        Ok(())
+    }
+
+    /// Start a transaction grouping the following writes and deletes.
+    async fn begin_transaction(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Apply all writes and deletes made since
+    /// [`begin_transaction`](GroupStateStorage::begin_transaction).
+    async fn commit_transaction(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Discard all writes and deletes made since
+    /// [`begin_transaction`](GroupStateStorage::begin_transaction).
+    async fn rollback_transaction(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
 }
@@ -63,7 +63,9 @@ pub trait KeyPackageStorage: Send + Sync {
     /// Delete [`KeyPackageData`] referenced by `id`.
     ///
     /// This function is called automatically when the key package referenced
-    /// by `id` is used to successfully join a group.
+    /// by `id` is used to successfully join a group, unless a grace period for
+    /// consumed key packages is configured, see
+    /// [`mark_consumed`](KeyPackageStorage::mark_consumed).
     ///
     /// # Warning
     ///
@@ -81,4 +83,56 @@ pub trait KeyPackageStorage: Send + Sync {
     /// `None` should be returned in the event that no key packages are found
     /// that match `id`.
     async fn get(&self, id: &[u8]) -> Result<Option<KeyPackageData>, Self::Error>;
+
+    /// Record that the key package referenced by `id` was used to join a
+    /// group at `consumed_at`, in seconds since the Unix epoch.
+    ///
+    /// This function is called instead of
+    /// [`delete`](KeyPackageStorage::delete) when a grace period for consumed
+    /// key packages is configured. The key package must remain available with
+    /// [`get`](KeyPackageStorage::get) until it is deleted by
+    /// [`delete_consumed`](KeyPackageStorage::delete_consumed). The default
+    /// implementation deletes it immediately.
+    async fn mark_consumed(&mut self, id: &[u8], consumed_at: u64) -> Result<(), Self::Error> {
+        let _ = consumed_at;
+        self.delete(id).await
+    }
+
+    /// Delete all key packages that were
+    /// [marked consumed](KeyPackageStorage::mark_consumed) at or before
+    /// `consumed_before`, in seconds since the Unix epoch.
+    ///
+    /// # Warning
+    ///
+    /// As for [`delete`](KeyPackageStorage::delete), the provided delete
+    /// mechanism should securely erase data. The default implementation does
+    /// nothing.
+    async fn delete_consumed(&mut self, consumed_before: u64) -> Result<(), Self::Error> {
+        let _ = consumed_before;
+        Ok(())
+    }
+
+    /// Start a transaction grouping the following deletes.
+    ///
+    /// When a group is persisted, the key package used to join it is deleted
+    /// in a transaction committed after the transaction of
+    /// [`GroupStateStorage`](crate::group::GroupStateStorage). If committing
+    /// fails, the group state remains written and the delete is retried the
+    /// next time the group is persisted. The default implementation does
+    /// nothing.
+    async fn begin_transaction(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Apply all deletes made since
+    /// [`begin_transaction`](KeyPackageStorage::begin_transaction).
+    async fn commit_transaction(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Discard all deletes made since
+    /// [`begin_transaction`](KeyPackageStorage::begin_transaction).
+    async fn rollback_transaction(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
 }
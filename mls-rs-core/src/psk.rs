@@ -134,4 +134,12 @@ pub trait PreSharedKeyStorage: Send + Sync {
     async fn contains(&self, id: &ExternalPskId) -> Result<bool, Self::Error> {
         self.get(id).await.map(|key| key.is_some())
     }
+
+    /// Enumerate the ids of all pre-shared keys within the store.
+    ///
+    /// The default implementation returns an empty list, which is appropriate
+    /// for stores that can't list their contents.
+    async fn ids(&self) -> Result<Vec<ExternalPskId>, Self::Error> {
+        Ok(Vec::new())
+    }
 }
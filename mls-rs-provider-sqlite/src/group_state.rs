@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
 use mls_rs_core::group::{EpochRecord, GroupState, GroupStateStorage};
-use rusqlite::{params, Connection, OptionalExtension};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
 use std::{
     fmt::Debug,
     sync::{Arc, Mutex},
@@ -13,11 +13,29 @@ use crate::SqLiteDataStorageError;
 
 pub(crate) const DEFAULT_EPOCH_RETENTION_LIMIT: u64 = 3;
 
+#[derive(Debug, Clone)]
+enum Operation {
+    Write {
+        group_id: Vec<u8>,
+        snapshot: Vec<u8>,
+        inserts: Vec<EpochRecord>,
+        updates: Vec<EpochRecord>,
+    },
+    Delete(Vec<u8>),
+}
+
 #[derive(Debug, Clone)]
 /// SQLite Storage for MLS group states.
+///
+/// Writes and deletes made through [`GroupStateStorage`] between
+/// [`begin_transaction`](GroupStateStorage::begin_transaction) and
+/// [`commit_transaction`](GroupStateStorage::commit_transaction) are
+/// buffered and applied in a single SQLite transaction when committing, so
+/// that the database is not locked while the group is persisted.
 pub struct SqLiteGroupStateStorage {
     connection: Arc<Mutex<Connection>>,
     max_epoch_retention: u64,
+    pending: Option<Vec<Operation>>,
 }
 
 impl SqLiteGroupStateStorage {
@@ -25,13 +43,14 @@ impl SqLiteGroupStateStorage {
         SqLiteGroupStateStorage {
             connection: Arc::new(Mutex::new(connection)),
             max_epoch_retention: DEFAULT_EPOCH_RETENTION_LIMIT,
+            pending: None,
         }
     }
 
     pub fn with_max_epoch_retention(self, max_epoch_retention: u64) -> Self {
         Self {
-            connection: self.connection,
             max_epoch_retention,
+            ..self
         }
     }
 
@@ -57,15 +76,7 @@ impl SqLiteGroupStateStorage {
 
     /// Delete a group from storage.
     pub fn delete_group(&self, group_id: &[u8]) -> Result<(), SqLiteDataStorageError> {
-        let connection = self.connection.lock().unwrap();
-
-        connection
-            .execute(
-                "DELETE FROM mls_group WHERE group_id = ?",
-                params![group_id],
-            )
-            .map(|_| ())
-            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+        self.apply(vec![Operation::Delete(group_id.to_vec())])
     }
 
     pub fn max_epoch_retention(&self) -> u64 {
@@ -117,6 +128,7 @@ impl SqLiteGroupStateStorage {
             .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
     }
 
+    #[cfg(test)]
     fn update_group_state(
         &self,
         group_id: &[u8],
@@ -124,13 +136,55 @@ impl SqLiteGroupStateStorage {
         inserts: Vec<EpochRecord>,
         updates: Vec<EpochRecord>,
     ) -> Result<(), SqLiteDataStorageError> {
-        let mut max_epoch_id = None;
+        self.apply(vec![Operation::Write {
+            group_id: group_id.to_vec(),
+            snapshot: group_snapshot,
+            inserts,
+            updates,
+        }])
+    }
 
+    fn apply(&self, operations: Vec<Operation>) -> Result<(), SqLiteDataStorageError> {
         let mut connection = self.connection.lock().unwrap();
         let transaction = connection
             .transaction()
             .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
 
+        for operation in operations {
+            match operation {
+                Operation::Write {
+                    group_id,
+                    snapshot,
+                    inserts,
+                    updates,
+                } => self.write_in(&transaction, &group_id, snapshot, inserts, updates)?,
+                Operation::Delete(group_id) => {
+                    transaction
+                        .execute(
+                            "DELETE FROM mls_group WHERE group_id = ?",
+                            params![group_id],
+                        )
+                        .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+                }
+            }
+        }
+
+        // Execute the full transaction
+        transaction
+            .commit()
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+    }
+
+    fn write_in(
+        &self,
+        transaction: &Transaction<'_>,
+        group_id: &[u8],
+        group_snapshot: Vec<u8>,
+        inserts: Vec<EpochRecord>,
+        updates: Vec<EpochRecord>,
+    ) -> Result<(), SqLiteDataStorageError> {
+        let mut max_epoch_id = None;
+
         // Upsert into the group table to set the most recent snapshot
         transaction.execute(
             "INSERT INTO mls_group (group_id, snapshot) VALUES (?, ?) ON CONFLICT(group_id) DO UPDATE SET snapshot=excluded.snapshot",
@@ -175,10 +229,17 @@ impl SqLiteGroupStateStorage {
             }
         }
 
-        // Execute the full transaction
-        transaction
-            .commit()
-            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+        Ok(())
+    }
+
+    fn submit(&mut self, operation: Operation) -> Result<(), SqLiteDataStorageError> {
+        match self.pending {
+            Some(ref mut pending) => {
+                pending.push(operation);
+                Ok(())
+            }
+            None => self.apply(vec![operation]),
+        }
     }
 }
 
@@ -193,10 +254,12 @@ impl GroupStateStorage for SqLiteGroupStateStorage {
         inserts: Vec<EpochRecord>,
         updates: Vec<EpochRecord>,
     ) -> Result<(), Self::Error> {
-        let group_id = state.id;
-        let snapshot_data = state.data;
-
-        self.update_group_state(&group_id, snapshot_data, inserts, updates)
+        self.submit(Operation::Write {
+            group_id: state.id,
+            snapshot: state.data,
+            inserts,
+            updates,
+        })
     }
 
     async fn state(&self, group_id: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
@@ -210,6 +273,27 @@ impl GroupStateStorage for SqLiteGroupStateStorage {
     async fn epoch(&self, group_id: &[u8], epoch_id: u64) -> Result<Option<Vec<u8>>, Self::Error> {
         self.get_epoch_data(group_id, epoch_id)
     }
+
+    async fn delete(&mut self, group_id: &[u8]) -> Result<(), Self::Error> {
+        self.submit(Operation::Delete(group_id.to_vec()))
+    }
+
+    async fn begin_transaction(&mut self) -> Result<(), Self::Error> {
+        self.pending = Some(Vec::new());
+        Ok(())
+    }
+
+    async fn commit_transaction(&mut self) -> Result<(), Self::Error> {
+        match self.pending.take() {
+            Some(operations) => self.apply(operations),
+            None => Ok(()),
+        }
+    }
+
+    async fn rollback_transaction(&mut self) -> Result<(), Self::Error> {
+        self.pending = None;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -490,4 +574,87 @@ mod tests {
 
         assert!(test_data.storage.group_ids().unwrap().is_empty());
     }
+
+    #[cfg(not(mls_build_async))]
+    #[test]
+    fn transaction_is_applied_on_commit() {
+        let mut test_data = setup_group_storage_test();
+        let new_group = test_group_id();
+        let new_snapshot = test_snapshot();
+
+        test_data.storage.begin_transaction().unwrap();
+
+        test_data
+            .storage
+            .write(
+                GroupState {
+                    id: new_group.clone(),
+                    data: new_snapshot.clone(),
+                },
+                vec![test_epoch(0)],
+                vec![],
+            )
+            .unwrap();
+
+        GroupStateStorage::delete(&mut test_data.storage, &test_data.group_id).unwrap();
+
+        // Nothing is written before the transaction is committed.
+        assert!(test_data
+            .storage
+            .get_snapshot_data(&new_group)
+            .unwrap()
+            .is_none());
+
+        assert!(test_data
+            .storage
+            .get_snapshot_data(&test_data.group_id)
+            .unwrap()
+            .is_some());
+
+        test_data.storage.commit_transaction().unwrap();
+
+        assert_eq!(
+            test_data.storage.get_snapshot_data(&new_group).unwrap(),
+            Some(new_snapshot)
+        );
+
+        assert_eq!(test_data.storage.group_ids().unwrap(), vec![new_group]);
+    }
+
+    #[cfg(not(mls_build_async))]
+    #[test]
+    fn transaction_is_discarded_on_rollback() {
+        let mut test_data = setup_group_storage_test();
+
+        test_data.storage.begin_transaction().unwrap();
+
+        test_data
+            .storage
+            .write(
+                GroupState {
+                    id: test_data.group_id.clone(),
+                    data: test_snapshot(),
+                },
+                vec![test_epoch(1)],
+                vec![],
+            )
+            .unwrap();
+
+        test_data.storage.rollback_transaction().unwrap();
+        test_data.storage.commit_transaction().unwrap();
+
+        assert_eq!(
+            test_data
+                .storage
+                .get_snapshot_data(&test_data.group_id)
+                .unwrap(),
+            Some(test_data.snapshot)
+        );
+
+        assert!(test_data
+            .storage
+            .get_epoch_data(&test_data.group_id, 1)
+            .unwrap()
+            .is_none());
+    }
 }
@@ -14,14 +14,21 @@ use crate::SqLiteDataStorageError;
 
 #[derive(Debug, Clone)]
 /// SQLite storage for MLS Key Packages.
+///
+/// Deletes made through [`KeyPackageStorage`] between
+/// [`begin_transaction`](KeyPackageStorage::begin_transaction) and
+/// [`commit_transaction`](KeyPackageStorage::commit_transaction) are
+/// buffered and applied in a single SQLite transaction when committing.
 pub struct SqLiteKeyPackageStorage {
     connection: Arc<Mutex<Connection>>,
+    pending_deletes: Option<Vec<Vec<u8>>>,
 }
 
 impl SqLiteKeyPackageStorage {
     pub(crate) fn new(connection: Connection) -> SqLiteKeyPackageStorage {
         SqLiteKeyPackageStorage {
             connection: Arc::new(Mutex::new(connection)),
+            pending_deletes: None,
         }
     }
 
@@ -75,6 +82,23 @@ impl SqLiteKeyPackageStorage {
             .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
     }
 
+    fn delete_all(&self, ids: Vec<Vec<u8>>) -> Result<(), SqLiteDataStorageError> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection
+            .transaction()
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+        for id in ids {
+            transaction
+                .execute("DELETE FROM key_package where id = ?", params![id])
+                .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+        }
+
+        transaction
+            .commit()
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+    }
+
     pub fn delete_expired(&self) -> Result<(), SqLiteDataStorageError> {
         self.delete_expired_by_time(MlsTime::now().seconds_since_epoch())
     }
@@ -106,7 +130,30 @@ impl KeyPackageStorage for SqLiteKeyPackageStorage {
     }
 
     async fn delete(&mut self, id: &[u8]) -> Result<(), Self::Error> {
-        (*self).delete(id)
+        match self.pending_deletes {
+            Some(ref mut pending) => {
+                pending.push(id.to_vec());
+                Ok(())
+            }
+            None => (*self).delete(id),
+        }
+    }
+
+    async fn begin_transaction(&mut self) -> Result<(), Self::Error> {
+        self.pending_deletes = Some(Vec::new());
+        Ok(())
+    }
+
+    async fn commit_transaction(&mut self) -> Result<(), Self::Error> {
+        match self.pending_deletes.take() {
+            Some(ids) => self.delete_all(ids),
+            None => Ok(()),
+        }
+    }
+
+    async fn rollback_transaction(&mut self) -> Result<(), Self::Error> {
+        self.pending_deletes = None;
+        Ok(())
     }
 }
 
@@ -215,4 +262,28 @@ mod tests {
         assert!(storage.get(&data[2].0).unwrap().is_none());
         assert!(storage.get(&data[3].0).unwrap().is_none());
     }
+
+    #[cfg(not(mls_build_async))]
+    #[test]
+    fn key_package_delete_in_transaction() {
+        use mls_rs_core::key_package::KeyPackageStorage;
+
+        let mut storage = test_storage();
+        let (key_package_id, key_package) = test_key_package();
+
+        storage.insert(&key_package_id, key_package).unwrap();
+
+        storage.begin_transaction().unwrap();
+        KeyPackageStorage::delete(&mut storage, &key_package_id).unwrap();
+        storage.rollback_transaction().unwrap();
+
+        assert!(storage.get(&key_package_id).unwrap().is_some());
+
+        storage.begin_transaction().unwrap();
+        KeyPackageStorage::delete(&mut storage, &key_package_id).unwrap();
+        assert!(storage.get(&key_package_id).unwrap().is_some());
+        storage.commit_transaction().unwrap();
+
+        assert!(storage.get(&key_package_id).unwrap().is_none());
+    }
 }
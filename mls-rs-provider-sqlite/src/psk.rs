@@ -51,6 +51,23 @@ impl SqLitePreSharedKeyStorage {
             .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
     }
 
+    /// Get the ids of all pre-shared keys in storage.
+    pub fn ids(&self) -> Result<Vec<ExternalPskId>, SqLiteDataStorageError> {
+        let connection = self.connection.lock().unwrap();
+
+        let mut statement = connection
+            .prepare("SELECT psk_id FROM psk")
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+        let ids = statement
+            .query_map([], |row| Ok(ExternalPskId::new(row.get(0)?)))
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?
+            .collect::<Result<_, _>>()
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+        Ok(ids)
+    }
+
     /// Delete a pre-shared key from storage based on a unique id.
     pub fn delete(&self, psk_id: &[u8]) -> Result<(), SqLiteDataStorageError> {
         let connection = self.connection.lock().unwrap();
@@ -71,6 +88,10 @@ impl PreSharedKeyStorage for SqLitePreSharedKeyStorage {
         self.get(id)
             .map_err(|e| SqLiteDataStorageError::DataConversionError(e.into()))
     }
+
+    async fn ids(&self) -> Result<Vec<ExternalPskId>, Self::Error> {
+        self.ids()
+    }
 }
 
 #[cfg(test)]
@@ -123,6 +144,25 @@ mod tests {
         assert_eq!(from_storage, new_psk);
     }
 
+    #[test]
+    fn test_ids() {
+        let (psk_id, psk) = test_psk();
+        let (other_id, other_psk) = test_psk();
+
+        let storage = test_storage();
+
+        storage.insert(psk_id.clone(), psk).unwrap();
+        storage.insert(other_id.clone(), other_psk).unwrap();
+
+        let mut ids = storage.ids().unwrap();
+        ids.sort();
+
+        let mut expected = vec![psk_id.into(), other_id.into()];
+        expected.sort();
+
+        assert_eq!(ids, expected);
+    }
+
     #[test]
     fn test_delete() {
         let (psk_id, psk) = test_psk();
@@ -9,7 +9,7 @@ use mls_rs::{
 use mls_rs_crypto_openssl::OpensslCryptoProvider;
 
 use self::group_state::{GroupStateStorage, GroupStateStorageAdapter};
-use crate::Error;
+use crate::{Error, ErrorRenderer};
 
 pub mod group_state;
 
@@ -70,6 +70,11 @@ pub struct ClientConfig {
     /// Use the ratchet tree extension. If this is false, then you
     /// must supply `ratchet_tree` out of band to clients.
     pub use_ratchet_tree_extension: bool,
+    /// Describe the errors of this client and its groups with the
+    /// messages of this renderer. If this is `None`, errors are
+    /// described with messages intended for developers.
+    #[uniffi(default = None)]
+    pub error_renderer: Option<Arc<dyn ErrorRenderer>>,
 }
 
 impl Default for ClientConfig {
@@ -79,6 +84,7 @@ impl Default for ClientConfig {
                 InMemoryGroupStateStorage::new(),
             )),
             use_ratchet_tree_extension: true,
+            error_renderer: None,
         }
     }
 }
@@ -19,6 +19,7 @@
 
 mod config;
 
+use std::fmt::Debug;
 use std::sync::Arc;
 
 pub use config::ClientConfig;
@@ -29,7 +30,7 @@ use std::sync::Mutex;
 #[cfg(mls_build_async)]
 use tokio::sync::Mutex;
 
-use mls_rs::error::{IntoAnyError, MlsError};
+use mls_rs::error::{ErrorRenderer as _, IntoAnyError, MlsError, SharedErrorRenderer};
 use mls_rs::group;
 use mls_rs::identity::basic;
 use mls_rs::mls_rules;
@@ -54,10 +55,10 @@ fn arc_unwrap_or_clone<T: Clone>(arc: Arc<T>) -> T {
 #[uniffi(flat_error)]
 #[non_exhaustive]
 pub enum Error {
-    #[error("A mls-rs error occurred: {inner}")]
+    #[error("{message}")]
     MlsError {
-        #[from]
         inner: mls_rs::error::MlsError,
+        message: String,
     },
     #[error("An unknown error occurred: {inner}")]
     AnyError {
@@ -78,6 +79,46 @@ pub enum Error {
 
 impl IntoAnyError for Error {}
 
+impl From<MlsError> for Error {
+    fn from(inner: MlsError) -> Self {
+        render_error(None, inner)
+    }
+}
+
+/// Describe `inner` with `renderer`, or with a message intended for
+/// developers if the client has no renderer.
+fn render_error(renderer: Option<SharedErrorRenderer>, inner: MlsError) -> Error {
+    let message = match renderer {
+        Some(renderer) => renderer.render(&inner),
+        None => format!("A mls-rs error occurred: {inner}"),
+    };
+
+    Error::MlsError { inner, message }
+}
+
+/// Mapping of mls-rs error codes to messages that can be shown to
+/// users, e.g. in their language.
+///
+/// A renderer is configured for each client with
+/// [`ClientConfig::error_renderer`]. See
+/// [`mls_rs::error::ErrorRenderer`] for details.
+#[uniffi::export(with_foreign)]
+pub trait ErrorRenderer: Send + Sync + Debug {
+    /// Message describing errors with `code`, or `None` to use the
+    /// default English message.
+    ///
+    /// See [`mls_rs::error::MlsError::code`] for the codes.
+    fn message(&self, code: String) -> Option<String>;
+}
+
+struct ErrorRendererAdapter(Arc<dyn ErrorRenderer>);
+
+impl mls_rs::error::ErrorRenderer for ErrorRendererAdapter {
+    fn message(&self, code: &str) -> Option<String> {
+        self.0.message(code.to_string())
+    }
+}
+
 /// A [`mls_rs::crypto::SignaturePublicKey`] wrapper.
 #[derive(Clone, Debug, uniffi::Record)]
 pub struct SignaturePublicKey {
@@ -345,6 +386,13 @@ pub struct Client {
     inner: mls_rs::client::Client<UniFFIConfig>,
 }
 
+impl Client {
+    /// Describe `inner` with the error renderer of this client.
+    fn mls_error(&self, inner: MlsError) -> Error {
+        render_error(self.inner.error_renderer(), inner)
+    }
+}
+
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
 #[uniffi::export]
@@ -372,13 +420,18 @@ impl Client {
             .with_ratchet_tree_extension(client_config.use_ratchet_tree_extension)
             .with_single_welcome_message(true);
         let mls_rules = mls_rules::DefaultMlsRules::new().with_commit_options(commit_options);
-        let client = mls_rs::Client::builder()
+        let builder = mls_rs::Client::builder()
             .crypto_provider(crypto_provider)
             .identity_provider(basic::BasicIdentityProvider::new())
             .signing_identity(signing_identity, secret_key.into(), cipher_suite.into())
             .group_state_storage(client_config.group_state_storage.into())
-            .mls_rules(mls_rules)
-            .build();
+            .mls_rules(mls_rules);
+
+        let client = match client_config.error_renderer {
+            Some(renderer) => builder.error_renderer(ErrorRendererAdapter(renderer)),
+            None => builder,
+        }
+        .build();
 
         Client { inner: client }
     }
@@ -392,12 +445,19 @@ impl Client {
     /// See [`mls_rs::Client::generate_key_package_message`] for
     /// details.
     pub async fn generate_key_package_message(&self) -> Result<Message, Error> {
-        let message = self.inner.generate_key_package_message().await?;
+        let message = self
+            .inner
+            .generate_key_package_message()
+            .await
+            .map_err(|err| self.mls_error(err))?;
         Ok(message.into())
     }
 
     pub fn signing_identity(&self) -> Result<Arc<SigningIdentity>, Error> {
-        let (signing_identity, _) = self.inner.signing_identity()?;
+        let (signing_identity, _) = self
+            .inner
+            .signing_identity()
+            .map_err(|err| self.mls_error(err))?;
         Ok(Arc::new(signing_identity.clone().into()))
     }
 
@@ -411,15 +471,13 @@ impl Client {
     pub async fn create_group(&self, group_id: Option<Vec<u8>>) -> Result<Group, Error> {
         let extensions = mls_rs::ExtensionList::new();
         let inner = match group_id {
-            Some(group_id) => {
-                self.inner
-                    .create_group_with_id(group_id, extensions)
-                    .await?
-            }
-            None => self.inner.create_group(extensions).await?,
-        };
+            Some(group_id) => self.inner.create_group_with_id(group_id, extensions).await,
+            None => self.inner.create_group(extensions).await,
+        }
+        .map_err(|err| self.mls_error(err))?;
         Ok(Group {
             inner: Arc::new(Mutex::new(inner)),
+            error_renderer: self.inner.error_renderer(),
         })
     }
 
@@ -428,20 +486,24 @@ impl Client {
     /// You must supply `ratchet_tree` if the client that created
     /// `welcome_message` did not set `use_ratchet_tree_extension`.
     ///
-    /// See [`mls_rs::Client::join_group`] for details.
+    /// See [`mls_rs::Client::join_group_validated`] for details.
     pub async fn join_group(
         &self,
         ratchet_tree: Option<RatchetTree>,
         welcome_message: &Message,
     ) -> Result<JoinInfo, Error> {
         let ratchet_tree = ratchet_tree.map(TryInto::try_into).transpose()?;
+        let welcome_message = group::Validated::new(welcome_message.inner.clone())
+            .map_err(|err| self.mls_error(err))?;
         let (group, new_member_info) = self
             .inner
-            .join_group(ratchet_tree, &welcome_message.inner)
-            .await?;
+            .join_group_validated(ratchet_tree, &welcome_message)
+            .await
+            .map_err(|err| self.mls_error(err))?;
 
         let group = Arc::new(Group {
             inner: Arc::new(Mutex::new(group)),
+            error_renderer: self.inner.error_renderer(),
         });
         let group_info_extensions = Arc::new(new_member_info.group_info_extensions.into());
         Ok(JoinInfo {
@@ -459,8 +521,9 @@ impl Client {
             .await
             .map(|g| Group {
                 inner: Arc::new(Mutex::new(g)),
+                error_renderer: self.inner.error_renderer(),
             })
-            .map_err(Into::into)
+            .map_err(|err| self.mls_error(err))
     }
 }
 
@@ -554,6 +617,7 @@ impl From<identity::SigningIdentity> for SigningIdentity {
 #[derive(Clone, uniffi::Object)]
 pub struct Group {
     inner: Arc<Mutex<mls_rs::Group<UniFFIConfig>>>,
+    error_renderer: Option<SharedErrorRenderer>,
 }
 
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -568,6 +632,12 @@ impl Group {
     async fn inner(&self) -> tokio::sync::MutexGuard<'_, mls_rs::Group<UniFFIConfig>> {
         self.inner.lock().await
     }
+
+    /// Describe `inner` with the error renderer of the client of this
+    /// group.
+    fn mls_error(&self, inner: MlsError) -> Error {
+        render_error(self.error_renderer.clone(), inner)
+    }
 }
 
 /// Find the identity for the member with a given index.
@@ -602,7 +672,10 @@ impl Group {
     /// [`ClientConfig::group_state_storage`]
     pub async fn write_to_storage(&self) -> Result<(), Error> {
         let mut group = self.inner().await;
-        group.write_to_storage().await.map_err(Into::into)
+        group
+            .write_to_storage()
+            .await
+            .map_err(|err| self.mls_error(err))
     }
 
     /// Export the current epoch's ratchet tree in serialized format.
@@ -624,7 +697,10 @@ impl Group {
     /// [`mls_rs::Group::commit`] for details.
     pub async fn commit(&self) -> Result<CommitOutput, Error> {
         let mut group = self.inner().await;
-        let commit_output = group.commit(Vec::new()).await?;
+        let commit_output = group
+            .commit(Vec::new())
+            .await
+            .map_err(|err| self.mls_error(err))?;
         commit_output.try_into()
     }
 
@@ -641,9 +717,14 @@ impl Group {
         let mut group = self.inner().await;
         let mut commit_builder = group.commit_builder();
         for key_package in key_packages {
-            commit_builder = commit_builder.add_member(arc_unwrap_or_clone(key_package).inner)?;
+            commit_builder = commit_builder
+                .add_member(arc_unwrap_or_clone(key_package).inner)
+                .map_err(|err| self.mls_error(err))?;
         }
-        let commit_output = commit_builder.build().await?;
+        let commit_output = commit_builder
+            .build()
+            .await
+            .map_err(|err| self.mls_error(err))?;
         commit_output.try_into()
     }
 
@@ -662,7 +743,10 @@ impl Group {
         let mut messages = Vec::with_capacity(key_packages.len());
         for key_package in key_packages {
             let key_package = arc_unwrap_or_clone(key_package);
-            let message = group.propose_add(key_package.inner, Vec::new()).await?;
+            let message = group
+                .propose_add(key_package.inner, Vec::new())
+                .await
+                .map_err(|err| self.mls_error(err))?;
             messages.push(Arc::new(message.into()));
         }
 
@@ -684,15 +768,23 @@ impl Group {
         let mut member_indixes = Vec::with_capacity(signing_identities.len());
         for signing_identity in signing_identities {
             let identifier = signing_identity_to_identifier(&signing_identity.inner).await?;
-            let member = group.member_with_identity(&identifier).await?;
+            let member = group
+                .member_with_identity(&identifier)
+                .await
+                .map_err(|err| self.mls_error(err))?;
             member_indixes.push(member.index);
         }
 
         let mut commit_builder = group.commit_builder();
         for index in member_indixes {
-            commit_builder = commit_builder.remove_member(index)?;
+            commit_builder = commit_builder
+                .remove_member(index)
+                .map_err(|err| self.mls_error(err))?;
         }
-        let commit_output = commit_builder.build().await?;
+        let commit_output = commit_builder
+            .build()
+            .await
+            .map_err(|err| self.mls_error(err))?;
         commit_output.try_into()
     }
 
@@ -711,8 +803,14 @@ impl Group {
         let mut messages = Vec::with_capacity(signing_identities.len());
         for signing_identity in signing_identities {
             let identifier = signing_identity_to_identifier(&signing_identity.inner).await?;
-            let member = group.member_with_identity(&identifier).await?;
-            let message = group.propose_remove(member.index, Vec::new()).await?;
+            let member = group
+                .member_with_identity(&identifier)
+                .await
+                .map_err(|err| self.mls_error(err))?;
+            let message = group
+                .propose_remove(member.index, Vec::new())
+                .await
+                .map_err(|err| self.mls_error(err))?;
             messages.push(Arc::new(message.into()));
         }
 
@@ -734,7 +832,8 @@ impl Group {
         let mut group = self.inner().await;
         let mls_message = group
             .encrypt_application_message(message, Vec::new())
-            .await?;
+            .await
+            .map_err(|err| self.mls_error(err))?;
         Ok(mls_message.into())
     }
 
@@ -743,9 +842,15 @@ impl Group {
         &self,
         message: Arc<Message>,
     ) -> Result<ReceivedMessage, Error> {
-        let message = arc_unwrap_or_clone(message);
+        let message = group::Validated::new(arc_unwrap_or_clone(message).inner)
+            .map_err(|err| self.mls_error(err))?;
         let mut group = self.inner().await;
-        match group.process_incoming_message(message.inner).await? {
+        let received_message = group
+            .process_incoming_message_validated(message)
+            .await
+            .map_err(|err| self.mls_error(err))?;
+
+        match received_message {
             group::ReceivedMessage::ApplicationMessage(application_message) => {
                 let sender =
                     Arc::new(index_to_identity(&group, application_message.sender_index)?.into());
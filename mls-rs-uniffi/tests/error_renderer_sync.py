@@ -0,0 +1,34 @@
+from mls_rs_uniffi import CipherSuite, generate_signature_keypair, Client, \
+    client_config_default, ErrorRenderer, Error
+
+
+class FrenchErrorRenderer(ErrorRenderer):
+
+    def message(self, code: str):
+        if code == "CantProcessMessageFromSelf":
+            return "Ce message a été envoyé par cet appareil."
+
+        return None
+
+
+def process_own_message(client_config):
+    key = generate_signature_keypair(CipherSuite.CURVE25519_AES128)
+    group = Client(b'alice', key, client_config).create_group(None)
+    msg = group.encrypt_application_message(b'hello')
+
+    try:
+        group.process_incoming_message(msg)
+        assert False
+    except Error as error:
+        return str(error)
+
+
+client_config = client_config_default()
+client_config.error_renderer = FrenchErrorRenderer()
+
+assert "Ce message a été envoyé par cet appareil." in \
+    process_own_message(client_config)
+
+# Clients without a renderer keep the messages intended for developers.
+assert "A mls-rs error occurred" in \
+    process_own_message(client_config_default())
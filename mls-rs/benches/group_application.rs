@@ -4,7 +4,7 @@
 
 use criterion::{BatchSize, BenchmarkId, Criterion, Throughput};
 use mls_rs::test_utils::benchmarks::load_group_states;
-use mls_rs::CipherSuite;
+use mls_rs::{CipherSuite, Validated};
 use rand::RngCore;
 
 fn bench(c: &mut Criterion) {
@@ -32,7 +32,44 @@ fn bench(c: &mut Criterion) {
                             .encrypt_application_message(&bytes[..n], vec![])
                             .unwrap();
 
-                        group_states.receiver.process_incoming_message(msg).unwrap();
+                        group_states
+                            .receiver
+                            .process_incoming_message_validated(Validated::new(msg).unwrap())
+                            .unwrap();
+                    },
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+
+        n *= 10;
+    }
+    bench_group.finish();
+
+    // Same messages, encoded into and decoded from buffers reused across iterations.
+    let mut n = 100;
+    let mut bench_group = c.benchmark_group("group_application_into");
+    let mut encrypted = Vec::new();
+    let mut decrypted = Vec::new();
+
+    while n <= 1000000 {
+        bench_group.throughput(Throughput::Bytes(n as u64));
+        bench_group.bench_with_input(
+            BenchmarkId::new(format!("{cipher_suite:?}"), n),
+            &n,
+            |b, _| {
+                b.iter_batched_ref(
+                    || group_states.clone(),
+                    |group_states| {
+                        group_states
+                            .sender
+                            .encrypt_application_message_into(&mut encrypted, &bytes[..n], &[])
+                            .unwrap();
+
+                        group_states
+                            .receiver
+                            .decrypt_application_message_into(&encrypted, &mut decrypted)
+                            .unwrap();
                     },
                     BatchSize::SmallInput,
                 )
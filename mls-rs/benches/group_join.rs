@@ -0,0 +1,106 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use criterion::{BenchmarkId, Criterion};
+use mls_rs::{
+    client_builder::MlsConfig,
+    identity::{
+        basic::{BasicCredential, BasicIdentityProvider},
+        SigningIdentity,
+    },
+    CipherSuite, CipherSuiteProvider, Client, CryptoProvider, MlsMessage, Validated,
+};
+use mls_rs_crypto_openssl::OpensslCryptoProvider;
+
+const CIPHER_SUITE: CipherSuite = CipherSuite::CURVE25519_AES128;
+
+fn bench(c: &mut Criterion) {
+    const MAX_ADD_COUNT: usize = 1000;
+
+    let cs = OpensslCryptoProvider::new()
+        .cipher_suite_provider(CIPHER_SUITE)
+        .unwrap();
+
+    let mut group = c.benchmark_group("group_join");
+
+    std::iter::successors(Some(10), |&i| Some(i * 10))
+        .take_while(|&i| i <= MAX_ADD_COUNT)
+        .for_each(|size| {
+            let mut alice = make_client("alice")
+                .create_group(Default::default())
+                .unwrap();
+
+            let joiners = (0..size)
+                .map(|i| {
+                    let client = make_client(&format!("bob-{i}"));
+                    let key_package = client.generate_key_package_message().unwrap();
+                    (client, key_package)
+                })
+                .collect::<Vec<_>>();
+
+            let commit = joiners
+                .iter()
+                .fold(alice.commit_builder(), |builder, (_, key_package)| {
+                    builder.add_member(key_package.clone()).unwrap()
+                })
+                .build()
+                .unwrap();
+
+            alice.apply_pending_commit().unwrap();
+
+            let welcome = commit.welcome_messages[0].to_bytes().unwrap();
+
+            // The last joiner has the last entry of the welcome message.
+            let (joiner, key_package) = joiners.last().unwrap();
+            let key_package_ref = key_package.key_package_reference(&cs).unwrap().unwrap();
+
+            group.bench_with_input(BenchmarkId::new("full_decode", size), &size, |b, _| {
+                b.iter(|| {
+                    let welcome = Validated::from_bytes(&welcome).unwrap();
+                    joiner.join_group_validated(None, &welcome).unwrap();
+                })
+            });
+
+            group.bench_with_input(BenchmarkId::new("recipient_decode", size), &size, |b, _| {
+                b.iter(|| {
+                    let welcome = MlsMessage::from_welcome_bytes_for(
+                        &welcome,
+                        core::slice::from_ref(&key_package_ref),
+                    )
+                    .unwrap();
+
+                    let welcome = Validated::new(welcome).unwrap();
+                    joiner.join_group_validated(None, &welcome).unwrap();
+                })
+            });
+        });
+
+    group.finish();
+}
+
+criterion::criterion_group!(benches, bench);
+criterion::criterion_main!(benches);
+
+fn make_client(name: &str) -> Client<impl MlsConfig> {
+    let crypto_provider = OpensslCryptoProvider::new();
+
+    let (secret_key, public_key) = crypto_provider
+        .cipher_suite_provider(CIPHER_SUITE)
+        .unwrap()
+        .signature_key_generate()
+        .unwrap();
+
+    Client::builder()
+        .crypto_provider(crypto_provider)
+        .identity_provider(BasicIdentityProvider)
+        .signing_identity(
+            SigningIdentity::new(
+                BasicCredential::new(name.as_bytes().to_vec()).into_credential(),
+                public_key,
+            ),
+            secret_key,
+            CIPHER_SUITE,
+        )
+        .build()
+}
@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
 use criterion::{BatchSize, BenchmarkId, Criterion};
-use mls_rs::{test_utils::benchmarks::load_group_states, CipherSuite};
+use mls_rs::{test_utils::benchmarks::load_group_states, CipherSuite, Validated};
 
 fn bench(c: &mut Criterion) {
     let cipher_suite = CipherSuite::CURVE25519_AES128;
@@ -23,7 +23,9 @@ fn bench(c: &mut Criterion) {
                     },
                     move |(commit, receiver)| {
                         receiver
-                            .process_incoming_message(commit.commit_message.clone())
+                            .process_incoming_message_validated(
+                                Validated::new(commit.commit_message.clone()).unwrap(),
+                            )
                             .unwrap();
                     },
                     BatchSize::SmallInput,
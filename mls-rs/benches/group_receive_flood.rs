@@ -0,0 +1,66 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use criterion::{BatchSize, BenchmarkId, Criterion, Throughput};
+use mls_rs::test_utils::benchmarks::load_group_states;
+use mls_rs::{CipherSuite, Validated};
+
+fn bench(c: &mut Criterion) {
+    let cipher_suite = CipherSuite::CURVE25519_AES128;
+    let mut group_states = load_group_states(cipher_suite).pop().unwrap();
+
+    let mut bench_group = c.benchmark_group("group_receive_flood");
+
+    for n in [10, 100, 1000] {
+        let messages = (0..n)
+            .map(|_| {
+                group_states
+                    .sender
+                    .encrypt_application_message(&[0; 100], vec![])
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        bench_group.throughput(Throughput::Elements(n as u64));
+
+        // Messages are ordered by sender and generation before being processed, which opens
+        // the sender data of each message twice.
+        bench_group.bench_with_input(
+            BenchmarkId::new(format!("{cipher_suite:?}/batch"), n),
+            &messages,
+            |b, messages| {
+                b.iter_batched(
+                    || (group_states.receiver.clone(), messages.clone()),
+                    |(mut receiver, messages)| receiver.process_incoming_messages(messages),
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+
+        bench_group.bench_with_input(
+            BenchmarkId::new(format!("{cipher_suite:?}/sequential"), n),
+            &messages,
+            |b, messages| {
+                b.iter_batched(
+                    || (group_states.receiver.clone(), messages.clone()),
+                    |(mut receiver, messages)| {
+                        for message in messages {
+                            receiver
+                                .process_incoming_message_validated(
+                                    Validated::new(message).unwrap(),
+                                )
+                                .unwrap();
+                        }
+                    },
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+
+    bench_group.finish();
+}
+
+criterion::criterion_group!(benches, bench);
+criterion::criterion_main!(benches);
@@ -0,0 +1,26 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use criterion::{BenchmarkId, Criterion};
+use mls_rs::test_utils::benchmarks::derive_secret_tree_keys;
+use mls_rs::CipherSuite;
+
+fn bench(c: &mut Criterion) {
+    let cipher_suite = CipherSuite::CURVE25519_AES128;
+    let mut bench_group = c.benchmark_group("secret_tree");
+    bench_group.sample_size(10);
+
+    for leaf_count in [100, 1000, 10000] {
+        bench_group.bench_with_input(
+            BenchmarkId::new(format!("{cipher_suite:?}"), leaf_count),
+            &leaf_count,
+            |b, &leaf_count| b.iter(|| derive_secret_tree_keys(cipher_suite, leaf_count)),
+        );
+    }
+
+    bench_group.finish();
+}
+
+criterion::criterion_group!(benches, bench);
+criterion::criterion_main!(benches);
@@ -14,7 +14,7 @@ use mls_rs::{
         basic::{BasicCredential, BasicIdentityProvider},
         SigningIdentity,
     },
-    CipherSuite, CipherSuiteProvider, Client, CryptoProvider, ExtensionList, MlsMessage,
+    CipherSuite, CipherSuiteProvider, Client, CryptoProvider, ExtensionList, MlsMessage, Validated,
 };
 use mls_rs_core::crypto::SignatureSecretKey;
 
@@ -143,13 +143,14 @@ fn main() -> Result<(), MlsError> {
     let mut alice_group = alice.create_group(ExtensionList::default())?;
     let bob_key_package = bob.generate_key_package_message()?;
 
-    let welcome = &alice_group
+    let welcome = alice_group
         .commit_builder()
         .add_member(bob_key_package)?
         .build()?
-        .welcome_messages[0];
+        .welcome_messages
+        .remove(0);
 
-    let (mut bob_group, _) = bob.join_group(None, welcome)?;
+    let (mut bob_group, _) = bob.join_group_validated(None, &Validated::new(welcome)?)?;
     alice_group.apply_pending_commit()?;
 
     // Server starts observing Alice's group
@@ -165,7 +166,7 @@ fn main() -> Result<(), MlsError> {
 
     // Alice downloads all messages and commits
     for m in server.download_messages(0) {
-        alice_group.process_incoming_message(MlsMessage::from_bytes(m)?)?;
+        alice_group.process_incoming_message_validated(Validated::from_bytes(m)?)?;
     }
 
     let commit = alice_group
@@ -181,7 +182,7 @@ fn main() -> Result<(), MlsError> {
     // Bob downloads the commit
     let message = server.download_messages(1).first().unwrap();
 
-    let res = bob_group.process_incoming_message(MlsMessage::from_bytes(message)?)?;
+    let res = bob_group.process_incoming_message_validated(Validated::from_bytes(message)?)?;
 
     let ReceivedMessage::Commit(commit_desc) = res else {
         panic!("expected commit message")
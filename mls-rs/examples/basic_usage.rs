@@ -9,7 +9,7 @@ use mls_rs::{
         basic::{BasicCredential, BasicIdentityProvider},
         SigningIdentity,
     },
-    CipherSuite, CipherSuiteProvider, Client, CryptoProvider, ExtensionList,
+    CipherSuite, CipherSuiteProvider, Client, CryptoProvider, ExtensionList, Validated,
 };
 
 const CIPHERSUITE: CipherSuite = CipherSuite::CURVE25519_AES128;
@@ -61,13 +61,16 @@ fn main() -> Result<(), MlsError> {
     alice_group.apply_pending_commit()?;
 
     // Bob joins the group with the welcome message created as part of Alice's commit.
-    let (mut bob_group, _) = bob.join_group(None, &alice_commit.welcome_messages[0])?;
+    let (mut bob_group, _) = bob.join_group_validated(
+        None,
+        &Validated::new(alice_commit.welcome_messages[0].clone())?,
+    )?;
 
     // Alice encrypts an application message to Bob.
     let msg = alice_group.encrypt_application_message(b"hello world", Default::default())?;
 
     // Bob decrypts the application message from Alice.
-    let msg = bob_group.process_incoming_message(msg)?;
+    let msg = bob_group.process_incoming_message_validated(Validated::new(msg)?)?;
 
     println!("Received message: {:?}", msg);
 
@@ -36,7 +36,7 @@ use mls_rs::{
         ProposalSource,
     },
     CipherSuite, CipherSuiteProvider, Client, CryptoProvider, ExtensionList, IdentityProvider,
-    MlsRules,
+    MlsRules, Validated,
 };
 use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
 use mls_rs_core::{
@@ -383,7 +383,8 @@ fn main() -> Result<(), CustomError> {
         .remove(0);
 
     alice_tablet_group.apply_pending_commit()?;
-    let (mut alice_pc_group, _) = alice_pc_client.join_group(None, &welcome)?;
+    let (mut alice_pc_group, _) =
+        alice_pc_client.join_group_validated(None, &Validated::new(welcome.clone())?)?;
 
     // Alice cannot add bob's devices yet
     let bob_tablet_client = make_client(bob_tablet)?;
@@ -407,9 +408,10 @@ fn main() -> Result<(), CustomError> {
         .add_member(key_package)?
         .build()?;
 
-    bob_tablet_client.join_group(None, &commit.welcome_messages[0])?;
+    bob_tablet_client
+        .join_group_validated(None, &Validated::new(commit.welcome_messages[0].clone())?)?;
     alice_tablet_group.apply_pending_commit()?;
-    alice_pc_group.process_incoming_message(commit.commit_message)?;
+    alice_pc_group.process_incoming_message_validated(Validated::new(commit.commit_message)?)?;
 
     Ok(())
 }
@@ -10,7 +10,7 @@ use mls_rs::{
         SigningIdentity,
     },
     mls_rules::{CommitOptions, DefaultMlsRules},
-    CipherSuite, CipherSuiteProvider, Client, CryptoProvider, Group,
+    CipherSuite, CipherSuiteProvider, Client, CryptoProvider, Group, Validated,
 };
 
 const CIPHERSUITE: CipherSuite = CipherSuite::CURVE25519_AES128;
@@ -78,14 +78,17 @@ fn make_groups_best_case<P: CryptoProvider + Clone>(
 
         // All other groups process the commit.
         for group in groups.iter_mut().rev().skip(1) {
-            group.process_incoming_message(commit.commit_message.clone())?;
+            group.process_incoming_message_validated(Validated::new(
+                commit.commit_message.clone(),
+            )?)?;
         }
 
         // The last group applies the generated commit.
         groups.last_mut().unwrap().apply_pending_commit()?;
 
         // The new member joins.
-        let (bob_group, _info) = bob_client.join_group(None, &commit.welcome_messages[0])?;
+        let (bob_group, _info) = bob_client
+            .join_group_validated(None, &Validated::new(commit.welcome_messages[0].clone())?)?;
 
         groups.push(bob_group);
     }
@@ -122,7 +125,8 @@ fn make_groups_worst_case<P: CryptoProvider + Clone>(
     let mut groups = vec![alice_group];
 
     for bob_client in &bob_clients {
-        let (bob_group, _info) = bob_client.join_group(None, welcome_message)?;
+        let (bob_group, _info) =
+            bob_client.join_group_validated(None, &Validated::new(welcome_message.clone())?)?;
         groups.push(bob_group);
     }
 
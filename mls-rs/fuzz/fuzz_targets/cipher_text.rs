@@ -7,10 +7,16 @@
 mod ciphertext {
     use libfuzzer_sys::fuzz_target;
     use mls_rs::test_utils::fuzz_tests::{create_fuzz_commit_message, GROUP};
+    use mls_rs::Validated;
 
     fuzz_target!(|data: (Vec<u8>, u64, Vec<u8>)| {
         let message = create_fuzz_commit_message(data.0, data.1, data.2).unwrap();
 
-        let _ = GROUP.lock().unwrap().process_incoming_message(message);
+        if let Ok(message) = Validated::new(message) {
+            let _ = GROUP
+                .lock()
+                .unwrap()
+                .process_incoming_message_validated(message);
+        }
     });
 }
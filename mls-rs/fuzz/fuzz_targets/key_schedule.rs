@@ -0,0 +1,16 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#![no_main]
+
+mod key_schedule {
+    use libfuzzer_sys::fuzz_target;
+    use mls_rs::{group::GroupContext, test_utils::fuzz_tests::check_key_schedule, CipherSuite};
+
+    fuzz_target!(|data: (u16, Vec<u8>, Vec<u8>, Vec<u8>, GroupContext)| {
+        let cipher_suite = CipherSuite::from(data.0);
+
+        check_key_schedule(cipher_suite, &data.1, &data.2, &data.3, &data.4);
+    });
+}
@@ -7,9 +7,14 @@
 mod mls_message {
     use libfuzzer_sys::fuzz_target;
     use mls_rs::test_utils::fuzz_tests::GROUP;
-    use mls_rs::MlsMessage;
+    use mls_rs::{MlsMessage, Validated};
 
     fuzz_target!(|data: MlsMessage| {
-        let _ = GROUP.lock().unwrap().process_incoming_message(data);
+        if let Ok(message) = Validated::new(data) {
+            let _ = GROUP
+                .lock()
+                .unwrap()
+                .process_incoming_message_validated(message);
+        }
     });
 }
@@ -6,13 +6,16 @@
 
 mod process_bytes {
     use libfuzzer_sys::fuzz_target;
-    use mls_rs::mls_rs_codec::MlsDecode;
     use mls_rs::test_utils::fuzz_tests::GROUP;
-    use mls_rs::MlsMessage;
+    use mls_rs::Validated;
 
     fuzz_target!(|data: &[u8]| {
-        if let Ok(message) = MlsMessage::mls_decode(&mut &*data) {
-            GROUP.lock().unwrap().process_incoming_message(message).ok();
+        if let Ok(message) = Validated::from_bytes(data) {
+            GROUP
+                .lock()
+                .unwrap()
+                .process_incoming_message_validated(message)
+                .ok();
         }
     });
 }
@@ -0,0 +1,211 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Each helper produces a message violating exactly one rule of the protocol
+//! that an honest member must reject. Helpers producing commits use the
+//! local member of a [`Group`] as the malicious committer and discard the
+//! resulting pending commit, leaving the group unchanged.
+//!
+//! These helpers are only meant to be used in tests.
+
+use alloc::vec::Vec;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{framing::MlsMessagePayload, CommitModifiers},
+    tree_kem::{leaf_node::LeafNodeSource, node::LeafIndex, parent_hash::ParentHash},
+    Group, MlsMessage,
+};
+
+/// Copy of `message` claiming to be sent in `epoch`.
+///
+/// Receivers reject proposals and commits from any epoch other than the
+/// current one with [`MlsError::InvalidEpoch`].
+pub fn with_epoch(message: &MlsMessage, epoch: u64) -> Result<MlsMessage, MlsError> {
+    let mut message = message.clone();
+
+    match &mut message.payload {
+        MlsMessagePayload::Plain(plaintext) => plaintext.content.epoch = epoch,
+        #[cfg(feature = "private_message")]
+        MlsMessagePayload::Cipher(ciphertext) => ciphertext.epoch = epoch,
+        _ => return Err(MlsError::UnexpectedMessageType),
+    }
+
+    Ok(message)
+}
+
+/// Copy of the public `message` with a membership tag that was not computed
+/// with the membership key of the group.
+///
+/// Receivers reject the message with [`MlsError::InvalidMembershipTag`].
+pub fn with_forged_membership_tag(message: &MlsMessage) -> Result<MlsMessage, MlsError> {
+    let mut message = message.clone();
+
+    let MlsMessagePayload::Plain(plaintext) = &mut message.payload else {
+        return Err(MlsError::UnexpectedMessageType);
+    };
+
+    let mut tag = plaintext
+        .membership_tag
+        .as_ref()
+        .map(|tag| tag.to_vec())
+        .unwrap_or_default();
+
+    match tag.first_mut() {
+        Some(byte) => *byte ^= 1,
+        None => tag.push(0),
+    }
+
+    plaintext.membership_tag = Some(tag.into());
+
+    Ok(message)
+}
+
+/// Commit whose update path carries a leaf node with a parent hash that does
+/// not match the parent nodes of the committer.
+///
+/// The leaf node is signed correctly. Receivers reject the commit with
+/// [`MlsError::ParentHashMismatch`].
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub async fn commit_with_bad_parent_hash<C>(group: &mut Group<C>) -> Result<MlsMessage, MlsError>
+where
+    C: ClientConfig + Clone,
+{
+    let modifiers = CommitModifiers {
+        modify_leaf: |leaf, signer| {
+            if let LeafNodeSource::Commit(parent_hash) = &mut leaf.leaf_node_source {
+                let mut forged = parent_hash.to_vec();
+
+                match forged.first_mut() {
+                    Some(byte) => *byte ^= 1,
+                    None => forged.push(0),
+                }
+
+                *parent_hash = ParentHash::from(forged);
+            }
+
+            Some(signer.clone())
+        },
+        ..Default::default()
+    };
+
+    commit_with_modifiers(group, modifiers).await
+}
+
+/// Commit whose update path carries a leaf node reusing the HPKE encryption
+/// key of the member at `member_index`.
+///
+/// The leaf node is signed correctly. Receivers reject the commit with
+/// [`MlsError::DuplicateLeafData`].
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub async fn commit_with_duplicate_hpke_key<C>(
+    group: &mut Group<C>,
+    member_index: u32,
+) -> Result<MlsMessage, MlsError>
+where
+    C: ClientConfig + Clone,
+{
+    let public_key = group
+        .state
+        .public_tree
+        .get_leaf_node(LeafIndex(member_index))?
+        .public_key
+        .clone();
+
+    let modifiers = CommitModifiers {
+        leaf_encryption_key: Some(public_key),
+        ..Default::default()
+    };
+
+    commit_with_modifiers(group, modifiers).await
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+async fn commit_with_modifiers<C>(
+    group: &mut Group<C>,
+    modifiers: CommitModifiers,
+) -> Result<MlsMessage, MlsError>
+where
+    C: ClientConfig + Clone,
+{
+    let previous = core::mem::replace(&mut group.commit_modifiers, modifiers);
+    let res = group.commit(Vec::new()).await;
+    group.commit_modifiers = previous;
+
+    let commit = res?.commit_message;
+    group.clear_pending_commit();
+
+    Ok(commit)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use super::{
+        commit_with_bad_parent_hash, commit_with_duplicate_hpke_key, with_epoch,
+        with_forged_membership_tag,
+    };
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::test_utils::test_n_member_group,
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn stale_epoch_is_rejected() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+        let epoch = groups[0].group.current_epoch();
+
+        let commit = groups[0].group.commit(vec![]).await.unwrap().commit_message;
+        let commit = with_epoch(&commit, epoch - 1).unwrap();
+
+        let res = groups[1].process_message(commit).await;
+        assert_matches!(res, Err(MlsError::InvalidEpoch));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn forged_membership_tag_is_rejected() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+
+        let commit = groups[0].group.commit(vec![]).await.unwrap().commit_message;
+        let commit = with_forged_membership_tag(&commit).unwrap();
+
+        let res = groups[1].process_message(commit).await;
+        assert_matches!(res, Err(MlsError::InvalidMembershipTag));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn bad_parent_hash_is_rejected() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+
+        let commit = commit_with_bad_parent_hash(&mut groups[0].group)
+            .await
+            .unwrap();
+
+        let res = groups[1].process_message(commit).await;
+        assert_matches!(res, Err(MlsError::ParentHashMismatch));
+
+        // The malicious member can still commit honestly.
+        assert!(!groups[0].group.has_pending_commit());
+        groups[0].group.commit(vec![]).await.unwrap();
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn duplicate_hpke_key_is_rejected() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+
+        let commit = commit_with_duplicate_hpke_key(&mut groups[0].group, 1)
+            .await
+            .unwrap();
+
+        let res = groups[2].process_message(commit).await;
+        assert_matches!(res, Err(MlsError::DuplicateLeafData(_)));
+    }
+}
@@ -0,0 +1,279 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Blocking wrappers around the async [`Client`](crate::Client) and
+//! [`Group`](crate::Group).
+//!
+//! When the crate is built with `--cfg mls_build_async`, all functions that
+//! may perform I/O or cryptographic operations are `async`. The types in this
+//! module drive those functions to completion on the current thread, so that
+//! the same build of the crate can serve async services as well as sync
+//! callers such as FFI bindings or tests.
+//!
+//! Functions that are not wrapped can be called on the inner value, obtained
+//! with e.g. [`Group::inner_mut`], and driven with [`block_on`].
+
+use alloc::vec::Vec;
+use core::{future::Future, ops::Deref};
+
+use mls_rs_core::{extension::ExtensionList, secret::Secret};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{
+        framing::MlsMessage, CommitMessageDescription, CommitOutput, ExportedTree, NewMemberInfo,
+        ReceivedMessage, Validated,
+    },
+};
+
+/// Run `future` to completion on the current thread.
+///
+/// This must not be called from within an async runtime that does not allow
+/// blocking its worker threads.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    futures::executor::block_on(future)
+}
+
+/// Blocking version of [`Client`](crate::Client).
+#[derive(Clone, Debug)]
+pub struct Client<C>(crate::Client<C>);
+
+impl<C> From<crate::Client<C>> for Client<C> {
+    fn from(client: crate::Client<C>) -> Self {
+        Self(client)
+    }
+}
+
+impl<C> Deref for Client<C> {
+    type Target = crate::Client<C>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<C> Client<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// The wrapped async client.
+    pub fn into_inner(self) -> crate::Client<C> {
+        self.0
+    }
+
+    /// See [`Client::generate_key_package_message`](crate::Client::generate_key_package_message).
+    pub fn generate_key_package_message(&self) -> Result<MlsMessage, MlsError> {
+        block_on(self.0.generate_key_package_message())
+    }
+
+    /// See [`Client::create_group`](crate::Client::create_group).
+    pub fn create_group(
+        &self,
+        group_context_extensions: ExtensionList,
+    ) -> Result<Group<C>, MlsError> {
+        block_on(self.0.create_group(group_context_extensions)).map(Group)
+    }
+
+    /// See [`Client::create_group_with_id`](crate::Client::create_group_with_id).
+    pub fn create_group_with_id(
+        &self,
+        group_id: Vec<u8>,
+        group_context_extensions: ExtensionList,
+    ) -> Result<Group<C>, MlsError> {
+        block_on(
+            self.0
+                .create_group_with_id(group_id, group_context_extensions),
+        )
+        .map(Group)
+    }
+
+    /// See [`Client::join_group_validated`](crate::Client::join_group_validated).
+    pub fn join_group_validated(
+        &self,
+        tree_data: Option<ExportedTree<'_>>,
+        welcome_message: &Validated<MlsMessage>,
+    ) -> Result<(Group<C>, NewMemberInfo), MlsError> {
+        block_on(self.0.join_group_validated(tree_data, welcome_message))
+            .map(|(group, info)| (Group(group), info))
+    }
+
+    /// See [`Client::join_group`](crate::Client::join_group).
+    #[deprecated(note = "use `Client::join_group_validated` instead")]
+    pub fn join_group(
+        &self,
+        tree_data: Option<ExportedTree<'_>>,
+        welcome_message: &MlsMessage,
+    ) -> Result<(Group<C>, NewMemberInfo), MlsError> {
+        self.join_group_validated(tree_data, &Validated::new(welcome_message.clone())?)
+    }
+
+    /// See [`Client::load_group`](crate::Client::load_group).
+    pub fn load_group(&self, group_id: &[u8]) -> Result<Group<C>, MlsError> {
+        block_on(self.0.load_group(group_id)).map(Group)
+    }
+}
+
+/// Blocking version of [`Group`](crate::Group).
+#[derive(Clone)]
+pub struct Group<C: ClientConfig>(crate::Group<C>);
+
+impl<C: ClientConfig> From<crate::Group<C>> for Group<C> {
+    fn from(group: crate::Group<C>) -> Self {
+        Self(group)
+    }
+}
+
+impl<C: ClientConfig> Deref for Group<C> {
+    type Target = crate::Group<C>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// The wrapped async group.
+    pub fn into_inner(self) -> crate::Group<C> {
+        self.0
+    }
+
+    /// Mutable access to the wrapped async group, e.g. to build a commit with
+    /// [`Group::commit_builder`](crate::Group::commit_builder) and [`block_on`].
+    pub fn inner_mut(&mut self) -> &mut crate::Group<C> {
+        &mut self.0
+    }
+
+    /// See [`Group::commit`](crate::Group::commit).
+    pub fn commit(&mut self, authenticated_data: Vec<u8>) -> Result<CommitOutput, MlsError> {
+        block_on(self.0.commit(authenticated_data))
+    }
+
+    /// See [`Group::apply_pending_commit`](crate::Group::apply_pending_commit).
+    pub fn apply_pending_commit(&mut self) -> Result<CommitMessageDescription, MlsError> {
+        block_on(self.0.apply_pending_commit())
+    }
+
+    /// See [`Group::process_incoming_message_validated`](crate::Group::process_incoming_message_validated).
+    pub fn process_incoming_message_validated(
+        &mut self,
+        message: Validated<MlsMessage>,
+    ) -> Result<ReceivedMessage, MlsError> {
+        block_on(self.0.process_incoming_message_validated(message))
+    }
+
+    /// See [`Group::process_incoming_message`](crate::Group::process_incoming_message).
+    #[deprecated(note = "use `Group::process_incoming_message_validated` instead")]
+    pub fn process_incoming_message(
+        &mut self,
+        message: MlsMessage,
+    ) -> Result<ReceivedMessage, MlsError> {
+        self.process_incoming_message_validated(Validated::new(message)?)
+    }
+
+    /// See [`Group::propose_add`](crate::Group::propose_add).
+    #[cfg(feature = "by_ref_proposal")]
+    pub fn propose_add(
+        &mut self,
+        key_package: MlsMessage,
+        authenticated_data: Vec<u8>,
+    ) -> Result<MlsMessage, MlsError> {
+        block_on(self.0.propose_add(key_package, authenticated_data))
+    }
+
+    /// See [`Group::propose_remove`](crate::Group::propose_remove).
+    #[cfg(feature = "by_ref_proposal")]
+    pub fn propose_remove(
+        &mut self,
+        index: u32,
+        authenticated_data: Vec<u8>,
+    ) -> Result<MlsMessage, MlsError> {
+        block_on(self.0.propose_remove(index, authenticated_data))
+    }
+
+    /// See [`Group::encrypt_application_message`](crate::Group::encrypt_application_message).
+    #[cfg(feature = "private_message")]
+    pub fn encrypt_application_message(
+        &mut self,
+        message: &[u8],
+        authenticated_data: Vec<u8>,
+    ) -> Result<MlsMessage, MlsError> {
+        block_on(
+            self.0
+                .encrypt_application_message(message, authenticated_data),
+        )
+    }
+
+    /// See [`Group::export_secret`](crate::Group::export_secret).
+    pub fn export_secret(
+        &self,
+        label: &[u8],
+        context: &[u8],
+        len: usize,
+    ) -> Result<Secret, MlsError> {
+        block_on(self.0.export_secret(label, context, len))
+    }
+
+    /// See [`Group::write_to_storage`](crate::Group::write_to_storage).
+    pub fn write_to_storage(&mut self) -> Result<(), MlsError> {
+        block_on(self.0.write_to_storage())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::{block_on, Client};
+
+    use crate::{
+        client::test_utils::{TestClientBuilder, TestClientConfig, TEST_CIPHER_SUITE},
+        group::ReceivedMessage,
+    };
+
+    fn test_client(name: &str) -> Client<TestClientConfig> {
+        let builder =
+            TestClientBuilder::new_for_test().with_random_signing_identity(name, TEST_CIPHER_SUITE);
+
+        block_on(builder).build().into()
+    }
+
+    #[test]
+    fn blocking_clients_can_communicate() {
+        let alice = test_client("alice");
+        let bob = test_client("bob");
+
+        let mut alice_group = alice.create_group(Default::default()).unwrap();
+        let key_package = bob.generate_key_package_message().unwrap();
+
+        let welcome = block_on(
+            alice_group
+                .inner_mut()
+                .commit_builder()
+                .add_member(key_package)
+                .unwrap()
+                .build(),
+        )
+        .unwrap()
+        .welcome_messages
+        .remove(0);
+
+        alice_group.apply_pending_commit().unwrap();
+
+        let (mut bob_group, _) = bob
+            .join_group(Some(alice_group.export_tree()), &welcome)
+            .unwrap();
+
+        let commit = bob_group.commit(vec![]).unwrap().commit_message;
+        bob_group.apply_pending_commit().unwrap();
+
+        let received = alice_group.process_incoming_message(commit).unwrap();
+
+        assert!(matches!(received, ReceivedMessage::Commit(_)));
+        assert_eq!(alice_group.current_epoch(), bob_group.current_epoch());
+    }
+}
@@ -5,6 +5,7 @@
 use crate::cipher_suite::CipherSuite;
 use crate::client_builder::{recreate_config, BaseConfig, ClientBuilder, MakeConfig};
 use crate::client_config::ClientConfig;
+use crate::error_render::{self, SharedErrorRenderer};
 use crate::group::framing::MlsMessage;
 
 #[cfg(feature = "by_ref_proposal")]
@@ -13,13 +14,14 @@ use crate::group::{
     message_signature::AuthenticatedContent,
     proposal::{AddProposal, Proposal},
 };
-use crate::group::{snapshot::Snapshot, ExportedTree, Group, NewMemberInfo};
+use crate::group::{
+    snapshot::Snapshot, ExportedTree, Group, NewMemberInfo, PendingJoin, Validated,
+};
 use crate::identity::SigningIdentity;
-use crate::key_package::{KeyPackageGeneration, KeyPackageGenerator};
+use crate::key_package::{KeyPackageGeneration, KeyPackageGenerator, KeyPackageRef};
 use crate::protocol_version::ProtocolVersion;
 use crate::tree_kem::node::NodeIndex;
 use alloc::vec::Vec;
-use mls_rs_codec::MlsDecode;
 use mls_rs_core::crypto::{CryptoProvider, SignatureSecretKey};
 use mls_rs_core::error::{AnyError, IntoAnyError};
 use mls_rs_core::extension::{ExtensionError, ExtensionList, ExtensionType};
@@ -29,6 +31,12 @@ use mls_rs_core::key_package::KeyPackageStorage;
 
 use crate::group::external_commit::ExternalCommitBuilder;
 
+#[cfg(feature = "psk")]
+use crate::psk::{resolver::PskStoreWithLookup, ExternalPskId};
+
+#[cfg(feature = "psk")]
+use mls_rs_core::psk::PreSharedKeyStorage;
+
 #[cfg(feature = "by_ref_proposal")]
 use alloc::boxed::Box;
 
@@ -49,14 +57,33 @@ pub enum MlsError {
     PskStoreError(AnyError),
     #[cfg_attr(feature = "std", error(transparent))]
     MlsRulesError(AnyError),
+    #[cfg_attr(feature = "std", error("Add proposal at index {0} rejected: {1}"))]
+    AddRejected(usize, AnyError),
     #[cfg_attr(feature = "std", error(transparent))]
     SerializationError(AnyError),
     #[cfg_attr(feature = "std", error(transparent))]
     ExtensionError(AnyError),
+    #[cfg_attr(feature = "std", error(transparent))]
+    CompressionError(AnyError),
+    #[cfg_attr(feature = "std", error("Unsupported compression algorithm {0}"))]
+    UnsupportedCompressionAlgorithm(u8),
+    #[cfg_attr(feature = "std", error("Invalid compression header"))]
+    InvalidCompressionHeader,
+    #[cfg_attr(
+        feature = "std",
+        error("Decompressed size {0} exceeds the configured maximum")
+    )]
+    DecompressedSizeExceeded(usize),
     #[cfg_attr(feature = "std", error("Cipher suite does not match"))]
     CipherSuiteMismatch,
+    #[cfg_attr(feature = "std", error("No escrow public key configured"))]
+    EscrowNotConfigured,
+    #[cfg_attr(feature = "std", error(transparent))]
+    TreeRejected(AnyError),
     #[cfg_attr(feature = "std", error("Invalid commit, missing required path"))]
     CommitMissingPath,
+    #[cfg_attr(feature = "std", error("Commit path not allowed by path policy"))]
+    CommitPathNotAllowed,
     #[cfg_attr(feature = "std", error("plaintext message for incorrect epoch"))]
     InvalidEpoch,
     #[cfg_attr(feature = "std", error("invalid signature found"))]
@@ -71,6 +98,14 @@ pub enum MlsError {
     WelcomeKeyPackageNotFound,
     #[cfg_attr(feature = "std", error("leaf not found in tree for index {0}"))]
     LeafNotFound(u32),
+    /// The welcome message of a commit would be larger than the
+    /// `max_welcome_bytes` commit option. Holds the size of the welcome
+    /// message and the maximum.
+    #[cfg_attr(
+        feature = "std",
+        error("welcome message of {0} bytes exceeds the maximum of {1} bytes")
+    )]
+    WelcomeTooLarge(usize, usize),
     #[cfg_attr(feature = "std", error("message from self can't be processed"))]
     CantProcessMessageFromSelf,
     #[cfg_attr(
@@ -109,6 +144,11 @@ pub enum MlsError {
     EpochNotFound,
     #[cfg_attr(feature = "std", error("Unencrypted application message"))]
     UnencryptedApplicationMessage,
+    #[cfg_attr(
+        feature = "std",
+        error("Unencrypted handshake message rejected by the encryption policy")
+    )]
+    UnencryptedHandshakeMessage,
     #[cfg_attr(
         feature = "std",
         error("NewMemberCommit sender type can only be used to send Commit content")
@@ -144,6 +184,50 @@ pub enum MlsError {
     ExistingPendingCommit,
     #[cfg_attr(feature = "std", error("pending commit not found"))]
     PendingCommitNotFound,
+    #[cfg_attr(feature = "std", error("commit not allowed while leaving the group"))]
+    CommitWhilePendingLeave,
+    #[cfg_attr(
+        feature = "std",
+        error("a commit updating the own leaf is required before sending application messages")
+    )]
+    SelfUpdateRequired,
+    #[cfg_attr(
+        feature = "std",
+        error("the current epoch exceeded its maximum age, a commit is required")
+    )]
+    EpochMaxAgeExceeded,
+    #[cfg_attr(
+        feature = "std",
+        error("application messages can't be sent in epoch {0}")
+    )]
+    EpochSendNotAllowed(u64),
+    #[cfg_attr(feature = "std", error("the buffer of future epoch messages is full"))]
+    FutureEpochBufferFull,
+    #[cfg_attr(
+        feature = "std",
+        error("ReInit to cipher suite {0:?} rejected by the cipher suite policy")
+    )]
+    ReInitDowngradeRejected(CipherSuite),
+    #[cfg_attr(
+        feature = "std",
+        error("cipher suite {0:?} is not allowed by the cipher suite policy")
+    )]
+    CipherSuiteNotAllowed(CipherSuite),
+    #[cfg_attr(
+        feature = "std",
+        error("protocol version {0:?} is not allowed by the cipher suite policy")
+    )]
+    ProtocolVersionNotAllowed(ProtocolVersion),
+    #[cfg_attr(
+        feature = "std",
+        error("The group is archived and can no longer be used to send or commit")
+    )]
+    GroupArchived,
+    #[cfg_attr(
+        feature = "std",
+        error("The local member was removed and the group state was deleted")
+    )]
+    GroupUsedAfterRemoval,
     #[cfg_attr(feature = "std", error("unexpected message type for action"))]
     UnexpectedMessageType,
     #[cfg_attr(
@@ -161,8 +245,30 @@ pub enum MlsError {
     InvalidSender,
     #[cfg_attr(feature = "std", error("GroupID mismatch"))]
     GroupIdMismatch,
+    #[cfg_attr(feature = "std", error("group ID of length {0} is too short"))]
+    GroupIdTooShort(usize),
+    #[cfg_attr(feature = "std", error("group ID of length {0} is too long"))]
+    GroupIdTooLong(usize),
+    #[cfg_attr(
+        feature = "std",
+        error("group ID contains invalid character at position {0}")
+    )]
+    InvalidGroupIdCharacter(usize),
+    #[cfg_attr(feature = "std", error("group policy is not supported by the client"))]
+    UnsupportedGroupPolicy,
+    #[cfg_attr(feature = "std", error("invite code length {0} is invalid"))]
+    InvalidInviteCodeLength(usize),
     #[cfg_attr(feature = "std", error("storage retention can not be zero"))]
     NonZeroRetentionRequired,
+    #[cfg_attr(
+        feature = "std",
+        error("storage encryption key of length {0} is invalid")
+    )]
+    InvalidStorageKeyLength(usize),
+    #[cfg_attr(feature = "std", error("storage encryption key with id {0} not found"))]
+    StorageKeyNotFound(u32),
+    #[cfg_attr(feature = "std", error("failed to decrypt stored group data"))]
+    StorageDecryptionFailed,
     #[cfg_attr(feature = "std", error("Too many PSK IDs to compute PSK secret"))]
     TooManyPskIds,
     #[cfg_attr(feature = "std", error("Missing required Psk"))]
@@ -171,6 +277,16 @@ pub enum MlsError {
     OldGroupStateNotFound,
     #[cfg_attr(feature = "std", error("leaf secret already consumed"))]
     InvalidLeafConsumption,
+    #[cfg_attr(
+        feature = "std",
+        error("application lanes of the sender are not available in this epoch")
+    )]
+    ApplicationLanesUnavailable,
+    #[cfg_attr(
+        feature = "std",
+        error("state after processing this message could not be persisted")
+    )]
+    StateNotPersisted,
     #[cfg_attr(feature = "std", error("key not available, invalid generation {0}"))]
     KeyMissing(u32),
     #[cfg_attr(
@@ -236,12 +352,46 @@ pub enum MlsError {
         error("init key can not be equal to leaf node public key")
     )]
     InitLeafKeyEquality,
+    #[cfg_attr(
+        feature = "std",
+        error("init key of add proposal {0} is also used by add proposal {1}")
+    )]
+    InitKeyReusedInCommit(usize, usize),
+    #[cfg_attr(
+        feature = "std",
+        error("init key of add proposal {0} is the HPKE key of tree node {1}")
+    )]
+    InitKeyReusedInTree(usize, u32),
+    #[cfg_attr(
+        feature = "std",
+        error("snapshot delta does not apply to the given snapshot")
+    )]
+    SnapshotDeltaBaseMismatch,
+    #[cfg_attr(
+        feature = "std",
+        error("snapshot resulting from applying a delta does not match its hash")
+    )]
+    SnapshotDeltaTargetMismatch,
+    #[cfg_attr(
+        feature = "std",
+        error("delta grows the tree to {0} nodes without setting them")
+    )]
+    SnapshotDeltaInvalidNodeCount(u32),
+    #[cfg_attr(feature = "std", error("unsupported group state version {0}"))]
+    UnsupportedSnapshotVersion(u16),
     #[cfg_attr(feature = "std", error("different identity in update for leaf {0}"))]
     DifferentIdentityInUpdate(u32),
     #[cfg_attr(feature = "std", error("update path pub key mismatch"))]
     PubKeyMismatch,
     #[cfg_attr(feature = "std", error("tree hash mismatch"))]
     TreeHashMismatch,
+    #[cfg_attr(
+        feature = "std",
+        error("ratchet tree delivered separately does not match the tree hash of the group info")
+    )]
+    RatchetTreeHashMismatch(Vec<u8>, Vec<u8>),
+    #[cfg_attr(feature = "std", error("transcript hash mismatch in epoch {0}"))]
+    TranscriptHashMismatch(u64),
     #[cfg_attr(feature = "std", error("bad update: no suitable secret key"))]
     UpdateErrorNoSecretKey,
     #[cfg_attr(feature = "std", error("invalid lca, not found on direct path"))]
@@ -271,6 +421,11 @@ pub enum MlsError {
     InvalidProtocolVersionInReInit,
     #[cfg_attr(feature = "std", error("More than one proposal applying to leaf: {0}"))]
     MoreThanOneProposalForLeaf(u32),
+    #[cfg_attr(
+        feature = "std",
+        error("More than one proposal of type {0:?} from the same sender")
+    )]
+    DuplicateProposalFromSender(ProposalType),
     #[cfg_attr(
         feature = "std",
         error("More than one GroupContextExtensions proposal")
@@ -313,6 +468,8 @@ pub enum MlsError {
     OtherProposalWithReInit,
     #[cfg_attr(feature = "std", error("Unsupported group extension {0:?}"))]
     UnsupportedGroupExtension(ExtensionType),
+    #[cfg_attr(feature = "std", error("Unknown critical extension {0:?}"))]
+    UnknownCriticalExtension(ExtensionType),
     #[cfg_attr(feature = "std", error("Unsupported custom proposal type {0:?}"))]
     UnsupportedCustomProposal(ProposalType),
     #[cfg_attr(feature = "std", error("by-ref proposal not found"))]
@@ -372,6 +529,7 @@ pub struct Client<C> {
     pub(crate) config: C,
     pub(crate) signing_identity: Option<(SigningIdentity, CipherSuite)>,
     pub(crate) signer: Option<SignatureSecretKey>,
+    pub(crate) additional_identities: Vec<(SigningIdentity, SignatureSecretKey, CipherSuite)>,
     pub(crate) version: ProtocolVersion,
 }
 
@@ -398,6 +556,7 @@ where
             config,
             signer,
             signing_identity,
+            additional_identities: Vec::new(),
             version,
         }
     }
@@ -408,6 +567,7 @@ where
             self.config.clone(),
             self.signer.clone(),
             self.signing_identity.clone(),
+            self.additional_identities.clone(),
             self.version,
         ))
     }
@@ -420,7 +580,7 @@ where
     /// the [KeyPackageStorage](crate::KeyPackageStorage)
     /// that was used to configure the client and will
     /// automatically be erased when this key package is used to
-    /// [join a group](Client::join_group).
+    /// [join a group](Client::join_group_validated).
     ///
     /// # Warning
     ///
@@ -430,8 +590,74 @@ where
         Ok(self.generate_key_package().await?.key_package_message())
     }
 
+    /// Creates a new key package message, as
+    /// [generate_key_package_message](Client::generate_key_package_message),
+    /// using the given key package and leaf node extensions instead of the
+    /// ones configured for the client.
+    ///
+    /// Leaf node extensions can carry per-device metadata, e.g. the platform
+    /// or the hash of a push token. Their types are automatically advertised
+    /// in the capabilities of the leaf node.
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    async fn generate_key_package(&self) -> Result<KeyPackageGeneration, MlsError> {
+    pub async fn generate_key_package_message_with_extensions(
+        &self,
+        key_package_extensions: ExtensionList,
+        leaf_node_extensions: ExtensionList,
+    ) -> Result<MlsMessage, MlsError> {
+        let key_pkg_gen = self
+            .generate_key_package_with_extensions(
+                self.signing_identity()?.1,
+                key_package_extensions,
+                leaf_node_extensions,
+            )
+            .await?;
+
+        Ok(key_pkg_gen.key_package_message())
+    }
+
+    /// Creates a new key package message, as
+    /// [generate_key_package_message](Client::generate_key_package_message),
+    /// for the given `cipher_suite` using the matching signing identity.
+    ///
+    /// The signing identity is either the one set with
+    /// [`ClientBuilder::signing_identity`] or one added with
+    /// [`ClientBuilder::additional_signing_identity`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn generate_key_package_message_with_cipher_suite(
+        &self,
+        cipher_suite: CipherSuite,
+    ) -> Result<MlsMessage, MlsError> {
+        let key_pkg_gen = self
+            .generate_key_package_with_extensions(
+                cipher_suite,
+                self.config.key_package_extensions(),
+                self.config.leaf_node_extensions(),
+            )
+            .await?;
+
+        Ok(key_pkg_gen.key_package_message())
+    }
+
+    /// Creates `count` new key package messages, as if calling
+    /// [generate_key_package_message](Client::generate_key_package_message)
+    /// `count` times, and returns them along with their references.
+    ///
+    /// This is more efficient than separate calls: HPKE keys are generated in
+    /// parallel if the `rayon` feature is enabled, and all key packages are
+    /// signed in sequence with the same signer. The secret keys of all key
+    /// packages are stored in the [KeyPackageStorage](crate::KeyPackageStorage)
+    /// before returning. References can be used to track which key packages
+    /// were uploaded to, and consumed from, a delivery service.
+    ///
+    /// # Warning
+    ///
+    /// Each key package message may only be used once.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn generate_key_packages(
+        &self,
+        count: usize,
+    ) -> Result<Vec<(KeyPackageRef, MlsMessage)>, MlsError> {
         let (signing_identity, cipher_suite) = self.signing_identity()?;
 
         let cipher_suite_provider = self
@@ -448,12 +674,81 @@ where
             identity_provider: &self.config.identity_provider(),
         };
 
+        let properties = self.config.leaf_properties();
+
+        let key_pkg_gens = key_package_generator
+            .generate_batch(
+                count,
+                self.config.lifetime(),
+                properties.capabilities,
+                self.config.key_package_extensions(),
+                properties.extensions,
+            )
+            .await?;
+
+        let mut key_package_repo = self.config.key_package_repo();
+        let mut key_packages = Vec::with_capacity(count);
+
+        for key_pkg_gen in key_pkg_gens {
+            let (id, key_package_data) = key_pkg_gen.to_storage()?;
+
+            key_package_repo
+                .insert(id, key_package_data)
+                .await
+                .map_err(|e| MlsError::KeyPackageRepoError(e.into_any_error()))?;
+
+            key_packages.push((
+                key_pkg_gen.reference.clone(),
+                key_pkg_gen.key_package_message(),
+            ));
+        }
+
+        Ok(key_packages)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn generate_key_package(&self) -> Result<KeyPackageGeneration, MlsError> {
+        self.generate_key_package_with_extensions(
+            self.signing_identity()?.1,
+            self.config.key_package_extensions(),
+            self.config.leaf_node_extensions(),
+        )
+        .await
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn generate_key_package_with_extensions(
+        &self,
+        cipher_suite: CipherSuite,
+        key_package_extensions: ExtensionList,
+        leaf_node_extensions: ExtensionList,
+    ) -> Result<KeyPackageGeneration, MlsError> {
+        let (signing_identity, signer) = self.identity_for(cipher_suite)?;
+
+        let cipher_suite_provider = self
+            .config
+            .crypto_provider()
+            .cipher_suite_provider(cipher_suite)
+            .ok_or(MlsError::UnsupportedCipherSuite(cipher_suite))?;
+
+        let key_package_generator = KeyPackageGenerator {
+            protocol_version: self.version,
+            cipher_suite_provider: &cipher_suite_provider,
+            signing_key: signer,
+            signing_identity,
+            identity_provider: &self.config.identity_provider(),
+        };
+
+        let properties = self
+            .config
+            .leaf_properties_with_extensions(leaf_node_extensions);
+
         let key_pkg_gen = key_package_generator
             .generate(
                 self.config.lifetime(),
-                self.config.capabilities(),
-                self.config.key_package_extensions(),
-                self.config.leaf_node_extensions(),
+                properties.capabilities,
+                key_package_extensions,
+                properties.extensions,
             )
             .await?;
 
@@ -523,6 +818,32 @@ where
         .await
     }
 
+    /// Create a MLS group, as [create_group](Client::create_group), using the
+    /// given `cipher_suite` and the matching signing identity.
+    ///
+    /// The signing identity is either the one set with
+    /// [`ClientBuilder::signing_identity`] or one added with
+    /// [`ClientBuilder::additional_signing_identity`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn create_group_with_cipher_suite(
+        &self,
+        cipher_suite: CipherSuite,
+        group_context_extensions: ExtensionList,
+    ) -> Result<Group<C>, MlsError> {
+        let (signing_identity, signer) = self.identity_for(cipher_suite)?;
+
+        Group::new(
+            self.config.clone(),
+            None,
+            cipher_suite,
+            self.version,
+            signing_identity.clone(),
+            group_context_extensions,
+            signer.clone(),
+        )
+        .await
+    }
+
     /// Join a MLS group via a welcome message created by a
     /// [Commit](crate::group::CommitOutput).
     ///
@@ -532,21 +853,173 @@ where
     /// at the time the welcome message was created. `tree_data` can
     /// be exported from a group using the
     /// [export tree function](crate::group::Group::export_tree).
+    ///
+    /// The signer matching the cipher suite of `welcome_message` is used.
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn join_group_validated(
+        &self,
+        tree_data: Option<ExportedTree<'_>>,
+        welcome_message: &Validated<MlsMessage>,
+    ) -> Result<(Group<C>, NewMemberInfo), MlsError> {
+        self.join(tree_data, welcome_message.borrowed()).await
+    }
+
+    /// Join a MLS group via a welcome message created by a
+    /// [Commit](crate::group::CommitOutput).
+    ///
+    /// Equivalent to [`Client::join_group_validated`] after validating
+    /// `welcome_message` with [`Validated::new`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    // The FFI bindings can't pass `Validated` values and keep using this.
+    #[cfg_attr(
+        not(all(feature = "ffi", not(test))),
+        deprecated(note = "use `Client::join_group_validated` instead")
+    )]
     pub async fn join_group(
         &self,
         tree_data: Option<ExportedTree<'_>>,
         welcome_message: &MlsMessage,
     ) -> Result<(Group<C>, NewMemberInfo), MlsError> {
+        self.join(tree_data, Validated::check(welcome_message)?)
+            .await
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn join(
+        &self,
+        tree_data: Option<ExportedTree<'_>>,
+        welcome_message: Validated<&MlsMessage>,
+    ) -> Result<(Group<C>, NewMemberInfo), MlsError> {
+        let signer = match self.additional_identity(welcome_message.cipher_suite()) {
+            Some((_, signer)) => signer,
+            None => self.signer()?,
+        };
+
         Group::join(
             welcome_message,
             tree_data,
             self.config.clone(),
-            self.signer()?.clone(),
+            signer.clone(),
         )
         .await
     }
 
+    /// Begin joining a MLS group via a welcome message that does not contain
+    /// the ratchet tree, when the tree is not available yet.
+    ///
+    /// The returned [`PendingJoin`] exposes the
+    /// [tree hash](PendingJoin::tree_hash) of the group, e.g. to download the
+    /// tree from the delivery service, and [finishes](PendingJoin::finish)
+    /// joining once the tree is available. Otherwise this is equivalent to
+    /// [`Client::join_group_validated`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn begin_join_group(
+        &self,
+        welcome_message: &MlsMessage,
+    ) -> Result<PendingJoin<C>, MlsError> {
+        let signer = match self.additional_identity(welcome_message.cipher_suite()) {
+            Some((_, signer)) => signer,
+            None => self.signer()?,
+        };
+
+        Group::begin_join(
+            Validated::check(welcome_message)?,
+            self.config.clone(),
+            signer.clone(),
+            #[cfg(feature = "psk")]
+            None,
+            &self.config.secret_store(),
+        )
+        .await
+    }
+
+    /// Join a MLS group via a welcome message, obtaining the external
+    /// pre-shared keys it requires from `psk_lookup` on demand.
+    ///
+    /// PSKs are first looked up in the
+    /// [PreSharedKeyStorage](crate::PreSharedKeyStorage) of this client and
+    /// only the missing ones are requested from `psk_lookup`, e.g. by
+    /// fetching them from a server. PSKs returned by `psk_lookup` are not
+    /// inserted into the storage of this client. Otherwise this is equivalent
+    /// to [`Client::join_group_validated`].
+    #[cfg(feature = "psk")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn join_group_with_psk_lookup<L: PreSharedKeyStorage>(
+        &self,
+        tree_data: Option<ExportedTree<'_>>,
+        welcome_message: &MlsMessage,
+        psk_lookup: &L,
+    ) -> Result<(Group<C>, NewMemberInfo), MlsError> {
+        let signer = match self.additional_identity(welcome_message.cipher_suite()) {
+            Some((_, signer)) => signer,
+            None => self.signer()?,
+        };
+
+        let psk_store = PskStoreWithLookup {
+            store: self.config.secret_store(),
+            lookup: psk_lookup,
+        };
+
+        let pending = Group::begin_join(
+            Validated::check(welcome_message)?,
+            self.config.clone(),
+            signer.clone(),
+            None,
+            &psk_store,
+        )
+        .await?;
+
+        Group::finish_join(pending, tree_data).await
+    }
+
+    /// Delete the key packages used to join groups whose grace period, set
+    /// with
+    /// [`ClientBuilder::consumed_key_package_grace_period`](crate::client_builder::ClientBuilder::consumed_key_package_grace_period),
+    /// elapsed.
+    ///
+    /// Key packages are only kept after being used if a grace period is set
+    /// and the current time is available.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn purge_consumed(&self) -> Result<(), MlsError> {
+        let grace_period = self
+            .config
+            .consumed_key_package_grace_period()
+            .unwrap_or_default();
+
+        let Some(now) = crate::group::epoch_age::current_time() else {
+            return Ok(());
+        };
+
+        self.config
+            .key_package_repo()
+            .delete_consumed(now.saturating_sub(grace_period))
+            .await
+            .map_err(|e| MlsError::KeyPackageRepoError(e.into_any_error()))
+    }
+
+    /// List the external pre-shared keys required to join a group with
+    /// `welcome_message`, before calling [Client::join_group_validated].
+    ///
+    /// The group secrets of the welcome message are decrypted with the key
+    /// package they were sent to, which is left in the
+    /// [KeyPackageStorage](crate::KeyPackageStorage), so that the application
+    /// can obtain the listed PSKs and insert them into the
+    /// [PreSharedKeyStorage](crate::PreSharedKeyStorage) of this client
+    /// before joining. Resumption PSKs are resolved from group state storage
+    /// and are not listed.
+    #[cfg(feature = "psk")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn welcome_required_psks(
+        &self,
+        welcome_message: &MlsMessage,
+    ) -> Result<Vec<ExternalPskId>, MlsError> {
+        Group::welcome_required_psks(Validated::check(welcome_message)?, &self.config).await
+    }
+
     /// 0-RTT add to an existing [group](crate::group::Group)
     ///
     /// External commits allow for immediate entry into a
@@ -559,7 +1032,7 @@ where
     /// [group_info_message](crate::group::Group::group_info_message)
     /// function.
     ///
-    /// `tree_data` may be provided following the same rules as [Client::join_group]
+    /// `tree_data` may be provided following the same rules as [Client::join_group_validated]
     ///
     /// If PSKs are provided in `external_psks`, the
     /// [PreSharedKeyStorage](crate::PreSharedKeyStorage)
@@ -584,9 +1057,15 @@ where
         &self,
         group_info_msg: MlsMessage,
     ) -> Result<(Group<C>, MlsMessage), MlsError> {
+        let (signing_identity, signer) =
+            match self.additional_identity(group_info_msg.cipher_suite()) {
+                Some(identity) => identity,
+                None => (self.signing_identity()?.0, self.signer()?),
+            };
+
         ExternalCommitBuilder::new(
-            self.signer()?.clone(),
-            self.signing_identity()?.0.clone(),
+            signer.clone(),
+            signing_identity.clone(),
             self.config.clone(),
         )
         .build(group_info_msg)
@@ -615,7 +1094,7 @@ where
             .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?
             .ok_or(MlsError::GroupNotFound)?;
 
-        let snapshot = Snapshot::mls_decode(&mut &*snapshot)?;
+        let snapshot = Snapshot::from_bytes(&snapshot)?;
 
         Group::from_snapshot(self.config.clone(), snapshot).await
     }
@@ -624,7 +1103,7 @@ where
     ///
     /// An existing group member will need to perform a
     /// [commit](crate::Group::commit) to complete the add and the resulting
-    /// welcome message can be used by [join_group](Client::join_group).
+    /// welcome message can be used by [join_group](Client::join_group_validated).
     #[cfg(feature = "by_ref_proposal")]
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn external_add_proposal(
@@ -660,11 +1139,14 @@ where
         )
         .await?;
 
-        let key_package = self.generate_key_package().await?.key_package;
-
-        (key_package.cipher_suite == cipher_suite)
-            .then_some(())
-            .ok_or(MlsError::UnsupportedCipherSuite(cipher_suite))?;
+        let key_package = self
+            .generate_key_package_with_extensions(
+                cipher_suite,
+                self.config.key_package_extensions(),
+                self.config.leaf_node_extensions(),
+            )
+            .await?
+            .key_package;
 
         let message = AuthenticatedContent::new_signed(
             &cipher_suite_provider,
@@ -673,7 +1155,7 @@ where
             Content::Proposal(Box::new(Proposal::Add(Box::new(AddProposal {
                 key_package,
             })))),
-            self.signer()?,
+            self.identity_for(cipher_suite)?.1,
             WireFormat::PublicMessage,
             authenticated_data,
         )
@@ -695,6 +1177,44 @@ where
         self.signer.as_ref().ok_or(MlsError::SignerNotFound)
     }
 
+    // Signing identity and signer used for groups with `cipher_suite`.
+    fn identity_for(
+        &self,
+        cipher_suite: CipherSuite,
+    ) -> Result<(&SigningIdentity, &SignatureSecretKey), MlsError> {
+        match self.signing_identity() {
+            Ok((signing_identity, cs)) if cs == cipher_suite => {
+                Ok((signing_identity, self.signer()?))
+            }
+            Ok(_) => self
+                .additional_identity(Some(cipher_suite))
+                .ok_or(MlsError::UnsupportedCipherSuite(cipher_suite)),
+            Err(e) => self.additional_identity(Some(cipher_suite)).ok_or(e),
+        }
+    }
+
+    // Identity added with `ClientBuilder::additional_signing_identity` for
+    // `cipher_suite`, if any.
+    fn additional_identity(
+        &self,
+        cipher_suite: Option<CipherSuite>,
+    ) -> Option<(&SigningIdentity, &SignatureSecretKey)> {
+        self.additional_identities
+            .iter()
+            .find(|(_, _, cs)| Some(*cs) == cipher_suite)
+            .map(|(signing_identity, signer, _)| (signing_identity, signer))
+    }
+
+    /// Cipher suites for which this client has a signing identity.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn cipher_suites(&self) -> Vec<CipherSuite> {
+        self.signing_identity
+            .iter()
+            .map(|(_, cs)| *cs)
+            .chain(self.additional_identities.iter().map(|(_, _, cs)| *cs))
+            .collect()
+    }
+
     #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
     pub fn signing_identity(&self) -> Result<(&SigningIdentity, CipherSuite), MlsError> {
         self.signing_identity
@@ -726,6 +1246,29 @@ where
     pub fn group_state_storage(&self) -> <C as ClientConfig>::GroupStateStorage {
         self.config.group_state_storage()
     }
+
+    /// The [`ErrorRenderer`](crate::error::ErrorRenderer) that this client
+    /// was configured to use, see [`ClientBuilder::error_renderer`].
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn error_renderer(&self) -> Option<SharedErrorRenderer> {
+        self.config.error_renderer()
+    }
+
+    /// Message describing `error` to users, using the renderer this client
+    /// was configured with.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn render_error(&self, error: &MlsError) -> String {
+        error_render::render_error(self.config.error_renderer(), error)
+    }
+
+    /// Message describing errors with [`code`](MlsError::code) to users,
+    /// using the renderer this client was configured with.
+    ///
+    /// This is [`Client::render_error`] for bindings that only return the
+    /// code of errors.
+    pub fn error_message(&self, code: &str) -> String {
+        error_render::error_message(self.config.error_renderer(), code)
+    }
 }
 
 #[cfg(test)]
@@ -798,6 +1341,72 @@ mod tests {
 
     use alloc::vec;
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn batch_key_packages_are_stored_and_distinct() {
+        let (identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"foo").await;
+
+        let client = TestClientBuilder::new_for_test()
+            .signing_identity(identity, secret_key, TEST_CIPHER_SUITE)
+            .build();
+
+        let key_packages = client.generate_key_packages(3).await.unwrap();
+        assert_eq!(key_packages.len(), 3);
+
+        let cs = client
+            .config
+            .crypto_provider()
+            .cipher_suite_provider(TEST_CIPHER_SUITE)
+            .unwrap();
+
+        for (i, (reference, message)) in key_packages.iter().enumerate() {
+            let key_package = message.clone().into_key_package().unwrap();
+
+            let computed = key_package.to_reference(&cs).await.unwrap();
+            assert_eq!(&computed, reference);
+
+            assert!(key_packages[i + 1..]
+                .iter()
+                .all(|(other, _)| other != reference));
+
+            let stored = client.config.key_package_repo().get(reference);
+            assert!(stored.is_some());
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn key_package_with_leaf_node_extensions_advertises_them() {
+        let (identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"foo").await;
+
+        let client = TestClientBuilder::new_for_test()
+            .signing_identity(identity, secret_key, TEST_CIPHER_SUITE)
+            .build();
+
+        let mut leaf_node_extensions = ExtensionList::new();
+
+        leaf_node_extensions
+            .set_from(crate::extension::test_utils::TestExtension { foo: 1 })
+            .unwrap();
+
+        let key_package = client
+            .generate_key_package_message_with_extensions(
+                ExtensionList::new(),
+                leaf_node_extensions.clone(),
+            )
+            .await
+            .unwrap()
+            .into_key_package()
+            .unwrap();
+
+        let leaf_node = &key_package.leaf_node;
+
+        assert_eq!(leaf_node.ungreased_extensions(), leaf_node_extensions);
+
+        assert!(leaf_node
+            .ungreased_capabilities()
+            .extensions
+            .contains(&42.into()));
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn test_keygen() {
         // This is meant to test the inputs to the internal key package generator
@@ -837,6 +1446,123 @@ mod tests {
         }
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn client_with_many_cipher_suites_picks_matching_identity() {
+        let Some(other_cipher_suite) = TestCryptoProvider::all_supported_cipher_suites()
+            .into_iter()
+            .find(|cs| cs != &TEST_CIPHER_SUITE)
+        else {
+            return;
+        };
+
+        let (alice_identity, secret_key) =
+            get_test_signing_identity(other_cipher_suite, b"alice").await;
+
+        let alice = TestClientBuilder::new_for_test()
+            .signing_identity(alice_identity, secret_key, other_cipher_suite)
+            .build();
+
+        let (bob_identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"bob").await;
+        let (bob_other_identity, other_secret_key) =
+            get_test_signing_identity(other_cipher_suite, b"bob").await;
+
+        let bob = TestClientBuilder::new_for_test()
+            .signing_identity(bob_identity, secret_key, TEST_CIPHER_SUITE)
+            .additional_signing_identity(
+                bob_other_identity.clone(),
+                other_secret_key,
+                other_cipher_suite,
+            )
+            .build();
+
+        assert_eq!(bob.cipher_suites(), [TEST_CIPHER_SUITE, other_cipher_suite]);
+
+        let key_package = bob
+            .generate_key_package_message_with_cipher_suite(other_cipher_suite)
+            .await
+            .unwrap();
+
+        assert_eq!(key_package.cipher_suite(), Some(other_cipher_suite));
+
+        let mut alice_group = alice.create_group(ExtensionList::new()).await.unwrap();
+
+        let commit = alice_group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let (bob_group, _) = bob
+            .join_group_validated(
+                None,
+                &Validated::new(commit.welcome_messages[0].clone()).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(bob_group.cipher_suite(), other_cipher_suite);
+        assert_eq!(
+            bob_group.current_member_signing_identity().unwrap(),
+            &bob_other_identity
+        );
+
+        let bob_group = bob
+            .create_group_with_cipher_suite(other_cipher_suite, ExtensionList::new())
+            .await
+            .unwrap();
+
+        assert_eq!(bob_group.cipher_suite(), other_cipher_suite);
+
+        let res = alice
+            .create_group_with_cipher_suite(TEST_CIPHER_SUITE, ExtensionList::new())
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::UnsupportedCipherSuite(cs)) if cs == TEST_CIPHER_SUITE);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn primary_identity_takes_precedence_over_additional_identity() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let (bob_identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"bob").await;
+        let (bob_other_identity, other_secret_key) =
+            get_test_signing_identity(TEST_CIPHER_SUITE, b"bob other").await;
+
+        let bob = TestClientBuilder::new_for_test()
+            .additional_signing_identity(bob_other_identity, other_secret_key, TEST_CIPHER_SUITE)
+            .signing_identity(bob_identity.clone(), secret_key, TEST_CIPHER_SUITE)
+            .build();
+
+        assert_eq!(bob.cipher_suites(), [TEST_CIPHER_SUITE]);
+
+        let key_package = bob.generate_key_package_message().await.unwrap();
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let (bob_group, _) = bob
+            .join_group_validated(
+                None,
+                &Validated::new(commit.welcome_messages[0].clone()).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            bob_group.current_member_signing_identity().unwrap(),
+            &bob_identity
+        );
+    }
+
     #[cfg(feature = "by_ref_proposal")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn new_member_add_proposal_adds_to_group() {
@@ -859,7 +1585,7 @@ mod tests {
 
         let message = alice_group
             .group
-            .process_incoming_message(proposal)
+            .process_incoming_message_validated(Validated::new(proposal).unwrap())
             .await
             .unwrap();
 
@@ -938,7 +1664,7 @@ mod tests {
 
         let _ = alice_group
             .group
-            .process_incoming_message(external_commit.clone())
+            .process_incoming_message_validated(Validated::new(external_commit.clone()).unwrap())
             .await
             .unwrap();
 
@@ -946,7 +1672,7 @@ mod tests {
 
         let message = bob_group
             .group
-            .process_incoming_message(external_commit)
+            .process_incoming_message_validated(Validated::new(external_commit).unwrap())
             .await
             .unwrap();
 
@@ -1033,7 +1759,7 @@ mod tests {
         // If Carol tries to join Alice's group using the group info from Bob's group, that fails.
         let res = alice_group
             .group
-            .process_incoming_message(external_commit)
+            .process_incoming_message_validated(Validated::new(external_commit).unwrap())
             .await;
         assert_matches!(res, Err(_));
     }
@@ -1046,4 +1772,49 @@ mod tests {
         let bob = alice.to_builder().extension_type(34.into()).build();
         assert_eq!(bob.config.supported_extensions(), [33, 34].map(Into::into));
     }
+
+    #[cfg(feature = "std")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn consumed_key_package_is_kept_for_grace_period() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let (mut bob, key_package) =
+            test_client_with_key_pkg_custom(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob", |c| {
+                c.0.settings.consumed_key_package_grace_period = Some(3600)
+            })
+            .await;
+
+        let store = bob.key_package_store();
+        let (id, _) = store.key_packages()[0].clone();
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let (mut bob_group, _) = bob
+            .join_group_validated(
+                None,
+                &Validated::new(commit.welcome_messages[0].clone()).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        bob_group.write_to_storage().await.unwrap();
+
+        assert!(store.consumed_at(&id).is_some());
+        assert!(store.get(&id).is_some());
+
+        bob.purge_consumed().await.unwrap();
+        assert!(store.get(&id).is_some());
+
+        bob.config.0.settings.consumed_key_package_grace_period = Some(0);
+
+        bob.purge_consumed().await.unwrap();
+        assert!(store.get(&id).is_none());
+    }
 }
@@ -10,10 +10,14 @@ use crate::{
     cipher_suite::CipherSuite,
     client::Client,
     client_config::ClientConfig,
+    crypto::{RandomSource, WithRandomSource},
+    error_render::{ErrorRenderer, SharedErrorRenderer},
     extension::{ExtensionType, MlsExtension},
     group::{
         mls_rules::{DefaultMlsRules, MlsRules},
         proposal::ProposalType,
+        CipherSuitePolicy, EpochAgePolicy, GroupIdPolicy, GroupPolicySupport, InitKeyReusePolicy,
+        ProposalCompaction, ReInitCipherSuitePolicy, SharedTreeValidator, TreeValidator,
     },
     identity::CredentialType,
     identity::SigningIdentity,
@@ -26,10 +30,34 @@ use crate::{
     Sealed,
 };
 
+#[cfg(feature = "signature_label_suffix")]
+use crate::crypto::WithSignatureLabelSuffix;
+
 #[cfg(feature = "std")]
 use crate::time::MlsTime;
 
-use alloc::vec::Vec;
+#[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+use crate::group::secret_tree::MAX_RATCHET_BACK_HISTORY;
+
+#[cfg(feature = "private_message")]
+use crate::group::{mls_rules::EncryptionPolicy, WatermarkPersistence};
+
+#[cfg(feature = "by_ref_proposal")]
+use crate::group::DuplicateProposalPolicy;
+
+#[cfg(any(
+    feature = "by_ref_proposal",
+    all(feature = "private_message", feature = "prior_epoch")
+))]
+use core::time::Duration;
+
+#[cfg(feature = "escrow")]
+use mls_rs_core::crypto::HpkePublicKey;
+
+#[cfg(feature = "compression")]
+use crate::group::{CompressionSettings, Compressor};
+
+use alloc::{sync::Arc, vec::Vec};
 
 #[cfg(feature = "sqlite")]
 use mls_rs_provider_sqlite::{
@@ -43,6 +71,10 @@ use mls_rs_provider_sqlite::{
 #[cfg(feature = "private_message")]
 pub use crate::group::padding::PaddingMode;
 
+mod presets;
+
+pub use presets::ClientPreset;
+
 /// Base client configuration type when instantiating `ClientBuilder`
 pub type BaseConfig = Config<
     InMemoryKeyPackageStorage,
@@ -202,6 +234,7 @@ impl ClientBuilder<BaseConfig> {
             crypto_provider: Missing,
             signer: Default::default(),
             signing_identity: Default::default(),
+            additional_identities: Default::default(),
             version: ProtocolVersion::MLS_10,
         }))
     }
@@ -219,6 +252,7 @@ impl ClientBuilder<EmptyConfig> {
             crypto_provider: Missing,
             signer: Default::default(),
             signing_identity: Default::default(),
+            additional_identities: Default::default(),
             version: ProtocolVersion::MLS_10,
         }))
     }
@@ -240,6 +274,7 @@ impl ClientBuilder<BaseSqlConfig> {
             crypto_provider: Missing,
             signer: Default::default(),
             signing_identity: Default::default(),
+            additional_identities: Default::default(),
             version: ProtocolVersion::MLS_10,
         })))
     }
@@ -354,6 +389,313 @@ impl<C: IntoConfig> ClientBuilder<C> {
         ClientBuilder(c)
     }
 
+    /// Set the policy used to generate and validate group IDs.
+    ///
+    /// By default, group IDs are 32 random bytes and any group ID is accepted
+    /// when joining a group.
+    pub fn group_id_policy(self, policy: GroupIdPolicy) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.group_id_policy = policy;
+        ClientBuilder(c)
+    }
+
+    /// Set the [group policies](crate::extension::built_in::GroupPolicyExt)
+    /// the client can honor when joining a group.
+    ///
+    /// By default, groups with any policy or without policy can be joined.
+    pub fn group_policy_support(
+        self,
+        support: GroupPolicySupport,
+    ) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.group_policy_support = support;
+        ClientBuilder(c)
+    }
+
+    /// Set the policy limiting how long groups may stay in the same epoch.
+    ///
+    /// By default, epochs never expire.
+    pub fn epoch_age_policy(self, policy: EpochAgePolicy) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.epoch_age_policy = Some(policy);
+        ClientBuilder(c)
+    }
+
+    /// Set the policy on the cipher suite groups may be re-initialized with.
+    ///
+    /// By default, any cipher suite is accepted.
+    pub fn reinit_cipher_suite_policy(
+        self,
+        policy: ReInitCipherSuitePolicy,
+    ) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.reinit_cipher_suite_policy = policy;
+        ClientBuilder(c)
+    }
+
+    /// Set the policy on the cipher suites and protocol versions of groups
+    /// created, joined or re-initialized by the client, see
+    /// [`CipherSuitePolicy`].
+    ///
+    /// By default, everything is allowed.
+    pub fn cipher_suite_policy(
+        self,
+        policy: CipherSuitePolicy,
+    ) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.cipher_suite_policy = policy;
+        ClientBuilder(c)
+    }
+
+    /// Set the policy on multiple Update proposals, or multiple custom
+    /// proposals of the same type, from the same sender in a commit.
+    ///
+    /// By default, no such proposals are filtered out when committing.
+    #[cfg(feature = "by_ref_proposal")]
+    pub fn duplicate_proposal_policy(
+        self,
+        policy: DuplicateProposalPolicy,
+    ) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.duplicate_proposal_policy = Some(policy);
+        ClientBuilder(c)
+    }
+
+    /// Leave proposals received by reference more than `ttl` ago out of the
+    /// commits created by the client, e.g. to avoid committing an Update
+    /// proposal that was received days earlier. Expired proposals are
+    /// reported in [`CommitOutput::expired_proposals`](crate::group::CommitOutput::expired_proposals).
+    ///
+    /// Proposals only expire if the current time is available, i.e. with the
+    /// `std` feature. By default, proposals never expire.
+    #[cfg(feature = "by_ref_proposal")]
+    pub fn proposal_ttl(self, ttl: Duration) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.proposal_ttl = Some(ttl);
+        ClientBuilder(c)
+    }
+
+    /// Leave proposals whose effect is already achieved by other proposals out
+    /// of the commits created by the client, as configured by `compaction`.
+    ///
+    /// By default, all proposals are committed.
+    pub fn proposal_compaction(
+        self,
+        compaction: ProposalCompaction,
+    ) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.proposal_compaction = Some(compaction);
+        ClientBuilder(c)
+    }
+
+    /// Set how strictly the init keys of added key packages are checked
+    /// against reuse as the HPKE key of other nodes or key packages.
+    ///
+    /// By default, only the checks required by RFC 9420 are performed.
+    pub fn init_key_reuse_policy(
+        self,
+        policy: InitKeyReusePolicy,
+    ) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.init_key_reuse_policy = policy;
+        ClientBuilder(c)
+    }
+
+    /// Set the policy on which messages must be encrypted.
+    ///
+    /// By default, only application messages must be encrypted.
+    #[cfg(feature = "private_message")]
+    pub fn encryption_policy(self, policy: EncryptionPolicy) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.encryption_policy = policy;
+        ClientBuilder(c)
+    }
+
+    /// Set when the group state is persisted to protect the secret tree
+    /// ratchets against crashes, see [`WatermarkPersistence`].
+    ///
+    /// By default, the state is only persisted by
+    /// [`Group::write_to_storage`](crate::group::Group::write_to_storage).
+    #[cfg(feature = "private_message")]
+    pub fn watermark_persistence(
+        self,
+        persistence: WatermarkPersistence,
+    ) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.watermark_persistence = persistence;
+        ClientBuilder(c)
+    }
+
+    /// Allow application messages to be sent in the previous epoch with
+    /// [`Group::encrypt_at_epoch`](crate::group::Group::encrypt_at_epoch)
+    /// for `window` after entering the current epoch, e.g. while receivers
+    /// that did not process the latest commit yet are still in the previous
+    /// epoch.
+    ///
+    /// Sending in the previous epoch requires the current time, i.e. the
+    /// `std` feature. By default, messages can only be sent in the current
+    /// epoch.
+    #[cfg(all(feature = "private_message", feature = "prior_epoch"))]
+    pub fn previous_epoch_send_window(
+        self,
+        window: Duration,
+    ) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.previous_epoch_send_window = Some(window);
+        ClientBuilder(c)
+    }
+
+    /// Set the maximum number of message keys retained per sender and
+    /// ratchet to decrypt messages received out of order.
+    ///
+    /// When the limit is exceeded, keys of the oldest generations are
+    /// discarded and the corresponding messages can no longer be decrypted.
+    /// By default, no limit is applied.
+    #[cfg(feature = "out_of_order")]
+    pub fn max_retained_message_keys(self, max_keys: usize) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.max_retained_message_keys = Some(max_keys);
+        ClientBuilder(c)
+    }
+
+    /// Set the maximum number of generations a received message may skip
+    /// ahead of the current generation of its sender's ratchet.
+    ///
+    /// Decrypting a message requires ratcheting forward to its generation,
+    /// so a sender claiming a very large generation could force the receiver
+    /// to derive that many keys. Messages skipping more generations are
+    /// rejected with [`MlsError::InvalidFutureGeneration`](crate::client::MlsError::InvalidFutureGeneration).
+    /// The default is 1024.
+    #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+    pub fn max_generation_jump(self, max_jump: u32) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.max_generation_jump = max_jump;
+        ClientBuilder(c)
+    }
+
+    /// Compress application messages with `compressor`.
+    ///
+    /// Received application messages decompressing to more than
+    /// `max_decompressed_len` bytes are rejected. All members of a group must
+    /// use the same compression settings. By default, application messages
+    /// are not compressed.
+    #[cfg(feature = "compression")]
+    pub fn compression<P>(
+        self,
+        compressor: P,
+        max_decompressed_len: usize,
+    ) -> ClientBuilder<IntoConfigOutput<C>>
+    where
+        P: Compressor + 'static,
+    {
+        let mut c = self.0.into_config();
+
+        c.0.settings.compression = Some(CompressionSettings {
+            compressor: Arc::new(compressor),
+            max_decompressed_len,
+        });
+
+        ClientBuilder(c)
+    }
+
+    /// Enable escrow of the exporter secret of each epoch to `escrow_key`,
+    /// see [`Group::escrow_record`](crate::group::Group::escrow_record).
+    /// Escrow is disabled by default.
+    #[cfg(feature = "escrow")]
+    pub fn escrow_public_key(
+        self,
+        escrow_key: HpkePublicKey,
+    ) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.escrow_public_key = Some(escrow_key);
+        ClientBuilder(c)
+    }
+
+    /// Check the tree of each new epoch with `validator` before a commit is
+    /// applied, see [`TreeValidator`].
+    pub fn tree_validator<V>(self, validator: V) -> ClientBuilder<IntoConfigOutput<C>>
+    where
+        V: TreeValidator + 'static,
+    {
+        let mut c = self.0.into_config();
+        c.0.settings.tree_validator = Some(SharedTreeValidator(Arc::new(validator)));
+        ClientBuilder(c)
+    }
+
+    /// Describe errors with the messages of `renderer` in
+    /// [`Client::render_error`] and
+    /// [`Group::render_error`](crate::Group::render_error). Errors are
+    /// described with the messages of [`DefaultErrorRenderer`] by default.
+    ///
+    /// [`DefaultErrorRenderer`]: crate::error::DefaultErrorRenderer
+    pub fn error_renderer<R>(self, renderer: R) -> ClientBuilder<IntoConfigOutput<C>>
+    where
+        R: ErrorRenderer + 'static,
+    {
+        let mut c = self.0.into_config();
+        c.0.settings.error_renderer = Some(SharedErrorRenderer(Arc::new(renderer)));
+        ClientBuilder(c)
+    }
+
+    /// Record the confirmed transcript hash and committer of each epoch of
+    /// each group, see
+    /// [`Group::transcript_chain`](crate::group::Group::transcript_chain).
+    /// Recording is disabled by default.
+    pub fn record_transcript_chain(self, record: bool) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.record_transcript_chain = record;
+        ClientBuilder(c)
+    }
+
+    /// Record the roster operations applied by each commit of each group, see
+    /// [`Group::roster_diffs`](crate::group::Group::roster_diffs).
+    /// Recording is disabled by default.
+    pub fn record_roster_diffs(self, record: bool) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.record_roster_diffs = record;
+        ClientBuilder(c)
+    }
+
+    /// Keep groups from which this client is removed as read-only archives,
+    /// see [`Group::archive`](crate::group::Group::archive), instead of
+    /// deleting their state from storage. Disabled by default.
+    pub fn archive_removed_groups(self, archive: bool) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.archive_removed_groups = archive;
+        ClientBuilder(c)
+    }
+
+    /// Keep key packages used to join a group for `grace_period_in_s` seconds
+    /// before they are deleted by
+    /// [`Client::purge_consumed`](crate::Client::purge_consumed), e.g. to
+    /// process the welcome message again if the group state could not be
+    /// written to storage.
+    ///
+    /// By default, the key package is deleted when the group joined with it is
+    /// first written to storage.
+    pub fn consumed_key_package_grace_period(
+        self,
+        grace_period_in_s: u64,
+    ) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.consumed_key_package_grace_period = Some(grace_period_in_s);
+        ClientBuilder(c)
+    }
+
+    /// Record an [`EpochDelta`](crate::group::EpochDelta) for each epoch of
+    /// the groups of this client, to persist group state in an event-sourced
+    /// backend instead of writing full snapshots.
+    ///
+    /// [`Group::compaction_due`](crate::Group::compaction_due) returns `true`
+    /// once `compaction_interval` deltas were recorded since the last
+    /// [compaction](crate::Group::compact). By default, no deltas are recorded.
+    #[cfg(feature = "event_sourcing")]
+    pub fn epoch_deltas(self, compaction_interval: u64) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.epoch_delta_compaction_interval = Some(compaction_interval);
+        ClientBuilder(c)
+    }
+
     /// Set the key package repository to be used by the client.
     ///
     /// By default, an in-memory repository is used.
@@ -373,6 +715,7 @@ impl<C: IntoConfig> ClientBuilder<C> {
             crypto_provider: c.crypto_provider,
             signer: c.signer,
             signing_identity: c.signing_identity,
+            additional_identities: c.additional_identities,
             version: c.version,
         }))
     }
@@ -396,6 +739,7 @@ impl<C: IntoConfig> ClientBuilder<C> {
             crypto_provider: c.crypto_provider,
             signer: c.signer,
             signing_identity: c.signing_identity,
+            additional_identities: c.additional_identities,
             version: c.version,
         }))
     }
@@ -422,6 +766,7 @@ impl<C: IntoConfig> ClientBuilder<C> {
             mls_rules: c.mls_rules,
             signer: c.signer,
             signing_identity: c.signing_identity,
+            additional_identities: c.additional_identities,
             version: c.version,
         }))
     }
@@ -446,6 +791,7 @@ impl<C: IntoConfig> ClientBuilder<C> {
             crypto_provider: c.crypto_provider,
             signer: c.signer,
             signing_identity: c.signing_identity,
+            additional_identities: c.additional_identities,
             version: c.version,
         }))
     }
@@ -470,6 +816,71 @@ impl<C: IntoConfig> ClientBuilder<C> {
             crypto_provider,
             signer: c.signer,
             signing_identity: c.signing_identity,
+            additional_identities: c.additional_identities,
+            version: c.version,
+        }))
+    }
+
+    /// Set the source of entropy used by the client.
+    ///
+    /// The crypto provider set with [`ClientBuilder::crypto_provider`] is
+    /// wrapped in a [`WithRandomSource`], see its documentation for details.
+    /// This function must therefore be called after setting the crypto
+    /// provider. By default, the random number generator of the crypto
+    /// provider is used.
+    pub fn random_source<R>(
+        self,
+        random_source: R,
+    ) -> ClientBuilder<WithCryptoProvider<WithRandomSource<C::CryptoProvider, R>, C>>
+    where
+        C::CryptoProvider: CryptoProvider,
+        R: RandomSource + Clone,
+    {
+        let Config(c) = self.0.into_config();
+
+        ClientBuilder(Config(ConfigInner {
+            settings: c.settings,
+            key_package_repo: c.key_package_repo,
+            psk_store: c.psk_store,
+            group_state_storage: c.group_state_storage,
+            identity_provider: c.identity_provider,
+            mls_rules: c.mls_rules,
+            crypto_provider: WithRandomSource::new(c.crypto_provider, random_source),
+            signer: c.signer,
+            signing_identity: c.signing_identity,
+            additional_identities: c.additional_identities,
+            version: c.version,
+        }))
+    }
+
+    /// Set the suffix appended to the label of every signature created or
+    /// verified by the client.
+    ///
+    /// The crypto provider set with [`ClientBuilder::crypto_provider`] is
+    /// wrapped in a [`WithSignatureLabelSuffix`], see its documentation for
+    /// details. This function must therefore be called after setting the
+    /// crypto provider. By default, no suffix is used.
+    #[cfg(feature = "signature_label_suffix")]
+    pub fn signature_label_suffix(
+        self,
+        suffix: Vec<u8>,
+    ) -> ClientBuilder<WithCryptoProvider<WithSignatureLabelSuffix<C::CryptoProvider>, C>>
+    where
+        C::CryptoProvider: CryptoProvider,
+    {
+        let Config(c) = self.0.into_config();
+
+        ClientBuilder(Config(ConfigInner {
+            settings: c.settings,
+            key_package_repo: c.key_package_repo,
+            psk_store: c.psk_store,
+            group_state_storage: c.group_state_storage,
+            identity_provider: c.identity_provider,
+            mls_rules: c.mls_rules,
+            crypto_provider: WithSignatureLabelSuffix::new(c.crypto_provider, suffix),
+            signer: c.signer,
+            signing_identity: c.signing_identity,
+            additional_identities: c.additional_identities,
             version: c.version,
         }))
     }
@@ -497,6 +908,7 @@ impl<C: IntoConfig> ClientBuilder<C> {
             crypto_provider: c.crypto_provider,
             signer: c.signer,
             signing_identity: c.signing_identity,
+            additional_identities: c.additional_identities,
             version: c.version,
         }))
     }
@@ -525,6 +937,35 @@ impl<C: IntoConfig> ClientBuilder<C> {
         ClientBuilder(c)
     }
 
+    /// Add a signing identity, with the matching signer, used by the client
+    /// for groups with the given cipher suite, in addition to the one set
+    /// with [`signing_identity`](ClientBuilder::signing_identity).
+    ///
+    /// A client configured with several cipher suites automatically uses the
+    /// matching identity when joining a group, e.g. with
+    /// [`Client::join_group_validated`], and can create groups and key
+    /// packages for any of them, e.g. with
+    /// [`Client::create_group_with_cipher_suite`].
+    /// Adding an identity for a cipher suite that already has one replaces
+    /// it. The identity set with
+    /// [`signing_identity`](ClientBuilder::signing_identity) takes precedence
+    /// over an additional identity for the same cipher suite, which is
+    /// dropped when the client is built.
+    pub fn additional_signing_identity(
+        self,
+        signing_identity: SigningIdentity,
+        signer: SignatureSecretKey,
+        cipher_suite: CipherSuite,
+    ) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        let identities = &mut c.0.additional_identities;
+
+        identities.retain(|(_, _, cs)| *cs != cipher_suite);
+        identities.push((signing_identity, signer, cipher_suite));
+
+        ClientBuilder(c)
+    }
+
     /// Set the signer used by the client. This must be called in order to join groups.
     pub fn signer(self, signer: SignatureSecretKey) -> ClientBuilder<IntoConfigOutput<C>> {
         let mut c = self.0.into_config();
@@ -571,8 +1012,15 @@ where
         let version = c.0.version;
         let signer = c.0.signer.take();
         let signing_identity = c.0.signing_identity.take();
+        let mut additional_identities = core::mem::take(&mut c.0.additional_identities);
+
+        if let Some((_, cipher_suite)) = &signing_identity {
+            additional_identities.retain(|(_, _, cs)| cs != cipher_suite);
+        }
 
-        Client::new(c, signer, signing_identity, version)
+        let mut client = Client::new(c, signer, signing_identity, version);
+        client.additional_identities = additional_identities;
+        client
     }
 }
 
@@ -741,6 +1189,108 @@ where
         self.settings.leaf_node_extensions.clone()
     }
 
+    fn group_id_policy(&self) -> GroupIdPolicy {
+        self.settings.group_id_policy.clone()
+    }
+
+    fn group_policy_support(&self) -> GroupPolicySupport {
+        self.settings.group_policy_support.clone()
+    }
+
+    fn epoch_age_policy(&self) -> Option<EpochAgePolicy> {
+        self.settings.epoch_age_policy
+    }
+
+    fn reinit_cipher_suite_policy(&self) -> ReInitCipherSuitePolicy {
+        self.settings.reinit_cipher_suite_policy.clone()
+    }
+
+    fn cipher_suite_policy(&self) -> CipherSuitePolicy {
+        self.settings.cipher_suite_policy.clone()
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    fn duplicate_proposal_policy(&self) -> Option<DuplicateProposalPolicy> {
+        self.settings.duplicate_proposal_policy
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    fn proposal_ttl(&self) -> Option<Duration> {
+        self.settings.proposal_ttl
+    }
+
+    fn proposal_compaction(&self) -> Option<ProposalCompaction> {
+        self.settings.proposal_compaction
+    }
+
+    fn init_key_reuse_policy(&self) -> InitKeyReusePolicy {
+        self.settings.init_key_reuse_policy
+    }
+
+    #[cfg(feature = "private_message")]
+    fn encryption_policy(&self) -> EncryptionPolicy {
+        self.settings.encryption_policy
+    }
+
+    #[cfg(feature = "private_message")]
+    fn watermark_persistence(&self) -> WatermarkPersistence {
+        self.settings.watermark_persistence
+    }
+
+    #[cfg(all(feature = "private_message", feature = "prior_epoch"))]
+    fn previous_epoch_send_window(&self) -> Option<Duration> {
+        self.settings.previous_epoch_send_window
+    }
+
+    #[cfg(feature = "out_of_order")]
+    fn max_retained_message_keys(&self) -> Option<usize> {
+        self.settings.max_retained_message_keys
+    }
+
+    #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+    fn max_generation_jump(&self) -> u32 {
+        self.settings.max_generation_jump
+    }
+
+    #[cfg(feature = "compression")]
+    fn compression(&self) -> Option<CompressionSettings> {
+        self.settings.compression.clone()
+    }
+
+    #[cfg(feature = "escrow")]
+    fn escrow_public_key(&self) -> Option<HpkePublicKey> {
+        self.settings.escrow_public_key.clone()
+    }
+
+    fn tree_validator(&self) -> Option<SharedTreeValidator> {
+        self.settings.tree_validator.clone()
+    }
+
+    fn error_renderer(&self) -> Option<SharedErrorRenderer> {
+        self.settings.error_renderer.clone()
+    }
+
+    fn record_transcript_chain(&self) -> bool {
+        self.settings.record_transcript_chain
+    }
+
+    fn record_roster_diffs(&self) -> bool {
+        self.settings.record_roster_diffs
+    }
+
+    fn archive_removed_groups(&self) -> bool {
+        self.settings.archive_removed_groups
+    }
+
+    fn consumed_key_package_grace_period(&self) -> Option<u64> {
+        self.settings.consumed_key_package_grace_period
+    }
+
+    #[cfg(feature = "event_sourcing")]
+    fn epoch_delta_compaction_interval(&self) -> Option<u64> {
+        self.settings.epoch_delta_compaction_interval
+    }
+
     fn lifetime(&self) -> Lifetime {
         #[cfg(feature = "std")]
         let now_timestamp = MlsTime::now().seconds_since_epoch();
@@ -852,6 +1402,108 @@ impl<T: MlsConfig> ClientConfig for T {
         self.get().lifetime()
     }
 
+    fn group_id_policy(&self) -> GroupIdPolicy {
+        self.get().group_id_policy()
+    }
+
+    fn group_policy_support(&self) -> GroupPolicySupport {
+        self.get().group_policy_support()
+    }
+
+    fn epoch_age_policy(&self) -> Option<EpochAgePolicy> {
+        self.get().epoch_age_policy()
+    }
+
+    fn reinit_cipher_suite_policy(&self) -> ReInitCipherSuitePolicy {
+        self.get().reinit_cipher_suite_policy()
+    }
+
+    fn cipher_suite_policy(&self) -> CipherSuitePolicy {
+        self.get().cipher_suite_policy()
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    fn duplicate_proposal_policy(&self) -> Option<DuplicateProposalPolicy> {
+        self.get().duplicate_proposal_policy()
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    fn proposal_ttl(&self) -> Option<Duration> {
+        self.get().proposal_ttl()
+    }
+
+    fn proposal_compaction(&self) -> Option<ProposalCompaction> {
+        self.get().proposal_compaction()
+    }
+
+    fn init_key_reuse_policy(&self) -> InitKeyReusePolicy {
+        self.get().init_key_reuse_policy()
+    }
+
+    #[cfg(feature = "private_message")]
+    fn encryption_policy(&self) -> EncryptionPolicy {
+        self.get().encryption_policy()
+    }
+
+    #[cfg(feature = "private_message")]
+    fn watermark_persistence(&self) -> WatermarkPersistence {
+        self.get().watermark_persistence()
+    }
+
+    #[cfg(all(feature = "private_message", feature = "prior_epoch"))]
+    fn previous_epoch_send_window(&self) -> Option<Duration> {
+        self.get().previous_epoch_send_window()
+    }
+
+    #[cfg(feature = "out_of_order")]
+    fn max_retained_message_keys(&self) -> Option<usize> {
+        self.get().max_retained_message_keys()
+    }
+
+    #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+    fn max_generation_jump(&self) -> u32 {
+        self.get().max_generation_jump()
+    }
+
+    #[cfg(feature = "compression")]
+    fn compression(&self) -> Option<CompressionSettings> {
+        self.get().compression()
+    }
+
+    #[cfg(feature = "escrow")]
+    fn escrow_public_key(&self) -> Option<HpkePublicKey> {
+        self.get().escrow_public_key()
+    }
+
+    fn tree_validator(&self) -> Option<SharedTreeValidator> {
+        self.get().tree_validator()
+    }
+
+    fn error_renderer(&self) -> Option<SharedErrorRenderer> {
+        self.get().error_renderer()
+    }
+
+    fn record_transcript_chain(&self) -> bool {
+        self.get().record_transcript_chain()
+    }
+
+    fn record_roster_diffs(&self) -> bool {
+        self.get().record_roster_diffs()
+    }
+
+    fn archive_removed_groups(&self) -> bool {
+        self.get().archive_removed_groups()
+    }
+
+    fn consumed_key_package_grace_period(&self) -> Option<u64> {
+        self.get().consumed_key_package_grace_period()
+    }
+
+    #[cfg(feature = "event_sourcing")]
+    fn epoch_delta_compaction_interval(&self) -> Option<u64> {
+        self.get().epoch_delta_compaction_interval()
+    }
+
     fn capabilities(&self) -> Capabilities {
         self.get().capabilities()
     }
@@ -873,6 +1525,39 @@ pub(crate) struct Settings {
     pub(crate) key_package_extensions: ExtensionList,
     pub(crate) leaf_node_extensions: ExtensionList,
     pub(crate) lifetime_in_s: u64,
+    pub(crate) group_id_policy: GroupIdPolicy,
+    pub(crate) group_policy_support: GroupPolicySupport,
+    pub(crate) epoch_age_policy: Option<EpochAgePolicy>,
+    pub(crate) reinit_cipher_suite_policy: ReInitCipherSuitePolicy,
+    pub(crate) cipher_suite_policy: CipherSuitePolicy,
+    #[cfg(feature = "by_ref_proposal")]
+    pub(crate) duplicate_proposal_policy: Option<DuplicateProposalPolicy>,
+    #[cfg(feature = "by_ref_proposal")]
+    pub(crate) proposal_ttl: Option<Duration>,
+    pub(crate) proposal_compaction: Option<ProposalCompaction>,
+    pub(crate) init_key_reuse_policy: InitKeyReusePolicy,
+    #[cfg(feature = "private_message")]
+    pub(crate) encryption_policy: EncryptionPolicy,
+    #[cfg(feature = "private_message")]
+    pub(crate) watermark_persistence: WatermarkPersistence,
+    #[cfg(all(feature = "private_message", feature = "prior_epoch"))]
+    pub(crate) previous_epoch_send_window: Option<Duration>,
+    #[cfg(feature = "out_of_order")]
+    pub(crate) max_retained_message_keys: Option<usize>,
+    #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+    pub(crate) max_generation_jump: u32,
+    #[cfg(feature = "compression")]
+    pub(crate) compression: Option<CompressionSettings>,
+    #[cfg(feature = "escrow")]
+    pub(crate) escrow_public_key: Option<HpkePublicKey>,
+    pub(crate) tree_validator: Option<SharedTreeValidator>,
+    pub(crate) error_renderer: Option<SharedErrorRenderer>,
+    pub(crate) record_transcript_chain: bool,
+    pub(crate) record_roster_diffs: bool,
+    pub(crate) archive_removed_groups: bool,
+    pub(crate) consumed_key_package_grace_period: Option<u64>,
+    #[cfg(feature = "event_sourcing")]
+    pub(crate) epoch_delta_compaction_interval: Option<u64>,
     #[cfg(any(test, feature = "test_util"))]
     pub(crate) key_package_not_before: Option<u64>,
 }
@@ -886,6 +1571,39 @@ impl Default for Settings {
             leaf_node_extensions: Default::default(),
             lifetime_in_s: 365 * 24 * 3600,
             custom_proposal_types: Default::default(),
+            group_id_policy: Default::default(),
+            group_policy_support: Default::default(),
+            epoch_age_policy: None,
+            reinit_cipher_suite_policy: Default::default(),
+            cipher_suite_policy: Default::default(),
+            #[cfg(feature = "by_ref_proposal")]
+            duplicate_proposal_policy: None,
+            #[cfg(feature = "by_ref_proposal")]
+            proposal_ttl: None,
+            proposal_compaction: None,
+            init_key_reuse_policy: Default::default(),
+            #[cfg(feature = "private_message")]
+            encryption_policy: Default::default(),
+            #[cfg(feature = "private_message")]
+            watermark_persistence: Default::default(),
+            #[cfg(all(feature = "private_message", feature = "prior_epoch"))]
+            previous_epoch_send_window: None,
+            #[cfg(feature = "out_of_order")]
+            max_retained_message_keys: None,
+            #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+            max_generation_jump: MAX_RATCHET_BACK_HISTORY,
+            #[cfg(feature = "compression")]
+            compression: None,
+            #[cfg(feature = "escrow")]
+            escrow_public_key: None,
+            tree_validator: None,
+            error_renderer: None,
+            record_transcript_chain: false,
+            record_roster_diffs: false,
+            archive_removed_groups: false,
+            consumed_key_package_grace_period: None,
+            #[cfg(feature = "event_sourcing")]
+            epoch_delta_compaction_interval: None,
             #[cfg(any(test, feature = "test_util"))]
             key_package_not_before: None,
         }
@@ -896,6 +1614,7 @@ pub(crate) fn recreate_config<T: ClientConfig>(
     c: T,
     signer: Option<SignatureSecretKey>,
     signing_identity: Option<(SigningIdentity, CipherSuite)>,
+    additional_identities: Vec<(SigningIdentity, SignatureSecretKey, CipherSuite)>,
     version: ProtocolVersion,
 ) -> MakeConfig<T> {
     Config(ConfigInner {
@@ -909,6 +1628,39 @@ pub(crate) fn recreate_config<T: ClientConfig>(
                 let l = c.lifetime();
                 l.not_after - l.not_before
             },
+            group_id_policy: c.group_id_policy(),
+            group_policy_support: c.group_policy_support(),
+            epoch_age_policy: c.epoch_age_policy(),
+            reinit_cipher_suite_policy: c.reinit_cipher_suite_policy(),
+            cipher_suite_policy: c.cipher_suite_policy(),
+            #[cfg(feature = "by_ref_proposal")]
+            duplicate_proposal_policy: c.duplicate_proposal_policy(),
+            #[cfg(feature = "by_ref_proposal")]
+            proposal_ttl: c.proposal_ttl(),
+            proposal_compaction: c.proposal_compaction(),
+            init_key_reuse_policy: c.init_key_reuse_policy(),
+            #[cfg(feature = "private_message")]
+            encryption_policy: c.encryption_policy(),
+            #[cfg(feature = "private_message")]
+            watermark_persistence: c.watermark_persistence(),
+            #[cfg(all(feature = "private_message", feature = "prior_epoch"))]
+            previous_epoch_send_window: c.previous_epoch_send_window(),
+            #[cfg(feature = "out_of_order")]
+            max_retained_message_keys: c.max_retained_message_keys(),
+            #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+            max_generation_jump: c.max_generation_jump(),
+            #[cfg(feature = "compression")]
+            compression: c.compression(),
+            #[cfg(feature = "escrow")]
+            escrow_public_key: c.escrow_public_key(),
+            tree_validator: c.tree_validator(),
+            error_renderer: c.error_renderer(),
+            record_transcript_chain: c.record_transcript_chain(),
+            record_roster_diffs: c.record_roster_diffs(),
+            archive_removed_groups: c.archive_removed_groups(),
+            consumed_key_package_grace_period: c.consumed_key_package_grace_period(),
+            #[cfg(feature = "event_sourcing")]
+            epoch_delta_compaction_interval: c.epoch_delta_compaction_interval(),
             #[cfg(any(test, feature = "test_util"))]
             key_package_not_before: None,
         },
@@ -920,6 +1672,7 @@ pub(crate) fn recreate_config<T: ClientConfig>(
         crypto_provider: c.crypto_provider(),
         signer,
         signing_identity,
+        additional_identities,
         version,
     })
 }
@@ -927,6 +1680,7 @@ pub(crate) fn recreate_config<T: ClientConfig>(
 /// Definitions meant to be private that are inaccessible outside this crate. They need to be marked
 /// `pub` because they appear in public definitions.
 mod private {
+    use alloc::vec::Vec;
     use mls_rs_core::{
         crypto::{CipherSuite, SignatureSecretKey},
         identity::SigningIdentity,
@@ -949,6 +1703,7 @@ mod private {
         pub(crate) crypto_provider: Cp,
         pub(crate) signer: Option<SignatureSecretKey>,
         pub(crate) signing_identity: Option<(SigningIdentity, CipherSuite)>,
+        pub(crate) additional_identities: Vec<(SigningIdentity, SignatureSecretKey, CipherSuite)>,
         pub(crate) version: ProtocolVersion,
     }
 
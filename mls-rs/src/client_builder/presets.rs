@@ -0,0 +1,206 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::group::mls_rules::{CommitOptions, CommitPathPolicy, DefaultMlsRules};
+
+#[cfg(feature = "private_message")]
+use crate::group::{
+    mls_rules::{EncryptionOptions, EncryptionPolicy},
+    padding::PaddingMode,
+    WatermarkPersistence,
+};
+
+#[cfg(feature = "by_ref_proposal")]
+use crate::group::DuplicateProposalPolicy;
+
+use super::{ClientBuilder, IntoConfig, Settings, WithMlsRules};
+
+/// Number of message keys retained per sender and ratchet by
+/// [`ClientPreset::MobileConstrained`].
+#[cfg(feature = "out_of_order")]
+const MOBILE_MAX_RETAINED_MESSAGE_KEYS: usize = 32;
+
+/// Bundle of settings for a common kind of deployment, applied with
+/// [`ClientBuilder::preset`].
+///
+/// A preset replaces the [`MlsRules`](crate::MlsRules) of the builder with
+/// [`DefaultMlsRules`] and sets the policies listed for each variant. Any
+/// setting can be overridden by calling the corresponding builder function
+/// after applying the preset.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ClientPreset {
+    /// Validate strictly and favor security over message size:
+    ///
+    /// * every commit includes an update path, and commits without one are
+    ///   rejected,
+    /// * proposals and commits are encrypted and padded, and unencrypted
+    ///   ones from members are rejected,
+    /// * commits carrying several proposals of the same kind from one sender
+    ///   are rejected,
+    /// * the state is persisted after each message, see
+    ///   [`WatermarkPersistence::AfterProcessing`].
+    StrictRfc,
+    /// Accept everything RFC 9420 allows, to interoperate with other
+    /// implementations:
+    ///
+    /// * received commits are accepted with or without an optional path,
+    /// * proposals and commits are sent unencrypted, and both encrypted and
+    ///   unencrypted ones are accepted,
+    /// * external commits are allowed.
+    InteropPermissive,
+    /// Keep messages, memory and storage small on constrained devices:
+    ///
+    /// * update paths are only included when required,
+    /// * messages are not padded,
+    /// * at most 32 message keys are retained per sender for out of order
+    ///   messages,
+    /// * the state is persisted after each message, since the application
+    ///   may be terminated at any time.
+    MobileConstrained,
+}
+
+impl ClientPreset {
+    fn mls_rules(self) -> DefaultMlsRules {
+        let commit_options = match self {
+            Self::StrictRfc => CommitOptions::new()
+                .with_path_policy(CommitPathPolicy::ForcePath)
+                .with_received_path_policy(CommitPathPolicy::ForcePath),
+            Self::InteropPermissive => CommitOptions::new().with_allow_external_commit(true),
+            Self::MobileConstrained => CommitOptions::new(),
+        };
+
+        #[cfg(feature = "private_message")]
+        let encryption_options = match self {
+            Self::StrictRfc => EncryptionOptions::new(true, PaddingMode::StepFunction),
+            Self::InteropPermissive => EncryptionOptions::new(false, PaddingMode::StepFunction),
+            Self::MobileConstrained => EncryptionOptions::new(false, PaddingMode::None),
+        };
+
+        #[cfg(not(feature = "private_message"))]
+        let encryption_options = Default::default();
+
+        DefaultMlsRules::new()
+            .with_commit_options(commit_options)
+            .with_encryption_options(encryption_options)
+    }
+
+    fn apply(self, settings: &mut Settings) {
+        #[cfg(feature = "by_ref_proposal")]
+        {
+            settings.duplicate_proposal_policy = match self {
+                Self::StrictRfc => Some(DuplicateProposalPolicy::Reject),
+                Self::InteropPermissive | Self::MobileConstrained => None,
+            };
+        }
+
+        #[cfg(feature = "private_message")]
+        {
+            settings.encryption_policy = EncryptionPolicy::new(self == Self::StrictRfc);
+
+            settings.watermark_persistence = match self {
+                Self::StrictRfc | Self::MobileConstrained => WatermarkPersistence::AfterProcessing,
+                Self::InteropPermissive => WatermarkPersistence::Manual,
+            };
+        }
+
+        #[cfg(feature = "out_of_order")]
+        {
+            settings.max_retained_message_keys = match self {
+                Self::MobileConstrained => Some(MOBILE_MAX_RETAINED_MESSAGE_KEYS),
+                Self::StrictRfc | Self::InteropPermissive => None,
+            };
+        }
+
+        #[cfg(not(any(
+            feature = "by_ref_proposal",
+            feature = "private_message",
+            feature = "out_of_order"
+        )))]
+        let _ = settings;
+    }
+}
+
+impl<C: IntoConfig> ClientBuilder<C> {
+    /// Apply `preset`, see [`ClientPreset`].
+    pub fn preset(self, preset: ClientPreset) -> ClientBuilder<WithMlsRules<DefaultMlsRules, C>> {
+        let ClientBuilder(mut c) = self.mls_rules(preset.mls_rules());
+        preset.apply(&mut c.0.settings);
+        ClientBuilder(c)
+    }
+
+    /// Apply [`ClientPreset::StrictRfc`].
+    pub fn strict_rfc(self) -> ClientBuilder<WithMlsRules<DefaultMlsRules, C>> {
+        self.preset(ClientPreset::StrictRfc)
+    }
+
+    /// Apply [`ClientPreset::InteropPermissive`].
+    pub fn interop_permissive(self) -> ClientBuilder<WithMlsRules<DefaultMlsRules, C>> {
+        self.preset(ClientPreset::InteropPermissive)
+    }
+
+    /// Apply [`ClientPreset::MobileConstrained`].
+    pub fn mobile_constrained(self) -> ClientBuilder<WithMlsRules<DefaultMlsRules, C>> {
+        self.preset(ClientPreset::MobileConstrained)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::ClientPreset;
+
+    use crate::{
+        client::test_utils::{TestClientBuilder, TEST_CIPHER_SUITE},
+        client_config::ClientConfig,
+        group::mls_rules::CommitPathPolicy,
+    };
+
+    #[test]
+    fn presets_can_be_overridden() {
+        let client = TestClientBuilder::new_for_test()
+            .strict_rfc()
+            .key_package_lifetime(60)
+            .build();
+
+        let rules = client.config.mls_rules();
+
+        assert_eq!(
+            rules.commit_options.path_policy,
+            CommitPathPolicy::ForcePath
+        );
+
+        assert_eq!(
+            rules.commit_options.received_path_policy,
+            CommitPathPolicy::ForcePath
+        );
+
+        #[cfg(feature = "private_message")]
+        assert!(client.config.encryption_policy().encrypt_handshake);
+
+        let lifetime = client.config.lifetime();
+        assert_eq!(lifetime.not_after - lifetime.not_before, 60);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn clients_with_presets_can_commit() {
+        for preset in [
+            ClientPreset::StrictRfc,
+            ClientPreset::InteropPermissive,
+            ClientPreset::MobileConstrained,
+        ] {
+            let client = TestClientBuilder::new_for_test()
+                .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+                .await
+                .preset(preset)
+                .build();
+
+            let mut group = client.create_group(Default::default()).await.unwrap();
+
+            group.commit(vec![]).await.unwrap();
+            group.apply_pending_commit().await.unwrap();
+        }
+    }
+}
@@ -4,13 +4,32 @@
 
 use crate::{
     extension::ExtensionType,
-    group::{mls_rules::MlsRules, proposal::ProposalType},
+    group::{
+        mls_rules::MlsRules, proposal::ProposalType, CipherSuitePolicy, EpochAgePolicy,
+        GroupIdPolicy, GroupPolicySupport, InitKeyReusePolicy, ProposalCompaction,
+        ReInitCipherSuitePolicy,
+    },
     identity::CredentialType,
     protocol_version::ProtocolVersion,
     tree_kem::{leaf_node::ConfigProperties, Capabilities, Lifetime},
-    ExtensionList,
+    CrateCapabilities, ExtensionList,
 };
 use alloc::vec::Vec;
+
+#[cfg(feature = "private_message")]
+use crate::group::{mls_rules::EncryptionPolicy, WatermarkPersistence};
+
+#[cfg(feature = "by_ref_proposal")]
+use crate::group::DuplicateProposalPolicy;
+
+#[cfg(feature = "compression")]
+use crate::group::CompressionSettings;
+use crate::group::SharedTreeValidator;
+
+use crate::error_render::SharedErrorRenderer;
+
+#[cfg(feature = "escrow")]
+use mls_rs_core::crypto::HpkePublicKey;
 use mls_rs_core::{
     crypto::CryptoProvider, group::GroupStateStorage, identity::IdentityProvider,
     key_package::KeyPackageStorage, psk::PreSharedKeyStorage,
@@ -40,15 +59,67 @@ pub trait ClientConfig: Send + Sync + Clone {
     fn key_package_extensions(&self) -> ExtensionList;
     fn leaf_node_extensions(&self) -> ExtensionList;
     fn lifetime(&self) -> Lifetime;
+    fn group_id_policy(&self) -> GroupIdPolicy;
+    fn group_policy_support(&self) -> GroupPolicySupport;
+    fn epoch_age_policy(&self) -> Option<EpochAgePolicy>;
+    fn reinit_cipher_suite_policy(&self) -> ReInitCipherSuitePolicy;
+
+    fn cipher_suite_policy(&self) -> CipherSuitePolicy;
+
+    #[cfg(feature = "by_ref_proposal")]
+    fn duplicate_proposal_policy(&self) -> Option<DuplicateProposalPolicy>;
+
+    #[cfg(feature = "by_ref_proposal")]
+    fn proposal_ttl(&self) -> Option<core::time::Duration>;
+
+    fn proposal_compaction(&self) -> Option<ProposalCompaction>;
+
+    fn init_key_reuse_policy(&self) -> InitKeyReusePolicy;
+
+    #[cfg(feature = "private_message")]
+    fn encryption_policy(&self) -> EncryptionPolicy;
+
+    #[cfg(feature = "private_message")]
+    fn watermark_persistence(&self) -> WatermarkPersistence;
+
+    #[cfg(all(feature = "private_message", feature = "prior_epoch"))]
+    fn previous_epoch_send_window(&self) -> Option<core::time::Duration>;
+
+    #[cfg(feature = "out_of_order")]
+    fn max_retained_message_keys(&self) -> Option<usize>;
+
+    #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+    fn max_generation_jump(&self) -> u32;
+
+    #[cfg(feature = "compression")]
+    fn compression(&self) -> Option<CompressionSettings>;
+
+    #[cfg(feature = "escrow")]
+    fn escrow_public_key(&self) -> Option<HpkePublicKey>;
+
+    fn tree_validator(&self) -> Option<SharedTreeValidator>;
+
+    fn error_renderer(&self) -> Option<SharedErrorRenderer>;
+
+    fn record_transcript_chain(&self) -> bool;
+
+    fn record_roster_diffs(&self) -> bool;
+
+    fn archive_removed_groups(&self) -> bool;
+
+    fn consumed_key_package_grace_period(&self) -> Option<u64>;
+
+    #[cfg(feature = "event_sourcing")]
+    fn epoch_delta_compaction_interval(&self) -> Option<u64>;
 
     fn capabilities(&self) -> Capabilities {
-        Capabilities {
+        CrateCapabilities::current().restrict(Capabilities {
             protocol_versions: self.supported_protocol_versions(),
             cipher_suites: self.crypto_provider().supported_cipher_suites(),
             extensions: self.supported_extensions(),
             proposals: self.supported_custom_proposals(),
             credentials: self.supported_credential_types(),
-        }
+        })
     }
 
     fn version_supported(&self, version: ProtocolVersion) -> bool {
@@ -60,9 +131,24 @@ pub trait ClientConfig: Send + Sync + Clone {
     }
 
     fn leaf_properties(&self) -> ConfigProperties {
+        self.leaf_properties_with_extensions(self.leaf_node_extensions())
+    }
+
+    /// Leaf properties with the given leaf node `extensions`. Non-default
+    /// extension types missing from the capabilities are advertised
+    /// automatically.
+    fn leaf_properties_with_extensions(&self, extensions: ExtensionList) -> ConfigProperties {
+        let mut capabilities = self.capabilities();
+
+        for ext_type in extensions.iter().map(|ext| ext.extension_type) {
+            if !ext_type.is_default() && !capabilities.extensions.contains(&ext_type) {
+                capabilities.extensions.push(ext_type);
+            }
+        }
+
         ConfigProperties {
-            capabilities: self.capabilities(),
-            extensions: self.leaf_node_extensions(),
+            capabilities,
+            extensions,
         }
     }
 }
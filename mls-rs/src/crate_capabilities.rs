@@ -0,0 +1,135 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use mls_rs_core::{
+    group::{Capabilities, ProposalType},
+    identity::CredentialType,
+};
+
+// `CredentialType::X509` is only defined if the x509 feature is enabled.
+const X509_CREDENTIAL_TYPE: CredentialType = CredentialType::new(2);
+
+/// Optional protocol features this build of the crate is able to process.
+///
+/// The values are fixed at compile time by the cargo features the crate was
+/// built with. Clients consult them when advertising their
+/// [`Capabilities`] in key packages and leaf nodes, so that other members
+/// never send proposals or credentials this build would reject.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CrateCapabilities {
+    /// Pre-shared key proposals can be processed (`psk` feature).
+    pub psk: bool,
+    /// External commits can be created and processed. They are always
+    /// supported.
+    pub external_commit: bool,
+    /// Proposals by reference, including Update proposals and proposals from
+    /// external senders, can be processed (`by_ref_proposal` feature).
+    pub external_proposal: bool,
+    /// Custom proposal types can be processed (`custom_proposal` feature).
+    pub custom_proposal: bool,
+    /// X.509 credentials can be processed (`x509` feature).
+    pub x509: bool,
+    /// Exporter secrets can be escrowed to an auditor (`escrow` feature).
+    /// This has no effect on the capabilities advertised to other members.
+    pub escrow: bool,
+}
+
+impl CrateCapabilities {
+    /// Capabilities of the current build.
+    pub const fn current() -> Self {
+        Self {
+            psk: cfg!(feature = "psk"),
+            external_commit: true,
+            external_proposal: cfg!(feature = "by_ref_proposal"),
+            custom_proposal: cfg!(feature = "custom_proposal"),
+            x509: cfg!(feature = "x509"),
+            escrow: cfg!(feature = "escrow"),
+        }
+    }
+
+    /// Determines if proposals of type `proposal_type` can be processed.
+    pub fn supports_proposal(&self, proposal_type: ProposalType) -> bool {
+        match proposal_type {
+            ProposalType::PSK => self.psk,
+            ProposalType::UPDATE => self.external_proposal,
+            ProposalType::EXTERNAL_INIT => self.external_commit,
+            t => ProposalType::DEFAULT.contains(&t) || self.custom_proposal,
+        }
+    }
+
+    /// Determines if credentials of type `credential_type` can be processed.
+    pub fn supports_credential(&self, credential_type: CredentialType) -> bool {
+        credential_type != X509_CREDENTIAL_TYPE || self.x509
+    }
+
+    /// Remove the proposal and credential types this build can't process
+    /// from `capabilities`.
+    pub fn restrict(&self, mut capabilities: Capabilities) -> Capabilities {
+        capabilities
+            .proposals
+            .retain(|&p| self.supports_proposal(p));
+
+        capabilities
+            .credentials
+            .retain(|&c| self.supports_credential(c));
+
+        capabilities
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use mls_rs_core::{
+        group::{Capabilities, ProposalType},
+        identity::CredentialType,
+    };
+
+    use super::{CrateCapabilities, X509_CREDENTIAL_TYPE};
+
+    #[test]
+    fn unsupported_types_are_removed() {
+        let build = CrateCapabilities {
+            custom_proposal: false,
+            x509: false,
+            ..CrateCapabilities::current()
+        };
+
+        let capabilities = Capabilities {
+            proposals: vec![ProposalType::new(65000)],
+            credentials: vec![CredentialType::BASIC, X509_CREDENTIAL_TYPE],
+            ..Default::default()
+        };
+
+        let restricted = build.restrict(capabilities);
+
+        assert!(restricted.proposals.is_empty());
+        assert_eq!(restricted.credentials, vec![CredentialType::BASIC]);
+
+        assert!(build.supports_proposal(ProposalType::ADD));
+        assert!(!build.supports_proposal(ProposalType::new(65000)));
+    }
+
+    #[cfg(feature = "custom_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn advertised_capabilities_match_build() {
+        use crate::{
+            client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            group::test_utils::test_group_custom_config,
+        };
+
+        let custom = ProposalType::new(65000);
+
+        let group = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.custom_proposal_type(custom)
+        })
+        .await;
+
+        let member = group.group.member_at_index(0).unwrap();
+
+        assert!(member.capabilities.proposals.contains(&custom));
+    }
+}
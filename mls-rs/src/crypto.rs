@@ -11,6 +11,16 @@ pub use mls_rs_core::crypto::{
 
 pub use mls_rs_core::secret::Secret;
 
+mod random;
+
+pub use random::{RandomSource, RandomSourceCipherSuite, RandomSourceError, WithRandomSource};
+
+#[cfg(feature = "signature_label_suffix")]
+mod label_suffix;
+
+#[cfg(feature = "signature_label_suffix")]
+pub use label_suffix::{SignatureLabelSuffixCipherSuite, WithSignatureLabelSuffix};
+
 #[cfg(test)]
 pub(crate) mod test_utils {
     use cfg_if::cfg_if;
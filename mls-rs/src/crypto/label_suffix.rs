@@ -0,0 +1,336 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use mls_rs_core::crypto::{
+    CipherSuite, CipherSuiteProvider, CryptoProvider, HpkeCiphertext, HpkePublicKey, HpkeSecretKey,
+    SignaturePublicKey, SignatureSecretKey,
+};
+use zeroize::Zeroizing;
+
+/// [`CryptoProvider`] that appends a suffix to the label of every signature
+/// created or verified by MLS.
+///
+/// The suffix separates the signatures of a closed ecosystem from those of
+/// any other MLS deployment, so that signed structs can not be replayed
+/// across deployments. Signatures created with a suffix are rejected by
+/// clients using a different suffix or none, so all clients of a deployment
+/// must use the same suffix.
+#[derive(Clone, Debug)]
+pub struct WithSignatureLabelSuffix<P> {
+    crypto_provider: P,
+    suffix: Vec<u8>,
+}
+
+impl<P> WithSignatureLabelSuffix<P>
+where
+    P: CryptoProvider,
+{
+    /// Append `suffix` to the signature labels used with `crypto_provider`.
+    pub fn new(crypto_provider: P, suffix: Vec<u8>) -> Self {
+        Self {
+            crypto_provider,
+            suffix,
+        }
+    }
+
+    /// The wrapped crypto provider.
+    pub fn crypto_provider(&self) -> &P {
+        &self.crypto_provider
+    }
+
+    /// The suffix appended to signature labels.
+    pub fn suffix(&self) -> &[u8] {
+        &self.suffix
+    }
+}
+
+impl<P> CryptoProvider for WithSignatureLabelSuffix<P>
+where
+    P: CryptoProvider,
+{
+    type CipherSuiteProvider = SignatureLabelSuffixCipherSuite<P::CipherSuiteProvider>;
+
+    fn supported_cipher_suites(&self) -> Vec<CipherSuite> {
+        self.crypto_provider.supported_cipher_suites()
+    }
+
+    fn cipher_suite_provider(
+        &self,
+        cipher_suite: CipherSuite,
+    ) -> Option<Self::CipherSuiteProvider> {
+        Some(SignatureLabelSuffixCipherSuite {
+            inner: self.crypto_provider.cipher_suite_provider(cipher_suite)?,
+            suffix: self.suffix.clone(),
+        })
+    }
+}
+
+/// [`CipherSuiteProvider`] returned by [`WithSignatureLabelSuffix`].
+#[derive(Clone, Debug)]
+pub struct SignatureLabelSuffixCipherSuite<C> {
+    inner: C,
+    suffix: Vec<u8>,
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(all(target_arch = "wasm32", mls_build_async), maybe_async::must_be_async(?Send))]
+#[cfg_attr(
+    all(not(target_arch = "wasm32"), mls_build_async),
+    maybe_async::must_be_async
+)]
+impl<C> CipherSuiteProvider for SignatureLabelSuffixCipherSuite<C>
+where
+    C: CipherSuiteProvider,
+{
+    type Error = C::Error;
+
+    type HpkeContextS = C::HpkeContextS;
+    type HpkeContextR = C::HpkeContextR;
+
+    fn cipher_suite(&self) -> CipherSuite {
+        self.inner.cipher_suite()
+    }
+
+    async fn hash(&self, data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        self.inner.hash(data).await
+    }
+
+    async fn mac(&self, key: &[u8], data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        self.inner.mac(key, data).await
+    }
+
+    async fn aead_seal(
+        &self,
+        key: &[u8],
+        data: &[u8],
+        aad: Option<&[u8]>,
+        nonce: &[u8],
+    ) -> Result<Vec<u8>, Self::Error> {
+        self.inner.aead_seal(key, data, aad, nonce).await
+    }
+
+    async fn aead_open(
+        &self,
+        key: &[u8],
+        ciphertext: &[u8],
+        aad: Option<&[u8]>,
+        nonce: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>, Self::Error> {
+        self.inner.aead_open(key, ciphertext, aad, nonce).await
+    }
+
+    fn aead_key_size(&self) -> usize {
+        self.inner.aead_key_size()
+    }
+
+    fn aead_nonce_size(&self) -> usize {
+        self.inner.aead_nonce_size()
+    }
+
+    async fn kdf_extract(
+        &self,
+        salt: &[u8],
+        ikm: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>, Self::Error> {
+        self.inner.kdf_extract(salt, ikm).await
+    }
+
+    async fn kdf_expand(
+        &self,
+        prk: &[u8],
+        info: &[u8],
+        len: usize,
+    ) -> Result<Zeroizing<Vec<u8>>, Self::Error> {
+        self.inner.kdf_expand(prk, info, len).await
+    }
+
+    fn kdf_extract_size(&self) -> usize {
+        self.inner.kdf_extract_size()
+    }
+
+    async fn hpke_seal(
+        &self,
+        remote_key: &HpkePublicKey,
+        info: &[u8],
+        aad: Option<&[u8]>,
+        pt: &[u8],
+    ) -> Result<HpkeCiphertext, Self::Error> {
+        self.inner.hpke_seal(remote_key, info, aad, pt).await
+    }
+
+    async fn hpke_open(
+        &self,
+        ciphertext: &HpkeCiphertext,
+        local_secret: &HpkeSecretKey,
+        local_public: &HpkePublicKey,
+        info: &[u8],
+        aad: Option<&[u8]>,
+    ) -> Result<Vec<u8>, Self::Error> {
+        self.inner
+            .hpke_open(ciphertext, local_secret, local_public, info, aad)
+            .await
+    }
+
+    async fn hpke_setup_s(
+        &self,
+        remote_key: &HpkePublicKey,
+        info: &[u8],
+    ) -> Result<(Vec<u8>, Self::HpkeContextS), Self::Error> {
+        self.inner.hpke_setup_s(remote_key, info).await
+    }
+
+    async fn hpke_setup_r(
+        &self,
+        kem_output: &[u8],
+        local_secret: &HpkeSecretKey,
+        local_public: &HpkePublicKey,
+        info: &[u8],
+    ) -> Result<Self::HpkeContextR, Self::Error> {
+        self.inner
+            .hpke_setup_r(kem_output, local_secret, local_public, info)
+            .await
+    }
+
+    async fn kem_derive(&self, ikm: &[u8]) -> Result<(HpkeSecretKey, HpkePublicKey), Self::Error> {
+        self.inner.kem_derive(ikm).await
+    }
+
+    async fn kem_generate(&self) -> Result<(HpkeSecretKey, HpkePublicKey), Self::Error> {
+        self.inner.kem_generate().await
+    }
+
+    fn kem_public_key_validate(&self, key: &HpkePublicKey) -> Result<(), Self::Error> {
+        self.inner.kem_public_key_validate(key)
+    }
+
+    fn random_bytes(&self, out: &mut [u8]) -> Result<(), Self::Error> {
+        self.inner.random_bytes(out)
+    }
+
+    async fn signature_key_generate(
+        &self,
+    ) -> Result<(SignatureSecretKey, SignaturePublicKey), Self::Error> {
+        self.inner.signature_key_generate().await
+    }
+
+    async fn signature_key_derive_public(
+        &self,
+        secret_key: &SignatureSecretKey,
+    ) -> Result<SignaturePublicKey, Self::Error> {
+        self.inner.signature_key_derive_public(secret_key).await
+    }
+
+    async fn sign(
+        &self,
+        secret_key: &SignatureSecretKey,
+        data: &[u8],
+    ) -> Result<Vec<u8>, Self::Error> {
+        self.inner.sign(secret_key, data).await
+    }
+
+    async fn verify(
+        &self,
+        public_key: &SignaturePublicKey,
+        signature: &[u8],
+        data: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.inner.verify(public_key, signature, data).await
+    }
+
+    fn signature_label_suffix(&self) -> &[u8] {
+        &self.suffix
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use super::WithSignatureLabelSuffix;
+
+    use crate::{
+        client::{test_utils::TEST_CIPHER_SUITE, MlsError},
+        client_builder::{
+            test_utils::{TestClientBuilder, TestClientConfig},
+            WithCryptoProvider,
+        },
+        crypto::test_utils::TestCryptoProvider,
+        group::Validated,
+        identity::test_utils::get_test_signing_identity,
+        Client,
+    };
+
+    type SuffixConfig =
+        WithCryptoProvider<WithSignatureLabelSuffix<TestCryptoProvider>, TestClientConfig>;
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn test_client(name: &str, suffix: &[u8]) -> Client<SuffixConfig> {
+        let (identity, secret_key) =
+            get_test_signing_identity(TEST_CIPHER_SUITE, name.as_bytes()).await;
+
+        TestClientBuilder::new_for_test()
+            .signature_label_suffix(suffix.to_vec())
+            .signing_identity(identity, secret_key, TEST_CIPHER_SUITE)
+            .build()
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn clients_with_same_suffix_interoperate() {
+        let alice = test_client("alice", b"deployment").await;
+        let bob = test_client("bob", b"deployment").await;
+
+        let mut group = alice.create_group(Default::default()).await.unwrap();
+        let key_package = bob.generate_key_package_message().await.unwrap();
+
+        let welcome = group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap()
+            .welcome_messages
+            .remove(0);
+
+        group.apply_pending_commit().await.unwrap();
+
+        bob.join_group_validated(None, &Validated::new(welcome.clone()).unwrap())
+            .await
+            .unwrap();
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn key_package_with_other_suffix_is_rejected() {
+        let alice = test_client("alice", b"deployment").await;
+        let bob = test_client("bob", b"other deployment").await;
+
+        let mut group = alice.create_group(Default::default()).await.unwrap();
+        let key_package = bob.generate_key_package_message().await.unwrap();
+
+        let res = group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::InvalidSignature));
+    }
+
+    #[test]
+    fn suffix_is_reported_by_cipher_suite_provider() {
+        use mls_rs_core::crypto::{CipherSuiteProvider, CryptoProvider};
+
+        let cs = WithSignatureLabelSuffix::new(TestCryptoProvider::new(), vec![1, 2, 3])
+            .cipher_suite_provider(TEST_CIPHER_SUITE)
+            .unwrap();
+
+        assert_eq!(cs.signature_label_suffix(), &[1, 2, 3]);
+    }
+}
@@ -0,0 +1,416 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use mls_rs_core::{
+    crypto::{
+        CipherSuite, CipherSuiteProvider, CryptoProvider, HpkeCiphertext, HpkePublicKey,
+        HpkeSecretKey, SignaturePublicKey, SignatureSecretKey,
+    },
+    error::{AnyError, IntoAnyError},
+};
+use zeroize::Zeroizing;
+
+/// Source of entropy used instead of the random number generator of a
+/// [`CryptoProvider`].
+///
+/// This is intended for environments where the default source of the crypto
+/// provider is not available, such as WebAssembly embeddings without a
+/// working `getrandom`, or for deterministic replay in tests. The source must
+/// be cryptographically secure for any other use.
+pub trait RandomSource: Send + Sync {
+    /// Error type that this source returns if no entropy is available.
+    type Error: IntoAnyError;
+
+    /// Fill `out` with random bytes.
+    fn fill_bytes(&self, out: &mut [u8]) -> Result<(), Self::Error>;
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+/// Error returned by a [`RandomSourceCipherSuite`].
+pub enum RandomSourceError {
+    #[cfg_attr(feature = "std", error(transparent))]
+    CryptoError(AnyError),
+    #[cfg_attr(feature = "std", error("random source failed: {0}"))]
+    RandomSourceError(AnyError),
+}
+
+impl IntoAnyError for RandomSourceError {
+    #[cfg(feature = "std")]
+    fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+        Ok(self.into())
+    }
+}
+
+fn crypto_error<E: IntoAnyError>(e: E) -> RandomSourceError {
+    RandomSourceError::CryptoError(e.into_any_error())
+}
+
+/// [`CryptoProvider`] that draws all randomness needed by MLS from a
+/// [`RandomSource`].
+///
+/// The random source is used for [`CipherSuiteProvider::random_bytes`], which
+/// generates path secrets, reuse guards, group IDs and other secrets, and for
+/// [`CipherSuiteProvider::kem_generate`], which is implemented by deriving
+/// the key pair from random bytes. HPKE encryption and signature key
+/// generation still use the random number generator of the wrapped provider.
+#[derive(Clone, Debug)]
+pub struct WithRandomSource<P, R> {
+    crypto_provider: P,
+    random_source: R,
+}
+
+impl<P, R> WithRandomSource<P, R>
+where
+    P: CryptoProvider,
+    R: RandomSource + Clone,
+{
+    /// Use `random_source` as the source of randomness for `crypto_provider`.
+    pub fn new(crypto_provider: P, random_source: R) -> Self {
+        Self {
+            crypto_provider,
+            random_source,
+        }
+    }
+
+    /// The wrapped crypto provider.
+    pub fn crypto_provider(&self) -> &P {
+        &self.crypto_provider
+    }
+}
+
+impl<P, R> CryptoProvider for WithRandomSource<P, R>
+where
+    P: CryptoProvider,
+    R: RandomSource + Clone,
+{
+    type CipherSuiteProvider = RandomSourceCipherSuite<P::CipherSuiteProvider, R>;
+
+    fn supported_cipher_suites(&self) -> Vec<CipherSuite> {
+        self.crypto_provider.supported_cipher_suites()
+    }
+
+    fn cipher_suite_provider(
+        &self,
+        cipher_suite: CipherSuite,
+    ) -> Option<Self::CipherSuiteProvider> {
+        Some(RandomSourceCipherSuite {
+            inner: self.crypto_provider.cipher_suite_provider(cipher_suite)?,
+            random_source: self.random_source.clone(),
+        })
+    }
+}
+
+/// [`CipherSuiteProvider`] returned by [`WithRandomSource`].
+#[derive(Clone, Debug)]
+pub struct RandomSourceCipherSuite<C, R> {
+    inner: C,
+    random_source: R,
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(all(target_arch = "wasm32", mls_build_async), maybe_async::must_be_async(?Send))]
+#[cfg_attr(
+    all(not(target_arch = "wasm32"), mls_build_async),
+    maybe_async::must_be_async
+)]
+impl<C, R> CipherSuiteProvider for RandomSourceCipherSuite<C, R>
+where
+    C: CipherSuiteProvider,
+    R: RandomSource,
+{
+    type Error = RandomSourceError;
+
+    type HpkeContextS = C::HpkeContextS;
+    type HpkeContextR = C::HpkeContextR;
+
+    fn cipher_suite(&self) -> CipherSuite {
+        self.inner.cipher_suite()
+    }
+
+    async fn hash(&self, data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        self.inner.hash(data).await.map_err(crypto_error)
+    }
+
+    async fn mac(&self, key: &[u8], data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        self.inner.mac(key, data).await.map_err(crypto_error)
+    }
+
+    async fn aead_seal(
+        &self,
+        key: &[u8],
+        data: &[u8],
+        aad: Option<&[u8]>,
+        nonce: &[u8],
+    ) -> Result<Vec<u8>, Self::Error> {
+        self.inner
+            .aead_seal(key, data, aad, nonce)
+            .await
+            .map_err(crypto_error)
+    }
+
+    async fn aead_open(
+        &self,
+        key: &[u8],
+        ciphertext: &[u8],
+        aad: Option<&[u8]>,
+        nonce: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>, Self::Error> {
+        self.inner
+            .aead_open(key, ciphertext, aad, nonce)
+            .await
+            .map_err(crypto_error)
+    }
+
+    fn aead_key_size(&self) -> usize {
+        self.inner.aead_key_size()
+    }
+
+    fn aead_nonce_size(&self) -> usize {
+        self.inner.aead_nonce_size()
+    }
+
+    async fn kdf_extract(
+        &self,
+        salt: &[u8],
+        ikm: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>, Self::Error> {
+        self.inner
+            .kdf_extract(salt, ikm)
+            .await
+            .map_err(crypto_error)
+    }
+
+    async fn kdf_expand(
+        &self,
+        prk: &[u8],
+        info: &[u8],
+        len: usize,
+    ) -> Result<Zeroizing<Vec<u8>>, Self::Error> {
+        self.inner
+            .kdf_expand(prk, info, len)
+            .await
+            .map_err(crypto_error)
+    }
+
+    fn kdf_extract_size(&self) -> usize {
+        self.inner.kdf_extract_size()
+    }
+
+    async fn hpke_seal(
+        &self,
+        remote_key: &HpkePublicKey,
+        info: &[u8],
+        aad: Option<&[u8]>,
+        pt: &[u8],
+    ) -> Result<HpkeCiphertext, Self::Error> {
+        self.inner
+            .hpke_seal(remote_key, info, aad, pt)
+            .await
+            .map_err(crypto_error)
+    }
+
+    async fn hpke_open(
+        &self,
+        ciphertext: &HpkeCiphertext,
+        local_secret: &HpkeSecretKey,
+        local_public: &HpkePublicKey,
+        info: &[u8],
+        aad: Option<&[u8]>,
+    ) -> Result<Vec<u8>, Self::Error> {
+        self.inner
+            .hpke_open(ciphertext, local_secret, local_public, info, aad)
+            .await
+            .map_err(crypto_error)
+    }
+
+    async fn hpke_setup_s(
+        &self,
+        remote_key: &HpkePublicKey,
+        info: &[u8],
+    ) -> Result<(Vec<u8>, Self::HpkeContextS), Self::Error> {
+        self.inner
+            .hpke_setup_s(remote_key, info)
+            .await
+            .map_err(crypto_error)
+    }
+
+    async fn hpke_setup_r(
+        &self,
+        kem_output: &[u8],
+        local_secret: &HpkeSecretKey,
+        local_public: &HpkePublicKey,
+        info: &[u8],
+    ) -> Result<Self::HpkeContextR, Self::Error> {
+        self.inner
+            .hpke_setup_r(kem_output, local_secret, local_public, info)
+            .await
+            .map_err(crypto_error)
+    }
+
+    async fn kem_derive(&self, ikm: &[u8]) -> Result<(HpkeSecretKey, HpkePublicKey), Self::Error> {
+        self.inner.kem_derive(ikm).await.map_err(crypto_error)
+    }
+
+    async fn kem_generate(&self) -> Result<(HpkeSecretKey, HpkePublicKey), Self::Error> {
+        let mut ikm = Zeroizing::new(vec![0u8; self.kdf_extract_size()]);
+        self.random_bytes(&mut ikm)?;
+        self.kem_derive(&ikm).await
+    }
+
+    fn kem_public_key_validate(&self, key: &HpkePublicKey) -> Result<(), Self::Error> {
+        self.inner
+            .kem_public_key_validate(key)
+            .map_err(crypto_error)
+    }
+
+    fn random_bytes(&self, out: &mut [u8]) -> Result<(), Self::Error> {
+        self.random_source
+            .fill_bytes(out)
+            .map_err(|e| RandomSourceError::RandomSourceError(e.into_any_error()))
+    }
+
+    async fn signature_key_generate(
+        &self,
+    ) -> Result<(SignatureSecretKey, SignaturePublicKey), Self::Error> {
+        self.inner
+            .signature_key_generate()
+            .await
+            .map_err(crypto_error)
+    }
+
+    async fn signature_key_derive_public(
+        &self,
+        secret_key: &SignatureSecretKey,
+    ) -> Result<SignaturePublicKey, Self::Error> {
+        self.inner
+            .signature_key_derive_public(secret_key)
+            .await
+            .map_err(crypto_error)
+    }
+
+    async fn sign(
+        &self,
+        secret_key: &SignatureSecretKey,
+        data: &[u8],
+    ) -> Result<Vec<u8>, Self::Error> {
+        self.inner
+            .sign(secret_key, data)
+            .await
+            .map_err(crypto_error)
+    }
+
+    async fn verify(
+        &self,
+        public_key: &SignaturePublicKey,
+        signature: &[u8],
+        data: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .verify(public_key, signature, data)
+            .await
+            .map_err(crypto_error)
+    }
+
+    fn signature_label_suffix(&self) -> &[u8] {
+        self.inner.signature_label_suffix()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+    use mls_rs_core::{
+        crypto::{CipherSuiteProvider, CryptoProvider},
+        error::IntoAnyError,
+    };
+
+    use super::{RandomSource, RandomSourceError, WithRandomSource};
+
+    use crate::{
+        client::test_utils::TEST_CIPHER_SUITE, client_builder::test_utils::TestClientBuilder,
+        crypto::test_utils::TestCryptoProvider, identity::test_utils::get_test_signing_identity,
+    };
+
+    #[derive(Debug)]
+    #[cfg_attr(feature = "std", derive(thiserror::Error))]
+    #[cfg_attr(feature = "std", error("no entropy"))]
+    struct NoEntropy;
+
+    impl IntoAnyError for NoEntropy {
+        #[cfg(feature = "std")]
+        fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+            Ok(self.into())
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    struct FixedRandom(Option<u8>);
+
+    impl RandomSource for FixedRandom {
+        type Error = NoEntropy;
+
+        fn fill_bytes(&self, out: &mut [u8]) -> Result<(), Self::Error> {
+            out.fill(self.0.ok_or(NoEntropy)?);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn random_bytes_come_from_source() {
+        let cs = WithRandomSource::new(TestCryptoProvider::new(), FixedRandom(Some(7)))
+            .cipher_suite_provider(TEST_CIPHER_SUITE)
+            .unwrap();
+
+        assert_eq!(cs.random_bytes_vec(4).unwrap(), vec![7; 4]);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn kem_keys_are_derived_from_source() {
+        let cs = WithRandomSource::new(TestCryptoProvider::new(), FixedRandom(Some(7)))
+            .cipher_suite_provider(TEST_CIPHER_SUITE)
+            .unwrap();
+
+        let generated = cs.kem_generate().await.unwrap();
+        let ikm = vec![7; cs.kdf_extract_size()];
+
+        let derived = cs.kem_derive(&ikm).await.unwrap();
+
+        assert_eq!(generated, derived);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn source_errors_are_reported() {
+        let cs = WithRandomSource::new(TestCryptoProvider::new(), FixedRandom(None))
+            .cipher_suite_provider(TEST_CIPHER_SUITE)
+            .unwrap();
+
+        assert_matches!(
+            cs.random_bytes_vec(4),
+            Err(RandomSourceError::RandomSourceError(_))
+        );
+
+        let res = cs.kem_generate().await;
+        assert_matches!(res, Err(RandomSourceError::RandomSourceError(_)));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn client_uses_random_source() {
+        let (identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"alice").await;
+
+        let client = TestClientBuilder::new_for_test()
+            .random_source(FixedRandom(Some(7)))
+            .signing_identity(identity, secret_key, TEST_CIPHER_SUITE)
+            .build();
+
+        let group = client.create_group(Default::default()).await.unwrap();
+
+        assert_eq!(group.group_id(), vec![7; 32]);
+    }
+}
@@ -0,0 +1,373 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::{string::String, sync::Arc};
+use core::fmt::{self, Debug};
+
+use crate::client::MlsError;
+
+impl MlsError {
+    /// Identifier of the kind of the error, independent of the data it
+    /// carries, e.g. `"InvalidEpoch"`.
+    ///
+    /// Codes are the names of the variants of [`MlsError`] and are used by
+    /// an [`ErrorRenderer`] to look up user-facing messages.
+    pub fn code(&self) -> &'static str {
+        match self {
+            MlsError::IdentityProviderError(..) => "IdentityProviderError",
+            MlsError::CryptoProviderError(..) => "CryptoProviderError",
+            MlsError::KeyPackageRepoError(..) => "KeyPackageRepoError",
+            MlsError::GroupStorageError(..) => "GroupStorageError",
+            MlsError::PskStoreError(..) => "PskStoreError",
+            MlsError::MlsRulesError(..) => "MlsRulesError",
+            MlsError::AddRejected(..) => "AddRejected",
+            MlsError::SerializationError(..) => "SerializationError",
+            MlsError::ExtensionError(..) => "ExtensionError",
+            MlsError::CompressionError(..) => "CompressionError",
+            MlsError::UnsupportedCompressionAlgorithm(..) => "UnsupportedCompressionAlgorithm",
+            MlsError::InvalidCompressionHeader => "InvalidCompressionHeader",
+            MlsError::DecompressedSizeExceeded(..) => "DecompressedSizeExceeded",
+            MlsError::CipherSuiteMismatch => "CipherSuiteMismatch",
+            MlsError::EscrowNotConfigured => "EscrowNotConfigured",
+            MlsError::TreeRejected(..) => "TreeRejected",
+            MlsError::CommitMissingPath => "CommitMissingPath",
+            MlsError::CommitPathNotAllowed => "CommitPathNotAllowed",
+            MlsError::InvalidEpoch => "InvalidEpoch",
+            MlsError::InvalidSignature => "InvalidSignature",
+            MlsError::InvalidConfirmationTag => "InvalidConfirmationTag",
+            MlsError::InvalidMembershipTag => "InvalidMembershipTag",
+            MlsError::InvalidTreeKemPrivateKey => "InvalidTreeKemPrivateKey",
+            MlsError::WelcomeKeyPackageNotFound => "WelcomeKeyPackageNotFound",
+            MlsError::LeafNotFound(..) => "LeafNotFound",
+            MlsError::WelcomeTooLarge(..) => "WelcomeTooLarge",
+            MlsError::CantProcessMessageFromSelf => "CantProcessMessageFromSelf",
+            MlsError::CommitRequired => "CommitRequired",
+            MlsError::RatchetTreeNotFound => "RatchetTreeNotFound",
+            MlsError::ExternalSenderCannotCommit => "ExternalSenderCannotCommit",
+            MlsError::UnsupportedProtocolVersion(..) => "UnsupportedProtocolVersion",
+            MlsError::ProtocolVersionMismatch => "ProtocolVersionMismatch",
+            MlsError::UnsupportedCipherSuite(..) => "UnsupportedCipherSuite",
+            MlsError::UnknownSigningIdentityForExternalSender => {
+                "UnknownSigningIdentityForExternalSender"
+            }
+            MlsError::ExternalProposalsDisabled => "ExternalProposalsDisabled",
+            MlsError::InvalidExternalSigningIdentity => "InvalidExternalSigningIdentity",
+            MlsError::MissingExternalPubExtension => "MissingExternalPubExtension",
+            MlsError::EpochNotFound => "EpochNotFound",
+            MlsError::UnencryptedApplicationMessage => "UnencryptedApplicationMessage",
+            MlsError::UnencryptedHandshakeMessage => "UnencryptedHandshakeMessage",
+            MlsError::ExpectedCommitForNewMemberCommit => "ExpectedCommitForNewMemberCommit",
+            MlsError::ExpectedAddProposalForNewMemberProposal => {
+                "ExpectedAddProposalForNewMemberProposal"
+            }
+            MlsError::ExternalCommitMissingExternalInit => "ExternalCommitMissingExternalInit",
+            MlsError::GroupUsedAfterReInit => "GroupUsedAfterReInit",
+            MlsError::PendingReInitNotFound => "PendingReInitNotFound",
+            MlsError::ReInitExtensionsMismatch => "ReInitExtensionsMismatch",
+            MlsError::SignerNotFound => "SignerNotFound",
+            MlsError::ExistingPendingCommit => "ExistingPendingCommit",
+            MlsError::PendingCommitNotFound => "PendingCommitNotFound",
+            MlsError::CommitWhilePendingLeave => "CommitWhilePendingLeave",
+            MlsError::SelfUpdateRequired => "SelfUpdateRequired",
+            MlsError::EpochMaxAgeExceeded => "EpochMaxAgeExceeded",
+            MlsError::EpochSendNotAllowed(..) => "EpochSendNotAllowed",
+            MlsError::FutureEpochBufferFull => "FutureEpochBufferFull",
+            MlsError::ReInitDowngradeRejected(..) => "ReInitDowngradeRejected",
+            MlsError::CipherSuiteNotAllowed(..) => "CipherSuiteNotAllowed",
+            MlsError::ProtocolVersionNotAllowed(..) => "ProtocolVersionNotAllowed",
+            MlsError::GroupArchived => "GroupArchived",
+            MlsError::GroupUsedAfterRemoval => "GroupUsedAfterRemoval",
+            MlsError::UnexpectedMessageType => "UnexpectedMessageType",
+            MlsError::MembershipTagForNonMember => "MembershipTagForNonMember",
+            MlsError::MemberNotFound => "MemberNotFound",
+            MlsError::GroupNotFound => "GroupNotFound",
+            MlsError::UnexpectedPskId => "UnexpectedPskId",
+            MlsError::InvalidSender => "InvalidSender",
+            MlsError::GroupIdMismatch => "GroupIdMismatch",
+            MlsError::GroupIdTooShort(..) => "GroupIdTooShort",
+            MlsError::GroupIdTooLong(..) => "GroupIdTooLong",
+            MlsError::InvalidGroupIdCharacter(..) => "InvalidGroupIdCharacter",
+            MlsError::UnsupportedGroupPolicy => "UnsupportedGroupPolicy",
+            MlsError::InvalidInviteCodeLength(..) => "InvalidInviteCodeLength",
+            MlsError::NonZeroRetentionRequired => "NonZeroRetentionRequired",
+            MlsError::InvalidStorageKeyLength(..) => "InvalidStorageKeyLength",
+            MlsError::StorageKeyNotFound(..) => "StorageKeyNotFound",
+            MlsError::StorageDecryptionFailed => "StorageDecryptionFailed",
+            MlsError::TooManyPskIds => "TooManyPskIds",
+            MlsError::MissingRequiredPsk => "MissingRequiredPsk",
+            MlsError::OldGroupStateNotFound => "OldGroupStateNotFound",
+            MlsError::InvalidLeafConsumption => "InvalidLeafConsumption",
+            MlsError::ApplicationLanesUnavailable => "ApplicationLanesUnavailable",
+            MlsError::StateNotPersisted => "StateNotPersisted",
+            MlsError::KeyMissing(..) => "KeyMissing",
+            MlsError::InvalidFutureGeneration(..) => "InvalidFutureGeneration",
+            MlsError::LeafNodeNoChildren => "LeafNodeNoChildren",
+            MlsError::LeafNodeNoParent => "LeafNodeNoParent",
+            MlsError::InvalidTreeIndex => "InvalidTreeIndex",
+            MlsError::TimeOverflow => "TimeOverflow",
+            MlsError::InvalidLeafNodeSource => "InvalidLeafNodeSource",
+            MlsError::InvalidLifetime => "InvalidLifetime",
+            MlsError::RequiredExtensionNotFound(..) => "RequiredExtensionNotFound",
+            MlsError::RequiredProposalNotFound(..) => "RequiredProposalNotFound",
+            MlsError::RequiredCredentialNotFound(..) => "RequiredCredentialNotFound",
+            MlsError::ExtensionNotInCapabilities(..) => "ExtensionNotInCapabilities",
+            MlsError::ExpectedNode => "ExpectedNode",
+            MlsError::InvalidNodeIndex(..) => "InvalidNodeIndex",
+            MlsError::UnexpectedEmptyNode => "UnexpectedEmptyNode",
+            MlsError::DuplicateLeafData(..) => "DuplicateLeafData",
+            MlsError::InUseCredentialTypeUnsupportedByNewLeaf => {
+                "InUseCredentialTypeUnsupportedByNewLeaf"
+            }
+            MlsError::CredentialTypeOfNewLeafIsUnsupported => {
+                "CredentialTypeOfNewLeafIsUnsupported"
+            }
+            MlsError::WrongPathLen => "WrongPathLen",
+            MlsError::SameHpkeKey(..) => "SameHpkeKey",
+            MlsError::InvalidInitKey => "InvalidInitKey",
+            MlsError::InitLeafKeyEquality => "InitLeafKeyEquality",
+            MlsError::InitKeyReusedInCommit(..) => "InitKeyReusedInCommit",
+            MlsError::InitKeyReusedInTree(..) => "InitKeyReusedInTree",
+            MlsError::SnapshotDeltaBaseMismatch => "SnapshotDeltaBaseMismatch",
+            MlsError::SnapshotDeltaTargetMismatch => "SnapshotDeltaTargetMismatch",
+            MlsError::SnapshotDeltaInvalidNodeCount(..) => "SnapshotDeltaInvalidNodeCount",
+            MlsError::UnsupportedSnapshotVersion(..) => "UnsupportedSnapshotVersion",
+            MlsError::DifferentIdentityInUpdate(..) => "DifferentIdentityInUpdate",
+            MlsError::PubKeyMismatch => "PubKeyMismatch",
+            MlsError::TreeHashMismatch => "TreeHashMismatch",
+            MlsError::RatchetTreeHashMismatch(..) => "RatchetTreeHashMismatch",
+            MlsError::TranscriptHashMismatch(..) => "TranscriptHashMismatch",
+            MlsError::UpdateErrorNoSecretKey => "UpdateErrorNoSecretKey",
+            MlsError::LcaNotFoundInDirectPath => "LcaNotFoundInDirectPath",
+            MlsError::ParentHashMismatch => "ParentHashMismatch",
+            MlsError::UnmergedLeavesMismatch => "UnmergedLeavesMismatch",
+            MlsError::UnexpectedEmptyTree => "UnexpectedEmptyTree",
+            MlsError::UnexpectedTrailingBlanks => "UnexpectedTrailingBlanks",
+            MlsError::InvalidCommitSelfUpdate => "InvalidCommitSelfUpdate",
+            MlsError::InvalidTypeOrUsageInPreSharedKeyProposal => {
+                "InvalidTypeOrUsageInPreSharedKeyProposal"
+            }
+            MlsError::InvalidPskNonceLength => "InvalidPskNonceLength",
+            MlsError::InvalidProtocolVersionInReInit => "InvalidProtocolVersionInReInit",
+            MlsError::MoreThanOneProposalForLeaf(..) => "MoreThanOneProposalForLeaf",
+            MlsError::DuplicateProposalFromSender(..) => "DuplicateProposalFromSender",
+            MlsError::MoreThanOneGroupContextExtensionsProposal => {
+                "MoreThanOneGroupContextExtensionsProposal"
+            }
+            MlsError::InvalidProposalTypeForSender => "InvalidProposalTypeForSender",
+            MlsError::ExternalCommitMustHaveExactlyOneExternalInit => {
+                "ExternalCommitMustHaveExactlyOneExternalInit"
+            }
+            MlsError::ExternalCommitMustHaveNewLeaf => "ExternalCommitMustHaveNewLeaf",
+            MlsError::ExternalCommitRemovesOtherIdentity => "ExternalCommitRemovesOtherIdentity",
+            MlsError::ExternalCommitWithMoreThanOneRemove => "ExternalCommitWithMoreThanOneRemove",
+            MlsError::DuplicatePskIds => "DuplicatePskIds",
+            MlsError::InvalidProposalTypeInExternalCommit(..) => {
+                "InvalidProposalTypeInExternalCommit"
+            }
+            MlsError::CommitterSelfRemoval => "CommitterSelfRemoval",
+            MlsError::OnlyMembersCanCommitProposalsByRef => "OnlyMembersCanCommitProposalsByRef",
+            MlsError::OtherProposalWithReInit => "OtherProposalWithReInit",
+            MlsError::UnsupportedGroupExtension(..) => "UnsupportedGroupExtension",
+            MlsError::UnknownCriticalExtension(..) => "UnknownCriticalExtension",
+            MlsError::UnsupportedCustomProposal(..) => "UnsupportedCustomProposal",
+            MlsError::ProposalNotFound => "ProposalNotFound",
+            MlsError::RemovingNonExistingMember => "RemovingNonExistingMember",
+            MlsError::InvalidSuccessor => "InvalidSuccessor",
+            MlsError::UpdatingNonExistingMember => "UpdatingNonExistingMember",
+            MlsError::FailedGeneratingPathSecret => "FailedGeneratingPathSecret",
+            MlsError::InvalidGroupInfo => "InvalidGroupInfo",
+            MlsError::InvalidWelcomeMessage => "InvalidWelcomeMessage",
+        }
+    }
+}
+
+/// Mapping of [error codes](MlsError::code) to messages that can be shown to
+/// users, e.g. in their language, instead of the description of the error
+/// intended for developers.
+///
+/// A renderer is configured for each client with
+/// [`ClientBuilder::error_renderer`](crate::client_builder::ClientBuilder::error_renderer)
+/// and used by [`Client::render_error`](crate::Client::render_error) and
+/// [`Group::render_error`](crate::Group::render_error). Bindings use it to
+/// describe the errors they return.
+pub trait ErrorRenderer: Send + Sync {
+    /// Message describing errors with `code`, or `None` to use the message of
+    /// [`DefaultErrorRenderer`].
+    fn message(&self, code: &str) -> Option<String>;
+
+    /// Message describing `error`.
+    fn render(&self, error: &MlsError) -> String {
+        self.message(error.code())
+            .unwrap_or_else(|| default_message(error.code()).into())
+    }
+}
+
+/// [`ErrorRenderer`] configured for a client, shared by its groups.
+#[derive(Clone)]
+pub struct SharedErrorRenderer(pub(crate) Arc<dyn ErrorRenderer>);
+
+impl Debug for SharedErrorRenderer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ErrorRenderer")
+    }
+}
+
+impl ErrorRenderer for SharedErrorRenderer {
+    fn message(&self, code: &str) -> Option<String> {
+        self.0.message(code)
+    }
+}
+
+/// Message describing `error` with `renderer`, or with
+/// [`DefaultErrorRenderer`] if no renderer is configured.
+pub(crate) fn render_error(renderer: Option<SharedErrorRenderer>, error: &MlsError) -> String {
+    match renderer {
+        Some(renderer) => renderer.render(error),
+        None => DefaultErrorRenderer.render(error),
+    }
+}
+
+/// Message describing errors with `code` with `renderer`, or with
+/// [`DefaultErrorRenderer`] if no renderer is configured.
+pub(crate) fn error_message(renderer: Option<SharedErrorRenderer>, code: &str) -> String {
+    renderer
+        .and_then(|renderer| renderer.message(code))
+        .unwrap_or_else(|| default_message(code).into())
+}
+
+/// [`ErrorRenderer`] with messages in English.
+///
+/// Errors that are not caused by the actions of users or by other members,
+/// such as errors of storage or crypto providers reported with their own
+/// description, are described by a generic message.
+#[derive(Clone, Copy, Debug, Default)]
+#[non_exhaustive]
+pub struct DefaultErrorRenderer;
+
+impl DefaultErrorRenderer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl ErrorRenderer for DefaultErrorRenderer {
+    fn message(&self, code: &str) -> Option<String> {
+        Some(default_message(code).into())
+    }
+}
+
+fn default_message(code: &str) -> &'static str {
+    match code {
+        "InvalidEpoch" => "This message belongs to a different epoch of the group.",
+        "EpochNotFound" => "This message is too old to be decrypted.",
+        "EpochMaxAgeExceeded" => "This message is too old to be accepted.",
+        "InvalidSignature" => "This message could not be authenticated.",
+        "InvalidMembershipTag" => "This message could not be authenticated.",
+        "InvalidConfirmationTag" => "This change to the group could not be verified.",
+        "CantProcessMessageFromSelf" => "This message was sent by this device.",
+        "WelcomeKeyPackageNotFound" => "This invitation is not addressed to this device.",
+        "InvalidWelcomeMessage" => "This invitation is not valid.",
+        "InvalidLifetime" => "This invitation has expired.",
+        "RatchetTreeNotFound" => {
+            "The group could not be joined because its member list is missing."
+        }
+        "TreeHashMismatch" => "The member list of the group could not be verified.",
+        "RatchetTreeHashMismatch" => "The member list of the group could not be verified.",
+        "GroupNotFound" => "This group was not found.",
+        "GroupArchived" => "This group is archived.",
+        "GroupUsedAfterRemoval" => "You were removed from this group.",
+        "GroupUsedAfterReInit" => "This group was replaced by a new group.",
+        "MemberNotFound" => "This member is not in the group.",
+        "RemovingNonExistingMember" => "This member is not in the group.",
+        "ExistingPendingCommit" => "Another change to the group is in progress.",
+        "PendingCommitNotFound" => "There is no change to the group in progress.",
+        "CommitRequired" => "The group must be updated before sending messages.",
+        "UnsupportedCipherSuite" => "This group uses an unsupported encryption algorithm.",
+        "CipherSuiteNotAllowed" => "This group uses an encryption algorithm that is not allowed.",
+        "UnsupportedProtocolVersion" => "This group uses an unsupported protocol version.",
+        "ProtocolVersionNotAllowed" => "This group uses a protocol version that is not allowed.",
+        "UnsupportedGroupPolicy" => "This group has a policy that this app can't honor.",
+        "WelcomeTooLarge" => "Too many members were added at once.",
+        "MissingRequiredPsk" => "A key required to access this group is missing.",
+        "StorageDecryptionFailed" => "The stored group could not be decrypted.",
+        "GroupStorageError" => "The group could not be loaded or saved.",
+        "KeyPackageRepoError" => "The invitation keys could not be loaded or saved.",
+        "UnencryptedApplicationMessage" => "This message was not encrypted.",
+        "ExternalProposalsDisabled" => {
+            "This group does not accept requests from outside the group."
+        }
+        _ => "Something went wrong in the secure group.",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::{String, ToString};
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::test_utils::{test_group, test_group_custom_config},
+    };
+
+    use super::{DefaultErrorRenderer, ErrorRenderer};
+
+    struct FrenchRenderer;
+
+    impl ErrorRenderer for FrenchRenderer {
+        fn message(&self, code: &str) -> Option<String> {
+            (code == "InvalidLifetime").then(|| "Cette invitation a expiré.".to_string())
+        }
+    }
+
+    #[test]
+    fn errors_are_rendered_by_code() {
+        let error = MlsError::LeafNotFound(3);
+        assert_eq!(error.code(), "LeafNotFound");
+
+        assert_eq!(
+            DefaultErrorRenderer.render(&MlsError::InvalidLifetime),
+            "This invitation has expired."
+        );
+
+        assert_eq!(
+            FrenchRenderer.render(&MlsError::InvalidLifetime),
+            "Cette invitation a expiré."
+        );
+
+        // Messages missing from a renderer fall back to the default table.
+        assert_eq!(
+            FrenchRenderer.render(&MlsError::GroupNotFound),
+            DefaultErrorRenderer.render(&MlsError::GroupNotFound)
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn errors_are_rendered_with_the_renderer_of_the_client() {
+        let alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.error_renderer(FrenchRenderer)
+        })
+        .await;
+
+        assert_eq!(
+            alice.group.render_error(&MlsError::InvalidLifetime),
+            "Cette invitation a expiré."
+        );
+
+        assert_eq!(
+            alice.group.error_message("GroupNotFound"),
+            "This group was not found."
+        );
+
+        // Other clients keep the default messages.
+        let bob = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        assert_eq!(
+            bob.group.render_error(&MlsError::InvalidLifetime),
+            "This invitation has expired."
+        );
+    }
+}
@@ -2,6 +2,7 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+use alloc::string::String;
 use alloc::vec::Vec;
 use core::fmt::{self, Debug};
 use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
@@ -235,6 +236,132 @@ impl MlsCodecExtension for ExternalSendersExt {
     }
 }
 
+/// Extension type of the [`GroupMetadataExt`], taken from the private use range.
+pub const GROUP_METADATA_EXTENSION_TYPE: ExtensionType = ExtensionType::new(0xF000);
+
+/// Application defined metadata of a group, such as its name or topic.
+///
+/// This extension is stored in the group context and can be read and
+/// modified with [`Group::group_metadata`](crate::group::Group::group_metadata)
+/// and [`CommitBuilder::set_group_metadata`](crate::group::CommitBuilder::set_group_metadata).
+/// All members of the group must support [`GROUP_METADATA_EXTENSION_TYPE`].
+///
+/// The metadata is visible to all members and to external parties with
+/// access to the group info. It should be kept small as it is included in
+/// the group context of every epoch.
+#[derive(Clone, Debug, Default, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[non_exhaustive]
+pub struct GroupMetadataExt {
+    /// Human readable name of the group.
+    pub name: Option<String>,
+    /// Human readable topic of the group.
+    pub topic: Option<String>,
+    /// Hash of the group avatar, which is stored by the application.
+    pub avatar_hash: Option<Vec<u8>>,
+}
+
+impl GroupMetadataExt {
+    /// Create empty group metadata.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set the name of the group.
+    pub fn with_name(self, name: String) -> Self {
+        Self {
+            name: Some(name),
+            ..self
+        }
+    }
+
+    /// Set the topic of the group.
+    pub fn with_topic(self, topic: String) -> Self {
+        Self {
+            topic: Some(topic),
+            ..self
+        }
+    }
+
+    /// Set the hash of the group avatar.
+    pub fn with_avatar_hash(self, avatar_hash: Vec<u8>) -> Self {
+        Self {
+            avatar_hash: Some(avatar_hash),
+            ..self
+        }
+    }
+}
+
+impl MlsCodecExtension for GroupMetadataExt {
+    fn extension_type() -> ExtensionType {
+        GROUP_METADATA_EXTENSION_TYPE
+    }
+}
+
+/// Extension type of the [`GroupPolicyExt`], taken from the private use
+/// range.
+pub const GROUP_POLICY_EXTENSION_TYPE: ExtensionType = ExtensionType::new(0xF002);
+
+/// Machine-readable policy of a group, such as how long members may retain
+/// messages.
+///
+/// This extension is set in the group context extensions when creating the
+/// group and can be read with
+/// [`Group::group_policy`](crate::group::Group::group_policy). Clients check
+/// it against their [`GroupPolicySupport`](crate::group::GroupPolicySupport)
+/// when joining the group and refuse to join groups whose policy they can't
+/// honor. All members of the group must support
+/// [`GROUP_POLICY_EXTENSION_TYPE`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[non_exhaustive]
+pub struct GroupPolicyExt {
+    /// Time in seconds after which members must delete received messages.
+    /// `None` if messages may be retained indefinitely.
+    pub retention_period: Option<u64>,
+    /// True if members must send proposals and commits as
+    /// [`PrivateMessage`](crate::WireFormat::PrivateMessage).
+    pub encrypt_handshake: bool,
+    /// Application defined reference to the list of administrators of the
+    /// group, e.g. a URL or the ID of a record.
+    pub admin_list: Option<Vec<u8>>,
+}
+
+impl GroupPolicyExt {
+    /// Create a policy without any requirement.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Require members to delete messages after `retention_period` seconds.
+    pub fn with_retention_period(self, retention_period: u64) -> Self {
+        Self {
+            retention_period: Some(retention_period),
+            ..self
+        }
+    }
+
+    /// Require members to encrypt proposals and commits.
+    pub fn with_encrypted_handshake(self) -> Self {
+        Self {
+            encrypt_handshake: true,
+            ..self
+        }
+    }
+
+    /// Set the reference to the list of administrators of the group.
+    pub fn with_admin_list(self, admin_list: Vec<u8>) -> Self {
+        Self {
+            admin_list: Some(admin_list),
+            ..self
+        }
+    }
+}
+
+impl MlsCodecExtension for GroupPolicyExt {
+    fn extension_type() -> ExtensionType {
+        GROUP_POLICY_EXTENSION_TYPE
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -327,4 +454,17 @@ mod tests {
         let restored = ExternalPubExt::from_extension(&as_extension).unwrap();
         assert_eq!(ext, restored)
     }
+
+    #[test]
+    fn test_group_metadata() {
+        let ext = GroupMetadataExt::new()
+            .with_name("name".into())
+            .with_avatar_hash(vec![0, 1, 2, 3]);
+
+        let as_extension = ext.clone().into_extension().unwrap();
+        assert_eq!(as_extension.extension_type, GROUP_METADATA_EXTENSION_TYPE);
+
+        let restored = GroupMetadataExt::from_extension(&as_extension).unwrap();
+        assert_eq!(ext, restored)
+    }
 }
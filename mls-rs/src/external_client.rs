@@ -11,6 +11,8 @@ use crate::{
 pub mod builder;
 mod config;
 mod group;
+pub mod interop;
+mod observer;
 
 pub(crate) use config::ExternalClientConfig;
 use mls_rs_core::{
@@ -21,6 +23,7 @@ use mls_rs_core::{
 use builder::{ExternalBaseConfig, ExternalClientBuilder};
 
 pub use group::{ExternalGroup, ExternalReceivedMessage, ExternalSnapshot};
+pub use observer::{MembershipDiff, MembershipObserver, ObserverGroup};
 
 /// A client capable of observing a group's state without having
 /// private keys required to read content.
@@ -86,6 +89,21 @@ where
         .await
     }
 
+    /// Begin observing a group based on its public state exported by
+    /// OpenMLS, see [`OpenMlsPublicGroupState`](interop::OpenMlsPublicGroupState).
+    ///
+    /// The ratchet tree is validated against the group context the same way
+    /// as in [`ExternalClient::observe_group`]. Since the state contains no
+    /// signed group info, the state must be obtained from a trusted source,
+    /// e.g. the storage of the delivery service being migrated.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn observe_openmls_group(
+        &self,
+        state: interop::OpenMlsPublicGroupState,
+    ) -> Result<ExternalGroup<C>, MlsError> {
+        ExternalGroup::from_openmls(self.config.clone(), self.signing_data.clone(), state).await
+    }
+
     /// Load an existing observed group by loading a snapshot that was
     /// generated by
     /// [ExternalGroup::snapshot](self::ExternalGroup::snapshot).
@@ -6,6 +6,8 @@
 //!
 //! See [`ExternalClientBuilder`].
 
+#[cfg(feature = "signature_label_suffix")]
+use crate::crypto::WithSignatureLabelSuffix;
 use crate::{
     crypto::SignaturePublicKey,
     extension::ExtensionType,
@@ -19,6 +21,7 @@ use crate::{
     tree_kem::Capabilities,
     CryptoProvider, Sealed,
 };
+
 use std::{
     collections::HashMap,
     fmt::{self, Debug},
@@ -248,6 +251,31 @@ impl<C: IntoConfig> ExternalClientBuilder<C> {
         }))
     }
 
+    /// Set the suffix appended to the label of every signature verified by
+    /// the client.
+    ///
+    /// The crypto provider set with [`ExternalClientBuilder::crypto_provider`]
+    /// is wrapped in a [`WithSignatureLabelSuffix`], see its documentation for
+    /// details. This function must therefore be called after setting the
+    /// crypto provider. By default, no suffix is used.
+    #[cfg(feature = "signature_label_suffix")]
+    pub fn signature_label_suffix(
+        self,
+        suffix: Vec<u8>,
+    ) -> ExternalClientBuilder<WithCryptoProvider<WithSignatureLabelSuffix<C::CryptoProvider>, C>>
+    where
+        C::CryptoProvider: CryptoProvider,
+    {
+        let Config(c) = self.0.into_config();
+        ExternalClientBuilder(Config(ConfigInner {
+            settings: c.settings,
+            identity_provider: c.identity_provider,
+            mls_rules: c.mls_rules,
+            crypto_provider: WithSignatureLabelSuffix::new(c.crypto_provider, suffix),
+            signing_data: c.signing_data,
+        }))
+    }
+
     /// Set the user-defined proposal rules to be used by the client.
     ///
     /// User-defined rules are used when sending and receiving commits before
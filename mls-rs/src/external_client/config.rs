@@ -11,7 +11,7 @@ use crate::{
     identity::CredentialType,
     protocol_version::ProtocolVersion,
     tree_kem::Capabilities,
-    CryptoProvider,
+    CrateCapabilities, CryptoProvider,
 };
 
 pub trait ExternalClientConfig: Send + Sync + Clone {
@@ -35,13 +35,13 @@ pub trait ExternalClientConfig: Send + Sync + Clone {
     }
 
     fn capabilities(&self) -> Capabilities {
-        Capabilities {
+        CrateCapabilities::current().restrict(Capabilities {
             protocol_versions: self.supported_protocol_versions(),
             cipher_suites: self.crypto_provider().supported_cipher_suites(),
             extensions: self.supported_extensions(),
             proposals: self.supported_custom_proposals(),
             credentials: self.supported_credentials(),
-        }
+        })
     }
 
     fn version_supported(&self, version: ProtocolVersion) -> bool {
@@ -25,7 +25,7 @@ use crate::{
         state::GroupState,
         transcript_hash::InterimTranscriptHash,
         validate_group_info_joiner, ContentType, ExportedTree, GroupContext, GroupInfo, Roster,
-        Welcome,
+        Validated, Welcome,
     },
     identity::SigningIdentity,
     protocol_version::ProtocolVersion,
@@ -118,9 +118,10 @@ impl<C: ExternalClientConfig + Clone> ExternalGroup<C> {
             return Err(MlsError::UnsupportedProtocolVersion(protocol_version));
         }
 
-        let group_info = group_info
+        let group_info = Validated::new(group_info)?
             .into_group_info()
-            .ok_or(MlsError::UnexpectedMessageType)?;
+            .ok_or(MlsError::UnexpectedMessageType)?
+            .into_inner();
 
         let cipher_suite_provider = cipher_suite_provider(
             config.crypto_provider(),
@@ -182,9 +183,11 @@ impl<C: ExternalClientConfig + Clone> ExternalGroup<C> {
     ) -> Result<ExternalReceivedMessage, MlsError> {
         MessageProcessor::process_incoming_message(
             self,
-            message,
+            Validated::new(message)?,
             #[cfg(feature = "by_ref_proposal")]
             self.config.cache_proposals(),
+            #[cfg(feature = "application_lanes")]
+            0,
         )
         .await
     }
@@ -354,9 +357,10 @@ impl<C: ExternalClientConfig + Clone> ExternalGroup<C> {
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn propose_group_context_extensions(
         &mut self,
-        extensions: ExtensionList,
+        mut extensions: ExtensionList,
         authenticated_data: Vec<u8>,
     ) -> Result<MlsMessage, MlsError> {
+        extensions.canonicalize();
         let proposal = Proposal::GroupContextExtensions(extensions);
         self.propose(proposal, authenticated_data).await
     }
@@ -587,13 +591,14 @@ where
         )
         .await?;
 
-        Ok(EventOrContent::Content(auth_content))
+        Ok(EventOrContent::Content(auth_content, None))
     }
 
     #[cfg(feature = "private_message")]
     async fn process_ciphertext(
         &mut self,
         cipher_text: &PrivateMessage,
+        #[cfg(feature = "application_lanes")] _lane: u16,
     ) -> Result<EventOrContent<Self::OutputType>, MlsError> {
         Ok(EventOrContent::Event(ExternalReceivedMessage::Ciphertext(
             cipher_text.content_type,
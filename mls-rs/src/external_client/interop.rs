@@ -0,0 +1,255 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Import of the public group state exported by other MLS implementations.
+
+use alloc::vec::Vec;
+use mls_rs_codec::MlsDecode;
+use mls_rs_core::{crypto::SignatureSecretKey, identity::SigningIdentity};
+
+#[cfg(feature = "by_ref_proposal")]
+use mls_rs_core::error::IntoAnyError;
+
+use crate::{
+    client::MlsError,
+    external_client::{ExternalClientConfig, ExternalGroup},
+    group::{
+        cipher_suite_provider, confirmation_tag::ConfirmationTag, state::GroupState,
+        transcript_hash::InterimTranscriptHash, ExportedTree, GroupContext,
+    },
+    tree_kem::{node::NodeVec, tree_validator::TreeValidator, TreeKemPublic},
+};
+
+#[cfg(feature = "by_ref_proposal")]
+use crate::extension::ExternalSendersExt;
+
+/// Public state of a group exported by OpenMLS.
+///
+/// The state consists of the values returned by the `export_group_context`,
+/// `export_ratchet_tree` and `confirmation_tag` functions of an OpenMLS
+/// group, each serialized with the TLS encoding used by OpenMLS. Use
+/// [`ExternalClient::observe_openmls_group`](crate::external_client::ExternalClient::observe_openmls_group)
+/// to start observing the group from this state, e.g. to migrate a delivery
+/// service tracking groups with OpenMLS.
+///
+/// The state does not contain a signed group info, so it can't be used to
+/// join the group with an external commit. A group info signed by a member
+/// is still required for that.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OpenMlsPublicGroupState {
+    /// Serialized `GroupContext` of the current epoch.
+    pub group_context: Vec<u8>,
+    /// Serialized `RatchetTree` of the current epoch.
+    pub ratchet_tree: Vec<u8>,
+    /// Serialized confirmation tag `Mac` of the current epoch.
+    pub confirmation_tag: Vec<u8>,
+}
+
+impl OpenMlsPublicGroupState {
+    pub fn new(group_context: Vec<u8>, ratchet_tree: Vec<u8>, confirmation_tag: Vec<u8>) -> Self {
+        Self {
+            group_context,
+            ratchet_tree,
+            confirmation_tag,
+        }
+    }
+}
+
+impl<C: ExternalClientConfig + Clone> ExternalGroup<C> {
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn from_openmls(
+        config: C,
+        signing_data: Option<(SignatureSecretKey, SigningIdentity)>,
+        state: OpenMlsPublicGroupState,
+    ) -> Result<Self, MlsError> {
+        let context = GroupContext::mls_decode(&mut &*state.group_context)?;
+
+        if !config.version_supported(context.protocol_version) {
+            return Err(MlsError::UnsupportedProtocolVersion(
+                context.protocol_version,
+            ));
+        }
+
+        let confirmation_tag = ConfirmationTag::mls_decode(&mut &*state.confirmation_tag)?;
+
+        // Older versions of OpenMLS export the tree without removing the
+        // trailing blank nodes, which RFC 9420 requires.
+        let mut nodes = NodeVec::from(ExportedTree::from_bytes(&state.ratchet_tree)?);
+        nodes.trim();
+
+        let cipher_suite_provider =
+            cipher_suite_provider(config.crypto_provider(), context.cipher_suite)?;
+
+        let identity_provider = config.identity_provider();
+
+        let mut public_tree =
+            TreeKemPublic::import_node_data(nodes, &identity_provider, &context.extensions).await?;
+
+        TreeValidator::new(&cipher_suite_provider, &context, &identity_provider)
+            .validate(&mut public_tree)
+            .await?;
+
+        #[cfg(feature = "by_ref_proposal")]
+        if let Some(ext_senders) = context.extensions.get_as::<ExternalSendersExt>()? {
+            ext_senders
+                .verify_all(&identity_provider, None, &context.extensions)
+                .await
+                .map_err(|e| MlsError::IdentityProviderError(e.into_any_error()))?;
+        }
+
+        let interim_transcript_hash = InterimTranscriptHash::create(
+            &cipher_suite_provider,
+            &context.confirmed_transcript_hash,
+            &confirmation_tag,
+        )
+        .await?;
+
+        Ok(Self {
+            config,
+            signing_data,
+            state: GroupState::new(
+                context,
+                public_tree,
+                interim_transcript_hash,
+                confirmation_tag,
+            ),
+            cipher_suite_provider,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+    use assert_matches::assert_matches;
+    use mls_rs_codec::MlsEncode;
+
+    use super::OpenMlsPublicGroupState;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        external_client::{
+            group::test_utils::make_external_group, tests_utils::TestExternalClientBuilder,
+        },
+        group::test_utils::{test_group, TestGroup},
+    };
+
+    /// Variable-length vector as encoded by the `tls_codec` crate used by
+    /// OpenMLS, with the size prefix of RFC 9000.
+    fn tls_vl_bytes(bytes: &[u8]) -> Vec<u8> {
+        let len = bytes.len();
+
+        let mut out = match len {
+            0..=0x3f => vec![len as u8],
+            0x40..=0x3fff => (len as u16 | 0x4000).to_be_bytes().to_vec(),
+            _ => (len as u32 | 0x8000_0000).to_be_bytes().to_vec(),
+        };
+
+        out.extend_from_slice(bytes);
+        out
+    }
+
+    /// State of `group` in the layout returned by OpenMLS, with the group
+    /// context and confirmation tag encoded field by field instead of with
+    /// the codec of this crate.
+    fn exported_state(group: &TestGroup) -> OpenMlsPublicGroupState {
+        let context = group.group.context();
+
+        let extensions = context
+            .extensions
+            .iter()
+            .flat_map(|ext| {
+                [
+                    ext.extension_type.raw_value().to_be_bytes().to_vec(),
+                    tls_vl_bytes(&ext.extension_data),
+                ]
+                .concat()
+            })
+            .collect::<Vec<_>>();
+
+        let group_context = [
+            u16::from(context.protocol_version).to_be_bytes().to_vec(),
+            u16::from(context.cipher_suite).to_be_bytes().to_vec(),
+            tls_vl_bytes(&context.group_id),
+            context.epoch.to_be_bytes().to_vec(),
+            tls_vl_bytes(&context.tree_hash),
+            tls_vl_bytes(&context.confirmed_transcript_hash),
+            tls_vl_bytes(&extensions),
+        ]
+        .concat();
+
+        OpenMlsPublicGroupState::new(
+            group_context,
+            group.group.export_tree().to_bytes().unwrap(),
+            tls_vl_bytes(&group.group.state.confirmation_tag),
+        )
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn observer_can_be_bootstrapped_from_exported_state() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let mut observer = TestExternalClientBuilder::new_for_test()
+            .build()
+            .observe_openmls_group(exported_state(&alice))
+            .await
+            .unwrap();
+
+        let expected = make_external_group(&alice).await;
+        assert_eq!(observer.group_state(), expected.group_state());
+
+        // The observer follows the group after the import.
+        let commit = bob.group.commit(Vec::new()).await.unwrap();
+        bob.process_pending_commit().await.unwrap();
+
+        observer
+            .process_incoming_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        assert_eq!(observer.group_context(), bob.group.context());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn trailing_blank_nodes_are_accepted() {
+        let alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let mut state = exported_state(&alice);
+
+        let mut nodes = alice.group.export_tree().0.into_owned();
+        nodes.push(None);
+        nodes.push(None);
+
+        state.ratchet_tree = nodes.mls_encode_to_vec().unwrap();
+
+        let observer = TestExternalClientBuilder::new_for_test()
+            .build()
+            .observe_openmls_group(state)
+            .await
+            .unwrap();
+
+        assert_eq!(observer.roster().members().len(), 1);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn tree_not_matching_context_is_rejected() {
+        let alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob, _) = alice.clone().join("bob").await;
+
+        let mut state = exported_state(&alice);
+        state.ratchet_tree = bob.group.export_tree().to_bytes().unwrap();
+
+        let res = TestExternalClientBuilder::new_for_test()
+            .build()
+            .observe_openmls_group(state)
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::TreeHashMismatch));
+    }
+}
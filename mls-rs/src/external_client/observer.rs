@@ -0,0 +1,359 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use mls_rs_core::{
+    error::IntoAnyError,
+    group::{Member, MemberUpdate},
+    identity::IdentityProvider,
+};
+
+use crate::{
+    client::MlsError,
+    external_client::{ExternalClientConfig, ExternalGroup, ExternalReceivedMessage},
+    MlsMessage,
+};
+
+/// Changes to the membership of a group caused by a single commit.
+///
+/// Members are matched by index. A leaf whose identity, as determined by
+/// the [`IdentityProvider`] of the observing client, changed is reported as
+/// one member leaving and another joining. A leaf whose identity stayed the
+/// same but whose credential, capabilities or extensions changed is reported
+/// as updated.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct MembershipDiff {
+    /// Epoch of the group after the commit.
+    pub epoch: u64,
+    /// Index of the member who performed the commit.
+    pub committer: u32,
+    /// Members that joined the group.
+    pub joined: Vec<Member>,
+    /// Members that left the group, as they were before the commit.
+    pub left: Vec<Member>,
+    /// Members that remained in the group with a new leaf.
+    pub updated: Vec<MemberUpdate>,
+}
+
+impl MembershipDiff {
+    /// Returns true if the commit did not change the membership of the group.
+    pub fn is_empty(&self) -> bool {
+        self.joined.is_empty() && self.left.is_empty() && self.updated.is_empty()
+    }
+}
+
+/// Callback invoked by an [`ObserverGroup`] after each processed commit.
+pub trait MembershipObserver {
+    /// Called with the membership changes of a commit once the commit has
+    /// been applied to the observed group. This function is called for
+    /// every commit, even if the diff is empty.
+    fn membership_changed(&mut self, diff: MembershipDiff);
+}
+
+impl<F> MembershipObserver for F
+where
+    F: FnMut(MembershipDiff),
+{
+    fn membership_changed(&mut self, diff: MembershipDiff) {
+        self(diff)
+    }
+}
+
+/// An [`ExternalGroup`] that reports membership changes to a
+/// [`MembershipObserver`].
+///
+/// This is intended for servers that want to drive webhooks or compliance
+/// logs from the control messages of a group without being a member.
+/// The same limitations as with [`ExternalGroup`] apply, in particular
+/// control messages must be sent unencrypted.
+pub struct ObserverGroup<C, O>
+where
+    C: ExternalClientConfig,
+{
+    group: ExternalGroup<C>,
+    observer: O,
+}
+
+impl<C, O> ObserverGroup<C, O>
+where
+    C: ExternalClientConfig + Clone,
+    O: MembershipObserver,
+{
+    /// Report the membership changes of `group` to `observer`.
+    pub fn new(group: ExternalGroup<C>, observer: O) -> Self {
+        Self { group, observer }
+    }
+
+    /// Process a message that was sent to the group with
+    /// [`ExternalGroup::process_incoming_message`], and notify the observer
+    /// if the message is a commit.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn process_incoming_message(
+        &mut self,
+        message: MlsMessage,
+    ) -> Result<ExternalReceivedMessage, MlsError> {
+        let prior_members = self.group.roster().members();
+        let received = self.group.process_incoming_message(message).await?;
+
+        if let ExternalReceivedMessage::Commit(description) = &received {
+            let diff = self
+                .membership_diff(prior_members, description.committer)
+                .await?;
+
+            self.observer.membership_changed(diff);
+        }
+
+        Ok(received)
+    }
+
+    /// The observed group.
+    pub fn group(&self) -> &ExternalGroup<C> {
+        &self.group
+    }
+
+    /// Mutable access to the observed group.
+    ///
+    /// Messages processed directly by the group are not reported to the
+    /// observer.
+    pub fn group_mut(&mut self) -> &mut ExternalGroup<C> {
+        &mut self.group
+    }
+
+    /// The membership observer.
+    pub fn observer(&self) -> &O {
+        &self.observer
+    }
+
+    /// Split into the observed group and the membership observer.
+    pub fn into_parts(self) -> (ExternalGroup<C>, O) {
+        (self.group, self.observer)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn membership_diff(
+        &self,
+        prior_members: Vec<Member>,
+        committer: u32,
+    ) -> Result<MembershipDiff, MlsError> {
+        let mut prior_members = prior_members
+            .into_iter()
+            .map(|member| (member.index, member))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut diff = MembershipDiff {
+            epoch: self.group.group_context().epoch,
+            committer,
+            ..Default::default()
+        };
+
+        for member in self.group.roster().members() {
+            match prior_members.remove(&member.index) {
+                None => diff.joined.push(member),
+                Some(prior) if prior == member => (),
+                Some(prior) if self.same_identity(&prior, &member).await? => {
+                    diff.updated.push(MemberUpdate::new(prior, member))
+                }
+                Some(prior) => {
+                    diff.left.push(prior);
+                    diff.joined.push(member);
+                }
+            }
+        }
+
+        diff.left.extend(prior_members.into_values());
+        diff.left.sort_by_key(|member| member.index);
+
+        Ok(diff)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn same_identity(&self, prior: &Member, new: &Member) -> Result<bool, MlsError> {
+        if prior.signing_identity == new.signing_identity {
+            return Ok(true);
+        }
+
+        let identity_provider = self.group.config.identity_provider();
+        let extensions = self.group.group_context().extensions();
+
+        let prior_identity = identity_provider
+            .identity(&prior.signing_identity, extensions)
+            .await
+            .map_err(|e| MlsError::IdentityProviderError(e.into_any_error()))?;
+
+        let new_identity = identity_provider
+            .identity(&new.signing_identity, extensions)
+            .await
+            .map_err(|e| MlsError::IdentityProviderError(e.into_any_error()))?;
+
+        Ok(prior_identity == new_identity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::{MembershipDiff, MembershipObserver, ObserverGroup};
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        external_client::{
+            group::test_utils::make_external_group, tests_utils::TestExternalClientConfig,
+        },
+        group::test_utils::{test_group, TestGroup},
+        identity::test_utils::get_test_signing_identity,
+        key_package::test_utils::test_key_package_message,
+    };
+
+    #[derive(Default)]
+    struct Recorder(Vec<MembershipDiff>);
+
+    impl MembershipObserver for Recorder {
+        fn membership_changed(&mut self, diff: MembershipDiff) {
+            self.0.push(diff)
+        }
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn setup() -> (TestGroup, ObserverGroup<TestExternalClientConfig, Recorder>) {
+        let alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let server = make_external_group(&alice).await;
+
+        (alice, ObserverGroup::new(server, Recorder::default()))
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn observer_reports_joined_and_left_members() {
+        let (mut alice, mut server) = setup().await;
+
+        let key_package =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.process_pending_commit().await.unwrap();
+
+        server
+            .process_incoming_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        let bob = alice.group.roster().member_with_index(1).unwrap();
+        let diff = server.observer().0.last().unwrap();
+
+        assert_eq!(diff.joined, vec![bob.clone()]);
+        assert!(diff.left.is_empty());
+        assert!(diff.updated.is_empty());
+        assert_eq!(diff.committer, 0);
+        assert_eq!(diff.epoch, alice.group.current_epoch());
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .remove_member(1)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.process_pending_commit().await.unwrap();
+
+        server
+            .process_incoming_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        let diff = server.observer().0.last().unwrap();
+
+        assert_eq!(diff.left, vec![bob]);
+        assert!(diff.joined.is_empty());
+        assert!(diff.updated.is_empty());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn observer_reports_updated_members() {
+        let (mut alice, mut server) = setup().await;
+
+        let prior = alice.group.roster().member_with_index(0).unwrap();
+
+        let (identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"member").await;
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .set_new_signing_identity(secret_key, identity)
+            .build()
+            .await
+            .unwrap();
+
+        alice.process_pending_commit().await.unwrap();
+
+        server
+            .process_incoming_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        let diff = server.observer().0.last().unwrap();
+
+        assert!(diff.joined.is_empty());
+        assert!(diff.left.is_empty());
+        assert_eq!(diff.updated.len(), 1);
+        assert_eq!(diff.updated[0].prior, prior);
+        assert_eq!(
+            diff.updated[0].new,
+            alice.group.roster().member_with_index(0).unwrap()
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn observer_is_notified_of_every_commit() {
+        let (mut alice, mut server) = setup().await;
+
+        for _ in 0..3 {
+            let commit = alice.group.commit(vec![]).await.unwrap();
+            alice.process_pending_commit().await.unwrap();
+
+            server
+                .process_incoming_message(commit.commit_message)
+                .await
+                .unwrap();
+        }
+
+        let diffs = &server.observer().0;
+
+        assert_eq!(diffs.len(), 3);
+        assert!(diffs.iter().all(MembershipDiff::is_empty));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn closures_can_observe_membership() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let mut diffs = Vec::new();
+
+        let mut server =
+            ObserverGroup::new(make_external_group(&alice).await, |diff| diffs.push(diff));
+
+        let commit = alice.group.commit(vec![]).await.unwrap();
+
+        server
+            .process_incoming_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        drop(server);
+
+        assert_eq!(diffs.len(), 1);
+    }
+}
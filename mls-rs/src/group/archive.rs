@@ -0,0 +1,131 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::{client::MlsError, client_config::ClientConfig, group::Group};
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Turn the group into a read-only archive, e.g. once it was closed or
+    /// replaced by a new group.
+    ///
+    /// An archived group can still be queried, written to storage and used
+    /// to decrypt application messages of its current epoch and of the prior
+    /// epochs that are retained. Creating commits, proposals or application
+    /// messages and processing received commits or proposals fails with
+    /// [`MlsError::GroupArchived`]. The pending commit and pending updates are
+    /// discarded. Archiving is persisted and cannot be undone.
+    ///
+    /// Groups are also archived once a ReInit is applied and, if enabled with
+    /// [`ClientBuilder::archive_removed_groups`](crate::client_builder::ClientBuilder::archive_removed_groups),
+    /// once this member is removed.
+    pub fn archive(&mut self) {
+        self.archived = true;
+        self.pending_commit = None;
+
+        #[cfg(feature = "by_ref_proposal")]
+        {
+            self.pending_updates = Default::default();
+            self.state.proposals.clear();
+        }
+    }
+
+    /// Determines if the group is a read-only archive, see [`Group::archive`].
+    pub fn is_archived(&self) -> bool {
+        self.archived || self.state.pending_reinit.is_some()
+    }
+
+    pub(crate) fn check_not_archived(&self) -> Result<(), MlsError> {
+        if self.is_archived() {
+            return Err(MlsError::GroupArchived);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::test_utils::test_group,
+        group::Validated,
+    };
+
+    #[cfg(feature = "private_message")]
+    use crate::group::ReceivedMessage;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn removed_member_keeps_archive() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let (mut bob, _) = alice
+            .join_with_custom_config("bob", false, |c| c.0.settings.archive_removed_groups = true)
+            .await
+            .unwrap();
+
+        #[cfg(feature = "private_message")]
+        let message = alice
+            .group
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .remove_member(1)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        bob.process_message(commit.commit_message).await.unwrap();
+
+        assert!(bob.group.is_archived());
+        bob.group.write_to_storage().await.unwrap();
+
+        #[cfg(feature = "private_message")]
+        {
+            let res = bob
+                .group
+                .process_incoming_message_validated(Validated::new(message).unwrap())
+                .await;
+
+            assert_matches!(
+                res,
+                Ok(ReceivedMessage::ApplicationMessage(m)) if m.data() == b"hello"
+            );
+        }
+
+        let res = bob.group.commit(vec![]).await;
+        assert_matches!(res, Err(MlsError::GroupArchived));
+
+        #[cfg(feature = "private_message")]
+        {
+            let res = bob.group.encrypt_application_message(b"hi", vec![]).await;
+            assert_matches!(res, Err(MlsError::GroupArchived));
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn archived_group_rejects_commits() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        bob.group.archive();
+
+        let commit = alice.group.commit(vec![]).await.unwrap();
+        let res = bob.process_message(commit.commit_message).await;
+
+        assert_matches!(res, Err(MlsError::GroupArchived));
+    }
+}
@@ -0,0 +1,183 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+
+use crate::{
+    client::MlsError,
+    group::{framing::MlsMessagePayload, CommitOutput, MlsMessage},
+    KeyPackageRef,
+};
+
+/// Container bundling the messages produced by a commit, exchanged as a
+/// single blob between clients and the delivery service.
+///
+/// The envelope holds the commit message for the existing members, the
+/// welcome messages for new members with the key package references they are
+/// addressed to, and optionally the group info allowing external commits.
+/// The encoding is specific to this library and is not part of the MLS
+/// protocol.
+#[derive(Clone, Debug, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+#[non_exhaustive]
+pub struct CommitEnvelope {
+    /// Commit message for the existing members of the group.
+    pub commit_message: MlsMessage,
+    /// Welcome messages for the members added by the commit.
+    pub welcome_messages: Vec<RoutedWelcome>,
+    /// Group info of the new epoch, see
+    /// [`CommitOutput::external_commit_group_info`].
+    pub group_info: Option<MlsMessage>,
+}
+
+/// Welcome message with the references of the key packages of the new
+/// members it is addressed to, used to route it.
+#[derive(Clone, Debug, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+#[non_exhaustive]
+pub struct RoutedWelcome {
+    /// References of the key packages of the recipients.
+    pub key_package_refs: Vec<KeyPackageRef>,
+    /// The welcome message.
+    pub welcome_message: MlsMessage,
+}
+
+impl RoutedWelcome {
+    /// Wrap `welcome_message` with the key package references read from it.
+    ///
+    /// Returns [`MlsError::UnexpectedMessageType`] if `welcome_message` is not
+    /// a welcome message.
+    pub fn new(welcome_message: MlsMessage) -> Result<Self, MlsError> {
+        if !matches!(welcome_message.payload, MlsMessagePayload::Welcome(_)) {
+            return Err(MlsError::UnexpectedMessageType);
+        }
+
+        Ok(Self {
+            key_package_refs: welcome_message
+                .welcome_key_package_references()
+                .into_iter()
+                .cloned()
+                .collect(),
+            welcome_message,
+        })
+    }
+}
+
+impl CommitEnvelope {
+    /// Bundle `commit_message`, `welcome_messages` and `group_info`.
+    pub fn new(
+        commit_message: MlsMessage,
+        welcome_messages: Vec<MlsMessage>,
+        group_info: Option<MlsMessage>,
+    ) -> Result<Self, MlsError> {
+        Ok(Self {
+            commit_message,
+            welcome_messages: welcome_messages
+                .into_iter()
+                .map(RoutedWelcome::new)
+                .collect::<Result<_, _>>()?,
+            group_info,
+        })
+    }
+
+    /// Bundle the messages of `commit_output`.
+    pub fn from_commit_output(commit_output: &CommitOutput) -> Result<Self, MlsError> {
+        Self::new(
+            commit_output.commit_message.clone(),
+            commit_output.welcome_messages.clone(),
+            commit_output.external_commit_group_info.clone(),
+        )
+    }
+
+    /// Welcome message addressed to the owner of the key package with
+    /// reference `key_package_ref`, if any.
+    pub fn welcome_for(&self, key_package_ref: &KeyPackageRef) -> Option<&MlsMessage> {
+        self.welcome_messages
+            .iter()
+            .find(|w| w.key_package_refs.contains(key_package_ref))
+            .map(|w| &w.welcome_message)
+    }
+
+    /// Deserialize an envelope from transport.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    /// Serialize an envelope for transport.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{test_client_with_key_pkg, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        crypto::test_utils::test_cipher_suite_provider,
+        group::test_utils::test_group_custom,
+        group::Validated,
+        mls_rules::CommitOptions,
+    };
+
+    use super::{CommitEnvelope, RoutedWelcome};
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn envelope_round_trips_and_routes_welcomes() {
+        let mut alice = test_group_custom(
+            TEST_PROTOCOL_VERSION,
+            TEST_CIPHER_SUITE,
+            Default::default(),
+            None,
+            Some(
+                CommitOptions::new()
+                    .with_single_welcome_message(false)
+                    .with_allow_external_commit(true),
+            ),
+        )
+        .await;
+
+        let (bob, bob_kp) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let (_, carol_kp) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "carol").await;
+
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let bob_ref = bob_kp.key_package_reference(&cs).await.unwrap().unwrap();
+        let carol_ref = carol_kp.key_package_reference(&cs).await.unwrap().unwrap();
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .add_member(bob_kp)
+            .unwrap()
+            .add_member(carol_kp)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let envelope = CommitEnvelope::from_commit_output(&commit).unwrap();
+        let envelope = CommitEnvelope::from_bytes(&envelope.to_bytes().unwrap()).unwrap();
+
+        assert_eq!(envelope.commit_message, commit.commit_message);
+        assert_eq!(envelope.welcome_messages.len(), 2);
+        assert!(envelope.group_info.is_some());
+
+        let bob_welcome = envelope.welcome_for(&bob_ref).unwrap();
+        assert_ne!(Some(bob_welcome), envelope.welcome_for(&carol_ref));
+
+        bob.join_group_validated(None, &Validated::new(bob_welcome.clone()).unwrap())
+            .await
+            .unwrap();
+
+        let res = RoutedWelcome::new(commit.commit_message);
+        assert_matches!(res, Err(MlsError::UnexpectedMessageType));
+    }
+}
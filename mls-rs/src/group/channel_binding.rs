@@ -0,0 +1,214 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use mls_rs_codec::{MlsEncode, MlsSize};
+use mls_rs_core::{crypto::CipherSuiteProvider, identity::SigningIdentity};
+
+use crate::{
+    client::MlsError, client_config::ClientConfig, group::Group, tree_kem::node::LeafIndex,
+};
+
+const EXPORTER_LABEL: &[u8] = b"channel binding";
+
+/// Value binding an external authentication to a member of a group in an
+/// epoch, computed with [`Group::channel_binding`].
+///
+/// The value is exported from the epoch and is bound to the transcript of the
+/// group, i.e. both transcript hashes, and to the leaf index and signing
+/// identity of the member. A member authenticating with an external system,
+/// e.g. with an OIDC token or a WebAuthn assertion, includes the value in the
+/// authentication, as the token nonce or the assertion challenge, and sends
+/// the result to the group. Other members verify the authentication with the
+/// external system and then check with [`Group::verify_channel_binding`] that
+/// it was created for the sender in the current epoch. This prevents
+/// replaying the authentication in another group or epoch, or for another
+/// member.
+#[derive(Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ChannelBinding {
+    /// Epoch in which the value was computed.
+    pub epoch: u64,
+    /// Leaf index of the member the value is bound to.
+    pub member_index: u32,
+    /// Application label provided when computing the value.
+    pub label: Vec<u8>,
+    /// The channel binding value.
+    pub value: Vec<u8>,
+}
+
+impl Debug for ChannelBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChannelBinding")
+            .field("epoch", &self.epoch)
+            .field("member_index", &self.member_index)
+            .field("label", &mls_rs_core::debug::pretty_bytes(&self.label))
+            .field("value", &mls_rs_core::debug::pretty_bytes(&self.value))
+            .finish()
+    }
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct ChannelBindingContext<'a> {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    label: &'a [u8],
+    epoch: u64,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    confirmed_transcript_hash: &'a [u8],
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    interim_transcript_hash: &'a [u8],
+    member_index: u32,
+    signing_identity: &'a SigningIdentity,
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Compute the [`ChannelBinding`] of the local member in the current
+    /// epoch for the application defined `label`.
+    ///
+    /// Distinct labels should be used for distinct external authentication
+    /// mechanisms.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn channel_binding(&self, label: &[u8]) -> Result<ChannelBinding, MlsError> {
+        self.member_channel_binding(self.current_member_index(), label)
+            .await
+    }
+
+    /// Compute the [`ChannelBinding`] of the member at `member_index` in the
+    /// current epoch for the application defined `label`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn member_channel_binding(
+        &self,
+        member_index: u32,
+        label: &[u8],
+    ) -> Result<ChannelBinding, MlsError> {
+        let leaf = self
+            .state
+            .public_tree
+            .get_leaf_node(LeafIndex(member_index))?;
+
+        let context = self.context();
+
+        let binding_context = ChannelBindingContext {
+            label,
+            epoch: context.epoch,
+            confirmed_transcript_hash: &context.confirmed_transcript_hash,
+            interim_transcript_hash: &self.state.interim_transcript_hash,
+            member_index,
+            signing_identity: &leaf.signing_identity,
+        }
+        .mls_encode_to_vec()?;
+
+        let len = self.cipher_suite_provider.kdf_extract_size();
+
+        let value = self
+            .export_secret(EXPORTER_LABEL, &binding_context, len)
+            .await?
+            .as_bytes()
+            .to_vec();
+
+        Ok(ChannelBinding {
+            epoch: context.epoch,
+            member_index,
+            label: label.to_vec(),
+            value,
+        })
+    }
+
+    /// Check that `value`, extracted from an external authentication sent by
+    /// the member at `member_index`, is the [`ChannelBinding`] value of that
+    /// member in the current epoch for `label`.
+    ///
+    /// The external authentication itself must be verified separately, and
+    /// `member_index` must be the authenticated sender of the message carrying
+    /// it, e.g. as reported in
+    /// [`ApplicationMessageDescription::sender_index`](crate::group::ApplicationMessageDescription::sender_index).
+    /// A binding computed in a previous epoch is not valid anymore, so
+    /// external authentications must be renewed after each commit.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn verify_channel_binding(
+        &self,
+        member_index: u32,
+        label: &[u8],
+        value: &[u8],
+    ) -> Result<bool, MlsError> {
+        let binding = self.member_channel_binding(member_index, label).await?;
+
+        Ok(binding.value == value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::test_utils::test_group,
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn members_can_verify_channel_binding() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob, _) = alice.join("bob").await;
+
+        let binding = bob.group.channel_binding(b"oidc").await.unwrap();
+        assert_eq!(binding.member_index, 1);
+
+        let res = alice
+            .group
+            .verify_channel_binding(1, b"oidc", &binding.value)
+            .await;
+
+        assert!(res.unwrap());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn channel_binding_is_bound_to_member_label_and_epoch() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob, _) = alice.join("bob").await;
+
+        let binding = bob.group.channel_binding(b"oidc").await.unwrap();
+
+        let res = alice
+            .group
+            .verify_channel_binding(0, b"oidc", &binding.value)
+            .await;
+
+        assert!(!res.unwrap());
+
+        let res = alice
+            .group
+            .verify_channel_binding(1, b"webauthn", &binding.value)
+            .await;
+
+        assert!(!res.unwrap());
+
+        alice.group.commit(vec![]).await.unwrap();
+        alice.process_pending_commit().await.unwrap();
+
+        let res = alice
+            .group
+            .verify_channel_binding(1, b"oidc", &binding.value)
+            .await;
+
+        assert!(!res.unwrap());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn channel_binding_requires_existing_member() {
+        let alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let res = alice.group.member_channel_binding(5, b"oidc").await;
+        assert_matches!(res, Err(MlsError::InvalidNodeIndex(_)));
+    }
+}
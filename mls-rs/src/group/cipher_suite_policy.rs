@@ -0,0 +1,193 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use crate::{client::MlsError, protocol_version::ProtocolVersion, CipherSuite};
+
+/// Client-wide policy on the cipher suites and protocol versions of groups.
+///
+/// Unlike the cipher suites supported by the
+/// [`CryptoProvider`](crate::CryptoProvider), the policy lets security teams
+/// centrally forbid weak cipher suites or protocol versions without changing
+/// the provider. It is checked when creating a group, when joining a group
+/// with a welcome message, when adding a key package to a group and when
+/// creating or processing a ReInit proposal. Violations are reported as
+/// [`MlsError::CipherSuiteNotAllowed`] and
+/// [`MlsError::ProtocolVersionNotAllowed`]. By default, everything is allowed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CipherSuitePolicy {
+    allowed_cipher_suites: Option<Vec<CipherSuite>>,
+    denied_cipher_suites: Vec<CipherSuite>,
+    allowed_protocol_versions: Option<Vec<ProtocolVersion>>,
+    denied_protocol_versions: Vec<ProtocolVersion>,
+}
+
+impl CipherSuitePolicy {
+    /// Create a policy allowing any cipher suite and protocol version.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Only allow the cipher suites in `allowed`.
+    pub fn with_allowed_cipher_suites(self, allowed: Vec<CipherSuite>) -> Self {
+        Self {
+            allowed_cipher_suites: Some(allowed),
+            ..self
+        }
+    }
+
+    /// Forbid the cipher suites in `denied`, even if they are allowed by
+    /// [`CipherSuitePolicy::with_allowed_cipher_suites`].
+    pub fn with_denied_cipher_suites(self, denied: Vec<CipherSuite>) -> Self {
+        Self {
+            denied_cipher_suites: denied,
+            ..self
+        }
+    }
+
+    /// Only allow the protocol versions in `allowed`.
+    pub fn with_allowed_protocol_versions(self, allowed: Vec<ProtocolVersion>) -> Self {
+        Self {
+            allowed_protocol_versions: Some(allowed),
+            ..self
+        }
+    }
+
+    /// Forbid the protocol versions in `denied`, even if they are allowed by
+    /// [`CipherSuitePolicy::with_allowed_protocol_versions`].
+    pub fn with_denied_protocol_versions(self, denied: Vec<ProtocolVersion>) -> Self {
+        Self {
+            denied_protocol_versions: denied,
+            ..self
+        }
+    }
+
+    /// Determines if `cipher_suite` is allowed.
+    pub fn allows_cipher_suite(&self, cipher_suite: CipherSuite) -> bool {
+        is_allowed(
+            &self.allowed_cipher_suites,
+            &self.denied_cipher_suites,
+            &cipher_suite,
+        )
+    }
+
+    /// Determines if `version` is allowed.
+    pub fn allows_protocol_version(&self, version: ProtocolVersion) -> bool {
+        is_allowed(
+            &self.allowed_protocol_versions,
+            &self.denied_protocol_versions,
+            &version,
+        )
+    }
+
+    /// Check that a group may use `version` and `cipher_suite`.
+    pub fn check(
+        &self,
+        version: ProtocolVersion,
+        cipher_suite: CipherSuite,
+    ) -> Result<(), MlsError> {
+        if !self.allows_protocol_version(version) {
+            return Err(MlsError::ProtocolVersionNotAllowed(version));
+        }
+
+        if !self.allows_cipher_suite(cipher_suite) {
+            return Err(MlsError::CipherSuiteNotAllowed(cipher_suite));
+        }
+
+        Ok(())
+    }
+}
+
+fn is_allowed<T: PartialEq>(allowed: &Option<Vec<T>>, denied: &[T], value: &T) -> bool {
+    allowed
+        .as_ref()
+        .map_or(true, |allowed| allowed.contains(value))
+        && !denied.contains(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use super::CipherSuitePolicy;
+
+    use crate::{
+        client::{
+            test_utils::{TestClientBuilder, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::test_utils::test_group,
+        key_package::test_utils::test_key_package_message,
+        CipherSuite,
+    };
+
+    #[test]
+    fn denylist_overrides_allowlist() {
+        let policy = CipherSuitePolicy::new()
+            .with_allowed_cipher_suites(vec![
+                CipherSuite::CURVE25519_AES128,
+                CipherSuite::P256_AES128,
+            ])
+            .with_denied_cipher_suites(vec![CipherSuite::P256_AES128]);
+
+        assert!(policy.allows_cipher_suite(CipherSuite::CURVE25519_AES128));
+        assert!(!policy.allows_cipher_suite(CipherSuite::P256_AES128));
+        assert!(!policy.allows_cipher_suite(CipherSuite::CURVE448_AES256));
+        assert!(policy.allows_protocol_version(TEST_PROTOCOL_VERSION));
+
+        assert_matches!(
+            policy.check(TEST_PROTOCOL_VERSION, CipherSuite::P256_AES128),
+            Err(MlsError::CipherSuiteNotAllowed(cs)) if cs == CipherSuite::P256_AES128
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn denied_cipher_suite_is_rejected() {
+        let policy = CipherSuitePolicy::new().with_denied_cipher_suites(vec![TEST_CIPHER_SUITE]);
+
+        let client = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .cipher_suite_policy(policy.clone())
+            .build();
+
+        let res = client.create_group(Default::default()).await.map(|_| ());
+
+        assert_matches!(res, Err(MlsError::CipherSuiteNotAllowed(cs)) if cs == TEST_CIPHER_SUITE);
+
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let res = alice
+            .join_with_custom_config("bob", false, |c| {
+                c.0.settings.cipher_suite_policy = policy.clone()
+            })
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::CipherSuiteNotAllowed(cs)) if cs == TEST_CIPHER_SUITE);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn denied_protocol_version_is_rejected_for_key_packages() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        // The policy was changed after the group was created, e.g. when the
+        // group is loaded by a client with a new configuration.
+        alice.group.config.0.settings.cipher_suite_policy =
+            CipherSuitePolicy::new().with_denied_protocol_versions(vec![TEST_PROTOCOL_VERSION]);
+
+        let key_package =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let res = alice.group.commit_builder().add_member(key_package);
+
+        assert_matches!(
+            res.map(|_| ()),
+            Err(MlsError::ProtocolVersionNotAllowed(v)) if v == TEST_PROTOCOL_VERSION
+        );
+    }
+}
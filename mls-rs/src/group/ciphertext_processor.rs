@@ -5,22 +5,22 @@
 use self::{
     message_key::MessageKey,
     reuse_guard::ReuseGuard,
-    sender_data_key::{SenderData, SenderDataAAD, SenderDataKey},
+    sender_data_key::{SenderDataAAD, SenderDataKey},
 };
 
 use super::{
     epoch::EpochSecrets,
     framing::{ContentType, FramedContent, Sender, WireFormat},
     message_signature::AuthenticatedContent,
-    padding::PaddingMode,
-    secret_tree::{KeyType, MessageKeyData},
+    padding::{LengthHistogram, PaddingMode},
+    secret_tree::{KeyType, MessageKeyData, MAX_RATCHET_BACK_HISTORY},
     GroupContext,
 };
 use crate::{
     client::MlsError,
     tree_kem::node::{LeafIndex, NodeIndex},
 };
-use mls_rs_codec::MlsEncode;
+use mls_rs_codec::{MlsEncode, MlsSize};
 use mls_rs_core::{crypto::CipherSuiteProvider, error::IntoAnyError};
 use zeroize::Zeroizing;
 
@@ -31,6 +31,8 @@ mod sender_data_key;
 #[cfg(feature = "private_message")]
 use super::framing::{PrivateContentAAD, PrivateMessage, PrivateMessageContent};
 
+pub(crate) use sender_data_key::{SenderData, SenderDataKeyCache};
+
 #[cfg(test)]
 pub use sender_data_key::test_utils::*;
 
@@ -39,6 +41,27 @@ pub(crate) trait GroupStateProvider {
     fn self_index(&self) -> LeafIndex;
     fn epoch_secrets_mut(&mut self) -> &mut EpochSecrets;
     fn epoch_secrets(&self) -> &EpochSecrets;
+
+    #[cfg(feature = "out_of_order")]
+    fn max_retained_message_keys(&self) -> Option<usize> {
+        None
+    }
+
+    /// Maximum number of generations a received message may skip.
+    fn max_generation_jump(&self) -> u32 {
+        MAX_RATCHET_BACK_HISTORY
+    }
+
+    /// Cache of the sender data keys derived to open received messages.
+    fn sender_data_key_cache(&mut self) -> Option<&mut SenderDataKeyCache> {
+        None
+    }
+
+    /// Sizes of the messages sent and received in the group, used by
+    /// [`PaddingMode::Adaptive`].
+    fn length_histogram(&mut self) -> Option<&mut LengthHistogram> {
+        None
+    }
 }
 
 pub(crate) struct CiphertextProcessor<'a, GS, CP>
@@ -48,6 +71,9 @@ where
 {
     group_state: &'a mut GS,
     cipher_suite_provider: CP,
+    #[cfg(feature = "application_lanes")]
+    application_lane: u16,
+    sender_data: Option<SenderData>,
 }
 
 impl<'a, GS, CP> CiphertextProcessor<'a, GS, CP>
@@ -62,6 +88,37 @@ where
         Self {
             group_state,
             cipher_suite_provider,
+            #[cfg(feature = "application_lanes")]
+            application_lane: 0,
+            sender_data: None,
+        }
+    }
+
+    /// Use `sender_data` previously returned by
+    /// [`open_sender_data`](Self::open_sender_data) for the message instead
+    /// of decrypting its sender data again.
+    pub fn with_sender_data(self, sender_data: Option<SenderData>) -> Self {
+        Self {
+            sender_data,
+            ..self
+        }
+    }
+
+    #[cfg(feature = "application_lanes")]
+    pub fn with_application_lane(self, application_lane: u16) -> Self {
+        Self {
+            application_lane,
+            ..self
+        }
+    }
+
+    fn key_type(&self, content_type: ContentType) -> KeyType {
+        match content_type {
+            #[cfg(feature = "application_lanes")]
+            ContentType::Application => KeyType::application_lane(self.application_lane),
+            #[cfg(not(feature = "application_lanes"))]
+            ContentType::Application => KeyType::Application,
+            _ => KeyType::Handshake,
         }
     }
 
@@ -88,11 +145,28 @@ where
     ) -> Result<MessageKeyData, MlsError> {
         let sender = NodeIndex::from(sender);
 
-        self.group_state
-            .epoch_secrets_mut()
-            .secret_tree
-            .message_key_generation(&self.cipher_suite_provider, sender, key_type, generation)
-            .await
+        #[cfg(feature = "out_of_order")]
+        let max_retained_keys = self.group_state.max_retained_message_keys();
+
+        let max_jump = self.group_state.max_generation_jump();
+        let secret_tree = &mut self.group_state.epoch_secrets_mut().secret_tree;
+
+        let key = secret_tree
+            .message_key_generation(
+                &self.cipher_suite_provider,
+                sender,
+                key_type,
+                generation,
+                max_jump,
+            )
+            .await?;
+
+        #[cfg(feature = "out_of_order")]
+        if let Some(max_keys) = max_retained_keys {
+            secret_tree.retain_message_keys(&sender, max_keys);
+        }
+
+        Ok(key)
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -127,15 +201,20 @@ where
             .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
 
         // Grab an encryption key from the current epoch's key schedule
-        let key_type = match &content_type {
-            ContentType::Application => KeyType::Application,
-            _ => KeyType::Handshake,
-        };
+        let key_type = self.key_type(content_type);
 
         let mut serialized_private_content = private_content.mls_encode_to_vec()?;
 
         // Apply padding to private content based on the current padding mode.
-        serialized_private_content.resize(padding.padded_size(serialized_private_content.len()), 0);
+        let content_size = serialized_private_content.len();
+        let mut history = self.group_state.length_histogram();
+        let padded_size = padding.padded_size_with_history(content_size, history.as_deref());
+
+        if let Some(history) = history.as_mut() {
+            history.record(content_size);
+        }
+
+        serialized_private_content.resize(padded_size, 0);
 
         let serialized_private_content = Zeroizing::new(serialized_private_content);
 
@@ -192,38 +271,69 @@ where
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    pub async fn open(
+    pub async fn open_sender_data(
         &mut self,
         ciphertext: &PrivateMessage,
-    ) -> Result<AuthenticatedContent, MlsError> {
+    ) -> Result<SenderData, MlsError> {
         // Decrypt the sender data with the derived sender_key and sender_nonce from the message
         // epoch's key schedule
+        let epoch = self.group_state.group_context().epoch;
+
         let sender_data_aad = SenderDataAAD {
             group_id: self.group_state.group_context().group_id.clone(),
-            epoch: self.group_state.group_context().epoch,
+            epoch,
             content_type: ciphertext.content_type,
         };
 
-        let sender_data_key = SenderDataKey::new(
-            &self.group_state.epoch_secrets().sender_data_secret,
-            &ciphertext.ciphertext,
-            &self.cipher_suite_provider,
-        )
-        .await?;
+        let ciphertext_sample =
+            SenderDataKey::ciphertext_sample(&ciphertext.ciphertext, &self.cipher_suite_provider);
+
+        let cached = self
+            .group_state
+            .sender_data_key_cache()
+            .and_then(|cache| cache.get(epoch, ciphertext_sample, &self.cipher_suite_provider));
+
+        let sender_data_key = match cached {
+            Some(sender_data_key) => sender_data_key,
+            None => {
+                let sender_data_key = SenderDataKey::new(
+                    &self.group_state.epoch_secrets().sender_data_secret,
+                    &ciphertext.ciphertext,
+                    &self.cipher_suite_provider,
+                )
+                .await?;
+
+                if let Some(cache) = self.group_state.sender_data_key_cache() {
+                    cache.insert(epoch, ciphertext_sample, &sender_data_key);
+                }
+
+                sender_data_key
+            }
+        };
 
-        let sender_data = sender_data_key
+        sender_data_key
             .open(&ciphertext.encrypted_sender_data, &sender_data_aad)
-            .await?;
+            .await
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    /// Decrypt `ciphertext`, returning its content and the generation of the
+    /// key of the sender ratchet that decrypted it.
+    pub async fn open(
+        &mut self,
+        ciphertext: &PrivateMessage,
+    ) -> Result<(AuthenticatedContent, u32), MlsError> {
+        let sender_data = match self.sender_data.take() {
+            Some(sender_data) => sender_data,
+            None => self.open_sender_data(ciphertext).await?,
+        };
 
         if self.group_state.self_index() == sender_data.sender {
             return Err(MlsError::CantProcessMessageFromSelf);
         }
 
         // Grab a decryption key from the message epoch's key schedule
-        let key_type = match &ciphertext.content_type {
-            ContentType::Application => KeyType::Application,
-            _ => KeyType::Handshake,
-        };
+        let key_type = self.key_type(ciphertext.content_type);
 
         // Decrypt the content of the message using the grabbed key
         let key = self
@@ -245,6 +355,10 @@ where
         let ciphertext_content =
             PrivateMessageContent::mls_decode(&mut &**decrypted_content, ciphertext.content_type)?;
 
+        if let Some(history) = self.group_state.length_histogram() {
+            history.record(ciphertext_content.mls_encoded_len());
+        }
+
         // Build the MLS plaintext object and process it
         let auth_content = AuthenticatedContent {
             wire_format: WireFormat::PrivateMessage,
@@ -258,7 +372,7 @@ where
             auth: ciphertext_content.auth,
         };
 
-        Ok(auth_content)
+        Ok((auth_content, sender_data.generation))
     }
 }
 
@@ -335,7 +449,7 @@ mod test {
 
             let mut receiver_processor = test_processor(&mut receiver_group, cipher_suite);
 
-            let decrypted = receiver_processor.open(&ciphertext).await.unwrap();
+            let (decrypted, _) = receiver_processor.open(&ciphertext).await.unwrap();
 
             assert_eq!(decrypted, test_data.content);
         }
@@ -407,4 +521,35 @@ mod test {
 
         assert!(res.is_err());
     }
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn opened_sender_data_is_reused() {
+        let mut test_data = test_data(TEST_CIPHER_SUITE).await;
+        let mut receiver_group = test_data.group.clone();
+        let mut ciphertext_processor = test_processor(&mut test_data.group, TEST_CIPHER_SUITE);
+
+        let mut ciphertext = ciphertext_processor
+            .seal(test_data.content.clone(), PaddingMode::StepFunction)
+            .await
+            .unwrap();
+
+        receiver_group.group.private_tree.self_index = LeafIndex::new(1);
+
+        let mut receiver_processor = test_processor(&mut receiver_group, TEST_CIPHER_SUITE);
+        let sender_data = receiver_processor
+            .open_sender_data(&ciphertext)
+            .await
+            .unwrap();
+
+        // The sender data is not decrypted again, so corrupting it has no
+        // effect.
+        ciphertext.encrypted_sender_data = random_bytes(ciphertext.encrypted_sender_data.len());
+
+        let (decrypted, _) = receiver_processor
+            .with_sender_data(Some(sender_data))
+            .open(&ciphertext)
+            .await
+            .unwrap();
+
+        assert_eq!(decrypted, test_data.content);
+    }
 }
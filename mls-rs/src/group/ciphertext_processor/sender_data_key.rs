@@ -17,6 +17,9 @@ use crate::{
 
 use super::ReuseGuard;
 
+/// Maximum number of sender data keys kept by a [`SenderDataKeyCache`].
+const MAX_CACHED_SENDER_DATA_KEYS: usize = 64;
+
 #[derive(Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
 pub(crate) struct SenderData {
     pub sender: LeafIndex,
@@ -68,10 +71,7 @@ impl<'a, CP: CipherSuiteProvider> SenderDataKey<'a, CP> {
         ciphertext: &[u8],
         cipher_suite_provider: &'a CP,
     ) -> Result<SenderDataKey<'a, CP>, MlsError> {
-        // Sample the first extract_size bytes of the ciphertext, and if it is shorter, just use
-        // the ciphertext itself
-        let extract_size = cipher_suite_provider.kdf_extract_size();
-        let ciphertext_sample = ciphertext.get(0..extract_size).unwrap_or(ciphertext);
+        let ciphertext_sample = Self::ciphertext_sample(ciphertext, cipher_suite_provider);
 
         // Generate a sender data key and nonce using the sender_data_secret from the current
         // epoch's key schedule
@@ -100,6 +100,17 @@ impl<'a, CP: CipherSuiteProvider> SenderDataKey<'a, CP> {
         })
     }
 
+    /// The part of `ciphertext` the sender data key is derived from.
+    pub(super) fn ciphertext_sample<'b>(
+        ciphertext: &'b [u8],
+        cipher_suite_provider: &CP,
+    ) -> &'b [u8] {
+        // Sample the first extract_size bytes of the ciphertext, and if it is shorter, just use
+        // the ciphertext itself
+        let extract_size = cipher_suite_provider.kdf_extract_size();
+        ciphertext.get(0..extract_size).unwrap_or(ciphertext)
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub(crate) async fn seal(
         &self,
@@ -136,6 +147,80 @@ impl<'a, CP: CipherSuiteProvider> SenderDataKey<'a, CP> {
     }
 }
 
+/// Sender data keys derived in one epoch, indexed by the ciphertext sample
+/// they were derived from.
+///
+/// The sender data key of a message depends on a sample of its ciphertext,
+/// so a cached key is only used again for the same message, e.g. when the
+/// sender data of a batch of messages is opened to order them before each
+/// message is processed. Message keys are never cached, they are deleted
+/// from the secret tree once used to preserve forward secrecy.
+#[derive(Clone, Default)]
+pub(crate) struct SenderDataKeyCache {
+    epoch: u64,
+    entries: Vec<CachedSenderDataKey>,
+}
+
+#[derive(Clone)]
+struct CachedSenderDataKey {
+    ciphertext_sample: Vec<u8>,
+    key: Zeroizing<Vec<u8>>,
+    nonce: Zeroizing<Vec<u8>>,
+}
+
+impl Debug for SenderDataKeyCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SenderDataKeyCache")
+            .field("epoch", &self.epoch)
+            .field("len", &self.entries.len())
+            .finish()
+    }
+}
+
+impl SenderDataKeyCache {
+    pub(super) fn get<'a, CP: CipherSuiteProvider>(
+        &self,
+        epoch: u64,
+        ciphertext_sample: &[u8],
+        cipher_suite_provider: &'a CP,
+    ) -> Option<SenderDataKey<'a, CP>> {
+        if epoch != self.epoch {
+            return None;
+        }
+
+        self.entries
+            .iter()
+            .find(|entry| entry.ciphertext_sample == ciphertext_sample)
+            .map(|entry| SenderDataKey {
+                key: entry.key.clone(),
+                nonce: entry.nonce.clone(),
+                cipher_suite_provider,
+            })
+    }
+
+    pub(super) fn insert<CP: CipherSuiteProvider>(
+        &mut self,
+        epoch: u64,
+        ciphertext_sample: &[u8],
+        sender_data_key: &SenderDataKey<'_, CP>,
+    ) {
+        if epoch != self.epoch {
+            self.epoch = epoch;
+            self.entries.clear();
+        }
+
+        if self.entries.len() == MAX_CACHED_SENDER_DATA_KEYS {
+            self.entries.remove(0);
+        }
+
+        self.entries.push(CachedSenderDataKey {
+            ciphertext_sample: ciphertext_sample.to_vec(),
+            key: sender_data_key.key.clone(),
+            nonce: sender_data_key.nonce.clone(),
+        });
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod test_utils {
     use alloc::vec::Vec;
@@ -189,7 +274,7 @@ pub(crate) mod test_utils {
 #[cfg(test)]
 mod tests {
 
-    use alloc::vec::Vec;
+    use alloc::{vec, vec::Vec};
     #[cfg(target_arch = "wasm32")]
     use wasm_bindgen_test::wasm_bindgen_test as test;
 
@@ -199,7 +284,9 @@ mod tests {
         tree_kem::node::LeafIndex,
     };
 
-    use super::{SenderData, SenderDataAAD, SenderDataKey};
+    use super::{
+        SenderData, SenderDataAAD, SenderDataKey, SenderDataKeyCache, MAX_CACHED_SENDER_DATA_KEYS,
+    };
 
     #[cfg(not(mls_build_async))]
     use crate::{
@@ -207,6 +294,9 @@ mod tests {
         group::test_utils::random_bytes, CipherSuiteProvider,
     };
 
+    #[cfg(mls_build_async)]
+    use crate::CipherSuiteProvider;
+
     #[derive(serde::Deserialize, serde::Serialize)]
     struct TestCase {
         cipher_suite: u16,
@@ -357,4 +447,35 @@ mod tests {
             assert_eq!(plaintext, sender_data);
         }
     }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn cached_keys_are_bound_to_epoch_and_sample() {
+        let provider = crate::crypto::test_utils::test_cipher_suite_provider(
+            crate::client::test_utils::TEST_CIPHER_SUITE,
+        );
+
+        let secret = vec![1u8; provider.kdf_extract_size()].into();
+        let ciphertext = vec![2u8; 100];
+
+        let sender_data_key = SenderDataKey::new(&secret, &ciphertext, &provider)
+            .await
+            .unwrap();
+
+        let sample = SenderDataKey::ciphertext_sample(&ciphertext, &provider);
+        let mut cache = SenderDataKeyCache::default();
+        cache.insert(3, sample, &sender_data_key);
+
+        let cached = cache.get(3, sample, &provider).unwrap();
+        assert_eq!(cached.key, sender_data_key.key);
+        assert_eq!(cached.nonce, sender_data_key.nonce);
+
+        assert!(cache.get(4, sample, &provider).is_none());
+        assert!(cache.get(3, &[0; 4], &provider).is_none());
+
+        for i in 0..MAX_CACHED_SENDER_DATA_KEYS {
+            cache.insert(3, &i.to_be_bytes(), &sender_data_key);
+        }
+
+        assert!(cache.get(3, sample, &provider).is_none());
+    }
 }
@@ -15,9 +15,10 @@ use crate::{
     cipher_suite::CipherSuite,
     client::MlsError,
     client_config::ClientConfig,
-    extension::RatchetTreeExt,
+    extension::{GroupMetadataExt, RatchetTreeExt},
     identity::SigningIdentity,
     protocol_version::ProtocolVersion,
+    shared_bytes::{self, SharedBytes},
     signer::Signable,
     tree_kem::{
         kem::TreeKem, node::LeafIndex, path_secret::PathSecret, TreeKemPrivate, UpdatePath,
@@ -47,8 +48,8 @@ use super::{
     message_signature::AuthenticatedContent,
     mls_rules::CommitDirection,
     proposal::{Proposal, ProposalOrRef},
-    ConfirmedTranscriptHash, EncryptedGroupSecrets, ExportedTree, Group, GroupContext, GroupInfo,
-    Welcome,
+    CompactionReport, ConfirmedTranscriptHash, EncryptedGroupSecrets, ExportedTree, Group,
+    GroupContext, GroupInfo, Welcome,
 };
 
 #[cfg(not(feature = "by_ref_proposal"))]
@@ -133,6 +134,20 @@ pub struct CommitOutput {
     /// Proposals that were received in the prior epoch but not included in the following commit.
     #[cfg(feature = "by_ref_proposal")]
     pub unused_proposals: Vec<crate::mls_rules::ProposalInfo<Proposal>>,
+    /// Proposals that were left out of the commit because they failed validation, along with
+    /// the reason. Invalid proposals in received commits are not filtered; instead the whole
+    /// commit is rejected.
+    #[cfg(feature = "by_ref_proposal")]
+    pub rejected_proposals: Vec<crate::mls_rules::ProposalRejection>,
+    /// Proposals that were left out of the commit because they were received
+    /// longer ago than the [proposal TTL](crate::client_builder::ClientBuilder::proposal_ttl)
+    /// of the client. They are not included in `unused_proposals`.
+    #[cfg(feature = "by_ref_proposal")]
+    pub expired_proposals: Vec<crate::mls_rules::ProposalInfo<Proposal>>,
+    /// Proposals left out of the commit by the
+    /// [`ProposalCompaction`](crate::group::ProposalCompaction) configured
+    /// for the client.
+    pub compaction_report: CompactionReport,
 }
 
 #[cfg_attr(all(feature = "ffi", not(test)), ::safer_ffi_gen::safer_ffi_gen)]
@@ -170,6 +185,24 @@ impl CommitOutput {
     pub fn unused_proposals(&self) -> &[crate::mls_rules::ProposalInfo<Proposal>] {
         &self.unused_proposals
     }
+
+    /// Proposals that were left out of the commit because they failed validation.
+    #[cfg(all(feature = "ffi", feature = "by_ref_proposal"))]
+    pub fn rejected_proposals(&self) -> &[crate::mls_rules::ProposalRejection] {
+        &self.rejected_proposals
+    }
+
+    /// Proposals that were left out of the commit because they expired.
+    #[cfg(all(feature = "ffi", feature = "by_ref_proposal"))]
+    pub fn expired_proposals(&self) -> &[crate::mls_rules::ProposalInfo<Proposal>] {
+        &self.expired_proposals
+    }
+
+    /// Proposals left out of the commit by proposal compaction.
+    #[cfg(feature = "ffi")]
+    pub fn compaction_report(&self) -> &CompactionReport {
+        &self.compaction_report
+    }
 }
 
 /// Build a commit with multiple proposals by-value.
@@ -189,6 +222,7 @@ where
     group_info_extensions: ExtensionList,
     new_signer: Option<SignatureSecretKey>,
     new_signing_identity: Option<SigningIdentity>,
+    new_leaf_node_extensions: Option<ExtensionList>,
 }
 
 impl<'a, C> CommitBuilder<'a, C>
@@ -212,7 +246,7 @@ where
     /// These extensions can be retrieved as part of
     /// [`NewMemberInfo`](crate::group::NewMemberInfo) that is returned
     /// by joining the group via
-    /// [`Client::join_group`](crate::Client::join_group).
+    /// [`Client::join_group_validated`](crate::Client::join_group_validated).
     pub fn set_group_info_ext(self, extensions: ExtensionList) -> Self {
         Self {
             group_info_extensions: extensions,
@@ -237,6 +271,20 @@ where
         Ok(self)
     }
 
+    /// Insert a
+    /// [`GroupContextExtensions`](crate::group::proposal::Proposal::GroupContextExtensions)
+    /// replacing the [`GroupMetadataExt`](crate::extension::built_in::GroupMetadataExt)
+    /// of the group into the current commit that is being built.
+    ///
+    /// All other group context extensions are kept. This function returns
+    /// [`MlsError::UnsupportedGroupExtension`] if a member of the group does not
+    /// support the metadata extension.
+    pub fn set_group_metadata(mut self, metadata: GroupMetadataExt) -> Result<Self, MlsError> {
+        let proposal = self.group.group_metadata_proposal(metadata)?;
+        self.proposals.push(proposal);
+        Ok(self)
+    }
+
     /// Insert a
     /// [`PreSharedKeyProposal`](crate::group::proposal::PreSharedKeyProposal) with
     /// an external PSK into the current commit that is being built.
@@ -318,6 +366,21 @@ where
         }
     }
 
+    /// Add application data of type `T` to the commit as additional
+    /// authenticated data, e.g. metadata scoped to the new epoch such as a
+    /// new group name, which receivers read with
+    /// [`CommitMessageDescription::typed_authenticated_data`](super::CommitMessageDescription::typed_authenticated_data).
+    ///
+    /// The data is bound to the commit and delivered atomically with it, but
+    /// is not part of the group state.
+    ///
+    /// # Warning
+    ///
+    /// The data provided here is always sent unencrypted.
+    pub fn typed_authenticated_data<T: MlsEncode>(self, data: &T) -> Result<Self, MlsError> {
+        Ok(self.authenticated_data(data.mls_encode_to_vec()?))
+    }
+
     /// Change the committer's signing identity as part of making this commit.
     /// This will only succeed if the [`IdentityProvider`](crate::IdentityProvider)
     /// in use by the group considers the credential inside this signing_identity
@@ -337,6 +400,21 @@ where
         }
     }
 
+    /// Change the committer's leaf node extensions as part of making this
+    /// commit, replacing the
+    /// [configured](crate::client_builder::ClientBuilder::leaf_node_extensions)
+    /// ones. Their types are automatically advertised in the capabilities of
+    /// the new leaf node.
+    ///
+    /// The commit always includes a path in order to update the leaf node,
+    /// even if it is not required by the proposals.
+    pub fn set_leaf_node_extensions(self, leaf_node_extensions: ExtensionList) -> Self {
+        Self {
+            new_leaf_node_extensions: Some(leaf_node_extensions),
+            ..self
+        }
+    }
+
     /// Finalize the commit to send.
     ///
     /// # Errors
@@ -355,6 +433,7 @@ where
                 self.group_info_extensions,
                 self.new_signer,
                 self.new_signing_identity,
+                self.new_leaf_node_extensions,
             )
             .await
     }
@@ -397,8 +476,9 @@ where
     ///
     /// Path updates provide forward secrecy and post-compromise security
     /// within the MLS protocol.
-    /// The `path_required` option returned by [`MlsRules::commit_options`](`crate::MlsRules::commit_options`)
-    /// controls the ability of a group to send a commit without a path update.
+    /// The `path_policy` option returned by [`MlsRules::commit_options`](`crate::MlsRules::commit_options`)
+    /// controls the ability of a group to send a commit without a path update,
+    /// see [`CommitPathPolicy`](crate::mls_rules::CommitPathPolicy).
     /// An update path will automatically be sent if there are no proposals
     /// in the commit, or if any proposal other than
     /// [`Add`](crate::group::proposal::Proposal::Add),
@@ -413,6 +493,7 @@ where
             Default::default(),
             None,
             None,
+            None,
         )
         .await
     }
@@ -427,6 +508,7 @@ where
             group_info_extensions: Default::default(),
             new_signer: Default::default(),
             new_signing_identity: Default::default(),
+            new_leaf_node_extensions: Default::default(),
         }
     }
 
@@ -442,6 +524,7 @@ where
         mut welcome_group_info_extensions: ExtensionList,
         new_signer: Option<SignatureSecretKey>,
         new_signing_identity: Option<SigningIdentity>,
+        new_leaf_node_extensions: Option<ExtensionList>,
     ) -> Result<CommitOutput, MlsError> {
         if self.pending_commit.is_some() {
             return Err(MlsError::ExistingPendingCommit);
@@ -451,6 +534,16 @@ where
             return Err(MlsError::GroupUsedAfterReInit);
         }
 
+        if self.removed {
+            return Err(MlsError::GroupUsedAfterRemoval);
+        }
+
+        self.check_not_archived()?;
+
+        if self.pending_leave {
+            return Err(MlsError::CommitWhilePendingLeave);
+        }
+
         let mls_rules = self.config.mls_rules();
 
         let is_external = external_leaf.is_some();
@@ -474,10 +567,20 @@ where
         let time = None;
 
         #[cfg(feature = "by_ref_proposal")]
-        let proposals = self.state.proposals.prepare_commit(sender, proposals);
+        let (mut proposals, expired_proposals) = self.state.proposals.prepare_commit_with_ttl(
+            sender,
+            proposals,
+            self.config.proposal_ttl(),
+        );
 
         #[cfg(not(feature = "by_ref_proposal"))]
-        let proposals = prepare_commit(sender, proposals);
+        let mut proposals = prepare_commit(sender, proposals);
+
+        let compaction_report = self
+            .config
+            .proposal_compaction()
+            .map(|c| c.compact(&mut proposals, &self.state.public_tree))
+            .unwrap_or_default();
 
         let mut provisional_state = self
             .state
@@ -491,9 +594,27 @@ where
                 &mls_rules,
                 time,
                 CommitDirection::Send,
+                self.config.init_key_reuse_policy(),
+                #[cfg(feature = "by_ref_proposal")]
+                self.config.duplicate_proposal_policy(),
             )
             .await?;
 
+        if let Some(reinit) = provisional_state
+            .applied_proposals
+            .reinitializations
+            .first()
+        {
+            self.check_reinit(
+                reinit.proposal.new_version(),
+                reinit.proposal.new_cipher_suite(),
+            )?;
+        }
+
+        #[cfg(feature = "by_ref_proposal")]
+        let rejected_proposals =
+            core::mem::take(&mut provisional_state.applied_proposals.rejections);
+
         let (mut provisional_private_tree, _) =
             self.provisional_private_tree(&provisional_state)?;
 
@@ -518,8 +639,12 @@ where
             )
             .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))?;
 
-        let perform_path_update = commit_options.path_required
-            || path_update_required(&provisional_state.applied_proposals);
+        let perform_path_update = commit_options.include_path(
+            path_update_required(&provisional_state.applied_proposals)
+                || self.requires_self_update
+                || new_leaf_node_extensions.is_some(),
+            is_external,
+        )?;
 
         let (update_path, path_secrets, commit_secret) = if perform_path_update {
             // If populating the path field: Create an UpdatePath using the new tree. Any new
@@ -536,10 +661,10 @@ where
                 &mut provisional_group_context,
                 &provisional_state.indexes_of_added_kpkgs,
                 new_signer_ref,
-                self.config.leaf_properties(),
+                self.leaf_properties(new_leaf_node_extensions),
                 new_signing_identity,
                 &self.cipher_suite_provider,
-                #[cfg(test)]
+                #[cfg(any(test, feature = "adversarial"))]
                 &self.commit_modifiers,
             )
             .await?;
@@ -594,7 +719,7 @@ where
             Content::Commit(alloc::boxed::Box::new(commit)),
             old_signer,
             #[cfg(feature = "private_message")]
-            self.encryption_options()?.control_wire_format(sender),
+            self.control_wire_format(sender)?,
             #[cfg(not(feature = "private_message"))]
             WireFormat::PublicMessage,
             authenticated_data,
@@ -695,9 +820,12 @@ where
         )
         .await?;
 
-        let encrypted_group_info = welcome_secret
-            .encrypt(&welcome_group_info.mls_encode_to_vec()?)
-            .await?;
+        // Shared by the welcome messages of all new members.
+        let encrypted_group_info = shared_bytes::from_vec(
+            welcome_secret
+                .encrypt(&welcome_group_info.mls_encode_to_vec()?)
+                .await?,
+        );
 
         // Encrypt path secrets and joiner secret to new members
         let path_secrets = path_secrets.as_ref();
@@ -754,6 +882,14 @@ where
                     .collect()
             };
 
+        if let Some(max_welcome_bytes) = commit_options.max_welcome_bytes {
+            let largest = welcome_messages.iter().map(MlsSize::mls_encoded_len).max();
+
+            if let Some(size) = largest.filter(|size| *size > max_welcome_bytes) {
+                return Err(MlsError::WelcomeTooLarge(size, max_welcome_bytes));
+            }
+        }
+
         let commit_message = self.format_for_wire(auth_content.clone()).await?;
 
         let pending_commit = CommitGeneration {
@@ -773,13 +909,26 @@ where
             self.signer = signer;
         }
 
+        #[cfg(feature = "by_ref_proposal")]
+        let with_epoch = |proposals: Vec<crate::mls_rules::ProposalInfo<Proposal>>| {
+            proposals
+                .into_iter()
+                .map(|p| p.with_received_epoch(self.current_epoch()))
+                .collect()
+        };
+
         Ok(CommitOutput {
             commit_message,
             welcome_messages,
             ratchet_tree,
             external_commit_group_info,
             #[cfg(feature = "by_ref_proposal")]
-            unused_proposals: provisional_state.unused_proposals,
+            unused_proposals: with_epoch(provisional_state.unused_proposals),
+            #[cfg(feature = "by_ref_proposal")]
+            rejected_proposals,
+            #[cfg(feature = "by_ref_proposal")]
+            expired_proposals: with_epoch(expired_proposals),
+            compaction_report,
         })
     }
 
@@ -814,7 +963,7 @@ where
     fn make_welcome_message(
         &self,
         secrets: Vec<EncryptedGroupSecrets>,
-        encrypted_group_info: Vec<u8>,
+        encrypted_group_info: SharedBytes,
     ) -> MlsMessage {
         MlsMessage::new(
             self.context().protocol_version,
@@ -827,20 +976,21 @@ where
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "adversarial"))]
 pub(crate) mod test_utils {
     use alloc::vec::Vec;
 
     use crate::{
-        crypto::SignatureSecretKey,
+        crypto::{HpkePublicKey, SignatureSecretKey},
         tree_kem::{leaf_node::LeafNode, TreeKemPublic, UpdatePathNode},
     };
 
-    #[derive(Copy, Clone, Debug)]
+    #[derive(Clone, Debug)]
     pub struct CommitModifiers {
         pub modify_leaf: fn(&mut LeafNode, &SignatureSecretKey) -> Option<SignatureSecretKey>,
         pub modify_tree: fn(&mut TreeKemPublic),
         pub modify_path: fn(Vec<UpdatePathNode>) -> Vec<UpdatePathNode>,
+        pub leaf_encryption_key: Option<HpkePublicKey>,
     }
 
     impl Default for CommitModifiers {
@@ -849,6 +999,7 @@ pub(crate) mod test_utils {
                 modify_leaf: |_, _| None,
                 modify_tree: |_| (),
                 modify_path: |a| a,
+                leaf_encryption_key: None,
             }
         }
     }
@@ -867,7 +1018,7 @@ mod tests {
 
     use crate::{
         crypto::test_utils::{test_cipher_suite_provider, TestCryptoProvider},
-        group::{mls_rules::DefaultMlsRules, test_utils::test_group_custom},
+        group::{mls_rules::DefaultMlsRules, test_utils::test_group_custom, Validated},
         mls_rules::CommitOptions,
         Client,
     };
@@ -885,7 +1036,8 @@ mod tests {
         extension::test_utils::{TestExtension, TEST_EXTENSION_TYPE},
         group::{
             proposal::ProposalType,
-            test_utils::{test_group_custom_config, test_n_member_group},
+            test_utils::{test_group, test_group_custom_config, test_n_member_group},
+            ReceivedMessage,
         },
         identity::test_utils::get_test_signing_identity,
         identity::{basic::BasicIdentityProvider, test_utils::get_test_basic_credential},
@@ -1007,7 +1159,10 @@ mod tests {
             .welcome_messages
             .remove(0);
 
-        let (_, context) = bob_client.join_group(None, &welcome_message).await.unwrap();
+        let (_, context) = bob_client
+            .join_group_validated(None, &Validated::new(welcome_message.clone()).unwrap())
+            .await
+            .unwrap();
 
         assert_eq!(
             context
@@ -1204,6 +1359,60 @@ mod tests {
         );
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn typed_authenticated_data_is_delivered_with_commit() {
+        #[derive(Debug, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+        struct GroupName(#[mls_codec(with = "mls_rs_codec::byte_vec")] Vec<u8>);
+
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .typed_authenticated_data(&GroupName(b"new name".to_vec()))
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let own = alice.group.apply_pending_commit().await.unwrap();
+
+        let received = bob
+            .group
+            .process_incoming_message_validated(Validated::new(commit.commit_message).unwrap())
+            .await
+            .unwrap();
+
+        let ReceivedMessage::Commit(received) = received else {
+            panic!("expected a commit");
+        };
+
+        for description in [own, received] {
+            assert_eq!(
+                description.typed_authenticated_data::<GroupName>().unwrap(),
+                Some(GroupName(b"new name".to_vec()))
+            );
+        }
+
+        // Commits without authenticated data have no typed data.
+        let commit = alice.group.commit(vec![]).await.unwrap();
+        let received = bob
+            .group
+            .process_incoming_message_validated(Validated::new(commit.commit_message).unwrap())
+            .await
+            .unwrap();
+
+        let ReceivedMessage::Commit(received) = received else {
+            panic!("expected a commit");
+        };
+
+        assert_eq!(
+            received.typed_authenticated_data::<GroupName>().unwrap(),
+            None
+        );
+    }
+
     #[cfg(feature = "by_ref_proposal")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn test_commit_builder_multiple_welcome_messages() {
@@ -1244,7 +1453,10 @@ mod tests {
                 .find(|w| w.welcome_key_package_references().contains(&&kp_ref))
                 .unwrap();
 
-            client.join_group(None, welcome).await.unwrap();
+            client
+                .join_group_validated(None, &Validated::new(welcome.clone()).unwrap())
+                .await
+                .unwrap();
 
             assert_eq!(welcome.clone().into_welcome().unwrap().secrets.len(), 1);
         }
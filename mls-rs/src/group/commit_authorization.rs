@@ -0,0 +1,337 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+use mls_rs_core::{
+    error::{AnyError, IntoAnyError},
+    extension::ExtensionList,
+};
+
+use crate::group::{
+    mls_rules::{CommitDirection, CommitOptions, CommitSource, EncryptionOptions, MlsRules},
+    proposal::AddProposal,
+    proposal_filter::{ProposalBundle, ProposalInfo},
+    Roster,
+};
+
+/// Application policy deciding who may commit which proposals, e.g. "only
+/// admins may remove members".
+///
+/// The policy is consulted by [`AuthorizingMlsRules`] as part of proposal
+/// filtering, before the wrapped [`MlsRules`] are applied, both when
+/// processing a received commit and when creating a commit. The `committer`
+/// carries the signing identity, and thus the credential, of the sender of
+/// the commit. The sender of each proposal covered by the commit, which may
+/// differ from the committer for proposals sent by reference, is available
+/// with [`ProposalInfo::sender`](crate::mls_rules::ProposalInfo::sender) and
+/// can be resolved with `current_roster`.
+///
+/// Rejecting a received commit makes processing it fail. Rejecting a commit
+/// being created makes creating it fail, since other members applying the
+/// same policy would reject it.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+pub trait CommitAuthorizationPolicy: Send + Sync {
+    /// Error type that this policy returns on internal failure or if the
+    /// commit is not authorized.
+    type Error: IntoAnyError;
+
+    /// Check that `committer` may commit `proposals` in the group described
+    /// by `current_roster` and `extension_list`, before the commit is
+    /// applied.
+    async fn authorize_commit(
+        &self,
+        direction: CommitDirection,
+        committer: &CommitSource,
+        current_roster: &Roster,
+        extension_list: &ExtensionList,
+        proposals: &ProposalBundle,
+    ) -> Result<(), Self::Error>;
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+/// Error returned by [`AuthorizingMlsRules`].
+pub enum AuthorizingMlsRulesError {
+    #[cfg_attr(feature = "std", error(transparent))]
+    MlsRulesError(AnyError),
+    #[cfg_attr(feature = "std", error("commit not authorized: {0}"))]
+    Unauthorized(AnyError),
+}
+
+impl IntoAnyError for AuthorizingMlsRulesError {
+    #[cfg(feature = "std")]
+    fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+        Ok(self.into())
+    }
+}
+
+/// MLS rules that require every commit to be authorized by a
+/// [`CommitAuthorizationPolicy`] in addition to inner [`MlsRules`].
+#[derive(Clone, Debug)]
+pub struct AuthorizingMlsRules<R, P> {
+    inner: R,
+    policy: P,
+}
+
+impl<R, P> AuthorizingMlsRules<R, P>
+where
+    R: MlsRules,
+    P: CommitAuthorizationPolicy,
+{
+    /// Authorize commits with `policy` and then filter proposals with
+    /// `inner`.
+    pub fn new(inner: R, policy: P) -> Self {
+        Self { inner, policy }
+    }
+
+    /// The inner MLS rules.
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// The commit authorization policy.
+    pub fn policy(&self) -> &P {
+        &self.policy
+    }
+}
+
+fn inner_error<E: IntoAnyError>(e: E) -> AuthorizingMlsRulesError {
+    AuthorizingMlsRulesError::MlsRulesError(e.into_any_error())
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl<R, P> MlsRules for AuthorizingMlsRules<R, P>
+where
+    R: MlsRules,
+    P: CommitAuthorizationPolicy,
+{
+    type Error = AuthorizingMlsRulesError;
+
+    async fn filter_proposals(
+        &self,
+        direction: CommitDirection,
+        source: CommitSource,
+        current_roster: &Roster,
+        extension_list: &ExtensionList,
+        proposals: ProposalBundle,
+    ) -> Result<ProposalBundle, Self::Error> {
+        self.policy
+            .authorize_commit(
+                direction,
+                &source,
+                current_roster,
+                extension_list,
+                &proposals,
+            )
+            .await
+            .map_err(|e| AuthorizingMlsRulesError::Unauthorized(e.into_any_error()))?;
+
+        self.inner
+            .filter_proposals(direction, source, current_roster, extension_list, proposals)
+            .await
+            .map_err(inner_error)
+    }
+
+    fn vet_add(
+        &self,
+        source: &CommitSource,
+        current_roster: &Roster,
+        extension_list: &ExtensionList,
+        add: &ProposalInfo<AddProposal>,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .vet_add(source, current_roster, extension_list, add)
+            .map_err(inner_error)
+    }
+
+    fn commit_options(
+        &self,
+        new_roster: &Roster,
+        new_extension_list: &ExtensionList,
+        proposals: &ProposalBundle,
+    ) -> Result<CommitOptions, Self::Error> {
+        self.inner
+            .commit_options(new_roster, new_extension_list, proposals)
+            .map_err(inner_error)
+    }
+
+    fn encryption_options(
+        &self,
+        current_roster: &Roster,
+        current_extension_list: &ExtensionList,
+    ) -> Result<EncryptionOptions, Self::Error> {
+        self.inner
+            .encryption_options(current_roster, current_extension_list)
+            .map_err(inner_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(mls_build_async)]
+    use alloc::boxed::Box;
+    use assert_matches::assert_matches;
+    use mls_rs_core::{error::IntoAnyError, extension::ExtensionList};
+
+    use super::{AuthorizingMlsRules, CommitAuthorizationPolicy};
+
+    use crate::{
+        client::{test_utils::TEST_CIPHER_SUITE, MlsError},
+        client_builder::{ClientBuilder, MlsConfig},
+        crypto::test_utils::TestCryptoProvider,
+        group::{
+            mls_rules::{CommitDirection, CommitSource, DefaultMlsRules, MlsRules},
+            proposal_filter::ProposalBundle,
+            Group, Roster, Validated,
+        },
+        identity::{basic::BasicIdentityProvider, test_utils::get_test_signing_identity},
+        Client,
+    };
+
+    #[derive(Debug)]
+    #[cfg_attr(feature = "std", derive(thiserror::Error))]
+    #[cfg_attr(feature = "std", error("only the admin can remove members"))]
+    struct NotAdmin;
+
+    impl IntoAnyError for NotAdmin {
+        #[cfg(feature = "std")]
+        fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+            Ok(self.into())
+        }
+    }
+
+    // The member at index 0 is the admin.
+    #[derive(Clone, Debug)]
+    struct AdminRemovesOnly;
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl CommitAuthorizationPolicy for AdminRemovesOnly {
+        type Error = NotAdmin;
+
+        async fn authorize_commit(
+            &self,
+            _direction: CommitDirection,
+            committer: &CommitSource,
+            _current_roster: &Roster,
+            _extension_list: &ExtensionList,
+            proposals: &ProposalBundle,
+        ) -> Result<(), Self::Error> {
+            let is_admin = matches!(committer, CommitSource::ExistingMember(m) if m.index == 0);
+
+            if proposals.remove_proposals().is_empty() || is_admin {
+                Ok(())
+            } else {
+                Err(NotAdmin)
+            }
+        }
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn test_client<R: MlsRules + Clone>(name: &[u8], mls_rules: R) -> Client<impl MlsConfig> {
+        let (signing_identity, signer) = get_test_signing_identity(TEST_CIPHER_SUITE, name).await;
+
+        ClientBuilder::new()
+            .crypto_provider(TestCryptoProvider::new())
+            .identity_provider(BasicIdentityProvider::new())
+            .signing_identity(signing_identity, signer, TEST_CIPHER_SUITE)
+            .mls_rules(mls_rules)
+            .build()
+    }
+
+    fn admin_rules() -> AuthorizingMlsRules<DefaultMlsRules, AdminRemovesOnly> {
+        AuthorizingMlsRules::new(DefaultMlsRules::new(), AdminRemovesOnly)
+    }
+
+    // Create a group of Alice, the admin, Bob, using `bob_rules`, and Carol.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn test_groups<R: MlsRules + Clone>(
+        bob_rules: R,
+    ) -> (Group<impl MlsConfig>, Group<impl MlsConfig>) {
+        let alice = test_client(b"alice", admin_rules()).await;
+        let bob = test_client(b"bob", bob_rules).await;
+        let carol = test_client(b"carol", admin_rules()).await;
+
+        let mut alice_group = alice.create_group(ExtensionList::new()).await.unwrap();
+
+        let bob_key_package = bob.generate_key_package_message().await.unwrap();
+        let carol_key_package = carol.generate_key_package_message().await.unwrap();
+
+        let commit = alice_group
+            .commit_builder()
+            .add_member(bob_key_package)
+            .unwrap()
+            .add_member(carol_key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice_group.apply_pending_commit().await.unwrap();
+
+        let (bob_group, _) = bob
+            .join_group_validated(
+                None,
+                &Validated::new(commit.welcome_messages[0].clone()).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        (alice_group, bob_group)
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn authorized_commit_is_accepted() {
+        let (mut alice_group, mut bob_group) = test_groups(admin_rules()).await;
+
+        let commit = alice_group
+            .commit_builder()
+            .remove_member(2)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        bob_group
+            .process_incoming_message_validated(Validated::new(commit.commit_message).unwrap())
+            .await
+            .unwrap();
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn unauthorized_commit_cannot_be_created() {
+        let (_, mut bob_group) = test_groups(admin_rules()).await;
+
+        let res = bob_group
+            .commit_builder()
+            .remove_member(2)
+            .unwrap()
+            .build()
+            .await;
+
+        assert_matches!(res, Err(MlsError::MlsRulesError(_)));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn unauthorized_commit_is_rejected() {
+        let (mut alice_group, mut bob_group) = test_groups(DefaultMlsRules::new()).await;
+
+        let commit = bob_group
+            .commit_builder()
+            .remove_member(2)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let res = alice_group
+            .process_incoming_message_validated(Validated::new(commit.commit_message).unwrap())
+            .await;
+
+        assert_matches!(res, Err(MlsError::MlsRulesError(_)));
+    }
+}
@@ -0,0 +1,373 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::crypto::{CipherSuiteProvider, SignaturePublicKey};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{Group, Roster},
+    signer::{Signable, SignatureLabel},
+    tree_kem::node::LeafIndex,
+    CipherSuite,
+};
+
+/// Acknowledgment by a member that it reached an epoch, created with
+/// [`Group::commit_receipt`] after the commit creating the epoch was
+/// applied.
+///
+/// The receipt contains the confirmed transcript hash of the epoch, which
+/// covers all commits of the group up to the epoch, and is signed by the
+/// leaf key of the member. Members that produce receipts with equal
+/// transcript hashes for an epoch agree on the history of the group. A
+/// delivery service can collect receipts in a [`CommitReceiptQuorum`] to
+/// learn when enough members converged on the epoch.
+#[derive(Clone, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+pub struct CommitReceipt {
+    cipher_suite: CipherSuite,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: Vec<u8>,
+    epoch: u64,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    confirmed_transcript_hash: Vec<u8>,
+    committer: LeafIndex,
+    signer_index: LeafIndex,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    signature: Vec<u8>,
+}
+
+impl Debug for CommitReceipt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CommitReceipt")
+            .field("cipher_suite", &self.cipher_suite)
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .field(
+                "confirmed_transcript_hash",
+                &mls_rs_core::debug::pretty_bytes(&self.confirmed_transcript_hash),
+            )
+            .field("committer", &self.committer)
+            .field("signer_index", &self.signer_index)
+            .field(
+                "signature",
+                &mls_rs_core::debug::pretty_bytes(&self.signature),
+            )
+            .finish()
+    }
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct CommitReceiptTBS<'a> {
+    cipher_suite: CipherSuite,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: &'a [u8],
+    epoch: u64,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    confirmed_transcript_hash: &'a [u8],
+    committer: LeafIndex,
+    signer_index: LeafIndex,
+}
+
+impl<'a> Signable<'a> for CommitReceipt {
+    const SIGN_LABEL: &'static str = SignatureLabel::CommitReceipt.as_str();
+
+    type SigningContext = ();
+
+    fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    fn signable_content(
+        &self,
+        _context: &Self::SigningContext,
+    ) -> Result<Vec<u8>, mls_rs_codec::Error> {
+        CommitReceiptTBS {
+            cipher_suite: self.cipher_suite,
+            group_id: &self.group_id,
+            epoch: self.epoch,
+            confirmed_transcript_hash: &self.confirmed_transcript_hash,
+            committer: self.committer,
+            signer_index: self.signer_index,
+        }
+        .mls_encode_to_vec()
+    }
+
+    fn write_signature(&mut self, signature: Vec<u8>) {
+        self.signature = signature
+    }
+}
+
+impl CommitReceipt {
+    /// Identifier of the group.
+    pub fn group_id(&self) -> &[u8] {
+        &self.group_id
+    }
+
+    /// Epoch reached by the signer.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Confirmed transcript hash of the epoch.
+    pub fn confirmed_transcript_hash(&self) -> &[u8] {
+        &self.confirmed_transcript_hash
+    }
+
+    /// Leaf index of the member that committed the epoch.
+    ///
+    /// For the epoch in which the signer joined, this is the signer of the
+    /// group info used to join. For the first epoch of a group, this is the
+    /// creator of the group.
+    pub fn committer(&self) -> u32 {
+        *self.committer
+    }
+
+    /// Leaf index of the member that signed the receipt.
+    pub fn signer_index(&self) -> u32 {
+        *self.signer_index
+    }
+
+    /// Serialize the receipt.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+
+    /// Deserialize a receipt produced by [`CommitReceipt::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    /// Check that the receipt was signed with `signature_key`, the key of
+    /// the member at [`CommitReceipt::signer_index`].
+    ///
+    /// The key must come from a trusted view of the group, e.g. the roster
+    /// of an [`ExternalGroup`](crate::external_client::ExternalGroup).
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn verify<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+        signature_key: &SignaturePublicKey,
+    ) -> Result<(), MlsError> {
+        if self.cipher_suite != cipher_suite_provider.cipher_suite() {
+            return Err(MlsError::CipherSuiteMismatch);
+        }
+
+        Signable::verify(self, cipher_suite_provider, signature_key, &()).await
+    }
+}
+
+/// Collection of [`CommitReceipt`]s acknowledging the same epoch.
+///
+/// Receipts are verified when they are added. A receipt for the epoch with
+/// a different confirmed transcript hash shows that its signer forked from
+/// the group and is rejected.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommitReceiptQuorum {
+    group_id: Vec<u8>,
+    epoch: u64,
+    confirmed_transcript_hash: Vec<u8>,
+    signers: Vec<u32>,
+}
+
+impl CommitReceiptQuorum {
+    /// Collect receipts acknowledging `epoch` of the group `group_id` with
+    /// the expected `confirmed_transcript_hash`, e.g. taken from the first
+    /// receipt or from the group state of the delivery service.
+    pub fn new(group_id: Vec<u8>, epoch: u64, confirmed_transcript_hash: Vec<u8>) -> Self {
+        Self {
+            group_id,
+            epoch,
+            confirmed_transcript_hash,
+            signers: Vec::new(),
+        }
+    }
+
+    /// Verify `receipt` with the signature key of its signer in `roster`
+    /// and count the signer. Adding a second receipt of the same signer has
+    /// no effect.
+    ///
+    /// The roster must be a trusted view of the group at the epoch of the
+    /// quorum, e.g. the roster of an
+    /// [`ExternalGroup`](crate::external_client::ExternalGroup) kept by the
+    /// delivery service. Returns [`MlsError::TranscriptHashMismatch`] if the
+    /// signer reached the epoch with a different history.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn add<P: CipherSuiteProvider>(
+        &mut self,
+        cipher_suite_provider: &P,
+        receipt: &CommitReceipt,
+        roster: &Roster<'_>,
+    ) -> Result<(), MlsError> {
+        if receipt.group_id != self.group_id {
+            return Err(MlsError::GroupIdMismatch);
+        }
+
+        if receipt.epoch != self.epoch {
+            return Err(MlsError::InvalidEpoch);
+        }
+
+        let signer = roster.public_tree.get_leaf_node(receipt.signer_index)?;
+
+        receipt
+            .verify(
+                cipher_suite_provider,
+                &signer.signing_identity.signature_key,
+            )
+            .await?;
+
+        if receipt.confirmed_transcript_hash != self.confirmed_transcript_hash {
+            return Err(MlsError::TranscriptHashMismatch(self.epoch));
+        }
+
+        if !self.signers.contains(&receipt.signer_index()) {
+            self.signers.push(receipt.signer_index());
+        }
+
+        Ok(())
+    }
+
+    /// Leaf indexes of the members whose receipts were added, in order of
+    /// addition.
+    pub fn signers(&self) -> &[u32] {
+        &self.signers
+    }
+
+    /// Determines if receipts of at least `threshold` members were added.
+    pub fn is_reached(&self, threshold: usize) -> bool {
+        self.signers.len() >= threshold
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Create a [`CommitReceipt`] for the current epoch, signed by the local
+    /// member.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn commit_receipt(&self) -> Result<CommitReceipt, MlsError> {
+        let mut receipt = CommitReceipt {
+            cipher_suite: self.cipher_suite(),
+            group_id: self.group_id().to_vec(),
+            epoch: self.current_epoch(),
+            confirmed_transcript_hash: self.context().confirmed_transcript_hash.to_vec(),
+            committer: self.epoch_committer,
+            signer_index: self.private_tree.self_index,
+            signature: Vec::new(),
+        };
+
+        receipt
+            .sign(&self.cipher_suite_provider, &self.signer, &())
+            .await?;
+
+        Ok(receipt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use super::{CommitReceipt, CommitReceiptQuorum};
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        crypto::test_utils::test_cipher_suite_provider,
+        group::test_utils::test_n_member_group,
+        tree_kem::node::LeafIndex,
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn quorum_of_receipts_is_collected() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let commit = groups[1].group.commit(vec![]).await.unwrap();
+        groups[1].process_pending_commit().await.unwrap();
+
+        for i in [0, 2] {
+            groups[i]
+                .process_message(commit.commit_message.clone())
+                .await
+                .unwrap();
+        }
+
+        let context = groups[0].group.context().clone();
+
+        let mut quorum = CommitReceiptQuorum::new(
+            context.group_id.clone(),
+            context.epoch,
+            context.confirmed_transcript_hash.to_vec(),
+        );
+
+        let roster = groups[0].group.roster();
+
+        for group in &groups {
+            let receipt = group.group.commit_receipt().await.unwrap();
+            let receipt = CommitReceipt::from_bytes(&receipt.to_bytes().unwrap()).unwrap();
+
+            assert_eq!(receipt.committer(), 1);
+
+            quorum.add(&cs, &receipt, &roster).await.unwrap();
+        }
+
+        assert_eq!(quorum.signers(), [0, 1, 2]);
+        assert!(quorum.is_reached(3));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn diverging_receipt_is_rejected() {
+        let groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let context = groups[0].group.context();
+        let roster = groups[0].group.roster();
+
+        let mut quorum =
+            CommitReceiptQuorum::new(context.group_id.clone(), context.epoch, vec![0; 32]);
+
+        let receipt = groups[1].group.commit_receipt().await.unwrap();
+
+        let res = quorum.add(&cs, &receipt, &roster).await;
+        assert_matches!(res, Err(MlsError::TranscriptHashMismatch(_)));
+
+        assert!(!quorum.is_reached(1));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn receipt_is_verified_with_key_of_signer_in_roster() {
+        let groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let context = groups[0].group.context();
+        let roster = groups[0].group.roster();
+
+        let mut quorum = CommitReceiptQuorum::new(
+            context.group_id.clone(),
+            context.epoch,
+            context.confirmed_transcript_hash.to_vec(),
+        );
+
+        let mut receipt = groups[1].group.commit_receipt().await.unwrap();
+
+        receipt.signer_index = LeafIndex(0);
+        let res = quorum.add(&cs, &receipt, &roster).await;
+        assert_matches!(res, Err(MlsError::InvalidSignature));
+
+        receipt.signer_index = LeafIndex(5);
+        let res = quorum.add(&cs, &receipt, &roster).await;
+        assert_matches!(res, Err(MlsError::InvalidNodeIndex(_)));
+
+        assert!(!quorum.is_reached(1));
+    }
+}
@@ -0,0 +1,364 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use mls_rs_core::error::AnyError;
+
+use crate::client::MlsError;
+
+/// Default limit on the size of a decompressed application message.
+pub const DEFAULT_MAX_DECOMPRESSED_LEN: usize = 1 << 20;
+
+// Algorithm identifier of application messages sent uncompressed.
+const UNCOMPRESSED: u8 = 0;
+
+/// Compression algorithm applied to application messages, e.g. zstd or
+/// deflate, configured with
+/// [`ClientBuilder::compression`](crate::client_builder::ClientBuilder::compression).
+///
+/// Application messages are compressed before encryption and decompressed
+/// after decryption. A message is sent compressed only if this makes it
+/// shorter. Whether a message is compressed, with which algorithm and its
+/// uncompressed length are indicated by a header prepended to the content of
+/// the message, so that they are encrypted with it. All members of a group
+/// must therefore use the same compression settings.
+///
+/// # Warning
+///
+/// The length of the encrypted message still depends on how well its content
+/// compresses. If a message contains both secret data and data influenced by
+/// an attacker, e.g. a secret token and a reflected string, an attacker
+/// observing message lengths can learn the secret data by guessing it in the
+/// data it controls, as in the CRIME attack. Such messages should be sent
+/// without compression, e.g. with
+/// [`Group::encrypt_padded_application_message`](crate::group::Group::encrypt_padded_application_message).
+pub trait Compressor: Send + Sync {
+    /// Identifier of the algorithm, carried in each compressed message.
+    /// Identifier `0` is reserved for uncompressed messages.
+    fn algorithm_id(&self) -> u8;
+
+    /// Compress `data`.
+    fn compress(&self, data: &[u8]) -> Result<Vec<u8>, AnyError>;
+
+    /// Decompress `data`. Implementations must stop and fail as soon as the
+    /// output would be longer than `max_len`.
+    fn decompress(&self, data: &[u8], max_len: usize) -> Result<Vec<u8>, AnyError>;
+}
+
+/// Compression settings of a client, see [`Compressor`].
+#[derive(Clone)]
+pub struct CompressionSettings {
+    pub(crate) compressor: Arc<dyn Compressor>,
+    pub(crate) max_decompressed_len: usize,
+}
+
+impl Debug for CompressionSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompressionSettings")
+            .field("algorithm_id", &self.compressor.algorithm_id())
+            .field("max_decompressed_len", &self.max_decompressed_len)
+            .finish()
+    }
+}
+
+impl CompressionSettings {
+    /// Compress `data`, returning the content to encrypt, starting with the
+    /// compression header.
+    pub(crate) fn compress(&self, data: &[u8]) -> Result<Vec<u8>, MlsError> {
+        let compressed = self
+            .compressor
+            .compress(data)
+            .map_err(MlsError::CompressionError)?;
+
+        match u32::try_from(data.len()) {
+            Ok(len) if compressed.len() < data.len() => {
+                let mut content = Vec::with_capacity(5 + compressed.len());
+                content.push(self.compressor.algorithm_id());
+                content.extend_from_slice(&len.to_be_bytes());
+                content.extend_from_slice(&compressed);
+                Ok(content)
+            }
+            _ => Ok(Self::uncompressed(data)),
+        }
+    }
+
+    /// Content to encrypt for `data` sent without compression.
+    pub(crate) fn uncompressed(data: &[u8]) -> Vec<u8> {
+        [&[UNCOMPRESSED], data].concat()
+    }
+
+    /// Decompress received decrypted `content`, starting with the
+    /// compression header.
+    pub(crate) fn decompress(&self, content: &[u8]) -> Result<Vec<u8>, MlsError> {
+        let Some((&algorithm_id, rest)) = content.split_first() else {
+            return Err(MlsError::InvalidCompressionHeader);
+        };
+
+        if algorithm_id == UNCOMPRESSED {
+            return Ok(rest.to_vec());
+        }
+
+        if algorithm_id != self.compressor.algorithm_id() {
+            return Err(MlsError::UnsupportedCompressionAlgorithm(algorithm_id));
+        }
+
+        if rest.len() < 4 {
+            return Err(MlsError::InvalidCompressionHeader);
+        }
+
+        let (len, data) = rest.split_at(4);
+        let len = u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize;
+
+        if len > self.max_decompressed_len {
+            return Err(MlsError::DecompressedSizeExceeded(len));
+        }
+
+        let decompressed = self
+            .compressor
+            .decompress(data, len)
+            .map_err(MlsError::CompressionError)?;
+
+        (decompressed.len() == len)
+            .then_some(decompressed)
+            .ok_or(MlsError::InvalidCompressionHeader)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use alloc::vec::Vec;
+    use mls_rs_core::error::{AnyError, IntoAnyError};
+
+    use crate::client::MlsError;
+
+    use super::Compressor;
+
+    /// Run-length encoding of bytes as (count, byte) pairs.
+    #[derive(Clone, Debug)]
+    pub struct RleCompressor;
+
+    impl Compressor for RleCompressor {
+        fn algorithm_id(&self) -> u8 {
+            1
+        }
+
+        fn compress(&self, data: &[u8]) -> Result<Vec<u8>, AnyError> {
+            let mut out = Vec::new();
+
+            for &byte in data {
+                match out.len() {
+                    n if n >= 2 && out[n - 1] == byte && out[n - 2] < u8::MAX => out[n - 2] += 1,
+                    _ => out.extend_from_slice(&[1, byte]),
+                }
+            }
+
+            Ok(out)
+        }
+
+        fn decompress(&self, data: &[u8], max_len: usize) -> Result<Vec<u8>, AnyError> {
+            let mut out = Vec::new();
+
+            for pair in data.chunks(2) {
+                let [count, byte] = pair else {
+                    return Err(MlsError::InvalidCompressionHeader.into_any_error());
+                };
+
+                if out.len() + *count as usize > max_len {
+                    return Err(MlsError::DecompressedSizeExceeded(max_len).into_any_error());
+                }
+
+                out.extend(core::iter::repeat(*byte).take(*count as usize));
+            }
+
+            Ok(out)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{sync::Arc, vec, vec::Vec};
+    use assert_matches::assert_matches;
+
+    use super::{test_utils::RleCompressor, CompressionSettings, DEFAULT_MAX_DECOMPRESSED_LEN};
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{test_utils::test_group_custom_config, ReceivedMessage},
+        CipherSuite,
+    };
+
+    fn settings(max_decompressed_len: usize) -> CompressionSettings {
+        CompressionSettings {
+            compressor: Arc::new(RleCompressor),
+            max_decompressed_len,
+        }
+    }
+
+    #[test]
+    fn compressible_data_round_trips() {
+        let settings = settings(DEFAULT_MAX_DECOMPRESSED_LEN);
+        let data = vec![7u8; 100];
+
+        let content = settings.compress(&data).unwrap();
+        assert!(content.len() < data.len());
+
+        let decompressed = settings.decompress(&content).unwrap();
+        assert_eq!(decompressed, data);
+    }
+
+    #[test]
+    fn incompressible_data_is_sent_uncompressed() {
+        let settings = settings(DEFAULT_MAX_DECOMPRESSED_LEN);
+        let data = (0..100).collect::<Vec<u8>>();
+
+        let content = settings.compress(&data).unwrap();
+        assert_eq!(content[0], 0);
+        assert_eq!(&content[1..], data);
+
+        let decompressed = settings.decompress(&content).unwrap();
+        assert_eq!(decompressed, data);
+    }
+
+    #[test]
+    fn decompression_bomb_is_rejected() {
+        let bomb = [u8::MAX, 0].repeat(1000);
+
+        let content = [&[1][..], &255_000u32.to_be_bytes(), &bomb].concat();
+
+        let res = settings(1000).decompress(&content);
+        assert_matches!(res, Err(MlsError::DecompressedSizeExceeded(255_000)));
+
+        // A sender lying about the size can't make the receiver allocate more.
+        let content = [&[1][..], &10u32.to_be_bytes(), &bomb].concat();
+
+        let res = settings(1000).decompress(&content);
+        assert_matches!(res, Err(MlsError::CompressionError(_)));
+    }
+
+    #[test]
+    fn unknown_algorithm_is_rejected() {
+        let res = settings(DEFAULT_MAX_DECOMPRESSED_LEN).decompress(b"\x02\0\0\0\x04data");
+        assert_matches!(res, Err(MlsError::UnsupportedCompressionAlgorithm(2)));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn members_exchange_compressed_application_messages() {
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.compression(RleCompressor, DEFAULT_MAX_DECOMPRESSED_LEN)
+        })
+        .await;
+
+        let (mut bob, _) = alice
+            .join_with_custom_config("bob", false, |c| {
+                c.0.settings.compression = Some(settings(DEFAULT_MAX_DECOMPRESSED_LEN))
+            })
+            .await
+            .unwrap();
+
+        let data = vec![42u8; 500];
+
+        let message = alice
+            .group
+            .encrypt_application_message(&data, b"aad".to_vec())
+            .await
+            .unwrap();
+
+        let ReceivedMessage::ApplicationMessage(received) =
+            bob.process_message(message).await.unwrap()
+        else {
+            panic!("expected application message");
+        };
+
+        assert_eq!(received.data(), data);
+        assert_eq!(received.authenticated_data, b"aad");
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn compression_header_is_encrypted() {
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.compression(RleCompressor, DEFAULT_MAX_DECOMPRESSED_LEN)
+        })
+        .await;
+
+        let message = alice
+            .group
+            .encrypt_application_message(&[42u8; 500], b"aad".to_vec())
+            .await
+            .unwrap();
+
+        let authenticated_data = message.into_ciphertext().unwrap().authenticated_data;
+
+        assert_eq!(authenticated_data, b"aad");
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn padded_application_messages_are_not_compressed() {
+        // Ed25519 signatures have a fixed length, unlike ECDSA signatures,
+        // so the length of the content only depends on the message.
+        let cipher_suite = CipherSuite::CURVE25519_AES128;
+
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, cipher_suite, |b| {
+            b.compression(RleCompressor, DEFAULT_MAX_DECOMPRESSED_LEN)
+        })
+        .await;
+
+        let (mut bob, _) = alice
+            .join_with_custom_config("bob", false, |c| {
+                c.0.settings.compression = Some(settings(DEFAULT_MAX_DECOMPRESSED_LEN))
+            })
+            .await
+            .unwrap();
+
+        let mut lengths = Vec::new();
+
+        for len in 0..64 {
+            let compressible = alice
+                .group
+                .encrypt_padded_application_message(&vec![42u8; len])
+                .await
+                .unwrap();
+
+            let incompressible = alice
+                .group
+                .encrypt_padded_application_message(&(0..len as u8).collect::<Vec<_>>())
+                .await
+                .unwrap();
+
+            let compressible = compressible.into_ciphertext().unwrap().ciphertext.len();
+            let incompressible = incompressible.into_ciphertext().unwrap().ciphertext.len();
+
+            assert_eq!(compressible, incompressible);
+            lengths.push(compressible);
+        }
+
+        // Small messages are padded to multiples of 32 bytes, so 64 lengths
+        // fall in at most 3 buckets.
+        lengths.dedup();
+        assert!(lengths.len() <= 3);
+
+        let data = vec![42u8; 500];
+
+        let message = alice
+            .group
+            .encrypt_padded_application_message(&data)
+            .await
+            .unwrap();
+
+        let ReceivedMessage::ApplicationMessage(received) =
+            bob.process_message(message).await.unwrap()
+        else {
+            panic!("expected application message");
+        };
+
+        assert_eq!(received.data(), data);
+        assert!(received.authenticated_data.is_empty());
+    }
+}
@@ -0,0 +1,293 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use mls_rs_core::identity::CredentialType;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    extension::RequiredCapabilitiesExt,
+    group::{framing::MlsMessagePayload, Group},
+    tree_kem::{leaf_node::LeafNode, node::LeafIndex},
+    MlsMessage,
+};
+
+/// Report of [`Group::credential_type_report`] and [`Group::can_add`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CredentialTypeReport {
+    /// Credential types used or required in the group that are not supported
+    /// by all members.
+    pub conflicts: Vec<CredentialTypeConflict>,
+}
+
+impl CredentialTypeReport {
+    /// Determines if no conflict was found.
+    pub fn is_compatible(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Credential type not supported by a member of the group or by a new member.
+///
+/// RFC 9420 requires each member to support the credential types used by all
+/// other members. Members are identified by their leaf index. The member
+/// given as using a credential type is the one with the lowest leaf index.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CredentialTypeConflict {
+    /// The member at `unsupported_by` does not support the credential type
+    /// of the member at `used_by`.
+    UnsupportedByMember {
+        credential_type: CredentialType,
+        used_by: u32,
+        unsupported_by: u32,
+    },
+    /// The new member does not support the credential type of the member at
+    /// `used_by`.
+    UnsupportedByNewMember {
+        credential_type: CredentialType,
+        used_by: u32,
+    },
+    /// The member at `unsupported_by` does not support the credential type of
+    /// the new member.
+    NewMemberCredentialUnsupported {
+        credential_type: CredentialType,
+        unsupported_by: u32,
+    },
+    /// The new member does not support a credential type required by the
+    /// required capabilities of the group.
+    RequiredByGroup { credential_type: CredentialType },
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Check that every member supports the credential types used by all
+    /// other members.
+    ///
+    /// Groups built by this crate always satisfy this requirement, since
+    /// proposals and commits breaking it are rejected. The report helps to
+    /// audit the group before changing the credential of a member.
+    pub fn credential_type_report(&self) -> CredentialTypeReport {
+        let in_use = self.credential_types_in_use();
+
+        let conflicts = self
+            .current_epoch_tree()
+            .non_empty_leaves()
+            .flat_map(|(index, leaf)| {
+                unsupported(&in_use, leaf).map(move |(credential_type, used_by)| {
+                    CredentialTypeConflict::UnsupportedByMember {
+                        credential_type,
+                        used_by: *used_by,
+                        unsupported_by: *index,
+                    }
+                })
+            })
+            .collect();
+
+        CredentialTypeReport { conflicts }
+    }
+
+    /// Check whether the member of `key_package` can be added to the group
+    /// as far as credential types are concerned, and explain why not.
+    ///
+    /// The new member must support the credential types used in the group
+    /// and required by its required capabilities, and all members must
+    /// support the credential type of the new member. Other requirements,
+    /// e.g. the signature and lifetime of the key package, are checked when
+    /// the member is added.
+    pub fn can_add(&self, key_package: &MlsMessage) -> Result<CredentialTypeReport, MlsError> {
+        let MlsMessagePayload::KeyPackage(key_package) = &key_package.payload else {
+            return Err(MlsError::UnexpectedMessageType);
+        };
+
+        let new_leaf = &key_package.leaf_node;
+        let new_type = new_leaf.signing_identity.credential.credential_type();
+
+        let mut conflicts = unsupported(&self.credential_types_in_use(), new_leaf)
+            .map(
+                |(credential_type, used_by)| CredentialTypeConflict::UnsupportedByNewMember {
+                    credential_type,
+                    used_by: *used_by,
+                },
+            )
+            .collect::<Vec<_>>();
+
+        if let Some(required) = self
+            .context()
+            .extensions
+            .get_as::<RequiredCapabilitiesExt>()?
+        {
+            conflicts.extend(
+                required
+                    .credentials
+                    .into_iter()
+                    .filter(|credential_type| {
+                        !new_leaf.capabilities.credentials.contains(credential_type)
+                    })
+                    .map(|credential_type| CredentialTypeConflict::RequiredByGroup {
+                        credential_type,
+                    }),
+            );
+        }
+
+        conflicts.extend(
+            self.current_epoch_tree()
+                .non_empty_leaves()
+                .filter(|(_, leaf)| !leaf.capabilities.credentials.contains(&new_type))
+                .map(
+                    |(index, _)| CredentialTypeConflict::NewMemberCredentialUnsupported {
+                        credential_type: new_type,
+                        unsupported_by: *index,
+                    },
+                ),
+        );
+
+        Ok(CredentialTypeReport { conflicts })
+    }
+
+    /// Credential types used in the group with the first member using each.
+    fn credential_types_in_use(&self) -> Vec<(CredentialType, LeafIndex)> {
+        let mut in_use: Vec<(CredentialType, LeafIndex)> = Vec::new();
+
+        for (index, leaf) in self.current_epoch_tree().non_empty_leaves() {
+            let credential_type = leaf.signing_identity.credential.credential_type();
+
+            if !in_use.iter().any(|(t, _)| *t == credential_type) {
+                in_use.push((credential_type, index));
+            }
+        }
+
+        in_use
+    }
+}
+
+fn unsupported<'a>(
+    in_use: &'a [(CredentialType, LeafIndex)],
+    leaf: &'a LeafNode,
+) -> impl Iterator<Item = (CredentialType, LeafIndex)> + 'a {
+    in_use
+        .iter()
+        .filter(|(credential_type, _)| !leaf.capabilities.credentials.contains(credential_type))
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+    use mls_rs_core::identity::{Credential, CredentialType, CustomCredential};
+
+    use super::CredentialTypeConflict;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{framing::MlsMessagePayload, test_utils::test_group},
+        key_package::test_utils::test_key_package_message,
+        tree_kem::{leaf_node::LeafNode, node::LeafIndex},
+        MlsMessage,
+    };
+
+    fn with_leaf(mut message: MlsMessage, f: impl FnOnce(&mut LeafNode)) -> MlsMessage {
+        if let MlsMessagePayload::KeyPackage(key_package) = &mut message.payload {
+            f(&mut key_package.leaf_node);
+        }
+
+        message
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn roster_can_be_queried_by_credential_type() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        alice.join("bob").await;
+
+        let roster = alice.group.roster();
+
+        assert_eq!(roster.credential_types(), vec![CredentialType::BASIC]);
+        assert_eq!(
+            roster
+                .members_with_credential_type(CredentialType::BASIC)
+                .len(),
+            2
+        );
+        assert!(roster
+            .members_with_credential_type(CredentialType::X509)
+            .is_empty());
+
+        assert!(alice.group.credential_type_report().is_compatible());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn member_not_supporting_credential_type_is_reported() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        alice.join("bob").await;
+
+        alice
+            .group
+            .state
+            .public_tree
+            .nodes
+            .borrow_as_leaf_mut(LeafIndex(1))
+            .unwrap()
+            .capabilities
+            .credentials = vec![42.into()];
+
+        assert_eq!(
+            alice.group.credential_type_report().conflicts,
+            vec![CredentialTypeConflict::UnsupportedByMember {
+                credential_type: CredentialType::BASIC,
+                used_by: 0,
+                unsupported_by: 1,
+            }]
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn can_add_explains_incompatibilities() {
+        let alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let key_package =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        assert!(alice.group.can_add(&key_package).unwrap().is_compatible());
+
+        let no_basic = with_leaf(key_package.clone(), |leaf| {
+            leaf.capabilities.credentials = vec![42.into()];
+        });
+
+        assert_eq!(
+            alice.group.can_add(&no_basic).unwrap().conflicts,
+            vec![CredentialTypeConflict::UnsupportedByNewMember {
+                credential_type: CredentialType::BASIC,
+                used_by: 0,
+            }]
+        );
+
+        let custom = with_leaf(key_package, |leaf| {
+            leaf.signing_identity.credential =
+                Credential::Custom(CustomCredential::new(43.into(), b"bob".to_vec()));
+
+            leaf.capabilities.credentials.push(43.into());
+        });
+
+        assert_eq!(
+            alice.group.can_add(&custom).unwrap().conflicts,
+            vec![CredentialTypeConflict::NewMemberCredentialUnsupported {
+                credential_type: 43.into(),
+                unsupported_by: 0,
+            }]
+        );
+
+        let group_info = alice.group.group_info_message(true).await.unwrap();
+        let res = alice.group.can_add(&group_info);
+        assert_matches!(res, Err(MlsError::UnexpectedMessageType));
+    }
+}
@@ -0,0 +1,72 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+/// Policy on multiple Update proposals, or multiple custom proposals of the
+/// same type, from the same sender in a commit.
+///
+/// Proposals are ordered as they were received, with proposals sent by value
+/// in the commit coming last. When committing, proposals by reference that
+/// are not retained are filtered out of the commit. Duplicate proposals that
+/// can not be filtered out, such as those received in a commit from another
+/// member, are rejected with
+/// [`MlsError::DuplicateProposalFromSender`](crate::client::MlsError::DuplicateProposalFromSender).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DuplicateProposalPolicy {
+    /// Retain the last proposal from each sender.
+    LatestWins,
+    /// Retain the first proposal from each sender.
+    FirstWins,
+    /// Retain none of the proposals from a sender that sent more than one.
+    Reject,
+}
+
+impl DuplicateProposalPolicy {
+    /// For each proposal identified by its sender and type, whether it is
+    /// retained.
+    pub(crate) fn retained<K: PartialEq>(self, keys: impl IntoIterator<Item = K>) -> Vec<bool> {
+        let keys = keys.into_iter().collect::<Vec<_>>();
+
+        (0..keys.len())
+            .map(|i| {
+                let mut same = (0..keys.len()).filter(|j| keys[*j] == keys[i]);
+
+                match self {
+                    Self::LatestWins => same.next_back() == Some(i),
+                    Self::FirstWins => same.next() == Some(i),
+                    Self::Reject => same.nth(1).is_none(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::DuplicateProposalPolicy;
+
+    #[test]
+    fn retained_proposals_follow_policy() {
+        let keys = [1, 2, 1, 3, 1];
+
+        assert_eq!(
+            DuplicateProposalPolicy::LatestWins.retained(keys),
+            vec![false, true, false, true, true]
+        );
+
+        assert_eq!(
+            DuplicateProposalPolicy::FirstWins.retained(keys),
+            vec![true, true, false, true, false]
+        );
+
+        assert_eq!(
+            DuplicateProposalPolicy::Reject.retained(keys),
+            vec![false, true, false, true, false]
+        );
+    }
+}
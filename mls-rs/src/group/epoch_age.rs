@@ -0,0 +1,182 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use core::time::Duration;
+
+#[cfg(feature = "std")]
+use mls_rs_core::time::MlsTime;
+
+use crate::{client_config::ClientConfig, group::Group};
+
+/// Policy limiting how long a group may stay in the same epoch.
+///
+/// Regularly moving to a new epoch, e.g. with an empty commit, refreshes the
+/// key material of the group and limits how many messages can be decrypted
+/// after a compromise of the current epoch secrets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct EpochAgePolicy {
+    /// Maximum age of an epoch.
+    pub max_age: Duration,
+    /// Refuse to encrypt application messages with
+    /// [`MlsError::EpochMaxAgeExceeded`](crate::client::MlsError::EpochMaxAgeExceeded)
+    /// once the current epoch is older than `max_age`, until a commit is
+    /// processed.
+    pub enforce: bool,
+}
+
+impl EpochAgePolicy {
+    /// Create a policy that only reports epochs older than `max_age` with
+    /// [`Group::epoch_expired`].
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            max_age,
+            enforce: false,
+        }
+    }
+
+    /// Set whether application messages are refused once the epoch expired.
+    pub fn with_enforcement(self, enforce: bool) -> Self {
+        Self { enforce, ..self }
+    }
+}
+
+/// Current wall-clock time in seconds, if available.
+pub(crate) fn current_time() -> Option<u64> {
+    #[cfg(feature = "std")]
+    return Some(MlsTime::now().seconds_since_epoch());
+
+    #[cfg(not(feature = "std"))]
+    None
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Time elapsed since this member entered the current epoch, either by
+    /// creating or joining the group or by processing a commit.
+    ///
+    /// Returns `None` if the current time is not available, i.e. without the
+    /// `std` feature.
+    pub fn epoch_age(&self) -> Option<Duration> {
+        let start = self.epoch_started_at?;
+        let now = current_time()?;
+
+        Some(Duration::from_secs(now.saturating_sub(start)))
+    }
+
+    /// Returns true if the current epoch is older than allowed by the
+    /// [`EpochAgePolicy`] of the client. A commit is needed to move the
+    /// group to a new epoch.
+    pub fn epoch_expired(&self) -> bool {
+        let Some(policy) = self.config.epoch_age_policy() else {
+            return false;
+        };
+
+        self.epoch_age().map_or(false, |age| age > policy.max_age)
+    }
+
+    pub(crate) fn epoch_age_exceeded(&self) -> bool {
+        self.config
+            .epoch_age_policy()
+            .map_or(false, |policy| policy.enforce)
+            && self.epoch_expired()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use alloc::vec;
+    use core::time::Duration;
+
+    use super::EpochAgePolicy;
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::test_utils::{test_group, test_group_custom_config, TestGroup},
+    };
+
+    #[cfg(feature = "private_message")]
+    use crate::client::MlsError;
+
+    #[cfg(feature = "private_message")]
+    use assert_matches::assert_matches;
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn group_with_policy(policy: EpochAgePolicy) -> TestGroup {
+        let mut group = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.epoch_age_policy(policy)
+        })
+        .await;
+
+        // Pretend the epoch started two hours ago.
+        group.group.epoch_started_at = group.group.epoch_started_at.map(|t| t - 7200);
+
+        group
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn epoch_age_is_reset_by_commit() {
+        let mut group = group_with_policy(EpochAgePolicy::new(Duration::from_secs(3600))).await;
+
+        assert!(group.group.epoch_age().unwrap() >= Duration::from_secs(7200));
+        assert!(group.group.epoch_expired());
+
+        group.group.commit(vec![]).await.unwrap();
+        group.process_pending_commit().await.unwrap();
+
+        assert!(group.group.epoch_age().unwrap() < Duration::from_secs(3600));
+        assert!(!group.group.epoch_expired());
+    }
+
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn expired_epoch_is_only_reported_without_enforcement() {
+        let mut group = group_with_policy(EpochAgePolicy::new(Duration::from_secs(3600))).await;
+
+        assert!(group.group.epoch_expired());
+
+        let res = group
+            .group
+            .encrypt_application_message(b"hello", vec![])
+            .await;
+
+        assert!(res.is_ok());
+    }
+
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn expired_epoch_blocks_application_messages_with_enforcement() {
+        let policy = EpochAgePolicy::new(Duration::from_secs(3600)).with_enforcement(true);
+        let mut group = group_with_policy(policy).await;
+
+        let res = group
+            .group
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::EpochMaxAgeExceeded));
+
+        group.group.commit(vec![]).await.unwrap();
+        group.process_pending_commit().await.unwrap();
+
+        let res = group
+            .group
+            .encrypt_application_message(b"hello", vec![])
+            .await;
+
+        assert!(res.is_ok());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn epoch_never_expires_without_policy() {
+        let mut group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        group.group.epoch_started_at = Some(0);
+
+        assert!(group.group.epoch_age().unwrap() > Duration::from_secs(3600));
+        assert!(!group.group.epoch_expired());
+    }
+}
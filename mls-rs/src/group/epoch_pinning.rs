@@ -0,0 +1,288 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use crate::{client::MlsError, client_config::ClientConfig, group::Group, MlsMessage};
+
+#[cfg(feature = "prior_epoch")]
+use crate::group::{
+    ciphertext_processor::CiphertextProcessor,
+    framing::{Content, MlsMessagePayload, Sender, WireFormat},
+    message_signature::AuthenticatedContent,
+};
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Encrypt an application message in `epoch`, which is either the
+    /// current epoch or the previous one.
+    ///
+    /// Sending in the previous epoch helps when a commit races with
+    /// application messages: receivers that did not process the commit yet
+    /// can still decrypt the message. It is only allowed within the
+    /// [`previous_epoch_send_window`](crate::client_builder::ClientBuilder::previous_epoch_send_window)
+    /// after this member entered the current epoch, only if the commit into
+    /// the current epoch did not remove or update any member, and only if the
+    /// signature key of this member did not change in the commit. Members
+    /// removed or updated by the commit could otherwise still read messages
+    /// sent in the previous epoch. Sending in the previous epoch is also
+    /// refused after the group is loaded from storage, since whether the
+    /// commit removed or updated members is not stored. Otherwise
+    /// [`MlsError::EpochSendNotAllowed`] is returned. Receivers that already
+    /// processed the commit decrypt the message with the previous epoch they
+    /// retain, as for any delayed message.
+    ///
+    /// Messages sent in the previous epoch are counted in
+    /// [`GroupStats::previous_epoch_sends`](crate::group::GroupStats::previous_epoch_sends).
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn encrypt_at_epoch(
+        &mut self,
+        epoch: u64,
+        message: &[u8],
+        authenticated_data: Vec<u8>,
+    ) -> Result<MlsMessage, MlsError> {
+        if epoch == self.current_epoch() {
+            return self
+                .encrypt_application_message(message, authenticated_data)
+                .await;
+        }
+
+        #[cfg(feature = "prior_epoch")]
+        return self
+            .encrypt_at_previous_epoch(epoch, message, authenticated_data)
+            .await;
+
+        #[cfg(not(feature = "prior_epoch"))]
+        Err(MlsError::EpochSendNotAllowed(epoch))
+    }
+
+    #[cfg(feature = "prior_epoch")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn encrypt_at_previous_epoch(
+        &mut self,
+        epoch_id: u64,
+        message: &[u8],
+        authenticated_data: Vec<u8>,
+    ) -> Result<MlsMessage, MlsError> {
+        if self.removed {
+            return Err(MlsError::GroupUsedAfterRemoval);
+        }
+
+        self.check_not_archived()?;
+
+        let within_window = self
+            .config
+            .previous_epoch_send_window()
+            .zip(self.epoch_age())
+            .map_or(false, |(window, age)| age <= window);
+
+        if !within_window
+            || !self.previous_epoch_sendable
+            || epoch_id.checked_add(1) != Some(self.current_epoch())
+        {
+            return Err(MlsError::EpochSendNotAllowed(epoch_id));
+        }
+
+        let message = self.application_payload(message)?;
+
+        let padding_mode = self.encryption_options()?.padding_mode;
+
+        let signature_key = self
+            .current_epoch_tree()
+            .get_leaf_node(self.private_tree.self_index)?
+            .signing_identity
+            .signature_key
+            .clone();
+
+        let epoch = self
+            .state_repo
+            .get_epoch_mut(epoch_id)
+            .await?
+            .ok_or(MlsError::EpochNotFound)?;
+
+        // Receivers verify the message with the key this member had in the
+        // previous epoch.
+        let same_key = epoch
+            .signature_public_keys
+            .get(*epoch.self_index as usize)
+            .map_or(false, |key| key.as_ref() == Some(&signature_key));
+
+        if !same_key {
+            return Err(MlsError::EpochSendNotAllowed(epoch_id));
+        }
+
+        let auth_content = AuthenticatedContent::new_signed(
+            &self.cipher_suite_provider,
+            &epoch.context,
+            Sender::Member(*epoch.self_index),
+            Content::Application(message.into()),
+            &self.signer,
+            WireFormat::PrivateMessage,
+            authenticated_data,
+        )
+        .await?;
+
+        let ciphertext = CiphertextProcessor::new(epoch, self.cipher_suite_provider.clone())
+            .seal(auth_content, padding_mode)
+            .await?;
+
+        self.previous_epoch_sends += 1;
+
+        Ok(MlsMessage::new(
+            self.protocol_version(),
+            MlsMessagePayload::Cipher(ciphertext),
+        ))
+    }
+}
+
+#[cfg(all(test, feature = "std", feature = "prior_epoch"))]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+    use core::time::Duration;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{
+            test_utils::{test_group_custom_config, TestGroup},
+            ReceivedMessage,
+        },
+    };
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn groups_after_commit() -> (TestGroup, TestGroup, TestGroup) {
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.previous_epoch_send_window(Duration::from_secs(60))
+        })
+        .await;
+
+        let (mut bob, _) = alice.join("bob").await;
+        let (mut carol, commit) = alice.join("carol").await;
+
+        bob.process_message(commit).await.unwrap();
+
+        // Bob does not receive the commit of Carol yet.
+        let commit = carol.group.commit(vec![]).await.unwrap();
+        carol.process_pending_commit().await.unwrap();
+        alice.process_message(commit.commit_message).await.unwrap();
+
+        (alice, bob, carol)
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn message_can_be_sent_in_previous_epoch() {
+        let (mut alice, mut bob, mut carol) = groups_after_commit().await;
+        let epoch = bob.group.current_epoch();
+
+        let message = alice
+            .group
+            .encrypt_at_epoch(epoch, b"hello", vec![])
+            .await
+            .unwrap();
+
+        assert_eq!(message.epoch(), Some(epoch));
+
+        for receiver in [&mut bob, &mut carol] {
+            let received = receiver.process_message(message.clone()).await.unwrap();
+
+            assert_matches!(
+                received,
+                ReceivedMessage::ApplicationMessage(m) if m.data() == b"hello"
+            );
+        }
+
+        let stats = alice.group.stats().await.unwrap();
+        assert_eq!(stats.previous_epoch_sends, 1);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn sending_in_previous_epoch_is_bounded() {
+        let (mut alice, _, _) = groups_after_commit().await;
+        let epoch = alice.group.current_epoch();
+
+        let res = alice
+            .group
+            .encrypt_at_epoch(epoch - 2, b"hello", vec![])
+            .await;
+        assert_matches!(res, Err(MlsError::EpochSendNotAllowed(e)) if e == epoch - 2);
+
+        let res = alice
+            .group
+            .encrypt_at_epoch(epoch + 1, b"hello", vec![])
+            .await;
+        assert_matches!(res, Err(MlsError::EpochSendNotAllowed(_)));
+
+        alice.group.epoch_started_at = alice.group.epoch_started_at.map(|t| t - 61);
+
+        let res = alice
+            .group
+            .encrypt_at_epoch(epoch - 1, b"hello", vec![])
+            .await;
+        assert_matches!(res, Err(MlsError::EpochSendNotAllowed(_)));
+
+        // Sending in the current epoch is always allowed.
+        alice
+            .group
+            .encrypt_at_epoch(epoch, b"hello", vec![])
+            .await
+            .unwrap();
+
+        let stats = alice.group.stats().await.unwrap();
+        assert_eq!(stats.previous_epoch_sends, 0);
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn alice_and_bob() -> (TestGroup, TestGroup) {
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.previous_epoch_send_window(Duration::from_secs(60))
+        })
+        .await;
+
+        let (bob, _) = alice.join("bob").await;
+
+        (alice, bob)
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn sending_in_previous_epoch_is_refused_after_removal() {
+        let (mut alice, _) = alice_and_bob().await;
+        let epoch = alice.group.current_epoch();
+
+        alice
+            .group
+            .commit_builder()
+            .remove_member(1)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.process_pending_commit().await.unwrap();
+
+        // Bob could decrypt a message sent in the previous epoch.
+        let res = alice.group.encrypt_at_epoch(epoch, b"hello", vec![]).await;
+        assert_matches!(res, Err(MlsError::EpochSendNotAllowed(e)) if e == epoch);
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn sending_in_previous_epoch_is_refused_after_update() {
+        let (mut alice, mut bob) = alice_and_bob().await;
+        let epoch = alice.group.current_epoch();
+
+        let proposal = bob.group.propose_update(vec![]).await.unwrap();
+        alice.process_message(proposal).await.unwrap();
+
+        alice.group.commit(vec![]).await.unwrap();
+        alice.process_pending_commit().await.unwrap();
+
+        let res = alice.group.encrypt_at_epoch(epoch, b"hello", vec![]).await;
+        assert_matches!(res, Err(MlsError::EpochSendNotAllowed(e)) if e == epoch);
+    }
+}
@@ -0,0 +1,241 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{
+    crypto::{CipherSuiteProvider, HpkeCiphertext, HpkePublicKey, HpkeSecretKey},
+    secret::Secret,
+};
+use zeroize::Zeroizing;
+
+use crate::{
+    client::MlsError, client_config::ClientConfig, group::Group,
+    tree_kem::hpke_encryption::HpkeEncryptable, CipherSuite,
+};
+
+/// Exporter secret of an epoch encrypted to the escrow key configured with
+/// [`ClientBuilder::escrow_public_key`](crate::client_builder::ClientBuilder::escrow_public_key),
+/// created with [`Group::escrow_record`].
+///
+/// Some regulated deployments require keying material to be auditable by a
+/// designated party. The holder of the escrow secret key can recover the
+/// exporter secret with [`open_escrow_record`] and thus derive every value
+/// exported from the epoch, but no message keys.
+///
+/// # Warning
+///
+/// Escrow deliberately weakens the confidentiality guarantees of MLS for all
+/// members of the group. It is only available with the `escrow` feature,
+/// which is visible in [`CrateCapabilities`](crate::CrateCapabilities).
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[non_exhaustive]
+pub struct EscrowRecord {
+    /// Cipher suite of the group, used for HPKE.
+    pub cipher_suite: CipherSuite,
+    /// Identifier of the group.
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    pub group_id: Vec<u8>,
+    /// Epoch of the exporter secret.
+    pub epoch: u64,
+    /// HPKE encryption of the exporter secret.
+    pub wrapped_exporter_secret: HpkeCiphertext,
+}
+
+impl Debug for EscrowRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EscrowRecord")
+            .field("cipher_suite", &self.cipher_suite)
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .field("wrapped_exporter_secret", &self.wrapped_exporter_secret)
+            .finish()
+    }
+}
+
+impl EscrowRecord {
+    /// Serialize the record.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+
+    /// Deserialize a record produced by [`EscrowRecord::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    // The group and epoch are authenticated as HPKE context, so that a
+    // secret can't be passed off as the one of another epoch.
+    fn hpke_context(&self) -> Result<Vec<u8>, MlsError> {
+        EscrowContext {
+            cipher_suite: self.cipher_suite,
+            group_id: &self.group_id,
+            epoch: self.epoch,
+        }
+        .mls_encode_to_vec()
+        .map_err(Into::into)
+    }
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct EscrowContext<'a> {
+    cipher_suite: CipherSuite,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: &'a [u8],
+    epoch: u64,
+}
+
+struct EscrowedSecret(Zeroizing<Vec<u8>>);
+
+impl HpkeEncryptable for EscrowedSecret {
+    const ENCRYPT_LABEL: &'static str = "ExporterEscrow";
+
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self, MlsError> {
+        Ok(Self(Zeroizing::new(bytes)))
+    }
+
+    fn get_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        Ok(self.0.to_vec())
+    }
+}
+
+/// Recover the exporter secret wrapped in `record` with the escrow HPKE key
+/// pair `secret_key` and `public_key`.
+///
+/// `cipher_suite_provider` must implement the cipher suite of `record`.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub async fn open_escrow_record<P: CipherSuiteProvider>(
+    cipher_suite_provider: &P,
+    secret_key: &HpkeSecretKey,
+    public_key: &HpkePublicKey,
+    record: &EscrowRecord,
+) -> Result<Secret, MlsError> {
+    if cipher_suite_provider.cipher_suite() != record.cipher_suite {
+        return Err(MlsError::CipherSuiteMismatch);
+    }
+
+    let secret = EscrowedSecret::decrypt(
+        cipher_suite_provider,
+        secret_key,
+        public_key,
+        &record.hpke_context()?,
+        &record.wrapped_exporter_secret,
+    )
+    .await?;
+
+    Ok(secret.0.into())
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Wrap the exporter secret of the current epoch to the configured escrow
+    /// key.
+    ///
+    /// Applications subject to escrow should call this function whenever the
+    /// epoch changes, i.e. after creating or joining the group and after each
+    /// commit, and hand the record over to the auditor.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn escrow_record(&self) -> Result<EscrowRecord, MlsError> {
+        let escrow_key = self
+            .config
+            .escrow_public_key()
+            .ok_or(MlsError::EscrowNotConfigured)?;
+
+        let mut record = EscrowRecord {
+            cipher_suite: self.cipher_suite(),
+            group_id: self.group_id().to_vec(),
+            epoch: self.current_epoch(),
+            wrapped_exporter_secret: HpkeCiphertext {
+                kem_output: Vec::new(),
+                ciphertext: Vec::new(),
+            },
+        };
+
+        let secret = EscrowedSecret(Zeroizing::new(self.key_schedule.exporter_secret().to_vec()));
+
+        record.wrapped_exporter_secret = secret
+            .encrypt(
+                &self.cipher_suite_provider,
+                &escrow_key,
+                &record.hpke_context()?,
+            )
+            .await?;
+
+        Ok(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use mls_rs_core::crypto::CipherSuiteProvider;
+
+    use super::{open_escrow_record, EscrowRecord};
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        crypto::test_utils::test_cipher_suite_provider,
+        group::test_utils::{test_group, test_group_custom_config},
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn auditor_recovers_exporter_secret() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let (secret_key, public_key) = cs.kem_generate().await.unwrap();
+
+        let alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.escrow_public_key(public_key.clone())
+        })
+        .await;
+
+        let record = alice.group.escrow_record().await.unwrap();
+        let record = EscrowRecord::from_bytes(&record.to_bytes().unwrap()).unwrap();
+
+        assert_eq!(record.epoch, alice.group.current_epoch());
+
+        let secret = open_escrow_record(&cs, &secret_key, &public_key, &record)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            secret.as_bytes(),
+            &alice.group.key_schedule.exporter_secret()[..]
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn record_is_bound_to_its_epoch() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let (secret_key, public_key) = cs.kem_generate().await.unwrap();
+
+        let alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.escrow_public_key(public_key.clone())
+        })
+        .await;
+
+        let mut record = alice.group.escrow_record().await.unwrap();
+        record.epoch += 1;
+
+        let res = open_escrow_record(&cs, &secret_key, &public_key, &record).await;
+        assert!(res.is_err());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn escrow_is_off_by_default() {
+        let alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let res = alice.group.escrow_record().await;
+
+        assert_matches!(res, Err(MlsError::EscrowNotConfigured));
+    }
+}
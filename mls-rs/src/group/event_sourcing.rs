@@ -0,0 +1,412 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+
+use crate::{
+    client::{Client, MlsError},
+    client_config::ClientConfig,
+    group::{
+        message_processor::MessageProcessor, message_signature::AuthenticatedContent,
+        CommitGeneration, Group,
+    },
+};
+
+#[cfg(feature = "by_ref_proposal")]
+use crate::{
+    crypto::{HpkePublicKey, HpkeSecretKey, SignatureSecretKey},
+    group::ProposalRef,
+    map::SmallMap,
+};
+
+#[cfg(feature = "by_ref_proposal")]
+use super::proposal_cache::{CachedProposal, ProposalCache};
+
+/// Change of the state of a group from one epoch to the next, recorded for
+/// groups of clients configured with
+/// [`ClientBuilder::epoch_deltas`](crate::client_builder::ClientBuilder::epoch_deltas).
+///
+/// A delta contains the commit creating the epoch and the secret state of the
+/// member required to apply it again, i.e. the path secrets of commits
+/// created by the member and the proposals cached when the commit was
+/// applied. Deltas allow persisting the state of a group in an event-sourced
+/// backend by appending one delta per epoch instead of writing a full
+/// snapshot, and restoring it with
+/// [`Client::load_group_with_epoch_deltas`].
+///
+/// Deltas do not record the keys used from the secret tree of the epoch they
+/// create, since these are used after the delta is recorded. The secret tree
+/// of each epoch restored from a delta is therefore erased, see
+/// [`Client::load_group_with_epoch_deltas`].
+///
+/// Deltas contain secrets and must be stored as securely as snapshots.
+#[derive(Clone, Debug, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+pub struct EpochDelta {
+    epoch: u64,
+    commit: DeltaCommit,
+    #[cfg(feature = "by_ref_proposal")]
+    proposals: SmallMap<ProposalRef, CachedProposal>,
+    #[cfg(feature = "by_ref_proposal")]
+    pending_updates: SmallMap<HpkePublicKey, (HpkeSecretKey, Option<SignatureSecretKey>)>,
+}
+
+#[derive(Clone, Debug, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+#[repr(u8)]
+enum DeltaCommit {
+    Received(AuthenticatedContent) = 1u8,
+    Own(CommitGeneration) = 2u8,
+}
+
+impl EpochDelta {
+    /// Epoch created by applying this delta.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Deserialize a delta from storage.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    /// Serialize a delta for storage.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Take the deltas recorded since the last call, in order of epoch, to
+    /// append them to the event store of the group.
+    pub fn take_epoch_deltas(&mut self) -> Vec<EpochDelta> {
+        core::mem::take(&mut self.epoch_deltas)
+    }
+
+    /// Determines if the number of deltas recorded since the last
+    /// [compaction](Group::compact) reached the interval configured with
+    /// [`ClientBuilder::epoch_deltas`](crate::client_builder::ClientBuilder::epoch_deltas).
+    pub fn compaction_due(&self) -> bool {
+        matches!(
+            self.config.epoch_delta_compaction_interval(),
+            Some(interval) if self.deltas_since_compaction >= interval
+        )
+    }
+
+    /// Write a snapshot of the group to the
+    /// [`GroupStateStorage`](crate::GroupStateStorage) as
+    /// [`Group::write_to_storage`] does, after which the deltas of epochs up
+    /// to the current one are no longer required to restore the group and
+    /// can be deleted from the event store.
+    ///
+    /// Deltas that were not [taken](Group::take_epoch_deltas) yet are
+    /// dropped.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn compact(&mut self) -> Result<(), MlsError> {
+        self.write_to_storage().await?;
+
+        self.epoch_deltas.clear();
+        self.deltas_since_compaction = 0;
+
+        Ok(())
+    }
+
+    pub(crate) fn epoch_delta_for(
+        &self,
+        auth_content: &AuthenticatedContent,
+    ) -> Option<EpochDelta> {
+        self.config.epoch_delta_compaction_interval()?;
+
+        let commit = match &self.pending_commit {
+            Some(pending) if &pending.content == auth_content => DeltaCommit::Own(pending.clone()),
+            _ => DeltaCommit::Received(auth_content.clone()),
+        };
+
+        Some(EpochDelta {
+            epoch: auth_content.content.epoch + 1,
+            commit,
+            #[cfg(feature = "by_ref_proposal")]
+            proposals: self.state.proposals.proposals.clone(),
+            #[cfg(feature = "by_ref_proposal")]
+            pending_updates: self.pending_updates.clone(),
+        })
+    }
+
+    pub(crate) fn record_epoch_delta(&mut self, epoch_delta: EpochDelta) {
+        self.epoch_deltas.push(epoch_delta);
+        self.deltas_since_compaction += 1;
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn replay_epoch_delta(&mut self, epoch_delta: EpochDelta) -> Result<(), MlsError> {
+        if epoch_delta.epoch != self.current_epoch() + 1 {
+            return Err(MlsError::InvalidEpoch);
+        }
+
+        #[cfg(feature = "by_ref_proposal")]
+        {
+            self.state.proposals = ProposalCache::import(
+                self.protocol_version(),
+                self.group_id().to_vec(),
+                epoch_delta.proposals,
+            );
+
+            self.pending_updates = epoch_delta.pending_updates;
+        }
+
+        match epoch_delta.commit {
+            DeltaCommit::Own(pending_commit) => {
+                self.pending_commit = Some(pending_commit);
+                self.apply_pending_commit().await?;
+            }
+            DeltaCommit::Received(auth_content) => {
+                self.pending_commit = None;
+                self.process_commit(auth_content, None).await?;
+            }
+        }
+
+        // Messages were sent and received with this tree after the delta was
+        // recorded. Restoring it fresh would reuse keys and nonces and accept
+        // replayed messages.
+        #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+        self.epoch_secrets.secret_tree.erase();
+
+        Ok(())
+    }
+}
+
+impl<C> Client<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Load the group with `group_id` from the snapshot in the
+    /// [`GroupStateStorage`](crate::GroupStateStorage) and apply
+    /// `epoch_deltas` to it.
+    ///
+    /// Deltas of epochs covered by the snapshot are skipped. The remaining
+    /// deltas must be given in order of epoch without gaps, otherwise
+    /// [`MlsError::InvalidEpoch`] is returned. The snapshot must have been
+    /// written at least once, e.g. after creating or joining the group, with
+    /// [`Group::write_to_storage`] or [`Group::compact`].
+    ///
+    /// The secret tree of each epoch created by applying a delta is erased,
+    /// since the keys used from it after the delta was recorded are unknown.
+    /// Encrypting or decrypting private messages in these epochs therefore
+    /// fails with [`MlsError::InvalidLeafConsumption`] until the next epoch.
+    /// The group only reaches the next epoch if commits are sent as public
+    /// messages, see [`EncryptionOptions`](crate::mls_rules::EncryptionOptions),
+    /// otherwise the member has to rejoin the group.
+    ///
+    /// Deltas do not replace the secrets held by the snapshot: the secrets
+    /// of the epoch of the snapshot and of the prior epochs it retains stay
+    /// in storage until the snapshot is overwritten, e.g. by
+    /// [`Group::compact`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn load_group_with_epoch_deltas(
+        &self,
+        group_id: &[u8],
+        epoch_deltas: Vec<EpochDelta>,
+    ) -> Result<Group<C>, MlsError> {
+        let mut group = self.load_group(group_id).await?;
+        let snapshot_epoch = group.current_epoch();
+
+        for epoch_delta in epoch_deltas {
+            if epoch_delta.epoch > snapshot_epoch {
+                group.replay_epoch_delta(epoch_delta).await?;
+            }
+        }
+
+        // Replayed deltas are already stored.
+        group.epoch_deltas.clear();
+
+        Ok(group)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{
+                TestClientBuilder, TestClientConfig, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION,
+            },
+            Client, MlsError,
+        },
+        client_config::ClientConfig,
+        group::test_utils::{test_group_custom_config, TestGroup},
+        group::Validated,
+    };
+
+    use super::EpochDelta;
+
+    fn reload_client(group: &TestGroup) -> Client<TestClientConfig> {
+        TestClientBuilder::new_for_test()
+            .epoch_deltas(2)
+            .group_state_storage(group.group.config.group_state_storage())
+            .key_package_repo(group.group.config.key_package_repo())
+            .build()
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn group_is_restored_from_snapshot_and_deltas() {
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.epoch_deltas(2)
+        })
+        .await;
+
+        alice.group.write_to_storage().await.unwrap();
+
+        // Own commit adding bob
+        let (mut bob, _) = alice.join("bob").await;
+
+        // Commit received from bob
+        let commit = bob.group.commit(vec![]).await.unwrap();
+        bob.process_pending_commit().await.unwrap();
+
+        alice.process_message(commit.commit_message).await.unwrap();
+
+        assert!(alice.group.compaction_due());
+
+        let deltas = alice
+            .group
+            .take_epoch_deltas()
+            .into_iter()
+            .map(|delta| EpochDelta::from_bytes(&delta.to_bytes().unwrap()).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(deltas.len(), 2);
+        assert_eq!(deltas[1].epoch(), alice.group.current_epoch());
+
+        let client = reload_client(&alice);
+
+        let mut restored = client
+            .load_group_with_epoch_deltas(alice.group.group_id(), deltas.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            restored.epoch_authenticator().unwrap(),
+            alice.group.epoch_authenticator().unwrap()
+        );
+
+        // The restored group keeps up with the group.
+        let commit = bob.group.commit(vec![]).await.unwrap();
+        bob.process_pending_commit().await.unwrap();
+
+        restored
+            .process_incoming_message_validated(Validated::new(commit.commit_message).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            restored.epoch_authenticator().unwrap(),
+            bob.group.epoch_authenticator().unwrap()
+        );
+
+        // Gaps in the deltas are detected.
+        let res = client
+            .load_group_with_epoch_deltas(alice.group.group_id(), deltas[1..].to_vec())
+            .await;
+
+        assert_matches!(res.map(|_| ()), Err(MlsError::InvalidEpoch));
+
+        // After compaction, the snapshot covers all epochs.
+        alice.group.compact().await.unwrap();
+        assert!(!alice.group.compaction_due());
+
+        let restored = client
+            .load_group_with_epoch_deltas(alice.group.group_id(), vec![])
+            .await
+            .unwrap();
+
+        assert_eq!(restored.current_epoch(), alice.group.current_epoch());
+    }
+
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn keys_of_restored_epochs_are_not_reused() {
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.epoch_deltas(2)
+        })
+        .await;
+
+        alice.group.write_to_storage().await.unwrap();
+
+        let (mut bob, _) = alice.join("bob").await;
+
+        // Keys of the restored epoch are used after its delta is recorded.
+        let message = bob
+            .group
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+
+        alice.process_message(message.clone()).await.unwrap();
+
+        alice
+            .group
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+
+        let deltas = alice.group.take_epoch_deltas();
+
+        let mut restored = reload_client(&alice)
+            .load_group_with_epoch_deltas(alice.group.group_id(), deltas)
+            .await
+            .unwrap();
+
+        let res = restored
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::InvalidLeafConsumption));
+
+        let res = restored
+            .process_incoming_message_validated(Validated::new(message).unwrap())
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::InvalidLeafConsumption));
+
+        // The erased tree is stored with the group.
+        restored.write_to_storage().await.unwrap();
+
+        let mut restored = reload_client(&alice)
+            .load_group(alice.group.group_id())
+            .await
+            .unwrap();
+
+        let res = restored
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::InvalidLeafConsumption));
+
+        // The next epoch has a new secret tree.
+        let commit = bob.group.commit(vec![]).await.unwrap();
+        bob.process_pending_commit().await.unwrap();
+
+        restored
+            .process_incoming_message_validated(Validated::new(commit.commit_message).unwrap())
+            .await
+            .unwrap();
+
+        let message = restored
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+
+        bob.process_message(message).await.unwrap();
+    }
+}
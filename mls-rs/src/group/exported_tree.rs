@@ -7,6 +7,16 @@ use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
 
 use crate::{client::MlsError, tree_kem::node::NodeVec};
 
+/// Public ratchet tree of a group, as returned by
+/// [`Group::export_tree`](crate::Group::export_tree).
+///
+/// The serialization produced by [`ExportedTree::to_bytes`] is the
+/// `ratchet_tree` encoding of RFC 9420, i.e. the vector of optional nodes
+/// that is also carried by the ratchet tree extension, with trailing blank
+/// nodes removed. Trees can therefore be exchanged with other MLS
+/// implementations, e.g. to provide the tree to a client joining externally.
+/// A tree received from another implementation is imported with
+/// [`ExportedTree::from_bytes`] and validated when it is used to join.
 #[cfg_attr(
     all(feature = "ffi", not(test)),
     safer_ffi_gen::ffi_type(clone, opaque)
@@ -24,6 +34,7 @@ impl<'a> ExportedTree<'a> {
         Self(Cow::Borrowed(node_data))
     }
 
+    /// Serialize the tree in the RFC 9420 `ratchet_tree` encoding.
     pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
         self.mls_encode_to_vec().map_err(Into::into)
     }
@@ -39,6 +50,7 @@ impl<'a> ExportedTree<'a> {
 
 #[cfg_attr(all(feature = "ffi", not(test)), ::safer_ffi_gen::safer_ffi_gen)]
 impl ExportedTree<'static> {
+    /// Deserialize a tree in the RFC 9420 `ratchet_tree` encoding.
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
         Self::mls_decode(&mut &*bytes).map_err(Into::into)
     }
@@ -49,3 +61,62 @@ impl From<ExportedTree<'_>> for NodeVec {
         value.0.into_owned()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        client::test_utils::{TestClientBuilder, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::test_utils::test_n_member_group,
+        identity::test_utils::get_test_signing_identity,
+    };
+
+    use super::ExportedTree;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn exported_tree_can_be_imported_to_join_externally() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+
+        // Removing the last member leaves blank nodes at the end of the tree
+        groups[0]
+            .group
+            .commit_builder()
+            .remove_member(2)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        groups[0].process_pending_commit().await.unwrap();
+
+        let tree = groups[0].group.export_tree();
+        assert!(matches!(tree.0.last(), Some(Some(_))));
+
+        let bytes = tree.to_bytes().unwrap();
+        let imported = ExportedTree::from_bytes(&bytes).unwrap();
+
+        assert_eq!(imported, tree);
+        assert_eq!(imported.to_bytes().unwrap(), bytes);
+
+        let (bob_identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"bob").await;
+
+        let bob = TestClientBuilder::new_for_test()
+            .signing_identity(bob_identity, secret_key, TEST_CIPHER_SUITE)
+            .build();
+
+        let group_info = groups[0]
+            .group
+            .group_info_message_allowing_ext_commit(false)
+            .await
+            .unwrap();
+
+        let (_, commit) = bob
+            .external_commit_builder()
+            .unwrap()
+            .with_tree_data(imported)
+            .build(group_info)
+            .await
+            .unwrap();
+
+        groups[0].process_message(commit).await.unwrap();
+    }
+}
@@ -39,7 +39,7 @@ use crate::group::{
     PreSharedKeyProposal, {JustPreSharedKeyID, PreSharedKeyID},
 };
 
-use super::{validate_group_info_joiner, ExportedTree};
+use super::{validate_group_info_joiner, ExportedTree, Validated};
 
 /// A builder that aids with the construction of an external commit.
 #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::ffi_type(opaque))]
@@ -149,10 +149,18 @@ impl<C: ClientConfig> ExternalCommitBuilder<C> {
             return Err(MlsError::UnsupportedProtocolVersion(protocol_version));
         }
 
-        let group_info = group_info
+        let group_info = Validated::new(group_info)?
             .into_group_info()
             .ok_or(MlsError::UnexpectedMessageType)?;
 
+        self.config
+            .group_id_policy()
+            .validate(&group_info.group_context.group_id)?;
+
+        self.config
+            .group_policy_support()
+            .check(&group_info.group_context.extensions)?;
+
         let cipher_suite = cipher_suite_provider(
             self.config.crypto_provider(),
             group_info.group_context.cipher_suite,
@@ -194,7 +202,7 @@ impl<C: ClientConfig> ExternalCommitBuilder<C> {
 
         let (mut group, _) = Group::join_with(
             self.config,
-            group_info,
+            group_info.into_inner(),
             public_tree,
             KeySchedule::new(init_secret),
             epoch_secrets,
@@ -238,7 +246,7 @@ impl<C: ClientConfig> ExternalCommitBuilder<C> {
                 .await?;
 
             group
-                .process_event_or_content(EventOrContent::Content(auth_content), true, None)
+                .process_event_or_content(EventOrContent::Content(auth_content, None), true, None)
                 .await?;
         }
 
@@ -256,6 +264,7 @@ impl<C: ClientConfig> ExternalCommitBuilder<C> {
                 Default::default(),
                 None,
                 None,
+                None,
             )
             .await?;
 
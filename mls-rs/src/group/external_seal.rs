@@ -0,0 +1,274 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::crypto::{CipherSuiteProvider, HpkeCiphertext, HpkePublicKey, HpkeSecretKey};
+
+use crate::{
+    client::MlsError, client_config::ClientConfig, group::Group,
+    tree_kem::hpke_encryption::HpkeEncryptable, CipherSuite,
+};
+
+const EXPORTER_LABEL: &[u8] = b"external seal";
+
+/// Data encrypted by a group member to a non-member with
+/// [`Group::seal_to_external`].
+///
+/// The container identifies the group and epoch it was created in and
+/// carries a value derived from the exporter secret of that epoch, which
+/// members can check with [`Group::verify_external_seal`] to confirm that it
+/// was created by a member of the epoch. The data itself can only be
+/// decrypted by the holder of the receiver HPKE secret key, using
+/// [`open_external`].
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[non_exhaustive]
+pub struct ExternalSealedData {
+    /// Cipher suite of the group, used for HPKE.
+    pub cipher_suite: CipherSuite,
+    /// Identifier of the group.
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    pub group_id: Vec<u8>,
+    /// Epoch in which the data was sealed.
+    pub epoch: u64,
+    /// Application context provided when sealing.
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    pub context: Vec<u8>,
+    /// Value exported from the epoch for `context`.
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    pub epoch_binding: Vec<u8>,
+    /// HPKE encryption of the data.
+    pub ciphertext: HpkeCiphertext,
+}
+
+impl Debug for ExternalSealedData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExternalSealedData")
+            .field("cipher_suite", &self.cipher_suite)
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .field("context", &mls_rs_core::debug::pretty_bytes(&self.context))
+            .field(
+                "epoch_binding",
+                &mls_rs_core::debug::pretty_bytes(&self.epoch_binding),
+            )
+            .field("ciphertext", &self.ciphertext)
+            .finish()
+    }
+}
+
+impl ExternalSealedData {
+    /// Serialize the container.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+
+    /// Deserialize a container produced by [`ExternalSealedData::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    // Everything but the ciphertext is authenticated as HPKE context.
+    fn hpke_context(&self) -> Result<Vec<u8>, MlsError> {
+        SealContext {
+            cipher_suite: self.cipher_suite,
+            group_id: &self.group_id,
+            epoch: self.epoch,
+            context: &self.context,
+            epoch_binding: &self.epoch_binding,
+        }
+        .mls_encode_to_vec()
+        .map_err(Into::into)
+    }
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct SealContext<'a> {
+    cipher_suite: CipherSuite,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: &'a [u8],
+    epoch: u64,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    context: &'a [u8],
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    epoch_binding: &'a [u8],
+}
+
+struct SealedPayload(Vec<u8>);
+
+impl HpkeEncryptable for SealedPayload {
+    const ENCRYPT_LABEL: &'static str = "ExternalSeal";
+
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self, MlsError> {
+        Ok(Self(bytes))
+    }
+
+    fn get_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        Ok(self.0.clone())
+    }
+}
+
+/// Decrypt data sealed to the HPKE key pair `secret_key` and `public_key`
+/// with [`Group::seal_to_external`].
+///
+/// `cipher_suite_provider` must implement the cipher suite of `sealed`.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub async fn open_external<P: CipherSuiteProvider>(
+    cipher_suite_provider: &P,
+    secret_key: &HpkeSecretKey,
+    public_key: &HpkePublicKey,
+    sealed: &ExternalSealedData,
+) -> Result<Vec<u8>, MlsError> {
+    if cipher_suite_provider.cipher_suite() != sealed.cipher_suite {
+        return Err(MlsError::CipherSuiteMismatch);
+    }
+
+    let payload = SealedPayload::decrypt(
+        cipher_suite_provider,
+        secret_key,
+        public_key,
+        &sealed.hpke_context()?,
+        &sealed.ciphertext,
+    )
+    .await?;
+
+    Ok(payload.0)
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Encrypt `data` to a non-member holding the HPKE secret key for
+    /// `receiver`, e.g. a compliance escrow service.
+    ///
+    /// `context` is an application defined label that is bound to the
+    /// encryption. The receiver decrypts the result with [`open_external`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn seal_to_external(
+        &self,
+        receiver: &HpkePublicKey,
+        context: &[u8],
+        data: &[u8],
+    ) -> Result<ExternalSealedData, MlsError> {
+        let mut sealed = ExternalSealedData {
+            cipher_suite: self.cipher_suite(),
+            group_id: self.group_id().to_vec(),
+            epoch: self.current_epoch(),
+            context: context.to_vec(),
+            epoch_binding: self.external_seal_binding(context).await?,
+            ciphertext: HpkeCiphertext {
+                kem_output: Vec::new(),
+                ciphertext: Vec::new(),
+            },
+        };
+
+        sealed.ciphertext = SealedPayload(data.to_vec())
+            .encrypt(
+                &self.cipher_suite_provider,
+                receiver,
+                &sealed.hpke_context()?,
+            )
+            .await?;
+
+        Ok(sealed)
+    }
+
+    /// Check that `sealed` was created with [`Group::seal_to_external`] by a
+    /// member of this group in the current epoch.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn verify_external_seal(
+        &self,
+        sealed: &ExternalSealedData,
+    ) -> Result<bool, MlsError> {
+        if sealed.group_id != self.group_id() || sealed.epoch != self.current_epoch() {
+            return Ok(false);
+        }
+
+        let binding = self.external_seal_binding(&sealed.context).await?;
+
+        Ok(binding == sealed.epoch_binding)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn external_seal_binding(&self, context: &[u8]) -> Result<Vec<u8>, MlsError> {
+        let len = self.cipher_suite_provider.kdf_extract_size();
+
+        Ok(self
+            .export_secret(EXPORTER_LABEL, context, len)
+            .await?
+            .as_bytes()
+            .to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use mls_rs_core::crypto::CipherSuiteProvider;
+
+    use super::{open_external, ExternalSealedData};
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        crypto::test_utils::test_cipher_suite_provider,
+        group::test_utils::test_group,
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn non_member_can_open_sealed_data() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob, _) = alice.join("bob").await;
+
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let (secret_key, public_key) = cs.kem_generate().await.unwrap();
+
+        let sealed = alice
+            .group
+            .seal_to_external(&public_key, b"escrow", b"secret data")
+            .await
+            .unwrap();
+
+        let sealed = ExternalSealedData::from_bytes(&sealed.to_bytes().unwrap()).unwrap();
+
+        let opened = open_external(&cs, &secret_key, &public_key, &sealed)
+            .await
+            .unwrap();
+
+        assert_eq!(opened, b"secret data");
+
+        let res = bob.group.verify_external_seal(&sealed).await;
+        assert!(res.unwrap());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn modified_container_fails_to_open() {
+        let alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let (secret_key, public_key) = cs.kem_generate().await.unwrap();
+
+        let mut sealed = alice
+            .group
+            .seal_to_external(&public_key, b"escrow", b"secret data")
+            .await
+            .unwrap();
+
+        sealed.context = b"other".to_vec();
+
+        let res = open_external(&cs, &secret_key, &public_key, &sealed).await;
+        assert_matches!(res, Err(MlsError::CryptoProviderError(_)));
+
+        let res = alice.group.verify_external_seal(&sealed).await;
+        assert!(!res.unwrap());
+    }
+}
@@ -0,0 +1,279 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{crypto::CipherSuiteProvider, error::IntoAnyError, identity::SigningIdentity};
+
+use crate::{client::MlsError, client_config::ClientConfig, group::Group};
+
+/// Compact summary of the state of a group in an epoch.
+///
+/// Two members that agree on the state of the group compute equal
+/// fingerprints. Fingerprints can be exchanged out of band, e.g. when
+/// investigating reports of members disagreeing on the group state, and
+/// compared using [`EpochFingerprint::divergence`] to find out which part of the
+/// state differs.
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct EpochFingerprint {
+    /// Identifier of the group.
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::vec_serde"))]
+    pub group_id: Vec<u8>,
+    /// Epoch in which the fingerprint was computed.
+    pub epoch: u64,
+    /// Confirmed transcript hash of the group context.
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::vec_serde"))]
+    pub confirmed_transcript_hash: Vec<u8>,
+    /// Ratchet tree hash of the group context.
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::vec_serde"))]
+    pub tree_hash: Vec<u8>,
+    /// Hash of the leaf index and signing identity of each member.
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::vec_serde"))]
+    pub roster_digest: Vec<u8>,
+}
+
+impl Debug for EpochFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EpochFingerprint")
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .field(
+                "confirmed_transcript_hash",
+                &mls_rs_core::debug::pretty_bytes(&self.confirmed_transcript_hash),
+            )
+            .field(
+                "tree_hash",
+                &mls_rs_core::debug::pretty_bytes(&self.tree_hash),
+            )
+            .field(
+                "roster_digest",
+                &mls_rs_core::debug::pretty_bytes(&self.roster_digest),
+            )
+            .finish()
+    }
+}
+
+/// Part of the group state in which two [`EpochFingerprint`]s differ.
+///
+/// Only the first difference is reported, checking the group ID, the epoch,
+/// the membership, the ratchet tree and the transcript in this order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FingerprintDivergence {
+    /// The fingerprints belong to different groups.
+    GroupId,
+    /// The fingerprints were computed in different epochs and can't be
+    /// compared further.
+    Epoch {
+        /// Epoch of the local fingerprint.
+        local: u64,
+        /// Epoch of the remote fingerprint.
+        remote: u64,
+    },
+    /// The members disagree on who is in the group.
+    Roster,
+    /// The members agree on who is in the group, but not on the ratchet tree,
+    /// e.g. because they applied different updates.
+    Tree,
+    /// The members agree on the ratchet tree, but processed different commits.
+    TranscriptHash,
+}
+
+impl EpochFingerprint {
+    /// Compare this (local) fingerprint to a `remote` one. Returns `None` if
+    /// they are equal.
+    pub fn divergence(&self, remote: &EpochFingerprint) -> Option<FingerprintDivergence> {
+        if self.group_id != remote.group_id {
+            Some(FingerprintDivergence::GroupId)
+        } else if self.epoch != remote.epoch {
+            Some(FingerprintDivergence::Epoch {
+                local: self.epoch,
+                remote: remote.epoch,
+            })
+        } else if self.roster_digest != remote.roster_digest {
+            Some(FingerprintDivergence::Roster)
+        } else if self.tree_hash != remote.tree_hash {
+            Some(FingerprintDivergence::Tree)
+        } else if self.confirmed_transcript_hash != remote.confirmed_transcript_hash {
+            Some(FingerprintDivergence::TranscriptHash)
+        } else {
+            None
+        }
+    }
+
+    /// Serialize the fingerprint.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+
+    /// Deserialize a fingerprint produced by [`EpochFingerprint::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct RosterEntry<'a> {
+    index: u32,
+    signing_identity: &'a SigningIdentity,
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Compute the [`EpochFingerprint`] of the current epoch.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn epoch_fingerprint(&self) -> Result<EpochFingerprint, MlsError> {
+        let roster = self
+            .state
+            .public_tree
+            .non_empty_leaves()
+            .map(|(index, leaf)| RosterEntry {
+                index: *index,
+                signing_identity: &leaf.signing_identity,
+            })
+            .collect::<Vec<_>>()
+            .mls_encode_to_vec()?;
+
+        let roster_digest = self
+            .cipher_suite_provider
+            .hash(&roster)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        let context = self.context();
+
+        Ok(EpochFingerprint {
+            group_id: context.group_id.clone(),
+            epoch: context.epoch,
+            confirmed_transcript_hash: context.confirmed_transcript_hash.to_vec(),
+            tree_hash: context.tree_hash.clone(),
+            roster_digest,
+        })
+    }
+
+    /// Compare the fingerprint of the current epoch to the fingerprint
+    /// `remote` computed by another member.
+    ///
+    /// Returns `None` if both members agree on the state of the group.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn verify_epoch_fingerprint(
+        &self,
+        remote: &EpochFingerprint,
+    ) -> Result<Option<FingerprintDivergence>, MlsError> {
+        Ok(self.epoch_fingerprint().await?.divergence(remote))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::{EpochFingerprint, FingerprintDivergence};
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::test_utils::test_group,
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn members_in_sync_have_equal_fingerprints() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob, _) = alice.join("bob").await;
+
+        let alice_fingerprint = alice.group.epoch_fingerprint().await.unwrap();
+        let bob_fingerprint = bob.group.epoch_fingerprint().await.unwrap();
+
+        assert_eq!(alice_fingerprint, bob_fingerprint);
+
+        let res = bob.group.verify_epoch_fingerprint(&alice_fingerprint).await;
+        assert_eq!(res.unwrap(), None);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn fingerprint_detects_epoch_and_roster_divergence() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob, _) = alice.join("bob").await;
+
+        let bob_fingerprint = bob.group.epoch_fingerprint().await.unwrap();
+
+        alice.join("charlie").await;
+
+        let res = alice.group.verify_epoch_fingerprint(&bob_fingerprint).await;
+
+        assert_eq!(
+            res.unwrap(),
+            Some(FingerprintDivergence::Epoch {
+                local: 2,
+                remote: 1
+            })
+        );
+
+        let mut alice_fingerprint = alice.group.epoch_fingerprint().await.unwrap();
+        alice_fingerprint.epoch = bob_fingerprint.epoch;
+
+        assert_eq!(
+            alice_fingerprint.divergence(&bob_fingerprint),
+            Some(FingerprintDivergence::Roster)
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn fingerprint_detects_tree_and_transcript_divergence() {
+        let alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let fingerprint = alice.group.epoch_fingerprint().await.unwrap();
+
+        let other = EpochFingerprint {
+            tree_hash: vec![1, 2, 3],
+            ..fingerprint.clone()
+        };
+
+        assert_eq!(
+            fingerprint.divergence(&other),
+            Some(FingerprintDivergence::Tree)
+        );
+
+        let other = EpochFingerprint {
+            confirmed_transcript_hash: vec![1, 2, 3],
+            ..fingerprint.clone()
+        };
+
+        assert_eq!(
+            fingerprint.divergence(&other),
+            Some(FingerprintDivergence::TranscriptHash)
+        );
+
+        let other = EpochFingerprint {
+            group_id: vec![1, 2, 3],
+            ..fingerprint.clone()
+        };
+
+        assert_eq!(
+            fingerprint.divergence(&other),
+            Some(FingerprintDivergence::GroupId)
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn fingerprint_serialization_round_trip() {
+        let alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let fingerprint = alice.group.epoch_fingerprint().await.unwrap();
+
+        let bytes = fingerprint.to_bytes().unwrap();
+
+        assert_eq!(EpochFingerprint::from_bytes(&bytes).unwrap(), fingerprint);
+    }
+}
@@ -0,0 +1,154 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Pinned encodings of messages and group snapshots produced by this crate.
+//!
+//! The fixture in `test_data/format_stability.json` is generated once and
+//! checked in. Each test decodes a pinned encoding and fails if it can no
+//! longer be decoded or if encoding it again does not produce the same
+//! message or group state, which would break interoperability with other
+//! versions of this crate, or the loading of stored group states. Messages
+//! must encode to the exact same bytes. Changes to the wire or storage format
+//! must be intentional and come with a new fixture.
+
+use alloc::vec::Vec;
+use mls_rs_codec::MlsEncode;
+
+use crate::{
+    client::test_utils::{test_client_with_key_pkg, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+    group::test_utils::test_group,
+    CipherSuite, MlsMessage,
+};
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+struct TestCase {
+    cipher_suite: u16,
+    #[serde(with = "hex::serde")]
+    key_package: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    commit: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    welcome: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    group_info: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    snapshot: Vec<u8>,
+}
+
+impl TestCase {
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn generate() -> TestCase {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let (_, key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .add_member(key_package.clone())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.process_pending_commit().await.unwrap();
+
+        let group_info = alice.group.group_info_message(true).await.unwrap();
+
+        TestCase {
+            cipher_suite: TEST_CIPHER_SUITE.into(),
+            key_package: key_package.to_bytes().unwrap(),
+            commit: commit.commit_message.to_bytes().unwrap(),
+            welcome: commit.welcome_messages[0].to_bytes().unwrap(),
+            group_info: group_info.to_bytes().unwrap(),
+            snapshot: alice.group.snapshot().mls_encode_to_vec().unwrap(),
+        }
+    }
+}
+
+#[cfg(mls_build_async)]
+async fn load_test_case() -> TestCase {
+    load_test_case_json!(format_stability, TestCase::generate().await)
+}
+
+#[cfg(not(mls_build_async))]
+fn load_test_case() -> TestCase {
+    load_test_case_json!(format_stability, TestCase::generate())
+}
+
+fn assert_stable_message(bytes: &[u8]) -> MlsMessage {
+    let message = MlsMessage::from_bytes(bytes).unwrap();
+    assert_eq!(message.to_bytes().unwrap(), bytes);
+    message
+}
+
+#[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+async fn key_package_encoding_is_stable() {
+    let test_case = load_test_case().await;
+
+    let key_package = assert_stable_message(&test_case.key_package)
+        .into_key_package()
+        .unwrap();
+
+    assert_eq!(
+        key_package.cipher_suite,
+        CipherSuite::from(test_case.cipher_suite)
+    );
+}
+
+#[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+async fn commit_encoding_is_stable() {
+    let test_case = load_test_case().await;
+    let commit = assert_stable_message(&test_case.commit);
+
+    assert_eq!(commit.epoch(), Some(0));
+}
+
+#[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+async fn welcome_encoding_is_stable() {
+    let test_case = load_test_case().await;
+
+    assert_stable_message(&test_case.welcome)
+        .into_welcome()
+        .unwrap();
+}
+
+#[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+async fn group_info_encoding_is_stable() {
+    let test_case = load_test_case().await;
+
+    let group_info = assert_stable_message(&test_case.group_info)
+        .into_group_info()
+        .unwrap();
+
+    assert_eq!(group_info.group_context().epoch, 1);
+}
+
+// The snapshot layout depends on the enabled features. The pinned snapshot
+// was written with the default features.
+#[cfg(all(feature = "rfc_compliant", feature = "tree_index"))]
+#[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+async fn snapshot_encoding_is_stable() {
+    use crate::{
+        client::test_utils::TestClientBuilder,
+        group::{snapshot::Snapshot, Group},
+    };
+
+    let test_case = load_test_case().await;
+
+    // The public tree index is a hash map with no fixed encoding order, so
+    // decoded snapshots are compared rather than bytes.
+    let snapshot = Snapshot::from_bytes(&test_case.snapshot).unwrap();
+    let encoded = snapshot.mls_encode_to_vec().unwrap();
+    assert_eq!(Snapshot::from_bytes(&encoded).unwrap(), snapshot);
+
+    // A group restored from the pinned snapshot saves the same state.
+    let config = TestClientBuilder::new_for_test().build().config;
+    let group = Group::from_snapshot(config, snapshot.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(group.snapshot(), snapshot);
+}
@@ -463,6 +463,20 @@ impl MlsMessage {
         Self::mls_decode(&mut &*bytes).map_err(Into::into)
     }
 
+    /// Deserialize a message from transport, referencing `bytes` instead of
+    /// copying the large opaque fields of the message.
+    ///
+    /// The encrypted group info of a welcome message, which contains the
+    /// ratchet tree when the ratchet tree extension is used, is kept as a
+    /// slice of `bytes`. This lowers the peak memory used to process large
+    /// welcome messages. Other messages are decoded as with
+    /// [`MlsMessage::from_bytes`].
+    #[cfg(feature = "bytes")]
+    #[cfg_attr(all(feature = "ffi", not(test)), ::safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn from_shared_bytes(bytes: &bytes::Bytes) -> Result<Self, MlsError> {
+        mls_rs_codec::shared_bytes::decode_shared(bytes).map_err(Into::into)
+    }
+
     /// Serialize a message for transport.
     pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
         self.mls_encode_to_vec().map_err(Into::into)
@@ -574,6 +588,99 @@ pub enum WireFormat {
     KeyPackage = 5u16,
 }
 
+/// Routing metadata of a serialized [`MlsMessage`], obtained with
+/// [`MessageDescription::parse`].
+///
+/// Only the header of the message is read, so this is much cheaper than
+/// [`MlsMessage::from_bytes`] and allows dispatching messages, e.g. by group
+/// and epoch, without decoding them. Nothing is authenticated: the values are
+/// taken from the wire as is and must be treated as hints until the message
+/// is processed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MessageDescription<'a> {
+    /// Protocol version of the message.
+    pub version: ProtocolVersion,
+    /// Wire format of the message.
+    pub wire_format: WireFormat,
+    /// Cipher suite, present for [`WireFormat::Welcome`],
+    /// [`WireFormat::GroupInfo`] and [`WireFormat::KeyPackage`].
+    pub cipher_suite: Option<CipherSuite>,
+    /// Group identifier, present for [`WireFormat::PublicMessage`],
+    /// [`WireFormat::PrivateMessage`] and [`WireFormat::GroupInfo`].
+    pub group_id: Option<&'a [u8]>,
+    /// Epoch, present for [`WireFormat::PublicMessage`],
+    /// [`WireFormat::PrivateMessage`] and [`WireFormat::GroupInfo`].
+    pub epoch: Option<u64>,
+    /// Content type, present for [`WireFormat::PublicMessage`] and
+    /// [`WireFormat::PrivateMessage`].
+    pub content_type: Option<ContentType>,
+    /// Sender, present for [`WireFormat::PublicMessage`] only since the
+    /// sender of a private message is encrypted.
+    pub sender: Option<Sender>,
+}
+
+impl<'a> MessageDescription<'a> {
+    /// Read the metadata of a message serialized with
+    /// [`MlsMessage::to_bytes`] without decoding the rest of it.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, MlsError> {
+        let reader = &mut &*bytes;
+
+        let version = ProtocolVersion::mls_decode(reader)?;
+        let wire_format = WireFormat::mls_decode(reader)?;
+
+        let mut description = MessageDescription {
+            version,
+            wire_format,
+            cipher_suite: None,
+            group_id: None,
+            epoch: None,
+            content_type: None,
+            sender: None,
+        };
+
+        match wire_format {
+            WireFormat::PublicMessage => {
+                description.group_id = Some(decode_slice(reader)?);
+                description.epoch = Some(u64::mls_decode(reader)?);
+                description.sender = Some(Sender::mls_decode(reader)?);
+
+                // Skip the authenticated data
+                decode_slice(reader)?;
+
+                description.content_type = Some(ContentType::mls_decode(reader)?);
+            }
+            WireFormat::PrivateMessage => {
+                description.group_id = Some(decode_slice(reader)?);
+                description.epoch = Some(u64::mls_decode(reader)?);
+                description.content_type = Some(ContentType::mls_decode(reader)?);
+            }
+            WireFormat::GroupInfo => {
+                // The group info starts with the group context
+                ProtocolVersion::mls_decode(reader)?;
+                description.cipher_suite = Some(CipherSuite::mls_decode(reader)?);
+                description.group_id = Some(decode_slice(reader)?);
+                description.epoch = Some(u64::mls_decode(reader)?);
+            }
+            WireFormat::Welcome => {
+                description.cipher_suite = Some(CipherSuite::mls_decode(reader)?);
+            }
+            WireFormat::KeyPackage => {
+                ProtocolVersion::mls_decode(reader)?;
+                description.cipher_suite = Some(CipherSuite::mls_decode(reader)?);
+            }
+        }
+
+        Ok(description)
+    }
+}
+
+fn decode_slice<'a>(reader: &mut &'a [u8]) -> Result<&'a [u8], mls_rs_codec::Error> {
+    let (data, rest) = mls_rs_codec::iter::mls_decode_split_on_collection(reader)?;
+    *reader = rest;
+    Ok(data)
+}
+
 #[derive(Clone, PartialEq, MlsSize, MlsEncode, MlsDecode)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -668,14 +775,16 @@ pub(crate) mod test_utils {
 #[cfg(feature = "private_message")]
 #[cfg(test)]
 mod tests {
+    use alloc::vec;
     use assert_matches::assert_matches;
 
     use crate::{
-        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        client::test_utils::{test_client_with_key_pkg, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
         crypto::test_utils::test_cipher_suite_provider,
         group::{
             framing::test_utils::get_test_ciphertext_content,
-            proposal_ref::test_utils::auth_content_from_proposal, RemoveProposal,
+            proposal_ref::test_utils::auth_content_from_proposal, test_utils::test_group,
+            RemoveProposal,
         },
     };
 
@@ -708,6 +817,80 @@ mod tests {
         assert_matches!(decoded, Err(mls_rs_codec::Error::Custom(_)));
     }
 
+    fn assert_description_matches(message: &MlsMessage) {
+        let bytes = message.to_bytes().unwrap();
+        let description = MessageDescription::parse(&bytes).unwrap();
+
+        assert_eq!(description.version, message.version);
+        assert_eq!(description.wire_format, message.wire_format());
+        assert_eq!(description.group_id, message.group_id());
+        assert_eq!(description.epoch, message.epoch());
+        assert_eq!(description.cipher_suite, message.cipher_suite());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn message_description_of_public_commit() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (_, commit) = alice.join("bob").await;
+
+        assert_description_matches(&commit);
+
+        let bytes = commit.to_bytes().unwrap();
+        let description = MessageDescription::parse(&bytes).unwrap();
+
+        assert_eq!(description.sender, Some(Sender::Member(0)));
+        assert_eq!(description.content_type, Some(ContentType::Commit));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn message_description_of_private_message() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let message = alice
+            .group
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+
+        assert_description_matches(&message);
+
+        let bytes = message.to_bytes().unwrap();
+        let description = MessageDescription::parse(&bytes).unwrap();
+
+        assert_eq!(description.sender, None);
+        assert_eq!(description.content_type, Some(ContentType::Application));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn message_description_of_welcome_group_info_and_key_package() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let (_, key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        assert_description_matches(&key_package);
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        assert_description_matches(&commit.welcome_messages[0]);
+
+        let group_info = alice.group.group_info_message(true).await.unwrap();
+        assert_description_matches(&group_info);
+    }
+
+    #[test]
+    fn message_description_of_truncated_message_fails() {
+        let res = MessageDescription::parse(&[0, 1, 0]);
+        assert_matches!(res, Err(MlsError::SerializationError(_)));
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn proposal_ref() {
         let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
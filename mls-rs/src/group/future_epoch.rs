@@ -0,0 +1,203 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{Group, ReceivedMessage, Validated},
+    MlsMessage,
+};
+
+/// Buffer holding messages of future epochs of a group until the commit
+/// creating their epoch is processed.
+///
+/// Messages sent in epoch `N + 1` may be delivered before the commit that
+/// creates epoch `N + 1`. Processing them directly with
+/// [`Group::process_incoming_message_validated`] fails, so
+/// [`FutureEpochBuffer::process_incoming_message`] instead holds them, up to
+/// the limit given to [`FutureEpochBuffer::new`], and processes them once
+/// the group reaches their epoch.
+///
+/// The buffer is kept in memory only and is not part of the stored group
+/// state.
+#[derive(Clone, Debug)]
+pub struct FutureEpochBuffer {
+    max_messages: usize,
+    messages: Vec<MlsMessage>,
+}
+
+impl FutureEpochBuffer {
+    /// Create a buffer holding at most `max_messages` messages.
+    pub fn new(max_messages: usize) -> Self {
+        Self {
+            max_messages,
+            messages: Vec::new(),
+        }
+    }
+
+    /// Number of buffered messages.
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Whether no message is buffered.
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Drop all buffered messages, e.g. after the group was left.
+    pub fn clear(&mut self) {
+        self.messages.clear();
+    }
+
+    /// Process `message` for `group`, or buffer it if it belongs to a future
+    /// epoch of `group`.
+    ///
+    /// If `message` is processed and advances the epoch of `group`, buffered
+    /// messages that reached their epoch are processed next, in the order in
+    /// which they were received. The results of all processed messages are
+    /// returned in processing order, so an empty vector means `message` was
+    /// buffered. If the buffer is full, the result is
+    /// [`MlsError::FutureEpochBufferFull`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn process_incoming_message<C>(
+        &mut self,
+        group: &mut Group<C>,
+        message: MlsMessage,
+    ) -> Vec<Result<ReceivedMessage, MlsError>>
+    where
+        C: ClientConfig + Clone,
+    {
+        let is_future = message.group_id() == Some(group.group_id())
+            && matches!(message.epoch(), Some(epoch) if epoch > group.current_epoch());
+
+        if is_future {
+            if self.messages.len() >= self.max_messages {
+                return vec![Err(MlsError::FutureEpochBufferFull)];
+            }
+
+            self.messages.push(message);
+
+            return Vec::new();
+        }
+
+        let mut epoch = group.current_epoch();
+        let first = Self::process(group, message).await;
+        let mut results = vec![first];
+
+        // Replaying a buffered commit may reach the epoch of more messages
+        while group.current_epoch() > epoch && !self.messages.is_empty() {
+            epoch = group.current_epoch();
+
+            let (ready, pending) = core::mem::take(&mut self.messages)
+                .into_iter()
+                .partition::<Vec<_>, _>(|m| matches!(m.epoch(), Some(e) if e <= epoch));
+
+            self.messages = pending;
+
+            for message in ready {
+                results.push(Self::process(group, message).await);
+            }
+        }
+
+        results
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn process<C: ClientConfig + Clone>(
+        group: &mut Group<C>,
+        message: MlsMessage,
+    ) -> Result<ReceivedMessage, MlsError> {
+        group
+            .process_incoming_message_validated(Validated::new(message)?)
+            .await
+    }
+}
+
+#[cfg(all(test, feature = "private_message"))]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use super::FutureEpochBuffer;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{test_utils::test_group, ReceivedMessage},
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn future_epoch_messages_are_replayed_after_commit() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let commit = alice.group.commit(vec![]).await.unwrap().commit_message;
+        alice.process_pending_commit().await.unwrap();
+
+        let message = alice
+            .group
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+
+        let mut buffer = FutureEpochBuffer::new(10);
+
+        let results = buffer
+            .process_incoming_message(&mut bob.group, message)
+            .await;
+        assert!(results.is_empty());
+        assert_eq!(buffer.len(), 1);
+
+        let results = buffer
+            .process_incoming_message(&mut bob.group, commit)
+            .await;
+
+        assert_eq!(results.len(), 2);
+        assert_matches!(results[0], Ok(ReceivedMessage::Commit(_)));
+
+        assert_matches!(
+            &results[1],
+            Ok(ReceivedMessage::ApplicationMessage(m)) if m.data() == b"hello"
+        );
+
+        assert!(buffer.is_empty());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn future_epoch_buffer_is_bounded() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        alice.group.commit(vec![]).await.unwrap();
+        alice.process_pending_commit().await.unwrap();
+
+        let mut buffer = FutureEpochBuffer::new(1);
+
+        for expect_full in [false, true] {
+            let message = alice
+                .group
+                .encrypt_application_message(b"hello", vec![])
+                .await
+                .unwrap();
+
+            let results = buffer
+                .process_incoming_message(&mut bob.group, message)
+                .await;
+
+            if expect_full {
+                assert_matches!(results[..], [Err(MlsError::FutureEpochBufferFull)]);
+            } else {
+                assert!(results.is_empty());
+            }
+        }
+
+        assert_eq!(buffer.len(), 1);
+    }
+}
@@ -0,0 +1,283 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+
+use mls_rs_core::{crypto::CipherSuiteProvider, error::IntoAnyError};
+
+use crate::client::MlsError;
+
+const DEFAULT_RANDOM_GROUP_ID_LEN: usize = 32;
+const UUID_LEN: usize = 16;
+
+/// Strategy used to generate a group ID when one is not provided by the
+/// application.
+#[derive(Clone)]
+#[non_exhaustive]
+pub enum GroupIdGeneration {
+    /// Random bytes of the given length, generated by the cipher suite provider.
+    Random { length: usize },
+    /// A random (version 4) UUID encoded as a lowercase hyphenated string.
+    Uuid,
+    /// Application provided callback.
+    #[cfg(target_has_atomic = "ptr")]
+    Custom(Arc<dyn Fn() -> Vec<u8> + Send + Sync>),
+}
+
+impl GroupIdGeneration {
+    /// Generate group IDs using an application provided callback.
+    #[cfg(target_has_atomic = "ptr")]
+    pub fn custom<F>(generator: F) -> Self
+    where
+        F: Fn() -> Vec<u8> + Send + Sync + 'static,
+    {
+        Self::Custom(Arc::new(generator))
+    }
+
+    fn generate<P: CipherSuiteProvider>(&self, cs: &P) -> Result<Vec<u8>, MlsError> {
+        match self {
+            Self::Random { length } => cs
+                .random_bytes_vec(*length)
+                .map_err(|e| MlsError::CryptoProviderError(e.into_any_error())),
+            Self::Uuid => {
+                let mut bytes = [0u8; UUID_LEN];
+
+                cs.random_bytes(&mut bytes)
+                    .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+                Ok(uuid_v4_string(bytes))
+            }
+            #[cfg(target_has_atomic = "ptr")]
+            Self::Custom(generator) => Ok(generator()),
+        }
+    }
+}
+
+impl Default for GroupIdGeneration {
+    fn default() -> Self {
+        Self::Random {
+            length: DEFAULT_RANDOM_GROUP_ID_LEN,
+        }
+    }
+}
+
+impl Debug for GroupIdGeneration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Random { length } => f.debug_struct("Random").field("length", length).finish(),
+            Self::Uuid => f.write_str("Uuid"),
+            #[cfg(target_has_atomic = "ptr")]
+            Self::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+
+fn uuid_v4_string(mut bytes: [u8; UUID_LEN]) -> Vec<u8> {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+
+    // Set the version (4) and variant (RFC 4122) bits.
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let mut out = Vec::with_capacity(2 * UUID_LEN + 4);
+
+    for (i, b) in bytes.iter().enumerate() {
+        if matches!(i, 4 | 6 | 8 | 10) {
+            out.push(b'-');
+        }
+
+        out.push(HEX[(b >> 4) as usize]);
+        out.push(HEX[(b & 0x0f) as usize]);
+    }
+
+    out
+}
+
+/// Set of bytes allowed in a group ID.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GroupIdCharset {
+    /// Any byte value is allowed.
+    #[default]
+    Any,
+    /// Printable ASCII characters, excluding space.
+    PrintableAscii,
+    /// ASCII letters and digits as well as `-` and `_`.
+    Alphanumeric,
+}
+
+impl GroupIdCharset {
+    fn allows(&self, byte: u8) -> bool {
+        match self {
+            Self::Any => true,
+            Self::PrintableAscii => byte.is_ascii_graphic(),
+            Self::Alphanumeric => byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_',
+        }
+    }
+}
+
+/// Policy controlling how group IDs are generated and which group IDs are
+/// accepted.
+///
+/// The policy is applied to group IDs of groups created by the client, as
+/// well as to group IDs of groups joined via welcome message or external
+/// commit. Violations are reported as [`MlsError::GroupIdTooShort`],
+/// [`MlsError::GroupIdTooLong`] or [`MlsError::InvalidGroupIdCharacter`].
+///
+/// By default, group IDs are 32 random bytes and any group ID is accepted.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct GroupIdPolicy {
+    pub generation: GroupIdGeneration,
+    pub min_length: usize,
+    pub max_length: Option<usize>,
+    pub charset: GroupIdCharset,
+}
+
+impl GroupIdPolicy {
+    /// Create a policy with default settings.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set the strategy used to generate group IDs.
+    pub fn with_generation(self, generation: GroupIdGeneration) -> Self {
+        Self { generation, ..self }
+    }
+
+    /// Set the minimum accepted group ID length in bytes.
+    pub fn with_min_length(self, min_length: usize) -> Self {
+        Self { min_length, ..self }
+    }
+
+    /// Set the maximum accepted group ID length in bytes.
+    pub fn with_max_length(self, max_length: usize) -> Self {
+        Self {
+            max_length: Some(max_length),
+            ..self
+        }
+    }
+
+    /// Set the set of bytes accepted in group IDs.
+    pub fn with_charset(self, charset: GroupIdCharset) -> Self {
+        Self { charset, ..self }
+    }
+
+    /// Check that `group_id` satisfies the length bounds and charset of this policy.
+    pub fn validate(&self, group_id: &[u8]) -> Result<(), MlsError> {
+        if group_id.len() < self.min_length {
+            return Err(MlsError::GroupIdTooShort(group_id.len()));
+        }
+
+        if matches!(self.max_length, Some(max) if group_id.len() > max) {
+            return Err(MlsError::GroupIdTooLong(group_id.len()));
+        }
+
+        if let Some(position) = group_id.iter().position(|b| !self.charset.allows(*b)) {
+            return Err(MlsError::InvalidGroupIdCharacter(position));
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn generate<P: CipherSuiteProvider>(&self, cs: &P) -> Result<Vec<u8>, MlsError> {
+        let group_id = self.generation.generate(cs)?;
+        self.validate(&group_id)?;
+        Ok(group_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    use crate::{
+        client::test_utils::TEST_CIPHER_SUITE, crypto::test_utils::test_cipher_suite_provider,
+    };
+
+    #[test]
+    fn default_policy_generates_32_random_bytes() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let policy = GroupIdPolicy::new();
+
+        let id1 = policy.generate(&cs).unwrap();
+        let id2 = policy.generate(&cs).unwrap();
+
+        assert_eq!(id1.len(), 32);
+        assert_ne!(id1, id2);
+    }
+
+    #[test]
+    fn uuid_generation_produces_version_4_uuid() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let policy = GroupIdPolicy::new()
+            .with_generation(GroupIdGeneration::Uuid)
+            .with_charset(GroupIdCharset::Alphanumeric);
+
+        let id = policy.generate(&cs).unwrap();
+
+        assert_eq!(id.len(), 36);
+        assert_eq!(id[14], b'4');
+        assert!(matches!(id[19], b'8' | b'9' | b'a' | b'b'));
+
+        for i in [8, 13, 18, 23] {
+            assert_eq!(id[i], b'-');
+        }
+    }
+
+    #[test]
+    fn custom_generation_uses_callback() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let policy = GroupIdPolicy::new()
+            .with_generation(GroupIdGeneration::custom(|| b"my-group".to_vec()));
+
+        assert_eq!(policy.generate(&cs).unwrap(), b"my-group".to_vec());
+    }
+
+    #[test]
+    fn custom_generation_is_validated() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let policy = GroupIdPolicy::new()
+            .with_generation(GroupIdGeneration::custom(|| b"ab".to_vec()))
+            .with_min_length(3);
+
+        assert_matches!(policy.generate(&cs), Err(MlsError::GroupIdTooShort(2)));
+    }
+
+    #[test]
+    fn validation_reports_each_violation() {
+        let policy = GroupIdPolicy::new()
+            .with_min_length(2)
+            .with_max_length(4)
+            .with_charset(GroupIdCharset::PrintableAscii);
+
+        assert_matches!(policy.validate(b"a"), Err(MlsError::GroupIdTooShort(1)));
+        assert_matches!(policy.validate(b"abcde"), Err(MlsError::GroupIdTooLong(5)));
+
+        assert_matches!(
+            policy.validate(b"a b"),
+            Err(MlsError::InvalidGroupIdCharacter(1))
+        );
+
+        assert_matches!(policy.validate(b"ab~"), Ok(()));
+    }
+
+    #[test]
+    fn default_policy_accepts_any_group_id() {
+        let policy = GroupIdPolicy::new();
+
+        assert_matches!(policy.validate(&[]), Ok(()));
+        assert_matches!(policy.validate(&vec![0xff; 1000]), Ok(()));
+    }
+}
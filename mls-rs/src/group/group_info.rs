@@ -7,7 +7,10 @@ use core::fmt::{self, Debug};
 use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
 use mls_rs_core::extension::ExtensionList;
 
-use crate::{signer::Signable, tree_kem::node::LeafIndex};
+use crate::{
+    signer::{Signable, SignatureLabel},
+    tree_kem::node::LeafIndex,
+};
 
 use super::{ConfirmationTag, GroupContext};
 
@@ -69,7 +72,7 @@ struct SignableGroupInfo<'a> {
 }
 
 impl<'a> Signable<'a> for GroupInfo {
-    const SIGN_LABEL: &'static str = "GroupInfoTBS";
+    const SIGN_LABEL: &'static str = SignatureLabel::GroupInfo.as_str();
     type SigningContext = ();
 
     fn signature(&self) -> &[u8] {
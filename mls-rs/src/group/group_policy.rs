@@ -0,0 +1,219 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use mls_rs_core::extension::ExtensionList;
+
+use crate::{
+    client::MlsError, client_config::ClientConfig, extension::GroupPolicyExt, group::Group,
+};
+
+/// Client-wide description of the [group policies](GroupPolicyExt) the
+/// client can honor.
+///
+/// The group policy is checked when joining a group with a welcome message
+/// or an external commit. Groups whose policy the client can't honor are
+/// refused with [`MlsError::UnsupportedGroupPolicy`]. By default, every
+/// policy is accepted, as well as groups without policy.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct GroupPolicySupport {
+    min_retention_period: Option<u64>,
+    encrypted_handshake: bool,
+    admin_list: bool,
+    require_policy: bool,
+}
+
+impl Default for GroupPolicySupport {
+    fn default() -> Self {
+        Self {
+            min_retention_period: None,
+            encrypted_handshake: cfg!(feature = "private_message"),
+            admin_list: true,
+            require_policy: false,
+        }
+    }
+}
+
+impl GroupPolicySupport {
+    /// Create a description accepting every policy.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Only accept retention periods of at least `min_retention_period`
+    /// seconds, e.g. because messages are deleted by a daily job.
+    pub fn with_min_retention_period(self, min_retention_period: u64) -> Self {
+        Self {
+            min_retention_period: Some(min_retention_period),
+            ..self
+        }
+    }
+
+    /// Refuse policies that require encrypted proposals and commits.
+    ///
+    /// Such policies are always refused without the `private_message`
+    /// feature.
+    pub fn without_encrypted_handshake(self) -> Self {
+        Self {
+            encrypted_handshake: false,
+            ..self
+        }
+    }
+
+    /// Refuse policies that reference a list of administrators, e.g. because
+    /// the application does not implement administration.
+    pub fn without_admin_list(self) -> Self {
+        Self {
+            admin_list: false,
+            ..self
+        }
+    }
+
+    /// Refuse groups without policy.
+    pub fn with_required_policy(self) -> Self {
+        Self {
+            require_policy: true,
+            ..self
+        }
+    }
+
+    /// Determines if the client can honor `policy`.
+    pub fn supports(&self, policy: &GroupPolicyExt) -> bool {
+        let retention_supported = match (self.min_retention_period, policy.retention_period) {
+            (Some(min), Some(period)) => period >= min,
+            _ => true,
+        };
+
+        retention_supported
+            && (self.encrypted_handshake || !policy.encrypt_handshake)
+            && (self.admin_list || policy.admin_list.is_none())
+    }
+
+    pub(crate) fn check(&self, extensions: &ExtensionList) -> Result<(), MlsError> {
+        match extensions.get_as::<GroupPolicyExt>()? {
+            Some(policy) if !self.supports(&policy) => Err(MlsError::UnsupportedGroupPolicy),
+            None if self.require_policy => Err(MlsError::UnsupportedGroupPolicy),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Policy of the group, if it was set when creating the group.
+    pub fn group_policy(&self) -> Result<Option<GroupPolicyExt>, MlsError> {
+        Ok(self.context().extensions.get_as()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::GroupPolicySupport;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        client_builder::test_utils::TestClientBuilder,
+        extension::{GroupPolicyExt, GROUP_POLICY_EXTENSION_TYPE},
+        group::test_utils::{group_extensions, test_group_custom_config, TestGroup, TEST_GROUP},
+        identity::test_utils::get_test_signing_identity,
+    };
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn policy_group(policy: GroupPolicyExt) -> TestGroup {
+        let (signing_identity, secret_key) =
+            get_test_signing_identity(TEST_CIPHER_SUITE, b"alice").await;
+
+        let mut extensions = group_extensions();
+        extensions.set_from(policy).unwrap();
+
+        let group = TestClientBuilder::new_for_test()
+            .used_protocol_version(TEST_PROTOCOL_VERSION)
+            .extension_type(GROUP_POLICY_EXTENSION_TYPE)
+            .signing_identity(signing_identity, secret_key, TEST_CIPHER_SUITE)
+            .build()
+            .create_group_with_id(TEST_GROUP.to_vec(), extensions)
+            .await
+            .unwrap();
+
+        TestGroup { group }
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn join_with_support(
+        group: &mut TestGroup,
+        support: GroupPolicySupport,
+    ) -> Result<TestGroup, MlsError> {
+        group
+            .join_with_custom_config("bob", true, |c| {
+                c.0.settings
+                    .extension_types
+                    .push(GROUP_POLICY_EXTENSION_TYPE);
+
+                c.0.settings.group_policy_support = support.clone();
+            })
+            .await
+            .map(|(group, _)| group)
+    }
+
+    #[test]
+    fn support_checks_each_requirement() {
+        let support = GroupPolicySupport::new()
+            .with_min_retention_period(3600)
+            .without_admin_list();
+
+        assert!(support.supports(&GroupPolicyExt::new()));
+        assert!(support.supports(&GroupPolicyExt::new().with_retention_period(3600)));
+        assert!(!support.supports(&GroupPolicyExt::new().with_retention_period(60)));
+        assert!(!support.supports(&GroupPolicyExt::new().with_admin_list(b"admins".to_vec())));
+
+        let support = GroupPolicySupport::new().without_encrypted_handshake();
+
+        assert!(!support.supports(&GroupPolicyExt::new().with_encrypted_handshake()));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn policy_can_be_read_after_joining() {
+        let policy = GroupPolicyExt::new()
+            .with_retention_period(86400)
+            .with_admin_list(b"admins".to_vec());
+
+        let mut alice = policy_group(policy.clone()).await;
+        let bob = join_with_support(&mut alice, GroupPolicySupport::new())
+            .await
+            .unwrap();
+
+        assert_eq!(alice.group.group_policy().unwrap(), Some(policy.clone()));
+        assert_eq!(bob.group.group_policy().unwrap(), Some(policy));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn joining_group_with_unsupported_policy_fails() {
+        let mut alice = policy_group(GroupPolicyExt::new().with_retention_period(60)).await;
+
+        let support = GroupPolicySupport::new().with_min_retention_period(3600);
+        let res = join_with_support(&mut alice, support).await.map(|_| ());
+
+        assert_matches!(res, Err(MlsError::UnsupportedGroupPolicy));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn joining_group_without_required_policy_fails() {
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.extension_type(GROUP_POLICY_EXTENSION_TYPE)
+        })
+        .await;
+
+        let support = GroupPolicySupport::new().with_required_policy();
+        let res = join_with_support(&mut alice, support).await.map(|_| ());
+
+        assert_matches!(res, Err(MlsError::UnsupportedGroupPolicy));
+    }
+}
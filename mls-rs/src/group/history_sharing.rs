@@ -0,0 +1,301 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{
+    crypto::{CipherSuiteProvider, HpkeCiphertext},
+    secret::Secret,
+};
+use zeroize::Zeroizing;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{framing::MlsMessagePayload, Group},
+    key_package::KeyPackageRef,
+    tree_kem::hpke_encryption::HpkeEncryptable,
+    CipherSuite, MlsMessage,
+};
+
+const EXPORTER_LABEL: &[u8] = b"history key";
+
+/// History key created by a committer for a member it adds, with
+/// [`Group::history_key_package`].
+///
+/// The history key is exported from the epoch in which the member is added,
+/// i.e. the last epoch before the member joins, and is encrypted to the HPKE
+/// key of the leaf node of the new member. The application can use it to
+/// give the new member access to messages sent before it joined, e.g. by
+/// encrypting a history archive with it. The new member decrypts the key
+/// with [`Group::open_history_key_package`].
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[non_exhaustive]
+pub struct HistoryKeyPackage {
+    /// Cipher suite of the group, used for HPKE.
+    pub cipher_suite: CipherSuite,
+    /// Identifier of the group.
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    pub group_id: Vec<u8>,
+    /// Epoch from which the history key was exported.
+    pub epoch: u64,
+    /// Application context provided when exporting the history key.
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    pub context: Vec<u8>,
+    /// Reference to the key package of the new member.
+    pub new_member: KeyPackageRef,
+    /// HPKE encryption of the history key.
+    pub ciphertext: HpkeCiphertext,
+}
+
+impl Debug for HistoryKeyPackage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HistoryKeyPackage")
+            .field("cipher_suite", &self.cipher_suite)
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .field("context", &mls_rs_core::debug::pretty_bytes(&self.context))
+            .field("new_member", &self.new_member)
+            .field("ciphertext", &self.ciphertext)
+            .finish()
+    }
+}
+
+impl HistoryKeyPackage {
+    /// Serialize the history key package.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+
+    /// Deserialize a history key package produced by
+    /// [`HistoryKeyPackage::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    // Everything but the ciphertext is authenticated as HPKE context.
+    fn hpke_context(&self) -> Result<Vec<u8>, MlsError> {
+        HistoryKeyContext {
+            cipher_suite: self.cipher_suite,
+            group_id: &self.group_id,
+            epoch: self.epoch,
+            context: &self.context,
+            new_member: &self.new_member,
+        }
+        .mls_encode_to_vec()
+        .map_err(Into::into)
+    }
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct HistoryKeyContext<'a> {
+    cipher_suite: CipherSuite,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: &'a [u8],
+    epoch: u64,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    context: &'a [u8],
+    new_member: &'a KeyPackageRef,
+}
+
+struct HistoryKey(Zeroizing<Vec<u8>>);
+
+impl HpkeEncryptable for HistoryKey {
+    const ENCRYPT_LABEL: &'static str = "HistoryKey";
+
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self, MlsError> {
+        Ok(Self(Zeroizing::new(bytes)))
+    }
+
+    fn get_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        Ok(self.0.to_vec())
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Export a history key from the current epoch and encrypt it to the
+    /// member joining with `key_package`.
+    ///
+    /// This is meant to be called by the committer adding `key_package`,
+    /// after creating the commit and before applying it with
+    /// [`Group::apply_pending_commit`]. `context` is an application defined
+    /// label that is bound to the history key. The returned package is sent
+    /// to the new member along with its welcome message. The history key
+    /// itself can be obtained with [`Group::history_key`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn history_key_package(
+        &self,
+        key_package: &MlsMessage,
+        context: &[u8],
+    ) -> Result<HistoryKeyPackage, MlsError> {
+        let MlsMessagePayload::KeyPackage(key_package) = &key_package.payload else {
+            return Err(MlsError::UnexpectedMessageType);
+        };
+
+        let mut package = HistoryKeyPackage {
+            cipher_suite: self.cipher_suite(),
+            group_id: self.group_id().to_vec(),
+            epoch: self.current_epoch(),
+            context: context.to_vec(),
+            new_member: key_package
+                .to_reference(&self.cipher_suite_provider)
+                .await?,
+            ciphertext: HpkeCiphertext {
+                kem_output: Vec::new(),
+                ciphertext: Vec::new(),
+            },
+        };
+
+        let history_key = self.history_key(context).await?;
+
+        package.ciphertext = HistoryKey(Zeroizing::new(history_key.as_bytes().to_vec()))
+            .encrypt(
+                &self.cipher_suite_provider,
+                &key_package.leaf_node.public_key,
+                &package.hpke_context()?,
+            )
+            .await?;
+
+        Ok(package)
+    }
+
+    /// Export the history key of the current epoch for `context`, as shared
+    /// with new members by [`Group::history_key_package`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn history_key(&self, context: &[u8]) -> Result<Secret, MlsError> {
+        let len = self.cipher_suite_provider.kdf_extract_size();
+
+        self.export_secret(EXPORTER_LABEL, context, len).await
+    }
+
+    /// Decrypt a history key created for the local member with
+    /// [`Group::history_key_package`] when it was added to the group.
+    ///
+    /// The history key is encrypted to the leaf node the local member joined
+    /// with, so this must be called before the local member updates its leaf.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn open_history_key_package(
+        &self,
+        package: &HistoryKeyPackage,
+    ) -> Result<Secret, MlsError> {
+        if package.cipher_suite != self.cipher_suite() {
+            return Err(MlsError::CipherSuiteMismatch);
+        }
+
+        if package.group_id != self.group_id() {
+            return Err(MlsError::GroupIdMismatch);
+        }
+
+        let secret_key = self
+            .private_tree
+            .secret_keys
+            .first()
+            .and_then(Option::as_ref)
+            .ok_or(MlsError::GroupUsedAfterRemoval)?;
+
+        let public_key = &self.current_user_leaf_node()?.public_key;
+
+        let history_key = HistoryKey::decrypt(
+            &self.cipher_suite_provider,
+            secret_key,
+            public_key,
+            &package.hpke_context()?,
+            &package.ciphertext,
+        )
+        .await?;
+
+        Ok(history_key.0.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::HistoryKeyPackage;
+
+    use crate::{
+        client::{
+            test_utils::{test_client_with_key_pkg, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::test_utils::{test_group, TestGroup},
+        group::Validated,
+        MlsMessage,
+    };
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn add_member_sharing_history(alice: &mut TestGroup) -> (TestGroup, HistoryKeyPackage) {
+        let (bob, key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .add_member(key_package.clone())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let package = alice
+            .group
+            .history_key_package(&key_package, b"archive")
+            .await
+            .unwrap();
+
+        alice.process_pending_commit().await.unwrap();
+
+        let (bob, _) = bob
+            .join_group_validated(
+                commit.ratchet_tree,
+                &Validated::new(commit.welcome_messages[0].clone()).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        (TestGroup { group: bob }, package)
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn new_member_can_open_history_key() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let history_key = alice.group.history_key(b"archive").await.unwrap();
+
+        let (bob, package) = add_member_sharing_history(&mut alice).await;
+        let package = HistoryKeyPackage::from_bytes(&package.to_bytes().unwrap()).unwrap();
+
+        let opened = bob.group.open_history_key_package(&package).await.unwrap();
+
+        assert_eq!(opened, history_key);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn modified_history_key_package_fails_to_open() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob, mut package) = add_member_sharing_history(&mut alice).await;
+
+        package.context = b"other".to_vec();
+
+        let res = bob.group.open_history_key_package(&package).await;
+        assert_matches!(res, Err(MlsError::CryptoProviderError(_)));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn history_key_package_requires_key_package() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let commit: MlsMessage = alice.join("bob").await.1;
+
+        let res = alice.group.history_key_package(&commit, b"archive").await;
+        assert_matches!(res, Err(MlsError::UnexpectedMessageType));
+    }
+}
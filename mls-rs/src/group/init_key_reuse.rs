@@ -0,0 +1,181 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::{
+    client::MlsError,
+    group::{proposal::AddProposal, proposal_filter::ProposalInfo},
+    tree_kem::TreeKemPublic,
+};
+
+/// Strictness of the checks against reuse of the init key of a key package
+/// added to a group.
+///
+/// RFC 9420 only requires the init key of a key package to differ from the
+/// encryption key of its own leaf node. An init key that is also the HPKE key
+/// of another node, or of another added key package, allows the holder of the
+/// corresponding secret key to decrypt the Welcome message of the new member.
+/// Stricter checks reject such Add proposals, which may however reject
+/// commits accepted by other implementations.
+///
+/// Rejected proposals by reference are filtered out of commits created by
+/// the client. Received commits with rejected proposals fail to process with
+/// [`MlsError::InitKeyReusedInCommit`] or [`MlsError::InitKeyReusedInTree`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InitKeyReusePolicy {
+    /// Perform only the checks required by RFC 9420.
+    #[default]
+    Allow,
+    /// Reject Add proposals whose init key is the init key or leaf encryption
+    /// key of another Add proposal of the same commit.
+    RejectWithinCommit,
+    /// In addition to [`RejectWithinCommit`](Self::RejectWithinCommit),
+    /// reject Add proposals whose init key is the HPKE key of a leaf or
+    /// parent node of the tree.
+    Reject,
+}
+
+impl InitKeyReusePolicy {
+    /// Check the init key of the Add proposal at `index` in `adds`, to be
+    /// applied to `tree`.
+    pub(crate) fn check(
+        self,
+        tree: &TreeKemPublic,
+        adds: &[ProposalInfo<AddProposal>],
+        index: usize,
+    ) -> Result<(), MlsError> {
+        if self == Self::Allow {
+            return Ok(());
+        }
+
+        let init_key = &adds[index].proposal.key_package.hpke_init_key;
+
+        let reused_by = adds.iter().enumerate().find(|(i, p)| {
+            let key_package = &p.proposal.key_package;
+
+            *i != index
+                && (&key_package.hpke_init_key == init_key
+                    || &key_package.leaf_node.public_key == init_key)
+        });
+
+        if let Some((other, _)) = reused_by {
+            return Err(MlsError::InitKeyReusedInCommit(index, other));
+        }
+
+        if self == Self::RejectWithinCommit {
+            return Ok(());
+        }
+
+        let node = tree
+            .nodes
+            .iter()
+            .position(|node| node.as_ref().map_or(false, |n| n.public_key() == init_key));
+
+        match node {
+            Some(node) => Err(MlsError::InitKeyReusedInTree(index, node as u32)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use mls_rs_core::crypto::HpkePublicKey;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        crypto::test_utils::test_cipher_suite_provider,
+        group::{
+            framing::MlsMessagePayload,
+            test_utils::{test_group_custom_config, TestGroup},
+        },
+        key_package::test_utils::test_key_package_with_signer,
+        signer::Signable,
+        MlsMessage,
+    };
+
+    use super::InitKeyReusePolicy;
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn group_with_policy(policy: InitKeyReusePolicy) -> TestGroup {
+        test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.init_key_reuse_policy(policy)
+        })
+        .await
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn key_package_with_init_key(name: &str, init_key: Option<HpkePublicKey>) -> MlsMessage {
+        let (mut key_package, signer) =
+            test_key_package_with_signer(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, name).await;
+
+        if let Some(init_key) = init_key {
+            key_package.hpke_init_key = init_key;
+
+            let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+            key_package.sign(&cs, &signer, &()).await.unwrap();
+        }
+
+        MlsMessage::new(
+            TEST_PROTOCOL_VERSION,
+            MlsMessagePayload::KeyPackage(key_package),
+        )
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn init_key_of_tree_node_is_rejected_if_strict() {
+        for (policy, rejected) in [
+            (InitKeyReusePolicy::Reject, true),
+            (InitKeyReusePolicy::RejectWithinCommit, false),
+        ] {
+            let mut alice = group_with_policy(policy).await;
+            let leaf_key = alice
+                .group
+                .current_user_leaf_node()
+                .unwrap()
+                .public_key
+                .clone();
+            let key_package = key_package_with_init_key("bob", Some(leaf_key)).await;
+
+            let res = alice
+                .group
+                .commit_builder()
+                .add_member(key_package)
+                .unwrap()
+                .build()
+                .await;
+
+            if rejected {
+                assert_matches!(res, Err(MlsError::InitKeyReusedInTree(0, 0)));
+            } else {
+                assert!(res.is_ok());
+            }
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn init_key_shared_between_adds_is_rejected() {
+        let mut alice = group_with_policy(InitKeyReusePolicy::RejectWithinCommit).await;
+
+        let bob = key_package_with_init_key("bob", None).await;
+        let init_key = bob.clone().into_key_package().unwrap().hpke_init_key;
+        let carol = key_package_with_init_key("carol", Some(init_key)).await;
+
+        let res = alice
+            .group
+            .commit_builder()
+            .add_member(bob)
+            .unwrap()
+            .add_member(carol)
+            .unwrap()
+            .build()
+            .await;
+
+        assert_matches!(res, Err(MlsError::InitKeyReusedInCommit(..)));
+    }
+}
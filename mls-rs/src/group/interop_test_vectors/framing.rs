@@ -20,7 +20,7 @@ use crate::{
         proposal::{Proposal, RemoveProposal},
         secret_tree::test_utils::get_test_tree,
         test_utils::{random_bytes, test_group_custom_config},
-        AuthenticatedContent, Commit, Group, GroupContext, MlsMessage, Sender,
+        AuthenticatedContent, Commit, Group, GroupContext, Sender, Validated,
     },
     mls_rules::DefaultMlsRules,
     test_utils::is_edwards,
@@ -451,11 +451,15 @@ async fn process_message<P: CipherSuiteProvider>(
 ) -> Content {
     // Enabling encryption doesn't matter for processing
     let mut group = make_group(test_case, false, true, cs).await;
-    let message = MlsMessage::mls_decode(&mut &*message).unwrap();
-    let evt_or_cont = group.get_event_from_incoming_message(message);
+    let message = Validated::from_bytes(message).unwrap();
+    let evt_or_cont = group.get_event_from_incoming_message(
+        message,
+        #[cfg(feature = "application_lanes")]
+        0,
+    );
 
     match evt_or_cont.await.unwrap() {
-        EventOrContent::Content(content) => content.content.content,
+        EventOrContent::Content(content, _) => content.content.content,
         EventOrContent::Event(_) => panic!("expected content, got event"),
     }
 }
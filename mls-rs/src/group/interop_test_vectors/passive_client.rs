@@ -18,7 +18,7 @@ use rand::{seq::IteratorRandom, Rng, SeedableRng};
 use crate::{
     client_builder::{ClientBuilder, MlsConfig},
     crypto::test_utils::TestCryptoProvider,
-    group::{ClientConfig, CommitBuilder, ExportedTree},
+    group::{ClientConfig, CommitBuilder, ExportedTree, Validated},
     identity::basic::BasicIdentityProvider,
     key_package::KeyPackageGeneration,
     mls_rules::CommitOptions,
@@ -195,7 +195,10 @@ async fn interop_passive_client() {
             .ratchet_tree
             .map(|t| ExportedTree::from_bytes(&t.0).unwrap());
 
-        let (mut group, _info) = client.join_group(tree, &welcome).await.unwrap();
+        let (mut group, _info) = client
+            .join_group_validated(tree, &Validated::new(welcome.clone()).unwrap())
+            .await
+            .unwrap();
 
         assert_eq!(
             group.epoch_authenticator().unwrap().to_vec(),
@@ -439,7 +442,10 @@ pub async fn generate_passive_client_proposal_tests() -> Vec<TestCase> {
         let mut group = groups[4].clone();
 
         for (p, _) in proposals.iter().filter(|(_, i)| *i != 4) {
-            group.process_incoming_message(p.clone()).await.unwrap();
+            group
+                .process_incoming_message_validated(Validated::new(p.clone()).unwrap())
+                .await
+                .unwrap();
         }
 
         let commit = group.commit(vec![]).await.unwrap().commit_message;
@@ -684,7 +690,10 @@ pub async fn add_random_members<C: MlsConfig>(
         let commit = commit_output.welcome_messages[0].clone();
 
         let group = client
-            .join_group(Some(tree_data.clone()), &commit)
+            .join_group_validated(
+                Some(tree_data.clone()),
+                &Validated::new(commit.clone()).unwrap(),
+            )
             .await
             .unwrap()
             .0;
@@ -0,0 +1,230 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::string::String;
+
+use crate::{client::MlsError, client_config::ClientConfig, group::Group};
+
+const EXPORTER_LABEL: &[u8] = b"invite code";
+const MAX_INVITE_CODE_LEN: usize = 32;
+
+// Bytes of exported secret used to compute each character. The bias
+// introduced by reducing them modulo the alphabet size is negligible.
+const BYTES_PER_CHAR: usize = 4;
+
+const DECIMAL_ALPHABET: &[u8] = b"0123456789";
+
+// Crockford's base32 alphabet, which excludes I, L, O and U.
+const BASE32_ALPHABET: &[u8] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Alphabet used to encode an [`InviteCode`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InviteCodeEncoding {
+    /// Decimal digits, e.g. `"482913"`.
+    #[default]
+    Decimal,
+    /// Crockford's base32, e.g. `"7K2QX9"`. Verification is case insensitive
+    /// and accepts `I` and `L` for `1` and `O` for `0`.
+    Base32,
+}
+
+impl InviteCodeEncoding {
+    fn alphabet(&self) -> &'static [u8] {
+        match self {
+            Self::Decimal => DECIMAL_ALPHABET,
+            Self::Base32 => BASE32_ALPHABET,
+        }
+    }
+
+    fn normalize(&self, c: char) -> char {
+        match (self, c.to_ascii_uppercase()) {
+            (Self::Base32, 'I' | 'L') => '1',
+            (Self::Base32, 'O') => '0',
+            (_, c) => c,
+        }
+    }
+}
+
+/// Length and encoding of an [`InviteCode`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct InviteCodeFormat {
+    /// Number of characters of the code, between 1 and 32.
+    pub length: usize,
+    pub encoding: InviteCodeEncoding,
+}
+
+impl InviteCodeFormat {
+    pub fn new(length: usize, encoding: InviteCodeEncoding) -> Self {
+        Self { length, encoding }
+    }
+
+    fn validate(&self) -> Result<(), MlsError> {
+        (1..=MAX_INVITE_CODE_LEN)
+            .contains(&self.length)
+            .then_some(())
+            .ok_or(MlsError::InvalidInviteCodeLength(self.length))
+    }
+}
+
+impl Default for InviteCodeFormat {
+    fn default() -> Self {
+        Self::new(6, InviteCodeEncoding::Decimal)
+    }
+}
+
+/// Short human-shareable code derived from the exporter secret of an epoch,
+/// computed with [`Group::invite_code`].
+///
+/// All members of the group compute the same code in the same epoch. Users
+/// can read the code to each other, e.g. over a phone call, and check with
+/// [`Group::verify_invite_code`] that they are in the same group. The code
+/// changes with every commit.
+///
+/// Short codes only resist online guessing. A code of `length` characters
+/// provides `length * log2(alphabet size)` bits of security, e.g. about 20
+/// bits for 6 decimal digits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct InviteCode {
+    /// Epoch in which the code was computed.
+    pub epoch: u64,
+    /// The code.
+    pub code: String,
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Compute the [`InviteCode`] of the current epoch in the given `format`.
+    ///
+    /// Codes computed in distinct formats are unrelated.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn invite_code(&self, format: InviteCodeFormat) -> Result<InviteCode, MlsError> {
+        format.validate()?;
+
+        let alphabet = format.encoding.alphabet();
+        let context = [format.encoding as u8, format.length as u8];
+
+        let secret = self
+            .export_secret(EXPORTER_LABEL, &context, format.length * BYTES_PER_CHAR)
+            .await?;
+
+        let code = secret
+            .as_bytes()
+            .chunks_exact(BYTES_PER_CHAR)
+            .map(|chunk| {
+                let value = u32::from_be_bytes(chunk.try_into().unwrap_or_default());
+                alphabet[value as usize % alphabet.len()] as char
+            })
+            .collect();
+
+        Ok(InviteCode {
+            epoch: self.current_epoch(),
+            code,
+        })
+    }
+
+    /// Check that `code`, entered by a user, is the [`InviteCode`] of the
+    /// current epoch in the given `format`.
+    ///
+    /// Spaces and dashes in `code` are ignored, so that long codes can be
+    /// displayed in groups. A code computed in a previous epoch is not valid
+    /// anymore.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn verify_invite_code(
+        &self,
+        format: InviteCodeFormat,
+        code: &str,
+    ) -> Result<bool, MlsError> {
+        let expected = self.invite_code(format).await?;
+
+        let code = code
+            .chars()
+            .filter(|c| !matches!(c, ' ' | '-'))
+            .map(|c| format.encoding.normalize(c));
+
+        Ok(code.eq(expected.code.chars()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, vec};
+    use assert_matches::assert_matches;
+
+    use super::{InviteCodeEncoding, InviteCodeFormat, BASE32_ALPHABET};
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::test_utils::test_group,
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn members_compute_equal_invite_codes() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob, _) = alice.join("bob").await;
+
+        let format = InviteCodeFormat::default();
+        let code = alice.group.invite_code(format).await.unwrap();
+
+        assert_eq!(code.code.len(), 6);
+        assert!(code.code.chars().all(|c| c.is_ascii_digit()));
+
+        let bob_code = bob.group.invite_code(format).await.unwrap();
+        assert_eq!(bob_code, code);
+
+        let res = bob.group.verify_invite_code(format, &code.code).await;
+        assert!(res.unwrap());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn base32_invite_code_verification_is_lenient() {
+        let alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let format = InviteCodeFormat::new(8, InviteCodeEncoding::Base32);
+        let code = alice.group.invite_code(format).await.unwrap().code;
+
+        assert!(code.bytes().all(|c| BASE32_ALPHABET.contains(&c)));
+
+        let entered = format!("{}-{}", &code[..4], &code[4..])
+            .to_ascii_lowercase()
+            .replace('1', "l")
+            .replace('0', "o");
+
+        let res = alice.group.verify_invite_code(format, &entered).await;
+        assert!(res.unwrap());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn invite_code_is_valid_for_one_epoch() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let format = InviteCodeFormat::new(12, InviteCodeEncoding::Decimal);
+        let code = alice.group.invite_code(format).await.unwrap();
+
+        alice.group.commit(vec![]).await.unwrap();
+        alice.process_pending_commit().await.unwrap();
+
+        let res = alice.group.verify_invite_code(format, &code.code).await;
+        assert!(!res.unwrap());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn invite_code_length_is_bounded() {
+        let alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        for length in [0, 33] {
+            let format = InviteCodeFormat::new(length, InviteCodeEncoding::Decimal);
+            let res = alice.group.invite_code(format).await;
+
+            assert_matches!(res, Err(MlsError::InvalidInviteCodeLength(l)) if l == length);
+        }
+    }
+}
@@ -68,6 +68,26 @@ impl Debug for KeySchedule {
     }
 }
 
+/// Derive a secret of `len` bytes from `exporter_secret` with the MLS
+/// exporter.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub(crate) async fn export_secret<P: CipherSuiteProvider>(
+    exporter_secret: &[u8],
+    label: &[u8],
+    context: &[u8],
+    len: usize,
+    cipher_suite: &P,
+) -> Result<Zeroizing<Vec<u8>>, MlsError> {
+    let secret = kdf_derive_secret(cipher_suite, exporter_secret, label).await?;
+
+    let context_hash = cipher_suite
+        .hash(context)
+        .await
+        .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+    kdf_expand_with_label(cipher_suite, &secret, b"exported", &context_hash, Some(len)).await
+}
+
 pub(crate) struct KeyScheduleDerivationResult {
     pub(crate) key_schedule: KeySchedule,
     pub(crate) confirmation_key: Zeroizing<Vec<u8>>,
@@ -226,6 +246,10 @@ impl KeySchedule {
         })
     }
 
+    pub(crate) fn exporter_secret(&self) -> &Zeroizing<Vec<u8>> {
+        &self.exporter_secret
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn export_secret<P: CipherSuiteProvider>(
         &self,
@@ -234,14 +258,7 @@ impl KeySchedule {
         len: usize,
         cipher_suite: &P,
     ) -> Result<Zeroizing<Vec<u8>>, MlsError> {
-        let secret = kdf_derive_secret(cipher_suite, &self.exporter_secret, label).await?;
-
-        let context_hash = cipher_suite
-            .hash(context)
-            .await
-            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
-
-        kdf_expand_with_label(cipher_suite, &secret, b"exported", &context_hash, Some(len)).await
+        export_secret(&self.exporter_secret, label, context, len, cipher_suite).await
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -502,6 +519,196 @@ async fn get_welcome_secret<P: CipherSuiteProvider>(
     kdf_derive_secret(cipher_suite, &epoch_seed, b"welcome").await
 }
 
+/// Deliberately direct implementation of the key schedule of RFC 9420,
+/// Section 8, sharing no code with [`KeySchedule`] other than the
+/// [`CipherSuiteProvider`]. It is used to check the implementation for
+/// regressions in differential tests.
+#[cfg(any(test, feature = "fuzz_util"))]
+pub(crate) mod reference {
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use mls_rs_codec::MlsEncode;
+    use zeroize::Zeroizing;
+
+    use crate::{
+        group::GroupContext, psk::secret::PskSecret, tree_kem::path_secret::PathSecret,
+        CipherSuiteProvider,
+    };
+
+    use super::{InitSecret, KeySchedule};
+
+    const DERIVED_SECRET_LABELS: [&str; 9] = [
+        "sender data",
+        "encryption",
+        "exporter",
+        "authentication",
+        "external",
+        "confirm",
+        "membership",
+        "resumption",
+        "init",
+    ];
+
+    /// Compute the joiner secret and all secrets derived from the epoch
+    /// secret, labeled as in RFC 9420.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn reference_key_schedule<P: CipherSuiteProvider>(
+        cipher_suite_provider: &P,
+        init_secret: &[u8],
+        commit_secret: &[u8],
+        psk_secret: &[u8],
+        group_context: &[u8],
+    ) -> Vec<(&'static str, Vec<u8>)> {
+        let hash_len = cipher_suite_provider.kdf_extract_size();
+
+        let joiner_seed = cipher_suite_provider
+            .kdf_extract(init_secret, commit_secret)
+            .await
+            .unwrap();
+
+        let joiner_secret = expand_with_label(
+            cipher_suite_provider,
+            &joiner_seed,
+            "joiner",
+            group_context,
+            hash_len,
+        )
+        .await;
+
+        let epoch_seed = cipher_suite_provider
+            .kdf_extract(&joiner_secret, psk_secret)
+            .await
+            .unwrap();
+
+        let epoch_secret = expand_with_label(
+            cipher_suite_provider,
+            &epoch_seed,
+            "epoch",
+            group_context,
+            hash_len,
+        )
+        .await;
+
+        let mut secrets = vec![("joiner", joiner_secret)];
+
+        for label in DERIVED_SECRET_LABELS {
+            let secret =
+                expand_with_label(cipher_suite_provider, &epoch_secret, label, &[], hash_len).await;
+
+            secrets.push((label, secret));
+        }
+
+        secrets
+    }
+
+    /// Derive the epoch following `init_secret` with [`KeySchedule`] and
+    /// assert that all resulting secrets match [`reference_key_schedule`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn assert_key_schedule_matches_reference<P: CipherSuiteProvider>(
+        cipher_suite_provider: &P,
+        init_secret: &[u8],
+        commit_secret: &[u8],
+        psk_secret: &[u8],
+        group_context: &GroupContext,
+    ) {
+        let last_key_schedule = KeySchedule::new(InitSecret(Zeroizing::new(init_secret.to_vec())));
+
+        let path_secret = PathSecret::from(commit_secret.to_vec());
+        let psk = PskSecret::from(psk_secret.to_vec());
+
+        let res = KeySchedule::from_key_schedule(
+            &last_key_schedule,
+            &path_secret,
+            group_context,
+            #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+            1,
+            &psk,
+            cipher_suite_provider,
+        )
+        .await
+        .unwrap();
+
+        let key_schedule = res.key_schedule;
+
+        let actual = [
+            ("joiner", res.joiner_secret.0.to_vec()),
+            (
+                "sender data",
+                res.epoch_secrets.sender_data_secret.as_ref().to_vec(),
+            ),
+            #[cfg(all(test, any(feature = "secret_tree_access", feature = "private_message")))]
+            (
+                "encryption",
+                res.epoch_secrets.secret_tree.get_root_secret(),
+            ),
+            ("exporter", key_schedule.exporter_secret.to_vec()),
+            (
+                "authentication",
+                key_schedule.authentication_secret.to_vec(),
+            ),
+            ("external", key_schedule.external_secret.to_vec()),
+            ("confirm", res.confirmation_key.to_vec()),
+            ("membership", key_schedule.membership_key.to_vec()),
+            #[cfg(feature = "psk")]
+            ("resumption", res.epoch_secrets.resumption_secret.to_vec()),
+            ("init", key_schedule.init_secret.0.to_vec()),
+        ];
+
+        let expected = reference_key_schedule(
+            cipher_suite_provider,
+            init_secret,
+            commit_secret,
+            psk_secret,
+            &group_context.mls_encode_to_vec().unwrap(),
+        )
+        .await;
+
+        for (label, secret) in actual {
+            let (_, expected) = expected.iter().find(|(l, _)| *l == label).unwrap();
+            assert_eq!(
+                &secret, expected,
+                "{label} secret differs from the reference"
+            );
+        }
+    }
+
+    /// ExpandWithLabel, with the KDFLabel structure encoded by hand.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn expand_with_label<P: CipherSuiteProvider>(
+        cipher_suite_provider: &P,
+        secret: &[u8],
+        label: &str,
+        context: &[u8],
+        len: usize,
+    ) -> Vec<u8> {
+        let full_label = [b"MLS 1.0 ".as_slice(), label.as_bytes()].concat();
+
+        let mut info = (len as u16).to_be_bytes().to_vec();
+        push_variable_length(&mut info, &full_label);
+        push_variable_length(&mut info, context);
+
+        cipher_suite_provider
+            .kdf_expand(secret, &info, len)
+            .await
+            .unwrap()
+            .to_vec()
+    }
+
+    /// Append `data` prefixed with its length as a variable-length integer
+    /// (RFC 9420, Section 2.1.2).
+    fn push_variable_length(out: &mut Vec<u8>, data: &[u8]) {
+        let len = data.len();
+
+        match len {
+            0..=0x3f => out.push(len as u8),
+            0x40..=0x3fff => out.extend_from_slice(&(0x4000 | len as u16).to_be_bytes()),
+            _ => out.extend_from_slice(&(0x8000_0000 | len as u32).to_be_bytes()),
+        }
+
+        out.extend_from_slice(data);
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod test_utils {
     use alloc::vec;
@@ -661,6 +868,45 @@ mod tests {
         secret: Vec<u8>,
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn key_schedule_matches_reference() {
+        use crate::crypto::test_utils::TestCryptoProvider;
+        use crate::group::test_utils::random_bytes;
+        use crate::CryptoProvider;
+
+        use super::reference::assert_key_schedule_matches_reference;
+
+        let crypto = TestCryptoProvider::new();
+
+        for cipher_suite in crypto.supported_cipher_suites() {
+            let cs_provider = crypto.cipher_suite_provider(cipher_suite).unwrap();
+            let key_size = cs_provider.kdf_extract_size();
+
+            for i in 0..16 {
+                // Group IDs of various lengths exercise all variable-length
+                // encodings of the group context in the KDF label.
+                let context = GroupContext {
+                    protocol_version: TEST_PROTOCOL_VERSION,
+                    cipher_suite,
+                    group_id: random_bytes(i * i * 128),
+                    epoch: i as u64,
+                    tree_hash: random_bytes(key_size),
+                    confirmed_transcript_hash: random_bytes(key_size).into(),
+                    extensions: ExtensionList::new(),
+                };
+
+                assert_key_schedule_matches_reference(
+                    &cs_provider,
+                    &random_bytes(key_size),
+                    &random_bytes(key_size),
+                    &random_bytes(key_size),
+                    &context,
+                )
+                .await;
+            }
+        }
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn test_key_schedule() {
         let test_cases: Vec<TestCase> =
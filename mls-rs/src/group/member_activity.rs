@@ -0,0 +1,187 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{
+    client_config::ClientConfig,
+    group::{
+        message_processor::ProvisionalState,
+        proposal::{Proposal, RemoveProposal},
+        Group, Member,
+    },
+    tree_kem::node::LeafIndex,
+};
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Last epoch in which the member at `index` was observed to be active,
+    /// if known.
+    ///
+    /// A member is active in an epoch if it sent a message, proposal or
+    /// commit in that epoch. Members are also active in the epoch in which
+    /// they were added or updated their leaf, and, when this member joins,
+    /// all current members are considered active in the epoch it joined.
+    pub fn member_last_active_epoch(&self, index: u32) -> Option<u64> {
+        self.member_activity.get(index as usize).copied().flatten()
+    }
+
+    /// Members, other than this member, that were not active in the last
+    /// `max_epochs` epochs, see [`Group::member_last_active_epoch`].
+    ///
+    /// Stale members are typically devices that were lost or uninstalled and
+    /// can be removed with the proposals returned by
+    /// [`Group::stale_member_removals`].
+    pub fn stale_members(&self, max_epochs: u64) -> Vec<Member> {
+        let self_index = self.current_member_index();
+        let current_epoch = self.current_epoch();
+
+        self.roster()
+            .members_iter()
+            .filter(|member| member.index != self_index)
+            .filter(|member| {
+                self.member_last_active_epoch(member.index)
+                    .map_or(false, |epoch| {
+                        current_epoch.saturating_sub(epoch) > max_epochs
+                    })
+            })
+            .collect()
+    }
+
+    /// Remove proposals for all [stale members](Group::stale_members), to be
+    /// committed with e.g.
+    /// [`CommitBuilder::raw_proposals`](crate::group::CommitBuilder::raw_proposals).
+    pub fn stale_member_removals(&self, max_epochs: u64) -> Vec<Proposal> {
+        self.stale_members(max_epochs)
+            .into_iter()
+            .map(|member| {
+                Proposal::Remove(RemoveProposal {
+                    to_remove: LeafIndex(member.index),
+                })
+            })
+            .collect()
+    }
+
+    /// Record that `member` was active in the current epoch.
+    pub(crate) fn record_member_activity(&mut self, member: LeafIndex) {
+        let epoch = self.current_epoch();
+        set_activity(&mut self.member_activity, member, Some(epoch));
+    }
+
+    /// Consider all current members active in the current epoch.
+    pub(crate) fn reset_member_activity(&mut self) {
+        let epoch = self.current_epoch();
+
+        self.member_activity = vec![None; self.current_epoch_tree().total_leaf_count() as usize];
+
+        let members = self
+            .current_epoch_tree()
+            .nodes
+            .non_empty_leaves()
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+
+        for index in members {
+            set_activity(&mut self.member_activity, index, Some(epoch));
+        }
+    }
+
+    /// Apply the membership changes of the commit creating the epoch of
+    /// `provisional_state`.
+    pub(crate) fn update_member_activity(&mut self, provisional_state: &ProvisionalState) {
+        let epoch = provisional_state.group_context.epoch;
+
+        for removal in &provisional_state.applied_proposals.removals {
+            set_activity(&mut self.member_activity, removal.proposal.to_remove, None);
+        }
+
+        #[cfg(feature = "by_ref_proposal")]
+        let updated = provisional_state.applied_proposals.update_senders.iter();
+
+        #[cfg(not(feature = "by_ref_proposal"))]
+        let updated = core::iter::empty();
+
+        let added = provisional_state.indexes_of_added_kpkgs.iter();
+
+        for index in updated
+            .chain(added)
+            .chain(&provisional_state.external_init_index)
+        {
+            set_activity(&mut self.member_activity, *index, Some(epoch));
+        }
+    }
+}
+
+fn set_activity(activity: &mut Vec<Option<u64>>, index: LeafIndex, epoch: Option<u64>) {
+    let index = *index as usize;
+
+    if activity.len() <= index {
+        activity.resize(index + 1, None);
+    }
+
+    activity[index] = epoch;
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::test_utils::{process_commit, test_group, test_n_member_group},
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn inactive_members_become_stale() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+
+        assert_eq!(groups[0].group.member_last_active_epoch(1), Some(1));
+        assert_eq!(groups[0].group.member_last_active_epoch(2), Some(2));
+
+        for committer in [0, 1] {
+            let commit = groups[committer].group.commit(vec![]).await.unwrap();
+            groups[committer].process_pending_commit().await.unwrap();
+            process_commit(&mut groups, commit.commit_message, committer as u32).await;
+        }
+
+        let alice = &mut groups[0];
+
+        assert_eq!(alice.group.current_epoch(), 4);
+        assert_eq!(alice.group.member_last_active_epoch(1), Some(4));
+
+        let stale = alice.group.stale_members(1);
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].index, 2);
+        assert!(alice.group.stale_members(2).is_empty());
+
+        let removals = alice.group.stale_member_removals(1);
+
+        alice
+            .group
+            .commit_builder()
+            .raw_proposals(removals)
+            .build()
+            .await
+            .unwrap();
+
+        alice.process_pending_commit().await.unwrap();
+
+        assert_eq!(alice.group.roster().members().len(), 2);
+        assert_eq!(alice.group.member_last_active_epoch(2), None);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn joiner_considers_members_active() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (_bob, _) = alice.join("bob").await;
+        let (carol, _) = alice.join("carol").await;
+
+        assert_eq!(carol.group.member_last_active_epoch(0), Some(2));
+        assert_eq!(carol.group.member_last_active_epoch(1), Some(2));
+        assert!(carol.group.stale_members(0).is_empty());
+    }
+}
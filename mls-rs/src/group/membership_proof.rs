@@ -0,0 +1,333 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{crypto::CipherSuiteProvider, identity::SigningIdentity};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{Group, GroupContext},
+    signer::{Signable, SignatureLabel},
+    tree_kem::{
+        leaf_node::LeafNode,
+        node::LeafIndex,
+        tree_hash::{tree_hash_from_path, TreeHashPathNode},
+    },
+    CipherSuite,
+};
+
+/// Statement by a member that it belongs to a group in an epoch, created
+/// with [`Group::membership_proof`].
+///
+/// The proof contains the leaf node of the member and the nodes needed to
+/// recompute the tree hash of the group from it, and is signed by the member.
+/// A third party that does not belong to the group checks the proof with
+/// [`MembershipProof::verify`] against the [`GroupContext`] of the epoch,
+/// e.g. taken from a [`GroupInfo`](crate::group::GroupInfo) it obtained from
+/// the delivery service. The proof is bound to an application `context`,
+/// such as a challenge chosen by the verifier, to prevent replays.
+///
+/// The proof reveals the leaf node of the member, but nothing else about
+/// other members than hashes.
+#[derive(Clone, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+pub struct MembershipProof {
+    cipher_suite: CipherSuite,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: Vec<u8>,
+    epoch: u64,
+    leaf_index: LeafIndex,
+    leaf_count: u32,
+    leaf_node: LeafNode,
+    path: Vec<TreeHashPathNode>,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    signature: Vec<u8>,
+}
+
+impl Debug for MembershipProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MembershipProof")
+            .field("cipher_suite", &self.cipher_suite)
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .field("leaf_index", &self.leaf_index)
+            .field("leaf_count", &self.leaf_count)
+            .field("leaf_node", &self.leaf_node)
+            .field("path", &self.path)
+            .field(
+                "signature",
+                &mls_rs_core::debug::pretty_bytes(&self.signature),
+            )
+            .finish()
+    }
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct MembershipProofTBS<'a> {
+    cipher_suite: CipherSuite,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: &'a [u8],
+    epoch: u64,
+    leaf_index: LeafIndex,
+    leaf_count: u32,
+    leaf_node: &'a LeafNode,
+    path: &'a [TreeHashPathNode],
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    context: &'a [u8],
+}
+
+impl<'a> Signable<'a> for MembershipProof {
+    const SIGN_LABEL: &'static str = SignatureLabel::MembershipProof.as_str();
+
+    type SigningContext = &'a [u8];
+
+    fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    fn signable_content(
+        &self,
+        context: &Self::SigningContext,
+    ) -> Result<Vec<u8>, mls_rs_codec::Error> {
+        MembershipProofTBS {
+            cipher_suite: self.cipher_suite,
+            group_id: &self.group_id,
+            epoch: self.epoch,
+            leaf_index: self.leaf_index,
+            leaf_count: self.leaf_count,
+            leaf_node: &self.leaf_node,
+            path: &self.path,
+            context,
+        }
+        .mls_encode_to_vec()
+    }
+
+    fn write_signature(&mut self, signature: Vec<u8>) {
+        self.signature = signature
+    }
+}
+
+impl MembershipProof {
+    /// Identifier of the group the member claims to belong to.
+    pub fn group_id(&self) -> &[u8] {
+        &self.group_id
+    }
+
+    /// Epoch in which the proof was created.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Leaf index of the member in the group.
+    pub fn member_index(&self) -> u32 {
+        *self.leaf_index
+    }
+
+    /// Signing identity of the member. The verifier is responsible for
+    /// validating the credential it contains.
+    pub fn signing_identity(&self) -> &SigningIdentity {
+        &self.leaf_node.signing_identity
+    }
+
+    /// Serialize the membership proof.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+
+    /// Deserialize a membership proof produced by
+    /// [`MembershipProof::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    /// Check that the member belongs to the group described by
+    /// `group_context` and signed this proof for `context`.
+    ///
+    /// `group_context` must come from a trusted source since the proof is
+    /// only as good as the tree hash it contains.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn verify<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+        group_context: &GroupContext,
+        context: &[u8],
+    ) -> Result<(), MlsError> {
+        if self.cipher_suite != group_context.cipher_suite
+            || self.cipher_suite != cipher_suite_provider.cipher_suite()
+        {
+            return Err(MlsError::CipherSuiteMismatch);
+        }
+
+        if self.group_id != group_context.group_id {
+            return Err(MlsError::GroupIdMismatch);
+        }
+
+        if self.epoch != group_context.epoch {
+            return Err(MlsError::InvalidEpoch);
+        }
+
+        let tree_hash = tree_hash_from_path(
+            self.leaf_index,
+            &self.leaf_node,
+            self.leaf_count,
+            &self.path,
+            cipher_suite_provider,
+        )
+        .await?;
+
+        if tree_hash != group_context.tree_hash {
+            return Err(MlsError::TreeHashMismatch);
+        }
+
+        Signable::verify(
+            self,
+            cipher_suite_provider,
+            &self.leaf_node.signing_identity.signature_key,
+            &context,
+        )
+        .await
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Create a [`MembershipProof`] showing that the local member belongs to
+    /// this group in the current epoch, bound to the application defined
+    /// `context`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn membership_proof(&self, context: &[u8]) -> Result<MembershipProof, MlsError> {
+        let leaf_index = self.private_tree.self_index;
+
+        let path = self
+            .state
+            .public_tree
+            .tree_hash_path(leaf_index, &self.cipher_suite_provider)
+            .await?;
+
+        let mut proof = MembershipProof {
+            cipher_suite: self.cipher_suite(),
+            group_id: self.group_id().to_vec(),
+            epoch: self.current_epoch(),
+            leaf_index,
+            leaf_count: self.state.public_tree.total_leaf_count(),
+            leaf_node: self.current_user_leaf_node()?.clone(),
+            path,
+            signature: Vec::new(),
+        };
+
+        proof
+            .sign(&self.cipher_suite_provider, &self.signer, &context)
+            .await?;
+
+        Ok(proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use super::MembershipProof;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        crypto::test_utils::test_cipher_suite_provider,
+        group::{
+            test_utils::{test_group, test_n_member_group, TestGroup},
+            GroupContext,
+        },
+        tree_kem::node::LeafIndex,
+    };
+
+    // A third party gets the group context of the current epoch from a
+    // group info published by a member.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn published_group_context(group: &TestGroup) -> GroupContext {
+        group
+            .group
+            .group_info_message(false)
+            .await
+            .unwrap()
+            .into_group_info()
+            .unwrap()
+            .group_context
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn third_party_can_verify_membership_proof() {
+        let groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 5).await;
+        let group_context = published_group_context(&groups[0]).await;
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        for group in &groups {
+            let proof = group.group.membership_proof(b"challenge").await.unwrap();
+            let proof = MembershipProof::from_bytes(&proof.to_bytes().unwrap()).unwrap();
+
+            assert_eq!(proof.member_index(), group.group.current_member_index());
+
+            proof
+                .verify(&cs, &group_context, b"challenge")
+                .await
+                .unwrap();
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn membership_proof_is_bound_to_context() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob, _) = alice.join("bob").await;
+        let group_context = published_group_context(&alice).await;
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let proof = bob.group.membership_proof(b"challenge").await.unwrap();
+        let res = proof.verify(&cs, &group_context, b"other").await;
+
+        assert_matches!(res, Err(MlsError::InvalidSignature));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn membership_proof_is_bound_to_epoch() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob, _) = alice.join("bob").await;
+        let proof = bob.group.membership_proof(b"challenge").await.unwrap();
+
+        alice.group.commit(vec![]).await.unwrap();
+        alice.process_pending_commit().await.unwrap();
+
+        let group_context = published_group_context(&alice).await;
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let res = proof.verify(&cs, &group_context, b"challenge").await;
+        assert_matches!(res, Err(MlsError::InvalidEpoch));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn membership_proof_for_other_leaf_fails() {
+        let groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+        let group_context = published_group_context(&groups[0]).await;
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let mut proof = groups[1]
+            .group
+            .membership_proof(b"challenge")
+            .await
+            .unwrap();
+        proof.leaf_index = LeafIndex(3);
+
+        let res = proof.verify(&cs, &group_context, b"challenge").await;
+        assert_matches!(res, Err(MlsError::TreeHashMismatch));
+    }
+}
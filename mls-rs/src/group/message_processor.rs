@@ -9,11 +9,13 @@ use super::{
         ApplicationData, Content, ContentType, MlsMessage, MlsMessagePayload, PublicMessage, Sender,
     },
     message_signature::AuthenticatedContent,
+    metadata::GroupMetadataUpdate,
     mls_rules::{CommitDirection, MlsRules},
     proposal_filter::ProposalBundle,
     state::GroupState,
     transcript_hash::InterimTranscriptHash,
-    transcript_hashes, validate_group_info_member, GroupContext, GroupInfo, Welcome,
+    transcript_hashes, validate_group_info_member, ExportedTree, GroupContext, GroupInfo,
+    InitKeyReusePolicy, MessageCounter, Validated, Welcome,
 };
 use crate::{
     client::MlsError,
@@ -32,19 +34,24 @@ use alloc::boxed::Box;
 use alloc::vec::Vec;
 use core::fmt::{self, Debug};
 use mls_rs_core::{
-    identity::IdentityProvider, protocol_version::ProtocolVersion, psk::PreSharedKeyStorage,
+    error::IntoAnyError, identity::IdentityProvider, protocol_version::ProtocolVersion,
+    psk::PreSharedKeyStorage,
 };
 
 #[cfg(feature = "by_ref_proposal")]
-use super::proposal_ref::ProposalRef;
+use super::{proposal_filter::ProposalSource, proposal_ref::ProposalRef, DuplicateProposalPolicy};
 
 #[cfg(not(feature = "by_ref_proposal"))]
 use crate::group::proposal_cache::resolve_for_commit;
 
-#[cfg(feature = "by_ref_proposal")]
+#[cfg(any(feature = "by_ref_proposal", feature = "state_update"))]
 use super::proposal::Proposal;
 
-#[cfg(feature = "custom_proposal")]
+#[cfg(any(
+    feature = "custom_proposal",
+    feature = "state_update",
+    feature = "by_ref_proposal"
+))]
 use super::proposal_filter::ProposalInfo;
 
 #[cfg(feature = "state_update")]
@@ -60,7 +67,7 @@ use mls_rs_core::psk::ExternalPskId;
 use crate::tree_kem::UpdatePath;
 
 #[cfg(feature = "state_update")]
-use super::{member_from_key_package, member_from_leaf_node};
+use super::{member_from_key_package, member_from_leaf_node, ProposalType};
 
 #[cfg(all(feature = "state_update", feature = "custom_proposal"))]
 use super::proposal::CustomProposal;
@@ -78,6 +85,8 @@ pub(crate) struct ProvisionalState {
     pub(crate) group_context: GroupContext,
     pub(crate) external_init_index: Option<LeafIndex>,
     pub(crate) indexes_of_added_kpkgs: Vec<LeafIndex>,
+    #[cfg(feature = "state_update")]
+    pub(crate) applied_order: Vec<ProposalType>,
     #[cfg(feature = "by_ref_proposal")]
     pub(crate) unused_proposals: Vec<crate::mls_rules::ProposalInfo<Proposal>>,
 }
@@ -113,8 +122,10 @@ pub struct StateUpdate {
     pub(crate) pending_reinit: Option<CipherSuite>,
     pub(crate) active: bool,
     pub(crate) epoch: u64,
+    pub(crate) applied_order: Vec<ProposalType>,
     #[cfg(feature = "custom_proposal")]
     pub(crate) custom_proposals: Vec<ProposalInfo<CustomProposal>>,
+    pub(crate) committed_proposals: Vec<ProposalInfo<Proposal>>,
     #[cfg(feature = "by_ref_proposal")]
     pub(crate) unused_proposals: Vec<crate::mls_rules::ProposalInfo<Proposal>>,
 }
@@ -155,12 +166,24 @@ impl StateUpdate {
         self.epoch
     }
 
+    /// Types of the committed proposals, in the order in which they were
+    /// applied. See [`PROPOSAL_APPLICATION_ORDER`](crate::mls_rules::PROPOSAL_APPLICATION_ORDER).
+    pub fn applied_proposal_order(&self) -> &[ProposalType] {
+        &self.applied_order
+    }
+
     /// Custom proposals that were committed to.
     #[cfg(feature = "custom_proposal")]
     pub fn custom_proposals(&self) -> &[ProposalInfo<CustomProposal>] {
         &self.custom_proposals
     }
 
+    /// All proposals that were committed to, with their sender and whether
+    /// they were sent by value with the commit or by reference.
+    pub fn committed_proposals(&self) -> &[ProposalInfo<Proposal>] {
+        &self.committed_proposals
+    }
+
     /// Proposals that were received in the prior epoch but not committed to.
     #[cfg(feature = "by_ref_proposal")]
     pub fn unused_proposals(&self) -> &[crate::mls_rules::ProposalInfo<Proposal>] {
@@ -179,7 +202,7 @@ impl StateUpdate {
 #[derive(Debug, Clone)]
 #[allow(clippy::large_enum_variant)]
 /// An event generated as a result of processing a message for a group with
-/// [`Group::process_incoming_message`](crate::group::Group::process_incoming_message).
+/// [`Group::process_incoming_message_validated`](crate::group::Group::process_incoming_message_validated).
 pub enum ReceivedMessage {
     /// An application message was decrypted.
     ApplicationMessage(ApplicationMessageDescription),
@@ -246,6 +269,7 @@ pub struct ApplicationMessageDescription {
     data: ApplicationData,
     /// Plaintext authenticated data in the received MLS packet.
     pub authenticated_data: Vec<u8>,
+    counter: MessageCounter,
 }
 
 impl Debug for ApplicationMessageDescription {
@@ -257,6 +281,7 @@ impl Debug for ApplicationMessageDescription {
                 "authenticated_data",
                 &mls_rs_core::debug::pretty_bytes(&self.authenticated_data),
             )
+            .field("counter", &self.counter)
             .finish()
     }
 }
@@ -266,6 +291,12 @@ impl ApplicationMessageDescription {
     pub fn data(&self) -> &[u8] {
         self.data.as_bytes()
     }
+
+    /// Epoch, sender and ratchet generation of the message, e.g. to
+    /// acknowledge it with a [`MessageRange`](crate::group::MessageRange).
+    pub fn counter(&self) -> MessageCounter {
+        self.counter
+    }
 }
 
 #[cfg_attr(
@@ -284,6 +315,14 @@ pub struct CommitMessageDescription {
     pub state_update: StateUpdate,
     /// Plaintext authenticated data in the received MLS packet.
     pub authenticated_data: Vec<u8>,
+    /// True if this commit removed the local member from the group.
+    ///
+    /// When processed by a [`Group`](crate::group::Group), the group state
+    /// has been deleted from storage and the group can no longer be used.
+    pub self_removed: bool,
+    /// Change to the application metadata of the group, if this commit
+    /// modified the [`GroupMetadataExt`](crate::extension::built_in::GroupMetadataExt).
+    pub group_metadata_update: Option<GroupMetadataUpdate>,
 }
 
 impl Debug for CommitMessageDescription {
@@ -296,10 +335,29 @@ impl Debug for CommitMessageDescription {
                 "authenticated_data",
                 &mls_rs_core::debug::pretty_bytes(&self.authenticated_data),
             )
+            .field("self_removed", &self.self_removed)
+            .field("group_metadata_update", &self.group_metadata_update)
             .finish()
     }
 }
 
+impl CommitMessageDescription {
+    /// Decode the [authenticated data](Self::authenticated_data) of the
+    /// commit as `T`, as set by the committer with
+    /// [`CommitBuilder::typed_authenticated_data`](crate::group::CommitBuilder::typed_authenticated_data).
+    ///
+    /// Returns `None` if the commit has no authenticated data.
+    pub fn typed_authenticated_data<T: MlsDecode>(&self) -> Result<Option<T>, MlsError> {
+        if self.authenticated_data.is_empty() {
+            return Ok(None);
+        }
+
+        T::mls_decode(&mut &*self.authenticated_data)
+            .map(Some)
+            .map_err(Into::into)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 /// Proposal sender type.
 pub enum ProposalSender {
@@ -327,6 +385,17 @@ impl TryFrom<Sender> for ProposalSender {
     }
 }
 
+#[cfg(feature = "by_ref_proposal")]
+impl From<ProposalSender> for Sender {
+    fn from(value: ProposalSender) -> Self {
+        match value {
+            ProposalSender::Member(i) => Sender::Member(i),
+            ProposalSender::External(i) => Sender::External(i),
+            ProposalSender::NewMember => Sender::NewMemberProposal,
+        }
+    }
+}
+
 #[cfg(feature = "by_ref_proposal")]
 #[cfg_attr(
     all(feature = "ffi", not(test)),
@@ -344,6 +413,8 @@ pub struct ProposalMessageDescription {
     pub authenticated_data: Vec<u8>,
     /// Proposal reference.
     pub proposal_ref: ProposalRef,
+    /// Epoch in which the proposal was sent.
+    pub epoch: u64,
 }
 
 #[cfg(feature = "by_ref_proposal")]
@@ -357,6 +428,7 @@ impl Debug for ProposalMessageDescription {
                 &mls_rs_core::debug::pretty_bytes(&self.authenticated_data),
             )
             .field("proposal_ref", &self.proposal_ref)
+            .field("epoch", &self.epoch)
             .finish()
     }
 }
@@ -385,22 +457,28 @@ impl CachedProposal {
 #[cfg(feature = "by_ref_proposal")]
 impl ProposalMessageDescription {
     pub fn cached_proposal(self) -> CachedProposal {
-        let sender = match self.sender {
-            ProposalSender::Member(i) => Sender::Member(i),
-            ProposalSender::External(i) => Sender::External(i),
-            ProposalSender::NewMember => Sender::NewMemberProposal,
-        };
-
         CachedProposal {
             proposal: self.proposal,
             proposal_ref: self.proposal_ref,
-            sender,
+            sender: self.sender.into(),
         }
     }
 
     pub fn proposal_ref(&self) -> Vec<u8> {
         self.proposal_ref.to_vec()
     }
+
+    /// Describe the proposal as it would be passed to
+    /// [`MlsRules`](crate::MlsRules) when committed by reference.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn proposal_info(&self) -> ProposalInfo<Proposal> {
+        ProposalInfo {
+            proposal: self.proposal.clone(),
+            sender: self.sender.into(),
+            source: ProposalSource::ByReference(self.proposal_ref.clone()),
+            received_epoch: Some(self.epoch),
+        }
+    }
 }
 
 #[cfg(not(feature = "by_ref_proposal"))]
@@ -419,7 +497,9 @@ pub(crate) enum EventOrContent<E> {
         allow(dead_code)
     )]
     Event(E),
-    Content(AuthenticatedContent),
+    /// Content to process, with the generation of the key that decrypted it
+    /// if it was encrypted.
+    Content(AuthenticatedContent, Option<u32>),
 }
 
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -442,13 +522,24 @@ pub(crate) trait MessageProcessor: Send + Sync {
     type CipherSuiteProvider: CipherSuiteProvider;
     type PreSharedKeyStorage: PreSharedKeyStorage;
 
+    /// Process `message`, decrypting application messages with the
+    /// application ratchet of the given `lane`.
     async fn process_incoming_message(
         &mut self,
-        message: MlsMessage,
+        message: Validated<MlsMessage>,
         #[cfg(feature = "by_ref_proposal")] cache_proposal: bool,
+        #[cfg(feature = "application_lanes")] lane: u16,
     ) -> Result<Self::OutputType, MlsError> {
-        self.process_incoming_message_with_time(
-            message,
+        let event_or_content = self
+            .get_event_from_incoming_message(
+                message,
+                #[cfg(feature = "application_lanes")]
+                lane,
+            )
+            .await?;
+
+        self.process_event_or_content(
+            event_or_content,
             #[cfg(feature = "by_ref_proposal")]
             cache_proposal,
             None,
@@ -462,7 +553,13 @@ pub(crate) trait MessageProcessor: Send + Sync {
         #[cfg(feature = "by_ref_proposal")] cache_proposal: bool,
         time_sent: Option<MlsTime>,
     ) -> Result<Self::OutputType, MlsError> {
-        let event_or_content = self.get_event_from_incoming_message(message).await?;
+        let event_or_content = self
+            .get_event_from_incoming_message(
+                Validated::new(message)?,
+                #[cfg(feature = "application_lanes")]
+                0,
+            )
+            .await?;
 
         self.process_event_or_content(
             event_or_content,
@@ -475,16 +572,26 @@ pub(crate) trait MessageProcessor: Send + Sync {
 
     async fn get_event_from_incoming_message(
         &mut self,
-        message: MlsMessage,
+        message: Validated<MlsMessage>,
+        #[cfg(feature = "application_lanes")] lane: u16,
     ) -> Result<EventOrContent<Self::OutputType>, MlsError> {
         self.check_metadata(&message)?;
 
+        let message = message.into_inner();
+
         match message.payload {
             MlsMessagePayload::Plain(plaintext) => {
                 self.verify_plaintext_authentication(plaintext).await
             }
             #[cfg(feature = "private_message")]
-            MlsMessagePayload::Cipher(cipher_text) => self.process_ciphertext(&cipher_text).await,
+            MlsMessagePayload::Cipher(cipher_text) => {
+                self.process_ciphertext(
+                    &cipher_text,
+                    #[cfg(feature = "application_lanes")]
+                    lane,
+                )
+                .await
+            }
             MlsMessagePayload::GroupInfo(group_info) => {
                 validate_group_info_member(
                     self.group_state(),
@@ -518,9 +625,10 @@ pub(crate) trait MessageProcessor: Send + Sync {
     ) -> Result<Self::OutputType, MlsError> {
         let msg = match event_or_content {
             EventOrContent::Event(event) => event,
-            EventOrContent::Content(content) => {
+            EventOrContent::Content(content, generation) => {
                 self.process_auth_content(
                     content,
+                    generation,
                     #[cfg(feature = "by_ref_proposal")]
                     cache_proposal,
                     time_sent,
@@ -532,32 +640,52 @@ pub(crate) trait MessageProcessor: Send + Sync {
         Ok(msg)
     }
 
+    #[cfg_attr(not(feature = "private_message"), allow(unused_variables))]
     async fn process_auth_content(
         &mut self,
         auth_content: AuthenticatedContent,
+        generation: Option<u32>,
         #[cfg(feature = "by_ref_proposal")] cache_proposal: bool,
         time_sent: Option<MlsTime>,
     ) -> Result<Self::OutputType, MlsError> {
+        let sender = auth_content.content.sender;
+
         let event = match auth_content.content.content {
             #[cfg(feature = "private_message")]
             Content::Application(data) => {
                 let authenticated_data = auth_content.content.authenticated_data;
-                let sender = auth_content.content.sender;
+                let epoch = auth_content.content.epoch;
 
-                self.process_application_message(data, sender, authenticated_data)
-                    .and_then(Self::OutputType::try_from)
+                self.process_application_message(
+                    data,
+                    sender,
+                    epoch,
+                    generation,
+                    authenticated_data,
+                )
+                .and_then(Self::OutputType::try_from)
+            }
+            Content::Commit(_) => {
+                self.check_handshake_allowed()?;
+
+                self.process_commit(auth_content, time_sent)
+                    .await
+                    .map(Self::OutputType::from)
             }
-            Content::Commit(_) => self
-                .process_commit(auth_content, time_sent)
-                .await
-                .map(Self::OutputType::from),
             #[cfg(feature = "by_ref_proposal")]
-            Content::Proposal(ref proposal) => self
-                .process_proposal(&auth_content, proposal, cache_proposal)
-                .await
-                .map(Self::OutputType::from),
+            Content::Proposal(ref proposal) => {
+                self.check_handshake_allowed()?;
+
+                self.process_proposal(&auth_content, proposal, cache_proposal)
+                    .await
+                    .map(Self::OutputType::from)
+            }
         }?;
 
+        if let Sender::Member(index) = sender {
+            self.member_active(LeafIndex(index));
+        }
+
         Ok(event)
     }
 
@@ -566,16 +694,31 @@ pub(crate) trait MessageProcessor: Send + Sync {
         &self,
         data: ApplicationData,
         sender: Sender,
+        epoch: u64,
+        generation: Option<u32>,
         authenticated_data: Vec<u8>,
     ) -> Result<ApplicationMessageDescription, MlsError> {
         let Sender::Member(sender_index) = sender else {
             return Err(MlsError::InvalidSender);
         };
 
+        let generation = generation.ok_or(MlsError::UnencryptedApplicationMessage)?;
+
+        #[cfg(feature = "compression")]
+        let data = match self.compression() {
+            Some(compression) => compression.decompress(data.as_bytes())?.into(),
+            None => data,
+        };
+
         Ok(ApplicationMessageDescription {
             authenticated_data,
             sender_index,
             data,
+            counter: MessageCounter {
+                epoch,
+                sender_index,
+                generation,
+            },
         })
     }
 
@@ -607,6 +750,7 @@ pub(crate) trait MessageProcessor: Send + Sync {
             proposal: proposal.clone(),
             sender: auth_content.content.sender.try_into()?,
             proposal_ref,
+            epoch: auth_content.content.epoch,
         })
     }
 
@@ -690,6 +834,9 @@ pub(crate) trait MessageProcessor: Send + Sync {
 
         let roster_update = RosterUpdate::new(added, removed, updated);
 
+        // Committed and unused proposals were sent in the epoch of the commit.
+        let epoch = self.group_state().context.epoch;
+
         let update = StateUpdate {
             roster_update,
             #[cfg(feature = "psk")]
@@ -701,10 +848,22 @@ pub(crate) trait MessageProcessor: Send + Sync {
                 .map(|ri| ri.proposal.new_cipher_suite()),
             active: true,
             epoch: provisional.group_context.epoch,
+            applied_order: provisional.applied_order.clone(),
             #[cfg(feature = "custom_proposal")]
             custom_proposals: provisional.applied_proposals.custom_proposals.clone(),
+            committed_proposals: provisional
+                .applied_proposals
+                .clone()
+                .into_proposals()
+                .map(|p| p.with_received_epoch(epoch))
+                .collect(),
             #[cfg(feature = "by_ref_proposal")]
-            unused_proposals: provisional.unused_proposals.clone(),
+            unused_proposals: provisional
+                .unused_proposals
+                .iter()
+                .cloned()
+                .map(|p| p.with_received_epoch(epoch))
+                .collect(),
         };
 
         Ok(update)
@@ -719,6 +878,9 @@ pub(crate) trait MessageProcessor: Send + Sync {
             return Err(MlsError::GroupUsedAfterReInit);
         }
 
+        #[cfg(feature = "event_sourcing")]
+        let epoch_delta = self.epoch_delta(&auth_content);
+
         // Update the new GroupContext's confirmed and interim transcript hashes using the new Commit.
         let (interim_transcript_hash, confirmed_transcript_hash) = transcript_hashes(
             self.cipher_suite_provider(),
@@ -758,9 +920,23 @@ pub(crate) trait MessageProcessor: Send + Sync {
                 &self.mls_rules(),
                 time_sent,
                 CommitDirection::Receive,
+                self.init_key_reuse_policy(),
+                #[cfg(feature = "by_ref_proposal")]
+                self.duplicate_proposal_policy(),
             )
             .await?;
 
+        if let Some(reinit) = provisional_state
+            .applied_proposals
+            .reinitializations
+            .first()
+        {
+            self.check_reinit(
+                reinit.proposal.new_version(),
+                reinit.proposal.new_cipher_suite(),
+            )?;
+        }
+
         let sender = commit_sender(&auth_content.content.sender, &provisional_state)?;
 
         #[cfg(feature = "state_update")]
@@ -773,10 +949,30 @@ pub(crate) trait MessageProcessor: Send + Sync {
 
         //Verify that the path value is populated if the proposals vector contains any Update
         // or Remove proposals, or if it's empty. Otherwise, the path value MAY be omitted.
-        if path_update_required(&provisional_state.applied_proposals) && commit.path.is_none() {
+        let path_required = path_update_required(&provisional_state.applied_proposals);
+
+        if path_required && commit.path.is_none() {
             return Err(MlsError::CommitMissingPath);
         }
 
+        self.mls_rules()
+            .commit_options(
+                &provisional_state.public_tree.roster(),
+                &provisional_state.group_context.extensions,
+                &provisional_state.applied_proposals,
+            )
+            .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))?
+            .check_received_path(
+                commit.path.is_some(),
+                path_required,
+                matches!(auth_content.content.sender, Sender::NewMemberCommit),
+            )?;
+
+        let group_metadata_update = GroupMetadataUpdate::from_extensions(
+            &self.group_state().context.extensions,
+            &provisional_state.group_context.extensions,
+        )?;
+
         if !self.can_continue_processing(&provisional_state) {
             #[cfg(feature = "state_update")]
             {
@@ -788,6 +984,8 @@ pub(crate) trait MessageProcessor: Send + Sync {
                 authenticated_data: auth_content.content.authenticated_data,
                 committer: *sender,
                 state_update,
+                self_removed: true,
+                group_metadata_update,
             });
         }
 
@@ -829,6 +1027,18 @@ pub(crate) trait MessageProcessor: Send + Sync {
             .tree_hash(self.cipher_suite_provider())
             .await?;
 
+        if let Some(validator) = self.tree_validator() {
+            let tree = ExportedTree::new_borrowed(&provisional_state.public_tree.nodes);
+
+            validator
+                .0
+                .validate_tree(&provisional_state.group_context, &tree)
+                .await
+                .map_err(MlsError::TreeRejected)?;
+        }
+
+        self.check_critical_extensions(&provisional_state)?;
+
         if let Some(reinit) = provisional_state.applied_proposals.reinitializations.pop() {
             self.group_state_mut().pending_reinit = Some(reinit.proposal);
 
@@ -848,11 +1058,20 @@ pub(crate) trait MessageProcessor: Send + Sync {
             )
             .await?;
 
+            #[cfg(feature = "event_sourcing")]
+            if let Some(epoch_delta) = epoch_delta {
+                self.epoch_delta_applied(epoch_delta);
+            }
+
+            self.commit_applied(sender);
+
             Ok(CommitMessageDescription {
                 is_external: matches!(auth_content.content.sender, Sender::NewMemberCommit),
                 authenticated_data: auth_content.content.authenticated_data,
                 committer: *sender,
                 state_update,
+                self_removed: false,
+                group_metadata_update,
             })
         } else {
             Err(MlsError::InvalidConfirmationTag)
@@ -870,6 +1089,77 @@ pub(crate) trait MessageProcessor: Send + Sync {
     #[cfg(feature = "private_message")]
     fn min_epoch_available(&self) -> Option<u64>;
 
+    /// Determines if proposals and commits sent by members must be encrypted.
+    #[cfg(feature = "private_message")]
+    fn handshake_encryption_required(&self) -> bool {
+        false
+    }
+
+    /// Compression applied to received application messages.
+    #[cfg(feature = "compression")]
+    fn compression(&self) -> Option<super::CompressionSettings> {
+        None
+    }
+
+    /// External validator of the tree of each new epoch.
+    fn tree_validator(&self) -> Option<super::SharedTreeValidator> {
+        None
+    }
+
+    /// Called once the commit of `committer` was applied and the group moved
+    /// to the new epoch.
+    fn commit_applied(&mut self, _committer: LeafIndex) {}
+
+    /// Called once a message sent by `member` was processed.
+    fn member_active(&mut self, _member: LeafIndex) {}
+
+    /// Delta to record for the epoch created by the commit `auth_content`,
+    /// captured before the commit is applied.
+    #[cfg(feature = "event_sourcing")]
+    fn epoch_delta(&self, _auth_content: &AuthenticatedContent) -> Option<super::EpochDelta> {
+        None
+    }
+
+    /// Called once the commit of `epoch_delta` was applied.
+    #[cfg(feature = "event_sourcing")]
+    fn epoch_delta_applied(&mut self, _epoch_delta: super::EpochDelta) {}
+
+    /// Check that the group state after a received commit contains no
+    /// unknown [critical](mls_rs_core::extension::ExtensionType::is_critical)
+    /// extensions.
+    fn check_critical_extensions(
+        &self,
+        _provisional_state: &ProvisionalState,
+    ) -> Result<(), MlsError> {
+        Ok(())
+    }
+
+    /// Determines if received commits and proposals may be processed.
+    fn check_handshake_allowed(&self) -> Result<(), MlsError> {
+        Ok(())
+    }
+
+    /// Policy on duplicate proposals from the same sender in received commits.
+    #[cfg(feature = "by_ref_proposal")]
+    fn duplicate_proposal_policy(&self) -> Option<DuplicateProposalPolicy> {
+        None
+    }
+
+    /// Policy on reused init keys of key packages added by received commits.
+    fn init_key_reuse_policy(&self) -> InitKeyReusePolicy {
+        InitKeyReusePolicy::default()
+    }
+
+    /// Determines if the group may be re-initialized with `new_version` and
+    /// `new_cipher_suite`.
+    fn check_reinit(
+        &self,
+        _new_version: ProtocolVersion,
+        _new_cipher_suite: CipherSuite,
+    ) -> Result<(), MlsError> {
+        Ok(())
+    }
+
     fn check_metadata(&self, message: &MlsMessage) -> Result<(), MlsError> {
         let context = &self.group_state().context;
 
@@ -944,6 +1234,15 @@ pub(crate) trait MessageProcessor: Send + Sync {
             }
         }
 
+        #[cfg(feature = "private_message")]
+        if let MlsMessagePayload::Plain(plaintext) = &message.payload {
+            if matches!(plaintext.content.sender, Sender::Member(_))
+                && self.handshake_encryption_required()
+            {
+                return Err(MlsError::UnencryptedHandshakeMessage);
+            }
+        }
+
         Ok(())
     }
 
@@ -971,10 +1270,13 @@ pub(crate) trait MessageProcessor: Send + Sync {
         validate_key_package(key_package, version, cs, &id).await
     }
 
+    /// Decrypt `cipher_text`, using the application ratchet of `lane` if it
+    /// is an application message.
     #[cfg(feature = "private_message")]
     async fn process_ciphertext(
         &mut self,
         cipher_text: &PrivateMessage,
+        #[cfg(feature = "application_lanes")] lane: u16,
     ) -> Result<EventOrContent<Self::OutputType>, MlsError>;
 
     async fn verify_plaintext_authentication(
@@ -0,0 +1,187 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// Position of an application message in the ratchet of its sender,
+/// available on
+/// [`ApplicationMessageDescription::counter`](crate::group::ApplicationMessageDescription::counter).
+///
+/// Each member encrypts its application messages with consecutive
+/// generations of its own ratchet of the secret tree, starting from 0 in
+/// each epoch. The generation of a message therefore numbers it among the
+/// messages of the same sender in the same epoch, which allows applications
+/// to acknowledge ranges of received messages and detect lost ones over
+/// lossy transports, see [`MessageRange`].
+#[cfg_attr(
+    all(feature = "ffi", not(test)),
+    safer_ffi_gen::ffi_type(clone, opaque)
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub struct MessageCounter {
+    /// Epoch in which the message was sent.
+    pub epoch: u64,
+    /// Leaf index of the sender.
+    pub sender_index: u32,
+    /// Generation of the key that encrypted the message.
+    pub generation: u32,
+}
+
+/// Generations of the messages received from one sender in one epoch.
+///
+/// Applications keep a range per sender and epoch, i.e. per
+/// [`MessageCounter::epoch`] and [`MessageCounter::sender_index`], and
+/// [insert](MessageRange::insert) the generation of each message received.
+/// The [received](MessageRange::received) ranges can be acknowledged to the
+/// sender, and the [missing](MessageRange::missing) ones requested again.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MessageRange {
+    received: Vec<Range<u32>>,
+}
+
+impl MessageRange {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the message with `generation` as received. Returns `false` if
+    /// it was already received.
+    pub fn insert(&mut self, generation: u32) -> bool {
+        let i = self.received.partition_point(|r| r.end < generation);
+
+        match self.received.get_mut(i) {
+            Some(r) if r.contains(&generation) => return false,
+            Some(r) if r.end == generation => r.end += 1,
+            Some(r) if r.start == generation + 1 => r.start = generation,
+            _ => self.received.insert(i, generation..generation + 1),
+        }
+
+        // Merge with the next range if the gap between them was filled.
+        if let Some(next) = self.received.get(i + 1).cloned() {
+            if self.received[i].end == next.start {
+                self.received[i].end = next.end;
+                self.received.remove(i + 1);
+            }
+        }
+
+        true
+    }
+
+    /// Determines if the message with `generation` was received.
+    pub fn contains(&self, generation: u32) -> bool {
+        self.received.iter().any(|r| r.contains(&generation))
+    }
+
+    /// Ranges of received generations, in increasing order and not
+    /// overlapping.
+    pub fn received(&self) -> &[Range<u32>] {
+        &self.received
+    }
+
+    /// Ranges of generations that were not received, up to the highest one
+    /// received.
+    pub fn missing(&self) -> Vec<Range<u32>> {
+        let mut start = 0;
+        let mut missing = Vec::new();
+
+        for r in &self.received {
+            if start < r.start {
+                missing.push(start..r.start);
+            }
+
+            start = r.end;
+        }
+
+        missing
+    }
+
+    /// Generations that were not received, up to the highest one received,
+    /// including at most `limit` of them.
+    pub fn missing_generations(&self, limit: usize) -> Vec<u32> {
+        self.missing().into_iter().flatten().take(limit).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::MessageRange;
+
+    #[cfg(feature = "private_message")]
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::{test_utils::test_group, ReceivedMessage, Validated},
+    };
+
+    #[test]
+    fn gaps_are_reported_until_filled() {
+        let mut range = MessageRange::new();
+
+        for generation in [0, 1, 4, 7, 5] {
+            assert!(range.insert(generation));
+        }
+
+        assert!(!range.insert(4));
+        assert!(range.contains(5));
+        assert!(!range.contains(3));
+
+        assert_eq!(range.received(), &[0..2, 4..6, 7..8]);
+        assert_eq!(range.missing(), vec![2..4, 6..7]);
+        assert_eq!(range.missing_generations(2), vec![2, 3]);
+
+        for generation in [2, 3, 6] {
+            assert!(range.insert(generation));
+        }
+
+        assert_eq!(range.received().len(), 1);
+        assert_eq!(range.received()[0], 0..8);
+        assert!(range.missing().is_empty());
+    }
+
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn lost_messages_are_detected_from_counters() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let mut messages = vec![];
+
+        for data in [b"a", b"b", b"c"] {
+            let message = alice
+                .group
+                .encrypt_application_message(data, vec![])
+                .await
+                .unwrap();
+
+            messages.push(message);
+        }
+
+        // The second message is lost.
+        messages.remove(1);
+
+        let mut range = MessageRange::new();
+
+        for message in messages {
+            let ReceivedMessage::ApplicationMessage(description) = bob
+                .group
+                .process_incoming_message_validated(Validated::new(message).unwrap())
+                .await
+                .unwrap()
+            else {
+                panic!("expected application message");
+            };
+
+            let counter = description.counter();
+            assert_eq!(counter.epoch, alice.group.current_epoch());
+            assert_eq!(counter.sender_index, 0);
+
+            range.insert(counter.generation);
+        }
+
+        assert_eq!(range.missing(), vec![1..2]);
+    }
+}
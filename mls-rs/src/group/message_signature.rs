@@ -7,7 +7,7 @@ use crate::client::MlsError;
 use crate::crypto::SignatureSecretKey;
 use crate::group::framing::{ContentType, FramedContent, PublicMessage, Sender, WireFormat};
 use crate::group::{ConfirmationTag, GroupContext};
-use crate::signer::Signable;
+use crate::signer::{Signable, SignatureLabel};
 use crate::CipherSuiteProvider;
 use alloc::vec;
 use alloc::vec::Vec;
@@ -211,7 +211,7 @@ pub(crate) struct MessageSigningContext<'a> {
 }
 
 impl<'a> Signable<'a> for AuthenticatedContent {
-    const SIGN_LABEL: &'static str = "FramedContentTBS";
+    const SIGN_LABEL: &'static str = SignatureLabel::FramedContent.as_str();
 
     type SigningContext = MessageSigningContext<'a>;
 
@@ -0,0 +1,729 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use mls_rs_codec::{MlsSize, VarInt};
+use mls_rs_core::{
+    crypto::{CipherSuite, CipherSuiteProvider, HpkeCiphertext},
+    error::IntoAnyError,
+};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{
+        commit::Commit,
+        framing::{ContentType, MlsMessagePayload},
+        message_processor::path_update_required,
+        mls_rules::{CommitDirection, CommitOptions, MlsRules},
+        proposal::{AddProposal, Proposal},
+        proposal_filter::{ProposalBundle, ProposalSource},
+        ExportedTree, Group, Sender,
+    },
+    key_package::KeyPackage,
+    tree_kem::{
+        leaf_node::LeafNodeSource,
+        node::{LeafIndex, NodeIndex, NodeVec},
+        parent_hash::ParentHash,
+        update_path::{UpdatePath, UpdatePathNode},
+    },
+    MlsMessage,
+};
+
+#[cfg(feature = "private_message")]
+use crate::group::{
+    padding::{LengthHistogram, PaddingMode},
+    GroupContext,
+};
+
+#[cfg(not(feature = "by_ref_proposal"))]
+use crate::group::proposal_cache::prepare_commit;
+
+/// Authentication tag size of all AEAD algorithms used by the cipher suites
+/// defined in RFC 9420.
+const AEAD_TAG_SIZE: usize = 16;
+
+/// Protocol version and wire format fields of an [`MlsMessage`].
+const MLS_MESSAGE_HEADER_SIZE: usize = 4;
+
+/// Encoded size of a `Sender::Member`.
+const MEMBER_SENDER_SIZE: usize = 5;
+
+/// Encoded size of the sender data of a private message: leaf index,
+/// generation and reuse guard.
+const SENDER_DATA_SIZE: usize = 12;
+
+/// Size of the GREASE extension added to group infos: extension type and
+/// empty data.
+const GREASE_EXTENSION_SIZE: usize = 3;
+
+/// Encoded size of an optional, present node and its node type.
+const NODE_HEADER_SIZE: usize = 2;
+
+/// Upper bound on the size of a signature produced with `cipher_suite`.
+///
+/// Cipher suites that are not defined in RFC 9420 are assumed to produce
+/// signatures no larger than the largest signature of a defined cipher suite.
+fn max_signature_size(cipher_suite: CipherSuite) -> usize {
+    match cipher_suite {
+        CipherSuite::CURVE25519_AES128 | CipherSuite::CURVE25519_CHACHA => 64,
+        CipherSuite::CURVE448_AES256 | CipherSuite::CURVE448_CHACHA => 114,
+        // ECDSA signatures are DER encoded
+        CipherSuite::P256_AES128 => 72,
+        CipherSuite::P384_AES256 => 104,
+        _ => 139,
+    }
+}
+
+/// Encoded size of a variable length vector holding `len` bytes.
+fn vec_size(len: usize) -> usize {
+    VarInt(len as u32).mls_encoded_len() + len
+}
+
+/// Upper bound on the size of an application message with a plaintext of
+/// `plaintext_len` bytes and empty authenticated data, sent in the epoch
+/// described by `context`, with the sizes of past messages in `history`.
+#[cfg(feature = "private_message")]
+pub(crate) fn ciphertext_size(
+    context: &GroupContext,
+    plaintext_len: usize,
+    padding_mode: PaddingMode,
+    history: Option<&LengthHistogram>,
+) -> usize {
+    let content_size = vec_size(plaintext_len) + vec_size(max_signature_size(context.cipher_suite));
+
+    private_message_size(
+        context,
+        padding_mode.padded_size_with_history(content_size, history),
+    )
+}
+
+#[cfg(feature = "private_message")]
+fn private_message_size(context: &GroupContext, padded_content_size: usize) -> usize {
+    MLS_MESSAGE_HEADER_SIZE
+        + vec_size(context.group_id.len())
+        + context.epoch.mls_encoded_len()
+        + ContentType::Application.mls_encoded_len()
+        + vec_size(0)
+        + vec_size(SENDER_DATA_SIZE + AEAD_TAG_SIZE)
+        + vec_size(padded_content_size + AEAD_TAG_SIZE)
+}
+
+/// Sizes of the parts of a welcome message for a batch of new members, used
+/// by [`Group::plan_add_commits`].
+#[derive(Clone)]
+struct WelcomeEstimate<'a> {
+    options: &'a CommitOptions,
+    hash_size: usize,
+    signature_size: usize,
+    context_size: usize,
+    include_path: bool,
+    /// Size of the ratchet tree before the batch is added.
+    tree_size: usize,
+    /// Number of leaves of the ratchet tree before the batch is added.
+    leaf_count: usize,
+    /// Sum, or maximum if each new member gets its own welcome message, of
+    /// the encrypted group secrets of the batch.
+    secrets_size: usize,
+    /// Size of the leaves added to the ratchet tree by the batch.
+    leaves_size: usize,
+    members: usize,
+}
+
+impl WelcomeEstimate<'_> {
+    fn group_secrets_size(&self, key_package: &KeyPackage) -> usize {
+        let path_secret_size = if self.include_path {
+            vec_size(self.hash_size)
+        } else {
+            0
+        };
+
+        // Joiner secret, optional path secret and empty list of PSKs
+        let group_secrets_size = vec_size(self.hash_size) + 1 + path_secret_size + vec_size(0);
+
+        vec_size(self.hash_size)
+            + vec_size(key_package.hpke_init_key.len())
+            + vec_size(group_secrets_size + AEAD_TAG_SIZE)
+    }
+
+    fn add(&mut self, key_package: &KeyPackage) {
+        let secrets_size = self.group_secrets_size(key_package);
+
+        self.secrets_size = match self.options.single_welcome_message {
+            true => self.secrets_size + secrets_size,
+            false => self.secrets_size.max(secrets_size),
+        };
+
+        self.leaves_size += NODE_HEADER_SIZE + key_package.leaf_node.mls_encoded_len() + 1;
+        self.members += 1;
+    }
+
+    /// Upper bound on the size of the ratchet tree after adding the batch.
+    fn new_tree_size(&self) -> usize {
+        let leaf_count = (self.leaf_count + self.members).next_power_of_two();
+        let depth = leaf_count.trailing_zeros() as usize;
+
+        // Each new member may be an unmerged leaf of all its ancestors.
+        let unmerged_size = self.members * depth * 4;
+
+        // The path of the committer may replace each node of its direct path
+        // and adds a parent hash to its leaf.
+        let path_size = match self.include_path {
+            true => {
+                depth * (NODE_HEADER_SIZE + 2 * vec_size(self.hash_size) + vec_size(0) + 8)
+                    + vec_size(self.hash_size)
+                    + 8
+            }
+            false => 0,
+        };
+
+        self.tree_size + self.leaves_size + unmerged_size + path_size
+    }
+
+    fn welcome_size(&self) -> usize {
+        // The length prefix of the tree grows by up to 3 bytes.
+        let tree_extension_size = match self.options.ratchet_tree_extension {
+            true => 2 + vec_size(self.new_tree_size() + 3),
+            false => 0,
+        };
+
+        let group_info_size = self.context_size
+            + vec_size(tree_extension_size + GREASE_EXTENSION_SIZE)
+            + vec_size(self.hash_size)
+            + 4
+            + vec_size(self.signature_size);
+
+        MLS_MESSAGE_HEADER_SIZE
+            + 2
+            + vec_size(self.secrets_size)
+            + vec_size(group_info_size + AEAD_TAG_SIZE)
+    }
+
+    /// Start a new batch, to be added in the epoch following the current
+    /// batch.
+    fn next_batch(&mut self) {
+        self.tree_size += self.leaves_size + self.members * 4;
+        self.leaf_count += self.members;
+        self.secrets_size = 0;
+        self.leaves_size = 0;
+        self.members = 0;
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Split adding the members with `key_packages` across several commits
+    /// so that the welcome message of each commit fits within the
+    /// `max_welcome_bytes` option of the
+    /// [`CommitOptions`](crate::mls_rules::CommitOptions) of the group.
+    ///
+    /// Returns the key packages to add with each commit, in order. The
+    /// commits must be created sequentially, each after the previous one was
+    /// applied, e.g. with [`Group::commit_builder`] and
+    /// [`CommitBuilder::add_member`](crate::group::CommitBuilder::add_member).
+    /// All key packages are added with a single commit if no maximum is set.
+    ///
+    /// The split relies on an upper bound of the size of welcome messages,
+    /// assuming no PSKs and no group info extensions other than the ratchet
+    /// tree. Fails with [`MlsError::WelcomeTooLarge`] if the welcome message
+    /// adding a single member may exceed the maximum.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn plan_add_commits(
+        &self,
+        key_packages: Vec<MlsMessage>,
+    ) -> Result<Vec<Vec<MlsMessage>>, MlsError> {
+        let sender = Sender::Member(*self.private_tree.self_index);
+        let mut proposals = ProposalBundle::default();
+
+        for message in &key_packages {
+            let MlsMessagePayload::KeyPackage(key_package) = &message.payload else {
+                return Err(MlsError::UnexpectedMessageType);
+            };
+
+            let proposal = Proposal::Add(alloc::boxed::Box::new(AddProposal {
+                key_package: key_package.clone(),
+            }));
+
+            proposals.add(proposal, sender, ProposalSource::ByValue);
+        }
+
+        let options = self
+            .config
+            .mls_rules()
+            .commit_options(&self.roster(), &self.context().extensions, &proposals)
+            .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))?;
+
+        let Some(max_welcome_bytes) = options.max_welcome_bytes else {
+            return Ok(match key_packages.is_empty() {
+                true => Vec::new(),
+                false => vec![key_packages],
+            });
+        };
+
+        let mut estimate = WelcomeEstimate {
+            options: &options,
+            hash_size: self.cipher_suite_provider.kdf_extract_size(),
+            signature_size: max_signature_size(self.cipher_suite()),
+            context_size: self.context().mls_encoded_len(),
+            include_path: options.include_path(path_update_required(&proposals), false)?,
+            tree_size: ExportedTree::new_borrowed(&self.current_epoch_tree().nodes)
+                .mls_encoded_len(),
+            leaf_count: self.current_epoch_tree().occupied_leaf_count() as usize,
+            secrets_size: 0,
+            leaves_size: 0,
+            members: 0,
+        };
+
+        let mut plan = Vec::new();
+        let mut batch = Vec::new();
+
+        for (message, info) in key_packages.into_iter().zip(proposals.add_proposals()) {
+            let key_package = &info.proposal.key_package;
+
+            let mut extended = estimate.clone();
+            extended.add(key_package);
+
+            if extended.welcome_size() > max_welcome_bytes && !batch.is_empty() {
+                plan.push(core::mem::take(&mut batch));
+                estimate.next_batch();
+
+                extended = estimate.clone();
+                extended.add(key_package);
+            }
+
+            let welcome_size = extended.welcome_size();
+
+            if welcome_size > max_welcome_bytes {
+                return Err(MlsError::WelcomeTooLarge(welcome_size, max_welcome_bytes));
+            }
+
+            estimate = extended;
+            batch.push(message);
+        }
+
+        if !batch.is_empty() {
+            plan.push(batch);
+        }
+
+        Ok(plan)
+    }
+
+    /// Estimate the size of the message produced by
+    /// [`Group::encrypt_application_message`] for a plaintext of
+    /// `plaintext_len` bytes and empty authenticated data.
+    ///
+    /// The estimate accounts for framing, the signature, AEAD tags and
+    /// padding according to `padding_mode`. It is an upper bound, since
+    /// the size of some signatures varies slightly from message to message.
+    #[cfg(feature = "private_message")]
+    pub fn estimate_ciphertext_size(
+        &self,
+        plaintext_len: usize,
+        padding_mode: PaddingMode,
+    ) -> usize {
+        ciphertext_size(
+            self.context(),
+            plaintext_len,
+            padding_mode,
+            Some(&self.length_histogram),
+        )
+    }
+
+    /// Estimate the size of the commit message produced by committing
+    /// `proposals` by value, along with all proposals currently cached by
+    /// reference, using [`Group::commit_builder`].
+    ///
+    /// The estimate accounts for framing, signatures, tags and padding, as
+    /// well as the update path if one is required by the proposals or the
+    /// [`CommitOptions`](crate::mls_rules::CommitOptions) of the group. It
+    /// assumes empty authenticated data and does not include the welcome
+    /// message or group info produced alongside the commit.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn estimate_commit_size(&self, proposals: &[Proposal]) -> Result<usize, MlsError> {
+        let sender = Sender::Member(*self.private_tree.self_index);
+        let mls_rules = self.config.mls_rules();
+
+        #[cfg(feature = "by_ref_proposal")]
+        let proposals = self
+            .state
+            .proposals
+            .prepare_commit(sender, proposals.to_vec());
+
+        #[cfg(not(feature = "by_ref_proposal"))]
+        let proposals = prepare_commit(sender, proposals.to_vec());
+
+        #[cfg(feature = "std")]
+        let time = Some(crate::time::MlsTime::now());
+
+        #[cfg(not(feature = "std"))]
+        let time = None;
+
+        let provisional_state = self
+            .state
+            .apply_resolved(
+                sender,
+                proposals,
+                None,
+                &self.config.identity_provider(),
+                &self.cipher_suite_provider,
+                &self.config.secret_store(),
+                &mls_rules,
+                time,
+                CommitDirection::Send,
+                self.config.init_key_reuse_policy(),
+                #[cfg(feature = "by_ref_proposal")]
+                self.config.duplicate_proposal_policy(),
+            )
+            .await?;
+
+        let commit_options = mls_rules
+            .commit_options(
+                &provisional_state.public_tree.roster(),
+                &provisional_state.group_context.extensions,
+                &provisional_state.applied_proposals,
+            )
+            .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))?;
+
+        let path = commit_options
+            .include_path(
+                path_update_required(&provisional_state.applied_proposals),
+                false,
+            )?
+            .then(|| {
+                self.estimated_update_path(
+                    &provisional_state.public_tree.nodes,
+                    &provisional_state.indexes_of_added_kpkgs,
+                )
+            })
+            .transpose()?;
+
+        let commit = Commit {
+            proposals: provisional_state.applied_proposals.into_proposals_or_refs(),
+            path,
+        };
+
+        let hash_size = self.cipher_suite_provider.kdf_extract_size();
+
+        // Signature and confirmation tag
+        let auth_size = vec_size(max_signature_size(self.cipher_suite())) + vec_size(hash_size);
+
+        #[cfg(feature = "private_message")]
+        {
+            let encryption_options = self.encryption_options()?;
+
+            if encryption_options.encrypt_control_messages {
+                let content_size = commit.mls_encoded_len() + auth_size;
+                let padded_size = encryption_options.padding_mode.padded_size(content_size);
+
+                return Ok(private_message_size(self.context(), padded_size));
+            }
+        }
+
+        let framed_content_size = vec_size(self.group_id().len())
+            + self.current_epoch().mls_encoded_len()
+            + MEMBER_SENDER_SIZE
+            + vec_size(0)
+            + ContentType::Commit.mls_encoded_len()
+            + commit.mls_encoded_len();
+
+        // Framed content, authentication data and membership tag
+        Ok(MLS_MESSAGE_HEADER_SIZE + framed_content_size + auth_size + vec_size(hash_size))
+    }
+
+    /// Build an update path with the same encoded size as the update path
+    /// this member would send for the provisional tree `nodes`.
+    fn estimated_update_path(
+        &self,
+        nodes: &NodeVec,
+        added_leaves: &[LeafIndex],
+    ) -> Result<UpdatePath, MlsError> {
+        let self_index = self.private_tree.self_index;
+        let hash_size = self.cipher_suite_provider.kdf_extract_size();
+
+        let mut leaf_node = self.current_user_leaf_node()?.clone();
+
+        leaf_node.leaf_node_source = LeafNodeSource::Commit(ParentHash::from(vec![0; hash_size]));
+        leaf_node.signature = vec![0; max_signature_size(self.cipher_suite())];
+
+        let public_key_size = leaf_node.public_key.len();
+
+        let ciphertext = HpkeCiphertext {
+            kem_output: vec![0; public_key_size],
+            ciphertext: vec![0; hash_size + AEAD_TAG_SIZE],
+        };
+
+        let copath = nodes.direct_copath(self_index);
+        let filtered = nodes.filtered(self_index)?;
+
+        let nodes = copath
+            .into_iter()
+            .zip(filtered)
+            .filter(|(_, filtered)| !filtered)
+            .map(|(node, _)| {
+                let recipients = nodes
+                    .get_resolution_index(node.copath)?
+                    .into_iter()
+                    .filter(|index| {
+                        !added_leaves
+                            .iter()
+                            .any(|leaf| NodeIndex::from(*leaf) == *index)
+                    })
+                    .count();
+
+                Ok(UpdatePathNode {
+                    public_key: vec![0; public_key_size].into(),
+                    encrypted_path_secret: vec![ciphertext.clone(); recipients],
+                })
+            })
+            .collect::<Result<Vec<_>, MlsError>>()?;
+
+        Ok(UpdatePath { leaf_node, nodes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use mls_rs_codec::MlsSize;
+
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        crypto::test_utils::TestCryptoProvider,
+        group::{
+            proposal::Proposal,
+            test_utils::{test_group, test_group_custom_config, TestGroup},
+        },
+        key_package::test_utils::{test_key_package, test_key_package_message},
+        mls_rules::{CommitOptions, DefaultMlsRules},
+        MlsMessage,
+    };
+
+    #[cfg(feature = "private_message")]
+    use crate::{client_builder::PaddingMode, mls_rules::EncryptionOptions};
+
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn ciphertext_size_estimate_bounds_actual_size() {
+        for cipher_suite in TestCryptoProvider::all_supported_cipher_suites() {
+            let mut group = test_group(TEST_PROTOCOL_VERSION, cipher_suite).await;
+
+            for len in [0, 1, 100, 1000, 20000] {
+                let estimate = group
+                    .group
+                    .estimate_ciphertext_size(len, PaddingMode::StepFunction);
+
+                let actual = group
+                    .group
+                    .encrypt_application_message(&vec![0u8; len], vec![])
+                    .await
+                    .unwrap()
+                    .mls_encoded_len();
+
+                assert!(estimate >= actual, "{estimate} < {actual}");
+                assert!(estimate - actual <= 8, "{estimate} - {actual} > 8");
+            }
+        }
+    }
+
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn ciphertext_size_estimate_is_exact_for_eddsa() {
+        let cipher_suite = crate::CipherSuite::CURVE25519_AES128;
+
+        if !TestCryptoProvider::all_supported_cipher_suites().contains(&cipher_suite) {
+            return;
+        }
+
+        let mut group = test_group_custom_config(TEST_PROTOCOL_VERSION, cipher_suite, |b| {
+            b.mls_rules(
+                DefaultMlsRules::new()
+                    .with_encryption_options(EncryptionOptions::new(false, PaddingMode::None)),
+            )
+        })
+        .await;
+
+        let estimate = group.group.estimate_ciphertext_size(500, PaddingMode::None);
+
+        let actual = group
+            .group
+            .encrypt_application_message(&[0u8; 500], vec![])
+            .await
+            .unwrap()
+            .mls_encoded_len();
+
+        assert_eq!(estimate, actual);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_size_estimate_bounds_actual_size() {
+        let mut group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        for name in ["bob", "carol", "dave"] {
+            group.join(name).await;
+        }
+
+        let key_package = test_key_package(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "erin").await;
+
+        let test_cases: Vec<Vec<Proposal>> = vec![
+            vec![],
+            vec![Proposal::Add(alloc::boxed::Box::new(key_package.into()))],
+            vec![group.group.remove_proposal(1).unwrap()],
+        ];
+
+        for proposals in test_cases {
+            let estimate = group.group.estimate_commit_size(&proposals).await.unwrap();
+
+            let mut builder = group.group.commit_builder();
+
+            for proposal in proposals {
+                builder = builder.raw_proposal(proposal);
+            }
+
+            let actual = builder
+                .build()
+                .await
+                .unwrap()
+                .commit_message
+                .mls_encoded_len();
+
+            group.group.clear_pending_commit();
+
+            assert!(estimate >= actual, "{estimate} < {actual}");
+            assert!(estimate - actual <= 16, "{estimate} - {actual} > 16");
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_size_estimate_accounts_for_options() {
+        let commit_options = CommitOptions::new().with_path_required(true);
+
+        #[cfg(feature = "private_message")]
+        let encryption_options = EncryptionOptions::new(true, PaddingMode::StepFunction);
+
+        let mut group = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            let mls_rules = DefaultMlsRules::new().with_commit_options(commit_options);
+
+            #[cfg(feature = "private_message")]
+            let mls_rules = mls_rules.with_encryption_options(encryption_options);
+
+            b.mls_rules(mls_rules)
+        })
+        .await;
+
+        group.join("bob").await;
+
+        let key_package = test_key_package(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "carol").await;
+        let proposal = Proposal::Add(alloc::boxed::Box::new(key_package.into()));
+
+        let estimate = group
+            .group
+            .estimate_commit_size(core::slice::from_ref(&proposal))
+            .await
+            .unwrap();
+
+        let actual = group
+            .group
+            .commit_builder()
+            .raw_proposal(proposal)
+            .build()
+            .await
+            .unwrap()
+            .commit_message
+            .mls_encoded_len();
+
+        assert!(estimate >= actual, "{estimate} < {actual}");
+        assert!(estimate - actual <= 16, "{estimate} - {actual} > 16");
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn test_key_packages() -> Vec<MlsMessage> {
+        let mut key_packages = Vec::new();
+
+        for name in ["bob", "carol", "dave", "erin", "frank", "grace"] {
+            key_packages.push(
+                test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, name).await,
+            );
+        }
+
+        key_packages
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn test_group_with_max_welcome(max_welcome_bytes: usize) -> TestGroup {
+        let options = CommitOptions::new().with_max_welcome_bytes(Some(max_welcome_bytes));
+
+        test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.mls_rules(DefaultMlsRules::new().with_commit_options(options))
+        })
+        .await
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn add_plan_keeps_welcomes_within_limit() {
+        let key_packages = test_key_packages().await;
+
+        let mut probe = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let mut builder = probe.group.commit_builder();
+
+        for key_package in key_packages.clone() {
+            builder = builder.add_member(key_package).unwrap();
+        }
+
+        let output = builder.build().await.unwrap();
+        let max_welcome_bytes = output.welcome_messages[0].mls_encoded_len() - 1;
+
+        let mut group = test_group_with_max_welcome(max_welcome_bytes).await;
+        let plan = group.group.plan_add_commits(key_packages).unwrap();
+
+        assert!(plan.len() > 1);
+        assert_eq!(plan.iter().map(Vec::len).sum::<usize>(), 6);
+
+        for batch in plan {
+            let mut builder = group.group.commit_builder();
+
+            for key_package in batch {
+                builder = builder.add_member(key_package).unwrap();
+            }
+
+            let output = builder.build().await.unwrap();
+
+            assert!(output.welcome_messages[0].mls_encoded_len() <= max_welcome_bytes);
+
+            group.process_pending_commit().await.unwrap();
+        }
+
+        assert_eq!(group.group.roster().members().len(), 7);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn welcome_larger_than_limit_is_rejected() {
+        let key_packages = test_key_packages().await;
+        let mut group = test_group_with_max_welcome(100).await;
+
+        let res = group.group.plan_add_commits(key_packages.clone());
+        assert_matches!(res, Err(MlsError::WelcomeTooLarge(_, 100)));
+
+        let res = group
+            .group
+            .commit_builder()
+            .add_member(key_packages[0].clone())
+            .unwrap()
+            .build()
+            .await;
+
+        assert_matches!(res, Err(MlsError::WelcomeTooLarge(size, 100)) if size > 100);
+        assert!(!group.group.has_pending_commit());
+    }
+}
@@ -229,7 +229,7 @@ mod tests {
             membership_tag::MembershipTag,
             message_signature::{AuthenticatedContent, MessageSignature},
             test_utils::{test_group_custom, TestGroup},
-            Group, PublicMessage,
+            Group, PublicMessage, Validated,
         },
         tree_kem::node::LeafIndex,
     };
@@ -308,7 +308,7 @@ mod tests {
             alice.group.apply_pending_commit().await.unwrap();
 
             let (bob, _) = Group::join(
-                &commit_output.welcome_messages[0],
+                Validated::check(&commit_output.welcome_messages[0]).unwrap(),
                 None,
                 bob_client.config,
                 bob_client.signer.unwrap(),
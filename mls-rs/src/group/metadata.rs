@@ -0,0 +1,268 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use mls_rs_core::extension::ExtensionList;
+
+#[cfg(feature = "by_ref_proposal")]
+use alloc::vec::Vec;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    extension::{GroupMetadataExt, GROUP_METADATA_EXTENSION_TYPE},
+    group::{proposal::Proposal, Group},
+};
+
+#[cfg(feature = "by_ref_proposal")]
+use crate::group::MlsMessage;
+
+/// Change to the [`GroupMetadataExt`] of a group caused by a commit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct GroupMetadataUpdate {
+    /// Metadata before the commit.
+    pub prior: Option<GroupMetadataExt>,
+    /// Metadata after the commit.
+    pub new: Option<GroupMetadataExt>,
+}
+
+impl GroupMetadataUpdate {
+    pub(crate) fn from_extensions(
+        prior: &ExtensionList,
+        new: &ExtensionList,
+    ) -> Result<Option<Self>, MlsError> {
+        let prior = prior.get_as::<GroupMetadataExt>()?;
+        let new = new.get_as::<GroupMetadataExt>()?;
+
+        Ok((prior != new).then_some(Self { prior, new }))
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Application metadata of the group in the current epoch.
+    pub fn group_metadata(&self) -> Result<Option<GroupMetadataExt>, MlsError> {
+        Ok(self.context().extensions.get_as()?)
+    }
+
+    /// Create a proposal message that replaces the application metadata of
+    /// the group.
+    ///
+    /// All other group context extensions are kept. This function returns
+    /// [`MlsError::UnsupportedGroupExtension`] if a member of the group does
+    /// not support the metadata extension.
+    ///
+    /// `authenticated_data` will be sent unencrypted along with the contents
+    /// of the proposal message.
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn propose_group_metadata(
+        &mut self,
+        metadata: GroupMetadataExt,
+        authenticated_data: Vec<u8>,
+    ) -> Result<MlsMessage, MlsError> {
+        let proposal = self.group_metadata_proposal(metadata)?;
+        self.proposal_message(proposal, authenticated_data).await
+    }
+
+    pub(crate) fn group_metadata_proposal(
+        &self,
+        metadata: GroupMetadataExt,
+    ) -> Result<Proposal, MlsError> {
+        let unsupported = self.roster().members_iter().any(|member| {
+            !member
+                .capabilities
+                .extensions
+                .contains(&GROUP_METADATA_EXTENSION_TYPE)
+        });
+
+        if unsupported {
+            return Err(MlsError::UnsupportedGroupExtension(
+                GROUP_METADATA_EXTENSION_TYPE,
+            ));
+        }
+
+        let mut extensions = self.context().extensions.clone();
+        extensions.set_from(metadata)?;
+
+        Ok(self.group_context_extensions_proposal(extensions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use super::GroupMetadataUpdate;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        extension::{ExtensionType, GroupMetadataExt, GROUP_METADATA_EXTENSION_TYPE},
+        group::{
+            test_utils::{test_group_custom_config, TestGroup},
+            CommitMessageDescription, ReceivedMessage,
+        },
+    };
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn metadata_group() -> TestGroup {
+        test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.extension_type(GROUP_METADATA_EXTENSION_TYPE)
+        })
+        .await
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn join_with_metadata_support(group: &mut TestGroup, name: &str) -> TestGroup {
+        group
+            .join_with_custom_config(name, true, |c| {
+                c.0.settings
+                    .extension_types
+                    .push(GROUP_METADATA_EXTENSION_TYPE)
+            })
+            .await
+            .unwrap()
+            .0
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn metadata_can_be_set_and_read() {
+        let mut alice = metadata_group().await;
+        let mut bob = join_with_metadata_support(&mut alice, "bob").await;
+
+        assert_eq!(alice.group.group_metadata().unwrap(), None);
+
+        let metadata = GroupMetadataExt::new()
+            .with_name("name".into())
+            .with_topic("topic".into());
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .set_group_metadata(metadata.clone())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.process_pending_commit().await.unwrap();
+
+        let res = bob.process_message(commit.commit_message).await.unwrap();
+
+        let expected = GroupMetadataUpdate {
+            prior: None,
+            new: Some(metadata.clone()),
+        };
+
+        assert_matches!(
+            res,
+            ReceivedMessage::Commit(CommitMessageDescription {
+                group_metadata_update: Some(update),
+                ..
+            }) if update == expected
+        );
+
+        assert_eq!(
+            alice.group.group_metadata().unwrap(),
+            Some(metadata.clone())
+        );
+        assert_eq!(bob.group.group_metadata().unwrap(), Some(metadata));
+
+        assert!(bob
+            .group
+            .context()
+            .extensions
+            .has_extension(ExtensionType::REQUIRED_CAPABILITIES));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn metadata_update_is_reported_only_on_change() {
+        let mut alice = metadata_group().await;
+        let mut bob = join_with_metadata_support(&mut alice, "bob").await;
+
+        let metadata = GroupMetadataExt::new().with_avatar_hash(vec![1, 2, 3]);
+
+        for expect_update in [true, false] {
+            let commit = alice
+                .group
+                .commit_builder()
+                .set_group_metadata(metadata.clone())
+                .unwrap()
+                .build()
+                .await
+                .unwrap();
+
+            alice.process_pending_commit().await.unwrap();
+
+            let res = bob.process_message(commit.commit_message).await.unwrap();
+
+            let ReceivedMessage::Commit(description) = res else {
+                panic!("expected commit")
+            };
+
+            assert_eq!(description.group_metadata_update.is_some(), expect_update);
+        }
+
+        let commit = alice.group.commit(vec![]).await.unwrap();
+        alice.process_pending_commit().await.unwrap();
+
+        let res = bob.process_message(commit.commit_message).await.unwrap();
+
+        assert_matches!(
+            res,
+            ReceivedMessage::Commit(CommitMessageDescription {
+                group_metadata_update: None,
+                ..
+            })
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn metadata_requires_support_by_all_members() {
+        let mut alice = metadata_group().await;
+        alice.join("bob").await;
+
+        let res = alice
+            .group
+            .commit_builder()
+            .set_group_metadata(GroupMetadataExt::new().with_name("name".into()))
+            .map(|_| ());
+
+        assert_matches!(
+            res,
+            Err(MlsError::UnsupportedGroupExtension(
+                GROUP_METADATA_EXTENSION_TYPE
+            ))
+        );
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn metadata_can_be_proposed() {
+        let mut alice = metadata_group().await;
+        let mut bob = join_with_metadata_support(&mut alice, "bob").await;
+
+        let metadata = GroupMetadataExt::new().with_name("name".into());
+
+        let proposal = bob
+            .group
+            .propose_group_metadata(metadata.clone(), vec![])
+            .await
+            .unwrap();
+
+        alice.process_message(proposal).await.unwrap();
+
+        let commit = alice.group.commit(vec![]).await.unwrap();
+        alice.process_pending_commit().await.unwrap();
+        bob.process_message(commit.commit_message).await.unwrap();
+
+        assert_eq!(bob.group.group_metadata().unwrap(), Some(metadata));
+    }
+}
@@ -0,0 +1,153 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Upgrade of group states persisted by earlier versions of this crate.
+
+use alloc::vec::Vec;
+
+use mls_rs_codec::{MlsDecode, MlsEncode};
+
+use crate::{
+    client::MlsError,
+    group::snapshot::{Snapshot, SNAPSHOT_VERSION},
+};
+
+/// Convert a group state read from a
+/// [`GroupStateStorage`](crate::GroupStateStorage) to the layout written by
+/// this version of the crate.
+///
+/// The result can be written back to storage and loaded with
+/// [`Client::load_group`](crate::Client::load_group). States in the version 1
+/// layout, written before the activity, transcript and archival data of a
+/// group was persisted, are converted with defaults for that data. States in
+/// the current layout are validated and returned unchanged. States with an
+/// unknown version are rejected with [`MlsError::UnsupportedSnapshotVersion`].
+///
+/// Every published release of this crate encodes group states with
+/// `mls-rs-codec` and starts them with the layout version. States written
+/// with `tls_codec` by the unreleased predecessor of this crate are not
+/// supported and fail to decode. Members with such states have to join
+/// their groups again.
+pub fn upgrade_group_state(bytes: &[u8]) -> Result<Vec<u8>, MlsError> {
+    match group_state_version(bytes)? {
+        1 => Snapshot::from_v1_bytes(bytes)?
+            .mls_encode_to_vec()
+            .map_err(Into::into),
+        SNAPSHOT_VERSION => {
+            // Decode the full state to make sure it is valid before it is
+            // written back to storage.
+            Snapshot::from_bytes(bytes)?;
+            Ok(bytes.to_vec())
+        }
+        version => Err(MlsError::UnsupportedSnapshotVersion(version)),
+    }
+}
+
+/// Version of the layout of a group state read from a
+/// [`GroupStateStorage`](crate::GroupStateStorage).
+pub fn group_state_version(bytes: &[u8]) -> Result<u16, MlsError> {
+    u16::mls_decode(&mut &*bytes).map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use mls_rs_codec::MlsEncode;
+
+    use super::{group_state_version, upgrade_group_state};
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{
+            snapshot::{test_utils::snapshot_v1_bytes, Snapshot, SNAPSHOT_VERSION},
+            test_utils::test_group,
+        },
+    };
+
+    #[derive(serde::Deserialize)]
+    struct TestCase {
+        #[serde(with = "hex::serde")]
+        snapshot: alloc::vec::Vec<u8>,
+    }
+
+    // Written by mls-rs 0.39.1, the last release with the version 1 layout,
+    // for a group of two members with basic credentials after the commit
+    // adding the second member.
+    fn load_test_case() -> TestCase {
+        serde_json::from_slice(include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_data/group_state_v1.json"
+        )))
+        .unwrap()
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn current_group_state_is_unchanged() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        alice.join("bob").await;
+
+        let bytes = alice.group.snapshot().mls_encode_to_vec().unwrap();
+
+        assert_eq!(group_state_version(&bytes).unwrap(), SNAPSHOT_VERSION);
+        assert_eq!(upgrade_group_state(&bytes).unwrap(), bytes);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn version_1_group_state_is_upgraded() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        alice.join("bob").await;
+
+        let bytes = snapshot_v1_bytes(alice.group.snapshot());
+        assert_eq!(group_state_version(&bytes).unwrap(), 1);
+
+        let upgraded = upgrade_group_state(&bytes).unwrap();
+        assert_eq!(group_state_version(&upgraded).unwrap(), SNAPSHOT_VERSION);
+
+        let snapshot = Snapshot::from_bytes(&upgraded).unwrap();
+        assert_eq!(snapshot, Snapshot::from_v1_bytes(&bytes).unwrap());
+        assert_eq!(snapshot.state.context, alice.group.state.context);
+    }
+
+    // The pinned version 1 state was written with the default features. It
+    // is upgraded with any additional features enabled, including those that
+    // add data to the epoch secrets such as `application_lanes`.
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn pinned_version_1_group_state_is_upgraded() {
+        use crate::{client::test_utils::TestClientBuilder, group::Group};
+
+        let test_case = load_test_case();
+        assert_eq!(group_state_version(&test_case.snapshot).unwrap(), 1);
+
+        let upgraded = upgrade_group_state(&test_case.snapshot).unwrap();
+        assert_eq!(group_state_version(&upgraded).unwrap(), SNAPSHOT_VERSION);
+
+        // Upgrading is idempotent.
+        assert_eq!(upgrade_group_state(&upgraded).unwrap(), upgraded);
+
+        let snapshot = Snapshot::from_bytes(&upgraded).unwrap();
+        let config = TestClientBuilder::new_for_test().build().config;
+        let group = Group::from_snapshot(config, snapshot).await.unwrap();
+
+        assert_eq!(group.context().epoch, 1);
+        assert_eq!(group.roster().members_iter().count(), 2);
+        assert!(!group.is_archived());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn unknown_group_state_version_is_rejected() {
+        let alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let mut bytes = alice.group.snapshot().mls_encode_to_vec().unwrap();
+        bytes[..2].copy_from_slice(&7u16.to_be_bytes());
+
+        let res = upgrade_group_state(&bytes);
+        assert_matches!(res, Err(MlsError::UnsupportedSnapshotVersion(7)));
+
+        let res = upgrade_group_state(&[0]);
+        assert_matches!(res, Err(MlsError::SerializationError(_)));
+    }
+}
@@ -2,7 +2,14 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
-use crate::group::{proposal_filter::ProposalBundle, Roster};
+use crate::{
+    client::MlsError,
+    group::{
+        proposal::AddProposal,
+        proposal_filter::{ProposalBundle, ProposalInfo},
+        Roster,
+    },
+};
 
 #[cfg(feature = "private_message")]
 use crate::{
@@ -30,23 +37,71 @@ pub enum CommitSource {
     NewMember(SigningIdentity),
 }
 
+/// Policy controlling when a commit includes an update path.
+///
+/// RFC 9420 requires a path if the commit covers no proposals, or covers an
+/// update, remove, external init or group context extensions proposal. The
+/// policy decides what happens for all other commits, e.g. add-only commits.
+///
+/// The policy used for commits created by this member is
+/// [`CommitOptions::path_policy`]. Commits received from other members are
+/// checked against [`CommitOptions::received_path_policy`], only where the RFC
+/// leaves the path optional. Commits by new members joining via external
+/// commit are exempt, since they always include a path.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CommitPathPolicy {
+    /// Always include a path, even if the RFC allows omitting it. This
+    /// provides post-compromise security with every commit. Received commits
+    /// that omit an optional path are rejected with
+    /// [`MlsError::CommitMissingPath`].
+    ForcePath,
+    /// Include a path only if it is required by the RFC. Received commits are
+    /// accepted with or without an optional path.
+    #[default]
+    PathRequiredOnly,
+    /// Never include a path, to keep commits small. Creating a commit for which
+    /// the RFC requires a path fails with [`MlsError::CommitPathNotAllowed`].
+    /// Received commits that include an optional path are rejected with the
+    /// same error.
+    NeverPath,
+}
+
 /// Options controlling commit generation
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[non_exhaustive]
 pub struct CommitOptions {
+    /// Always include an update path. This is equivalent to using
+    /// [`CommitPathPolicy::ForcePath`] when creating commits, but does not
+    /// affect the validation of received commits.
     pub path_required: bool,
+    /// Policy for the path of commits created by this member.
+    pub path_policy: CommitPathPolicy,
+    /// Policy for the path of commits received from other members. The
+    /// default accepts every commit that complies with the RFC, whatever
+    /// `path_policy` is.
+    pub received_path_policy: CommitPathPolicy,
     pub ratchet_tree_extension: bool,
     pub single_welcome_message: bool,
     pub allow_external_commit: bool,
+    /// Maximum encoded size of each welcome message produced by a commit.
+    /// Creating a commit with a larger welcome message fails with
+    /// [`MlsError::WelcomeTooLarge`]. Large sets of new members can be split
+    /// across several commits with
+    /// [`Group::plan_add_commits`](crate::group::Group::plan_add_commits).
+    pub max_welcome_bytes: Option<usize>,
 }
 
 impl Default for CommitOptions {
     fn default() -> Self {
         CommitOptions {
             path_required: false,
+            path_policy: CommitPathPolicy::default(),
+            received_path_policy: CommitPathPolicy::default(),
             ratchet_tree_extension: true,
             single_welcome_message: true,
             allow_external_commit: false,
+            max_welcome_bytes: None,
         }
     }
 }
@@ -63,6 +118,20 @@ impl CommitOptions {
         }
     }
 
+    pub fn with_path_policy(self, path_policy: CommitPathPolicy) -> Self {
+        Self {
+            path_policy,
+            ..self
+        }
+    }
+
+    pub fn with_received_path_policy(self, received_path_policy: CommitPathPolicy) -> Self {
+        Self {
+            received_path_policy,
+            ..self
+        }
+    }
+
     pub fn with_ratchet_tree_extension(self, ratchet_tree_extension: bool) -> Self {
         Self {
             ratchet_tree_extension,
@@ -83,6 +152,47 @@ impl CommitOptions {
             ..self
         }
     }
+
+    pub fn with_max_welcome_bytes(self, max_welcome_bytes: Option<usize>) -> Self {
+        Self {
+            max_welcome_bytes,
+            ..self
+        }
+    }
+
+    /// Decide whether a new commit includes a path, given whether the path is
+    /// `mandatory` according to the RFC.
+    pub(crate) fn include_path(
+        &self,
+        mandatory: bool,
+        is_external: bool,
+    ) -> Result<bool, MlsError> {
+        match self.path_policy {
+            _ if is_external => Ok(true),
+            _ if self.path_required => Ok(true),
+            CommitPathPolicy::ForcePath => Ok(true),
+            CommitPathPolicy::PathRequiredOnly => Ok(mandatory),
+            CommitPathPolicy::NeverPath if mandatory => Err(MlsError::CommitPathNotAllowed),
+            CommitPathPolicy::NeverPath => Ok(false),
+        }
+    }
+
+    /// Check that the presence of a path in a received commit complies with
+    /// the received path policy, given whether the path is `mandatory`
+    /// according to the RFC.
+    pub(crate) fn check_received_path(
+        &self,
+        has_path: bool,
+        mandatory: bool,
+        is_external: bool,
+    ) -> Result<(), MlsError> {
+        match self.received_path_policy {
+            _ if is_external || mandatory => Ok(()),
+            CommitPathPolicy::ForcePath if !has_path => Err(MlsError::CommitMissingPath),
+            CommitPathPolicy::NeverPath if has_path => Err(MlsError::CommitPathNotAllowed),
+            _ => Ok(()),
+        }
+    }
 }
 
 /// Options controlling encryption of control and application messages
@@ -112,6 +222,44 @@ impl EncryptionOptions {
     }
 }
 
+/// Client policy on which content types must be sent and received as
+/// [`PrivateMessage`](WireFormat::PrivateMessage).
+///
+/// Unlike [`EncryptionOptions`], which only apply to messages sent by the
+/// client, the policy is also enforced on incoming messages.
+#[cfg(feature = "private_message")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct EncryptionPolicy {
+    /// Encrypt proposals and commits sent by members, and reject those
+    /// received from members as [`PublicMessage`](WireFormat::PublicMessage).
+    ///
+    /// Messages sent by external senders and new members are never
+    /// encrypted and are not affected.
+    pub encrypt_handshake: bool,
+    /// Encrypt application messages. RFC 9420 does not allow unencrypted
+    /// application messages, so they are always encrypted and unencrypted
+    /// ones are always rejected.
+    pub encrypt_application: bool,
+}
+
+#[cfg(feature = "private_message")]
+impl EncryptionPolicy {
+    pub fn new(encrypt_handshake: bool) -> Self {
+        Self {
+            encrypt_handshake,
+            encrypt_application: true,
+        }
+    }
+}
+
+#[cfg(feature = "private_message")]
+impl Default for EncryptionPolicy {
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
 /// A set of user controlled rules that customize the behavior of MLS.
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
@@ -147,12 +295,37 @@ pub trait MlsRules: Send + Sync {
         proposals: ProposalBundle,
     ) -> Result<ProposalBundle, Self::Error>;
 
+    /// This is called when receiving a commit, once for each Add proposal it covers, after the
+    /// proposals have been filtered and validated by the library, including the validation of the
+    /// new member's credential by the
+    /// [`IdentityProvider`](mls_rs_core::identity::IdentityProvider).
+    ///
+    /// This allows the application to perform additional vetting of new members, e.g. checking
+    /// them against a list of approved contacts. Returning an error rejects the whole commit with
+    /// [`MlsError::AddRejected`], which contains the index of the rejected proposal among the
+    /// [add proposals](ProposalBundle::add_proposals) of the commit.
+    ///
+    /// The default implementation accepts all Add proposals.
+    fn vet_add(
+        &self,
+        source: &CommitSource,
+        current_roster: &Roster,
+        extension_list: &ExtensionList,
+        add: &ProposalInfo<AddProposal>,
+    ) -> Result<(), Self::Error> {
+        let _ = (source, current_roster, extension_list, add);
+        Ok(())
+    }
+
     /// This is called when preparing a commit to determine various options: whether to enforce an update
     /// path in case it is not mandated by MLS, whether to include the ratchet tree in the welcome
     /// message (if the commit adds members) and whether to generate a single welcome message, or one
     /// welcome message for each added member.
     ///
     /// The `new_roster` and `new_extension_list` describe the group state after the commit.
+    ///
+    /// This is also called when processing a received commit, in order to validate the commit
+    /// against the [`CommitOptions::path_policy`].
     fn commit_options(
         &self,
         new_roster: &Roster,
@@ -193,6 +366,16 @@ macro_rules! delegate_mls_rules {
                     .await
             }
 
+            fn vet_add(
+                &self,
+                source: &CommitSource,
+                current_roster: &Roster,
+                extension_list: &ExtensionList,
+                add: &ProposalInfo<AddProposal>,
+            ) -> Result<(), Self::Error> {
+                (**self).vet_add(source, current_roster, extension_list, add)
+            }
+
             fn commit_options(
                 &self,
                 roster: &Roster,
@@ -281,3 +464,169 @@ impl MlsRules for DefaultMlsRules {
         Ok(self.encryption_options)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #[cfg(mls_build_async)]
+    use alloc::boxed::Box;
+    use assert_matches::assert_matches;
+    use mls_rs_core::{error::IntoAnyError, extension::ExtensionList};
+
+    use super::{
+        CommitDirection, CommitOptions, CommitSource, DefaultMlsRules, EncryptionOptions, MlsRules,
+    };
+
+    use crate::{
+        client::{test_utils::TEST_CIPHER_SUITE, MlsError},
+        client_builder::{ClientBuilder, MlsConfig},
+        crypto::test_utils::TestCryptoProvider,
+        group::{
+            proposal::AddProposal,
+            proposal_filter::{ProposalBundle, ProposalInfo},
+            Group, Roster, Validated,
+        },
+        identity::{basic::BasicIdentityProvider, test_utils::get_test_signing_identity},
+        Client,
+    };
+
+    #[derive(Debug)]
+    #[cfg_attr(feature = "std", derive(thiserror::Error))]
+    #[cfg_attr(feature = "std", error("new member is not vetted"))]
+    struct NotVetted;
+
+    impl IntoAnyError for NotVetted {
+        #[cfg(feature = "std")]
+        fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+            Ok(self.into())
+        }
+    }
+
+    // Rules refusing to let Mallory join.
+    #[derive(Clone, Debug)]
+    struct NoMallory;
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl MlsRules for NoMallory {
+        type Error = NotVetted;
+
+        async fn filter_proposals(
+            &self,
+            _direction: CommitDirection,
+            _source: CommitSource,
+            _current_roster: &Roster,
+            _extension_list: &ExtensionList,
+            proposals: ProposalBundle,
+        ) -> Result<ProposalBundle, Self::Error> {
+            Ok(proposals)
+        }
+
+        fn vet_add(
+            &self,
+            _source: &CommitSource,
+            _current_roster: &Roster,
+            _extension_list: &ExtensionList,
+            add: &ProposalInfo<AddProposal>,
+        ) -> Result<(), Self::Error> {
+            let credential = add.proposal.signing_identity().credential.as_basic();
+
+            match credential {
+                Some(c) if c.identifier == b"mallory" => Err(NotVetted),
+                _ => Ok(()),
+            }
+        }
+
+        fn commit_options(
+            &self,
+            _new_roster: &Roster,
+            _new_extension_list: &ExtensionList,
+            _proposals: &ProposalBundle,
+        ) -> Result<CommitOptions, Self::Error> {
+            Ok(CommitOptions::default())
+        }
+
+        fn encryption_options(
+            &self,
+            _current_roster: &Roster,
+            _current_extension_list: &ExtensionList,
+        ) -> Result<EncryptionOptions, Self::Error> {
+            Ok(EncryptionOptions::default())
+        }
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn test_client<R: MlsRules + Clone>(name: &[u8], mls_rules: R) -> Client<impl MlsConfig> {
+        let (signing_identity, signer) = get_test_signing_identity(TEST_CIPHER_SUITE, name).await;
+
+        ClientBuilder::new()
+            .crypto_provider(TestCryptoProvider::new())
+            .identity_provider(BasicIdentityProvider::new())
+            .signing_identity(signing_identity, signer, TEST_CIPHER_SUITE)
+            .mls_rules(mls_rules)
+            .build()
+    }
+
+    // Create a group of Alice, using default rules, and Bob, refusing to let
+    // Mallory join. Alice commits adding Carol and `name`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn commit_adding(name: &[u8]) -> (Group<impl MlsConfig>, Result<(), MlsError>) {
+        let alice = test_client(b"alice", DefaultMlsRules::new()).await;
+        let bob = test_client(b"bob", NoMallory).await;
+
+        let mut alice_group = alice.create_group(ExtensionList::new()).await.unwrap();
+
+        let commit = alice_group
+            .commit_builder()
+            .add_member(bob.generate_key_package_message().await.unwrap())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice_group.apply_pending_commit().await.unwrap();
+
+        let (mut bob_group, _) = bob
+            .join_group_validated(
+                None,
+                &Validated::new(commit.welcome_messages[0].clone()).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let carol = test_client(b"carol", DefaultMlsRules::new()).await;
+        let other = test_client(name, DefaultMlsRules::new()).await;
+
+        let commit = alice_group
+            .commit_builder()
+            .add_member(carol.generate_key_package_message().await.unwrap())
+            .unwrap()
+            .add_member(other.generate_key_package_message().await.unwrap())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let res = bob_group
+            .process_incoming_message_validated(Validated::new(commit.commit_message).unwrap())
+            .await
+            .map(|_| ());
+
+        (bob_group, res)
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn vetted_add_is_accepted() {
+        let (bob_group, res) = commit_adding(b"dave").await;
+
+        res.unwrap();
+        assert_eq!(bob_group.roster().members().len(), 4);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_with_unvetted_add_is_rejected() {
+        let (bob_group, res) = commit_adding(b"mallory").await;
+
+        assert_matches!(res, Err(MlsError::AddRejected(1, _)));
+        assert_eq!(bob_group.roster().members().len(), 2);
+    }
+}
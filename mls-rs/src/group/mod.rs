@@ -7,6 +7,7 @@ use alloc::vec::Vec;
 use core::fmt::{self, Debug};
 use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
 use mls_rs_core::error::IntoAnyError;
+use mls_rs_core::key_package::KeyPackageStorage;
 use mls_rs_core::secret::Secret;
 use mls_rs_core::time::MlsTime;
 
@@ -14,12 +15,14 @@ use crate::cipher_suite::CipherSuite;
 use crate::client::MlsError;
 use crate::client_config::ClientConfig;
 use crate::crypto::{HpkeCiphertext, SignatureSecretKey};
+use crate::error_render;
 use crate::extension::RatchetTreeExt;
 use crate::identity::SigningIdentity;
-use crate::key_package::{KeyPackage, KeyPackageRef};
+use crate::key_package::{KeyPackage, KeyPackageGeneration, KeyPackageRef};
 use crate::protocol_version::ProtocolVersion;
 use crate::psk::secret::PskSecret;
 use crate::psk::PreSharedKeyID;
+use crate::shared_bytes::SharedBytes;
 use crate::signer::Signable;
 use crate::tree_kem::hpke_encryption::HpkeEncryptable;
 use crate::tree_kem::kem::TreeKem;
@@ -27,12 +30,14 @@ use crate::tree_kem::node::LeafIndex;
 use crate::tree_kem::path_secret::PathSecret;
 pub use crate::tree_kem::Capabilities;
 use crate::tree_kem::{
-    leaf_node::LeafNode,
+    leaf_node::{ConfigProperties, LeafNode},
     leaf_node_validator::{LeafNodeValidator, ValidationContext},
 };
 use crate::tree_kem::{math as tree_math, ValidatedUpdatePath};
 use crate::tree_kem::{TreeKemPrivate, TreeKemPublic};
-use crate::{CipherSuiteProvider, CryptoProvider};
+#[cfg(feature = "by_ref_proposal")]
+use crate::CipherSuiteProvider;
+use crate::CryptoProvider;
 
 #[cfg(feature = "by_ref_proposal")]
 use crate::crypto::{HpkePublicKey, HpkeSecretKey};
@@ -40,7 +45,10 @@ use crate::crypto::{HpkePublicKey, HpkeSecretKey};
 use crate::extension::ExternalPubExt;
 
 #[cfg(feature = "private_message")]
-use self::mls_rules::{EncryptionOptions, MlsRules};
+use self::mls_rules::{EncryptionOptions, EncryptionPolicy, MlsRules};
+
+#[cfg(feature = "private_message")]
+use self::padding::{LengthHistogram, PaddingMode};
 
 #[cfg(feature = "psk")]
 pub use self::resumption::ReinitClient;
@@ -51,6 +59,8 @@ use crate::psk::{
     ResumptionPSKUsage, ResumptionPsk,
 };
 
+use mls_rs_core::psk::PreSharedKeyStorage;
+
 #[cfg(feature = "private_message")]
 use ciphertext_processor::*;
 
@@ -66,15 +76,20 @@ use proposal_cache::*;
 use state::*;
 use transcript_hash::*;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "adversarial"))]
 pub(crate) use self::commit::test_utils::CommitModifiers;
 
 #[cfg(all(test, feature = "private_message"))]
 pub use self::framing::PrivateMessage;
 
-#[cfg(feature = "psk")]
+#[cfg(any(feature = "psk", feature = "by_ref_proposal"))]
 use self::proposal_filter::ProposalInfo;
 
+#[cfg(feature = "by_ref_proposal")]
+use self::proposal_filter::ProposalSource;
+
+use self::proposal_filter::{unsupported_group_extension, validate_new_capabilities};
+
 #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
 use secret_tree::*;
 
@@ -91,11 +106,54 @@ use self::message_processor::{EventOrContent, MessageProcessor, ProvisionalState
 use self::proposal_ref::ProposalRef;
 use self::state_repo::GroupStateRepository;
 pub use group_info::GroupInfo;
+pub use metadata::GroupMetadataUpdate;
+pub use pending_commit::PendingCommit;
+pub use pending_join::PendingJoin;
 
 pub use self::framing::{ContentType, Sender};
+#[cfg(feature = "batch_envelope")]
+pub use batch_envelope::{CommitEnvelope, RoutedWelcome};
+pub use channel_binding::ChannelBinding;
+pub use cipher_suite_policy::CipherSuitePolicy;
 pub use commit::*;
+pub use commit_receipt::{CommitReceipt, CommitReceiptQuorum};
+#[cfg(feature = "compression")]
+pub(crate) use compression::CompressionSettings;
+#[cfg(feature = "compression")]
+pub use compression::{Compressor, DEFAULT_MAX_DECOMPRESSED_LEN};
 pub use context::GroupContext;
+pub use credential_types::{CredentialTypeConflict, CredentialTypeReport};
+#[cfg(feature = "by_ref_proposal")]
+pub use duplicate_proposal::DuplicateProposalPolicy;
+pub use epoch_age::EpochAgePolicy;
+#[cfg(feature = "escrow")]
+pub use escrow::{open_escrow_record, EscrowRecord};
+#[cfg(feature = "event_sourcing")]
+pub use event_sourcing::EpochDelta;
+pub use external_seal::{open_external, ExternalSealedData};
+pub use fingerprint::{EpochFingerprint, FingerprintDivergence};
+pub use future_epoch::FutureEpochBuffer;
+pub use group_id::{GroupIdCharset, GroupIdGeneration, GroupIdPolicy};
+pub use group_policy::GroupPolicySupport;
+pub use history_sharing::HistoryKeyPackage;
+pub use init_key_reuse::InitKeyReusePolicy;
+pub use invite_code::{InviteCode, InviteCodeEncoding, InviteCodeFormat};
+pub use membership_proof::MembershipProof;
+pub use message_range::{MessageCounter, MessageRange};
+pub use proposal_compaction::{CompactionReport, ProposalCompaction};
+pub use reinit_policy::ReInitCipherSuitePolicy;
+pub use revalidation::{InvalidMember, RevalidationReport};
 pub use roster::*;
+pub use roster_diff::{RosterDiff, RosterOperation};
+pub use self_check::{SelfCheckIssue, SelfCheckReport};
+pub use signed_roster::{SignedRoster, SignedRosterEntry};
+pub use stats::GroupStats;
+pub use transcript_chain::{TranscriptChain, TranscriptChainEntry};
+pub use tree_validator::{SharedTreeValidator, TreeValidator};
+pub use validated::Validated;
+pub use view::GroupView;
+#[cfg(feature = "private_message")]
+pub use watermark::WatermarkPersistence;
 
 pub(crate) use transcript_hash::ConfirmedTranscriptHash;
 pub(crate) use util::*;
@@ -103,34 +161,76 @@ pub(crate) use util::*;
 #[cfg(all(feature = "by_ref_proposal", feature = "external_client"))]
 pub use self::message_processor::CachedProposal;
 
+mod archive;
+#[cfg(feature = "batch_envelope")]
+mod batch_envelope;
+mod channel_binding;
+mod cipher_suite_policy;
 #[cfg(feature = "private_message")]
 mod ciphertext_processor;
 
 mod commit;
+pub(crate) mod commit_authorization;
+mod commit_receipt;
+#[cfg(feature = "compression")]
+mod compression;
 pub(crate) mod confirmation_tag;
 mod context;
+mod credential_types;
+#[cfg(feature = "by_ref_proposal")]
+mod duplicate_proposal;
 pub(crate) mod epoch;
+pub(crate) mod epoch_age;
+#[cfg(feature = "private_message")]
+mod epoch_pinning;
+#[cfg(feature = "escrow")]
+mod escrow;
+#[cfg(feature = "event_sourcing")]
+mod event_sourcing;
+mod external_seal;
+mod fingerprint;
 pub(crate) mod framing;
+mod future_epoch;
+mod group_id;
 mod group_info;
+mod group_policy;
+mod history_sharing;
+mod init_key_reuse;
+mod invite_code;
 pub(crate) mod key_schedule;
+mod member_activity;
+mod membership_proof;
 mod membership_tag;
 pub(crate) mod message_processor;
+mod message_range;
 pub(crate) mod message_signature;
+mod message_size;
 pub(crate) mod message_verifier;
+mod metadata;
+pub mod migrate;
 pub mod mls_rules;
 #[cfg(feature = "private_message")]
 pub(crate) mod padding;
+mod pending_commit;
+mod pending_join;
 /// Proposals to evolve a MLS [`Group`]
 pub mod proposal;
 mod proposal_cache;
+mod proposal_compaction;
 pub(crate) mod proposal_filter;
 #[cfg(feature = "by_ref_proposal")]
 pub(crate) mod proposal_ref;
+mod reinit_policy;
 #[cfg(feature = "psk")]
 mod resumption;
+mod revalidation;
 mod roster;
+mod roster_diff;
+mod self_check;
+mod signed_roster;
 pub(crate) mod snapshot;
 pub(crate) mod state;
+mod stats;
 
 #[cfg(feature = "prior_epoch")]
 pub(crate) mod state_repo;
@@ -139,8 +239,16 @@ pub(crate) mod state_repo_light;
 #[cfg(not(feature = "prior_epoch"))]
 pub(crate) use state_repo_light as state_repo;
 
+mod transcript_chain;
 pub(crate) mod transcript_hash;
+mod tree_validator;
+mod unknown_extensions;
 mod util;
+mod validated;
+mod view;
+#[cfg(feature = "private_message")]
+mod watermark;
+mod welcome_decode;
 
 /// External commit building.
 pub mod external_commit;
@@ -149,11 +257,19 @@ pub mod external_commit;
 pub(crate) mod secret_tree;
 
 #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
-pub use secret_tree::MessageKeyData as MessageKey;
+pub use secret_tree::{MessageKeyData as MessageKey, SecretTreeMemoryUsage};
 
 #[cfg(all(test, feature = "rfc_compliant"))]
 mod interop_test_vectors;
 
+#[cfg(all(
+    test,
+    feature = "std",
+    feature = "by_ref_proposal",
+    not(target_arch = "wasm32")
+))]
+mod format_stability;
+
 mod exported_tree;
 
 pub use exported_tree::ExportedTree;
@@ -189,8 +305,9 @@ pub(crate) struct EncryptedGroupSecrets {
 pub(crate) struct Welcome {
     pub cipher_suite: CipherSuite,
     pub secrets: Vec<EncryptedGroupSecrets>,
-    #[mls_codec(with = "mls_rs_codec::byte_vec")]
-    pub encrypted_group_info: Vec<u8>,
+    #[mls_codec(with = "crate::shared_bytes::codec")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::shared_bytes::arbitrary))]
+    pub encrypted_group_info: SharedBytes,
 }
 
 impl Debug for Welcome {
@@ -266,9 +383,42 @@ where
     pending_updates:
         crate::map::SmallMap<HpkePublicKey, (HpkeSecretKey, Option<SignatureSecretKey>)>, // Hash of leaf node hpke public key to secret key
     pending_commit: Option<CommitGeneration>,
+    pending_leave: bool,
+    requires_self_update: bool,
+    epoch_started_at: Option<u64>,
+    epoch_committer: LeafIndex,
+    removed: bool,
     #[cfg(feature = "psk")]
     previous_psk: Option<PskSecretInput>,
-    #[cfg(test)]
+    #[cfg(feature = "private_message")]
+    sender_data_keys: SenderDataKeyCache,
+    #[cfg(feature = "private_message")]
+    length_histogram: LengthHistogram,
+    // Sender data decrypted while ordering a batch of messages, together with
+    // the encrypted sender data of the message it belongs to.
+    #[cfg(feature = "private_message")]
+    batch_sender_data: Option<(Vec<u8>, SenderData)>,
+    // Set while the writes of `WatermarkPersistence::AfterProcessing` are
+    // deferred to the end of `process_incoming_messages`, true once a write
+    // was deferred.
+    watermark_batch: Option<bool>,
+    previous_epoch_sends: u64,
+    // Whether the commit into the current epoch left all leaves of the
+    // previous epoch in place, so that sending in the previous epoch does not
+    // reveal messages to removed or updated members. Not stored, so sending
+    // in the previous epoch is refused after loading the group.
+    #[cfg(feature = "prior_epoch")]
+    previous_epoch_sendable: bool,
+    epoch_view: Option<alloc::sync::Arc<view::EpochView>>,
+    transcript_chain: Vec<TranscriptChainEntry>,
+    member_activity: Vec<Option<u64>>,
+    roster_diffs: Vec<RosterDiff>,
+    archived: bool,
+    #[cfg(feature = "event_sourcing")]
+    epoch_deltas: Vec<EpochDelta>,
+    #[cfg(feature = "event_sourcing")]
+    deltas_since_compaction: u64,
+    #[cfg(any(test, feature = "adversarial"))]
     pub(crate) commit_modifiers: CommitModifiers,
     pub(crate) signer: SignatureSecretKey,
 }
@@ -285,11 +435,17 @@ where
         cipher_suite: CipherSuite,
         protocol_version: ProtocolVersion,
         signing_identity: SigningIdentity,
-        group_context_extensions: ExtensionList,
+        mut group_context_extensions: ExtensionList,
         signer: SignatureSecretKey,
     ) -> Result<Self, MlsError> {
+        config
+            .cipher_suite_policy()
+            .check(protocol_version, cipher_suite)?;
+
         let cipher_suite_provider = cipher_suite_provider(config.crypto_provider(), cipher_suite)?;
 
+        group_context_extensions.canonicalize();
+
         let (leaf_node, leaf_node_secret) = LeafNode::generate(
             &cipher_suite_provider,
             config.leaf_properties(),
@@ -321,11 +477,12 @@ where
 
         let tree_hash = public_tree.tree_hash(&cipher_suite_provider).await?;
 
-        let group_id = group_id.map(Ok).unwrap_or_else(|| {
-            cipher_suite_provider
-                .random_bytes_vec(cipher_suite_provider.kdf_extract_size())
-                .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
-        })?;
+        let group_id_policy = config.group_id_policy();
+
+        let group_id = match group_id {
+            Some(group_id) => group_id_policy.validate(&group_id).map(|_| group_id),
+            None => group_id_policy.generate(&cipher_suite_provider),
+        }?;
 
         let context = GroupContext::new_group(
             protocol_version,
@@ -364,7 +521,9 @@ where
         )
         .await?;
 
-        Ok(Self {
+        let self_index = private_tree.self_index;
+
+        let mut group = Self {
             config,
             state: GroupState::new(context, public_tree, interim_hash, confirmation_tag),
             private_tree,
@@ -372,7 +531,31 @@ where
             #[cfg(feature = "by_ref_proposal")]
             pending_updates: Default::default(),
             pending_commit: None,
-            #[cfg(test)]
+            pending_leave: false,
+            requires_self_update: false,
+            epoch_started_at: epoch_age::current_time(),
+            epoch_committer: self_index,
+            removed: false,
+            #[cfg(feature = "private_message")]
+            sender_data_keys: Default::default(),
+            #[cfg(feature = "private_message")]
+            length_histogram: Default::default(),
+            #[cfg(feature = "private_message")]
+            batch_sender_data: None,
+            watermark_batch: None,
+            previous_epoch_sends: 0,
+            #[cfg(feature = "prior_epoch")]
+            previous_epoch_sendable: false,
+            epoch_view: None,
+            transcript_chain: Vec::new(),
+            member_activity: Vec::new(),
+            roster_diffs: Vec::new(),
+            archived: false,
+            #[cfg(feature = "event_sourcing")]
+            epoch_deltas: Vec::new(),
+            #[cfg(feature = "event_sourcing")]
+            deltas_since_compaction: 0,
+            #[cfg(any(test, feature = "adversarial"))]
             commit_modifiers: Default::default(),
             epoch_secrets: key_schedule_result.epoch_secrets,
             state_repo,
@@ -380,12 +563,17 @@ where
             #[cfg(feature = "psk")]
             previous_psk: None,
             signer,
-        })
+        };
+
+        group.record_transcript_chain_entry(group.private_tree.self_index);
+        group.reset_member_activity();
+
+        Ok(group)
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub(crate) async fn join(
-        welcome: &MlsMessage,
+        welcome: Validated<&MlsMessage>,
         tree_data: Option<ExportedTree<'_>>,
         config: C,
         signer: SignatureSecretKey,
@@ -401,14 +589,103 @@ where
         .await
     }
 
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn decrypt_group_secrets<P: CipherSuiteProvider>(
+        welcome: &Welcome,
+        protocol_version: ProtocolVersion,
+        config: &C,
+        cipher_suite_provider: &P,
+    ) -> Result<(GroupSecrets, KeyPackageGeneration), MlsError> {
+        let (encrypted_group_secrets, key_package_generation) =
+            find_key_package_generation(&config.key_package_repo(), &welcome.secrets).await?;
+
+        let key_package_version = key_package_generation.key_package.version;
+
+        if key_package_version != protocol_version {
+            return Err(MlsError::ProtocolVersionMismatch);
+        }
+
+        // Decrypt the encrypted_group_secrets using HPKE with the algorithms indicated by the
+        // cipher suite and the HPKE private key corresponding to the GroupSecrets.
+        let group_secrets = GroupSecrets::decrypt(
+            cipher_suite_provider,
+            &key_package_generation.init_secret_key,
+            &key_package_generation.key_package.hpke_init_key,
+            &welcome.encrypted_group_info,
+            &encrypted_group_secrets.encrypted_group_secrets,
+        )
+        .await?;
+
+        Ok((group_secrets, key_package_generation))
+    }
+
+    #[cfg(feature = "psk")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn welcome_required_psks(
+        welcome: Validated<&MlsMessage>,
+        config: &C,
+    ) -> Result<Vec<ExternalPskId>, MlsError> {
+        let protocol_version = welcome.version;
+
+        if !config.version_supported(protocol_version) {
+            return Err(MlsError::UnsupportedProtocolVersion(protocol_version));
+        }
+
+        let MlsMessagePayload::Welcome(welcome) = &welcome.payload else {
+            return Err(MlsError::UnexpectedMessageType);
+        };
+
+        let cipher_suite_provider =
+            cipher_suite_provider(config.crypto_provider(), welcome.cipher_suite)?;
+
+        let (group_secrets, _) =
+            Self::decrypt_group_secrets(welcome, protocol_version, config, &cipher_suite_provider)
+                .await?;
+
+        Ok(group_secrets
+            .psks
+            .into_iter()
+            .filter_map(|psk| match psk.key_id {
+                JustPreSharedKeyID::External(id) => Some(id),
+                JustPreSharedKeyID::Resumption(_) => None,
+            })
+            .collect())
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn from_welcome_message(
-        welcome: &MlsMessage,
+        welcome: Validated<&MlsMessage>,
         tree_data: Option<ExportedTree<'_>>,
         config: C,
         signer: SignatureSecretKey,
         #[cfg(feature = "psk")] additional_psk: Option<PskSecretInput>,
     ) -> Result<(Self, NewMemberInfo), MlsError> {
+        let psk_store = config.secret_store();
+
+        let pending = Self::begin_join(
+            welcome,
+            config,
+            signer,
+            #[cfg(feature = "psk")]
+            additional_psk,
+            &psk_store,
+        )
+        .await?;
+
+        Self::finish_join(pending, tree_data).await
+    }
+
+    /// Decrypt `welcome` up to the point where the ratchet tree is required.
+    ///
+    /// External PSKs required by `welcome` are resolved from `psk_store`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn begin_join<PS: PreSharedKeyStorage>(
+        welcome: Validated<&MlsMessage>,
+        config: C,
+        signer: SignatureSecretKey,
+        #[cfg(feature = "psk")] additional_psk: Option<PskSecretInput>,
+        psk_store: &PS,
+    ) -> Result<PendingJoin<C>, MlsError> {
         let protocol_version = welcome.version;
 
         if !config.version_supported(protocol_version) {
@@ -419,30 +696,18 @@ where
             return Err(MlsError::UnexpectedMessageType);
         };
 
+        config
+            .cipher_suite_policy()
+            .check(protocol_version, welcome.cipher_suite)?;
+
         let cipher_suite_provider =
             cipher_suite_provider(config.crypto_provider(), welcome.cipher_suite)?;
 
-        let (encrypted_group_secrets, key_package_generation) =
-            find_key_package_generation(&config.key_package_repo(), &welcome.secrets).await?;
-
-        let key_package_version = key_package_generation.key_package.version;
-
-        if key_package_version != protocol_version {
-            return Err(MlsError::ProtocolVersionMismatch);
-        }
-
-        // Decrypt the encrypted_group_secrets using HPKE with the algorithms indicated by the
-        // cipher suite and the HPKE private key corresponding to the GroupSecrets. If a
-        // PreSharedKeyID is part of the GroupSecrets and the client is not in possession of
-        // the corresponding PSK, return an error
-        let group_secrets = GroupSecrets::decrypt(
-            &cipher_suite_provider,
-            &key_package_generation.init_secret_key,
-            &key_package_generation.key_package.hpke_init_key,
-            &welcome.encrypted_group_info,
-            &encrypted_group_secrets.encrypted_group_secrets,
-        )
-        .await?;
+        // If a PreSharedKeyID is part of the GroupSecrets and the client is not in possession
+        // of the corresponding PSK, return an error
+        let (group_secrets, key_package_generation) =
+            Self::decrypt_group_secrets(welcome, protocol_version, &config, &cipher_suite_provider)
+                .await?;
 
         #[cfg(feature = "psk")]
         let psk_secret = if let Some(psk) = additional_psk {
@@ -465,19 +730,22 @@ where
             PskResolver::<
                 <C as ClientConfig>::GroupStateStorage,
                 <C as ClientConfig>::KeyPackageRepository,
-                <C as ClientConfig>::PskStore,
+                PS,
             > {
                 group_context: None,
                 current_epoch: None,
                 prior_epochs: None,
-                psk_store: &config.secret_store(),
+                psk_store,
             }
             .resolve_to_secret(&group_secrets.psks, &cipher_suite_provider)
             .await?
         };
 
         #[cfg(not(feature = "psk"))]
-        let psk_secret = PskSecret::new(&cipher_suite_provider);
+        let psk_secret = {
+            let _ = psk_store;
+            PskSecret::new(&cipher_suite_provider)
+        };
 
         // From the joiner_secret in the decrypted GroupSecrets object and the PSKs specified in
         // the GroupSecrets, derive the welcome_secret and using that the welcome_key and
@@ -496,6 +764,42 @@ where
 
         let group_info = GroupInfo::mls_decode(&mut &**decrypted_group_info)?;
 
+        config
+            .group_id_policy()
+            .validate(&group_info.group_context.group_id)?;
+
+        config
+            .group_policy_support()
+            .check(&group_info.group_context.extensions)?;
+
+        Ok(PendingJoin {
+            config,
+            signer,
+            protocol_version,
+            cipher_suite_provider,
+            group_secrets,
+            key_package_generation,
+            psk_secret,
+            group_info,
+        })
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn finish_join(
+        pending: PendingJoin<C>,
+        tree_data: Option<ExportedTree<'_>>,
+    ) -> Result<(Self, NewMemberInfo), MlsError> {
+        let PendingJoin {
+            config,
+            signer,
+            protocol_version,
+            cipher_suite_provider,
+            group_secrets,
+            key_package_generation,
+            psk_secret,
+            group_info,
+        } = pending;
+
         let public_tree = validate_group_info_joiner(
             protocol_version,
             &group_info,
@@ -556,17 +860,34 @@ where
             return Err(MlsError::InvalidConfirmationTag);
         }
 
-        Self::join_with(
+        // With a grace period, the key package is marked consumed once the group is joined instead
+        // of being deleted when the group is written to storage.
+        let consumed_at = config
+            .consumed_key_package_grace_period()
+            .and(epoch_age::current_time());
+
+        let (group, new_member_info) = Self::join_with(
             config,
             group_info,
             public_tree,
             key_schedule_result.key_schedule,
             key_schedule_result.epoch_secrets,
             private_tree,
-            Some(used_key_package_ref),
+            consumed_at.is_none().then(|| used_key_package_ref.clone()),
             signer,
         )
-        .await
+        .await?;
+
+        if let Some(consumed_at) = consumed_at {
+            group
+                .config
+                .key_package_repo()
+                .mark_consumed(&used_key_package_ref, consumed_at)
+                .await
+                .map_err(|e| MlsError::KeyPackageRepoError(e.into_any_error()))?;
+        }
+
+        Ok((group, new_member_info))
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -581,6 +902,18 @@ where
         used_key_package_ref: Option<KeyPackageRef>,
         signer: SignatureSecretKey,
     ) -> Result<(Self, NewMemberInfo), MlsError> {
+        let known_extensions = config.supported_extensions();
+
+        if let Some(ext_type) = group_info.extensions.unknown_critical(&known_extensions) {
+            return Err(MlsError::UnknownCriticalExtension(ext_type));
+        }
+
+        unknown_extensions::check_critical_extensions(
+            &known_extensions,
+            &group_info.group_context.extensions,
+            &public_tree,
+        )?;
+
         let cs = group_info.group_context.cipher_suite;
 
         let cs = config
@@ -605,7 +938,7 @@ where
             used_key_package_ref,
         )?;
 
-        let group = Group {
+        let mut group = Group {
             config,
             state: GroupState::new(
                 group_info.group_context,
@@ -618,7 +951,31 @@ where
             #[cfg(feature = "by_ref_proposal")]
             pending_updates: Default::default(),
             pending_commit: None,
-            #[cfg(test)]
+            pending_leave: false,
+            requires_self_update: false,
+            epoch_started_at: epoch_age::current_time(),
+            epoch_committer: group_info.signer,
+            removed: false,
+            #[cfg(feature = "private_message")]
+            sender_data_keys: Default::default(),
+            #[cfg(feature = "private_message")]
+            length_histogram: Default::default(),
+            #[cfg(feature = "private_message")]
+            batch_sender_data: None,
+            watermark_batch: None,
+            previous_epoch_sends: 0,
+            #[cfg(feature = "prior_epoch")]
+            previous_epoch_sendable: false,
+            epoch_view: None,
+            transcript_chain: Vec::new(),
+            member_activity: Vec::new(),
+            roster_diffs: Vec::new(),
+            archived: false,
+            #[cfg(feature = "event_sourcing")]
+            epoch_deltas: Vec::new(),
+            #[cfg(feature = "event_sourcing")]
+            deltas_since_compaction: 0,
+            #[cfg(any(test, feature = "adversarial"))]
             commit_modifiers: Default::default(),
             epoch_secrets,
             state_repo,
@@ -628,6 +985,9 @@ where
             signer,
         };
 
+        group.record_transcript_chain_entry(group_info.signer);
+        group.reset_member_activity();
+
         Ok((group, NewMemberInfo::new(group_info.extensions)))
     }
 
@@ -676,6 +1036,34 @@ where
             .map(|ln| member_from_leaf_node(ln, leaf_index))
     }
 
+    /// Members that don't support the group context `extensions`, e.g. that
+    /// don't advertise an extension type or a capability required by them.
+    ///
+    /// A commit setting `extensions` would be invalid, or would drop the
+    /// proposal if received by reference, as long as this list is not empty.
+    /// Applications can check it before proposing or committing new group
+    /// context extensions and prompt the listed members to update their
+    /// capabilities.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn unsupported_members_for(&self, extensions: &ExtensionList) -> Vec<Member> {
+        let identity_provider = self.config.identity_provider();
+
+        let leaf_validator = LeafNodeValidator::new(
+            &self.cipher_suite_provider,
+            &identity_provider,
+            Some(extensions),
+        );
+
+        self.current_epoch_tree()
+            .non_empty_leaves()
+            .filter(|(_, leaf)| {
+                validate_new_capabilities(&leaf_validator, leaf).is_err()
+                    || unsupported_group_extension(leaf, extensions).is_some()
+            })
+            .map(|(index, leaf)| member_from_leaf_node(leaf, index))
+            .collect()
+    }
+
     #[cfg(feature = "by_ref_proposal")]
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn proposal_message(
@@ -683,6 +1071,8 @@ where
         proposal: Proposal,
         authenticated_data: Vec<u8>,
     ) -> Result<MlsMessage, MlsError> {
+        self.check_not_archived()?;
+
         let sender = Sender::Member(*self.private_tree.self_index);
 
         let auth_content = AuthenticatedContent::new_signed(
@@ -692,7 +1082,7 @@ where
             Content::Proposal(alloc::boxed::Box::new(proposal.clone())),
             &self.signer,
             #[cfg(feature = "private_message")]
-            self.encryption_options()?.control_wire_format(sender),
+            self.control_wire_format(sender)?,
             #[cfg(not(feature = "private_message"))]
             WireFormat::PublicMessage,
             authenticated_data,
@@ -836,10 +1226,17 @@ where
     }
 
     fn add_proposal(&self, key_package: MlsMessage) -> Result<Proposal, MlsError> {
+        let key_package = Validated::new(key_package)?
+            .into_key_package()
+            .ok_or(MlsError::UnexpectedMessageType)?
+            .into_inner();
+
+        self.config
+            .cipher_suite_policy()
+            .check(key_package.version, key_package.cipher_suite)?;
+
         Ok(Proposal::Add(alloc::boxed::Box::new(AddProposal {
-            key_package: key_package
-                .into_key_package()
-                .ok_or(MlsError::UnexpectedMessageType)?,
+            key_package,
         })))
     }
 
@@ -857,7 +1254,32 @@ where
         &mut self,
         authenticated_data: Vec<u8>,
     ) -> Result<MlsMessage, MlsError> {
-        let proposal = self.update_proposal(None, None).await?;
+        let proposal = self.update_proposal(None, None, None).await?;
+        self.proposal_message(proposal, authenticated_data).await
+    }
+
+    /// Create a proposal message that updates your own public keys as well
+    /// as your leaf node extensions, replacing the
+    /// [configured](crate::client_builder::ClientBuilder::leaf_node_extensions)
+    /// ones.
+    ///
+    /// Leaf node extensions can carry per-device metadata, e.g. the platform
+    /// or the hash of a push token. Their types are automatically advertised
+    /// in the capabilities of the leaf node.
+    ///
+    /// `authenticated_data` will be sent unencrypted along with the contents
+    /// of the proposal message.
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn propose_update_with_extensions(
+        &mut self,
+        leaf_node_extensions: ExtensionList,
+        authenticated_data: Vec<u8>,
+    ) -> Result<MlsMessage, MlsError> {
+        let proposal = self
+            .update_proposal(None, None, Some(leaf_node_extensions))
+            .await?;
+
         self.proposal_message(proposal, authenticated_data).await
     }
 
@@ -887,18 +1309,28 @@ where
         authenticated_data: Vec<u8>,
     ) -> Result<MlsMessage, MlsError> {
         let proposal = self
-            .update_proposal(Some(signer), Some(signing_identity))
+            .update_proposal(Some(signer), Some(signing_identity), None)
             .await?;
 
         self.proposal_message(proposal, authenticated_data).await
     }
 
+    /// Properties of a new leaf node of the local member, with the given
+    /// leaf node extensions or the configured ones.
+    fn leaf_properties(&self, leaf_node_extensions: Option<ExtensionList>) -> ConfigProperties {
+        match leaf_node_extensions {
+            Some(extensions) => self.config.leaf_properties_with_extensions(extensions),
+            None => self.config.leaf_properties(),
+        }
+    }
+
     #[cfg(feature = "by_ref_proposal")]
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn update_proposal(
         &mut self,
         signer: Option<SignatureSecretKey>,
         signing_identity: Option<SigningIdentity>,
+        leaf_node_extensions: Option<ExtensionList>,
     ) -> Result<Proposal, MlsError> {
         // Grab a copy of the current node and update it to have new key material
         let mut new_leaf_node = self.current_user_leaf_node()?.clone();
@@ -908,7 +1340,7 @@ where
                 &self.cipher_suite_provider,
                 self.group_id(),
                 self.current_member_index(),
-                self.config.leaf_properties(),
+                self.leaf_properties(leaf_node_extensions),
                 signing_identity,
                 signer.as_ref().unwrap_or(&self.signer),
             )
@@ -944,6 +1376,52 @@ where
         self.proposal_message(proposal, authenticated_data).await
     }
 
+    /// Create a proposal message that removes the local member from the
+    /// group, and mark the group as pending leave.
+    ///
+    /// A member can not commit its own removal. Instead, the proposal must be
+    /// committed by another member. Once the resulting commit is processed
+    /// with [`Group::process_incoming_message_validated`], the group state is
+    /// deleted and [`CommitMessageDescription::self_removed`] is set.
+    ///
+    /// `authenticated_data` will be sent unencrypted along with the contents
+    /// of the proposal message.
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn propose_self_remove(
+        &mut self,
+        authenticated_data: Vec<u8>,
+    ) -> Result<MlsMessage, MlsError> {
+        let proposal = self.remove_proposal(*self.private_tree.self_index)?;
+        let message = self.proposal_message(proposal, authenticated_data).await?;
+
+        self.pending_leave = true;
+
+        Ok(message)
+    }
+
+    /// Mark the group as pending leave, or clear the mark.
+    ///
+    /// While the group is pending leave, creating commits fails with
+    /// [`MlsError::CommitWhilePendingLeave`], since a commit can not remove
+    /// its own sender.
+    pub fn set_pending_leave(&mut self, pending_leave: bool) {
+        self.pending_leave = pending_leave;
+    }
+
+    /// Returns true if the group was marked as pending leave with
+    /// [`Group::set_pending_leave`] or [`Group::propose_self_remove`].
+    pub fn is_pending_leave(&self) -> bool {
+        self.pending_leave
+    }
+
+    /// Returns true if this member must commit an update of its own leaf
+    /// before sending application messages, see
+    /// [`Group::from_provisioned_snapshot`].
+    pub fn requires_self_update(&self) -> bool {
+        self.requires_self_update
+    }
+
     fn remove_proposal(&self, index: u32) -> Result<Proposal, MlsError> {
         let leaf_index = LeafIndex(index);
 
@@ -976,6 +1454,39 @@ where
         self.proposal_message(proposal, authenticated_data).await
     }
 
+    /// Create a commit that injects the external pre-shared key `new_psk` into
+    /// the key schedule, completing the rotation of a shared secret gating
+    /// the group.
+    ///
+    /// The key must already be installed in the
+    /// [`PreSharedKeyStorage`](mls_rs_core::psk::PreSharedKeyStorage) of this
+    /// client, otherwise [`MlsError::MissingRequiredPsk`] is returned. Other
+    /// members need the key when processing the commit. Once the commit is
+    /// processed, the key being replaced is no longer needed by this group
+    /// and can be revoked.
+    #[cfg(feature = "psk")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn commit_external_psk_rotation(
+        &mut self,
+        new_psk: ExternalPskId,
+    ) -> Result<CommitOutput, MlsError> {
+        let found = self
+            .config
+            .secret_store()
+            .contains(&new_psk)
+            .await
+            .map_err(|e| MlsError::PskStoreError(e.into_any_error()))?;
+
+        if !found {
+            return Err(MlsError::MissingRequiredPsk);
+        }
+
+        self.commit_builder()
+            .add_external_psk(new_psk)?
+            .build()
+            .await
+    }
+
     #[cfg(feature = "psk")]
     fn psk_proposal(&self, key_id: JustPreSharedKeyID) -> Result<Proposal, MlsError> {
         Ok(Proposal::Psk(PreSharedKeyProposal {
@@ -1039,11 +1550,14 @@ where
         cipher_suite: CipherSuite,
         extensions: ExtensionList,
     ) -> Result<Proposal, MlsError> {
-        let group_id = group_id.map(Ok).unwrap_or_else(|| {
-            self.cipher_suite_provider
-                .random_bytes_vec(self.cipher_suite_provider.kdf_extract_size())
-                .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
-        })?;
+        let group_id_policy = self.config.group_id_policy();
+
+        let group_id = match group_id {
+            Some(group_id) => group_id_policy.validate(&group_id).map(|_| group_id),
+            None => group_id_policy.generate(&self.cipher_suite_provider),
+        }?;
+
+        self.check_reinit(version, cipher_suite)?;
 
         Ok(Proposal::ReInit(ReInitProposal {
             group_id,
@@ -1077,7 +1591,9 @@ where
         self.proposal_message(proposal, authenticated_data).await
     }
 
-    fn group_context_extensions_proposal(&self, extensions: ExtensionList) -> Proposal {
+    fn group_context_extensions_proposal(&self, mut extensions: ExtensionList) -> Proposal {
+        // All members apply the extensions in the order of the proposal.
+        extensions.canonicalize();
         Proposal::GroupContextExtensions(extensions)
     }
 
@@ -1102,6 +1618,40 @@ where
         self.state.proposals.clear()
     }
 
+    /// Find a sent or received proposal cached for commit in the current epoch
+    /// by its reference.
+    ///
+    /// The reference of a proposal message can be computed with
+    /// [`ProposalRef::compute`].
+    #[cfg(feature = "by_ref_proposal")]
+    pub fn proposal_by_ref(&self, proposal_ref: &ProposalRef) -> Option<ProposalInfo<Proposal>> {
+        self.state
+            .proposals
+            .get(proposal_ref)
+            .map(|p| ProposalInfo {
+                proposal: p.proposal.clone(),
+                sender: p.sender,
+                source: ProposalSource::ByReference(proposal_ref.clone()),
+                received_epoch: Some(self.current_epoch()),
+            })
+    }
+
+    /// All sent and received proposals cached for commit in the current
+    /// epoch, in order of arrival.
+    ///
+    /// Applications can use this to review pending proposals before
+    /// committing them, e.g. with [`Group::commit_builder`].
+    #[cfg(feature = "by_ref_proposal")]
+    pub fn cached_proposals(&self) -> Vec<ProposalInfo<Proposal>> {
+        let epoch = self.current_epoch();
+
+        self.state
+            .proposals
+            .proposals()
+            .map(|p| p.with_received_epoch(epoch))
+            .collect()
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub(crate) async fn format_for_wire(
         &mut self,
@@ -1151,8 +1701,12 @@ where
         let padding_mode = self.encryption_options()?.padding_mode;
 
         let mut encryptor = CiphertextProcessor::new(self, self.cipher_suite_provider.clone());
+        let ciphertext = encryptor.seal(auth_content, padding_mode).await?;
+
+        self.persist_watermarks(WatermarkPersistence::AfterProcessing)
+            .await?;
 
-        encryptor.seal(auth_content, padding_mode).await
+        Ok(ciphertext)
     }
 
     /// Encrypt an application message using the current group state.
@@ -1166,39 +1720,262 @@ where
         message: &[u8],
         authenticated_data: Vec<u8>,
     ) -> Result<MlsMessage, MlsError> {
-        // A group member that has observed one or more proposals within an epoch MUST send a Commit message
-        // before sending application data
-        #[cfg(feature = "by_ref_proposal")]
-        if !self.state.proposals.is_empty() {
-            return Err(MlsError::CommitRequired);
-        }
+        let message = self.application_payload(message)?;
 
-        let auth_content = AuthenticatedContent::new_signed(
-            &self.cipher_suite_provider,
-            self.context(),
-            Sender::Member(*self.private_tree.self_index),
-            Content::Application(message.to_vec().into()),
-            &self.signer,
-            WireFormat::PrivateMessage,
-            authenticated_data,
-        )
-        .await?;
+        let auth_content = self
+            .application_content(message, authenticated_data)
+            .await?;
 
         self.format_for_wire(auth_content).await
     }
 
+    /// Encrypt an application message as with
+    /// [`Group::encrypt_application_message`] and write the encoded
+    /// [`MlsMessage`] to `out`, replacing its content.
+    ///
+    /// Only the buffer of the encoded message is reused. The message is
+    /// framed and encrypted with the same intermediate allocations as
+    /// [`Group::encrypt_application_message`], since the
+    /// [`CipherSuiteProvider`] seals into a new buffer, and is then encoded
+    /// into `out`.
     #[cfg(feature = "private_message")]
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    async fn decrypt_incoming_ciphertext(
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn encrypt_application_message_into(
         &mut self,
-        message: &PrivateMessage,
+        out: &mut Vec<u8>,
+        message: &[u8],
+        authenticated_data: &[u8],
+    ) -> Result<(), MlsError> {
+        let message = self
+            .encrypt_application_message(message, authenticated_data.to_vec())
+            .await?;
+
+        out.clear();
+        out.reserve(message.mls_encoded_len());
+        message.mls_encode(out)?;
+
+        Ok(())
+    }
+
+    /// Decrypt an encoded application message and write its content to
+    /// `out`, replacing its content. Returns the leaf index of the sender.
+    ///
+    /// This is the counterpart of
+    /// [`Group::encrypt_application_message_into`]. The message is decoded
+    /// and processed as with [`Group::process_incoming_message_validated`],
+    /// and the decrypted content is then copied to `out`, so only the buffer
+    /// of the content is reused. The authenticated data of the message is not
+    /// returned. Messages other than encrypted
+    /// application messages are rejected with
+    /// [`MlsError::UnexpectedMessageType`] without being processed and must
+    /// be passed to [`Group::process_incoming_message_validated`] instead.
+    #[cfg(feature = "private_message")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn decrypt_application_message_into(
+        &mut self,
+        message: &[u8],
+        out: &mut Vec<u8>,
+    ) -> Result<u32, MlsError> {
+        let message = MlsMessage::from_bytes(message)?;
+
+        let is_application = matches!(
+            &message.payload,
+            MlsMessagePayload::Cipher(ciphertext) if ciphertext.content_type == ContentType::Application
+        );
+
+        if !is_application {
+            return Err(MlsError::UnexpectedMessageType);
+        }
+
+        let ReceivedMessage::ApplicationMessage(description) = self
+            .process_incoming_message_validated(Validated::new(message)?)
+            .await?
+        else {
+            return Err(MlsError::UnexpectedMessageType);
+        };
+
+        out.clear();
+        out.extend_from_slice(description.data());
+
+        Ok(description.sender_index)
+    }
+
+    /// Encrypt an application message with no authenticated data, padding
+    /// the content with
+    /// [`PaddingMode::StepFunction`](crate::client_builder::PaddingMode::StepFunction)
+    /// regardless of the [`EncryptionOptions`] of the group.
+    ///
+    /// This only changes the padding and the authenticated data of the
+    /// message, it is not a separate anonymous sending mode. As for any
+    /// [`PrivateMessage`], the leaf index of the sender is only part of the
+    /// encrypted sender data, and the delivery service still sees the group
+    /// id, epoch and content type.
+    ///
+    /// The message is never compressed, even if compression is configured,
+    /// so that the length of the ciphertext only depends on the padding
+    /// bucket the length of `message` falls in, and not on its content.
+    #[cfg(feature = "private_message")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn encrypt_padded_application_message(
+        &mut self,
+        message: &[u8],
+    ) -> Result<MlsMessage, MlsError> {
+        let message = self.uncompressed_application_payload(message);
+
+        let auth_content = self.application_content(message, Vec::new()).await?;
+
+        let ciphertext = CiphertextProcessor::new(self, self.cipher_suite_provider.clone())
+            .seal(auth_content, PaddingMode::StepFunction)
+            .await?;
+
+        self.persist_watermarks(WatermarkPersistence::AfterProcessing)
+            .await?;
+
+        Ok(MlsMessage::new(
+            self.protocol_version(),
+            MlsMessagePayload::Cipher(ciphertext),
+        ))
+    }
+
+    /// Encrypt an application message using the application ratchet of
+    /// the given `lane`.
+    ///
+    /// Each lane has its own ratchet, derived from the secret tree, so that
+    /// messages sent at a high rate on one lane (e.g. media) do not consume
+    /// keys needed by messages sent on another lane (e.g. control). Lane `0`
+    /// is the default application ratchet used by
+    /// [`Group::encrypt_application_message`].
+    ///
+    /// The lane is not transmitted as part of the message. Receivers must
+    /// process the message with
+    /// [`Group::process_incoming_message_on_lane`] using the same `lane`,
+    /// agreed on outside of MLS.
+    ///
+    /// If the group was loaded from a state written without the
+    /// `application_lanes` feature after this member already sent messages in
+    /// the current epoch, lanes other than `0` fail with
+    /// [`MlsError::ApplicationLanesUnavailable`] until the next epoch.
+    #[cfg(feature = "application_lanes")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn encrypt_application_message_on_lane(
+        &mut self,
+        message: &[u8],
+        authenticated_data: Vec<u8>,
+        lane: u16,
+    ) -> Result<MlsMessage, MlsError> {
+        let message = self.application_payload(message)?;
+
+        let auth_content = self
+            .application_content(message, authenticated_data)
+            .await?;
+
+        let padding_mode = self.encryption_options()?.padding_mode;
+
+        let ciphertext = CiphertextProcessor::new(self, self.cipher_suite_provider.clone())
+            .with_application_lane(lane)
+            .seal(auth_content, padding_mode)
+            .await?;
+
+        self.persist_watermarks(WatermarkPersistence::AfterProcessing)
+            .await?;
+
+        Ok(MlsMessage::new(
+            self.protocol_version(),
+            MlsMessagePayload::Cipher(ciphertext),
+        ))
+    }
+
+    /// Content of an application message, compressed if compression is
+    /// configured.
+    #[cfg(feature = "private_message")]
+    fn application_payload(&self, message: &[u8]) -> Result<Vec<u8>, MlsError> {
+        #[cfg(feature = "compression")]
+        if let Some(compression) = self.config.compression() {
+            return compression.compress(message);
+        }
+
+        Ok(message.to_vec())
+    }
+
+    /// Content of an application message sent without compression, even if
+    /// compression is configured.
+    #[cfg(feature = "private_message")]
+    fn uncompressed_application_payload(&self, message: &[u8]) -> Vec<u8> {
+        #[cfg(feature = "compression")]
+        if self.config.compression().is_some() {
+            return CompressionSettings::uncompressed(message);
+        }
+
+        message.to_vec()
+    }
+
+    #[cfg(feature = "private_message")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn application_content(
+        &self,
+        message: Vec<u8>,
+        authenticated_data: Vec<u8>,
     ) -> Result<AuthenticatedContent, MlsError> {
+        if self.removed {
+            return Err(MlsError::GroupUsedAfterRemoval);
+        }
+
+        self.check_not_archived()?;
+
+        // A group member that has observed one or more proposals within an epoch MUST send a Commit message
+        // before sending application data
+        #[cfg(feature = "by_ref_proposal")]
+        if !self.state.proposals.is_empty() {
+            return Err(MlsError::CommitRequired);
+        }
+
+        if self.requires_self_update {
+            return Err(MlsError::SelfUpdateRequired);
+        }
+
+        if self.epoch_age_exceeded() {
+            return Err(MlsError::EpochMaxAgeExceeded);
+        }
+
+        AuthenticatedContent::new_signed(
+            &self.cipher_suite_provider,
+            self.context(),
+            Sender::Member(*self.private_tree.self_index),
+            Content::Application(message.into()),
+            &self.signer,
+            WireFormat::PrivateMessage,
+            authenticated_data,
+        )
+        .await
+    }
+
+    #[cfg(feature = "private_message")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn decrypt_incoming_ciphertext(
+        &mut self,
+        message: &PrivateMessage,
+        #[cfg(feature = "application_lanes")] lane: u16,
+    ) -> Result<(AuthenticatedContent, u32), MlsError> {
         let epoch_id = message.epoch;
 
-        let auth_content = if epoch_id == self.context().epoch {
-            let content = CiphertextProcessor::new(self, self.cipher_suite_provider.clone())
-                .open(message)
-                .await?;
+        let decrypted = if epoch_id == self.context().epoch {
+            let sender_data = self
+                .batch_sender_data
+                .take()
+                .filter(|(encrypted, _)| *encrypted == message.encrypted_sender_data)
+                .map(|(_, sender_data)| sender_data);
+
+            let mut processor = CiphertextProcessor::new(self, self.cipher_suite_provider.clone())
+                .with_sender_data(sender_data);
+
+            #[cfg(feature = "application_lanes")]
+            {
+                processor = processor.with_application_lane(lane);
+            }
+
+            let (content, generation) = processor.open(message).await?;
 
             verify_auth_content_signature(
                 &self.cipher_suite_provider,
@@ -1210,7 +1987,7 @@ where
             )
             .await?;
 
-            Ok::<_, MlsError>(content)
+            Ok::<_, MlsError>((content, generation))
         } else {
             #[cfg(feature = "prior_epoch")]
             {
@@ -1220,9 +1997,15 @@ where
                     .await?
                     .ok_or(MlsError::EpochNotFound)?;
 
-                let content = CiphertextProcessor::new(epoch, self.cipher_suite_provider.clone())
-                    .open(message)
-                    .await?;
+                let mut processor =
+                    CiphertextProcessor::new(epoch, self.cipher_suite_provider.clone());
+
+                #[cfg(feature = "application_lanes")]
+                {
+                    processor = processor.with_application_lane(lane);
+                }
+
+                let (content, generation) = processor.open(message).await?;
 
                 verify_auth_content_signature(
                     &self.cipher_suite_provider,
@@ -1234,14 +2017,17 @@ where
                 )
                 .await?;
 
-                Ok(content)
+                Ok((content, generation))
             }
 
             #[cfg(not(feature = "prior_epoch"))]
             Err(MlsError::EpochNotFound)
         }?;
 
-        Ok(auth_content)
+        self.persist_watermarks(WatermarkPersistence::WriteAhead)
+            .await?;
+
+        Ok(decrypted)
     }
 
     /// Apply a pending commit that was created by [`Group::commit`] or
@@ -1265,12 +2051,31 @@ where
     /// Clear the currently pending commit.
     ///
     /// This function will automatically be called in the event that a
-    /// commit message is processed using [`Group::process_incoming_message`]
-    /// before [`Group::apply_pending_commit`] is called.
+    /// commit message is processed using
+    /// [`Group::process_incoming_message_validated`] before
+    /// [`Group::apply_pending_commit`] is called.
     pub fn clear_pending_commit(&mut self) {
         self.pending_commit = None
     }
 
+    /// Process an inbound message for this group.
+    ///
+    /// Equivalent to [`Group::process_incoming_message_validated`] after
+    /// validating `message` with [`Validated::new`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    // The FFI bindings can't pass `Validated` values and keep using this.
+    #[cfg_attr(
+        not(all(feature = "ffi", not(test))),
+        deprecated(note = "use `Group::process_incoming_message_validated` instead")
+    )]
+    pub async fn process_incoming_message(
+        &mut self,
+        message: MlsMessage,
+    ) -> Result<ReceivedMessage, MlsError> {
+        self.process_incoming_message_validated(Validated::new(message)?)
+            .await
+    }
+
     /// Process an inbound message for this group.
     ///
     /// # Warning
@@ -1280,10 +2085,25 @@ where
     /// [`GroupStateStorage`](crate::GroupStateStorage)
     /// in use by this group until [`Group::write_to_storage`] is called.
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
     #[inline(never)]
-    pub async fn process_incoming_message(
+    pub async fn process_incoming_message_validated(
         &mut self,
-        message: MlsMessage,
+        message: Validated<MlsMessage>,
+    ) -> Result<ReceivedMessage, MlsError> {
+        self.process_message(
+            message,
+            #[cfg(feature = "application_lanes")]
+            0,
+        )
+        .await
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn process_message(
+        &mut self,
+        message: Validated<MlsMessage>,
+        #[cfg(feature = "application_lanes")] lane: u16,
     ) -> Result<ReceivedMessage, MlsError> {
         if let Some(pending) = &self.pending_commit {
             let message_hash = CommitHash::compute(&self.cipher_suite_provider, &message).await?;
@@ -1291,17 +2111,101 @@ where
             if message_hash == pending.commit_message_hash {
                 let message_description = self.apply_pending_commit().await?;
 
+                #[cfg(feature = "private_message")]
+                self.persist_watermarks(WatermarkPersistence::AfterProcessing)
+                    .await?;
+
                 return Ok(ReceivedMessage::Commit(message_description));
             }
         }
 
-        MessageProcessor::process_incoming_message(
+        let received = MessageProcessor::process_incoming_message(
             self,
             message,
             #[cfg(feature = "by_ref_proposal")]
             true,
+            #[cfg(feature = "application_lanes")]
+            lane,
         )
-        .await
+        .await?;
+
+        self.after_processing(&received).await?;
+
+        Ok(received)
+    }
+
+    /// Steps taken after processing an incoming message, shared by all
+    /// functions processing messages.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn after_processing(&mut self, received: &ReceivedMessage) -> Result<(), MlsError> {
+        if matches!(received, ReceivedMessage::Commit(description) if description.self_removed) {
+            if self.config.archive_removed_groups() {
+                self.archive();
+            } else {
+                self.wipe_state().await?;
+            }
+        }
+
+        #[cfg(feature = "private_message")]
+        self.persist_watermarks(WatermarkPersistence::AfterProcessing)
+            .await?;
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn wipe_state(&mut self) -> Result<(), MlsError> {
+        #[cfg(feature = "prior_epoch")]
+        self.state_repo.delete_from_storage().await?;
+
+        #[cfg(not(feature = "prior_epoch"))]
+        self.state_repo
+            .delete_from_storage(&self.state.context.group_id)
+            .await?;
+
+        #[cfg(feature = "by_ref_proposal")]
+        {
+            self.pending_updates = Default::default();
+            self.state.proposals.clear();
+        }
+
+        self.private_tree.secret_keys.clear();
+        self.pending_commit = None;
+        self.pending_leave = false;
+        self.removed = true;
+
+        Ok(())
+    }
+
+    /// Process an inbound message for this group, decrypting application
+    /// messages with the application ratchet of the given `lane`.
+    ///
+    /// The message is validated and processed as with
+    /// [`Group::process_incoming_message_validated`], which uses lane `0`. The lane
+    /// only selects the ratchet used to decrypt application messages and is
+    /// ignored for all other messages.
+    ///
+    /// The lane is not part of the message, so sender and receivers must
+    /// agree on it outside of MLS, for example by sending each lane over its
+    /// own transport channel or by fixing the lane of each kind of
+    /// application data. A message processed on another lane than the one
+    /// it was encrypted on fails to decrypt. See
+    /// [`Group::encrypt_application_message_on_lane`].
+    ///
+    /// # Warning
+    ///
+    /// Changes to the group's state as a result of processing `message` will
+    /// not be persisted by the
+    /// [`GroupStateStorage`](crate::GroupStateStorage)
+    /// in use by this group until [`Group::write_to_storage`] is called.
+    #[cfg(feature = "application_lanes")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn process_incoming_message_on_lane(
+        &mut self,
+        message: MlsMessage,
+        lane: u16,
+    ) -> Result<ReceivedMessage, MlsError> {
+        self.process_message(Validated::new(message)?, lane).await
     }
 
     /// Process an inbound message for this group, providing additional context
@@ -1326,14 +2230,150 @@ where
         message: MlsMessage,
         time: MlsTime,
     ) -> Result<ReceivedMessage, MlsError> {
-        MessageProcessor::process_incoming_message_with_time(
+        let received = MessageProcessor::process_incoming_message_with_time(
             self,
             message,
             #[cfg(feature = "by_ref_proposal")]
             true,
             Some(time),
         )
-        .await
+        .await?;
+
+        self.after_processing(&received).await?;
+
+        Ok(received)
+    }
+
+    /// Process a batch of inbound messages for this group, such as the
+    /// backlog received by a client after being offline.
+    ///
+    /// Messages are processed by increasing epoch rather than in the order
+    /// given. Within an epoch, commits are processed after all other
+    /// messages, and application messages are ordered by sender and
+    /// generation so that each sender ratchet is advanced once, without
+    /// retaining keys for messages received out of order. The sender data of
+    /// application messages of the current epoch is decrypted once, to order
+    /// them, and reused to process them. The result of processing each
+    /// message is returned at the index of that message in `messages`.
+    ///
+    /// With [`WatermarkPersistence::AfterProcessing`], the state is written
+    /// once after all messages are processed instead of after each message.
+    /// If this write fails, the result of the first message processed
+    /// successfully is replaced by the error, and the results of the other
+    /// messages processed successfully by [`MlsError::StateNotPersisted`].
+    ///
+    /// # Warning
+    ///
+    /// Changes to the group's state as a result of processing `messages` will
+    /// not be persisted by the
+    /// [`GroupStateStorage`](crate::GroupStateStorage)
+    /// in use by this group until [`Group::write_to_storage`] is called.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub async fn process_incoming_messages(
+        &mut self,
+        messages: Vec<MlsMessage>,
+    ) -> Vec<Result<ReceivedMessage, MlsError>> {
+        let mut results = messages.iter().map(|_| None).collect::<Vec<_>>();
+
+        // Sender data decrypted to order application messages, reused to
+        // process them.
+        #[cfg(feature = "private_message")]
+        let mut sender_data = messages.iter().map(|_| None).collect::<Vec<_>>();
+
+        self.begin_watermark_batch();
+
+        let mut messages = messages.into_iter().enumerate().collect::<Vec<_>>();
+        messages.sort_by_key(|(_, message)| message.epoch());
+
+        let mut messages = messages.into_iter().peekable();
+
+        while let Some((_, message)) = messages.peek() {
+            let epoch = message.epoch();
+            let mut batch = Vec::new();
+
+            while let Some((i, message)) = messages.next_if(|(_, m)| m.epoch() == epoch) {
+                // Sender data can only be decrypted once the commits of previous
+                // epochs are processed.
+                #[cfg(feature = "private_message")]
+                {
+                    sender_data[i] = self.batch_sender_data(&message).await;
+                }
+
+                let key = Self::batch_order_key(
+                    &message,
+                    #[cfg(feature = "private_message")]
+                    sender_data[i].as_ref().map(|(_, sender_data)| sender_data),
+                );
+
+                batch.push((key, i, message));
+            }
+
+            batch.sort_by_key(|(key, ..)| *key);
+
+            for (_, i, message) in batch {
+                #[cfg(feature = "private_message")]
+                {
+                    self.batch_sender_data = sender_data[i].take();
+                }
+
+                let result = match Validated::new(message) {
+                    Ok(message) => self.process_incoming_message_validated(message).await,
+                    Err(e) => Err(e),
+                };
+
+                results[i] = Some(result);
+
+                #[cfg(feature = "private_message")]
+                {
+                    self.batch_sender_data = None;
+                }
+            }
+        }
+
+        self.end_watermark_batch(&mut results).await;
+
+        results.into_iter().flatten().collect()
+    }
+
+    fn batch_order_key(
+        message: &MlsMessage,
+        #[cfg(feature = "private_message")] sender_data: Option<&SenderData>,
+    ) -> (bool, Option<(LeafIndex, u32)>) {
+        match &message.payload {
+            MlsMessagePayload::Plain(plaintext) => (
+                plaintext.content.content_type() == ContentType::Commit,
+                None,
+            ),
+            #[cfg(feature = "private_message")]
+            MlsMessagePayload::Cipher(ciphertext) => (
+                ciphertext.content_type == ContentType::Commit,
+                sender_data.map(|sender_data| (sender_data.sender, sender_data.generation)),
+            ),
+            _ => (false, None),
+        }
+    }
+
+    /// Decrypted sender data of `message` if it is an application message of
+    /// the current epoch, together with its encrypted sender data.
+    #[cfg(feature = "private_message")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn batch_sender_data(&mut self, message: &MlsMessage) -> Option<(Vec<u8>, SenderData)> {
+        let MlsMessagePayload::Cipher(ciphertext) = &message.payload else {
+            return None;
+        };
+
+        if ciphertext.content_type != ContentType::Application
+            || ciphertext.epoch != self.context().epoch
+        {
+            return None;
+        }
+
+        CiphertextProcessor::new(self, self.cipher_suite_provider.clone())
+            .open_sender_data(ciphertext)
+            .await
+            .ok()
+            .map(|sender_data| (ciphertext.encrypted_sender_data.clone(), sender_data))
     }
 
     /// Find a group member by
@@ -1429,13 +2469,50 @@ where
         ))
     }
 
-    /// Get the current group context summarizing various information about the group.
-    #[inline(always)]
-    pub fn context(&self) -> &GroupContext {
-        &self.group_state().context
-    }
+    /// Re-sign a group info message of the current epoch, signed by any
+    /// member, with the signing key of the local member.
+    ///
+    /// `group_info` is first verified against the current group state, as
+    /// when calling [`process_incoming_message`](Self::process_incoming_message).
+    /// Its extensions are kept, so a group info allowing external commits
+    /// still does. This allows a designated member to refresh the group info
+    /// provided to external joiners after each commit, regardless of which
+    /// member committed. Joiners verify the signature of a group info using the
+    /// signing key of the member at its [sender](GroupInfo::sender) index in
+    /// the ratchet tree, so the result is valid until the next commit.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn resign_group_info(&self, group_info: &MlsMessage) -> Result<MlsMessage, MlsError> {
+        let MlsMessagePayload::GroupInfo(info) = &group_info.payload else {
+            return Err(MlsError::UnexpectedMessageType);
+        };
 
-    /// Get the
+        validate_group_info_member(
+            &self.state,
+            group_info.version,
+            info,
+            &self.cipher_suite_provider,
+        )
+        .await?;
+
+        let mut info = info.clone();
+        info.signer = self.private_tree.self_index;
+
+        info.sign(&self.cipher_suite_provider, &self.signer, &())
+            .await?;
+
+        Ok(MlsMessage::new(
+            group_info.version,
+            MlsMessagePayload::GroupInfo(info),
+        ))
+    }
+
+    /// Get the current group context summarizing various information about the group.
+    #[inline(always)]
+    pub fn context(&self) -> &GroupContext {
+        &self.group_state().context
+    }
+
+    /// Get the
     /// [epoch_authenticator](https://messaginglayersecurity.rocks/mls-protocol/draft-ietf-mls-protocol.html#name-key-schedule)
     /// of the current epoch.
     pub fn epoch_authenticator(&self) -> Result<Secret, MlsError> {
@@ -1478,6 +2555,20 @@ where
         self.group_state().public_tree.roster()
     }
 
+    /// Message describing `error` to users, using the
+    /// [`ErrorRenderer`](crate::error::ErrorRenderer) the client of this
+    /// group was configured with.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn render_error(&self, error: &MlsError) -> String {
+        error_render::render_error(self.config.error_renderer(), error)
+    }
+
+    /// Message describing errors with [`code`](MlsError::code) to users,
+    /// see [`Group::render_error`].
+    pub fn error_message(&self, code: &str) -> String {
+        error_render::error_message(self.config.error_renderer(), code)
+    }
+
     /// Determines equality of two different groups internal states.
     /// Useful for testing.
     ///
@@ -1516,6 +2607,37 @@ where
         }
     }
 
+    /// Policy on which incoming and outgoing messages must be encrypted, as
+    /// configured with
+    /// [`ClientBuilder::encryption_policy`](crate::client_builder::ClientBuilder::encryption_policy).
+    ///
+    /// Proposals and commits may additionally be encrypted when sent if
+    /// requested by [`MlsRules::encryption_options`].
+    #[cfg(feature = "private_message")]
+    pub fn encryption_policy(&self) -> EncryptionPolicy {
+        EncryptionPolicy {
+            encrypt_application: true,
+            ..self.config.encryption_policy()
+        }
+    }
+
+    /// Memory held by the secret tree of the current epoch, used to derive
+    /// the keys of private messages.
+    #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+    pub fn secret_tree_memory_usage(&self) -> SecretTreeMemoryUsage {
+        self.epoch_secrets.secret_tree.memory_usage()
+    }
+
+    #[cfg(feature = "private_message")]
+    pub(crate) fn control_wire_format(&self, sender: Sender) -> Result<WireFormat, MlsError> {
+        let required = self.config.encryption_policy().encrypt_handshake;
+
+        match sender {
+            Sender::Member(_) if required => Ok(WireFormat::PrivateMessage),
+            _ => Ok(self.encryption_options()?.control_wire_format(sender)),
+        }
+    }
+
     #[cfg(feature = "private_message")]
     pub(crate) fn encryption_options(&self) -> Result<EncryptionOptions, MlsError> {
         self.config
@@ -1557,6 +2679,7 @@ where
                 crate::tree_kem::node::NodeIndex::from(sender),
                 KeyType::Application,
                 generation,
+                self.config.max_generation_jump(),
             )
             .await
     }
@@ -1582,6 +2705,23 @@ where
     fn epoch_secrets(&self) -> &EpochSecrets {
         &self.epoch_secrets
     }
+
+    #[cfg(feature = "out_of_order")]
+    fn max_retained_message_keys(&self) -> Option<usize> {
+        self.config.max_retained_message_keys()
+    }
+
+    fn max_generation_jump(&self) -> u32 {
+        self.config.max_generation_jump()
+    }
+
+    fn sender_data_key_cache(&mut self) -> Option<&mut SenderDataKeyCache> {
+        Some(&mut self.sender_data_keys)
+    }
+
+    fn length_histogram(&mut self) -> Option<&mut LengthHistogram> {
+        Some(&mut self.length_histogram)
+    }
 }
 
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -1604,10 +2744,15 @@ where
     async fn process_ciphertext(
         &mut self,
         cipher_text: &PrivateMessage,
+        #[cfg(feature = "application_lanes")] lane: u16,
     ) -> Result<EventOrContent<Self::OutputType>, MlsError> {
-        self.decrypt_incoming_ciphertext(cipher_text)
-            .await
-            .map(EventOrContent::Content)
+        self.decrypt_incoming_ciphertext(
+            cipher_text,
+            #[cfg(feature = "application_lanes")]
+            lane,
+        )
+        .await
+        .map(|(content, generation)| EventOrContent::Content(content, Some(generation)))
     }
 
     async fn verify_plaintext_authentication(
@@ -1623,7 +2768,7 @@ where
         )
         .await?;
 
-        Ok(EventOrContent::Content(auth_content))
+        Ok(EventOrContent::Content(auth_content, None))
     }
 
     async fn apply_update_path(
@@ -1751,6 +2896,34 @@ where
             return Err(MlsError::InvalidConfirmationTag);
         }
 
+        if self.requires_self_update {
+            let self_index = self.private_tree.self_index;
+
+            let old_leaf = self.state.public_tree.get_leaf_node(self_index).ok();
+            let new_leaf = provisional_state.public_tree.get_leaf_node(self_index).ok();
+
+            self.requires_self_update =
+                old_leaf.map(|l| &l.public_key) == new_leaf.map(|l| &l.public_key);
+        }
+
+        self.epoch_started_at = epoch_age::current_time();
+
+        #[cfg(feature = "prior_epoch")]
+        {
+            let proposals = &provisional_state.applied_proposals;
+
+            #[cfg(feature = "by_ref_proposal")]
+            let updated = !proposals.updates.is_empty();
+
+            #[cfg(not(feature = "by_ref_proposal"))]
+            let updated = false;
+
+            self.previous_epoch_sendable = proposals.removals.is_empty() && !updated;
+        }
+
+        self.update_member_activity(&provisional_state);
+        self.record_roster_diff(&provisional_state);
+
         #[cfg(feature = "prior_epoch")]
         let signature_public_keys = self
             .state
@@ -1826,6 +2999,81 @@ where
         None
     }
 
+    #[cfg(feature = "private_message")]
+    fn handshake_encryption_required(&self) -> bool {
+        self.config.encryption_policy().encrypt_handshake
+    }
+
+    #[cfg(feature = "compression")]
+    fn compression(&self) -> Option<CompressionSettings> {
+        self.config.compression()
+    }
+
+    fn tree_validator(&self) -> Option<SharedTreeValidator> {
+        self.config.tree_validator()
+    }
+
+    fn commit_applied(&mut self, committer: LeafIndex) {
+        self.epoch_committer = committer;
+        self.record_transcript_chain_entry(committer)
+    }
+
+    fn member_active(&mut self, member: LeafIndex) {
+        self.record_member_activity(member)
+    }
+
+    #[cfg(feature = "event_sourcing")]
+    fn epoch_delta(&self, auth_content: &AuthenticatedContent) -> Option<EpochDelta> {
+        self.epoch_delta_for(auth_content)
+    }
+
+    #[cfg(feature = "event_sourcing")]
+    fn epoch_delta_applied(&mut self, epoch_delta: EpochDelta) {
+        self.record_epoch_delta(epoch_delta)
+    }
+
+    fn check_critical_extensions(
+        &self,
+        provisional_state: &ProvisionalState,
+    ) -> Result<(), MlsError> {
+        unknown_extensions::check_critical_extensions(
+            &self.config.supported_extensions(),
+            &provisional_state.group_context.extensions,
+            &provisional_state.public_tree,
+        )
+    }
+
+    fn check_handshake_allowed(&self) -> Result<(), MlsError> {
+        if self.archived {
+            return Err(MlsError::GroupArchived);
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    fn duplicate_proposal_policy(&self) -> Option<DuplicateProposalPolicy> {
+        self.config.duplicate_proposal_policy()
+    }
+
+    fn init_key_reuse_policy(&self) -> InitKeyReusePolicy {
+        self.config.init_key_reuse_policy()
+    }
+
+    fn check_reinit(
+        &self,
+        new_version: ProtocolVersion,
+        new_cipher_suite: CipherSuite,
+    ) -> Result<(), MlsError> {
+        self.config
+            .cipher_suite_policy()
+            .check(new_version, new_cipher_suite)?;
+
+        self.config
+            .reinit_cipher_suite_policy()
+            .check(self.cipher_suite(), new_cipher_suite)
+    }
+
     fn cipher_suite_provider(&self) -> &Self::CipherSuiteProvider {
         &self.cipher_suite_provider
     }
@@ -1852,7 +3100,7 @@ mod tests {
             test_utils::{get_test_signing_identity, BasicWithCustomProvider},
         },
         key_package::test_utils::test_key_package_message,
-        mls_rules::CommitOptions,
+        mls_rules::{CommitOptions, CommitPathPolicy},
         tree_kem::{
             leaf_node::{test_utils::get_test_capabilities, LeafNodeSource},
             UpdatePathNode,
@@ -1867,11 +3115,12 @@ mod tests {
 
     use crate::{extension::RequiredCapabilitiesExt, key_package::test_utils::test_key_package};
 
-    #[cfg(all(feature = "by_ref_proposal", feature = "custom_proposal"))]
     use super::test_utils::test_group_custom_config;
 
     #[cfg(feature = "psk")]
-    use crate::{client::Client, psk::PreSharedKey};
+    use crate::{
+        client::Client, psk::PreSharedKey, storage_provider::in_memory::InMemoryPreSharedKeyStorage,
+    };
 
     #[cfg(any(feature = "by_ref_proposal", feature = "private_message"))]
     use crate::group::test_utils::random_bytes;
@@ -1893,6 +3142,7 @@ mod tests {
     use assert_matches::assert_matches;
 
     use mls_rs_core::extension::{Extension, ExtensionType};
+    use mls_rs_core::group::GroupStateStorage;
     use mls_rs_core::identity::{Credential, CredentialType, CustomCredential};
 
     #[cfg(feature = "by_ref_proposal")]
@@ -2034,6 +3284,157 @@ mod tests {
         );
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_can_set_leaf_node_extensions() {
+        let mut extension_list = ExtensionList::default();
+        extension_list.set_from(TestExtension { foo: 10 }).unwrap();
+
+        let (mut alice_group, mut bob_group) =
+            test_two_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, true).await;
+
+        let commit = alice_group
+            .group
+            .commit_builder()
+            .set_leaf_node_extensions(extension_list.clone())
+            .build()
+            .await
+            .unwrap();
+
+        alice_group.process_pending_commit().await.unwrap();
+        bob_group
+            .process_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        let alice_leaf = bob_group
+            .group
+            .state
+            .public_tree
+            .get_leaf_node(LeafIndex(0));
+        let alice_leaf = alice_leaf.unwrap();
+
+        assert_eq!(alice_leaf.ungreased_extensions(), extension_list);
+
+        assert!(alice_leaf
+            .ungreased_capabilities()
+            .extensions
+            .contains(&42.into()));
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn update_proposal_can_set_leaf_node_extensions() {
+        let mut extension_list = ExtensionList::default();
+        extension_list.set_from(TestExtension { foo: 10 }).unwrap();
+
+        let (mut alice_group, mut bob_group) =
+            test_two_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, true).await;
+
+        let proposal = bob_group
+            .group
+            .propose_update_with_extensions(extension_list.clone(), vec![])
+            .await
+            .unwrap();
+
+        alice_group.process_message(proposal).await.unwrap();
+
+        let commit = alice_group.group.commit(vec![]).await.unwrap();
+        alice_group.process_pending_commit().await.unwrap();
+        bob_group
+            .process_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        let bob_leaf = alice_group
+            .group
+            .state
+            .public_tree
+            .get_leaf_node(LeafIndex(1));
+
+        assert_eq!(bob_leaf.unwrap().ungreased_extensions(), extension_list);
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn duplicate_update_proposals_follow_policy() {
+        let cases = [
+            (DuplicateProposalPolicy::LatestWins, Some(1)),
+            (DuplicateProposalPolicy::FirstWins, Some(0)),
+            (DuplicateProposalPolicy::Reject, None),
+        ];
+
+        for (policy, expected) in cases {
+            let mut alice =
+                test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+                    b.duplicate_proposal_policy(policy)
+                })
+                .await;
+
+            let (mut bob, _) = alice.join("bob").await;
+            let mut updates = Vec::new();
+
+            for _ in 0..2 {
+                let proposal = bob.group.propose_update(vec![]).await.unwrap();
+
+                let Ok(ReceivedMessage::Proposal(proposal)) = alice.process_message(proposal).await
+                else {
+                    panic!("expected proposal");
+                };
+
+                let Proposal::Update(update) = proposal.proposal else {
+                    panic!("expected update proposal");
+                };
+
+                updates.push(update.leaf_node);
+            }
+
+            alice.group.commit(vec![]).await.unwrap();
+            alice.process_pending_commit().await.unwrap();
+
+            let bob_leaf = alice
+                .group
+                .state
+                .public_tree
+                .get_leaf_node(LeafIndex(1))
+                .unwrap();
+
+            match expected {
+                Some(i) => assert_eq!(bob_leaf, &updates[i]),
+                None => assert!(!updates.contains(bob_leaf)),
+            }
+        }
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn receiving_duplicate_update_proposals_fails_with_policy() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let (mut carol, commit) = alice
+            .join_with_custom_config("carol", false, |c| {
+                c.0.settings.duplicate_proposal_policy = Some(DuplicateProposalPolicy::LatestWins)
+            })
+            .await
+            .unwrap();
+
+        bob.process_message(commit).await.unwrap();
+
+        for _ in 0..2 {
+            let proposal = bob.group.propose_update(vec![]).await.unwrap();
+            alice.process_message(proposal.clone()).await.unwrap();
+            carol.process_message(proposal).await.unwrap();
+        }
+
+        let commit = alice.group.commit(vec![]).await.unwrap().commit_message;
+        let res = carol.process_message(commit).await;
+
+        assert_matches!(
+            res,
+            Err(MlsError::DuplicateProposalFromSender(ProposalType::UPDATE))
+        );
+    }
+
     #[cfg(feature = "by_ref_proposal")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn test_invalid_commit_self_update() {
@@ -2119,6 +3520,121 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn rejected_proposals_are_reported_when_committing() {
+        let (mut alice_group, mut bob_group) =
+            test_two_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, true).await;
+
+        // Bob proposes to remove Alice, which Alice can't commit
+        let proposal = bob_group.group.propose_remove(0, vec![]).await.unwrap();
+        let res = alice_group.process_message(proposal).await.unwrap();
+
+        let ReceivedMessage::Proposal(description) = res else {
+            panic!("expected proposal")
+        };
+
+        let commit_output = alice_group.group.commit(vec![]).await.unwrap();
+
+        assert_eq!(commit_output.rejected_proposals.len(), 1);
+
+        let rejection = &commit_output.rejected_proposals[0];
+        assert_eq!(rejection.proposal_ref, Some(description.proposal_ref));
+        assert_eq!(rejection.sender, Sender::Member(1));
+        assert!(!rejection.reason.is_empty());
+
+        alice_group.process_pending_commit().await.unwrap();
+
+        let commit_output = alice_group.group.commit(vec![]).await.unwrap();
+        assert!(commit_output.rejected_proposals.is_empty());
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn cached_proposal_can_be_found_by_ref() {
+        let (mut alice_group, mut bob_group) =
+            test_two_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, true).await;
+
+        let proposal = bob_group
+            .group
+            .propose_group_context_extensions(ExtensionList::new(), vec![])
+            .await
+            .unwrap();
+
+        let proposal_ref =
+            ProposalRef::compute(&alice_group.group.cipher_suite_provider, &proposal)
+                .await
+                .unwrap();
+
+        assert_eq!(alice_group.group.proposal_by_ref(&proposal_ref), None);
+
+        alice_group.process_message(proposal).await.unwrap();
+
+        let expected = ProposalInfo {
+            proposal: Proposal::GroupContextExtensions(ExtensionList::new()),
+            sender: Sender::Member(1),
+            source: ProposalSource::ByReference(proposal_ref.clone()),
+            received_epoch: Some(alice_group.group.current_epoch()),
+        };
+
+        assert_eq!(alice_group.group.cached_proposals(), vec![expected.clone()]);
+
+        assert_eq!(
+            alice_group.group.proposal_by_ref(&proposal_ref),
+            Some(expected.clone())
+        );
+
+        assert_eq!(
+            bob_group.group.proposal_by_ref(&proposal_ref),
+            Some(expected)
+        );
+
+        alice_group.group.commit(vec![]).await.unwrap();
+        alice_group.process_pending_commit().await.unwrap();
+
+        assert_eq!(alice_group.group.proposal_by_ref(&proposal_ref), None);
+        assert!(alice_group.group.cached_proposals().is_empty());
+    }
+
+    #[cfg(all(feature = "by_ref_proposal", feature = "state_update"))]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn committed_proposals_are_described() {
+        let (mut alice_group, mut bob_group) =
+            test_two_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, true).await;
+
+        let epoch = alice_group.group.current_epoch();
+
+        let proposal = bob_group
+            .group
+            .propose_group_context_extensions(ExtensionList::new(), vec![])
+            .await
+            .unwrap();
+
+        let ReceivedMessage::Proposal(description) =
+            alice_group.process_message(proposal).await.unwrap()
+        else {
+            panic!("expected proposal")
+        };
+
+        let by_ref = description.proposal_info();
+
+        assert_eq!(by_ref.sender(), &Sender::Member(1));
+        assert_eq!(by_ref.proposal_ref(), Some(&description.proposal_ref));
+        assert_eq!(by_ref.received_epoch(), Some(epoch));
+
+        let commit = alice_group.group.commit(vec![]).await.unwrap();
+
+        let ReceivedMessage::Commit(description) = bob_group
+            .process_message(commit.commit_message)
+            .await
+            .unwrap()
+        else {
+            panic!("expected commit")
+        };
+
+        assert_eq!(description.state_update.committed_proposals(), [by_ref]);
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn test_two_member_group(
         protocol_version: ProtocolVersion,
@@ -2180,7 +3696,7 @@ mod tests {
 
         // Group from Bob's perspective
         let bob_group = Group::join(
-            &commit_output.welcome_messages[0],
+            Validated::check(&commit_output.welcome_messages[0]).unwrap(),
             None,
             bob_client.config,
             bob_client.signer.unwrap(),
@@ -2192,13 +3708,69 @@ mod tests {
     }
 
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn test_group_context_ext_proposal_create() {
-        let test_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+    async fn test_welcome_processing_mismatched_tree() {
+        let mut test_group = test_group_custom(
+            TEST_PROTOCOL_VERSION,
+            TEST_CIPHER_SUITE,
+            Default::default(),
+            None,
+            Some(CommitOptions::new().with_ratchet_tree_extension(false)),
+        )
+        .await;
 
-        let mut extension_list = ExtensionList::new();
-        extension_list
-            .set_from(RequiredCapabilitiesExt {
-                extensions: vec![42.into()],
+        let (bob_client, bob_key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let old_tree = test_group.group.export_tree().into_owned();
+        let old_tree_hash = test_group.group.context().tree_hash.clone();
+
+        let commit_output = test_group
+            .group
+            .commit_builder()
+            .add_member(bob_key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        test_group.process_pending_commit().await.unwrap();
+
+        let new_tree_hash = test_group.group.context().tree_hash.clone();
+
+        // The tree of the previous epoch does not match the welcome.
+        let res = Group::join(
+            Validated::check(&commit_output.welcome_messages[0]).unwrap(),
+            Some(old_tree),
+            bob_client.config.clone(),
+            bob_client.signer.clone().unwrap(),
+        )
+        .await
+        .map(|_| ());
+
+        assert_matches!(
+            res,
+            Err(MlsError::RatchetTreeHashMismatch(expected, actual))
+                if expected == new_tree_hash && actual == old_tree_hash
+        );
+
+        Group::join(
+            Validated::check(&commit_output.welcome_messages[0]).unwrap(),
+            Some(test_group.group.export_tree().into_owned()),
+            bob_client.config,
+            bob_client.signer.unwrap(),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn test_group_context_ext_proposal_create() {
+        let test_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let mut extension_list = ExtensionList::new();
+        extension_list
+            .set_from(RequiredCapabilitiesExt {
+                extensions: vec![42.into()],
                 proposals: vec![],
                 credentials: vec![],
             })
@@ -2211,6 +3783,33 @@ mod tests {
         assert_matches!(proposal, Proposal::GroupContextExtensions(ext) if ext == extension_list);
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn group_context_ext_proposal_is_canonical() {
+        let test_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let extensions = vec![
+            Extension::new(ExtensionType::new(65001), vec![1]),
+            Extension::new(ExtensionType::new(65000), vec![2]),
+        ];
+
+        let mut extension_list =
+            ExtensionList::mls_decode(&mut &*extensions.mls_encode_to_vec().unwrap()).unwrap();
+
+        assert!(!extension_list.is_canonical());
+
+        let proposal = test_group
+            .group
+            .group_context_extensions_proposal(extension_list.clone());
+
+        extension_list.canonicalize();
+
+        assert_matches!(
+            proposal,
+            Proposal::GroupContextExtensions(ext)
+                if ext.is_canonical() && ext.to_vec() == extension_list.to_vec()
+        );
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn group_context_extension_proposal_test(
         ext_list: ExtensionList,
@@ -2400,6 +3999,300 @@ mod tests {
         assert!(with_padding.mls_encoded_len() > without_padding.mls_encoded_len());
     }
 
+    #[cfg(all(not(target_arch = "wasm32"), feature = "private_message"))]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn adaptive_padding_hides_small_messages() {
+        // This test requires a cipher suite whose signatures are not variable in length.
+        let cipher_suite = CipherSuite::CURVE25519_AES128;
+
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, cipher_suite, |b| {
+            b.mls_rules(
+                DefaultMlsRules::default()
+                    .with_encryption_options(EncryptionOptions::new(true, PaddingMode::Adaptive)),
+            )
+        })
+        .await;
+
+        let (mut bob, _) = alice.join("bob").await;
+
+        let mut sizes = Vec::new();
+
+        for _ in 0..40 {
+            let message = alice
+                .group
+                .encrypt_application_message(&random_bytes(500), vec![])
+                .await
+                .unwrap();
+
+            sizes.push(message.mls_encoded_len());
+            bob.process_message(message).await.unwrap();
+        }
+
+        let small = alice
+            .group
+            .encrypt_application_message(&random_bytes(10), vec![])
+            .await
+            .unwrap();
+
+        assert_eq!(small.mls_encoded_len(), sizes[sizes.len() - 1]);
+
+        // Bob learned the same buckets from the messages he received.
+        assert_eq!(
+            bob.group.length_histogram.padded_size(10),
+            alice.group.length_histogram.padded_size(10)
+        );
+
+        assert!(
+            small.mls_encoded_len()
+                > alice
+                    .group
+                    .estimate_ciphertext_size(10, PaddingMode::StepFunction)
+        );
+
+        let received = bob.process_message(small).await.unwrap();
+
+        assert_matches!(
+            received,
+            ReceivedMessage::ApplicationMessage(m) if m.data().len() == 10
+        );
+    }
+
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn encryption_policy_is_enforced_for_handshake_messages() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let (mut bob, _) = alice
+            .join_with_custom_config("bob", false, |c| {
+                c.0.settings.encryption_policy = EncryptionPolicy::new(true)
+            })
+            .await
+            .unwrap();
+
+        assert!(!alice.group.encryption_policy().encrypt_handshake);
+        assert!(bob.group.encryption_policy().encrypt_handshake);
+        assert!(bob.group.encryption_policy().encrypt_application);
+
+        let commit = alice.group.commit(vec![]).await.unwrap().commit_message;
+        assert_eq!(commit.wire_format(), WireFormat::PublicMessage);
+
+        let res = bob.process_message(commit).await;
+        assert_matches!(res, Err(MlsError::UnencryptedHandshakeMessage));
+
+        alice.group.clear_pending_commit();
+
+        let commit = bob.group.commit(vec![]).await.unwrap().commit_message;
+        assert_eq!(commit.wire_format(), WireFormat::PrivateMessage);
+
+        bob.process_pending_commit().await.unwrap();
+        alice.process_message(commit).await.unwrap();
+    }
+
+    #[cfg(all(feature = "private_message", feature = "out_of_order"))]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn retained_message_keys_are_limited_by_config() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let (mut bob, _) = alice
+            .join_with_custom_config("bob", false, |c| {
+                c.0.settings.max_retained_message_keys = Some(2)
+            })
+            .await
+            .unwrap();
+
+        let mut messages = Vec::new();
+
+        for _ in 0..5 {
+            let message = alice
+                .group
+                .encrypt_application_message(b"hello", vec![])
+                .await
+                .unwrap();
+
+            messages.push(message);
+        }
+
+        bob.process_message(messages.pop().unwrap()).await.unwrap();
+
+        let usage = bob.group.secret_tree_memory_usage();
+        assert_eq!(usage.retained_message_keys, 2);
+        assert_eq!(usage.sender_ratchets, 1);
+
+        bob.process_message(messages.pop().unwrap()).await.unwrap();
+
+        let res = bob.process_message(messages.remove(0)).await;
+        assert_matches!(res, Err(MlsError::KeyMissing(0)));
+    }
+
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn generation_jumps_are_limited_by_config() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let (mut bob, _) = alice
+            .join_with_custom_config("bob", false, |c| c.0.settings.max_generation_jump = 2)
+            .await
+            .unwrap();
+
+        let mut messages = Vec::new();
+
+        for _ in 0..6 {
+            let message = alice
+                .group
+                .encrypt_application_message(b"hello", vec![])
+                .await
+                .unwrap();
+
+            messages.push(message);
+        }
+
+        let res = bob.process_message(messages.pop().unwrap()).await;
+        assert_matches!(res, Err(MlsError::InvalidFutureGeneration(5)));
+
+        bob.process_message(messages.remove(2)).await.unwrap();
+        bob.process_message(messages.pop().unwrap()).await.unwrap();
+    }
+
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn incoming_messages_are_processed_in_epoch_and_generation_order() {
+        let (mut alice, mut bob) =
+            test_two_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, true).await;
+
+        let mut messages = Vec::new();
+
+        for data in [b"a0", b"a1"] {
+            let message = alice
+                .group
+                .encrypt_application_message(data, vec![])
+                .await
+                .unwrap();
+
+            messages.push(message);
+        }
+
+        let commit = alice.group.commit(vec![]).await.unwrap().commit_message;
+        alice.process_pending_commit().await.unwrap();
+        messages.push(commit);
+
+        let message = alice
+            .group
+            .encrypt_application_message(b"b0", vec![])
+            .await
+            .unwrap();
+
+        messages.push(message);
+        messages.reverse();
+
+        let results = bob.group.process_incoming_messages(messages).await;
+
+        assert_matches!(
+            &results[0],
+            Ok(ReceivedMessage::ApplicationMessage(m)) if m.data() == b"b0"
+        );
+
+        assert_matches!(&results[1], Ok(ReceivedMessage::Commit(_)));
+
+        assert_matches!(
+            &results[2],
+            Ok(ReceivedMessage::ApplicationMessage(m)) if m.data() == b"a1"
+        );
+
+        assert_matches!(
+            &results[3],
+            Ok(ReceivedMessage::ApplicationMessage(m)) if m.data() == b"a0"
+        );
+
+        assert_eq!(bob.group.current_epoch(), alice.group.current_epoch());
+    }
+
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn application_message_into_caller_buffers() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let mut encrypted = Vec::with_capacity(1024);
+        let mut decrypted = Vec::with_capacity(1024);
+
+        for data in [&b"hello"[..], &b"hi"[..]] {
+            alice
+                .group
+                .encrypt_application_message_into(&mut encrypted, data, b"aad")
+                .await
+                .unwrap();
+
+            let sender = bob
+                .group
+                .decrypt_application_message_into(&encrypted, &mut decrypted)
+                .await
+                .unwrap();
+
+            assert_eq!(sender, 0);
+            assert_eq!(decrypted, data);
+            assert!(decrypted.capacity() >= 1024);
+        }
+
+        let commit = alice.group.commit(vec![]).await.unwrap().commit_message;
+
+        let res = bob
+            .group
+            .decrypt_application_message_into(&commit.to_bytes().unwrap(), &mut decrypted)
+            .await;
+
+        assert_matches!(res, Err(MlsError::UnexpectedMessageType));
+        assert_eq!(bob.group.current_epoch(), 1);
+    }
+
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn padded_application_messages_do_not_depend_on_sender() {
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.mls_rules(
+                DefaultMlsRules::default()
+                    .with_encryption_options(EncryptionOptions::new(false, PaddingMode::None)),
+            )
+        })
+        .await;
+
+        let (mut bob, _) = alice.join("bob").await;
+
+        let from_alice = alice
+            .group
+            .encrypt_padded_application_message(b"hi")
+            .await
+            .unwrap();
+
+        let from_bob = bob
+            .group
+            .encrypt_padded_application_message(b"hello there")
+            .await
+            .unwrap();
+
+        let alice_ciphertext = from_alice.clone().into_ciphertext().unwrap();
+        let bob_ciphertext = from_bob.into_ciphertext().unwrap();
+
+        assert!(alice_ciphertext.authenticated_data.is_empty());
+        assert_eq!(
+            alice_ciphertext.encrypted_sender_data.len(),
+            bob_ciphertext.encrypted_sender_data.len()
+        );
+        assert_eq!(
+            alice_ciphertext.ciphertext.len(),
+            bob_ciphertext.ciphertext.len()
+        );
+
+        let received = bob.process_message(from_alice).await.unwrap();
+
+        assert_matches!(
+            received,
+            ReceivedMessage::ApplicationMessage(ApplicationMessageDescription {
+                sender_index: 0,
+                ..
+            })
+        );
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn external_commit_requires_external_pub_extension() {
         let protocol_version = TEST_PROTOCOL_VERSION;
@@ -2460,6 +4353,165 @@ mod tests {
             .unwrap();
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn group_creation_applies_group_id_policy() {
+        let policy = GroupIdPolicy::new()
+            .with_generation(GroupIdGeneration::Uuid)
+            .with_charset(GroupIdCharset::Alphanumeric);
+
+        let group = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.group_id_policy(policy.clone())
+        })
+        .await;
+
+        let config = group.group.config.clone();
+        let signer = group.group.signer.clone();
+
+        let signing_identity = group
+            .group
+            .current_member_signing_identity()
+            .unwrap()
+            .clone();
+
+        let generated = Group::new(
+            config.clone(),
+            None,
+            TEST_CIPHER_SUITE,
+            TEST_PROTOCOL_VERSION,
+            signing_identity.clone(),
+            Default::default(),
+            signer.clone(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(generated.group_id().len(), 36);
+
+        let res = Group::new(
+            config,
+            Some(b"group id".to_vec()),
+            TEST_CIPHER_SUITE,
+            TEST_PROTOCOL_VERSION,
+            signing_identity,
+            Default::default(),
+            signer,
+        )
+        .await
+        .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::InvalidGroupIdCharacter(5)));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn joining_rejects_group_id_violating_policy() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let res = alice
+            .join_with_custom_config("bob", false, |c| {
+                c.0.settings.group_id_policy = GroupIdPolicy::new().with_min_length(16)
+            })
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::GroupIdTooShort(5)));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn external_join_rejects_group_id_violating_policy() {
+        let mut group = test_group_custom(
+            TEST_PROTOCOL_VERSION,
+            TEST_CIPHER_SUITE,
+            vec![],
+            None,
+            CommitOptions::default()
+                .with_allow_external_commit(true)
+                .into(),
+        )
+        .await;
+
+        let commit_output = group.group.commit(vec![]).await.unwrap();
+
+        let (mut test_client, _) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        test_client.config.0.settings.group_id_policy = GroupIdPolicy::new().with_max_length(4);
+
+        let res = test_client
+            .external_commit_builder()
+            .unwrap()
+            .build(commit_output.external_commit_group_info.unwrap())
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::GroupIdTooLong(5)));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn proposing_reinit_rejects_cipher_suite_violating_policy() {
+        let policy =
+            ReInitCipherSuitePolicy::new().with_allowed_cipher_suites(vec![TEST_CIPHER_SUITE]);
+
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.reinit_cipher_suite_policy(policy.clone())
+        })
+        .await;
+
+        let res = alice
+            .group
+            .commit_builder()
+            .reinit(
+                None,
+                TEST_PROTOCOL_VERSION,
+                CipherSuite::CURVE448_CHACHA,
+                Default::default(),
+            )
+            .map(|_| ());
+
+        assert_matches!(
+            res,
+            Err(MlsError::ReInitDowngradeRejected(
+                CipherSuite::CURVE448_CHACHA
+            ))
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn receiving_reinit_rejects_cipher_suite_violating_policy() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let (mut bob, _) = alice
+            .join_with_custom_config("bob", false, |c| {
+                c.0.settings.reinit_cipher_suite_policy = ReInitCipherSuitePolicy::new()
+                    .with_allowed_cipher_suites(vec![TEST_CIPHER_SUITE])
+            })
+            .await
+            .unwrap();
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .reinit(
+                None,
+                TEST_PROTOCOL_VERSION,
+                CipherSuite::CURVE448_CHACHA,
+                Default::default(),
+            )
+            .unwrap()
+            .build()
+            .await
+            .unwrap()
+            .commit_message;
+
+        let res = bob.process_message(commit).await;
+
+        assert_matches!(
+            res,
+            Err(MlsError::ReInitDowngradeRejected(
+                CipherSuite::CURVE448_CHACHA
+            ))
+        );
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn test_path_update_preference() {
         let protocol_version = TEST_PROTOCOL_VERSION;
@@ -2559,7 +4611,10 @@ mod tests {
             .make_plaintext(Content::Application(b"hello".to_vec().into()))
             .await;
 
-        let res = bob.group.process_incoming_message(message).await;
+        let res = bob
+            .group
+            .process_incoming_message_validated(Validated::new(message).unwrap())
+            .await;
 
         assert_matches!(res, Err(MlsError::UnencryptedApplicationMessage));
     }
@@ -2679,6 +4734,18 @@ mod tests {
                 .collect::<Vec<_>>()
         );
 
+        let expected_order = [
+            (ProposalType::UPDATE, 1),
+            (ProposalType::REMOVE, 3),
+            (ProposalType::ADD, 5),
+            (ProposalType::PSK, 5),
+        ]
+        .into_iter()
+        .flat_map(|(t, n)| core::iter::repeat(t).take(n))
+        .collect::<Vec<_>>();
+
+        assert_eq!(state_update_alice.applied_proposal_order(), expected_order);
+
         let payload = bob
             .process_message(commit_output.commit_message)
             .await
@@ -2766,6 +4833,69 @@ mod tests {
         alice_group.process_message(commit).await.unwrap();
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn any_member_can_resign_group_info() {
+        use crate::client::test_utils::TestClientBuilder;
+
+        let mut alice_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob_group, _) = alice_group.join("bob").await;
+
+        let group_info = alice_group
+            .group
+            .group_info_message_allowing_ext_commit(true)
+            .await
+            .unwrap();
+
+        let group_info = bob_group
+            .group
+            .resign_group_info(&group_info)
+            .await
+            .unwrap();
+
+        assert_eq!(group_info.as_group_info().unwrap().sender(), 1);
+
+        let event = alice_group
+            .process_message(group_info.clone())
+            .await
+            .unwrap();
+
+        assert_matches!(event, ReceivedMessage::GroupInfo(_));
+
+        let (carol_identity, secret_key) =
+            get_test_signing_identity(TEST_CIPHER_SUITE, b"carol").await;
+
+        let carol = TestClientBuilder::new_for_test()
+            .signing_identity(carol_identity, secret_key, TEST_CIPHER_SUITE)
+            .build();
+
+        let (_, commit) = carol
+            .external_commit_builder()
+            .unwrap()
+            .build(group_info)
+            .await
+            .unwrap();
+
+        alice_group.process_message(commit).await.unwrap();
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn group_info_from_previous_epoch_cannot_be_resigned() {
+        let mut alice_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob_group, _) = alice_group.join("bob").await;
+
+        let group_info = alice_group.group.group_info_message(false).await.unwrap();
+
+        let commit = alice_group.group.commit(vec![]).await.unwrap();
+        alice_group.process_pending_commit().await.unwrap();
+        bob_group
+            .process_message(commit.commit_message)
+            .await
+            .unwrap();
+
+        let res = bob_group.group.resign_group_info(&group_info).await;
+        assert_matches!(res, Err(MlsError::InvalidGroupInfo));
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn test_membership_tag_from_non_member() {
         let (mut alice_group, mut bob_group) =
@@ -2809,6 +4939,135 @@ mod tests {
         assert!(commit.path.is_none());
     }
 
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn group_with_path_policy(policy: CommitPathPolicy) -> TestGroup {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        alice.group.config.0.mls_rules.commit_options.path_policy = policy;
+
+        alice
+    }
+
+    fn commit_has_path(message: MlsMessage) -> bool {
+        let Content::Commit(commit) = message.into_plaintext().unwrap().content.content else {
+            panic!("Expected commit")
+        };
+
+        commit.path.is_some()
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn force_path_policy_adds_path_to_add_only_commit() {
+        let mut alice = group_with_path_policy(CommitPathPolicy::ForcePath).await;
+        let (_, commit) = alice.join("bob").await;
+
+        assert!(commit_has_path(commit));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn never_path_policy_omits_path_and_rejects_required_path() {
+        let mut alice = group_with_path_policy(CommitPathPolicy::NeverPath).await;
+        let (_, commit) = alice.join("bob").await;
+
+        assert!(!commit_has_path(commit));
+
+        let res = alice
+            .group
+            .commit_builder()
+            .remove_member(1)
+            .unwrap()
+            .build()
+            .await;
+
+        assert_matches!(res, Err(MlsError::CommitPathNotAllowed));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn path_policy_does_not_apply_to_received_commits() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        // Add-only commit without a path.
+        bob.group.config.0.mls_rules.commit_options.path_policy = CommitPathPolicy::ForcePath;
+
+        let (_, commit) = alice.join("charlie").await;
+        let res = bob.process_message(commit).await.map(|_| ());
+        assert_matches!(res, Ok(()));
+
+        // Remove commit with a required path.
+        bob.group.config.0.mls_rules.commit_options.path_policy = CommitPathPolicy::NeverPath;
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .remove_member(2)
+            .unwrap()
+            .build()
+            .await
+            .unwrap()
+            .commit_message;
+
+        alice.process_pending_commit().await.unwrap();
+
+        let res = bob.process_message(commit).await.map(|_| ());
+        assert_matches!(res, Ok(()));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn force_path_policy_rejects_received_commit_without_path() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        bob.group
+            .config
+            .0
+            .mls_rules
+            .commit_options
+            .received_path_policy = CommitPathPolicy::ForcePath;
+
+        let (_, commit) = alice.join("charlie").await;
+        let res = bob.process_message(commit).await;
+
+        assert_matches!(res, Err(MlsError::CommitMissingPath));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn never_path_policy_rejects_received_commit_with_optional_path() {
+        let mut alice = group_with_path_policy(CommitPathPolicy::ForcePath).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        bob.group
+            .config
+            .0
+            .mls_rules
+            .commit_options
+            .received_path_policy = CommitPathPolicy::NeverPath;
+
+        let (_, commit) = alice.join("charlie").await;
+        let res = bob.process_message(commit).await;
+
+        assert_matches!(res, Err(MlsError::CommitPathNotAllowed));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn never_path_policy_accepts_received_commit_with_required_path() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        bob.group
+            .config
+            .0
+            .mls_rules
+            .commit_options
+            .received_path_policy = CommitPathPolicy::NeverPath;
+
+        let commit = alice.group.commit(vec![]).await.unwrap().commit_message;
+        alice.process_pending_commit().await.unwrap();
+
+        let res = bob.process_message(commit).await.map(|_| ());
+        assert_matches!(res, Ok(()));
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn group_with_path_required() -> TestGroup {
         let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
@@ -2885,7 +5144,10 @@ mod tests {
         let (carol, commit) = alice.join("carol").await;
 
         // Apply the commit that adds carol
-        bob.group.process_incoming_message(commit).await.unwrap();
+        bob.group
+            .process_incoming_message_validated(Validated::new(commit).unwrap())
+            .await
+            .unwrap();
 
         let bob_identity = bob.group.current_member_signing_identity().unwrap().clone();
         let signer = bob.group.signer.clone();
@@ -2918,7 +5180,9 @@ mod tests {
         let commit_output = alice_sub_group.commit(vec![]).await.unwrap();
 
         bob_sub_group
-            .process_incoming_message(commit_output.commit_message)
+            .process_incoming_message_validated(
+                Validated::new(commit_output.commit_message).unwrap(),
+            )
             .await
             .unwrap();
     }
@@ -2985,7 +5249,7 @@ mod tests {
 
         let received_by_alice = alice_group
             .group
-            .process_incoming_message(msg)
+            .process_incoming_message_validated(Validated::new(msg).unwrap())
             .await
             .unwrap();
 
@@ -3001,38 +5265,169 @@ mod tests {
         let mut alice_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
         let (bob_group, _) = alice_group.join("bob").await;
 
-        assert_eq!(
-            alice_group.group.epoch_authenticator().unwrap(),
-            bob_group.group.epoch_authenticator().unwrap()
-        );
+        assert_eq!(
+            alice_group.group.epoch_authenticator().unwrap(),
+            bob_group.group.epoch_authenticator().unwrap()
+        );
+    }
+
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn member_cannot_decrypt_same_message_twice() {
+        let mut alice_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob_group, _) = alice_group.join("bob").await;
+
+        let message = alice_group
+            .group
+            .encrypt_application_message(b"foobar", Vec::new())
+            .await
+            .unwrap();
+
+        let received_message = bob_group
+            .group
+            .process_incoming_message_validated(Validated::new(message.clone()).unwrap())
+            .await
+            .unwrap();
+
+        assert_matches!(
+            received_message,
+            ReceivedMessage::ApplicationMessage(m) if m.data() == b"foobar"
+        );
+
+        let res = bob_group
+            .group
+            .process_incoming_message_validated(Validated::new(message).unwrap())
+            .await;
+
+        assert_matches!(res, Err(MlsError::KeyMissing(0)));
+    }
+
+    #[cfg(feature = "application_lanes")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn application_message_on_lane_round_trip() {
+        let mut alice_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob_group, _) = alice_group.join("bob").await;
+
+        let message = alice_group
+            .group
+            .encrypt_application_message_on_lane(b"media", vec![], 1)
+            .await
+            .unwrap();
+
+        let res = bob_group
+            .group
+            .process_incoming_message_validated(Validated::new(message.clone()).unwrap())
+            .await;
+
+        assert!(res.is_err());
+
+        let received_message = bob_group
+            .group
+            .process_incoming_message_on_lane(message, 1)
+            .await
+            .unwrap();
+
+        assert_matches!(
+            received_message,
+            ReceivedMessage::ApplicationMessage(m) if m.data() == b"media"
+        );
+    }
+
+    #[cfg(feature = "application_lanes")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn application_lanes_use_separate_ratchets() {
+        let mut alice_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob_group, _) = alice_group.join("bob").await;
+
+        for _ in 0..3 {
+            alice_group
+                .group
+                .encrypt_application_message_on_lane(b"media", vec![], 1)
+                .await
+                .unwrap();
+        }
+
+        let control = alice_group
+            .group
+            .encrypt_application_message(b"control", vec![])
+            .await
+            .unwrap();
+
+        let media = alice_group
+            .group
+            .encrypt_application_message_on_lane(b"media", vec![], 2)
+            .await
+            .unwrap();
+
+        bob_group
+            .group
+            .process_incoming_message_validated(Validated::new(control.clone()).unwrap())
+            .await
+            .unwrap();
+
+        bob_group
+            .group
+            .process_incoming_message_on_lane(media.clone(), 2)
+            .await
+            .unwrap();
+
+        // Both messages used the first key of their respective ratchet
+        let res = bob_group
+            .group
+            .process_incoming_message_validated(Validated::new(control).unwrap())
+            .await;
+        assert_matches!(res, Err(MlsError::KeyMissing(0)));
+
+        let res = bob_group
+            .group
+            .process_incoming_message_on_lane(media, 2)
+            .await;
+
+        assert_matches!(res, Err(MlsError::KeyMissing(0)));
+    }
+
+    #[cfg(feature = "application_lanes")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn application_message_on_lane_is_validated() {
+        let mut alice_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob_group, _) = alice_group.join("bob").await;
+
+        let mut message = alice_group
+            .group
+            .encrypt_application_message_on_lane(b"media", vec![], 1)
+            .await
+            .unwrap();
+
+        let MlsMessagePayload::Cipher(ciphertext) = &mut message.payload else {
+            panic!("expected ciphertext")
+        };
+
+        ciphertext.group_id = b"other group".to_vec();
+
+        let res = bob_group
+            .group
+            .process_incoming_message_on_lane(message, 1)
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::GroupIdMismatch));
     }
 
-    #[cfg(feature = "private_message")]
+    #[cfg(feature = "application_lanes")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn member_cannot_decrypt_same_message_twice() {
+    async fn handshake_messages_ignore_lane() {
         let mut alice_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
         let (mut bob_group, _) = alice_group.join("bob").await;
 
-        let message = alice_group
-            .group
-            .encrypt_application_message(b"foobar", Vec::new())
-            .await
-            .unwrap();
+        let commit = alice_group.group.commit(vec![]).await.unwrap();
 
         let received_message = bob_group
             .group
-            .process_incoming_message(message.clone())
+            .process_incoming_message_on_lane(commit.commit_message, 1)
             .await
             .unwrap();
 
-        assert_matches!(
-            received_message,
-            ReceivedMessage::ApplicationMessage(m) if m.data() == b"foobar"
-        );
-
-        let res = bob_group.group.process_incoming_message(message).await;
-
-        assert_matches!(res, Err(MlsError::KeyMissing(0)));
+        assert_matches!(received_message, ReceivedMessage::Commit(_));
     }
 
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
@@ -3274,6 +5669,29 @@ mod tests {
         assert_matches!(res, Err(MlsError::InvalidSignature));
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn members_not_supporting_context_extensions_are_listed() {
+        const EXT_TYPE: ExtensionType = ExtensionType::new(999);
+
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.extension_type(EXT_TYPE)
+        })
+        .await;
+
+        let (bob, _) = alice.join("bob").await;
+
+        let extensions = ExtensionList::from(vec![Extension::new(EXT_TYPE, vec![])]);
+        let unsupported = alice.group.unsupported_members_for(&extensions);
+
+        assert_eq!(unsupported.len(), 1);
+        assert_eq!(unsupported[0].index, bob.group.current_member_index());
+
+        assert!(alice
+            .group
+            .unsupported_members_for(&ExtensionList::new())
+            .is_empty());
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn commit_leaf_not_supporting_used_context_extension() {
@@ -3293,7 +5711,9 @@ mod tests {
         let commit_output = groups[0].commit(vec![]).await.unwrap();
 
         let res = groups[1]
-            .process_incoming_message(commit_output.commit_message)
+            .process_incoming_message_validated(
+                Validated::new(commit_output.commit_message).unwrap(),
+            )
             .await;
 
         assert_matches!(res, Err(MlsError::UnsupportedGroupExtension(EXT_TYPE)));
@@ -3321,7 +5741,9 @@ mod tests {
         let commit_output = groups[0].commit(vec![]).await.unwrap();
 
         let res = groups[2]
-            .process_incoming_message(commit_output.commit_message)
+            .process_incoming_message_validated(
+                Validated::new(commit_output.commit_message).unwrap(),
+            )
             .await;
 
         assert!(res.is_err());
@@ -3354,7 +5776,9 @@ mod tests {
         let commit_output = groups[0].commit(vec![]).await.unwrap();
 
         let res = groups[2]
-            .process_incoming_message(commit_output.commit_message)
+            .process_incoming_message_validated(
+                Validated::new(commit_output.commit_message).unwrap(),
+            )
             .await;
 
         assert_matches!(res, Err(MlsError::CredentialTypeOfNewLeafIsUnsupported));
@@ -3375,7 +5799,9 @@ mod tests {
         let commit_output = groups[0].commit(vec![]).await.unwrap();
 
         let res = groups[2]
-            .process_incoming_message(commit_output.commit_message)
+            .process_incoming_message_validated(
+                Validated::new(commit_output.commit_message).unwrap(),
+            )
             .await;
 
         assert_matches!(res, Err(MlsError::InUseCredentialTypeUnsupportedByNewLeaf));
@@ -3403,7 +5829,9 @@ mod tests {
         let commit_output = groups[0].commit(vec![]).await.unwrap();
 
         let res = groups[2]
-            .process_incoming_message(commit_output.commit_message)
+            .process_incoming_message_validated(
+                Validated::new(commit_output.commit_message).unwrap(),
+            )
             .await;
 
         assert_matches!(res, Err(MlsError::RequiredCredentialNotFound(_)));
@@ -3726,7 +6154,7 @@ mod tests {
 
         groups[1]
             .group
-            .process_incoming_message(proposal)
+            .process_incoming_message_validated(Validated::new(proposal).unwrap())
             .await
             .unwrap();
         let res = groups[1]
@@ -3773,7 +6201,11 @@ mod tests {
             .unwrap()
             .commit_message;
 
-        let res = bob.group.process_incoming_message(commit).await.unwrap();
+        let res = bob
+            .group
+            .process_incoming_message_validated(Validated::new(commit).unwrap())
+            .await
+            .unwrap();
 
         #[cfg(feature = "state_update")]
         assert_matches!(res, ReceivedMessage::Commit(CommitMessageDescription { state_update: StateUpdate { custom_proposals, .. }, .. })
@@ -3796,13 +6228,21 @@ mod tests {
             .await
             .unwrap();
 
-        let recv_prop = bob.group.process_incoming_message(proposal).await.unwrap();
+        let recv_prop = bob
+            .group
+            .process_incoming_message_validated(Validated::new(proposal).unwrap())
+            .await
+            .unwrap();
 
         assert_matches!(recv_prop, ReceivedMessage::Proposal(ProposalMessageDescription { proposal: Proposal::Custom(c), ..})
             if c == custom_proposal);
 
         let commit = bob.group.commit(vec![]).await.unwrap().commit_message;
-        let res = alice.group.process_incoming_message(commit).await.unwrap();
+        let res = alice
+            .group
+            .process_incoming_message_validated(Validated::new(commit).unwrap())
+            .await
+            .unwrap();
 
         #[cfg(feature = "state_update")]
         assert_matches!(res, ReceivedMessage::Commit(CommitMessageDescription { state_update: StateUpdate { custom_proposals, .. }, .. })
@@ -3812,6 +6252,96 @@ mod tests {
         assert_matches!(res, ReceivedMessage::Commit(_));
     }
 
+    #[cfg(feature = "psk")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn welcome_required_psks_can_be_listed_before_joining() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE)
+            .await
+            .group;
+
+        let (bob, key_pkg) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let psk_id = ExternalPskId::new(vec![0]);
+        let psk = PreSharedKey::from(vec![0]);
+
+        alice
+            .config
+            .secret_store()
+            .insert(psk_id.clone(), psk.clone());
+
+        let commit = alice
+            .commit_builder()
+            .add_member(key_pkg)
+            .unwrap()
+            .add_external_psk(psk_id.clone())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let welcome = &commit.welcome_messages[0];
+
+        let required = bob.welcome_required_psks(welcome).await.unwrap();
+        assert_eq!(required, vec![psk_id.clone()]);
+
+        // Listing the PSKs does not consume the key package
+        bob.config.secret_store().insert(psk_id, psk);
+        bob.join_group_validated(None, &Validated::new(welcome.clone()).unwrap())
+            .await
+            .unwrap();
+    }
+
+    #[cfg(feature = "psk")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn missing_welcome_psks_can_be_looked_up_on_join() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE)
+            .await
+            .group;
+
+        let (bob, key_pkg) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let psk_id = ExternalPskId::new(vec![0]);
+        let psk = PreSharedKey::from(vec![0]);
+
+        alice
+            .config
+            .secret_store()
+            .insert(psk_id.clone(), psk.clone());
+
+        let commit = alice
+            .commit_builder()
+            .add_member(key_pkg)
+            .unwrap()
+            .add_external_psk(psk_id.clone())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let welcome = &commit.welcome_messages[0];
+        let mut lookup = InMemoryPreSharedKeyStorage::default();
+
+        let res = bob
+            .join_group_with_psk_lookup(None, welcome, &lookup)
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::MissingRequiredPsk));
+
+        // The failed attempt does not consume the key package
+
+        lookup.insert(psk_id.clone(), psk);
+
+        bob.join_group_with_psk_lookup(None, welcome, &lookup)
+            .await
+            .unwrap();
+
+        // PSKs obtained from the lookup are not stored by the client
+        assert!(bob.config.secret_store().get(&psk_id).is_none());
+    }
+
     #[cfg(feature = "psk")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn can_join_with_psk() {
@@ -3842,9 +6372,61 @@ mod tests {
             .await
             .unwrap();
 
-        bob.join_group(None, &commit.welcome_messages[0])
+        bob.join_group_validated(
+            None,
+            &Validated::new(commit.welcome_messages[0].clone()).unwrap(),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[cfg(feature = "psk")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn external_psk_can_be_rotated() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let old_id = ExternalPskId::new(vec![0]);
+        let new_id = ExternalPskId::new(vec![1]);
+
+        for group in [&alice, &bob] {
+            let mut store = group.group.config.secret_store();
+            assert!(store.register(old_id.clone(), PreSharedKey::from(vec![0])));
+        }
+
+        let res = alice
+            .group
+            .commit_external_psk_rotation(new_id.clone())
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::MissingRequiredPsk));
+
+        for group in [&alice, &bob] {
+            let mut store = group.group.config.secret_store();
+            assert!(store.rotate(&old_id, new_id.clone(), PreSharedKey::from(vec![1])));
+            assert_eq!(store.successor(&old_id), Some(new_id.clone()));
+        }
+
+        let commit = alice
+            .group
+            .commit_external_psk_rotation(new_id.clone())
             .await
             .unwrap();
+
+        alice.process_pending_commit().await.unwrap();
+        bob.process_message(commit.commit_message).await.unwrap();
+
+        for group in [&alice, &bob] {
+            let mut store = group.group.config.secret_store();
+            assert!(store.revoke(&old_id).is_some());
+            assert_eq!(store.ids(), vec![new_id.clone()]);
+        }
+
+        assert_eq!(
+            alice.group.epoch_authenticator().unwrap(),
+            bob.group.epoch_authenticator().unwrap()
+        );
     }
 
     #[cfg(feature = "by_ref_proposal")]
@@ -3909,7 +6491,10 @@ mod tests {
         alice.apply_pending_commit().await.unwrap();
 
         let mut bob = bob_client
-            .join_group(None, &commit.welcome_messages[0])
+            .join_group_validated(
+                None,
+                &Validated::new(commit.welcome_messages[0].clone()).unwrap(),
+            )
             .await
             .unwrap()
             .0;
@@ -3928,13 +6513,19 @@ mod tests {
             .unwrap();
 
         let mut carol = carol_client
-            .join_group(None, &commit.welcome_messages[0])
+            .join_group_validated(
+                None,
+                &Validated::new(commit.welcome_messages[0].clone()).unwrap(),
+            )
             .await
             .unwrap()
             .0;
 
         let mut dave = dave_client
-            .join_group(None, &commit.welcome_messages[0])
+            .join_group_validated(
+                None,
+                &Validated::new(commit.welcome_messages[0].clone()).unwrap(),
+            )
             .await
             .unwrap()
             .0;
@@ -3945,9 +6536,18 @@ mod tests {
         let dave_update = dave.propose_update(Vec::new()).await.unwrap();
 
         // Alice receives the update proposals to be committed.
-        alice.process_incoming_message(bob_update).await.unwrap();
-        alice.process_incoming_message(carol_update).await.unwrap();
-        alice.process_incoming_message(dave_update).await.unwrap();
+        alice
+            .process_incoming_message_validated(Validated::new(bob_update).unwrap())
+            .await
+            .unwrap();
+        alice
+            .process_incoming_message_validated(Validated::new(carol_update).unwrap())
+            .await
+            .unwrap();
+        alice
+            .process_incoming_message_validated(Validated::new(dave_update).unwrap())
+            .await
+            .unwrap();
 
         // Alice commits the update proposals.
         alice.commit(Vec::new()).await.unwrap();
@@ -4098,7 +6698,10 @@ mod tests {
 
         if external_joiner_can_send_custom {
             let commit = commit.unwrap().1;
-            alice.process_incoming_message(commit).await.unwrap();
+            alice
+                .process_incoming_message_validated(Validated::new(commit).unwrap())
+                .await
+                .unwrap();
         } else {
             assert_matches!(commit.map(|_| ()), Err(MlsError::MlsRulesError(_)));
         }
@@ -4135,7 +6738,10 @@ mod tests {
             .await
             .unwrap();
 
-        alice.process_incoming_message(commit).await.unwrap();
+        alice
+            .process_incoming_message_validated(Validated::new(commit).unwrap())
+            .await
+            .unwrap();
     }
 
     #[cfg(feature = "custom_proposal")]
@@ -4219,7 +6825,7 @@ mod tests {
         let commit = group.commit(vec![]).await.unwrap();
 
         let update = group
-            .process_incoming_message(commit.commit_message)
+            .process_incoming_message_validated(Validated::new(commit.commit_message).unwrap())
             .await
             .unwrap();
 
@@ -4239,11 +6845,149 @@ mod tests {
 
         groups[1]
             .group
-            .process_incoming_message(commit)
+            .process_incoming_message_validated(Validated::new(commit).unwrap())
             .await
             .unwrap();
 
         let res = groups[1].group.apply_pending_commit().await;
         assert_matches!(res, Err(MlsError::PendingCommitNotFound));
     }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn removed_member_state_is_wiped() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        bob.group.write_to_storage().await.unwrap();
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .remove_member(1)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let res = bob.process_message(commit.commit_message).await.unwrap();
+
+        assert_matches!(
+            res,
+            ReceivedMessage::Commit(CommitMessageDescription {
+                self_removed: true,
+                ..
+            })
+        );
+
+        let stored = bob
+            .group
+            .config
+            .group_state_storage()
+            .state(TEST_GROUP)
+            .await
+            .unwrap();
+
+        assert!(stored.is_none());
+
+        let res = bob.group.write_to_storage().await;
+        assert_matches!(res, Err(MlsError::GroupUsedAfterRemoval));
+
+        let res = bob.group.commit(vec![]).await;
+        assert_matches!(res, Err(MlsError::GroupUsedAfterRemoval));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn removed_member_state_is_wiped_when_processing_with_time() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        bob.group.write_to_storage().await.unwrap();
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .remove_member(1)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let res = bob
+            .group
+            .process_incoming_message_with_time(commit.commit_message, crate::time::MlsTime::now())
+            .await
+            .unwrap();
+
+        assert_matches!(
+            res,
+            ReceivedMessage::Commit(CommitMessageDescription {
+                self_removed: true,
+                ..
+            })
+        );
+
+        let stored = bob
+            .group
+            .config
+            .group_state_storage()
+            .state(TEST_GROUP)
+            .await
+            .unwrap();
+
+        assert!(stored.is_none());
+
+        let res = bob.group.write_to_storage().await;
+        assert_matches!(res, Err(MlsError::GroupUsedAfterRemoval));
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn self_remove_proposal_is_committed_by_other_member() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let proposal = bob.group.propose_self_remove(vec![]).await.unwrap();
+
+        assert!(bob.group.is_pending_leave());
+
+        alice.process_message(proposal).await.unwrap();
+
+        let commit = alice.group.commit(vec![]).await.unwrap();
+        alice.process_pending_commit().await.unwrap();
+
+        assert_eq!(alice.group.roster().members_iter().count(), 1);
+
+        let res = bob.process_message(commit.commit_message).await.unwrap();
+
+        assert_matches!(
+            res,
+            ReceivedMessage::Commit(CommitMessageDescription {
+                self_removed: true,
+                ..
+            })
+        );
+
+        assert!(!bob.group.is_pending_leave());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_fails_while_pending_leave() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        alice.group.set_pending_leave(true);
+
+        let res = alice.group.commit(vec![]).await;
+        assert_matches!(res, Err(MlsError::CommitWhilePendingLeave));
+
+        let snapshot = alice.group.snapshot();
+
+        let restored = Group::from_snapshot(alice.group.config.clone(), snapshot)
+            .await
+            .unwrap();
+
+        assert!(restored.is_pending_leave());
+
+        alice.group.set_pending_leave(false);
+        alice.group.commit(vec![]).await.unwrap();
+    }
 }
@@ -2,6 +2,8 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+use alloc::{collections::VecDeque, vec::Vec};
+
 /// Padding used when sending an encrypted group message.
 #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::ffi_type)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -14,12 +16,30 @@ pub enum PaddingMode {
     StepFunction,
     /// No padding.
     None,
+    /// Pad to buckets learned from the sizes of the messages recently sent
+    /// and received by this member in the group.
+    ///
+    /// The buckets are the 50th, 75th, 90th and 99th percentiles of the
+    /// sizes of the last 256 encrypted messages, rounded up with
+    /// [`PaddingMode::StepFunction`]. Messages are padded to the smallest
+    /// bucket they fit in. Messages larger than all buckets, and all messages
+    /// until 32 sizes were learned, are padded with
+    /// [`PaddingMode::StepFunction`].
+    ///
+    /// Typical messages are padded to few sizes, which hides more of their
+    /// size than the step function at the cost of more bandwidth for small
+    /// messages. Members learning from the same messages use the same
+    /// buckets, so that the padded size of a message doesn't reveal which
+    /// member sent it. The learned sizes are kept in memory only and not
+    /// stored with the group state, so buckets differ until members that
+    /// loaded or joined the group learned enough sizes.
+    Adaptive,
 }
 
 impl PaddingMode {
     pub(super) fn padded_size(&self, content_size: usize) -> usize {
         match self {
-            PaddingMode::StepFunction => {
+            PaddingMode::StepFunction | PaddingMode::Adaptive => {
                 // The padding hides all but 2 most significant bits of `length`. The hidden bits are replaced
                 // by zeros and then the next number is taken to make sure the message fits.
                 let blind = 1
@@ -34,11 +54,71 @@ impl PaddingMode {
             PaddingMode::None => content_size,
         }
     }
+
+    /// Padded size of a message, using the buckets learned by `history` in
+    /// [`PaddingMode::Adaptive`].
+    pub(super) fn padded_size_with_history(
+        &self,
+        content_size: usize,
+        history: Option<&LengthHistogram>,
+    ) -> usize {
+        match (self, history) {
+            (PaddingMode::Adaptive, Some(history)) => history.padded_size(content_size),
+            _ => self.padded_size(content_size),
+        }
+    }
+}
+
+/// Sizes of the content of the last encrypted messages sent and received in
+/// the group, before padding.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct LengthHistogram {
+    sizes: VecDeque<usize>,
+}
+
+impl LengthHistogram {
+    const WINDOW: usize = 256;
+    const MIN_SAMPLES: usize = 32;
+    const PERCENTILES: [usize; 4] = [50, 75, 90, 99];
+
+    pub(crate) fn record(&mut self, content_size: usize) {
+        if self.sizes.len() == Self::WINDOW {
+            self.sizes.pop_front();
+        }
+
+        self.sizes.push_back(content_size);
+    }
+
+    pub(crate) fn padded_size(&self, content_size: usize) -> usize {
+        let step = |size| PaddingMode::StepFunction.padded_size(size);
+
+        if self.sizes.len() < Self::MIN_SAMPLES {
+            return step(content_size);
+        }
+
+        self.buckets()
+            .into_iter()
+            .find(|&bucket| bucket >= content_size)
+            .unwrap_or_else(|| step(content_size))
+    }
+
+    /// Buckets in increasing order.
+    fn buckets(&self) -> Vec<usize> {
+        let mut sizes = self.sizes.iter().copied().collect::<Vec<_>>();
+        sizes.sort_unstable();
+
+        let n = sizes.len();
+
+        Self::PERCENTILES
+            .iter()
+            .map(|p| PaddingMode::StepFunction.padded_size(sizes[(p * n + 99) / 100 - 1]))
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::PaddingMode;
+    use super::{LengthHistogram, PaddingMode};
 
     use alloc::vec;
     use alloc::vec::Vec;
@@ -77,6 +157,81 @@ mod tests {
         }
     }
 
+    fn seeded_histogram(seed: u64) -> LengthHistogram {
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        let mut history = LengthHistogram::default();
+
+        for _ in 0..1000 {
+            let size = if rng.gen_bool(0.05) {
+                rng.gen_range(2000..3000)
+            } else {
+                rng.gen_range(100..300)
+            };
+
+            history.record(size);
+        }
+
+        history
+    }
+
+    #[test]
+    fn adaptive_padding_uses_learned_buckets() {
+        let history = seeded_histogram(42);
+
+        assert_eq!(history.sizes.len(), LengthHistogram::WINDOW);
+        assert_eq!(history, seeded_histogram(42));
+
+        let buckets = history.buckets();
+        assert!(buckets.windows(2).all(|w| w[0] <= w[1]));
+
+        let padded = (0..3000)
+            .map(|size| PaddingMode::Adaptive.padded_size_with_history(size, Some(&history)))
+            .collect::<Vec<_>>();
+
+        assert!(padded.iter().enumerate().all(|(size, &p)| p >= size));
+
+        // Typical messages share the few learned buckets.
+        assert!((0..300).all(|size| buckets.contains(&padded[size])));
+
+        // Messages larger than all buckets fall back to the step function.
+        let largest = buckets[buckets.len() - 1];
+
+        assert_eq!(
+            history.padded_size(largest + 1),
+            PaddingMode::StepFunction.padded_size(largest + 1)
+        );
+    }
+
+    #[test]
+    fn adaptive_padding_needs_history() {
+        let mut history = LengthHistogram::default();
+
+        for size in [100, 1000, 10000] {
+            assert_eq!(
+                PaddingMode::Adaptive.padded_size_with_history(size, None),
+                PaddingMode::StepFunction.padded_size(size)
+            );
+        }
+
+        for _ in 0..LengthHistogram::MIN_SAMPLES - 1 {
+            history.record(1000);
+        }
+
+        assert_eq!(
+            history.padded_size(10),
+            PaddingMode::StepFunction.padded_size(10)
+        );
+
+        history.record(1000);
+
+        assert_eq!(
+            history.padded_size(10),
+            PaddingMode::StepFunction.padded_size(1000)
+        );
+    }
+
     #[test]
     fn test_padding_length() {
         assert_eq!(PaddingMode::StepFunction.padded_size(0), 32);
@@ -0,0 +1,175 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{
+        framing::Content, member_from_leaf_node, message_processor::MessageProcessor,
+        mls_rules::CommitDirection, proposal_filter::ProposalBundle, Group, Member, Sender,
+    },
+    key_package::KeyPackageRef,
+    tree_kem::node::LeafIndex,
+};
+
+#[cfg(not(feature = "by_ref_proposal"))]
+use crate::group::proposal_cache::resolve_for_commit;
+
+/// Preview of the commit created by [`Group::commit`] or
+/// [`CommitBuilder::build`](crate::group::CommitBuilder::build) that was not
+/// applied yet, returned by [`Group::pending_commit`].
+///
+/// The preview describes the group as it will be after
+/// [`Group::apply_pending_commit`], e.g. to show that the commit is waiting
+/// for confirmation by the delivery service.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct PendingCommit {
+    /// Epoch the commit will produce once applied.
+    pub epoch: u64,
+    /// Proposals covered by the commit, both by value and by reference.
+    pub proposals: ProposalBundle,
+    /// Members of the group after the commit, in order of leaf index.
+    pub roster: Vec<Member>,
+    /// References of the key packages of members added by the commit. Each
+    /// of them is a recipient of a welcome message.
+    pub welcome_recipients: Vec<KeyPackageRef>,
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Preview of the pending commit, or `None` if there is no commit
+    /// pending, see [`Group::has_pending_commit`].
+    ///
+    /// The preview is computed from the current group state and the pending
+    /// commit. It does not modify the group.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn pending_commit(&self) -> Result<Option<PendingCommit>, MlsError> {
+        let Some(pending) = &self.pending_commit else {
+            return Ok(None);
+        };
+
+        let sender = pending.content.content.sender;
+
+        let commit = match &pending.content.content.content {
+            Content::Commit(commit) => Ok(commit),
+            #[cfg(any(feature = "private_message", feature = "by_ref_proposal"))]
+            _ => Err(MlsError::UnexpectedMessageType),
+        }?;
+
+        #[cfg(feature = "by_ref_proposal")]
+        let proposals = self
+            .state
+            .proposals
+            .resolve_for_commit(sender, commit.proposals.clone())?;
+
+        #[cfg(not(feature = "by_ref_proposal"))]
+        let proposals = resolve_for_commit(sender, commit.proposals.clone())?;
+
+        let provisional_state = self
+            .state
+            .apply_resolved(
+                sender,
+                proposals,
+                commit.path.as_ref().map(|path| &path.leaf_node),
+                &self.identity_provider(),
+                self.cipher_suite_provider(),
+                &self.psk_storage(),
+                &self.mls_rules(),
+                None,
+                CommitDirection::Receive,
+                self.init_key_reuse_policy(),
+                #[cfg(feature = "by_ref_proposal")]
+                self.duplicate_proposal_policy(),
+            )
+            .await?;
+
+        let committer = match sender {
+            Sender::Member(index) => Some(LeafIndex(index)),
+            _ => None,
+        };
+
+        let roster = provisional_state
+            .public_tree
+            .non_empty_leaves()
+            .map(|(index, leaf)| match &commit.path {
+                Some(path) if Some(index) == committer => {
+                    member_from_leaf_node(&path.leaf_node, index)
+                }
+                _ => member_from_leaf_node(leaf, index),
+            })
+            .collect();
+
+        let mut welcome_recipients = Vec::new();
+
+        for addition in provisional_state.applied_proposals.add_proposals() {
+            welcome_recipients.push(
+                addition
+                    .proposal
+                    .key_package
+                    .to_reference(self.cipher_suite_provider())
+                    .await?,
+            );
+        }
+
+        Ok(Some(PendingCommit {
+            epoch: provisional_state.group_context.epoch,
+            proposals: provisional_state.applied_proposals,
+            roster,
+            welcome_recipients,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::test_utils::{test_group, test_member},
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn pending_commit_previews_additions() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let pending = alice.group.pending_commit().await.unwrap();
+        assert!(pending.is_none());
+
+        let (bob, _) = test_member(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, b"bob").await;
+
+        alice
+            .group
+            .commit_builder()
+            .add_member(bob.key_package_message())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let pending = alice.group.pending_commit().await.unwrap().unwrap();
+
+        assert_eq!(pending.epoch, alice.group.current_epoch() + 1);
+        assert_eq!(pending.proposals.add_proposals().len(), 1);
+        assert_eq!(pending.roster.len(), 2);
+        assert_eq!(
+            pending.roster[1].signing_identity,
+            bob.key_package.leaf_node.signing_identity
+        );
+        assert_eq!(pending.welcome_recipients, vec![bob.reference.clone()]);
+
+        let roster = pending.roster;
+
+        alice.group.apply_pending_commit().await.unwrap();
+
+        let pending = alice.group.pending_commit().await.unwrap();
+        assert!(pending.is_none());
+        assert_eq!(alice.group.roster().members(), roster);
+    }
+}
@@ -0,0 +1,181 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use mls_rs_core::{
+    crypto::{CryptoProvider, SignatureSecretKey},
+    extension::ExtensionType,
+};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{ExportedTree, Group, GroupContext, GroupInfo, GroupSecrets, NewMemberInfo},
+    key_package::KeyPackageGeneration,
+    protocol_version::ProtocolVersion,
+    psk::secret::PskSecret,
+    CipherSuite,
+};
+
+/// Welcome message that was decrypted but not yet applied, created with
+/// [`Client::begin_join_group`](crate::Client::begin_join_group).
+///
+/// Joining is split in two steps for welcome messages that do not contain the
+/// ratchet tree, e.g. because the delivery service serves trees from a CDN.
+/// The [tree hash](PendingJoin::tree_hash) identifies the tree to download,
+/// and [`PendingJoin::finish`] verifies the downloaded tree against it before
+/// creating the group.
+pub struct PendingJoin<C: ClientConfig> {
+    pub(super) config: C,
+    pub(super) signer: SignatureSecretKey,
+    pub(super) protocol_version: ProtocolVersion,
+    pub(super) cipher_suite_provider: <C::CryptoProvider as CryptoProvider>::CipherSuiteProvider,
+    pub(super) group_secrets: GroupSecrets,
+    pub(super) key_package_generation: KeyPackageGeneration,
+    pub(super) psk_secret: PskSecret,
+    pub(super) group_info: GroupInfo,
+}
+
+impl<C> PendingJoin<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Group context of the epoch joined.
+    pub fn context(&self) -> &GroupContext {
+        &self.group_info.group_context
+    }
+
+    /// Unique identifier of the group.
+    pub fn group_id(&self) -> &[u8] {
+        &self.group_info.group_context.group_id
+    }
+
+    /// The epoch joined.
+    pub fn epoch(&self) -> u64 {
+        self.group_info.group_context.epoch
+    }
+
+    /// Cipher suite in use by the group.
+    pub fn cipher_suite(&self) -> CipherSuite {
+        self.group_info.group_context.cipher_suite
+    }
+
+    /// Hash of the ratchet tree required to finish joining.
+    pub fn tree_hash(&self) -> &[u8] {
+        &self.group_info.group_context.tree_hash
+    }
+
+    /// Determines if the ratchet tree is included in the welcome message, in
+    /// which case [`PendingJoin::finish`] does not require the tree.
+    pub fn has_ratchet_tree(&self) -> bool {
+        self.group_info
+            .extensions
+            .has_extension(ExtensionType::RATCHET_TREE)
+    }
+
+    /// Finish joining the group with `tree_data`, which must be the ratchet
+    /// tree whose hash is [`PendingJoin::tree_hash`] unless the tree is
+    /// included in the welcome message.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn finish(
+        self,
+        tree_data: Option<ExportedTree<'_>>,
+    ) -> Result<(Group<C>, NewMemberInfo), MlsError> {
+        Group::finish_join(self, tree_data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{
+                TestClientBuilder, TestClientConfig, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION,
+            },
+            MlsError,
+        },
+        group::test_utils::{test_group, test_group_custom},
+        group::Validated,
+        mls_rules::CommitOptions,
+        Client,
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn join_with_deferred_tree() {
+        let mut alice = test_group_custom(
+            TEST_PROTOCOL_VERSION,
+            TEST_CIPHER_SUITE,
+            Default::default(),
+            None,
+            Some(CommitOptions::new().with_ratchet_tree_extension(false)),
+        )
+        .await;
+
+        let bob: Client<TestClientConfig> = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("bob", TEST_CIPHER_SUITE)
+            .await
+            .build();
+
+        let key_package = bob.generate_key_package_message().await.unwrap();
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.process_pending_commit().await.unwrap();
+
+        let pending = bob
+            .begin_join_group(&commit.welcome_messages[0])
+            .await
+            .unwrap();
+
+        assert!(!pending.has_ratchet_tree());
+        assert_eq!(pending.group_id(), alice.group.group_id());
+        assert_eq!(pending.epoch(), alice.group.current_epoch());
+        assert_eq!(pending.tree_hash(), &alice.group.context().tree_hash[..]);
+
+        // A tree that does not match the tree hash is rejected.
+        let other = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let res = pending.finish(Some(other.group.export_tree())).await;
+        assert!(res.is_err());
+
+        let pending = bob
+            .begin_join_group(&commit.welcome_messages[0])
+            .await
+            .unwrap();
+
+        let res = pending.finish(None).await.map(|_| ());
+        assert_matches!(res, Err(MlsError::RatchetTreeNotFound));
+
+        let pending = bob
+            .begin_join_group(&commit.welcome_messages[0])
+            .await
+            .unwrap();
+
+        let (mut bob_group, _) = pending
+            .finish(Some(alice.group.export_tree()))
+            .await
+            .unwrap();
+
+        let commit = alice.group.commit(vec![]).await.unwrap();
+        alice.process_pending_commit().await.unwrap();
+
+        bob_group
+            .process_incoming_message_validated(Validated::new(commit.commit_message).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            bob_group.epoch_authenticator().unwrap(),
+            alice.group.epoch_authenticator().unwrap()
+        );
+    }
+}
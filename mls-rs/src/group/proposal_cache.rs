@@ -13,13 +13,15 @@ use crate::{
     client::MlsError,
     group::{
         proposal_filter::{ProposalApplier, ProposalBundle, ProposalSource},
-        Proposal, Sender,
+        InitKeyReusePolicy, Proposal, Sender,
     },
     time::MlsTime,
 };
 
 #[cfg(feature = "by_ref_proposal")]
-use crate::group::{proposal_filter::FilterStrategy, ProposalRef, ProtocolVersion};
+use crate::group::{
+    proposal_filter::FilterStrategy, DuplicateProposalPolicy, ProposalRef, ProtocolVersion,
+};
 
 use crate::tree_kem::leaf_node::LeafNode;
 
@@ -32,7 +34,13 @@ use mls_rs_core::{
 };
 
 #[cfg(feature = "by_ref_proposal")]
-use core::fmt::{self, Debug};
+use crate::group::proposal_filter::ProposalInfo;
+
+#[cfg(feature = "by_ref_proposal")]
+use core::{
+    fmt::{self, Debug},
+    time::Duration,
+};
 
 #[cfg(feature = "by_ref_proposal")]
 #[derive(Debug, Clone, MlsSize, MlsEncode, MlsDecode, PartialEq)]
@@ -40,14 +48,30 @@ use core::fmt::{self, Debug};
 pub struct CachedProposal {
     pub(crate) proposal: Proposal,
     pub(crate) sender: Sender,
+    // Time in seconds since the epoch at which the proposal was cached, if
+    // the current time is available.
+    pub(crate) received_at: Option<u64>,
 }
 
 #[cfg(feature = "by_ref_proposal")]
-#[derive(Clone, PartialEq)]
+#[derive(Clone)]
 pub(crate) struct ProposalCache {
     protocol_version: ProtocolVersion,
     group_id: Vec<u8>,
     pub(crate) proposals: crate::map::SmallMap<ProposalRef, CachedProposal>,
+    // Order in which proposals were received, used to order proposals in a
+    // commit. It is not persisted, so imported proposals come first in an
+    // arbitrary order.
+    arrival_order: Vec<ProposalRef>,
+}
+
+#[cfg(feature = "by_ref_proposal")]
+impl PartialEq for ProposalCache {
+    fn eq(&self, other: &Self) -> bool {
+        self.protocol_version == other.protocol_version
+            && self.group_id == other.group_id
+            && self.proposals == other.proposals
+    }
 }
 
 #[cfg(feature = "by_ref_proposal")]
@@ -71,6 +95,7 @@ impl ProposalCache {
             protocol_version,
             group_id,
             proposals: Default::default(),
+            arrival_order: Vec::new(),
         }
     }
 
@@ -79,16 +104,20 @@ impl ProposalCache {
         group_id: Vec<u8>,
         proposals: crate::map::SmallMap<ProposalRef, CachedProposal>,
     ) -> Self {
+        let arrival_order = proposals.keys().cloned().collect();
+
         Self {
             protocol_version,
             group_id,
             proposals,
+            arrival_order,
         }
     }
 
     #[inline]
     pub fn clear(&mut self) {
         self.proposals.clear();
+        self.arrival_order.clear();
     }
 
     #[cfg(feature = "private_message")]
@@ -98,7 +127,19 @@ impl ProposalCache {
     }
 
     pub fn insert(&mut self, proposal_ref: ProposalRef, proposal: Proposal, sender: Sender) {
-        let cached_proposal = CachedProposal { proposal, sender };
+        let cached_proposal = CachedProposal {
+            proposal,
+            sender,
+            received_at: crate::group::epoch_age::current_time(),
+        };
+
+        self.insert_cached(proposal_ref, cached_proposal);
+    }
+
+    fn insert_cached(&mut self, proposal_ref: ProposalRef, cached_proposal: CachedProposal) {
+        if !self.arrival_order.contains(&proposal_ref) {
+            self.arrival_order.push(proposal_ref.clone());
+        }
 
         #[cfg(feature = "std")]
         self.proposals.insert(proposal_ref, cached_proposal);
@@ -108,26 +149,85 @@ impl ProposalCache {
         self.proposals.push((proposal_ref, cached_proposal));
     }
 
+    pub fn get(&self, proposal_ref: &ProposalRef) -> Option<&CachedProposal> {
+        #[cfg(feature = "std")]
+        return self.proposals.get(proposal_ref);
+
+        #[cfg(not(feature = "std"))]
+        return self
+            .proposals
+            .iter()
+            .find_map(|(r, p)| (r == proposal_ref).then_some(p));
+    }
+
+    /// The cached proposals in order of arrival.
+    pub fn proposals(&self) -> impl Iterator<Item = ProposalInfo<Proposal>> + '_ {
+        self.arrival_order
+            .iter()
+            .filter_map(|r| Some((r, self.get(r)?)))
+            .map(|(r, p)| ProposalInfo {
+                proposal: p.proposal.clone(),
+                sender: p.sender,
+                source: ProposalSource::ByReference(r.clone()),
+                received_epoch: None,
+            })
+    }
+
     pub fn prepare_commit(
         &self,
         sender: Sender,
         additional_proposals: Vec<Proposal>,
     ) -> ProposalBundle {
-        self.proposals
+        self.prepare_commit_with_ttl(sender, additional_proposals, None)
+            .0
+    }
+
+    /// Bundle the cached proposals, in order of arrival, with the
+    /// `additional_proposals` sent by value. Proposals cached more than `ttl`
+    /// ago are left out and returned separately. They stay in the cache, so
+    /// commits of other members referencing them can still be processed.
+    pub fn prepare_commit_with_ttl(
+        &self,
+        sender: Sender,
+        additional_proposals: Vec<Proposal>,
+        ttl: Option<Duration>,
+    ) -> (ProposalBundle, Vec<ProposalInfo<Proposal>>) {
+        let now = ttl.and(crate::group::epoch_age::current_time());
+
+        let is_expired = |p: &CachedProposal| match (now, ttl, p.received_at) {
+            (Some(now), Some(ttl), Some(received_at)) => {
+                now.saturating_sub(received_at) > ttl.as_secs()
+            }
+            _ => false,
+        };
+
+        let mut expired = Vec::new();
+        let mut proposals = ProposalBundle::default();
+
+        for (r, p) in self
+            .arrival_order
             .iter()
-            .map(|(r, p)| {
-                (
-                    p.proposal.clone(),
-                    p.sender,
-                    ProposalSource::ByReference(r.clone()),
-                )
-            })
-            .chain(
-                additional_proposals
-                    .into_iter()
-                    .map(|p| (p, sender, ProposalSource::ByValue)),
-            )
-            .collect()
+            .filter_map(|r| Some((r, self.get(r)?)))
+        {
+            let source = ProposalSource::ByReference(r.clone());
+
+            if is_expired(p) {
+                expired.push(ProposalInfo {
+                    proposal: p.proposal.clone(),
+                    sender: p.sender,
+                    source,
+                    received_epoch: None,
+                });
+            } else {
+                proposals.add(p.proposal.clone(), p.sender, source);
+            }
+        }
+
+        for p in additional_proposals {
+            proposals.add(p, sender, ProposalSource::ByValue);
+        }
+
+        (proposals, expired)
     }
 
     pub fn resolve_for_commit(
@@ -141,19 +241,7 @@ impl ProposalCache {
             match p {
                 ProposalOrRef::Proposal(p) => proposals.add(*p, sender, ProposalSource::ByValue),
                 ProposalOrRef::Reference(r) => {
-                    #[cfg(feature = "std")]
-                    let p = self
-                        .proposals
-                        .get(&r)
-                        .ok_or(MlsError::ProposalNotFound)?
-                        .clone();
-                    #[cfg(not(feature = "std"))]
-                    let p = self
-                        .proposals
-                        .iter()
-                        .find_map(|(rr, p)| (rr == &r).then_some(p))
-                        .ok_or(MlsError::ProposalNotFound)?
-                        .clone();
+                    let p = self.get(&r).ok_or(MlsError::ProposalNotFound)?.clone();
 
                     proposals.add(p.proposal, p.sender, ProposalSource::ByReference(r));
                 }
@@ -208,6 +296,10 @@ impl GroupState {
         user_rules: &F,
         commit_time: Option<MlsTime>,
         direction: CommitDirection,
+        init_key_reuse_policy: InitKeyReusePolicy,
+        #[cfg(feature = "by_ref_proposal")] duplicate_proposal_policy: Option<
+            DuplicateProposalPolicy,
+        >,
     ) -> Result<ProvisionalState, MlsError>
     where
         C: IdentityProvider,
@@ -237,7 +329,13 @@ impl GroupState {
         }?;
 
         proposals = user_rules
-            .filter_proposals(direction, origin, &roster, group_extensions, proposals)
+            .filter_proposals(
+                direction,
+                origin.clone(),
+                &roster,
+                group_extensions,
+                proposals,
+            )
             .await
             .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))?;
 
@@ -249,8 +347,11 @@ impl GroupState {
             external_leaf,
             identity_provider,
             psk_storage,
+            init_key_reuse_policy,
             #[cfg(feature = "by_ref_proposal")]
             &self.context.group_id,
+            #[cfg(feature = "by_ref_proposal")]
+            duplicate_proposal_policy,
         );
 
         #[cfg(feature = "by_ref_proposal")]
@@ -291,12 +392,22 @@ impl GroupState {
         #[cfg(feature = "by_ref_proposal")]
         let proposals = applier_output.applied_proposals;
 
+        if direction == CommitDirection::Receive {
+            for (i, add) in proposals.add_proposals().iter().enumerate() {
+                user_rules
+                    .vet_add(&origin, &roster, group_extensions, add)
+                    .map_err(|e| MlsError::AddRejected(i, e.into_any_error()))?;
+            }
+        }
+
         Ok(ProvisionalState {
             public_tree: applier_output.new_tree,
             group_context,
             applied_proposals: proposals,
             external_init_index: applier_output.external_init_index,
             indexes_of_added_kpkgs: applier_output.indexes_of_added_kpkgs,
+            #[cfg(feature = "state_update")]
+            applied_order: applier_output.applied_order,
             #[cfg(feature = "by_ref_proposal")]
             unused_proposals,
         })
@@ -309,7 +420,7 @@ impl Extend<(ProposalRef, CachedProposal)> for ProposalCache {
     where
         T: IntoIterator<Item = (ProposalRef, CachedProposal)>,
     {
-        self.proposals.extend(iter);
+        iter.into_iter().for_each(|(r, p)| self.insert_cached(r, p));
     }
 }
 
@@ -363,7 +474,11 @@ pub(crate) mod test_utils {
 
     impl CachedProposal {
         pub fn new(proposal: Proposal, sender: Sender) -> Self {
-            Self { proposal, sender }
+            Self {
+                proposal,
+                sender,
+                received_at: None,
+            }
         }
     }
 
@@ -558,6 +673,8 @@ pub(crate) mod test_utils {
                     &user_rules,
                     None,
                     CommitDirection::Receive,
+                    Default::default(),
+                    None,
                 )
                 .await
         }
@@ -602,6 +719,8 @@ pub(crate) mod test_utils {
                     &user_rules,
                     None,
                     CommitDirection::Send,
+                    Default::default(),
+                    None,
                 )
                 .await
         }
@@ -848,6 +967,8 @@ mod tests {
             external_init_index: None,
             indexes_of_added_kpkgs: vec![LeafIndex(1)],
             #[cfg(feature = "state_update")]
+            applied_order: vec![],
+            #[cfg(feature = "state_update")]
             unused_proposals: vec![],
             applied_proposals: bundle,
         };
@@ -905,6 +1026,7 @@ mod tests {
             proposal: p.clone(),
             sender: sender.clone().into(),
             source: ProposalSource::ByReference(make_proposal_ref(p, sender).await),
+            received_epoch: None,
         }
     }
 
@@ -4206,4 +4328,63 @@ mod tests {
 
         assert_eq!(p.proposal_ref(), Some(&proposal_ref));
     }
+
+    #[cfg(feature = "std")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn expired_proposals_are_left_out_of_commit() {
+        let sender = Sender::Member(0);
+        let mut cache = make_proposal_cache();
+
+        let old = Proposal::GroupContextExtensions(Default::default());
+        let old_ref = make_proposal_ref(&old, sender).await;
+        cache.insert(old_ref.clone(), old.clone(), sender);
+
+        let new = Proposal::Remove(RemoveProposal {
+            to_remove: LeafIndex(1),
+        });
+
+        let new_ref = make_proposal_ref(&new, sender).await;
+        cache.insert(new_ref.clone(), new.clone(), sender);
+
+        // Pretend the first proposal was received a day ago.
+        let now = crate::group::epoch_age::current_time().unwrap();
+
+        cache.proposals.insert(
+            old_ref.clone(),
+            CachedProposal {
+                proposal: old.clone(),
+                sender,
+                received_at: Some(now - 86400),
+            },
+        );
+
+        let ttl = Some(core::time::Duration::from_secs(3600));
+        let (bundle, expired) = cache.prepare_commit_with_ttl(sender, vec![], ttl);
+
+        let refs = bundle
+            .iter_proposals()
+            .filter_map(|p| p.proposal_ref().cloned())
+            .collect::<Vec<_>>();
+
+        assert_eq!(refs, vec![new_ref]);
+
+        assert_eq!(
+            expired,
+            vec![ProposalInfo {
+                proposal: old,
+                sender,
+                source: ProposalSource::ByReference(old_ref.clone()),
+                received_epoch: None,
+            }]
+        );
+
+        // The expired proposal is still available to process commits of
+        // other members referencing it.
+        assert!(cache.get(&old_ref).is_some());
+
+        let (bundle, expired) = cache.prepare_commit_with_ttl(sender, vec![], None);
+
+        assert_eq!(bundle.length(), 2);
+        assert!(expired.is_empty());
+    }
 }
@@ -0,0 +1,303 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use mls_rs_codec::MlsSize;
+
+use crate::{
+    group::{
+        proposal::{AddProposal, Proposal, ProposalOrRef, RemoveProposal},
+        proposal_filter::{Proposable, ProposalBundle, ProposalInfo, ProposalSource},
+    },
+    tree_kem::TreeKemPublic,
+};
+
+#[cfg(feature = "by_ref_proposal")]
+use crate::{
+    group::{proposal::UpdateProposal, DuplicateProposalPolicy, Sender},
+    tree_kem::node::LeafIndex,
+};
+
+/// Compaction of the proposals of commits created by a client configured
+/// with
+/// [`ClientBuilder::proposal_compaction`](crate::client_builder::ClientBuilder::proposal_compaction).
+///
+/// Before a commit is created, proposals whose effect is already achieved by
+/// other proposals of the commit are left out of it to reduce its size. The
+/// proposals left out are listed in the [`CompactionReport`] of the
+/// [`CommitOutput`](crate::group::CommitOutput).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ProposalCompaction {
+    /// Leave out Update proposals from members removed by the commit, and
+    /// all but the last Update proposal from each member.
+    pub drop_superseded_updates: bool,
+    /// Leave out all but the first Remove proposal of each member.
+    pub merge_duplicate_removals: bool,
+    /// Leave out Add proposals of key packages with the same credential as a
+    /// member removed by the commit. This is disabled by default since
+    /// replacing the leaf of a member with a remove and an add is a common
+    /// pattern.
+    pub drop_additions_of_removed_identities: bool,
+}
+
+impl Default for ProposalCompaction {
+    fn default() -> Self {
+        Self {
+            drop_superseded_updates: true,
+            merge_duplicate_removals: true,
+            drop_additions_of_removed_identities: false,
+        }
+    }
+}
+
+impl ProposalCompaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_drop_superseded_updates(self, drop_superseded_updates: bool) -> Self {
+        Self {
+            drop_superseded_updates,
+            ..self
+        }
+    }
+
+    pub fn with_merge_duplicate_removals(self, merge_duplicate_removals: bool) -> Self {
+        Self {
+            merge_duplicate_removals,
+            ..self
+        }
+    }
+
+    pub fn with_drop_additions_of_removed_identities(
+        self,
+        drop_additions_of_removed_identities: bool,
+    ) -> Self {
+        Self {
+            drop_additions_of_removed_identities,
+            ..self
+        }
+    }
+
+    pub(crate) fn compact(
+        &self,
+        proposals: &mut ProposalBundle,
+        tree: &TreeKemPublic,
+    ) -> CompactionReport {
+        let mut report = CompactionReport::default();
+
+        if self.merge_duplicate_removals {
+            let mut removed = Vec::new();
+
+            let keep = proposals
+                .remove_proposals()
+                .iter()
+                .map(|p| {
+                    let first = !removed.contains(&p.proposal.to_remove);
+                    removed.push(p.proposal.to_remove);
+                    first
+                })
+                .collect();
+
+            elide::<RemoveProposal>(proposals, keep, &mut report, Proposal::Remove);
+        }
+
+        let removed = proposals
+            .remove_proposals()
+            .iter()
+            .map(|p| p.proposal.to_remove)
+            .collect::<Vec<_>>();
+
+        #[cfg(feature = "by_ref_proposal")]
+        if self.drop_superseded_updates {
+            let senders = proposals.update_proposals().iter().map(|p| p.sender);
+
+            let keep = DuplicateProposalPolicy::LatestWins
+                .retained(senders)
+                .into_iter()
+                .zip(proposals.update_proposals())
+                .map(|(latest, p)| {
+                    latest
+                        && !matches!(p.sender, Sender::Member(i) if removed.contains(&LeafIndex(i)))
+                })
+                .collect();
+
+            elide::<UpdateProposal>(proposals, keep, &mut report, Proposal::Update);
+        }
+
+        if self.drop_additions_of_removed_identities {
+            let removed = removed
+                .iter()
+                .filter_map(|i| tree.get_leaf_node(*i).ok())
+                .map(|leaf| &leaf.signing_identity.credential)
+                .collect::<Vec<_>>();
+
+            let keep = proposals
+                .add_proposals()
+                .iter()
+                .map(|p| {
+                    let credential = &p.proposal.key_package.leaf_node.signing_identity.credential;
+                    !removed.contains(&credential)
+                })
+                .collect();
+
+            elide::<AddProposal>(proposals, keep, &mut report, |p| {
+                Proposal::Add(alloc::boxed::Box::new(p))
+            });
+        }
+
+        report
+    }
+}
+
+fn elide<T: Proposable + Clone>(
+    proposals: &mut ProposalBundle,
+    keep: Vec<bool>,
+    report: &mut CompactionReport,
+    to_proposal: impl Fn(T) -> Proposal,
+) {
+    let mut keep = keep.into_iter();
+
+    T::retain(proposals, |p| {
+        if keep.next().unwrap_or(true) {
+            return true;
+        }
+
+        let p = p.clone().map(&to_proposal);
+
+        report.saved_bytes += match &p.source {
+            ProposalSource::ByValue => ProposalOrRef::from(p.proposal.clone()).mls_encoded_len(),
+            #[cfg(feature = "by_ref_proposal")]
+            ProposalSource::ByReference(r) => ProposalOrRef::Reference(r.clone()).mls_encoded_len(),
+            ProposalSource::Local => 0,
+        };
+
+        report.elided_proposals.push(p);
+
+        false
+    });
+}
+
+/// Proposals left out of a commit by [`ProposalCompaction`].
+#[cfg_attr(
+    all(feature = "ffi", not(test)),
+    safer_ffi_gen::ffi_type(clone, opaque)
+)]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct CompactionReport {
+    /// Proposals left out of the commit. Proposals by reference are not
+    /// reported as [unused](crate::group::CommitOutput::unused_proposals).
+    pub elided_proposals: Vec<ProposalInfo<Proposal>>,
+    /// Encoded size of the proposals and references left out of the commit.
+    pub saved_bytes: usize,
+}
+
+impl CompactionReport {
+    /// Determines if no proposal was left out of the commit.
+    pub fn is_empty(&self) -> bool {
+        self.elided_proposals.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::test_utils::{test_client_with_key_pkg, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::{
+            proposal::Proposal,
+            test_utils::{test_group, test_group_custom_config, TestGroup},
+        },
+    };
+
+    use super::ProposalCompaction;
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn three_member_group(compaction: ProposalCompaction) -> (TestGroup, TestGroup) {
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.proposal_compaction(compaction)
+        })
+        .await;
+
+        let (mut bob, _) = alice.join("bob").await;
+        let (_, commit) = alice.join("carol").await;
+        bob.process_message(commit).await.unwrap();
+
+        (alice, bob)
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn duplicate_removals_are_merged() {
+        let (mut alice, mut bob) = three_member_group(ProposalCompaction::new()).await;
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .remove_member(2)
+            .unwrap()
+            .remove_member(2)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let report = &commit.compaction_report;
+
+        assert_matches!(
+            &report.elided_proposals[..],
+            [p] if matches!(&p.proposal, Proposal::Remove(r) if r.to_remove() == 2)
+        );
+
+        assert!(report.saved_bytes > 0);
+
+        alice.process_pending_commit().await.unwrap();
+        bob.process_message(commit.commit_message).await.unwrap();
+
+        assert_eq!(bob.group.roster().members().len(), 2);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn additions_of_removed_identities_are_dropped_if_configured() {
+        let compaction = ProposalCompaction::new().with_drop_additions_of_removed_identities(true);
+        let (mut alice, _) = three_member_group(compaction).await;
+
+        let (_, key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "carol").await;
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .remove_member(2)
+            .unwrap()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        assert!(commit.welcome_messages.is_empty());
+
+        assert_matches!(
+            &commit.compaction_report.elided_proposals[..],
+            [p] if matches!(&p.proposal, Proposal::Add(_))
+        );
+
+        alice.process_pending_commit().await.unwrap();
+        assert_eq!(alice.group.roster().members().len(), 2);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn nothing_is_elided_without_compaction() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        alice.join("bob").await;
+
+        let commit = alice.group.commit(vec![]).await.unwrap();
+        assert!(commit.compaction_report.is_empty());
+    }
+}
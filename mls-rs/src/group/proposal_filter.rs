@@ -12,12 +12,19 @@ pub mod filtering_lite;
 #[cfg(all(feature = "custom_proposal", not(feature = "by_ref_proposal")))]
 use filtering_lite as filtering;
 
-pub use bundle::{ProposalBundle, ProposalInfo, ProposalSource};
+pub use bundle::{ProposalBundle, ProposalInfo, ProposalSource, PROPOSAL_APPLICATION_ORDER};
+
+pub(crate) use bundle::Proposable;
+
+#[cfg(feature = "by_ref_proposal")]
+pub use bundle::ProposalRejection;
 
 #[cfg(feature = "by_ref_proposal")]
 pub(crate) use filtering::FilterStrategy;
 
-pub(crate) use filtering_common::ProposalApplier;
+pub(crate) use filtering_common::{
+    unsupported_group_extension, validate_new_capabilities, ProposalApplier,
+};
 
 #[cfg(all(feature = "by_ref_proposal", test))]
 pub(crate) use filtering::proposer_can_propose;
@@ -17,7 +17,13 @@ use crate::{
 };
 
 #[cfg(feature = "by_ref_proposal")]
-use crate::group::{proposal_cache::CachedProposal, LeafIndex, ProposalRef, UpdateProposal};
+use crate::{
+    client::MlsError,
+    group::{proposal_cache::CachedProposal, LeafIndex, ProposalRef, UpdateProposal},
+};
+
+#[cfg(feature = "by_ref_proposal")]
+use alloc::string::String;
 
 #[cfg(feature = "psk")]
 use crate::group::PreSharedKeyProposal;
@@ -27,7 +33,25 @@ use crate::group::proposal::CustomProposal;
 
 use crate::group::ExternalInit;
 
-use core::iter::empty;
+use core::iter::{empty, repeat};
+
+/// Order in which the standard proposals of a commit are applied to the
+/// group state, as specified in
+/// [RFC 9420 section 12.3](https://www.rfc-editor.org/rfc/rfc9420.html#section-12.3).
+///
+/// Proposals of the same type are applied in the order they appear in the
+/// commit. ExternalInit and ReInit proposals only affect the key schedule and
+/// the group lifecycle, and custom proposals are applied by the application,
+/// after all proposals modifying the ratchet tree.
+pub const PROPOSAL_APPLICATION_ORDER: [ProposalType; 7] = [
+    ProposalType::GROUP_CONTEXT_EXTENSIONS,
+    ProposalType::UPDATE,
+    ProposalType::REMOVE,
+    ProposalType::ADD,
+    ProposalType::PSK,
+    ProposalType::EXTERNAL_INIT,
+    ProposalType::RE_INIT,
+];
 
 #[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -46,6 +70,9 @@ pub struct ProposalBundle {
     pub(crate) group_context_extensions: Vec<ProposalInfo<ExtensionList>>,
     #[cfg(feature = "custom_proposal")]
     pub(crate) custom_proposals: Vec<ProposalInfo<CustomProposal>>,
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) rejections: Vec<ProposalRejection>,
 }
 
 impl ProposalBundle {
@@ -55,39 +82,46 @@ impl ProposalBundle {
                 proposal: *proposal,
                 sender,
                 source,
+                received_epoch: None,
             }),
             #[cfg(feature = "by_ref_proposal")]
             Proposal::Update(proposal) => self.updates.push(ProposalInfo {
                 proposal,
                 sender,
                 source,
+                received_epoch: None,
             }),
             Proposal::Remove(proposal) => self.removals.push(ProposalInfo {
                 proposal,
                 sender,
                 source,
+                received_epoch: None,
             }),
             #[cfg(feature = "psk")]
             Proposal::Psk(proposal) => self.psks.push(ProposalInfo {
                 proposal,
                 sender,
                 source,
+                received_epoch: None,
             }),
             Proposal::ReInit(proposal) => self.reinitializations.push(ProposalInfo {
                 proposal,
                 sender,
                 source,
+                received_epoch: None,
             }),
             Proposal::ExternalInit(proposal) => self.external_initializations.push(ProposalInfo {
                 proposal,
                 sender,
                 source,
+                received_epoch: None,
             }),
             Proposal::GroupContextExtensions(proposal) => {
                 self.group_context_extensions.push(ProposalInfo {
                     proposal,
                     sender,
                     source,
+                    received_epoch: None,
                 })
             }
             #[cfg(feature = "custom_proposal")]
@@ -95,10 +129,45 @@ impl ProposalBundle {
                 proposal,
                 sender,
                 source,
+                received_epoch: None,
             }),
         }
     }
 
+    /// Types of the proposals in the bundle, in the order in which they are
+    /// applied to the group state.
+    ///
+    /// Standard proposals are ordered according to
+    /// [`PROPOSAL_APPLICATION_ORDER`], followed by custom proposals in the
+    /// order they appear in the bundle.
+    pub fn application_order(&self) -> Vec<ProposalType> {
+        let count = |proposal_type| match proposal_type {
+            ProposalType::GROUP_CONTEXT_EXTENSIONS => self.group_context_extensions.len(),
+            #[cfg(feature = "by_ref_proposal")]
+            ProposalType::UPDATE => self.updates.len(),
+            ProposalType::REMOVE => self.removals.len(),
+            ProposalType::ADD => self.additions.len(),
+            #[cfg(feature = "psk")]
+            ProposalType::PSK => self.psks.len(),
+            ProposalType::EXTERNAL_INIT => self.external_initializations.len(),
+            ProposalType::RE_INIT => self.reinitializations.len(),
+            _ => 0,
+        };
+
+        let order = PROPOSAL_APPLICATION_ORDER
+            .iter()
+            .flat_map(|t| repeat(*t).take(count(*t)));
+
+        #[cfg(feature = "custom_proposal")]
+        let order = order.chain(
+            self.custom_proposals
+                .iter()
+                .map(|p| p.proposal.proposal_type()),
+        );
+
+        order.collect()
+    }
+
     /// Remove the proposal of type `T` at `index`
     ///
     /// Type `T` can be any of the standard MLS proposal types defined in the
@@ -110,6 +179,17 @@ impl ProposalBundle {
         T::remove(self, index);
     }
 
+    /// Remove the proposal of type `T` at `index` and record that it was
+    /// rejected because of `reason`.
+    #[cfg(feature = "by_ref_proposal")]
+    pub(crate) fn reject<T: Proposable>(&mut self, index: usize, reason: &MlsError) {
+        if let Some(proposal) = T::filter(self).get(index) {
+            self.rejections
+                .push(ProposalRejection::new(proposal, reason));
+            T::remove(self, index);
+        }
+    }
+
     /// Iterate over proposals, filtered by type.
     ///
     /// Type `T` can be any of the standard MLS proposal types defined in the
@@ -495,6 +575,10 @@ pub enum ProposalSource {
 #[non_exhaustive]
 /// Proposal description used as input to a
 /// [`MlsRules`](crate::MlsRules).
+///
+/// It is also returned by the group to describe cached, committed and unused
+/// proposals, e.g. by [`Group::proposal_by_ref`](crate::group::Group::proposal_by_ref)
+/// and [`StateUpdate::committed_proposals`](crate::group::StateUpdate::committed_proposals).
 pub struct ProposalInfo<T> {
     /// The underlying proposal value.
     pub proposal: T,
@@ -502,6 +586,9 @@ pub struct ProposalInfo<T> {
     pub sender: Sender,
     /// The source of the proposal.
     pub source: ProposalSource,
+    /// The epoch in which the proposal was sent, if known.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub received_epoch: Option<u64>,
 }
 
 #[cfg_attr(all(feature = "ffi", not(test)), ::safer_ffi_gen::safer_ffi_gen)]
@@ -526,19 +613,42 @@ impl<T> ProposalInfo<T> {
             proposal,
             sender,
             source,
+            received_epoch: None,
         }
     }
 
-    #[cfg(all(feature = "ffi", not(test)))]
+    /// The underlying proposal value.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn proposal(&self) -> &T {
+        &self.proposal
+    }
+
+    /// The sender of this proposal.
     pub fn sender(&self) -> &Sender {
         &self.sender
     }
 
-    #[cfg(all(feature = "ffi", not(test)))]
+    /// The source of the proposal.
     pub fn source(&self) -> &ProposalSource {
         &self.source
     }
 
+    /// The epoch in which the proposal was sent, if known.
+    ///
+    /// The epoch is known for proposals described by the group. It is not
+    /// known for proposals created with [`ProposalInfo::new`].
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn received_epoch(&self) -> Option<u64> {
+        self.received_epoch
+    }
+
+    pub(crate) fn with_received_epoch(self, epoch: u64) -> Self {
+        Self {
+            received_epoch: Some(epoch),
+            ..self
+        }
+    }
+
     #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
     pub fn map<U, F>(self, f: F) -> ProposalInfo<U>
     where
@@ -548,6 +658,7 @@ impl<T> ProposalInfo<T> {
             proposal: f(self.proposal),
             sender: self.sender,
             source: self.source,
+            received_epoch: self.received_epoch,
         }
     }
 
@@ -557,14 +668,18 @@ impl<T> ProposalInfo<T> {
             proposal: &self.proposal,
             sender: self.sender,
             source: self.source.clone(),
+            received_epoch: self.received_epoch,
         }
     }
 
+    /// Determines if the proposal is sent by value with the commit.
     #[inline(always)]
     pub fn is_by_value(&self) -> bool {
         self.source == ProposalSource::ByValue
     }
 
+    /// Determines if the proposal is not sent by value with the commit, i.e.
+    /// it was sent by reference or it is local.
     #[inline(always)]
     pub fn is_by_reference(&self) -> bool {
         !self.is_by_value()
@@ -583,6 +698,43 @@ impl<T> ProposalInfo<T> {
 #[cfg(all(feature = "ffi", not(test)))]
 safer_ffi_gen::specialize!(ProposalInfoFfi = ProposalInfo<Proposal>);
 
+#[cfg(feature = "by_ref_proposal")]
+#[cfg_attr(
+    all(feature = "ffi", not(test)),
+    safer_ffi_gen::ffi_type(clone, opaque)
+)]
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+/// Proposal that was left out of a commit because it failed validation.
+///
+/// Only proposals that are not sent by value with the commit can be
+/// rejected, see [`CommitOutput::rejected_proposals`](crate::group::CommitOutput::rejected_proposals).
+pub struct ProposalRejection {
+    /// The reference of the rejected proposal if it was received by reference.
+    pub proposal_ref: Option<ProposalRef>,
+    /// The sender of the rejected proposal.
+    pub sender: Sender,
+    /// Description of the validation error that caused the rejection.
+    pub reason: String,
+}
+
+#[cfg(feature = "by_ref_proposal")]
+impl ProposalRejection {
+    pub(crate) fn new<T>(proposal: &ProposalInfo<T>, reason: &MlsError) -> Self {
+        #[cfg(feature = "std")]
+        let reason = reason.to_string();
+
+        #[cfg(not(feature = "std"))]
+        let reason = alloc::format!("{reason:?}");
+
+        Self {
+            proposal_ref: proposal.proposal_ref().cloned(),
+            sender: proposal.sender,
+            reason,
+        }
+    }
+}
+
 pub trait Proposable: Sized {
     const TYPE: ProposalType;
 
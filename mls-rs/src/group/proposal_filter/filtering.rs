@@ -6,8 +6,8 @@ use crate::{
     client::MlsError,
     group::{
         proposal::ReInitProposal,
-        proposal_filter::{ProposalBundle, ProposalInfo},
-        AddProposal, ProposalType, RemoveProposal, Sender, UpdateProposal,
+        proposal_filter::{bundle::Proposable, ProposalBundle, ProposalInfo, ProposalRejection},
+        AddProposal, DuplicateProposalPolicy, ProposalType, RemoveProposal, Sender, UpdateProposal,
     },
     iter::wrap_iter,
     protocol_version::ProtocolVersion,
@@ -59,6 +59,9 @@ where
         proposals: ProposalBundle,
         commit_time: Option<MlsTime>,
     ) -> Result<ApplyProposalsOutput, MlsError> {
+        let proposals =
+            filter_out_duplicate_proposals(strategy, self.duplicate_proposal_policy, proposals)?;
+
         let proposals = filter_out_invalid_proposers(strategy, proposals)?;
 
         let mut proposals: ProposalBundle =
@@ -158,6 +161,8 @@ where
             indexes_of_added_kpkgs: added,
             external_init_index: None,
             new_context_extensions,
+            #[cfg(feature = "state_update")]
+            applied_order: Vec::new(),
         })
     }
 
@@ -209,26 +214,32 @@ where
                 };
 
                 apply_strategy(strategy, p.is_by_reference(), res)
-                    .map(|b| (!b).then_some(i))
+                    .map(|e| e.map(|e| (i, e)))
                     .transpose()
             })
             .try_collect()
             .await?;
 
-        bad_indices.into_iter().rev().for_each(|i| {
-            proposals.remove::<UpdateProposal>(i);
+        bad_indices.into_iter().rev().for_each(|(i, e)| {
+            proposals.reject::<UpdateProposal>(i, &e);
             proposals.update_senders.remove(i);
         });
 
-        let bad_indices: Vec<_> = wrap_iter(proposals.add_proposals())
+        let adds = proposals.add_proposals();
+
+        let bad_indices: Vec<_> = wrap_iter(adds)
             .enumerate()
             .filter_map(|(i, p)| async move {
                 let res = self
                     .validate_new_node(leaf_node_validator, &p.proposal.key_package, commit_time)
-                    .await;
+                    .await
+                    .and_then(|_| {
+                        self.init_key_reuse_policy
+                            .check(self.original_tree, adds, i)
+                    });
 
                 apply_strategy(strategy, p.is_by_reference(), res)
-                    .map(|b| (!b).then_some(i))
+                    .map(|e| e.map(|e| (i, e)))
                     .transpose()
             })
             .try_collect()
@@ -237,7 +248,7 @@ where
         bad_indices
             .into_iter()
             .rev()
-            .for_each(|i| proposals.remove::<AddProposal>(i));
+            .for_each(|(i, e)| proposals.reject::<AddProposal>(i, &e));
 
         Ok(proposals)
     }
@@ -265,13 +276,92 @@ impl FilterStrategy {
     }
 }
 
+/// Returns the error of `r` if the proposal is invalid but can be ignored
+/// according to `strategy`, and `None` if it is valid.
 pub(crate) fn apply_strategy(
     strategy: FilterStrategy,
     by_ref: bool,
     r: Result<(), MlsError>,
-) -> Result<bool, MlsError> {
-    r.map(|_| true)
-        .or_else(|error| strategy.ignore(by_ref).then_some(false).ok_or(error))
+) -> Result<Option<MlsError>, MlsError> {
+    match r {
+        Ok(()) => Ok(None),
+        Err(error) if strategy.ignore(by_ref) => Ok(Some(error)),
+        Err(error) => Err(error),
+    }
+}
+
+fn retain_valid<T, F>(
+    strategy: FilterStrategy,
+    proposals: &mut ProposalBundle,
+    mut f: F,
+) -> Result<(), MlsError>
+where
+    T: Proposable,
+    F: FnMut(&ProposalInfo<T>) -> Result<(), MlsError>,
+{
+    let mut rejections = Vec::new();
+
+    proposals.retain_by_type::<T, _, MlsError>(|p| {
+        let rejection = apply_strategy(strategy, p.is_by_reference(), f(p))?;
+
+        Ok(rejection
+            .map(|e| rejections.push(ProposalRejection::new(p, &e)))
+            .is_none())
+    })?;
+
+    proposals.rejections.append(&mut rejections);
+
+    Ok(())
+}
+
+fn filter_out_duplicate_proposals(
+    strategy: FilterStrategy,
+    policy: Option<DuplicateProposalPolicy>,
+    mut proposals: ProposalBundle,
+) -> Result<ProposalBundle, MlsError> {
+    let Some(policy) = policy else {
+        return Ok(proposals);
+    };
+
+    let senders = proposals.update_proposals().iter().map(|p| p.sender);
+    let mut retained = policy.retained(senders).into_iter();
+
+    retain_valid::<UpdateProposal, _>(strategy, &mut proposals, |_| {
+        retained
+            .next()
+            .unwrap_or(true)
+            .then_some(())
+            .ok_or(MlsError::DuplicateProposalFromSender(ProposalType::UPDATE))
+    })?;
+
+    #[cfg(feature = "custom_proposal")]
+    {
+        let keys = proposals
+            .custom_proposals()
+            .iter()
+            .map(|p| (p.sender, p.proposal.proposal_type()));
+
+        let mut retained = policy.retained(keys).into_iter();
+        let mut rejections = Vec::new();
+
+        proposals.retain_custom(|p| {
+            let res = retained.next().unwrap_or(true).then_some(()).ok_or(
+                MlsError::DuplicateProposalFromSender(p.proposal.proposal_type()),
+            );
+
+            let rejection = apply_strategy(strategy, p.is_by_reference(), res)?;
+
+            Ok::<_, MlsError>(
+                rejection
+                    .map(|e| rejections.push(ProposalRejection::new(p, &e)))
+                    .is_none(),
+            )
+        })?;
+
+        proposals.rejections.append(&mut rejections);
+    }
+
+    Ok(proposals)
 }
 
 fn filter_out_update_for_committer(
@@ -279,14 +369,10 @@ fn filter_out_update_for_committer(
     commit_sender: LeafIndex,
     mut proposals: ProposalBundle,
 ) -> Result<ProposalBundle, MlsError> {
-    proposals.retain_by_type::<UpdateProposal, _, _>(|p| {
-        apply_strategy(
-            strategy,
-            p.is_by_reference(),
-            (p.sender != Sender::Member(*commit_sender))
-                .then_some(())
-                .ok_or(MlsError::InvalidCommitSelfUpdate),
-        )
+    retain_valid::<UpdateProposal, _>(strategy, &mut proposals, |p| {
+        (p.sender != Sender::Member(*commit_sender))
+            .then_some(())
+            .ok_or(MlsError::InvalidCommitSelfUpdate)
     })?;
     Ok(proposals)
 }
@@ -296,14 +382,10 @@ fn filter_out_removal_of_committer(
     commit_sender: LeafIndex,
     mut proposals: ProposalBundle,
 ) -> Result<ProposalBundle, MlsError> {
-    proposals.retain_by_type::<RemoveProposal, _, _>(|p| {
-        apply_strategy(
-            strategy,
-            p.is_by_reference(),
-            (p.proposal.to_remove != commit_sender)
-                .then_some(())
-                .ok_or(MlsError::CommitterSelfRemoval),
-        )
+    retain_valid::<RemoveProposal, _>(strategy, &mut proposals, |p| {
+        (p.proposal.to_remove != commit_sender)
+            .then_some(())
+            .ok_or(MlsError::CommitterSelfRemoval)
     })?;
     Ok(proposals)
 }
@@ -333,15 +415,15 @@ where
             Err(e) => Err(MlsError::from(e)),
         };
 
-        if !apply_strategy(strategy, p.is_by_reference(), res)? {
-            bad_indices.push(i);
+        if let Some(e) = apply_strategy(strategy, p.is_by_reference(), res)? {
+            bad_indices.push((i, e));
         }
     }
 
     bad_indices
         .into_iter()
         .rev()
-        .for_each(|i| proposals.remove::<ExtensionList>(i));
+        .for_each(|(i, e)| proposals.reject::<ExtensionList>(i, &e));
 
     Ok(proposals)
 }
@@ -352,14 +434,10 @@ fn filter_out_extra_group_context_extensions(
 ) -> Result<ProposalBundle, MlsError> {
     let mut found = false;
 
-    proposals.retain_by_type::<ExtensionList, _, _>(|p| {
-        apply_strategy(
-            strategy,
-            p.is_by_reference(),
-            (!core::mem::replace(&mut found, true))
-                .then_some(())
-                .ok_or(MlsError::MoreThanOneGroupContextExtensionsProposal),
-        )
+    retain_valid::<ExtensionList, _>(strategy, &mut proposals, |_| {
+        (!core::mem::replace(&mut found, true))
+            .then_some(())
+            .ok_or(MlsError::MoreThanOneGroupContextExtensionsProposal)
     })?;
 
     Ok(proposals)
@@ -370,14 +448,10 @@ fn filter_out_invalid_reinit(
     mut proposals: ProposalBundle,
     protocol_version: ProtocolVersion,
 ) -> Result<ProposalBundle, MlsError> {
-    proposals.retain_by_type::<ReInitProposal, _, _>(|p| {
-        apply_strategy(
-            strategy,
-            p.is_by_reference(),
-            (p.proposal.version >= protocol_version)
-                .then_some(())
-                .ok_or(MlsError::InvalidProtocolVersionInReInit),
-        )
+    retain_valid::<ReInitProposal, _>(strategy, &mut proposals, |p| {
+        (p.proposal.version >= protocol_version)
+            .then_some(())
+            .ok_or(MlsError::InvalidProtocolVersionInReInit)
     })?;
 
     Ok(proposals)
@@ -400,11 +474,10 @@ fn filter_out_reinit_if_other_proposals(
         }
 
         let has_other_proposal_type = proposal_count > proposals.reinit_proposals().len();
+        let keep = usize::from(!has_other_proposal_type);
 
-        if has_other_proposal_type {
-            proposals.reinitializations = Vec::new();
-        } else {
-            proposals.reinitializations.truncate(1);
+        for i in (keep..proposals.reinit_proposals().len()).rev() {
+            proposals.reject::<ReInitProposal>(i, &MlsError::OtherProposalWithReInit);
         }
     }
 
@@ -415,12 +488,8 @@ fn filter_out_external_init(
     strategy: FilterStrategy,
     mut proposals: ProposalBundle,
 ) -> Result<ProposalBundle, MlsError> {
-    proposals.retain_by_type::<ExternalInit, _, _>(|p| {
-        apply_strategy(
-            strategy,
-            p.is_by_reference(),
-            Err(MlsError::InvalidProposalTypeForSender),
-        )
+    retain_valid::<ExternalInit, _>(strategy, &mut proposals, |_| {
+        Err(MlsError::InvalidProposalTypeForSender)
     })?;
 
     Ok(proposals)
@@ -482,8 +551,8 @@ pub(crate) fn filter_out_invalid_proposers(
         let p = &proposals.add_proposals()[i];
         let res = proposer_can_propose(p.sender, ProposalType::ADD, p.is_by_reference());
 
-        if !apply_strategy(strategy, p.is_by_reference(), res)? {
-            proposals.remove::<AddProposal>(i);
+        if let Some(e) = apply_strategy(strategy, p.is_by_reference(), res)? {
+            proposals.reject::<AddProposal>(i, &e);
         }
     }
 
@@ -491,8 +560,8 @@ pub(crate) fn filter_out_invalid_proposers(
         let p = &proposals.update_proposals()[i];
         let res = proposer_can_propose(p.sender, ProposalType::UPDATE, p.is_by_reference());
 
-        if !apply_strategy(strategy, p.is_by_reference(), res)? {
-            proposals.remove::<UpdateProposal>(i);
+        if let Some(e) = apply_strategy(strategy, p.is_by_reference(), res)? {
+            proposals.reject::<UpdateProposal>(i, &e);
             proposals.update_senders.remove(i);
         }
     }
@@ -501,8 +570,8 @@ pub(crate) fn filter_out_invalid_proposers(
         let p = &proposals.remove_proposals()[i];
         let res = proposer_can_propose(p.sender, ProposalType::REMOVE, p.is_by_reference());
 
-        if !apply_strategy(strategy, p.is_by_reference(), res)? {
-            proposals.remove::<RemoveProposal>(i);
+        if let Some(e) = apply_strategy(strategy, p.is_by_reference(), res)? {
+            proposals.reject::<RemoveProposal>(i, &e);
         }
     }
 
@@ -511,8 +580,8 @@ pub(crate) fn filter_out_invalid_proposers(
         let p = &proposals.psk_proposals()[i];
         let res = proposer_can_propose(p.sender, ProposalType::PSK, p.is_by_reference());
 
-        if !apply_strategy(strategy, p.is_by_reference(), res)? {
-            proposals.remove::<PreSharedKeyProposal>(i);
+        if let Some(e) = apply_strategy(strategy, p.is_by_reference(), res)? {
+            proposals.reject::<PreSharedKeyProposal>(i, &e);
         }
     }
 
@@ -520,8 +589,8 @@ pub(crate) fn filter_out_invalid_proposers(
         let p = &proposals.reinit_proposals()[i];
         let res = proposer_can_propose(p.sender, ProposalType::RE_INIT, p.is_by_reference());
 
-        if !apply_strategy(strategy, p.is_by_reference(), res)? {
-            proposals.remove::<ReInitProposal>(i);
+        if let Some(e) = apply_strategy(strategy, p.is_by_reference(), res)? {
+            proposals.reject::<ReInitProposal>(i, &e);
         }
     }
 
@@ -529,8 +598,8 @@ pub(crate) fn filter_out_invalid_proposers(
         let p = &proposals.external_init_proposals()[i];
         let res = proposer_can_propose(p.sender, ProposalType::EXTERNAL_INIT, p.is_by_reference());
 
-        if !apply_strategy(strategy, p.is_by_reference(), res)? {
-            proposals.remove::<ExternalInit>(i);
+        if let Some(e) = apply_strategy(strategy, p.is_by_reference(), res)? {
+            proposals.reject::<ExternalInit>(i, &e);
         }
     }
 
@@ -539,8 +608,8 @@ pub(crate) fn filter_out_invalid_proposers(
         let gce_type = ProposalType::GROUP_CONTEXT_EXTENSIONS;
         let res = proposer_can_propose(p.sender, gce_type, p.is_by_reference());
 
-        if !apply_strategy(strategy, p.is_by_reference(), res)? {
-            proposals.remove::<ExtensionList>(i);
+        if let Some(e) = apply_strategy(strategy, p.is_by_reference(), res)? {
+            proposals.reject::<ExtensionList>(i, &e);
         }
     }
 
@@ -565,16 +634,28 @@ pub(super) fn filter_out_unsupported_custom_proposals(
         .filter(|t| tree.can_support_proposal(*t))
         .collect_vec();
 
+    let mut rejections = Vec::new();
+
     proposals.retain_custom(|p| {
         let proposal_type = p.proposal.proposal_type();
 
-        apply_strategy(
+        let rejection = apply_strategy(
             strategy,
             p.is_by_reference(),
             supported_types
                 .contains(&proposal_type)
                 .then_some(())
                 .ok_or(MlsError::UnsupportedCustomProposal(proposal_type)),
+        )?;
+
+        Ok::<_, MlsError>(
+            rejection
+                .map(|e| rejections.push(ProposalRejection::new(p, &e)))
+                .is_none(),
         )
-    })
+    })?;
+
+    proposals.rejections.append(&mut rejections);
+
+    Ok(())
 }
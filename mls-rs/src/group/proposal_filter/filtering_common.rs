@@ -23,14 +23,14 @@ use super::ProposalInfo;
 use crate::extension::{MlsExtension, RequiredCapabilitiesExt};
 
 #[cfg(feature = "by_ref_proposal")]
-use crate::extension::ExternalSendersExt;
+use crate::{extension::ExternalSendersExt, group::DuplicateProposalPolicy};
 
-use mls_rs_core::error::IntoAnyError;
+use mls_rs_core::{error::IntoAnyError, extension::ExtensionType};
 
 use alloc::vec::Vec;
 use mls_rs_core::{identity::IdentityProvider, psk::PreSharedKeyStorage};
 
-use crate::group::{ExternalInit, ProposalType, RemoveProposal};
+use crate::group::{ExternalInit, InitKeyReusePolicy, ProposalType, RemoveProposal};
 
 #[cfg(all(feature = "by_ref_proposal", feature = "psk"))]
 use crate::group::proposal::PreSharedKeyProposal;
@@ -56,8 +56,11 @@ pub(crate) struct ProposalApplier<'a, C, P, CSP> {
     pub external_leaf: Option<&'a LeafNode>,
     pub identity_provider: &'a C,
     pub psk_storage: &'a P,
+    pub init_key_reuse_policy: InitKeyReusePolicy,
     #[cfg(feature = "by_ref_proposal")]
     pub group_id: &'a [u8],
+    #[cfg(feature = "by_ref_proposal")]
+    pub duplicate_proposal_policy: Option<DuplicateProposalPolicy>,
 }
 
 #[derive(Debug)]
@@ -68,6 +71,9 @@ pub(crate) struct ApplyProposalsOutput {
     #[cfg(feature = "by_ref_proposal")]
     pub(crate) applied_proposals: ProposalBundle,
     pub(crate) new_context_extensions: Option<ExtensionList>,
+    /// Types of the applied proposals, in the order they were applied.
+    #[cfg(feature = "state_update")]
+    pub(crate) applied_order: Vec<ProposalType>,
 }
 
 impl<'a, C, P, CSP> ProposalApplier<'a, C, P, CSP>
@@ -85,7 +91,11 @@ where
         external_leaf: Option<&'a LeafNode>,
         identity_provider: &'a C,
         psk_storage: &'a P,
+        init_key_reuse_policy: InitKeyReusePolicy,
         #[cfg(feature = "by_ref_proposal")] group_id: &'a [u8],
+        #[cfg(feature = "by_ref_proposal")] duplicate_proposal_policy: Option<
+            DuplicateProposalPolicy,
+        >,
     ) -> Self {
         Self {
             original_tree,
@@ -95,8 +105,11 @@ where
             external_leaf,
             identity_provider,
             psk_storage,
+            init_key_reuse_policy,
             #[cfg(feature = "by_ref_proposal")]
             group_id,
+            #[cfg(feature = "by_ref_proposal")]
+            duplicate_proposal_policy,
         }
     }
 
@@ -109,7 +122,11 @@ where
         #[cfg(feature = "by_ref_proposal")] proposals: ProposalBundle,
         commit_time: Option<MlsTime>,
     ) -> Result<ApplyProposalsOutput, MlsError> {
-        let output = match commit_sender {
+        #[cfg_attr(
+            not(any(feature = "state_update", feature = "custom_proposal")),
+            allow(unused_mut)
+        )]
+        let mut output = match commit_sender {
             Sender::Member(sender) => {
                 self.apply_proposals_from_member(
                     #[cfg(feature = "by_ref_proposal")]
@@ -130,9 +147,6 @@ where
             Sender::NewMemberProposal => Err(MlsError::ExternalSenderCannotCommit),
         }?;
 
-        #[cfg(all(feature = "by_ref_proposal", feature = "custom_proposal"))]
-        let mut output = output;
-
         #[cfg(all(feature = "by_ref_proposal", feature = "custom_proposal"))]
         filter_out_unsupported_custom_proposals(
             &mut output.applied_proposals,
@@ -143,6 +157,16 @@ where
         #[cfg(all(not(feature = "by_ref_proposal"), feature = "custom_proposal"))]
         filter_out_unsupported_custom_proposals(proposals, &output.new_tree)?;
 
+        #[cfg(all(feature = "state_update", feature = "by_ref_proposal"))]
+        {
+            output.applied_order = output.applied_proposals.application_order();
+        }
+
+        #[cfg(all(feature = "state_update", not(feature = "by_ref_proposal")))]
+        {
+            output.applied_order = proposals.application_order();
+        }
+
         Ok(output)
     }
 
@@ -259,28 +283,16 @@ where
             output
                 .new_tree
                 .non_empty_leaves()
-                .try_for_each(|(_, leaf)| {
-                    leaf_validator.validate_required_capabilities(leaf)?;
-
-                    #[cfg(feature = "by_ref_proposal")]
-                    leaf_validator.validate_external_senders_ext_credentials(leaf)?;
-
-                    Ok(())
-                })
+                .try_for_each(|(_, leaf)| validate_new_capabilities(&leaf_validator, leaf))
         } else {
             Ok(())
         };
 
-        let new_extensions_supported = group_context_extensions_proposal
-            .proposal
-            .iter()
-            .map(|extension| extension.extension_type)
-            .filter(|&ext_type| !ext_type.is_default())
-            .find(|ext_type| {
-                !output
-                    .new_tree
-                    .non_empty_leaves()
-                    .all(|(_, leaf)| leaf.capabilities.extensions.contains(ext_type))
+        let new_extensions_supported = output
+            .new_tree
+            .non_empty_leaves()
+            .find_map(|(_, leaf)| {
+                unsupported_group_extension(leaf, &group_context_extensions_proposal.proposal)
             })
             .map_or(Ok(()), |ext| Err(MlsError::UnsupportedGroupExtension(ext)));
 
@@ -298,7 +310,9 @@ where
             Ok(()) => Ok(output),
             Err(e) => {
                 if strategy.ignore(group_context_extensions_proposal.is_by_reference()) {
-                    proposals_clone.group_context_extensions.clear();
+                    for i in (0..proposals_clone.group_context_extensions.len()).rev() {
+                        proposals_clone.reject::<ExtensionList>(i, &e);
+                    }
 
                     self.apply_tree_changes(
                         strategy,
@@ -442,8 +456,8 @@ where
                 external_id_is_valid
             };
 
-            if !apply_strategy(strategy, p.is_by_reference(), res)? {
-                bad_indices.push(i)
+            if let Some(e) = apply_strategy(strategy, p.is_by_reference(), res)? {
+                bad_indices.push((i, e))
             }
         }
 
@@ -455,7 +469,7 @@ where
     bad_indices
         .into_iter()
         .rev()
-        .for_each(|i| proposals.remove::<PreSharedKeyProposal>(i));
+        .for_each(|(i, e)| proposals.reject::<PreSharedKeyProposal>(i, &e));
 
     Ok(())
 }
@@ -577,3 +591,33 @@ async fn insert_external_leaf<I: IdentityProvider>(
     tree.add_leaf(leaf_node, identity_provider, extensions, None)
         .await
 }
+
+/// Check that `leaf` satisfies the required capabilities and external
+/// senders of the group context extensions given to `leaf_validator`.
+pub(crate) fn validate_new_capabilities<C, CP>(
+    leaf_validator: &LeafNodeValidator<'_, C, CP>,
+    leaf: &LeafNode,
+) -> Result<(), MlsError>
+where
+    C: IdentityProvider,
+    CP: CipherSuiteProvider,
+{
+    leaf_validator.validate_required_capabilities(leaf)?;
+
+    #[cfg(feature = "by_ref_proposal")]
+    leaf_validator.validate_external_senders_ext_credentials(leaf)?;
+
+    Ok(())
+}
+
+/// First non-default extension type in `extensions` that `leaf` does not
+/// advertise in its capabilities.
+pub(crate) fn unsupported_group_extension(
+    leaf: &LeafNode,
+    extensions: &ExtensionList,
+) -> Option<ExtensionType> {
+    extensions
+        .iter()
+        .map(|extension| extension.extension_type)
+        .find(|ext_type| !ext_type.is_default() && !leaf.capabilities.extensions.contains(ext_type))
+}
@@ -114,6 +114,8 @@ where
             indexes_of_added_kpkgs: added,
             external_init_index: None,
             new_context_extensions,
+            #[cfg(feature = "state_update")]
+            applied_order: alloc::vec::Vec::new(),
         })
     }
 
@@ -130,6 +132,11 @@ where
             Some(group_extensions_in_use),
         );
 
+        (0..proposals.add_proposals().len()).try_for_each(|i| {
+            self.init_key_reuse_policy
+                .check(self.original_tree, proposals.add_proposals(), i)
+        })?;
+
         let adds = wrap_iter(proposals.add_proposals());
 
         #[cfg(mls_build_async)]
@@ -40,6 +40,32 @@ impl ProposalRef {
         ))
     }
 
+    /// Compute the reference of the proposal contained in `message`.
+    ///
+    /// The reference can be used to match the proposal with proposals
+    /// committed by reference, e.g. using
+    /// [`Group::proposal_by_ref`](crate::Group::proposal_by_ref). This function
+    /// returns [`MlsError::UnexpectedMessageType`] if `message` is not a
+    /// proposal sent as a public message. The reference of a proposal sent as a
+    /// private message can only be computed by group members after decrypting
+    /// it.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn compute<CS: CipherSuiteProvider>(
+        cipher_suite_provider: &CS,
+        message: &MlsMessage,
+    ) -> Result<Self, MlsError> {
+        let MlsMessagePayload::Plain(public_message) = &message.payload else {
+            return Err(MlsError::UnexpectedMessageType);
+        };
+
+        if !matches!(public_message.content.content, Content::Proposal(_)) {
+            return Err(MlsError::UnexpectedMessageType);
+        }
+
+        Self::from_content(cipher_suite_provider, &public_message.clone().into()).await
+    }
+
     pub fn as_slice(&self) -> &[u8] {
         &self.0
     }
@@ -83,11 +109,13 @@ mod test {
     use super::test_utils::auth_content_from_proposal;
     use super::*;
     use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
         crypto::test_utils::{test_cipher_suite_provider, try_test_cipher_suite_provider},
-        key_package::test_utils::test_key_package,
+        key_package::test_utils::{test_key_package, test_key_package_message},
         tree_kem::leaf_node::test_utils::get_basic_test_node,
     };
     use alloc::boxed::Box;
+    use assert_matches::assert_matches;
 
     use crate::extension::RequiredCapabilitiesExt;
 
@@ -223,4 +251,43 @@ mod test {
             assert_eq!(expected_out, proposal_ref);
         }
     }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn computing_ref_of_proposal_message() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let content = auth_content_from_proposal(
+            Proposal::Remove(RemoveProposal {
+                to_remove: LeafIndex(1),
+            }),
+            LeafIndex(0),
+        );
+
+        let expected = ProposalRef::from_content(&cs, &content).await.unwrap();
+
+        let message = MlsMessage {
+            version: TEST_PROTOCOL_VERSION,
+            payload: MlsMessagePayload::Plain(PublicMessage {
+                content: content.content,
+                auth: content.auth,
+                membership_tag: None,
+            }),
+        };
+
+        let computed = ProposalRef::compute(&cs, &message).await.unwrap();
+
+        assert_eq!(computed, expected);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn computing_ref_of_non_proposal_message_fails() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let message =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "alice").await;
+
+        let res = ProposalRef::compute(&cs, &message).await;
+
+        assert_matches!(res, Err(MlsError::UnexpectedMessageType));
+    }
 }
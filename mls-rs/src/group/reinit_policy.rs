@@ -0,0 +1,116 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use crate::{client::MlsError, CipherSuite};
+
+/// Policy on the cipher suite a group may be re-initialized with using a
+/// ReInit proposal.
+///
+/// A malicious or compromised member could use a ReInit proposal to move the
+/// group to a weaker cipher suite. The policy is checked when creating a
+/// ReInit proposal and when processing a commit containing one. A violation
+/// is reported as [`MlsError::ReInitDowngradeRejected`]. By default, any
+/// cipher suite is accepted.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ReInitCipherSuitePolicy {
+    strength_order: Vec<CipherSuite>,
+    allowed: Option<Vec<CipherSuite>>,
+}
+
+impl ReInitCipherSuitePolicy {
+    /// Create a policy accepting any cipher suite.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Rank cipher suites by `order`, listed from weakest to strongest, and
+    /// reject moving the group to a cipher suite ranked below its current
+    /// one. Cipher suites missing from `order` are ranked below all listed
+    /// cipher suites.
+    pub fn with_strength_order(self, order: Vec<CipherSuite>) -> Self {
+        Self {
+            strength_order: order,
+            ..self
+        }
+    }
+
+    /// Only accept moving the group to a cipher suite in `allowed`.
+    pub fn with_allowed_cipher_suites(self, allowed: Vec<CipherSuite>) -> Self {
+        Self {
+            allowed: Some(allowed),
+            ..self
+        }
+    }
+
+    /// Check that a group using `current` may be re-initialized with `new`.
+    pub fn check(&self, current: CipherSuite, new: CipherSuite) -> Result<(), MlsError> {
+        let allowed = self
+            .allowed
+            .as_ref()
+            .map_or(true, |allowed| allowed.contains(&new));
+
+        let rank = |cs| self.strength_order.iter().position(|c| *c == cs);
+
+        let downgrade =
+            !self.strength_order.is_empty() && new != current && rank(new) < rank(current);
+
+        (allowed && !downgrade)
+            .then_some(())
+            .ok_or(MlsError::ReInitDowngradeRejected(new))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use super::ReInitCipherSuitePolicy;
+
+    use crate::{client::MlsError, CipherSuite};
+
+    const WEAK: CipherSuite = CipherSuite::CURVE25519_AES128;
+    const STRONG: CipherSuite = CipherSuite::CURVE448_AES256;
+    const UNLISTED: CipherSuite = CipherSuite::P256_AES128;
+
+    #[test]
+    fn default_policy_accepts_any_cipher_suite() {
+        let policy = ReInitCipherSuitePolicy::new();
+
+        assert!(policy.check(STRONG, WEAK).is_ok());
+    }
+
+    #[test]
+    fn strength_order_rejects_downgrades() {
+        let policy = ReInitCipherSuitePolicy::new().with_strength_order(vec![WEAK, STRONG]);
+
+        assert!(policy.check(WEAK, STRONG).is_ok());
+        assert!(policy.check(STRONG, STRONG).is_ok());
+
+        assert_matches!(
+            policy.check(STRONG, WEAK),
+            Err(MlsError::ReInitDowngradeRejected(WEAK))
+        );
+
+        assert_matches!(
+            policy.check(WEAK, UNLISTED),
+            Err(MlsError::ReInitDowngradeRejected(UNLISTED))
+        );
+    }
+
+    #[test]
+    fn allowlist_rejects_other_cipher_suites() {
+        let policy = ReInitCipherSuitePolicy::new().with_allowed_cipher_suites(vec![STRONG]);
+
+        assert!(policy.check(WEAK, STRONG).is_ok());
+
+        assert_matches!(
+            policy.check(STRONG, WEAK),
+            Err(MlsError::ReInitDowngradeRejected(WEAK))
+        );
+    }
+}
@@ -16,6 +16,7 @@ use crate::{client::MlsError, Client, Group, MlsMessage};
 use super::{
     proposal::ReInitProposal, ClientConfig, ExportedTree, JustPreSharedKeyID, MessageProcessor,
     NewMemberInfo, PreSharedKeyID, PskGroupId, PskSecretInput, ResumptionPSKUsage, ResumptionPsk,
+    Validated,
 };
 
 struct ResumptionGroupParameters<'a> {
@@ -282,8 +283,14 @@ async fn resumption_join_group<C: ClientConfig + Clone>(
 ) -> Result<(Group<C>, NewMemberInfo), MlsError> {
     let psk_input = Some(psk_input);
 
-    let (group, new_member_info) =
-        Group::<C>::from_welcome_message(welcome, tree_data, config, signer, psk_input).await?;
+    let (group, new_member_info) = Group::<C>::from_welcome_message(
+        Validated::check(welcome)?,
+        tree_data,
+        config,
+        signer,
+        psk_input,
+    )
+    .await?;
 
     if group.protocol_version() != expected_new_group_params.version {
         Err(MlsError::ProtocolVersionMismatch)
@@ -0,0 +1,250 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use mls_rs_core::{error::IntoAnyError, identity::IdentityProvider, time::MlsTime};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{
+        member_from_leaf_node,
+        proposal::{Proposal, RemoveProposal},
+        Group, Member,
+    },
+    tree_kem::node::LeafIndex,
+};
+
+/// Outcome of [`Group::revalidate_members`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct RevalidationReport {
+    /// Members whose credential is no longer accepted by the
+    /// [`IdentityProvider`](crate::IdentityProvider), in order of leaf index.
+    pub invalid_members: Vec<InvalidMember>,
+    self_index: u32,
+}
+
+/// Member whose credential was rejected during
+/// [revalidation](Group::revalidate_members).
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct InvalidMember {
+    /// The rejected member.
+    pub member: Member,
+    /// Error returned by the identity provider, e.g. because the certificate
+    /// of the member expired or was revoked.
+    pub error: MlsError,
+}
+
+impl RevalidationReport {
+    /// Determines if all members are still valid.
+    pub fn is_empty(&self) -> bool {
+        self.invalid_members.is_empty()
+    }
+
+    /// Remove proposals for all invalid members other than this member, to
+    /// be committed with e.g.
+    /// [`CommitBuilder::raw_proposals`](crate::group::CommitBuilder::raw_proposals).
+    ///
+    /// If the credential of this member is invalid, it must be replaced with
+    /// a commit updating the leaf of this member or this member must leave
+    /// the group.
+    pub fn removals(&self) -> Vec<Proposal> {
+        self.invalid_members
+            .iter()
+            .filter(|invalid| invalid.member.index != self.self_index)
+            .map(|invalid| {
+                Proposal::Remove(RemoveProposal {
+                    to_remove: LeafIndex(invalid.member.index),
+                })
+            })
+            .collect()
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Validate the credentials of all members again with
+    /// [`IdentityProvider::validate_member`](crate::IdentityProvider::validate_member),
+    /// given `timestamp` and the current group context extensions.
+    ///
+    /// Credentials are only validated when members join or update their
+    /// leaf. Revalidating them, e.g. periodically or after a revocation list
+    /// was updated, detects members whose credential expired or was revoked
+    /// since. Pass the current time as `timestamp` to detect expired
+    /// credentials.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn revalidate_members(&self, timestamp: Option<MlsTime>) -> RevalidationReport {
+        let identity_provider = self.config.identity_provider();
+        let extensions = &self.context().extensions;
+        let mut invalid_members = Vec::new();
+
+        for (index, leaf) in self.state.public_tree.non_empty_leaves() {
+            let res = identity_provider
+                .validate_member(&leaf.signing_identity, timestamp, Some(extensions))
+                .await;
+
+            if let Err(e) = res {
+                invalid_members.push(InvalidMember {
+                    member: member_from_leaf_node(leaf, index),
+                    error: MlsError::IdentityProviderError(e.into_any_error()),
+                });
+            }
+        }
+
+        RevalidationReport {
+            invalid_members,
+            self_index: self.current_member_index(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{boxed::Box, vec, vec::Vec};
+
+    use mls_rs_core::{
+        error::IntoAnyError,
+        extension::ExtensionList,
+        identity::{CredentialType, IdentityProvider, SigningIdentity},
+        time::MlsTime,
+    };
+
+    use crate::{
+        client::test_utils::TEST_CIPHER_SUITE,
+        client_builder::{BaseConfig, ClientBuilder, WithCryptoProvider, WithIdentityProvider},
+        crypto::test_utils::TestCryptoProvider,
+        group::proposal::{Proposal, RemoveProposal},
+        identity::{basic::BasicIdentityProvider, test_utils::get_test_signing_identity},
+        tree_kem::node::LeafIndex,
+        Client,
+    };
+
+    const EXPIRY: u64 = 4_000_000_000;
+
+    /// Rejects the credential of bob from [`EXPIRY`] on.
+    #[derive(Clone, Debug)]
+    struct ExpiringIdentityProvider;
+
+    #[derive(Debug)]
+    #[cfg_attr(feature = "std", derive(thiserror::Error))]
+    #[cfg_attr(feature = "std", error("credential expired"))]
+    struct Expired;
+
+    impl IntoAnyError for Expired {
+        #[cfg(feature = "std")]
+        fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+            Ok(self.into())
+        }
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl IdentityProvider for ExpiringIdentityProvider {
+        type Error = Expired;
+
+        async fn validate_member(
+            &self,
+            signing_identity: &SigningIdentity,
+            timestamp: Option<MlsTime>,
+            _extensions: Option<&ExtensionList>,
+        ) -> Result<(), Self::Error> {
+            let is_bob = signing_identity
+                .credential
+                .as_basic()
+                .map_or(false, |c| c.identifier == b"bob");
+
+            let expired = timestamp.map_or(false, |t| t >= MlsTime::from(EXPIRY));
+
+            (!(is_bob && expired)).then_some(()).ok_or(Expired)
+        }
+
+        async fn validate_external_sender(
+            &self,
+            _signing_identity: &SigningIdentity,
+            _timestamp: Option<MlsTime>,
+            _extensions: Option<&ExtensionList>,
+        ) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn identity(
+            &self,
+            signing_identity: &SigningIdentity,
+            extensions: &ExtensionList,
+        ) -> Result<Vec<u8>, Self::Error> {
+            BasicIdentityProvider
+                .identity(signing_identity, extensions)
+                .await
+                .map_err(|_| Expired)
+        }
+
+        async fn valid_successor(
+            &self,
+            _predecessor: &SigningIdentity,
+            _successor: &SigningIdentity,
+            _extensions: &ExtensionList,
+        ) -> Result<bool, Self::Error> {
+            Ok(true)
+        }
+
+        fn supported_types(&self) -> Vec<CredentialType> {
+            BasicIdentityProvider.supported_types()
+        }
+    }
+
+    type ExpiringClientConfig = WithIdentityProvider<
+        ExpiringIdentityProvider,
+        WithCryptoProvider<TestCryptoProvider, BaseConfig>,
+    >;
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn client(name: &[u8]) -> Client<ExpiringClientConfig> {
+        let (identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, name).await;
+
+        ClientBuilder::new()
+            .crypto_provider(TestCryptoProvider::new())
+            .identity_provider(ExpiringIdentityProvider)
+            .signing_identity(identity, secret_key, TEST_CIPHER_SUITE)
+            .build()
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn expired_members_are_reported() {
+        let alice = client(b"alice").await;
+        let bob = client(b"bob").await;
+
+        let mut group = alice.create_group(ExtensionList::new()).await.unwrap();
+        let key_package = bob.generate_key_package_message().await.unwrap();
+
+        group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        group.apply_pending_commit().await.unwrap();
+
+        let report = group.revalidate_members(None).await;
+        assert!(report.is_empty());
+
+        let report = group.revalidate_members(Some(MlsTime::from(EXPIRY))).await;
+
+        assert_eq!(report.invalid_members.len(), 1);
+        assert_eq!(report.invalid_members[0].member.index, 1);
+
+        assert_eq!(
+            report.removals(),
+            vec![Proposal::Remove(RemoveProposal {
+                to_remove: LeafIndex(1)
+            })]
+        );
+    }
+}
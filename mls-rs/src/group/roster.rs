@@ -4,6 +4,8 @@
 
 use super::*;
 
+use mls_rs_core::identity::CredentialType;
+
 pub use mls_rs_core::group::Member;
 
 #[cfg(feature = "state_update")]
@@ -82,6 +84,27 @@ impl<'a> Roster<'a> {
             .non_empty_leaves()
             .map(|(_, node)| &node.signing_identity)
     }
+
+    /// The members using a credential of type `credential_type`.
+    pub fn members_with_credential_type(&self, credential_type: CredentialType) -> Vec<Member> {
+        self.members_iter()
+            .filter(|member| {
+                member.signing_identity.credential.credential_type() == credential_type
+            })
+            .collect()
+    }
+
+    /// The credential types used by at least one member, in increasing order.
+    pub fn credential_types(&self) -> Vec<CredentialType> {
+        let mut credential_types = self
+            .member_identities_iter()
+            .map(|identity| identity.credential.credential_type())
+            .collect::<Vec<_>>();
+
+        credential_types.sort_unstable();
+        credential_types.dedup();
+        credential_types
+    }
 }
 
 impl TreeKemPublic {
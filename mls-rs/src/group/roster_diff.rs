@@ -0,0 +1,296 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::identity::SigningIdentity;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{message_processor::ProvisionalState, Group},
+};
+
+/// Change to the roster of a group, as recorded in a [`RosterDiff`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum RosterOperation {
+    /// A member with `identity` was added at `index`.
+    Add {
+        index: u32,
+        identity: SigningIdentity,
+    },
+    /// The member at `index` was removed.
+    Remove { index: u32 },
+    /// The member at `index` replaced its leaf, e.g. with an update proposal
+    /// or the update path of a commit.
+    Update { index: u32 },
+}
+
+const ADD: u8 = 1;
+const REMOVE: u8 = 2;
+const UPDATE: u8 = 3;
+
+impl MlsSize for RosterOperation {
+    fn mls_encoded_len(&self) -> usize {
+        let fields_len = match self {
+            Self::Add { index, identity } => index.mls_encoded_len() + identity.mls_encoded_len(),
+            Self::Remove { index } | Self::Update { index } => index.mls_encoded_len(),
+        };
+
+        ADD.mls_encoded_len() + fields_len
+    }
+}
+
+impl MlsEncode for RosterOperation {
+    fn mls_encode(&self, writer: &mut Vec<u8>) -> Result<(), mls_rs_codec::Error> {
+        match self {
+            Self::Add { index, identity } => {
+                ADD.mls_encode(writer)?;
+                index.mls_encode(writer)?;
+                identity.mls_encode(writer)
+            }
+            Self::Remove { index } => {
+                REMOVE.mls_encode(writer)?;
+                index.mls_encode(writer)
+            }
+            Self::Update { index } => {
+                UPDATE.mls_encode(writer)?;
+                index.mls_encode(writer)
+            }
+        }
+    }
+}
+
+impl MlsDecode for RosterOperation {
+    fn mls_decode(reader: &mut &[u8]) -> Result<Self, mls_rs_codec::Error> {
+        match u8::mls_decode(reader)? {
+            ADD => Ok(Self::Add {
+                index: u32::mls_decode(reader)?,
+                identity: SigningIdentity::mls_decode(reader)?,
+            }),
+            REMOVE => Ok(Self::Remove {
+                index: u32::mls_decode(reader)?,
+            }),
+            UPDATE => Ok(Self::Update {
+                index: u32::mls_decode(reader)?,
+            }),
+            _ => Err(mls_rs_codec::Error::UnsupportedEnumDiscriminant),
+        }
+    }
+}
+
+/// Roster operations of a single epoch, exported with
+/// [`Group::roster_diffs`].
+///
+/// Operations are listed in the order commits apply them: updates first,
+/// then removals, then additions, each in increasing leaf index order. An
+/// index removed and filled by an addition in the same epoch therefore
+/// appears in a removal followed by an addition. Applying the diffs of
+/// consecutive epochs in order to the roster of the epoch preceding the
+/// first one, e.g. obtained with [`Group::roster_snapshot`], yields the
+/// roster of the last epoch.
+#[derive(Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct RosterDiff {
+    /// Epoch created by the commit that applied the operations.
+    pub epoch: u64,
+    /// Operations in application order.
+    pub operations: Vec<RosterOperation>,
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Roster diffs of the epochs after `since_epoch`, up to the current
+    /// epoch.
+    ///
+    /// Diffs are only recorded if enabled with
+    /// [`ClientBuilder::record_roster_diffs`](crate::client_builder::ClientBuilder::record_roster_diffs),
+    /// starting after the epoch in which the group was created or joined.
+    /// Returns [`MlsError::EpochNotFound`] if diffs after `since_epoch` are
+    /// not available, either because they were not recorded or because they
+    /// were removed by [`Group::prune_roster_diffs`].
+    pub fn roster_diffs(&self, since_epoch: u64) -> Result<Vec<RosterDiff>, MlsError> {
+        let first_epoch = self
+            .roster_diffs
+            .first()
+            .map_or(self.current_epoch(), |diff| diff.epoch - 1);
+
+        if since_epoch < first_epoch || since_epoch > self.current_epoch() {
+            return Err(MlsError::EpochNotFound);
+        }
+
+        Ok(self
+            .roster_diffs
+            .iter()
+            .filter(|diff| diff.epoch > since_epoch)
+            .cloned()
+            .collect())
+    }
+
+    /// The current roster as a diff adding every member, to start replaying
+    /// diffs returned by [`Group::roster_diffs`] from the current epoch.
+    pub fn roster_snapshot(&self) -> RosterDiff {
+        let operations = self
+            .current_epoch_tree()
+            .nodes
+            .non_empty_leaves()
+            .map(|(index, leaf)| RosterOperation::Add {
+                index: *index,
+                identity: leaf.signing_identity.clone(),
+            })
+            .collect();
+
+        RosterDiff {
+            epoch: self.current_epoch(),
+            operations,
+        }
+    }
+
+    /// Remove the recorded diffs of epochs before `epoch`, e.g. once they
+    /// were exported with [`Group::roster_diffs`].
+    pub fn prune_roster_diffs(&mut self, epoch: u64) {
+        self.roster_diffs.retain(|diff| diff.epoch >= epoch);
+    }
+
+    /// Record the roster operations of the commit creating the epoch of
+    /// `provisional_state`, if enabled.
+    pub(crate) fn record_roster_diff(&mut self, provisional_state: &ProvisionalState) {
+        if !self.config.record_roster_diffs() {
+            return;
+        }
+
+        let old_tree = &self.state.public_tree;
+        let new_tree = &provisional_state.public_tree;
+
+        let mut removed = provisional_state
+            .applied_proposals
+            .removals
+            .iter()
+            .map(|removal| removal.proposal.to_remove)
+            .collect::<Vec<_>>();
+
+        let mut added = provisional_state
+            .indexes_of_added_kpkgs
+            .iter()
+            .chain(&provisional_state.external_init_index)
+            .copied()
+            .collect::<Vec<_>>();
+
+        removed.sort_unstable();
+        added.sort_unstable();
+
+        // Any other leaf whose encryption key changed was updated, either by
+        // an update proposal or by the committer's update path.
+        let updates = old_tree
+            .nodes
+            .non_empty_leaves()
+            .filter(|(index, _)| !removed.contains(index) && !added.contains(index))
+            .filter(|(index, leaf)| {
+                new_tree
+                    .get_leaf_node(*index)
+                    .map_or(false, |new_leaf| new_leaf.public_key != leaf.public_key)
+            })
+            .map(|(index, _)| RosterOperation::Update { index: *index });
+
+        let removals = removed
+            .iter()
+            .map(|index| RosterOperation::Remove { index: **index });
+
+        let additions = added.iter().filter_map(|index| {
+            let leaf = new_tree.get_leaf_node(*index).ok()?;
+
+            Some(RosterOperation::Add {
+                index: **index,
+                identity: leaf.signing_identity.clone(),
+            })
+        });
+
+        let operations = updates.chain(removals).chain(additions).collect();
+
+        self.roster_diffs.push(RosterDiff {
+            epoch: provisional_state.group_context.epoch,
+            operations,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use super::RosterOperation;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::test_utils::test_group_custom_config,
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commits_are_recorded_as_roster_operations() {
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.record_roster_diffs(true)
+        })
+        .await;
+
+        let snapshot = alice.group.roster_snapshot();
+        assert_eq!(snapshot.epoch, 0);
+        assert_eq!(snapshot.operations.len(), 1);
+
+        let (bob, _) = alice.join("bob").await;
+
+        alice
+            .group
+            .commit_builder()
+            .remove_member(1)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.process_pending_commit().await.unwrap();
+
+        let diffs = alice.group.roster_diffs(0).unwrap();
+
+        assert_eq!(diffs.len(), 2);
+        assert_eq!(diffs[0].epoch, 1);
+
+        let bob_identity = bob.group.current_member_signing_identity().unwrap().clone();
+
+        // Whether the committer of an add-only commit sends an update path
+        // depends on the commit options.
+        assert_eq!(
+            diffs[0].operations.last(),
+            Some(&RosterOperation::Add {
+                index: 1,
+                identity: bob_identity
+            })
+        );
+
+        assert_eq!(
+            diffs[1].operations,
+            vec![
+                RosterOperation::Update { index: 0 },
+                RosterOperation::Remove { index: 1 }
+            ]
+        );
+
+        assert_eq!(alice.group.roster_diffs(1).unwrap(), diffs[1..].to_vec());
+        assert!(alice.group.roster_diffs(2).unwrap().is_empty());
+
+        alice.group.prune_roster_diffs(2);
+
+        assert_matches!(alice.group.roster_diffs(0), Err(MlsError::EpochNotFound));
+        assert_eq!(alice.group.roster_diffs(1).unwrap().len(), 1);
+    }
+}
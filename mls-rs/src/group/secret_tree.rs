@@ -17,14 +17,96 @@ use mls_rs_core::error::IntoAnyError;
 
 use super::key_schedule::kdf_expand_with_label;
 
+/// Default maximum number of generations a received message may skip.
 pub(crate) const MAX_RATCHET_BACK_HISTORY: u32 = 1024;
 
-#[derive(Clone, Debug, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+/// Number of generations derived between yield points when ratcheting
+/// forward, so that large jumps do not block the executor.
+#[cfg(mls_build_async)]
+const RATCHET_CHUNK_SIZE: u32 = 64;
+
+#[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[repr(u8)]
+#[allow(clippy::large_enum_variant)]
 enum SecretTreeNode {
-    Secret(TreeSecret) = 0u8,
-    Ratchet(SecretRatchets) = 1u8,
+    Secret(TreeSecret),
+    Ratchet(SecretRatchets),
+}
+
+// Ratchets with application lanes are encoded with their own discriminant,
+// so that the encoding of all other nodes is the same with and without the
+// `application_lanes` feature and stored states can be loaded by both.
+const SECRET_NODE: u8 = 0;
+const RATCHET_NODE: u8 = 1;
+const RATCHET_WITH_LANES_NODE: u8 = 2;
+
+impl MlsSize for SecretTreeNode {
+    fn mls_encoded_len(&self) -> usize {
+        let len = match self {
+            SecretTreeNode::Secret(secret) => secret.mls_encoded_len(),
+            SecretTreeNode::Ratchet(ratchets) => {
+                ratchets.application.mls_encoded_len()
+                    + ratchets.handshake.mls_encoded_len()
+                    + ratchets
+                        .lanes
+                        .as_ref()
+                        .map_or(0, |lanes| lanes.mls_encoded_len())
+            }
+        };
+
+        SECRET_NODE.mls_encoded_len() + len
+    }
+}
+
+impl MlsEncode for SecretTreeNode {
+    fn mls_encode(&self, writer: &mut Vec<u8>) -> Result<(), mls_rs_codec::Error> {
+        match self {
+            SecretTreeNode::Secret(secret) => {
+                SECRET_NODE.mls_encode(writer)?;
+                secret.mls_encode(writer)
+            }
+            SecretTreeNode::Ratchet(ratchets) => {
+                let discriminant = match ratchets.lanes {
+                    Some(_) => RATCHET_WITH_LANES_NODE,
+                    None => RATCHET_NODE,
+                };
+
+                discriminant.mls_encode(writer)?;
+                ratchets.application.mls_encode(writer)?;
+                ratchets.handshake.mls_encode(writer)?;
+
+                match &ratchets.lanes {
+                    Some(lanes) => lanes.mls_encode(writer),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+impl MlsDecode for SecretTreeNode {
+    fn mls_decode(reader: &mut &[u8]) -> Result<Self, mls_rs_codec::Error> {
+        let discriminant = u8::mls_decode(reader)?;
+
+        match discriminant {
+            SECRET_NODE => TreeSecret::mls_decode(reader).map(SecretTreeNode::Secret),
+            RATCHET_NODE | RATCHET_WITH_LANES_NODE => {
+                let application = SecretKeyRatchet::mls_decode(reader)?;
+                let handshake = SecretKeyRatchet::mls_decode(reader)?;
+
+                let lanes = (discriminant == RATCHET_WITH_LANES_NODE)
+                    .then(|| ApplicationLanes::mls_decode(reader))
+                    .transpose()?;
+
+                Ok(SecretTreeNode::Ratchet(SecretRatchets {
+                    application,
+                    handshake,
+                    lanes,
+                }))
+            }
+            _ => Err(mls_rs_codec::Error::UnsupportedEnumDiscriminant),
+        }
+    }
 }
 
 impl SecretTreeNode {
@@ -117,11 +199,36 @@ impl<T: TreeIndex> SecretTree<T> {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+/// Memory held by the secret tree of the current epoch.
+///
+/// Node secrets are derived lazily along the path from the root to a leaf the
+/// first time a key of that leaf is needed, and every intermediate secret is
+/// deleted as soon as its children are derived. In large groups, the memory is
+/// therefore dominated by the ratchets of members that sent messages in the
+/// epoch and by message keys retained to decrypt messages received out of
+/// order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SecretTreeMemoryUsage {
+    /// Number of node secrets derived but not consumed yet.
+    pub node_secrets: usize,
+    /// Number of members with initialized sender ratchets.
+    pub sender_ratchets: usize,
+    /// Number of message keys retained for out-of-order messages.
+    pub retained_message_keys: usize,
+    /// Total size in bytes of the secrets and keys held.
+    pub secret_bytes: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SecretRatchets {
     pub application: SecretKeyRatchet,
     pub handshake: SecretKeyRatchet,
+    /// Lane ratchets, derived when the leaf secret is consumed with the
+    /// `application_lanes` feature. Ratchets loaded from a state written
+    /// without the feature have no lanes until the next epoch.
+    pub lanes: Option<ApplicationLanes>,
 }
 
 impl SecretRatchets {
@@ -131,16 +238,25 @@ impl SecretRatchets {
         cipher_suite_provider: &P,
         generation: u32,
         key_type: KeyType,
+        max_jump: u32,
     ) -> Result<MessageKeyData, MlsError> {
         match key_type {
             KeyType::Handshake => {
                 self.handshake
-                    .get_message_key(cipher_suite_provider, generation)
+                    .get_message_key(cipher_suite_provider, generation, max_jump)
                     .await
             }
             KeyType::Application => {
                 self.application
-                    .get_message_key(cipher_suite_provider, generation)
+                    .get_message_key(cipher_suite_provider, generation, max_jump)
+                    .await
+            }
+            #[cfg(feature = "application_lanes")]
+            KeyType::ApplicationLane(lane) => {
+                self.lanes_mut()?
+                    .ratchet(cipher_suite_provider, lane)
+                    .await?
+                    .get_message_key(cipher_suite_provider, generation, max_jump)
                     .await
             }
         }
@@ -155,7 +271,163 @@ impl SecretRatchets {
         match key_type {
             KeyType::Handshake => self.handshake.next_message_key(cipher_suite).await,
             KeyType::Application => self.application.next_message_key(cipher_suite).await,
+            #[cfg(feature = "application_lanes")]
+            KeyType::ApplicationLane(lane) => {
+                self.lanes_mut()?
+                    .ratchet(cipher_suite, lane)
+                    .await?
+                    .next_message_key(cipher_suite)
+                    .await
+            }
+        }
+    }
+
+    #[cfg(feature = "application_lanes")]
+    fn lanes_mut(&mut self) -> Result<&mut ApplicationLanes, MlsError> {
+        self.lanes
+            .as_mut()
+            .ok_or(MlsError::ApplicationLanesUnavailable)
+    }
+
+    fn add_memory_usage(&self, usage: &mut SecretTreeMemoryUsage) {
+        self.application.add_memory_usage(usage);
+        self.handshake.add_memory_usage(usage);
+
+        if let Some(lanes) = &self.lanes {
+            usage.secret_bytes += lanes
+                .nodes
+                .values()
+                .map(|secret| secret.len())
+                .sum::<usize>();
+
+            lanes
+                .ratchets
+                .values()
+                .for_each(|ratchet| ratchet.add_memory_usage(usage));
+        }
+    }
+
+    #[cfg(feature = "out_of_order")]
+    fn retain_message_keys(&mut self, max_keys: usize) {
+        self.application.retain_message_keys(max_keys);
+        self.handshake.retain_message_keys(max_keys);
+
+        if let Some(lanes) = &mut self.lanes {
+            lanes
+                .ratchets
+                .values_mut()
+                .for_each(|ratchet| ratchet.retain_message_keys(max_keys));
+        }
+    }
+}
+
+/// Additional application ratchets of a leaf, one per lane.
+///
+/// Lane ratchets are derived on first use, so that messages sent on one lane
+/// never consume keys of another lane or of the default application ratchet.
+/// As in the secret tree, the secret of each lane is derived from a binary
+/// tree with one leaf per lane, rooted at a lane secret derived from the leaf
+/// secret. Deriving the secret of a lane consumes the secrets of the nodes on
+/// its path, keeping only those of their other children, so that the state
+/// never holds a secret from which the keys already used on a lane can be
+/// derived.
+#[derive(Clone, Debug, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ApplicationLanes {
+    nodes: LargeMap<u32, TreeSecret>,
+    ratchets: LargeMap<u16, SecretKeyRatchet>,
+}
+
+/// Depth of the tree of lane secrets, with one leaf per lane.
+#[cfg(feature = "application_lanes")]
+const LANE_TREE_DEPTH: u32 = u16::BITS;
+
+/// Identifier of the node at `depth` on the path from the root of the tree
+/// of lane secrets to `lane`.
+#[cfg(feature = "application_lanes")]
+fn lane_node(depth: u32, lane: u16) -> u32 {
+    (depth << LANE_TREE_DEPTH) | (lane as u32 >> (LANE_TREE_DEPTH - depth))
+}
+
+#[cfg(feature = "application_lanes")]
+impl ApplicationLanes {
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn new<P: CipherSuiteProvider>(
+        cipher_suite_provider: &P,
+        secret: &[u8],
+    ) -> Result<Self, MlsError> {
+        let secret =
+            kdf_expand_with_label(cipher_suite_provider, secret, b"lanes", &[], None).await?;
+
+        let mut nodes = LargeMap::default();
+        nodes.insert(lane_node(0, 0), TreeSecret::from(secret));
+
+        Ok(Self {
+            nodes,
+            ratchets: Default::default(),
+        })
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn ratchet<P: CipherSuiteProvider>(
+        &mut self,
+        cipher_suite_provider: &P,
+        lane: u16,
+    ) -> Result<&mut SecretKeyRatchet, MlsError> {
+        if !self.ratchets.contains_key(&lane) {
+            let secret = self.take_lane_secret(cipher_suite_provider, lane).await?;
+
+            let ratchet = SecretKeyRatchet::new(
+                cipher_suite_provider,
+                &secret,
+                KeyType::ApplicationLane(lane),
+            )
+            .await?;
+
+            self.ratchets.insert(lane, ratchet);
+        }
+
+        self.ratchets
+            .get_mut(&lane)
+            .ok_or(MlsError::InvalidLeafConsumption)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn take_lane_secret<P: CipherSuiteProvider>(
+        &mut self,
+        cipher_suite_provider: &P,
+        lane: u16,
+    ) -> Result<TreeSecret, MlsError> {
+        // Start at the deepest known node on the path to the lane and work
+        // down, consuming the secret of each node.
+        let start = (0..=LANE_TREE_DEPTH)
+            .rev()
+            .find(|depth| self.nodes.contains_key(&lane_node(*depth, lane)))
+            .ok_or(MlsError::InvalidLeafConsumption)?;
+
+        for depth in start..LANE_TREE_DEPTH {
+            let secret = self
+                .nodes
+                .remove(&lane_node(depth, lane))
+                .ok_or(MlsError::InvalidLeafConsumption)?;
+
+            let left =
+                kdf_expand_with_label(cipher_suite_provider, &secret, b"tree", b"left", None)
+                    .await?;
+
+            let right =
+                kdf_expand_with_label(cipher_suite_provider, &secret, b"tree", b"right", None)
+                    .await?;
+
+            let child = lane_node(depth + 1, lane);
+
+            self.nodes.insert(child & !1, left.into());
+            self.nodes.insert(child | 1, right.into());
         }
+
+        self.nodes
+            .remove(&lane_node(LANE_TREE_DEPTH, lane))
+            .ok_or(MlsError::InvalidLeafConsumption)
     }
 }
 
@@ -230,6 +502,10 @@ impl<T: TreeIndex> SecretTree<T> {
                 application: SecretKeyRatchet::new(cipher_suite, &secret, KeyType::Application)
                     .await?,
                 handshake: SecretKeyRatchet::new(cipher_suite, &secret, KeyType::Handshake).await?,
+                #[cfg(feature = "application_lanes")]
+                lanes: Some(ApplicationLanes::new(cipher_suite, &secret).await?),
+                #[cfg(not(feature = "application_lanes"))]
+                lanes: None,
             },
         })
     }
@@ -242,14 +518,18 @@ impl<T: TreeIndex> SecretTree<T> {
         key_type: KeyType,
     ) -> Result<MessageKeyData, MlsError> {
         let mut ratchet = self.take_leaf_ratchet(cipher_suite, &leaf_index).await?;
-        let res = ratchet.next_message_key(cipher_suite, key_type).await?;
+        let res = ratchet.next_message_key(cipher_suite, key_type).await;
 
+        // Put the ratchets back even on failure so that an error on one
+        // ratchet does not discard the others.
         self.known_secrets
             .set_node(leaf_index, SecretTreeNode::Ratchet(ratchet));
 
-        Ok(res)
+        res
     }
 
+    /// Get the key of `generation` of the ratchet of `leaf_index`, rejecting
+    /// generations more than `max_jump` ahead of the current generation.
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn message_key_generation<P: CipherSuiteProvider>(
         &mut self,
@@ -257,17 +537,65 @@ impl<T: TreeIndex> SecretTree<T> {
         leaf_index: T,
         key_type: KeyType,
         generation: u32,
+        max_jump: u32,
     ) -> Result<MessageKeyData, MlsError> {
         let mut ratchet = self.take_leaf_ratchet(cipher_suite, &leaf_index).await?;
 
         let res = ratchet
-            .message_key_generation(cipher_suite, generation, key_type)
-            .await?;
+            .message_key_generation(cipher_suite, generation, key_type, max_jump)
+            .await;
 
         self.known_secrets
             .set_node(leaf_index, SecretTreeNode::Ratchet(ratchet));
 
-        Ok(res)
+        res
+    }
+
+    /// Drop the oldest message keys retained for out-of-order messages from
+    /// `leaf_index` until each of its ratchets holds at most `max_keys`.
+    #[cfg(feature = "out_of_order")]
+    pub fn retain_message_keys(&mut self, leaf_index: &T, max_keys: usize) {
+        if let Some(SecretTreeNode::Ratchet(ratchets)) =
+            self.known_secrets.inner.get_mut(leaf_index)
+        {
+            ratchets.retain_message_keys(max_keys);
+        }
+    }
+
+    /// Drop all secrets of the tree, after which deriving any key fails with
+    /// [`MlsError::InvalidLeafConsumption`] as if all leaf secrets were
+    /// consumed.
+    #[cfg(feature = "event_sourcing")]
+    pub(crate) fn erase(&mut self) {
+        self.known_secrets = Default::default();
+    }
+
+    /// Determines if the tree has `leaf_count` leaves and only holds secrets
+    /// of nodes within it.
+    pub(crate) fn within_bounds(&self, leaf_count: &T) -> bool {
+        let root = leaf_count.root();
+
+        &self.leaf_count == leaf_count
+            && self.known_secrets.inner.keys().all(|i| i.is_in_tree(&root))
+    }
+
+    pub fn memory_usage(&self) -> SecretTreeMemoryUsage {
+        let mut usage = SecretTreeMemoryUsage::default();
+
+        for node in self.known_secrets.inner.values() {
+            match node {
+                SecretTreeNode::Secret(secret) => {
+                    usage.node_secrets += 1;
+                    usage.secret_bytes += secret.len();
+                }
+                SecretTreeNode::Ratchet(ratchets) => {
+                    usage.sender_ratchets += 1;
+                    ratchets.add_memory_usage(&mut usage);
+                }
+            }
+        }
+
+        usage
     }
 }
 
@@ -275,6 +603,20 @@ impl<T: TreeIndex> SecretTree<T> {
 pub enum KeyType {
     Handshake,
     Application,
+    #[cfg(feature = "application_lanes")]
+    ApplicationLane(u16),
+}
+
+impl KeyType {
+    /// Key type used for application messages sent on `lane`. Lane 0 is the
+    /// default application ratchet defined by RFC 9420.
+    #[cfg(feature = "application_lanes")]
+    pub(crate) fn application_lane(lane: u16) -> Self {
+        match lane {
+            0 => KeyType::Application,
+            lane => KeyType::ApplicationLane(lane),
+        }
+    }
 }
 
 #[cfg_attr(
@@ -390,12 +732,20 @@ impl SecretKeyRatchet {
         secret: &[u8],
         key_type: KeyType,
     ) -> Result<Self, MlsError> {
-        let label = match key_type {
-            KeyType::Handshake => b"handshake".as_slice(),
-            KeyType::Application => b"application".as_slice(),
+        #[cfg(feature = "application_lanes")]
+        let lane_context;
+
+        let (label, context) = match key_type {
+            KeyType::Handshake => (b"handshake".as_slice(), [].as_slice()),
+            KeyType::Application => (b"application".as_slice(), [].as_slice()),
+            #[cfg(feature = "application_lanes")]
+            KeyType::ApplicationLane(lane) => {
+                lane_context = lane.to_be_bytes();
+                (b"application lane".as_slice(), lane_context.as_slice())
+            }
         };
 
-        let secret = kdf_expand_with_label(cipher_suite_provider, secret, label, &[], None)
+        let secret = kdf_expand_with_label(cipher_suite_provider, secret, label, context, None)
             .await
             .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
 
@@ -412,6 +762,7 @@ impl SecretKeyRatchet {
         &mut self,
         cipher_suite_provider: &P,
         generation: u32,
+        max_jump: u32,
     ) -> Result<MessageKeyData, MlsError> {
         #[cfg(feature = "out_of_order")]
         if generation < self.generation {
@@ -427,21 +778,29 @@ impl SecretKeyRatchet {
             return Err(MlsError::KeyMissing(generation));
         }
 
-        let max_generation_allowed = self.generation + MAX_RATCHET_BACK_HISTORY;
-
-        if generation > max_generation_allowed {
+        // Checked before deriving anything, so that a sender claiming a huge
+        // generation can not make the receiver ratchet for it.
+        if generation - self.generation > max_jump {
             return Err(MlsError::InvalidFutureGeneration(generation));
         }
 
-        #[cfg(not(feature = "out_of_order"))]
-        while self.generation < generation {
-            self.next_message_key(cipher_suite_provider)?;
-        }
+        #[cfg(mls_build_async)]
+        let start = self.generation;
 
-        #[cfg(feature = "out_of_order")]
         while self.generation < generation {
-            let key_data = self.next_message_key(cipher_suite_provider).await?;
-            self.history.insert(key_data.generation, key_data);
+            #[cfg(not(feature = "out_of_order"))]
+            self.next_message_key(cipher_suite_provider).await?;
+
+            #[cfg(feature = "out_of_order")]
+            {
+                let key_data = self.next_message_key(cipher_suite_provider).await?;
+                self.history.insert(key_data.generation, key_data);
+            }
+
+            #[cfg(mls_build_async)]
+            if (self.generation - start) % RATCHET_CHUNK_SIZE == 0 {
+                yield_now().await;
+            }
         }
 
         self.next_message_key(cipher_suite_provider).await
@@ -486,6 +845,32 @@ impl SecretKeyRatchet {
         Ok(key)
     }
 
+    fn add_memory_usage(&self, usage: &mut SecretTreeMemoryUsage) {
+        usage.secret_bytes += self.secret.len();
+
+        #[cfg(feature = "out_of_order")]
+        for key in self.history.values() {
+            usage.retained_message_keys += 1;
+            usage.secret_bytes += key.key.len() + key.nonce.len();
+        }
+    }
+
+    #[cfg(feature = "out_of_order")]
+    fn retain_message_keys(&mut self, max_keys: usize) {
+        let excess = self.history.len().saturating_sub(max_keys);
+
+        if excess == 0 {
+            return;
+        }
+
+        let mut generations = self.history.keys().copied().collect::<Vec<_>>();
+        generations.sort_unstable();
+
+        for generation in generations.into_iter().take(excess) {
+            self.history.remove(&generation);
+        }
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn derive_secret<P: CipherSuiteProvider>(
         &self,
@@ -505,6 +890,31 @@ impl SecretKeyRatchet {
     }
 }
 
+/// Let other tasks run before deriving the next chunk of a ratchet.
+#[cfg(mls_build_async)]
+async fn yield_now() {
+    struct YieldNow(bool);
+
+    impl core::future::Future for YieldNow {
+        type Output = ();
+
+        fn poll(
+            mut self: core::pin::Pin<&mut Self>,
+            cx: &mut core::task::Context<'_>,
+        ) -> core::task::Poll<()> {
+            if self.0 {
+                return core::task::Poll::Ready(());
+            }
+
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            core::task::Poll::Pending
+        }
+    }
+
+    YieldNow(false).await
+}
+
 #[cfg(test)]
 pub(crate) mod test_utils {
     use alloc::{string::String, vec::Vec};
@@ -590,7 +1000,7 @@ mod tests {
         crypto::test_utils::{
             test_cipher_suite_provider, try_test_cipher_suite_provider, TestCryptoProvider,
         },
-        tree_kem::node::NodeIndex,
+        tree_kem::node::{LeafIndex, NodeIndex},
     };
 
     #[cfg(not(mls_build_async))]
@@ -679,6 +1089,160 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "application_lanes")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn application_lanes_are_independent() {
+        let provider = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let mut tree = get_test_tree(vec![0u8; provider.kdf_extract_size()], 2u32);
+
+        let mut keys = Vec::new();
+
+        for key_type in [
+            KeyType::Application,
+            KeyType::ApplicationLane(1),
+            KeyType::ApplicationLane(2),
+        ] {
+            let key = tree.next_message_key(&provider, 0, key_type).await.unwrap();
+
+            assert_eq!(key.generation, 0);
+            keys.push(key);
+        }
+
+        let count = keys.len();
+        keys.dedup();
+        assert_eq!(count, keys.len());
+
+        // Lane 1 advanced independently of the other ratchets
+        let key = tree
+            .next_message_key(&provider, 0, KeyType::ApplicationLane(1))
+            .await
+            .unwrap();
+
+        assert_eq!(key.generation, 1);
+
+        let key = tree
+            .message_key_generation(
+                &provider,
+                0,
+                KeyType::ApplicationLane(2),
+                1,
+                MAX_RATCHET_BACK_HISTORY,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(key.generation, 1);
+    }
+
+    #[cfg(feature = "application_lanes")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn lane_secrets_are_consumed() {
+        let provider = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let mut tree = get_test_tree(vec![0u8; provider.kdf_extract_size()], 2u32);
+
+        let mut ratchets = tree.take_leaf_ratchet(&provider, &0).await.unwrap();
+        let lanes = ratchets.lanes.clone().unwrap();
+
+        let key = ratchets
+            .next_message_key(&provider, KeyType::ApplicationLane(1))
+            .await
+            .unwrap();
+
+        let used = ratchets.lanes.as_ref().unwrap();
+
+        // Only the siblings of the nodes on the path to lane 1 are kept.
+        assert!(!used.nodes.contains_key(&lane_node(0, 0)));
+        assert!(!used.nodes.contains_key(&lane_node(LANE_TREE_DEPTH, 1)));
+        assert!(used.nodes.contains_key(&lane_node(LANE_TREE_DEPTH, 0)));
+        assert_eq!(used.nodes.len(), LANE_TREE_DEPTH as usize);
+
+        // The secrets held before and after using lane 1 derive the same
+        // keys for other lanes.
+        let mut before = SecretRatchets {
+            lanes: Some(lanes),
+            ..ratchets.clone()
+        };
+
+        for lane in [0, 2, u16::MAX] {
+            let expected = before
+                .next_message_key(&provider, KeyType::ApplicationLane(lane))
+                .await
+                .unwrap();
+
+            let key = ratchets
+                .next_message_key(&provider, KeyType::ApplicationLane(lane))
+                .await
+                .unwrap();
+
+            assert_eq!(key, expected);
+        }
+
+        // Lane 1 keeps its ratchet.
+        let next = ratchets
+            .next_message_key(&provider, KeyType::ApplicationLane(1))
+            .await
+            .unwrap();
+
+        assert_eq!(next.generation, key.generation + 1);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn ratchets_without_lanes_keep_the_original_encoding() {
+        let provider = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let mut tree = get_test_tree(vec![0u8; provider.kdf_extract_size()], 2u32);
+
+        let mut ratchets = tree.take_leaf_ratchet(&provider, &0).await.unwrap();
+        ratchets.lanes = None;
+
+        let node = SecretTreeNode::Ratchet(ratchets.clone());
+        let bytes = node.mls_encode_to_vec().unwrap();
+
+        let expected = [
+            vec![RATCHET_NODE],
+            ratchets.application.mls_encode_to_vec().unwrap(),
+            ratchets.handshake.mls_encode_to_vec().unwrap(),
+        ]
+        .concat();
+
+        assert_eq!(bytes, expected);
+        assert_eq!(SecretTreeNode::mls_decode(&mut &*bytes).unwrap(), node);
+    }
+
+    #[cfg(feature = "application_lanes")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn ratchets_with_lanes_can_be_encoded() {
+        let provider = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let mut tree = get_test_tree(vec![0u8; provider.kdf_extract_size()], 2u32);
+
+        tree.next_message_key(&provider, 0, KeyType::ApplicationLane(1))
+            .await
+            .unwrap();
+
+        let bytes = tree.mls_encode_to_vec().unwrap();
+        assert_eq!(SecretTree::mls_decode(&mut &*bytes).unwrap(), tree);
+    }
+
+    #[cfg(feature = "application_lanes")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn lanes_are_unavailable_for_ratchets_created_without_them() {
+        let provider = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let mut tree = get_test_tree(vec![0u8; provider.kdf_extract_size()], 2u32);
+
+        let mut ratchets = tree.take_leaf_ratchet(&provider, &0).await.unwrap();
+        ratchets.lanes = None;
+
+        let res = ratchets
+            .next_message_key(&provider, KeyType::ApplicationLane(1))
+            .await;
+
+        assert_matches!(res, Err(MlsError::ApplicationLanesUnavailable));
+
+        ratchets
+            .next_message_key(&provider, KeyType::Application)
+            .await
+            .unwrap();
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn test_get_key() {
         for cipher_suite in TestCryptoProvider::all_supported_cipher_suites() {
@@ -699,12 +1263,18 @@ mod tests {
             let clone_2 = ratchet_clone.next_message_key(&provider).await.unwrap();
 
             // Going back in time should result in an error
-            let res = ratchet_clone.get_message_key(&provider, 0).await;
+            let res = ratchet_clone
+                .get_message_key(&provider, 0, MAX_RATCHET_BACK_HISTORY)
+                .await;
             assert!(res.is_err());
 
             // Calling get key should be the same as calling next until hitting the desired generation
             let second_key = ratchet
-                .get_message_key(&provider, ratchet_clone.generation - 1)
+                .get_message_key(
+                    &provider,
+                    ratchet_clone.generation - 1,
+                    MAX_RATCHET_BACK_HISTORY,
+                )
                 .await
                 .unwrap();
 
@@ -747,12 +1317,21 @@ mod tests {
         let mut ordered_keys = Vec::<MessageKeyData>::new();
 
         for i in 0..=MAX_RATCHET_BACK_HISTORY {
-            ordered_keys.push(ratchet.get_message_key(&provider, i).await.unwrap());
+            ordered_keys.push(
+                ratchet
+                    .get_message_key(&provider, i, MAX_RATCHET_BACK_HISTORY)
+                    .await
+                    .unwrap(),
+            );
         }
 
         // Ask for a key at index MAX_RATCHET_BACK_HISTORY in the clone
         let last_key = ratchet_clone
-            .get_message_key(&provider, MAX_RATCHET_BACK_HISTORY)
+            .get_message_key(
+                &provider,
+                MAX_RATCHET_BACK_HISTORY,
+                MAX_RATCHET_BACK_HISTORY,
+            )
             .await
             .unwrap();
 
@@ -762,7 +1341,12 @@ mod tests {
         let mut back_history_keys = Vec::<MessageKeyData>::new();
 
         for i in 0..MAX_RATCHET_BACK_HISTORY - 1 {
-            back_history_keys.push(ratchet_clone.get_message_key(&provider, i).await.unwrap());
+            back_history_keys.push(
+                ratchet_clone
+                    .get_message_key(&provider, i, MAX_RATCHET_BACK_HISTORY)
+                    .await
+                    .unwrap(),
+            );
         }
 
         assert_eq!(
@@ -771,6 +1355,123 @@ mod tests {
         );
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn memory_usage_tracks_lazily_derived_secrets() {
+        let provider = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let secret = vec![0u8; provider.kdf_extract_size()];
+        let mut tree = get_test_tree(secret, 16u32);
+
+        let usage = tree.memory_usage();
+        assert_eq!(usage.node_secrets, 1);
+        assert_eq!(usage.sender_ratchets, 0);
+
+        tree.message_key_generation(
+            &provider,
+            0,
+            KeyType::Application,
+            0,
+            MAX_RATCHET_BACK_HISTORY,
+        )
+        .await
+        .unwrap();
+
+        // Only the copath of the leaf remains, the secrets on its direct path
+        // were deleted after deriving their children.
+        let usage = tree.memory_usage();
+        assert_eq!(usage.node_secrets, 4);
+        assert_eq!(usage.sender_ratchets, 1);
+        assert_eq!(usage.retained_message_keys, 0);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn memory_usage_is_linear_in_senders_for_large_groups() {
+        let provider = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let secret_len = provider.kdf_extract_size();
+        let leaf_count = 10_000u32;
+
+        // Like the ratchet tree, the secret tree always has a power of two leaves.
+        let mut tree = get_test_tree(vec![0u8; secret_len], leaf_count.next_power_of_two());
+        let mut ratchet_len = 0;
+
+        for leaf in 0..leaf_count {
+            tree.message_key_generation(
+                &provider,
+                NodeIndex::from(LeafIndex(leaf)),
+                KeyType::Application,
+                0,
+                MAX_RATCHET_BACK_HISTORY,
+            )
+            .await
+            .unwrap();
+
+            if leaf == 0 {
+                let usage = tree.memory_usage();
+                ratchet_len = usage.secret_bytes - usage.node_secrets * secret_len;
+            }
+        }
+
+        let usage = tree.memory_usage();
+
+        // The only node secrets left are the roots of the subtrees of the
+        // leaves past `leaf_count`, and no message key is retained.
+        let unused_leaves = leaf_count.next_power_of_two() - leaf_count;
+        assert_eq!(usage.node_secrets, unused_leaves.count_ones() as usize);
+        assert_eq!(usage.sender_ratchets, leaf_count as usize);
+        assert_eq!(usage.retained_message_keys, 0);
+
+        assert_eq!(
+            usage.secret_bytes,
+            usage.node_secrets * secret_len + usage.sender_ratchets * ratchet_len
+        );
+    }
+
+    #[cfg(feature = "out_of_order")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn retained_message_keys_can_be_limited() {
+        let provider = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let secret = vec![0u8; provider.kdf_extract_size()];
+        let mut tree = get_test_tree(secret, 16u32);
+
+        tree.message_key_generation(
+            &provider,
+            2,
+            KeyType::Application,
+            10,
+            MAX_RATCHET_BACK_HISTORY,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(tree.memory_usage().retained_message_keys, 10);
+
+        tree.retain_message_keys(&2, 3);
+        assert_eq!(tree.memory_usage().retained_message_keys, 3);
+
+        let res = tree
+            .message_key_generation(
+                &provider,
+                2,
+                KeyType::Application,
+                6,
+                MAX_RATCHET_BACK_HISTORY,
+            )
+            .await;
+
+        assert_matches!(res, Err(MlsError::KeyMissing(6)));
+
+        for generation in 7..10 {
+            tree.message_key_generation(
+                &provider,
+                2,
+                KeyType::Application,
+                generation,
+                MAX_RATCHET_BACK_HISTORY,
+            )
+            .await
+            .unwrap();
+        }
+    }
+
     #[cfg(not(feature = "out_of_order"))]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn out_of_order_keys_should_throw_error() {
@@ -781,8 +1482,13 @@ mod tests {
             .await
             .unwrap();
 
-        ratchet.get_message_key(&provider, 10).await.unwrap();
-        let res = ratchet.get_message_key(&provider, 9).await;
+        ratchet
+            .get_message_key(&provider, 10, MAX_RATCHET_BACK_HISTORY)
+            .await
+            .unwrap();
+        let res = ratchet
+            .get_message_key(&provider, 9, MAX_RATCHET_BACK_HISTORY)
+            .await;
         assert_matches!(res, Err(MlsError::KeyMissing(9)))
     }
 
@@ -796,7 +1502,11 @@ mod tests {
             .unwrap();
 
         let res = ratchet
-            .get_message_key(&provider, MAX_RATCHET_BACK_HISTORY + 1)
+            .get_message_key(
+                &provider,
+                MAX_RATCHET_BACK_HISTORY + 1,
+                MAX_RATCHET_BACK_HISTORY,
+            )
             .await;
 
         let invalid_generation = MAX_RATCHET_BACK_HISTORY + 1;
@@ -923,7 +1633,10 @@ mod interop_tests {
 
     use crate::{
         crypto::test_utils::try_test_cipher_suite_provider,
-        group::{ciphertext_processor::InteropSenderData, secret_tree::KeyType},
+        group::{
+            ciphertext_processor::InteropSenderData,
+            secret_tree::{KeyType, MAX_RATCHET_BACK_HISTORY},
+        },
     };
 
     use super::SecretTree;
@@ -953,6 +1666,7 @@ mod interop_tests {
                             (index as u32) * 2,
                             KeyType::Application,
                             leaf.generation,
+                            MAX_RATCHET_BACK_HISTORY,
                         )
                         .await
                         .unwrap();
@@ -966,6 +1680,7 @@ mod interop_tests {
                             (index as u32) * 2,
                             KeyType::Handshake,
                             leaf.generation,
+                            MAX_RATCHET_BACK_HISTORY,
                         )
                         .await
                         .unwrap();
@@ -1028,11 +1743,23 @@ mod interop_tests {
                                 let index = leaf * 2u32;
 
                                 let handshake_key = tree
-                                    .message_key_generation(&cs, index, KeyType::Handshake, gen)
+                                    .message_key_generation(
+                                        &cs,
+                                        index,
+                                        KeyType::Handshake,
+                                        gen,
+                                        MAX_RATCHET_BACK_HISTORY,
+                                    )
                                     .unwrap();
 
                                 let app_key = tree
-                                    .message_key_generation(&cs, index, KeyType::Application, gen)
+                                    .message_key_generation(
+                                        &cs,
+                                        index,
+                                        KeyType::Application,
+                                        gen,
+                                        MAX_RATCHET_BACK_HISTORY,
+                                    )
                                     .unwrap();
 
                                 InteropLeaf {
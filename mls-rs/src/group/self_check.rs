@@ -0,0 +1,203 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use crate::{
+    client::MlsError, client_config::ClientConfig, group::Group, tree_kem::node::NodeIndex,
+};
+
+/// Report of [`Group::self_check`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SelfCheckReport {
+    /// Inconsistencies found in the group state, in the order the checks
+    /// were performed.
+    pub issues: Vec<SelfCheckIssue>,
+}
+
+impl SelfCheckReport {
+    /// Determines if no inconsistency was found.
+    pub fn is_healthy(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Inconsistency of the group state found by [`Group::self_check`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SelfCheckIssue {
+    /// The tree hash of the ratchet tree does not match the tree hash of the
+    /// group context.
+    TreeHashMismatch,
+    /// The parent hashes of the ratchet tree are not valid.
+    InvalidParentHashes,
+    /// The leaf of this member is blank or outside of the ratchet tree.
+    MissingOwnLeaf { leaf_index: u32 },
+    /// No private key is held for a node of the direct path of this member
+    /// whose public key this member should know.
+    MissingPrivateKey { node_index: u32 },
+    /// The secret tree of the current epoch does not match the size of the
+    /// ratchet tree, or holds secrets of nodes outside of it.
+    SecretTreeOutOfBounds,
+    /// The [`GroupStateStorage`](crate::GroupStateStorage) holds a prior
+    /// epoch that is not older than the current epoch, e.g. because an older
+    /// group state was restored over a newer one.
+    StoredEpochAhead {
+        stored_epoch: u64,
+        current_epoch: u64,
+    },
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Verify the internal consistency of the group state, e.g. to diagnose
+    /// a state that fails to process messages after being restored from
+    /// storage.
+    ///
+    /// The check recomputes the tree hash and the parent hashes of the
+    /// ratchet tree, checks that this member holds the private keys of its
+    /// direct path and that the secret tree and the prior epochs in storage
+    /// are consistent with the current epoch. Inconsistencies are listed in
+    /// the returned report. An error is only returned if a check could not be
+    /// performed, e.g. because the storage failed.
+    ///
+    /// The check does not modify the group. It is expensive for large groups.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn self_check(&self) -> Result<SelfCheckReport, MlsError> {
+        let mut issues = Vec::new();
+        let tree = &self.state.public_tree;
+
+        let tree_hash = tree
+            .recomputed_tree_hash(&self.cipher_suite_provider)
+            .await?;
+
+        if tree_hash != self.state.context.tree_hash {
+            issues.push(SelfCheckIssue::TreeHashMismatch);
+        }
+
+        if tree
+            .validate_parent_hashes(&self.cipher_suite_provider)
+            .await
+            .is_err()
+        {
+            issues.push(SelfCheckIssue::InvalidParentHashes);
+        }
+
+        self.check_private_keys(&mut issues)?;
+
+        #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+        if !self
+            .epoch_secrets
+            .secret_tree
+            .within_bounds(&tree.total_leaf_count())
+        {
+            issues.push(SelfCheckIssue::SecretTreeOutOfBounds);
+        }
+
+        #[cfg(feature = "prior_epoch")]
+        if let Some(stored_epoch) = self.state_repo.max_stored_epoch_id().await? {
+            let current_epoch = self.current_epoch();
+
+            if stored_epoch >= current_epoch {
+                issues.push(SelfCheckIssue::StoredEpochAhead {
+                    stored_epoch,
+                    current_epoch,
+                });
+            }
+        }
+
+        Ok(SelfCheckReport { issues })
+    }
+
+    fn check_private_keys(&self, issues: &mut Vec<SelfCheckIssue>) -> Result<(), MlsError> {
+        let self_index = self.private_tree.self_index;
+        let nodes = &self.state.public_tree.nodes;
+
+        if nodes.borrow_as_leaf(self_index).is_err() {
+            issues.push(SelfCheckIssue::MissingOwnLeaf {
+                leaf_index: *self_index,
+            });
+
+            return Ok(());
+        }
+
+        let secret_keys = &self.private_tree.secret_keys;
+
+        if secret_keys.first().map_or(true, Option::is_none) {
+            issues.push(SelfCheckIssue::MissingPrivateKey {
+                node_index: NodeIndex::from(self_index),
+            });
+        }
+
+        let path = nodes.direct_copath(self_index);
+        let filtered = nodes.filtered(self_index)?;
+
+        for (i, (n, f)) in path.iter().zip(filtered).enumerate() {
+            // The key of a node is only known if this member was not added
+            // below it since it was last set.
+            let known = !f
+                && nodes
+                    .borrow_as_parent(n.path)
+                    .map_or(false, |p| !p.unmerged_leaves.contains(&self_index));
+
+            if known && secret_keys.get(i + 1).map_or(true, Option::is_none) {
+                issues.push(SelfCheckIssue::MissingPrivateKey { node_index: n.path });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::SelfCheckIssue;
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::test_utils::test_group,
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn consistent_group_is_healthy() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob, _) = alice.join("bob").await;
+
+        alice.group.write_to_storage().await.unwrap();
+
+        let report = alice.group.self_check().await.unwrap();
+        assert!(report.is_healthy());
+
+        let report = bob.group.self_check().await.unwrap();
+        assert!(report.is_healthy());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn inconsistencies_are_reported() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        alice.join("bob").await;
+
+        // A commit with a path gives alice the private key of the root.
+        alice.group.commit(vec![]).await.unwrap();
+        alice.process_pending_commit().await.unwrap();
+
+        alice.group.private_tree.secret_keys[1] = None;
+        alice.group.state.context.tree_hash = vec![0; 32];
+
+        let report = alice.group.self_check().await.unwrap();
+
+        assert_eq!(
+            report.issues,
+            vec![
+                SelfCheckIssue::TreeHashMismatch,
+                SelfCheckIssue::MissingPrivateKey { node_index: 1 }
+            ]
+        );
+    }
+}
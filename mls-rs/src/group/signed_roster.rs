@@ -0,0 +1,295 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{crypto::CipherSuiteProvider, identity::SigningIdentity};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{Group, GroupContext},
+    signer::{Signable, SignatureLabel},
+    tree_kem::node::LeafIndex,
+    CipherSuite,
+};
+
+/// Membership of a group in an epoch as seen by one of its members, created
+/// with [`Group::signed_roster`].
+///
+/// The roster lists the signing identity of each member with its leaf index,
+/// in order of leaf index, and is signed by the leaf key of the member that
+/// created it. It allows systems outside of the group, such as access control
+/// engines, to consume membership assertions they can verify with
+/// [`SignedRoster::verify`].
+///
+/// A third party can only check that the roster was signed by a member
+/// listed in it and that it matches the [`GroupContext`] of the epoch. It
+/// trusts the signer to list the members of the tree whose hash is in the
+/// group context.
+#[derive(Clone, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+pub struct SignedRoster {
+    cipher_suite: CipherSuite,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: Vec<u8>,
+    epoch: u64,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    tree_hash: Vec<u8>,
+    members: Vec<SignedRosterEntry>,
+    signer_index: LeafIndex,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    signature: Vec<u8>,
+}
+
+/// Member listed in a [`SignedRoster`].
+#[derive(Clone, Debug, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+#[non_exhaustive]
+pub struct SignedRosterEntry {
+    /// Leaf index of the member.
+    pub index: u32,
+    /// Signing identity of the member. The consumer of the roster is
+    /// responsible for validating the credential it contains.
+    pub signing_identity: SigningIdentity,
+}
+
+impl Debug for SignedRoster {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignedRoster")
+            .field("cipher_suite", &self.cipher_suite)
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("epoch", &self.epoch)
+            .field(
+                "tree_hash",
+                &mls_rs_core::debug::pretty_bytes(&self.tree_hash),
+            )
+            .field("members", &self.members)
+            .field("signer_index", &self.signer_index)
+            .field(
+                "signature",
+                &mls_rs_core::debug::pretty_bytes(&self.signature),
+            )
+            .finish()
+    }
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct SignedRosterTBS<'a> {
+    cipher_suite: CipherSuite,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: &'a [u8],
+    epoch: u64,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    tree_hash: &'a [u8],
+    members: &'a [SignedRosterEntry],
+    signer_index: LeafIndex,
+}
+
+impl<'a> Signable<'a> for SignedRoster {
+    const SIGN_LABEL: &'static str = SignatureLabel::SignedRoster.as_str();
+
+    type SigningContext = ();
+
+    fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    fn signable_content(
+        &self,
+        _context: &Self::SigningContext,
+    ) -> Result<Vec<u8>, mls_rs_codec::Error> {
+        SignedRosterTBS {
+            cipher_suite: self.cipher_suite,
+            group_id: &self.group_id,
+            epoch: self.epoch,
+            tree_hash: &self.tree_hash,
+            members: &self.members,
+            signer_index: self.signer_index,
+        }
+        .mls_encode_to_vec()
+    }
+
+    fn write_signature(&mut self, signature: Vec<u8>) {
+        self.signature = signature
+    }
+}
+
+impl SignedRoster {
+    /// Identifier of the group.
+    pub fn group_id(&self) -> &[u8] {
+        &self.group_id
+    }
+
+    /// Epoch of the roster.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Tree hash of the epoch.
+    pub fn tree_hash(&self) -> &[u8] {
+        &self.tree_hash
+    }
+
+    /// Members of the group, in order of leaf index.
+    pub fn members(&self) -> &[SignedRosterEntry] {
+        &self.members
+    }
+
+    /// The member that signed the roster.
+    pub fn signer(&self) -> Result<&SignedRosterEntry, MlsError> {
+        self.members
+            .iter()
+            .find(|member| member.index == *self.signer_index)
+            .ok_or(MlsError::MemberNotFound)
+    }
+
+    /// Serialize the signed roster.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+
+    /// Deserialize a signed roster produced by [`SignedRoster::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    /// Check that the roster matches the epoch described by `group_context`
+    /// and was signed by the [signer](SignedRoster::signer) listed in it.
+    ///
+    /// `group_context` must come from a trusted source. The credential of the
+    /// signer must be validated by the caller.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn verify<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+        group_context: &GroupContext,
+    ) -> Result<(), MlsError> {
+        if self.cipher_suite != group_context.cipher_suite
+            || self.cipher_suite != cipher_suite_provider.cipher_suite()
+        {
+            return Err(MlsError::CipherSuiteMismatch);
+        }
+
+        if self.group_id != group_context.group_id {
+            return Err(MlsError::GroupIdMismatch);
+        }
+
+        if self.epoch != group_context.epoch {
+            return Err(MlsError::InvalidEpoch);
+        }
+
+        if self.tree_hash != group_context.tree_hash {
+            return Err(MlsError::TreeHashMismatch);
+        }
+
+        let signer = self.signer()?;
+
+        Signable::verify(
+            self,
+            cipher_suite_provider,
+            &signer.signing_identity.signature_key,
+            &(),
+        )
+        .await
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Create a [`SignedRoster`] of the current epoch, signed by the local
+    /// member.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn signed_roster(&self) -> Result<SignedRoster, MlsError> {
+        let members = self
+            .state
+            .public_tree
+            .non_empty_leaves()
+            .map(|(index, leaf)| SignedRosterEntry {
+                index: *index,
+                signing_identity: leaf.signing_identity.clone(),
+            })
+            .collect();
+
+        let mut roster = SignedRoster {
+            cipher_suite: self.cipher_suite(),
+            group_id: self.group_id().to_vec(),
+            epoch: self.current_epoch(),
+            tree_hash: self.context().tree_hash.clone(),
+            members,
+            signer_index: self.private_tree.self_index,
+            signature: Vec::new(),
+        };
+
+        roster
+            .sign(&self.cipher_suite_provider, &self.signer, &())
+            .await?;
+
+        Ok(roster)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use super::SignedRoster;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        crypto::test_utils::test_cipher_suite_provider,
+        group::test_utils::{test_group, test_n_member_group},
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn third_party_can_verify_signed_roster() {
+        let groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let roster = groups[1].group.signed_roster().await.unwrap();
+        let roster = SignedRoster::from_bytes(&roster.to_bytes().unwrap()).unwrap();
+
+        roster.verify(&cs, groups[0].group.context()).await.unwrap();
+
+        let members = groups[0].group.roster().members();
+        assert_eq!(roster.members().len(), members.len());
+
+        for (entry, member) in roster.members().iter().zip(members) {
+            assert_eq!(entry.index, member.index);
+            assert_eq!(entry.signing_identity, member.signing_identity);
+        }
+
+        assert_eq!(roster.signer().unwrap().index, 1);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn tampered_signed_roster_is_rejected() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob, _) = alice.join("bob").await;
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let mut roster = alice.group.signed_roster().await.unwrap();
+        roster.members.swap(0, 1);
+
+        let res = roster.verify(&cs, bob.group.context()).await;
+        assert_matches!(res, Err(MlsError::InvalidSignature));
+
+        let roster = alice.group.signed_roster().await.unwrap();
+
+        alice.group.commit(vec![]).await.unwrap();
+        alice.process_pending_commit().await.unwrap();
+
+        let res = roster.verify(&cs, alice.group.context()).await;
+        assert_matches!(res, Err(MlsError::InvalidEpoch));
+    }
+}
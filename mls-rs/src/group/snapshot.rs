@@ -2,20 +2,23 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+use alloc::vec::Vec;
+
 use crate::{
-    client::MlsError,
+    client::{Client, MlsError},
     client_config::ClientConfig,
     group::{
-        key_schedule::KeySchedule, CommitGeneration, ConfirmationTag, Group, GroupContext,
-        GroupState, InterimTranscriptHash, ReInitProposal, TreeKemPublic,
+        key_schedule::KeySchedule, CommitGeneration, CommitOutput, ConfirmationTag, Group,
+        GroupContext, GroupState, InterimTranscriptHash, ReInitProposal, RosterDiff,
+        TranscriptChainEntry, TreeKemPublic,
     },
-    tree_kem::TreeKemPrivate,
+    tree_kem::{node::LeafIndex, TreeKemPrivate},
 };
 
 #[cfg(feature = "by_ref_proposal")]
 use crate::{
     crypto::{HpkePublicKey, HpkeSecretKey},
-    group::ProposalRef,
+    group::{Proposal, ProposalRef, Sender},
     map::SmallMap,
 };
 
@@ -30,7 +33,13 @@ use mls_rs_core::identity::IdentityProvider;
 
 use super::{cipher_suite_provider, epoch::EpochSecrets, state_repo::GroupStateRepository};
 
-#[derive(Debug, PartialEq, Clone, MlsEncode, MlsDecode, MlsSize)]
+/// Version of the [`Snapshot`] layout written by this crate.
+///
+/// Version 1 is the layout written before the fields following `signer` were
+/// added. It is still decoded, with defaults for the missing fields.
+pub(crate) const SNAPSHOT_VERSION: u16 = 2;
+
+#[derive(Debug, PartialEq, Clone, MlsEncode, MlsSize)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct Snapshot {
     version: u16,
@@ -42,6 +51,157 @@ pub(crate) struct Snapshot {
     pending_updates: SmallMap<HpkePublicKey, (HpkeSecretKey, Option<SignatureSecretKey>)>,
     pending_commit: Option<CommitGeneration>,
     signer: SignatureSecretKey,
+    // Fields added in version 2. New fields must be appended here and the
+    // version bumped, so that older layouts can still be decoded.
+    pending_leave: bool,
+    requires_self_update: bool,
+    epoch_started_at: Option<u64>,
+    epoch_committer: LeafIndex,
+    transcript_chain: Vec<TranscriptChainEntry>,
+    member_activity: Vec<Option<u64>>,
+    roster_diffs: Vec<RosterDiff>,
+    archived: bool,
+}
+
+impl Snapshot {
+    /// Decode a snapshot of any supported version.
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        let version = u16::mls_decode(&mut &*bytes)?;
+
+        if !Self::is_supported(version) {
+            return Err(MlsError::UnsupportedSnapshotVersion(version));
+        }
+
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    /// Decode a snapshot in the version 1 layout, with defaults for the
+    /// fields added in later versions.
+    pub(crate) fn from_v1_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        SnapshotV1::mls_decode(&mut &*bytes)
+            .map(Into::into)
+            .map_err(Into::into)
+    }
+
+    fn is_supported(version: u16) -> bool {
+        version == SNAPSHOT_VERSION || version == 1
+    }
+}
+
+impl MlsDecode for Snapshot {
+    fn mls_decode(reader: &mut &[u8]) -> Result<Self, mls_rs_codec::Error> {
+        let version = u16::mls_decode(&mut &**reader)?;
+
+        if !Self::is_supported(version) {
+            return Err(mls_rs_codec::Error::UnsupportedEnumDiscriminant);
+        }
+
+        if version == 1 {
+            return SnapshotV1::mls_decode(reader).map(Into::into);
+        }
+
+        Ok(Self {
+            version: u16::mls_decode(reader)?,
+            state: RawGroupState::mls_decode(reader)?,
+            private_tree: TreeKemPrivate::mls_decode(reader)?,
+            epoch_secrets: EpochSecrets::mls_decode(reader)?,
+            key_schedule: KeySchedule::mls_decode(reader)?,
+            #[cfg(feature = "by_ref_proposal")]
+            pending_updates: SmallMap::mls_decode(reader)?,
+            pending_commit: Option::mls_decode(reader)?,
+            signer: SignatureSecretKey::mls_decode(reader)?,
+            pending_leave: bool::mls_decode(reader)?,
+            requires_self_update: bool::mls_decode(reader)?,
+            epoch_started_at: Option::mls_decode(reader)?,
+            epoch_committer: LeafIndex::mls_decode(reader)?,
+            transcript_chain: Vec::mls_decode(reader)?,
+            member_activity: Vec::mls_decode(reader)?,
+            roster_diffs: Vec::mls_decode(reader)?,
+            archived: bool::mls_decode(reader)?,
+        })
+    }
+}
+
+/// Layout of version 1 snapshots.
+#[derive(MlsEncode, MlsDecode, MlsSize)]
+struct SnapshotV1 {
+    version: u16,
+    state: RawGroupStateV1,
+    private_tree: TreeKemPrivate,
+    epoch_secrets: EpochSecrets,
+    key_schedule: KeySchedule,
+    #[cfg(feature = "by_ref_proposal")]
+    pending_updates: SmallMap<HpkePublicKey, (HpkeSecretKey, Option<SignatureSecretKey>)>,
+    pending_commit: Option<CommitGeneration>,
+    signer: SignatureSecretKey,
+}
+
+#[derive(MlsEncode, MlsDecode, MlsSize)]
+struct RawGroupStateV1 {
+    context: GroupContext,
+    #[cfg(feature = "by_ref_proposal")]
+    proposals: SmallMap<ProposalRef, CachedProposalV1>,
+    public_tree: TreeKemPublic,
+    interim_transcript_hash: InterimTranscriptHash,
+    pending_reinit: Option<ReInitProposal>,
+    confirmation_tag: ConfirmationTag,
+}
+
+/// Cached proposal of version 1 snapshots, which did not record when the
+/// proposal was received.
+#[cfg(feature = "by_ref_proposal")]
+#[derive(MlsEncode, MlsDecode, MlsSize)]
+struct CachedProposalV1 {
+    proposal: Proposal,
+    sender: Sender,
+}
+
+impl From<SnapshotV1> for Snapshot {
+    fn from(snapshot: SnapshotV1) -> Self {
+        let state = snapshot.state;
+
+        #[cfg(feature = "by_ref_proposal")]
+        let mut proposals = SmallMap::default();
+
+        #[cfg(feature = "by_ref_proposal")]
+        for (proposal_ref, p) in state.proposals.iter() {
+            let proposal = CachedProposal {
+                proposal: p.proposal.clone(),
+                sender: p.sender,
+                received_at: None,
+            };
+
+            proposals.insert(proposal_ref.clone(), proposal);
+        }
+
+        Self {
+            version: SNAPSHOT_VERSION,
+            state: RawGroupState {
+                context: state.context,
+                #[cfg(feature = "by_ref_proposal")]
+                proposals,
+                public_tree: state.public_tree,
+                interim_transcript_hash: state.interim_transcript_hash,
+                pending_reinit: state.pending_reinit,
+                confirmation_tag: state.confirmation_tag,
+            },
+            epoch_committer: snapshot.private_tree.self_index,
+            private_tree: snapshot.private_tree,
+            epoch_secrets: snapshot.epoch_secrets,
+            key_schedule: snapshot.key_schedule,
+            #[cfg(feature = "by_ref_proposal")]
+            pending_updates: snapshot.pending_updates,
+            pending_commit: snapshot.pending_commit,
+            signer: snapshot.signer,
+            pending_leave: false,
+            requires_self_update: false,
+            epoch_started_at: None,
+            transcript_chain: Vec::new(),
+            member_activity: Vec::new(),
+            roster_diffs: Vec::new(),
+            archived: false,
+        }
+    }
 }
 
 #[derive(Debug, MlsEncode, MlsDecode, MlsSize, PartialEq, Clone)]
@@ -144,9 +304,43 @@ where
     /// that is currently in use by the group.
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn write_to_storage(&mut self) -> Result<(), MlsError> {
+        if self.removed {
+            return Err(MlsError::GroupUsedAfterRemoval);
+        }
+
         self.state_repo.write_to_storage(self.snapshot()).await
     }
 
+    /// Load a group from a snapshot provisioned out of band, e.g. by a fleet
+    /// management service, instead of joining it with a welcome message.
+    ///
+    /// `snapshot` is the group state as written by [`Group::write_to_storage`]
+    /// to the [`GroupStateStorage`](crate::GroupStateStorage) of the
+    /// provisioning client. The secrets in the snapshot are known to whoever
+    /// provisioned it, so this function immediately creates a commit that
+    /// updates the own leaf and its path. The returned commit must be sent to
+    /// the group. Until a commit updating the own leaf is applied, all commits
+    /// created by this member include a path and encrypting application
+    /// messages fails with [`MlsError::SelfUpdateRequired`].
+    ///
+    /// The signing key of the member is part of the snapshot and is not
+    /// changed by the commit.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn from_provisioned_snapshot(
+        client: &Client<C>,
+        snapshot: &[u8],
+    ) -> Result<(Self, CommitOutput), MlsError> {
+        let snapshot = Snapshot::from_bytes(snapshot)?;
+        let mut group = Self::from_snapshot(client.config.clone(), snapshot).await?;
+
+        group.pending_commit = None;
+        group.requires_self_update = true;
+
+        let commit_output = group.commit(Vec::new()).await?;
+
+        Ok((group, commit_output))
+    }
+
     pub(crate) fn snapshot(&self) -> Snapshot {
         Snapshot {
             state: RawGroupState::export(&self.state),
@@ -155,8 +349,16 @@ where
             #[cfg(feature = "by_ref_proposal")]
             pending_updates: self.pending_updates.clone(),
             pending_commit: self.pending_commit.clone(),
+            pending_leave: self.pending_leave,
+            requires_self_update: self.requires_self_update,
+            epoch_started_at: self.epoch_started_at,
+            epoch_committer: self.epoch_committer,
+            transcript_chain: self.transcript_chain.clone(),
+            member_activity: self.member_activity.clone(),
+            roster_diffs: self.roster_diffs.clone(),
+            archived: self.archived,
             epoch_secrets: self.epoch_secrets.clone(),
-            version: 1,
+            version: SNAPSHOT_VERSION,
             signer: self.signer.clone(),
         }
     }
@@ -193,7 +395,31 @@ where
             #[cfg(feature = "by_ref_proposal")]
             pending_updates: snapshot.pending_updates,
             pending_commit: snapshot.pending_commit,
-            #[cfg(test)]
+            pending_leave: snapshot.pending_leave,
+            requires_self_update: snapshot.requires_self_update,
+            epoch_started_at: snapshot.epoch_started_at,
+            epoch_committer: snapshot.epoch_committer,
+            removed: false,
+            #[cfg(feature = "private_message")]
+            sender_data_keys: Default::default(),
+            #[cfg(feature = "private_message")]
+            length_histogram: Default::default(),
+            #[cfg(feature = "private_message")]
+            batch_sender_data: None,
+            watermark_batch: None,
+            previous_epoch_sends: 0,
+            #[cfg(feature = "prior_epoch")]
+            previous_epoch_sendable: false,
+            epoch_view: None,
+            transcript_chain: snapshot.transcript_chain,
+            member_activity: snapshot.member_activity,
+            roster_diffs: snapshot.roster_diffs,
+            archived: snapshot.archived,
+            #[cfg(feature = "event_sourcing")]
+            epoch_deltas: Vec::new(),
+            #[cfg(feature = "event_sourcing")]
+            deltas_since_compaction: 0,
+            #[cfg(any(test, feature = "adversarial"))]
             commit_modifiers: Default::default(),
             epoch_secrets: snapshot.epoch_secrets,
             state_repo,
@@ -220,7 +446,16 @@ pub(crate) mod test_utils {
         tree_kem::{node::LeafIndex, TreeKemPrivate},
     };
 
-    use super::{RawGroupState, Snapshot};
+    use super::{RawGroupState, Snapshot, SNAPSHOT_VERSION};
+
+    use {
+        super::{RawGroupStateV1, SnapshotV1},
+        alloc::vec::Vec,
+        mls_rs_codec::MlsEncode,
+    };
+
+    #[cfg(feature = "by_ref_proposal")]
+    use {super::CachedProposalV1, crate::map::SmallMap};
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub(crate) async fn get_test_snapshot(cipher_suite: CipherSuite, epoch_id: u64) -> Snapshot {
@@ -241,24 +476,81 @@ pub(crate) mod test_utils {
             #[cfg(feature = "by_ref_proposal")]
             pending_updates: Default::default(),
             pending_commit: None,
-            version: 1,
+            pending_leave: false,
+            requires_self_update: false,
+            epoch_started_at: None,
+            epoch_committer: LeafIndex(0),
+            transcript_chain: Default::default(),
+            member_activity: Default::default(),
+            roster_diffs: Default::default(),
+            archived: false,
+            version: SNAPSHOT_VERSION,
             signer: vec![].into(),
         }
     }
+
+    /// Encode `snapshot` in the version 1 layout.
+    pub(crate) fn snapshot_v1_bytes(snapshot: Snapshot) -> Vec<u8> {
+        let state = snapshot.state;
+
+        #[cfg(feature = "by_ref_proposal")]
+        let mut proposals = SmallMap::default();
+
+        #[cfg(feature = "by_ref_proposal")]
+        for (proposal_ref, p) in state.proposals.iter() {
+            let proposal = CachedProposalV1 {
+                proposal: p.proposal.clone(),
+                sender: p.sender,
+            };
+
+            proposals.insert(proposal_ref.clone(), proposal);
+        }
+
+        SnapshotV1 {
+            version: 1,
+            state: RawGroupStateV1 {
+                context: state.context,
+                #[cfg(feature = "by_ref_proposal")]
+                proposals,
+                public_tree: state.public_tree,
+                interim_transcript_hash: state.interim_transcript_hash,
+                pending_reinit: state.pending_reinit,
+                confirmation_tag: state.confirmation_tag,
+            },
+            private_tree: snapshot.private_tree,
+            epoch_secrets: snapshot.epoch_secrets,
+            key_schedule: snapshot.key_schedule,
+            #[cfg(feature = "by_ref_proposal")]
+            pending_updates: snapshot.pending_updates,
+            pending_commit: snapshot.pending_commit,
+            signer: snapshot.signer,
+        }
+        .mls_encode_to_vec()
+        .unwrap()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use alloc::vec;
+    use assert_matches::assert_matches;
+    use mls_rs_codec::MlsEncode;
 
     use crate::{
-        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        client::{
+            test_utils::{TestClientConfig, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            Client, MlsError,
+        },
         group::{
+            framing::Content,
             test_utils::{test_group, TestGroup},
-            Group,
+            Group, MlsMessage, Validated,
         },
+        key_package::test_utils::test_key_package_message,
     };
 
+    use super::{test_utils::snapshot_v1_bytes, Snapshot, SNAPSHOT_VERSION};
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn snapshot_restore(group: TestGroup) {
         let snapshot = group.group.snapshot();
@@ -305,6 +597,145 @@ mod tests {
         snapshot_restore(group).await
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn version_1_snapshot_is_decoded_with_defaults() {
+        let mut group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        #[cfg(feature = "by_ref_proposal")]
+        {
+            let update_proposal = group.update_proposal().await;
+            let _ = group.group.proposal_message(update_proposal, vec![]).await;
+        }
+
+        let snapshot = group.group.snapshot();
+        let bytes = snapshot_v1_bytes(snapshot.clone());
+        let decoded = Snapshot::from_bytes(&bytes).unwrap();
+
+        assert_eq!(decoded.version, SNAPSHOT_VERSION);
+        assert_eq!(decoded.state.context, snapshot.state.context);
+        assert_eq!(decoded.private_tree, snapshot.private_tree);
+        assert_eq!(decoded.epoch_secrets, snapshot.epoch_secrets);
+        assert_eq!(decoded.key_schedule, snapshot.key_schedule);
+        assert_eq!(decoded.signer, snapshot.signer);
+        assert_eq!(decoded.epoch_committer, snapshot.private_tree.self_index);
+        assert!(decoded.transcript_chain.is_empty());
+        assert!(decoded.member_activity.is_empty());
+        assert!(!decoded.archived);
+
+        #[cfg(feature = "by_ref_proposal")]
+        {
+            assert_eq!(decoded.state.proposals.len(), 1);
+
+            assert!(decoded
+                .state
+                .proposals
+                .iter()
+                .all(|(_, p)| p.received_at.is_none()));
+        }
+
+        let restored = Group::from_snapshot(group.group.config.clone(), decoded)
+            .await
+            .unwrap();
+
+        assert_eq!(restored.context(), group.group.context());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn unknown_snapshot_version_is_rejected() {
+        let group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let mut bytes = group.group.snapshot().mls_encode_to_vec().unwrap();
+        bytes[..2].copy_from_slice(&7u16.to_be_bytes());
+
+        let res = Snapshot::from_bytes(&bytes).map(|_| ());
+        assert_matches!(res, Err(MlsError::UnsupportedSnapshotVersion(7)));
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn provision_bob() -> (TestGroup, Group<TestClientConfig>, MlsMessage) {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob, _) = alice.join("bob").await;
+
+        let snapshot = bob.group.snapshot().mls_encode_to_vec().unwrap();
+        let client = Client::new(bob.group.config.clone(), None, None, TEST_PROTOCOL_VERSION);
+
+        let (device, commit) = Group::from_provisioned_snapshot(&client, &snapshot)
+            .await
+            .unwrap();
+
+        (alice, device, commit.commit_message)
+    }
+
+    fn commit_has_path(message: MlsMessage) -> bool {
+        let Content::Commit(commit) = message.into_plaintext().unwrap().content.content else {
+            panic!("Expected commit")
+        };
+
+        commit.path.is_some()
+    }
+
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn provisioned_group_requires_self_update_before_application_messages() {
+        let (mut alice, mut device, commit) = provision_bob().await;
+
+        assert!(device.requires_self_update());
+        assert!(commit_has_path(commit.clone()));
+
+        let res = device
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::SelfUpdateRequired));
+
+        alice.process_message(commit).await.unwrap();
+        device.apply_pending_commit().await.unwrap();
+
+        assert!(!device.requires_self_update());
+
+        let message = device
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+
+        alice.process_message(message).await.unwrap();
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn provisioned_group_forces_path_until_self_update() {
+        let (mut alice, mut device, _) = provision_bob().await;
+
+        // Another member's commit does not update the own leaf
+        let commit = alice.group.commit(vec![]).await.unwrap();
+        alice.process_pending_commit().await.unwrap();
+        device
+            .process_incoming_message_validated(Validated::new(commit.commit_message).unwrap())
+            .await
+            .unwrap();
+
+        assert!(device.requires_self_update());
+
+        let restored = Group::from_snapshot(device.config.clone(), device.snapshot())
+            .await
+            .unwrap();
+
+        assert!(restored.requires_self_update());
+
+        let key_package =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "carol").await;
+
+        let commit = device
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        assert!(commit_has_path(commit.commit_message));
+    }
+
     #[cfg(feature = "serde")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn serde() {
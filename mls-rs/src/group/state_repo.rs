@@ -8,7 +8,7 @@ use crate::{group::PriorEpoch, key_package::KeyPackageRef};
 use alloc::collections::VecDeque;
 use alloc::vec::Vec;
 use core::fmt::{self, Debug};
-use mls_rs_codec::{MlsDecode, MlsEncode};
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
 use mls_rs_core::group::{EpochRecord, GroupState};
 use mls_rs_core::{error::IntoAnyError, group::GroupStateStorage, key_package::KeyPackageStorage};
 
@@ -96,6 +96,61 @@ where
         }
     }
 
+    /// Number of prior epochs retained, both pending a write and in storage.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn retained_epoch_count(&self) -> Result<u64, MlsError> {
+        let pending = self.pending_commit.inserts.len() as u64;
+
+        let Some(mut epoch_id) = self
+            .storage
+            .max_epoch_id(&self.group_id)
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?
+        else {
+            return Ok(pending);
+        };
+
+        let mut stored = 0;
+
+        // Stored epochs are contiguous, since the oldest ones are deleted first.
+        while self
+            .storage
+            .epoch(&self.group_id, epoch_id)
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?
+            .is_some()
+        {
+            stored += 1;
+
+            let Some(previous) = epoch_id.checked_sub(1) else {
+                break;
+            };
+
+            epoch_id = previous;
+        }
+
+        Ok(pending + stored)
+    }
+
+    /// Identifier of the most recent prior epoch written to storage.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn max_stored_epoch_id(&self) -> Result<Option<u64>, MlsError> {
+        self.storage
+            .max_epoch_id(&self.group_id)
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))
+    }
+
+    /// Encoded size of the prior epochs held in memory until the next write.
+    pub fn pending_epochs_size(&self) -> usize {
+        self.pending_commit
+            .inserts
+            .iter()
+            .chain(&self.pending_commit.updates)
+            .map(|epoch| epoch.mls_encoded_len())
+            .sum()
+    }
+
     #[cfg(feature = "psk")]
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn resumption_secret(
@@ -191,8 +246,24 @@ where
         Ok(())
     }
 
+    /// Write the group state and pending prior epochs in a transaction of the
+    /// group state storage, and remove the used key package in a transaction
+    /// of the key package storage.
+    ///
+    /// The two storages are independent, so the two transactions are not
+    /// atomic together. The group state transaction is committed first. If
+    /// committing the key package transaction then fails, the group state
+    /// remains written and removing the key package is retried by the next
+    /// call.
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn write_to_storage(&mut self, group_snapshot: Snapshot) -> Result<(), MlsError> {
+        self.begin_transaction().await?;
+        let res = self.write_pending(group_snapshot).await;
+        self.end_transaction(res).await
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn write_pending(&mut self, group_snapshot: Snapshot) -> Result<(), MlsError> {
         let inserts = self
             .pending_commit
             .inserts
@@ -224,12 +295,90 @@ where
                 .map_err(|e| MlsError::KeyPackageRepoError(e.into_any_error()))?;
         }
 
+        Ok(())
+    }
+
+    /// Delete the group state and all prior epochs from storage, discarding
+    /// any pending changes.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn delete_from_storage(&mut self) -> Result<(), MlsError> {
         self.pending_commit.inserts.clear();
         self.pending_commit.updates.clear();
 
+        self.begin_transaction().await?;
+        let res = self.delete_all().await;
+        self.end_transaction(res).await
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn delete_all(&mut self) -> Result<(), MlsError> {
+        self.storage
+            .delete(&self.group_id)
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?;
+
+        if let Some(ref key_package_ref) = self.pending_key_package_removal {
+            self.key_package_repo
+                .delete(key_package_ref)
+                .await
+                .map_err(|e| MlsError::KeyPackageRepoError(e.into_any_error()))?;
+        }
+
         Ok(())
     }
 
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn begin_transaction(&mut self) -> Result<(), MlsError> {
+        self.storage
+            .begin_transaction()
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?;
+
+        let res = self
+            .key_package_repo
+            .begin_transaction()
+            .await
+            .map_err(|e| MlsError::KeyPackageRepoError(e.into_any_error()));
+
+        if res.is_err() {
+            let _ = self.storage.rollback_transaction().await;
+        }
+
+        res
+    }
+
+    // Commit the transactions if `result` is a success and roll them back
+    // otherwise, preserving the original error. Pending epochs are written
+    // once the group state transaction is committed, even if committing the
+    // key package transaction fails afterwards.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn end_transaction(&mut self, result: Result<(), MlsError>) -> Result<(), MlsError> {
+        let res = match result {
+            Ok(()) => self
+                .storage
+                .commit_transaction()
+                .await
+                .map_err(|e| MlsError::GroupStorageError(e.into_any_error())),
+            Err(e) => {
+                let _ = self.storage.rollback_transaction().await;
+                Err(e)
+            }
+        };
+
+        if let Err(e) = res {
+            let _ = self.key_package_repo.rollback_transaction().await;
+            return Err(e);
+        }
+
+        self.pending_commit.inserts.clear();
+        self.pending_commit.updates.clear();
+
+        self.key_package_repo
+            .commit_transaction()
+            .await
+            .map_err(|e| MlsError::KeyPackageRepoError(e.into_any_error()))
+    }
+
     #[cfg(any(feature = "psk", feature = "private_message"))]
     fn find_pending(&self, epoch_id: u64) -> Option<usize> {
         self.pending_commit
@@ -241,6 +390,8 @@ where
 
 #[cfg(test)]
 mod tests {
+    #[cfg(mls_build_async)]
+    use alloc::boxed::Box;
     use alloc::vec;
     use mls_rs_codec::MlsEncode;
 
@@ -253,6 +404,7 @@ mod tests {
         },
         storage_provider::in_memory::{InMemoryGroupStateStorage, InMemoryKeyPackageStorage},
     };
+    use mls_rs_core::key_package::KeyPackageData;
 
     use super::*;
 
@@ -570,4 +722,203 @@ mod tests {
 
         assert!(repo.key_package_repo.get(&key_package.reference).is_none());
     }
+
+    #[derive(Clone, Debug, Default)]
+    struct TransactionLog {
+        calls: Vec<&'static str>,
+        fail_write: bool,
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl GroupStateStorage for TransactionLog {
+        type Error = MlsError;
+
+        async fn state(&self, _group_id: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+            Ok(None)
+        }
+
+        async fn epoch(
+            &self,
+            _group_id: &[u8],
+            _epoch_id: u64,
+        ) -> Result<Option<Vec<u8>>, Self::Error> {
+            Ok(None)
+        }
+
+        async fn write(
+            &mut self,
+            _state: GroupState,
+            _epoch_inserts: Vec<EpochRecord>,
+            _epoch_updates: Vec<EpochRecord>,
+        ) -> Result<(), Self::Error> {
+            self.calls.push("write");
+
+            match self.fail_write {
+                true => Err(MlsError::InvalidEpoch),
+                false => Ok(()),
+            }
+        }
+
+        async fn max_epoch_id(&self, _group_id: &[u8]) -> Result<Option<u64>, Self::Error> {
+            Ok(None)
+        }
+
+        async fn begin_transaction(&mut self) -> Result<(), Self::Error> {
+            self.calls.push("begin");
+            Ok(())
+        }
+
+        async fn commit_transaction(&mut self) -> Result<(), Self::Error> {
+            self.calls.push("commit");
+            Ok(())
+        }
+
+        async fn rollback_transaction(&mut self) -> Result<(), Self::Error> {
+            self.calls.push("rollback");
+            Ok(())
+        }
+    }
+
+    fn logging_repo(
+        fail_write: bool,
+    ) -> GroupStateRepository<TransactionLog, InMemoryKeyPackageStorage> {
+        let storage = TransactionLog {
+            fail_write,
+            ..Default::default()
+        };
+
+        GroupStateRepository::new(
+            TEST_GROUP.to_vec(),
+            storage,
+            InMemoryKeyPackageStorage::default(),
+            None,
+        )
+        .unwrap()
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn writes_happen_in_a_transaction() {
+        let mut repo = logging_repo(false);
+
+        repo.insert(test_epoch(0)).await.unwrap();
+        repo.write_to_storage(test_snapshot(0).await).await.unwrap();
+
+        assert_eq!(repo.storage.calls, ["begin", "write", "commit"]);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn failed_write_is_rolled_back() {
+        let mut repo = logging_repo(true);
+
+        repo.insert(test_epoch(0)).await.unwrap();
+
+        let res = repo.write_to_storage(test_snapshot(0).await).await;
+
+        assert_matches::assert_matches!(res, Err(MlsError::GroupStorageError(_)));
+        assert_eq!(repo.storage.calls, ["begin", "write", "rollback"]);
+
+        // Pending epochs are kept so that writing can be retried.
+        assert_eq!(repo.pending_commit.inserts.len(), 1);
+    }
+
+    #[derive(Clone, Debug, Default)]
+    struct KeyPackageLog {
+        calls: Vec<&'static str>,
+        fail_commit: bool,
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl KeyPackageStorage for KeyPackageLog {
+        type Error = MlsError;
+
+        async fn delete(&mut self, _id: &[u8]) -> Result<(), Self::Error> {
+            self.calls.push("delete");
+            Ok(())
+        }
+
+        async fn insert(&mut self, _id: Vec<u8>, _pkg: KeyPackageData) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn get(&self, _id: &[u8]) -> Result<Option<KeyPackageData>, Self::Error> {
+            Ok(None)
+        }
+
+        async fn begin_transaction(&mut self) -> Result<(), Self::Error> {
+            self.calls.push("begin");
+            Ok(())
+        }
+
+        async fn commit_transaction(&mut self) -> Result<(), Self::Error> {
+            self.calls.push("commit");
+
+            match self.fail_commit {
+                true => Err(MlsError::InvalidEpoch),
+                false => Ok(()),
+            }
+        }
+
+        async fn rollback_transaction(&mut self) -> Result<(), Self::Error> {
+            self.calls.push("rollback");
+            Ok(())
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn failed_key_package_commit_keeps_group_state() {
+        let key_package_repo = KeyPackageLog {
+            fail_commit: true,
+            ..Default::default()
+        };
+
+        let mut repo = GroupStateRepository::new(
+            TEST_GROUP.to_vec(),
+            TransactionLog::default(),
+            key_package_repo,
+            Some(KeyPackageRef::from(vec![0; 16])),
+        )
+        .unwrap();
+
+        repo.insert(test_epoch(0)).await.unwrap();
+
+        let res = repo.write_to_storage(test_snapshot(0).await).await;
+
+        assert_matches::assert_matches!(res, Err(MlsError::KeyPackageRepoError(_)));
+
+        // The group state is written even though the key package is not
+        // deleted.
+        assert_eq!(repo.storage.calls, ["begin", "write", "commit"]);
+        assert_eq!(repo.key_package_repo.calls, ["begin", "delete", "commit"]);
+        assert!(repo.pending_commit.inserts.is_empty());
+
+        // Deleting the key package is retried by the next write.
+        repo.key_package_repo.fail_commit = false;
+        repo.write_to_storage(test_snapshot(0).await).await.unwrap();
+
+        assert_eq!(
+            repo.key_package_repo.calls,
+            ["begin", "delete", "commit", "begin", "delete", "commit"]
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn failed_group_state_write_rolls_back_key_package_deletion() {
+        let mut repo = GroupStateRepository::new(
+            TEST_GROUP.to_vec(),
+            TransactionLog {
+                fail_write: true,
+                ..Default::default()
+            },
+            KeyPackageLog::default(),
+            Some(KeyPackageRef::from(vec![0; 16])),
+        )
+        .unwrap();
+
+        let res = repo.write_to_storage(test_snapshot(0).await).await;
+
+        assert_matches::assert_matches!(res, Err(MlsError::GroupStorageError(_)));
+        assert_eq!(repo.key_package_repo.calls, ["begin", "rollback"]);
+    }
 }
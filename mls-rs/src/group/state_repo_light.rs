@@ -44,8 +44,17 @@ where
         })
     }
 
+    /// Write the group state and removal of the used key package in a single
+    /// storage transaction.
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn write_to_storage(&mut self, group_snapshot: Snapshot) -> Result<(), MlsError> {
+        self.begin_transaction().await?;
+        let res = self.write_pending(group_snapshot).await;
+        self.end_transaction(res).await
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn write_pending(&mut self, group_snapshot: Snapshot) -> Result<(), MlsError> {
         let group_state = GroupState {
             data: group_snapshot.mls_encode_to_vec()?,
             id: group_snapshot.state.context.group_id,
@@ -65,6 +74,72 @@ where
 
         Ok(())
     }
+
+    /// Delete the group state from storage.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn delete_from_storage(&mut self, group_id: &[u8]) -> Result<(), MlsError> {
+        self.begin_transaction().await?;
+        let res = self.delete_all(group_id).await;
+        self.end_transaction(res).await
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn delete_all(&mut self, group_id: &[u8]) -> Result<(), MlsError> {
+        self.storage
+            .delete(group_id)
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?;
+
+        if let Some(ref key_package_ref) = self.pending_key_package_removal {
+            self.key_package_repo
+                .delete(key_package_ref)
+                .await
+                .map_err(|e| MlsError::KeyPackageRepoError(e.into_any_error()))?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn begin_transaction(&mut self) -> Result<(), MlsError> {
+        self.storage
+            .begin_transaction()
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?;
+
+        let res = self
+            .key_package_repo
+            .begin_transaction()
+            .await
+            .map_err(|e| MlsError::KeyPackageRepoError(e.into_any_error()));
+
+        if res.is_err() {
+            let _ = self.storage.rollback_transaction().await;
+        }
+
+        res
+    }
+
+    // Commit the transaction if `result` is a success and roll it back
+    // otherwise, preserving the original error.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn end_transaction(&mut self, result: Result<(), MlsError>) -> Result<(), MlsError> {
+        if let Err(e) = result {
+            let _ = self.storage.rollback_transaction().await;
+            let _ = self.key_package_repo.rollback_transaction().await;
+            return Err(e);
+        }
+
+        self.storage
+            .commit_transaction()
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?;
+
+        self.key_package_repo
+            .commit_transaction()
+            .await
+            .map_err(|e| MlsError::KeyPackageRepoError(e.into_any_error()))
+    }
 }
 
 #[cfg(test)]
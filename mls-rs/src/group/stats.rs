@@ -0,0 +1,130 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use mls_rs_codec::MlsSize;
+
+use crate::{client::MlsError, client_config::ClientConfig, group::Group};
+
+/// Size report of a group, returned by [`Group::stats`].
+///
+/// Sizes are in bytes. They are meant to monitor the growth of groups and to
+/// tune retention settings, e.g. the number of prior epochs kept by the
+/// [`GroupStateStorage`](crate::GroupStateStorage).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct GroupStats {
+    /// Number of members of the group.
+    pub member_count: usize,
+    /// Number of nodes of the ratchet tree, including blank nodes.
+    pub tree_node_count: usize,
+    /// Number of blank nodes of the ratchet tree.
+    pub blank_node_count: usize,
+    /// Encoded size of the ratchet tree, as exported with
+    /// [`Group::export_tree`].
+    pub tree_size: usize,
+    /// Encoded size of the group state written to storage by
+    /// [`Group::write_to_storage`], excluding prior epochs.
+    pub snapshot_size: usize,
+    /// Number of prior epochs retained to process delayed messages, both
+    /// written to storage and pending the next write.
+    pub retained_epochs: u64,
+    /// Number of proposals received in the current epoch and not committed
+    /// yet.
+    pub cached_proposal_count: usize,
+    /// Approximate memory held by the group, i.e. the encoded size of its
+    /// state and of the prior epochs pending the next write.
+    pub approximate_memory_size: usize,
+    /// Number of application messages sent in the previous epoch with
+    /// [`Group::encrypt_at_epoch`] since the group was created or loaded.
+    pub previous_epoch_sends: u64,
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Report the size of the group and of its state.
+    ///
+    /// Counting retained epochs reads the epochs stored by the
+    /// [`GroupStateStorage`](crate::GroupStateStorage), which may be slow.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn stats(&self) -> Result<GroupStats, MlsError> {
+        let nodes = &self.current_epoch_tree().nodes;
+
+        #[cfg(feature = "prior_epoch")]
+        let (retained_epochs, pending_epochs_size) = (
+            self.state_repo.retained_epoch_count().await?,
+            self.state_repo.pending_epochs_size(),
+        );
+
+        #[cfg(not(feature = "prior_epoch"))]
+        let (retained_epochs, pending_epochs_size) = (0, 0);
+
+        #[cfg(feature = "by_ref_proposal")]
+        let cached_proposal_count = self.state.proposals.proposals.len();
+
+        #[cfg(not(feature = "by_ref_proposal"))]
+        let cached_proposal_count = 0;
+
+        let snapshot_size = self.snapshot().mls_encoded_len();
+
+        Ok(GroupStats {
+            member_count: nodes.non_empty_leaves().count(),
+            tree_node_count: nodes.len(),
+            blank_node_count: nodes.iter().filter(|node| node.is_none()).count(),
+            tree_size: nodes.mls_encoded_len(),
+            snapshot_size,
+            retained_epochs,
+            cached_proposal_count,
+            approximate_memory_size: snapshot_size + pending_epochs_size,
+            previous_epoch_sends: self.previous_epoch_sends,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::test_utils::test_group,
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn stats_track_group_growth() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let initial = alice.group.stats().await.unwrap();
+
+        assert_eq!(initial.member_count, 1);
+        assert_eq!(initial.tree_node_count, 1);
+        assert_eq!(initial.blank_node_count, 0);
+        assert_eq!(initial.cached_proposal_count, 0);
+
+        alice.join("bob").await;
+        alice.join("carol").await;
+
+        alice.group.commit(vec![]).await.unwrap();
+        alice.process_pending_commit().await.unwrap();
+
+        let stats = alice.group.stats().await.unwrap();
+
+        assert_eq!(stats.member_count, 3);
+        assert_eq!(stats.tree_node_count, 5);
+        assert!(stats.tree_size > initial.tree_size);
+        assert!(stats.snapshot_size > initial.snapshot_size);
+        assert!(stats.approximate_memory_size >= stats.snapshot_size);
+
+        #[cfg(feature = "prior_epoch")]
+        {
+            assert_eq!(stats.retained_epochs, 3);
+
+            alice.group.write_to_storage().await.unwrap();
+
+            let stats = alice.group.stats().await.unwrap();
+            assert_eq!(stats.retained_epochs, 3);
+        }
+    }
+}
@@ -48,7 +48,7 @@ impl TestGroup {
     #[cfg(feature = "by_ref_proposal")]
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub(crate) async fn update_proposal(&mut self) -> Proposal {
-        self.group.update_proposal(None, None).await.unwrap()
+        self.group.update_proposal(None, None, None).await.unwrap()
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -100,7 +100,7 @@ impl TestGroup {
 
         // Group from new member's perspective
         let (new_group, _) = Group::join(
-            &welcome_messages[0],
+            Validated::check(&welcome_messages[0]).unwrap(),
             ratchet_tree,
             new_client.config.clone(),
             new_client.signer.clone().unwrap(),
@@ -131,7 +131,9 @@ impl TestGroup {
         &mut self,
         message: MlsMessage,
     ) -> Result<ReceivedMessage, MlsError> {
-        self.group.process_incoming_message(message).await
+        self.group
+            .process_incoming_message_validated(Validated::new(message).unwrap())
+            .await
     }
 
     #[cfg(feature = "private_message")]
@@ -368,14 +370,19 @@ pub(crate) async fn get_test_groups_with_features(
 
         for group in groups.iter_mut().skip(1) {
             group
-                .process_incoming_message(commit_output.commit_message.clone())
+                .process_incoming_message_validated(
+                    Validated::new(commit_output.commit_message.clone()).unwrap(),
+                )
                 .await
                 .unwrap();
         }
 
         groups.push(
             client
-                .join_group(None, &commit_output.welcome_messages[0])
+                .join_group_validated(
+                    None,
+                    &Validated::new(commit_output.welcome_messages[0].clone()).unwrap(),
+                )
                 .await
                 .unwrap()
                 .0,
@@ -489,8 +496,15 @@ impl MessageProcessor for GroupWithoutKeySchedule {
     async fn process_ciphertext(
         &mut self,
         cipher_text: &PrivateMessage,
+        #[cfg(feature = "application_lanes")] lane: u16,
     ) -> Result<EventOrContent<Self::OutputType>, MlsError> {
-        self.inner.process_ciphertext(cipher_text).await
+        self.inner
+            .process_ciphertext(
+                cipher_text,
+                #[cfg(feature = "application_lanes")]
+                lane,
+            )
+            .await
     }
 
     #[cfg_attr(coverage_nightly, coverage(off))]
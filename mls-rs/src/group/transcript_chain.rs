@@ -0,0 +1,364 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::identity::SigningIdentity;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{
+        framing::{Content, MlsMessagePayload},
+        message_signature::AuthenticatedContent,
+        transcript_hashes, Group, InterimTranscriptHash, MlsMessage,
+    },
+    tree_kem::node::LeafIndex,
+};
+
+/// Confirmed transcript hash of an epoch and the member that created it,
+/// as recorded in a [`TranscriptChain`].
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct TranscriptChainEntry {
+    /// Epoch of the entry.
+    pub epoch: u64,
+    /// Confirmed transcript hash of the group context of the epoch.
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::vec_serde"))]
+    pub confirmed_transcript_hash: Vec<u8>,
+    /// Leaf index of the member that committed the epoch.
+    ///
+    /// For the epoch in which the recording member joined, this is the
+    /// signer of the group info used to join, i.e. the committer when joining
+    /// with a welcome message. For the first epoch of a group, this is the
+    /// creator of the group.
+    pub committer: u32,
+    /// Signing identity of `committer` in the epoch.
+    pub committer_identity: SigningIdentity,
+    /// Interim transcript hash of the epoch, from which the confirmed
+    /// transcript hash of the next epoch is computed.
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::vec_serde"))]
+    pub interim_transcript_hash: Vec<u8>,
+}
+
+impl Debug for TranscriptChainEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TranscriptChainEntry")
+            .field("epoch", &self.epoch)
+            .field(
+                "confirmed_transcript_hash",
+                &mls_rs_core::debug::pretty_bytes(&self.confirmed_transcript_hash),
+            )
+            .field("committer", &self.committer)
+            .field("committer_identity", &self.committer_identity)
+            .field(
+                "interim_transcript_hash",
+                &mls_rs_core::debug::pretty_bytes(&self.interim_transcript_hash),
+            )
+            .finish()
+    }
+}
+
+/// Ordered chain of confirmed transcript hashes of a group, exported with
+/// [`Group::transcript_chain`].
+///
+/// Each confirmed transcript hash covers the commit that created its epoch
+/// and, through the interim transcript hash, all previous commits. The chain
+/// lets compliance systems demonstrate the order of membership events, e.g.
+/// by matching it against chains exported by other members, without storing
+/// any message content.
+///
+/// The chain is serialized with [`TranscriptChain::to_bytes`] using the MLS
+/// encoding, so equal chains always have equal serializations.
+#[derive(Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct TranscriptChain {
+    /// Identifier of the group.
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::vec_serde"))]
+    pub group_id: Vec<u8>,
+    /// Entries in increasing epoch order.
+    pub entries: Vec<TranscriptChainEntry>,
+}
+
+impl Debug for TranscriptChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TranscriptChain")
+            .field(
+                "group_id",
+                &mls_rs_core::debug::pretty_group_id(&self.group_id),
+            )
+            .field("entries", &self.entries)
+            .finish()
+    }
+}
+
+impl TranscriptChain {
+    /// Serialize the chain.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+
+    /// Deserialize a chain produced by [`TranscriptChain::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Export the chain of confirmed transcript hashes from `since_epoch` to
+    /// the current epoch.
+    ///
+    /// Entries are only recorded if enabled with
+    /// [`ClientBuilder::record_transcript_chain`](crate::client_builder::ClientBuilder::record_transcript_chain),
+    /// starting with the epoch in which the group was created or joined.
+    /// Returns [`MlsError::EpochNotFound`] if no entry was recorded for
+    /// `since_epoch`, either because it is older than the first recorded
+    /// epoch or because it was removed by
+    /// [`Group::prune_transcript_chain`].
+    pub fn transcript_chain(&self, since_epoch: u64) -> Result<TranscriptChain, MlsError> {
+        let start = self
+            .transcript_chain
+            .iter()
+            .position(|entry| entry.epoch == since_epoch)
+            .ok_or(MlsError::EpochNotFound)?;
+
+        Ok(TranscriptChain {
+            group_id: self.group_id().to_vec(),
+            entries: self.transcript_chain[start..].to_vec(),
+        })
+    }
+
+    /// Remove the recorded entries of the transcript chain for epochs before
+    /// `epoch`, e.g. once they were exported with [`Group::transcript_chain`].
+    pub fn prune_transcript_chain(&mut self, epoch: u64) {
+        self.transcript_chain.retain(|entry| entry.epoch >= epoch);
+    }
+
+    /// Verify that `commits`, the commits sent in consecutive epochs starting
+    /// with `from_epoch`, produce the transcript hashes recorded for the
+    /// epochs they created, e.g. to check a group restored from a backup
+    /// against the commit log of the delivery service.
+    ///
+    /// Transcript hashes are computed from the interim transcript hash
+    /// recorded for `from_epoch`, and compared with the hashes recorded in
+    /// the [transcript chain](Group::transcript_chain) or with the current
+    /// epoch. Commits must be public messages. Returns
+    /// [`MlsError::EpochNotFound`] if an epoch was not recorded and
+    /// [`MlsError::TranscriptHashMismatch`] with the first epoch whose hashes
+    /// differ.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn verify_transcript(
+        &self,
+        from_epoch: u64,
+        commits: &[MlsMessage],
+    ) -> Result<(), MlsError> {
+        let mut interim_transcript_hash = InterimTranscriptHash::from(
+            self.transcript_chain_entry(from_epoch)?
+                .interim_transcript_hash
+                .clone(),
+        );
+
+        for (epoch, commit) in (from_epoch..).zip(commits) {
+            let MlsMessagePayload::Plain(commit) = &commit.payload else {
+                return Err(MlsError::UnexpectedMessageType);
+            };
+
+            let auth_content = AuthenticatedContent::from(commit.clone());
+
+            if !matches!(auth_content.content.content, Content::Commit(_)) {
+                return Err(MlsError::UnexpectedMessageType);
+            }
+
+            if auth_content.content.group_id != self.group_id() {
+                return Err(MlsError::GroupIdMismatch);
+            }
+
+            if auth_content.content.epoch != epoch {
+                return Err(MlsError::InvalidEpoch);
+            }
+
+            let (interim, confirmed) = transcript_hashes(
+                &self.cipher_suite_provider,
+                &interim_transcript_hash,
+                &auth_content,
+            )
+            .await?;
+
+            let (expected_interim, expected_confirmed) = if epoch + 1 == self.current_epoch() {
+                (
+                    &self.state.interim_transcript_hash[..],
+                    &self.state.context.confirmed_transcript_hash[..],
+                )
+            } else {
+                let entry = self.transcript_chain_entry(epoch + 1)?;
+
+                (
+                    &entry.interim_transcript_hash[..],
+                    &entry.confirmed_transcript_hash[..],
+                )
+            };
+
+            if interim[..] != *expected_interim || confirmed[..] != *expected_confirmed {
+                return Err(MlsError::TranscriptHashMismatch(epoch + 1));
+            }
+
+            interim_transcript_hash = interim;
+        }
+
+        Ok(())
+    }
+
+    fn transcript_chain_entry(&self, epoch: u64) -> Result<&TranscriptChainEntry, MlsError> {
+        self.transcript_chain
+            .iter()
+            .find(|entry| entry.epoch == epoch)
+            .ok_or(MlsError::EpochNotFound)
+    }
+
+    /// Record the current epoch, created by `committer`, if enabled.
+    pub(crate) fn record_transcript_chain_entry(&mut self, committer: LeafIndex) {
+        if !self.config.record_transcript_chain() {
+            return;
+        }
+
+        // The committer is a member of the epoch it created, having either
+        // signed the commit or the group info for this epoch.
+        let Ok(leaf) = self.state.public_tree.get_leaf_node(committer) else {
+            return;
+        };
+
+        let entry = TranscriptChainEntry {
+            epoch: self.state.context.epoch,
+            confirmed_transcript_hash: self.state.context.confirmed_transcript_hash.to_vec(),
+            committer: *committer,
+            committer_identity: leaf.signing_identity.clone(),
+            interim_transcript_hash: self.state.interim_transcript_hash.to_vec(),
+        };
+
+        self.transcript_chain.push(entry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use super::TranscriptChain;
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::test_utils::{test_group, test_group_custom_config},
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn members_export_equal_chains() {
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.record_transcript_chain(true)
+        })
+        .await;
+
+        let (mut bob, _) = alice
+            .join_with_custom_config("bob", false, |c| {
+                c.0.settings.record_transcript_chain = true
+            })
+            .await
+            .unwrap();
+
+        let commit = bob.group.commit(vec![]).await.unwrap().commit_message;
+        bob.process_pending_commit().await.unwrap();
+        alice.process_message(commit).await.unwrap();
+
+        let chain = alice.group.transcript_chain(1).unwrap();
+
+        assert_eq!(chain, bob.group.transcript_chain(1).unwrap());
+        assert_eq!(chain.entries.len(), 2);
+        assert_eq!(chain.entries[0].committer, 0);
+        assert_eq!(chain.entries[1].committer, 1);
+
+        assert_eq!(
+            chain.entries[1].confirmed_transcript_hash,
+            alice.group.context().confirmed_transcript_hash.to_vec()
+        );
+
+        let bytes = chain.to_bytes().unwrap();
+        assert_eq!(TranscriptChain::from_bytes(&bytes).unwrap(), chain);
+
+        assert_eq!(alice.group.transcript_chain(0).unwrap().entries.len(), 3);
+        assert_matches!(bob.group.transcript_chain(0), Err(MlsError::EpochNotFound));
+
+        alice.group.prune_transcript_chain(2);
+        assert_matches!(
+            alice.group.transcript_chain(1),
+            Err(MlsError::EpochNotFound)
+        );
+        assert_eq!(alice.group.transcript_chain(2).unwrap().entries.len(), 1);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn transcript_is_verified_from_commits() {
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.record_transcript_chain(true)
+        })
+        .await;
+
+        let (mut bob, _) = alice.join("bob").await;
+
+        // Commit from epoch 1 that is discarded and never applied.
+        let discarded = bob.group.commit(vec![]).await.unwrap().commit_message;
+        bob.group.clear_pending_commit();
+
+        let mut commits = vec![];
+
+        for _ in 0..2 {
+            let commit = bob.group.commit(vec![]).await.unwrap().commit_message;
+            bob.process_pending_commit().await.unwrap();
+            alice.process_message(commit.clone()).await.unwrap();
+            commits.push(commit);
+        }
+
+        alice.group.verify_transcript(1, &commits).await.unwrap();
+        alice
+            .group
+            .verify_transcript(2, &commits[1..])
+            .await
+            .unwrap();
+
+        let res = alice.group.verify_transcript(1, &commits[1..]).await;
+        assert_matches!(res, Err(MlsError::InvalidEpoch));
+
+        let res = alice
+            .group
+            .verify_transcript(1, &[discarded, commits[1].clone()])
+            .await;
+
+        assert_matches!(res, Err(MlsError::TranscriptHashMismatch(2)));
+
+        let res = bob.group.verify_transcript(1, &commits).await;
+        assert_matches!(res, Err(MlsError::EpochNotFound));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn chain_is_not_recorded_by_default() {
+        let alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        assert_matches!(
+            alice.group.transcript_chain(0),
+            Err(MlsError::EpochNotFound)
+        );
+    }
+}
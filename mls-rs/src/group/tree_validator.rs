@@ -0,0 +1,125 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use core::fmt::{self, Debug};
+
+use mls_rs_core::error::AnyError;
+
+use crate::group::{ExportedTree, GroupContext};
+
+/// External check of the ratchet tree of each new epoch, configured with
+/// [`ClientBuilder::tree_validator`](crate::client_builder::ClientBuilder::tree_validator).
+///
+/// The validator is called when a commit is processed, either received with
+/// [`Group::process_incoming_message_validated`](crate::group::Group::process_incoming_message_validated)
+/// or created by this member and applied with
+/// [`Group::apply_pending_commit`](crate::group::Group::apply_pending_commit),
+/// after the commit was verified and before the group moves to the new
+/// epoch. Deployments can use it to check the tree hash found in `context`
+/// with an external consistency service, e.g. one gossiping tree hashes
+/// between servers, and reject commits that would fork the group.
+///
+/// Rejecting the tree makes processing the commit fail without changing the
+/// group state. A rejected commit created by this member stays pending and
+/// can be discarded with
+/// [`Group::clear_pending_commit`](crate::group::Group::clear_pending_commit).
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+pub trait TreeValidator: Send + Sync {
+    /// Check `tree`, the ratchet tree of the new epoch described by
+    /// `context`.
+    async fn validate_tree(
+        &self,
+        context: &GroupContext,
+        tree: &ExportedTree<'_>,
+    ) -> Result<(), AnyError>;
+}
+
+/// [`TreeValidator`] configured for a client, shared by its groups.
+#[derive(Clone)]
+pub struct SharedTreeValidator(pub(crate) Arc<dyn TreeValidator>);
+
+impl Debug for SharedTreeValidator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TreeValidator")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(mls_build_async)]
+    use alloc::boxed::Box;
+    use alloc::sync::Arc;
+    use assert_matches::assert_matches;
+    use mls_rs_core::error::{AnyError, IntoAnyError};
+
+    use super::{SharedTreeValidator, TreeValidator};
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{
+            test_utils::{test_group, test_group_custom_config},
+            ExportedTree, GroupContext,
+        },
+    };
+
+    struct RejectEpoch(u64);
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl TreeValidator for RejectEpoch {
+        async fn validate_tree(
+            &self,
+            context: &GroupContext,
+            tree: &ExportedTree<'_>,
+        ) -> Result<(), AnyError> {
+            assert!(!tree.to_bytes().unwrap().is_empty());
+
+            if context.epoch == self.0 {
+                Err(MlsError::InvalidEpoch.into_any_error())
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn rejected_tree_fails_received_commit() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let (mut bob, _) = alice
+            .join_with_custom_config("bob", false, |c| {
+                c.0.settings.tree_validator = Some(SharedTreeValidator(Arc::new(RejectEpoch(2))))
+            })
+            .await
+            .unwrap();
+
+        let commit = alice.group.commit(vec![]).await.unwrap().commit_message;
+        let res = bob.process_message(commit).await;
+
+        assert_matches!(res, Err(MlsError::TreeRejected(_)));
+        assert_eq!(bob.group.current_epoch(), 1);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn rejected_tree_keeps_own_commit_pending() {
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.tree_validator(RejectEpoch(1))
+        })
+        .await;
+
+        alice.group.commit(vec![]).await.unwrap();
+        let res = alice.process_pending_commit().await;
+
+        assert_matches!(res, Err(MlsError::TreeRejected(_)));
+        assert_eq!(alice.group.current_epoch(), 0);
+        assert!(alice.group.has_pending_commit());
+    }
+}
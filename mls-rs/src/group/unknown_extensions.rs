@@ -0,0 +1,154 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use mls_rs_core::extension::{Extension, ExtensionList, ExtensionType};
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::Group,
+    tree_kem::{node::LeafIndex, TreeKemPublic},
+};
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Group context extensions of types that are neither default types nor
+    /// supported by this client.
+    ///
+    /// Unknown extensions are retained with the exact bytes they were
+    /// received with, so that they are re-encoded identically. Groups with
+    /// unknown [critical](ExtensionType::is_critical) extensions are rejected
+    /// with [`MlsError::UnknownCriticalExtension`].
+    pub fn unknown_group_context_extensions(&self) -> Vec<Extension> {
+        let known = self.config.supported_extensions();
+
+        self.context().extensions.unknown(&known).cloned().collect()
+    }
+
+    /// Leaf node extensions of the member at `index` of types that are
+    /// neither default types nor supported by this client, see
+    /// [`Group::unknown_group_context_extensions`]. Returns `None` if there
+    /// is no member at `index`.
+    pub fn unknown_leaf_extensions(&self, index: u32) -> Option<Vec<Extension>> {
+        let known = self.config.supported_extensions();
+
+        self.current_epoch_tree()
+            .get_leaf_node(LeafIndex(index))
+            .ok()
+            .map(|leaf| leaf.extensions.unknown(&known).cloned().collect())
+    }
+}
+
+/// Check that neither the group context extensions nor the extensions of any
+/// leaf of `tree` contain an unknown critical extension.
+pub(crate) fn check_critical_extensions(
+    known: &[ExtensionType],
+    context_extensions: &ExtensionList,
+    tree: &TreeKemPublic,
+) -> Result<(), MlsError> {
+    let leaf_extensions = tree
+        .nodes
+        .non_empty_leaves()
+        .map(|(_, leaf)| &leaf.extensions);
+
+    core::iter::once(context_extensions)
+        .chain(leaf_extensions)
+        .find_map(|extensions| extensions.unknown_critical(known))
+        .map_or(Ok(()), |ext_type| {
+            Err(MlsError::UnknownCriticalExtension(ext_type))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+    use mls_rs_core::extension::{Extension, ExtensionList, ExtensionType};
+
+    use crate::{
+        client::{
+            test_utils::{
+                TestClientBuilder, TestClientConfig, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION,
+            },
+            MlsError,
+        },
+        group::test_utils::test_group,
+        Client, MlsMessage,
+    };
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn key_package_with_leaf_extension(name: &str, extension: Extension) -> MlsMessage {
+        let client: Client<TestClientConfig> = TestClientBuilder::new_for_test()
+            .with_random_signing_identity(name, TEST_CIPHER_SUITE)
+            .await
+            .build();
+
+        client
+            .generate_key_package_message_with_extensions(
+                ExtensionList::new(),
+                ExtensionList::from(vec![extension]),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn unknown_non_critical_leaf_extensions_are_preserved() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let extension = Extension::new(ExtensionType::new(0x0042), vec![1, 2, 3]);
+        let key_package = key_package_with_leaf_extension("carol", extension.clone()).await;
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.process_pending_commit().await.unwrap();
+        bob.process_message(commit.commit_message).await.unwrap();
+
+        assert_eq!(bob.group.unknown_leaf_extensions(2), Some(vec![extension]));
+        assert_eq!(bob.group.unknown_leaf_extensions(3), None);
+        assert!(bob.group.unknown_group_context_extensions().is_empty());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn unknown_critical_leaf_extensions_are_rejected() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let ext_type = ExtensionType::new(0x8042);
+        assert!(ext_type.is_critical());
+
+        // Alice supports the extension, Bob does not.
+        alice.group.config.0.settings.extension_types.push(ext_type);
+
+        let extension = Extension::new(ext_type, vec![1]);
+        let key_package = key_package_with_leaf_extension("carol", extension).await;
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.process_pending_commit().await.unwrap();
+
+        let res = bob.process_message(commit.commit_message).await;
+
+        assert_matches!(res, Err(MlsError::UnknownCriticalExtension(t)) if t == ext_type);
+    }
+}
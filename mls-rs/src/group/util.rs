@@ -89,15 +89,33 @@ where
     C: CipherSuiteProvider,
     I: IdentityProvider,
 {
-    let tree = match group_info.extensions.get_as::<RatchetTreeExt>()? {
-        Some(ext) => ext.tree_data,
-        None => tree.ok_or(MlsError::RatchetTreeNotFound)?,
+    let (tree_data, detached) = match group_info.extensions.get_as::<RatchetTreeExt>()? {
+        Some(ext) => (ext.tree_data, false),
+        None => (tree.ok_or(MlsError::RatchetTreeNotFound)?, true),
     };
 
     let context = &group_info.group_context;
 
-    let mut tree =
-        TreeKemPublic::import_node_data(tree.into(), id_provider, &context.extensions).await?;
+    let mut tree = TreeKemPublic::new();
+    tree.nodes = tree_data.into();
+
+    // A tree delivered separately from the group info is not covered by its
+    // signature, so it is matched against the signed tree hash before any
+    // of its content is used.
+    if detached {
+        let tree_hash = tree.tree_hash(cs).await?;
+
+        if tree_hash != context.tree_hash {
+            return Err(MlsError::RatchetTreeHashMismatch(
+                context.tree_hash.clone(),
+                tree_hash,
+            ));
+        }
+    }
+
+    #[cfg(feature = "tree_index")]
+    tree.initialize_index_if_necessary(id_provider, &context.extensions)
+        .await?;
 
     // Verify the integrity of the ratchet tree
     TreeValidator::new(cs, context, id_provider)
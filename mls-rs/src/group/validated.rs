@@ -0,0 +1,237 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use core::ops::Deref;
+
+use mls_rs_core::protocol_version::ProtocolVersion;
+
+use crate::{
+    client::MlsError,
+    group::{
+        framing::{Content, FramedContent, MlsMessagePayload, Sender},
+        GroupInfo, Welcome,
+    },
+    tree_kem::leaf_node::LeafNodeSource,
+    KeyPackage, MlsMessage,
+};
+
+#[cfg(feature = "by_ref_proposal")]
+use crate::group::proposal::Proposal;
+
+/// Value received from outside of the library that passed the structural
+/// checks performed when decoding it.
+///
+/// A `Validated` value can only be obtained by decoding and validating a
+/// message, e.g. with [`Validated::from_bytes`]. The checks cover the
+/// consistency of the message with itself, such as matching protocol versions
+/// of the message and its payload, and do not depend on the state of a group.
+/// Signatures and credentials are verified when the message is processed.
+///
+/// APIs changing the state of a group or client, such as
+/// [`Client::join_group_validated`](crate::Client::join_group_validated) or
+/// [`Group::process_incoming_message_validated`](crate::Group::process_incoming_message_validated),
+/// take validated values. The variants of these APIs taking unvalidated
+/// messages are deprecated.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Validated<T>(T);
+
+pub(crate) trait Validate {
+    fn validate(&self) -> Result<(), MlsError>;
+}
+
+impl<T: Validate + ?Sized> Validate for &T {
+    fn validate(&self) -> Result<(), MlsError> {
+        (**self).validate()
+    }
+}
+
+impl<T> Validated<T> {
+    pub(crate) fn check(value: T) -> Result<Self, MlsError>
+    where
+        T: Validate,
+    {
+        value.validate()?;
+        Ok(Self(value))
+    }
+
+    /// The validated value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+
+    pub(crate) fn borrowed(&self) -> Validated<&T> {
+        Validated(&self.0)
+    }
+}
+
+impl<T> Deref for Validated<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> AsRef<T> for Validated<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl Validated<MlsMessage> {
+    /// Deserialize a message from transport and validate it.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::new(MlsMessage::from_bytes(bytes)?)
+    }
+
+    /// Validate a message decoded by other means than
+    /// [`Validated::from_bytes`].
+    pub fn new(message: MlsMessage) -> Result<Self, MlsError> {
+        Self::check(message)
+    }
+
+    /// The key package carried by the message, if any.
+    pub fn into_key_package(self) -> Option<Validated<KeyPackage>> {
+        self.0.into_key_package().map(Validated)
+    }
+
+    /// The group info carried by the message, if any.
+    pub fn into_group_info(self) -> Option<Validated<GroupInfo>> {
+        self.0.into_group_info().map(Validated)
+    }
+}
+
+impl From<Validated<MlsMessage>> for MlsMessage {
+    fn from(message: Validated<MlsMessage>) -> Self {
+        message.0
+    }
+}
+
+impl Validate for MlsMessage {
+    fn validate(&self) -> Result<(), MlsError> {
+        match &self.payload {
+            MlsMessagePayload::Plain(message) => message.content.validate(),
+            #[cfg(feature = "private_message")]
+            MlsMessagePayload::Cipher(_) => Ok(()),
+            MlsMessagePayload::Welcome(welcome) => welcome.validate(),
+            MlsMessagePayload::GroupInfo(group_info) => {
+                check_version(self.version, group_info.group_context.protocol_version)
+            }
+            MlsMessagePayload::KeyPackage(key_package) => {
+                check_version(self.version, key_package.version)?;
+                key_package.validate()
+            }
+        }
+    }
+}
+
+fn check_version(expected: ProtocolVersion, found: ProtocolVersion) -> Result<(), MlsError> {
+    (expected == found)
+        .then_some(())
+        .ok_or(MlsError::ProtocolVersionMismatch)
+}
+
+impl Validate for FramedContent {
+    fn validate(&self) -> Result<(), MlsError> {
+        match (&self.sender, &self.content) {
+            (Sender::NewMemberCommit, Content::Commit(_)) => Ok(()),
+            #[cfg(any(feature = "private_message", feature = "by_ref_proposal"))]
+            (Sender::NewMemberCommit, _) => Err(MlsError::ExpectedCommitForNewMemberCommit),
+            #[cfg(feature = "by_ref_proposal")]
+            (Sender::NewMemberProposal, Content::Proposal(p))
+                if matches!(**p, Proposal::Add(_)) =>
+            {
+                Ok(())
+            }
+            #[cfg(feature = "by_ref_proposal")]
+            (Sender::NewMemberProposal, _) => {
+                Err(MlsError::ExpectedAddProposalForNewMemberProposal)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Validate for Welcome {
+    fn validate(&self) -> Result<(), MlsError> {
+        (!self.secrets.is_empty())
+            .then_some(())
+            .ok_or(MlsError::InvalidWelcomeMessage)
+    }
+}
+
+impl Validate for KeyPackage {
+    fn validate(&self) -> Result<(), MlsError> {
+        if !matches!(
+            self.leaf_node.leaf_node_source,
+            LeafNodeSource::KeyPackage(_)
+        ) {
+            return Err(MlsError::InvalidLeafNodeSource);
+        }
+
+        if self.hpke_init_key.as_ref() == self.leaf_node.public_key.as_ref() {
+            return Err(MlsError::InitLeafKeyEquality);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{test_client_with_key_pkg, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        group::{framing::MlsMessagePayload, test_utils::test_group},
+        key_package::test_utils::test_key_package,
+        protocol_version::ProtocolVersion,
+        MlsMessage,
+    };
+
+    use super::Validated;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn decoded_key_package_is_validated() {
+        let (_, key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let validated = Validated::from_bytes(&key_package.to_bytes().unwrap()).unwrap();
+        assert_eq!(*validated, key_package);
+
+        let validated = validated.into_key_package().unwrap();
+        assert_eq!(Some(validated.into_inner()), key_package.into_key_package());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn inconsistent_key_package_is_rejected() {
+        let mut key_package =
+            test_key_package(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+        key_package.hpke_init_key = key_package.leaf_node.public_key.clone();
+
+        let message = MlsMessage::new(
+            TEST_PROTOCOL_VERSION,
+            MlsMessagePayload::KeyPackage(key_package.clone()),
+        );
+
+        let res = Validated::from_bytes(&message.to_bytes().unwrap());
+        assert_matches!(res, Err(MlsError::InitLeafKeyEquality));
+
+        // State-changing APIs validate the messages they are given.
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let res = alice.group.commit_builder().add_member(message);
+        assert_matches!(res.map(|_| ()), Err(MlsError::InitLeafKeyEquality));
+
+        let message = MlsMessage::new(
+            ProtocolVersion::from(64999),
+            MlsMessagePayload::KeyPackage(key_package),
+        );
+
+        let res = Validated::new(message);
+        assert_matches!(res, Err(MlsError::ProtocolVersionMismatch));
+    }
+}
@@ -0,0 +1,204 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use mls_rs_core::{crypto::CryptoProvider, secret::Secret};
+use zeroize::Zeroizing;
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{
+        key_schedule::export_secret, member_from_leaf_node, ExportedTree, Group, GroupContext,
+        Member, Roster,
+    },
+    protocol_version::ProtocolVersion,
+    tree_kem::{node::LeafIndex, TreeKemPublic},
+    CipherSuite,
+};
+
+#[cfg(feature = "private_message")]
+use crate::group::{message_size::ciphertext_size, padding::PaddingMode};
+
+/// State of an epoch that does not change until the next commit is applied.
+pub(crate) struct EpochView {
+    context: GroupContext,
+    public_tree: TreeKemPublic,
+    exporter_secret: Zeroizing<Vec<u8>>,
+    epoch_authenticator: Zeroizing<Vec<u8>>,
+}
+
+/// Read-only handle on an epoch of a [`Group`], created with
+/// [`Group::view`].
+///
+/// A view can be cloned cheaply and used from other tasks, e.g. to query the
+/// roster or export secrets while the group itself is processing a commit.
+/// It always describes the epoch it was created in, regardless of commits
+/// applied to the group afterwards.
+pub struct GroupView<C: ClientConfig> {
+    epoch: Arc<EpochView>,
+    cipher_suite_provider: <C::CryptoProvider as CryptoProvider>::CipherSuiteProvider,
+}
+
+impl<C: ClientConfig> Clone for GroupView<C> {
+    fn clone(&self) -> Self {
+        Self {
+            epoch: self.epoch.clone(),
+            cipher_suite_provider: self.cipher_suite_provider.clone(),
+        }
+    }
+}
+
+impl<C: ClientConfig> GroupView<C> {
+    /// Group context of the epoch.
+    pub fn context(&self) -> &GroupContext {
+        &self.epoch.context
+    }
+
+    /// Unique identifier of the group.
+    pub fn group_id(&self) -> &[u8] {
+        &self.epoch.context.group_id
+    }
+
+    /// The epoch described by this view.
+    pub fn current_epoch(&self) -> u64 {
+        self.epoch.context.epoch
+    }
+
+    /// Cipher suite in use by the group.
+    pub fn cipher_suite(&self) -> CipherSuite {
+        self.epoch.context.cipher_suite
+    }
+
+    /// Version of the MLS protocol in use by the group.
+    pub fn protocol_version(&self) -> ProtocolVersion {
+        self.epoch.context.protocol_version
+    }
+
+    /// Roster of the epoch.
+    pub fn roster(&self) -> Roster<'_> {
+        self.epoch.public_tree.roster()
+    }
+
+    /// Get the member at `index` in the epoch, see [`Group::member_at_index`].
+    pub fn member_at_index(&self, index: u32) -> Option<Member> {
+        let leaf_index = LeafIndex(index);
+
+        self.epoch
+            .public_tree
+            .get_leaf_node(leaf_index)
+            .ok()
+            .map(|leaf_node| member_from_leaf_node(leaf_node, leaf_index))
+    }
+
+    /// Ratchet tree of the epoch, see [`Group::export_tree`].
+    pub fn export_tree(&self) -> ExportedTree<'_> {
+        ExportedTree::new_borrowed(&self.epoch.public_tree.nodes)
+    }
+
+    /// Epoch authenticator of the epoch, see [`Group::epoch_authenticator`].
+    pub fn epoch_authenticator(&self) -> Result<Secret, MlsError> {
+        Ok(self.epoch.epoch_authenticator.clone().into())
+    }
+
+    /// Export a secret of the epoch, see [`Group::export_secret`].
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn export_secret(
+        &self,
+        label: &[u8],
+        context: &[u8],
+        len: usize,
+    ) -> Result<Secret, MlsError> {
+        export_secret(
+            &self.epoch.exporter_secret,
+            label,
+            context,
+            len,
+            &self.cipher_suite_provider,
+        )
+        .await
+        .map(Into::into)
+    }
+
+    /// Estimate the size of an application message sent in the epoch, see
+    /// [`Group::estimate_ciphertext_size`].
+    ///
+    /// The view does not know the sizes of past messages, so
+    /// [`PaddingMode::Adaptive`] is estimated as
+    /// [`PaddingMode::StepFunction`].
+    #[cfg(feature = "private_message")]
+    pub fn estimate_ciphertext_size(
+        &self,
+        plaintext_len: usize,
+        padding_mode: PaddingMode,
+    ) -> usize {
+        ciphertext_size(&self.epoch.context, plaintext_len, padding_mode, None)
+    }
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Read-only handle on the current epoch.
+    ///
+    /// The state of an epoch is copied out of the group once, by the first
+    /// view created in the epoch, and shared by all views of that epoch.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn view(&mut self) -> GroupView<C> {
+        let epoch = match &self.epoch_view {
+            Some(epoch) if epoch.context.epoch == self.current_epoch() => epoch.clone(),
+            _ => {
+                let epoch = Arc::new(EpochView {
+                    context: self.context().clone(),
+                    public_tree: self.current_epoch_tree().clone(),
+                    exporter_secret: self.key_schedule.exporter_secret().clone(),
+                    epoch_authenticator: self.key_schedule.authentication_secret.clone(),
+                });
+
+                self.epoch_view = Some(epoch.clone());
+                epoch
+            }
+        };
+
+        GroupView {
+            epoch,
+            cipher_suite_provider: self.cipher_suite_provider.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{sync::Arc, vec};
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::test_utils::test_group,
+    };
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn view_describes_epoch_it_was_created_in() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (_bob, _) = alice.join("bob").await;
+
+        let view = alice.group.view();
+        assert!(Arc::ptr_eq(&view.epoch, &alice.group.view().epoch));
+
+        let secret = alice.group.export_secret(b"label", b"context", 32).await;
+        let view_secret = view.export_secret(b"label", b"context", 32).await;
+
+        assert_eq!(view_secret.unwrap(), secret.unwrap());
+
+        alice.group.commit(vec![]).await.unwrap();
+        alice.process_pending_commit().await.unwrap();
+
+        assert_eq!(view.current_epoch(), 1);
+        assert_eq!(view.roster().members().len(), 2);
+        assert_eq!(alice.group.view().current_epoch(), 2);
+        assert!(!Arc::ptr_eq(&view.epoch, &alice.group.view().epoch));
+    }
+}
@@ -0,0 +1,252 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use crate::{
+    client::MlsError,
+    client_config::ClientConfig,
+    group::{Group, ReceivedMessage},
+};
+
+/// When the group state, and with it the generations consumed from the
+/// secret tree ratchets and the current epoch, is persisted to the
+/// [`GroupStateStorage`](crate::GroupStateStorage) without an explicit call
+/// to [`Group::write_to_storage`].
+///
+/// A client restarting from a state older than the messages it already
+/// processed or sent accepts replays of those messages and, worse, encrypts
+/// new messages with keys and nonces it already used. Persisting the state
+/// each time a ratchet is advanced prevents this, at the cost of a write
+/// for every message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WatermarkPersistence {
+    /// The state is only persisted by [`Group::write_to_storage`].
+    #[default]
+    Manual,
+    /// The state is persisted after each message is successfully processed
+    /// and after each message is encrypted, before the result is returned.
+    /// [`Group::process_incoming_messages`] persists the state once, after
+    /// processing all messages.
+    AfterProcessing,
+    /// As [`WatermarkPersistence::AfterProcessing`], but the state is also
+    /// persisted as soon as a received message is decrypted, before its
+    /// content is processed. Decrypted content is never returned if the
+    /// ratchet generation it consumed could not be persisted.
+    WriteAhead,
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Persist the state if the configured [`WatermarkPersistence`] is at
+    /// least `persistence`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn persist_watermarks(
+        &mut self,
+        persistence: WatermarkPersistence,
+    ) -> Result<(), MlsError> {
+        let configured = self.config.watermark_persistence();
+
+        let persist = match persistence {
+            WatermarkPersistence::Manual => false,
+            WatermarkPersistence::AfterProcessing => configured != WatermarkPersistence::Manual,
+            WatermarkPersistence::WriteAhead => configured == WatermarkPersistence::WriteAhead,
+        };
+
+        if !persist || self.removed {
+            return Ok(());
+        }
+
+        if let (WatermarkPersistence::AfterProcessing, Some(deferred)) =
+            (persistence, &mut self.watermark_batch)
+        {
+            *deferred = true;
+            return Ok(());
+        }
+
+        self.write_to_storage().await
+    }
+
+    /// Defer the writes for [`WatermarkPersistence::AfterProcessing`] until
+    /// [`Group::end_watermark_batch`] is called.
+    pub(crate) fn begin_watermark_batch(&mut self) {
+        self.watermark_batch = Some(false);
+    }
+
+    /// Write the state once if any write was deferred since
+    /// [`Group::begin_watermark_batch`]. If writing fails, the successful
+    /// `results` are replaced by errors, since their state is not persisted.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn end_watermark_batch(
+        &mut self,
+        results: &mut [Option<Result<ReceivedMessage, MlsError>>],
+    ) {
+        if self.watermark_batch.take() != Some(true) {
+            return;
+        }
+
+        let Err(error) = self
+            .persist_watermarks(WatermarkPersistence::AfterProcessing)
+            .await
+        else {
+            return;
+        };
+
+        let mut error = Some(error);
+
+        for result in results.iter_mut().flatten().filter(|result| result.is_ok()) {
+            *result = Err(error.take().unwrap_or(MlsError::StateNotPersisted));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use super::WatermarkPersistence;
+
+    use crate::{
+        client::{
+            test_utils::{TestClientBuilder, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        client_config::ClientConfig,
+        group::test_utils::{test_group, test_group_custom_config, TestGroup},
+        group::Validated,
+        Group,
+    };
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn reload(group: &TestGroup) -> Group<impl ClientConfig> {
+        TestClientBuilder::new_for_test()
+            .group_state_storage(group.group.config.group_state_storage())
+            .build()
+            .load_group(group.group.group_id())
+            .await
+            .unwrap()
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn receiver_with(persistence: WatermarkPersistence) -> (TestGroup, TestGroup) {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let (bob, _) = alice
+            .join_with_custom_config("bob", false, |c| {
+                c.0.settings.watermark_persistence = persistence
+            })
+            .await
+            .unwrap();
+
+        (alice, bob)
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn replay_is_rejected_after_restart() {
+        for persistence in [
+            WatermarkPersistence::AfterProcessing,
+            WatermarkPersistence::WriteAhead,
+        ] {
+            let (mut alice, mut bob) = receiver_with(persistence).await;
+
+            let message = alice
+                .group
+                .encrypt_application_message(b"hello", vec![])
+                .await
+                .unwrap();
+
+            bob.process_message(message.clone()).await.unwrap();
+
+            let mut restarted = reload(&bob).await;
+            let res = restarted
+                .process_incoming_message_validated(Validated::new(message).unwrap())
+                .await;
+
+            assert_matches!(res, Err(MlsError::KeyMissing(_)));
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn batch_is_persisted_once_processed() {
+        let (mut alice, mut bob) = receiver_with(WatermarkPersistence::AfterProcessing).await;
+
+        let mut messages = vec![];
+
+        for _ in 0..3 {
+            let message = alice
+                .group
+                .encrypt_application_message(b"hello", vec![])
+                .await
+                .unwrap();
+
+            messages.push(message);
+        }
+
+        let results = bob.group.process_incoming_messages(messages.clone()).await;
+
+        assert!(results.iter().all(|res| res.is_ok()));
+        assert_eq!(bob.group.watermark_batch, None);
+
+        let mut restarted = reload(&bob).await;
+
+        for message in messages {
+            let res = restarted
+                .process_incoming_message_validated(Validated::new(message).unwrap())
+                .await;
+            assert_matches!(res, Err(MlsError::KeyMissing(_)));
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn manual_persistence_does_not_write() {
+        let (mut alice, mut bob) = receiver_with(WatermarkPersistence::Manual).await;
+
+        bob.group.write_to_storage().await.unwrap();
+
+        let message = alice
+            .group
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+
+        bob.process_message(message.clone()).await.unwrap();
+
+        let mut restarted = reload(&bob).await;
+
+        restarted
+            .process_incoming_message_validated(Validated::new(message).unwrap())
+            .await
+            .unwrap();
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn sender_generation_survives_restart() {
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.watermark_persistence(WatermarkPersistence::AfterProcessing)
+        })
+        .await;
+
+        let (mut bob, _) = alice.join("bob").await;
+
+        let first = alice
+            .group
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+
+        // Without persistence, the restarted sender would reuse the
+        // generation of the first message, which bob already consumed.
+        let mut restarted = reload(&alice).await;
+
+        let second = restarted
+            .encrypt_application_message(b"hello", vec![])
+            .await
+            .unwrap();
+
+        bob.process_message(first).await.unwrap();
+        bob.process_message(second).await.unwrap();
+    }
+}
@@ -0,0 +1,250 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use mls_rs_codec::{iter::mls_decode_split_on_collection, MlsDecode};
+use mls_rs_core::{crypto::CipherSuite, protocol_version::ProtocolVersion};
+
+#[cfg(feature = "bytes")]
+use mls_rs_codec::shared_bytes::{DecodeContext, MlsDecodeShared};
+
+use crate::{
+    client::MlsError,
+    crypto::HpkeCiphertext,
+    group::{
+        framing::{MlsMessagePayload, WireFormat},
+        EncryptedGroupSecrets, MlsMessage, Welcome,
+    },
+    KeyPackageRef,
+};
+
+impl Welcome {
+    /// Decode a welcome, keeping only the secrets addressed to `recipients`.
+    ///
+    /// Only the key package reference of each entry is decoded. The
+    /// ciphertexts of other entries are skipped without being copied and
+    /// the remaining entries are skipped at once when all recipients were
+    /// found.
+    fn mls_decode_for_recipients(
+        reader: &mut &[u8],
+        recipients: &[KeyPackageRef],
+    ) -> Result<Self, mls_rs_codec::Error> {
+        let cipher_suite = CipherSuite::mls_decode(reader)?;
+        let (mut entries, rest) = mls_decode_split_on_collection(reader)?;
+        let mut secrets = Vec::new();
+
+        while !entries.is_empty() && secrets.len() < recipients.len() {
+            let new_member = KeyPackageRef::mls_decode(&mut entries)?;
+
+            if recipients.contains(&new_member) {
+                secrets.push(EncryptedGroupSecrets {
+                    new_member,
+                    encrypted_group_secrets: HpkeCiphertext::mls_decode(&mut entries)?,
+                });
+            } else {
+                // Skip the KEM output and the ciphertext of the HPKE ciphertext.
+                for _ in 0..2 {
+                    let (_, rest) = mls_decode_split_on_collection(&mut entries)?;
+                    entries = rest;
+                }
+            }
+        }
+
+        *reader = rest;
+
+        Ok(Self {
+            cipher_suite,
+            secrets,
+            encrypted_group_info: crate::shared_bytes::codec::mls_decode(reader)?,
+        })
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl MlsDecodeShared for Welcome {
+    fn mls_decode_shared(
+        reader: &mut &[u8],
+        context: &DecodeContext<'_>,
+    ) -> Result<Self, mls_rs_codec::Error> {
+        Ok(Self {
+            cipher_suite: CipherSuite::mls_decode(reader)?,
+            secrets: Vec::mls_decode(reader)?,
+            encrypted_group_info: context.mls_decode(reader)?,
+        })
+    }
+}
+
+/// Welcome messages are decoded with [`Welcome::mls_decode_shared`], other
+/// messages as with [`MlsDecode`].
+#[cfg(feature = "bytes")]
+impl MlsDecodeShared for MlsMessage {
+    fn mls_decode_shared(
+        reader: &mut &[u8],
+        context: &DecodeContext<'_>,
+    ) -> Result<Self, mls_rs_codec::Error> {
+        let version = ProtocolVersion::mls_decode(reader)?;
+
+        let payload = if WireFormat::mls_decode(&mut &**reader)? == WireFormat::Welcome {
+            WireFormat::mls_decode(reader)?;
+            MlsMessagePayload::Welcome(Welcome::mls_decode_shared(reader, context)?)
+        } else {
+            MlsMessagePayload::mls_decode(reader)?
+        };
+
+        Ok(Self::new(version, payload))
+    }
+}
+
+impl MlsMessage {
+    /// Deserialize a welcome message from transport, keeping only the
+    /// encrypted group secrets addressed to the key packages in `recipients`.
+    ///
+    /// Welcome messages adding many members contain one entry per new member
+    /// and joining a group looks up the key package of each entry in the
+    /// [`KeyPackageStorage`](crate::KeyPackageStorage) until one is found.
+    /// If the references of the key packages of this client are known, e.g.
+    /// from [`MlsMessage::key_package_reference`], decoding the message with
+    /// this function instead of [`MlsMessage::from_bytes`] skips the entries
+    /// of other members and the lookups of their key packages. The result
+    /// can be validated with [`Validated::new`](crate::group::Validated::new)
+    /// and passed to
+    /// [`Client::join_group_validated`](crate::Client::join_group_validated).
+    ///
+    /// Returns [`MlsError::WelcomeKeyPackageNotFound`] if the welcome
+    /// message contains no entry for `recipients`.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn from_welcome_bytes_for(
+        bytes: &[u8],
+        recipients: &[KeyPackageRef],
+    ) -> Result<Self, MlsError> {
+        let reader = &mut &*bytes;
+        let version = ProtocolVersion::mls_decode(reader)?;
+
+        if WireFormat::mls_decode(reader)? != WireFormat::Welcome {
+            return Err(MlsError::UnexpectedMessageType);
+        }
+
+        let welcome = Welcome::mls_decode_for_recipients(reader, recipients)?;
+
+        if welcome.secrets.is_empty() {
+            return Err(MlsError::WelcomeKeyPackageNotFound);
+        }
+
+        Ok(Self::new(version, MlsMessagePayload::Welcome(welcome)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{
+            test_utils::{test_client_with_key_pkg, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        crypto::test_utils::test_cipher_suite_provider,
+        group::{test_utils::test_group, MlsMessage, Validated},
+        KeyPackageRef,
+    };
+
+    #[cfg(all(feature = "bytes", feature = "std"))]
+    use crate::group::framing::MlsMessagePayload;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn welcome_can_be_decoded_for_recipient() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let mut builder = alice.group.commit_builder();
+        let mut clients = Vec::new();
+        let mut refs: Vec<KeyPackageRef> = Vec::new();
+
+        for name in ["bob", "carol", "dave"] {
+            let (client, key_package) =
+                test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, name).await;
+
+            refs.push(
+                key_package
+                    .key_package_reference(&cs)
+                    .await
+                    .unwrap()
+                    .unwrap(),
+            );
+            builder = builder.add_member(key_package).unwrap();
+            clients.push(client);
+        }
+
+        let commit = builder.build().await.unwrap();
+        alice.group.apply_pending_commit().await.unwrap();
+
+        let bytes = commit.welcome_messages[0].to_bytes().unwrap();
+
+        let welcome = MlsMessage::from_welcome_bytes_for(&bytes, &refs[1..2]).unwrap();
+
+        assert_eq!(welcome.welcome_key_package_references(), vec![&refs[1]]);
+
+        let (group, _) = clients[1]
+            .join_group_validated(
+                commit.ratchet_tree.clone(),
+                &Validated::new(welcome.clone()).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(group.current_epoch(), alice.group.current_epoch());
+
+        let res = MlsMessage::from_welcome_bytes_for(&bytes, &[]);
+        assert_matches!(res, Err(MlsError::WelcomeKeyPackageNotFound));
+    }
+
+    #[cfg(all(feature = "bytes", feature = "std"))]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn welcome_can_be_decoded_without_copy() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let (bob, key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let bytes = bytes::Bytes::from(commit.welcome_messages[0].to_bytes().unwrap());
+        let welcome = MlsMessage::from_shared_bytes(&bytes).unwrap();
+
+        let MlsMessagePayload::Welcome(w) = &welcome.payload else {
+            panic!("expected welcome")
+        };
+
+        let range = bytes.as_ptr_range();
+        assert!(range.contains(&w.encrypted_group_info.as_ptr()));
+
+        assert_eq!(welcome, commit.welcome_messages[0]);
+
+        bob.join_group_validated(
+            commit.ratchet_tree,
+            &Validated::new(welcome.clone()).unwrap(),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn decoding_non_welcome_fails() {
+        let (_, key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let res = MlsMessage::from_welcome_bytes_for(&key_package.to_bytes().unwrap(), &[]);
+
+        assert_matches!(res, Err(MlsError::UnexpectedMessageType));
+    }
+}
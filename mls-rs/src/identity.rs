@@ -5,6 +5,12 @@
 /// Basic credential identity provider.
 pub mod basic;
 
+/// Key transparency verification of signing identities.
+pub mod key_transparency;
+
+/// Namespacing of identities by tenant for multi-tenant deployments.
+pub mod tenant;
+
 /// X.509 certificate identity provider.
 #[cfg(feature = "x509")]
 pub mod x509 {
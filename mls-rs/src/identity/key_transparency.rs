@@ -0,0 +1,339 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use mls_rs_core::{
+    error::{AnyError, IntoAnyError},
+    extension::ExtensionList,
+    identity::{CredentialType, IdentityProvider, SigningIdentity},
+    time::MlsTime,
+};
+
+/// Key transparency log used to verify signing identities before they are
+/// accepted.
+///
+/// The log is consulted by [`KeyTransparencyIdentityProvider`] whenever a
+/// group member or external sender is validated, e.g. when processing Add or
+/// Update proposals, after the wrapped [`IdentityProvider`] accepted the
+/// identity. Implementations would typically fetch and check an inclusion
+/// proof for the signature key of `signing_identity`.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+pub trait KeyTransparency: Send + Sync {
+    /// Error type that this log returns on internal failure or if the
+    /// identity could not be verified.
+    type Error: IntoAnyError;
+
+    /// Verify that `signing_identity` is included in the log.
+    ///
+    /// The `timestamp` is the time at which the identity is used, if it is
+    /// known. It can be used to check that the identity was included in the
+    /// log at that time.
+    async fn verify(
+        &self,
+        signing_identity: &SigningIdentity,
+        timestamp: Option<MlsTime>,
+    ) -> Result<(), Self::Error>;
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+/// Error returned by a [`KeyTransparencyIdentityProvider`].
+pub enum KeyTransparencyError {
+    #[cfg_attr(feature = "std", error(transparent))]
+    IdentityProviderError(AnyError),
+    #[cfg_attr(feature = "std", error("key transparency verification failed: {0}"))]
+    VerificationFailed(AnyError),
+}
+
+impl IntoAnyError for KeyTransparencyError {
+    #[cfg(feature = "std")]
+    fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+        Ok(self.into())
+    }
+}
+
+/// Identity provider that requires all members and external senders to be
+/// verified by a [`KeyTransparency`] log in addition to an inner
+/// [`IdentityProvider`].
+#[derive(Clone, Debug)]
+pub struct KeyTransparencyIdentityProvider<I, K> {
+    inner: I,
+    key_transparency: K,
+}
+
+impl<I, K> KeyTransparencyIdentityProvider<I, K>
+where
+    I: IdentityProvider,
+    K: KeyTransparency,
+{
+    /// Validate identities with `inner` and then verify them with
+    /// `key_transparency`.
+    pub fn new(inner: I, key_transparency: K) -> Self {
+        Self {
+            inner,
+            key_transparency,
+        }
+    }
+
+    /// The inner identity provider.
+    pub fn inner(&self) -> &I {
+        &self.inner
+    }
+
+    /// The key transparency log.
+    pub fn key_transparency(&self) -> &K {
+        &self.key_transparency
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn verify(
+        &self,
+        signing_identity: &SigningIdentity,
+        timestamp: Option<MlsTime>,
+    ) -> Result<(), KeyTransparencyError> {
+        self.key_transparency
+            .verify(signing_identity, timestamp)
+            .await
+            .map_err(|e| KeyTransparencyError::VerificationFailed(e.into_any_error()))
+    }
+}
+
+fn inner_error<E: IntoAnyError>(e: E) -> KeyTransparencyError {
+    KeyTransparencyError::IdentityProviderError(e.into_any_error())
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl<I, K> IdentityProvider for KeyTransparencyIdentityProvider<I, K>
+where
+    I: IdentityProvider,
+    K: KeyTransparency,
+{
+    type Error = KeyTransparencyError;
+
+    async fn validate_member(
+        &self,
+        signing_identity: &SigningIdentity,
+        timestamp: Option<MlsTime>,
+        extensions: Option<&ExtensionList>,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .validate_member(signing_identity, timestamp, extensions)
+            .await
+            .map_err(inner_error)?;
+
+        self.verify(signing_identity, timestamp).await
+    }
+
+    async fn validate_external_sender(
+        &self,
+        signing_identity: &SigningIdentity,
+        timestamp: Option<MlsTime>,
+        extensions: Option<&ExtensionList>,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .validate_external_sender(signing_identity, timestamp, extensions)
+            .await
+            .map_err(inner_error)?;
+
+        self.verify(signing_identity, timestamp).await
+    }
+
+    async fn identity(
+        &self,
+        signing_identity: &SigningIdentity,
+        extensions: &ExtensionList,
+    ) -> Result<Vec<u8>, Self::Error> {
+        self.inner
+            .identity(signing_identity, extensions)
+            .await
+            .map_err(inner_error)
+    }
+
+    async fn valid_successor(
+        &self,
+        predecessor: &SigningIdentity,
+        successor: &SigningIdentity,
+        extensions: &ExtensionList,
+    ) -> Result<bool, Self::Error> {
+        self.inner
+            .valid_successor(predecessor, successor, extensions)
+            .await
+            .map_err(inner_error)
+    }
+
+    fn supported_types(&self) -> Vec<CredentialType> {
+        self.inner.supported_types()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(mls_build_async)]
+    use alloc::boxed::Box;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use assert_matches::assert_matches;
+    use mls_rs_core::{
+        crypto::SignaturePublicKey,
+        error::IntoAnyError,
+        identity::{Credential, CustomCredential, IdentityProvider, SigningIdentity},
+        time::MlsTime,
+    };
+
+    use super::{KeyTransparency, KeyTransparencyError, KeyTransparencyIdentityProvider};
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        client_builder::ClientBuilder,
+        crypto::test_utils::TestCryptoProvider,
+        identity::{basic::BasicIdentityProvider, test_utils::get_test_signing_identity},
+        key_package::test_utils::test_key_package_message,
+    };
+
+    #[derive(Debug)]
+    #[cfg_attr(feature = "std", derive(thiserror::Error))]
+    #[cfg_attr(feature = "std", error("identity not found in log"))]
+    struct NotInLog;
+
+    impl IntoAnyError for NotInLog {
+        #[cfg(feature = "std")]
+        fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+            Ok(self.into())
+        }
+    }
+
+    #[derive(Clone, Debug, Default)]
+    struct TestLog {
+        keys: Vec<SignaturePublicKey>,
+        require_timestamp: bool,
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl KeyTransparency for TestLog {
+        type Error = NotInLog;
+
+        async fn verify(
+            &self,
+            signing_identity: &SigningIdentity,
+            timestamp: Option<MlsTime>,
+        ) -> Result<(), Self::Error> {
+            let included = self.keys.contains(&signing_identity.signature_key);
+
+            if included && (timestamp.is_some() || !self.require_timestamp) {
+                Ok(())
+            } else {
+                Err(NotInLog)
+            }
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn identities_must_be_included_in_log() {
+        let alice = get_test_signing_identity(TEST_CIPHER_SUITE, b"alice")
+            .await
+            .0;
+        let bob = get_test_signing_identity(TEST_CIPHER_SUITE, b"bob").await.0;
+
+        let log = TestLog {
+            keys: vec![alice.signature_key.clone()],
+            ..Default::default()
+        };
+
+        let provider = KeyTransparencyIdentityProvider::new(BasicIdentityProvider, log);
+
+        let res = provider.validate_member(&alice, None, None).await;
+        assert_matches!(res, Ok(()));
+
+        let res = provider.validate_member(&bob, None, None).await;
+        assert_matches!(res, Err(KeyTransparencyError::VerificationFailed(_)));
+
+        let res = provider.validate_external_sender(&bob, None, None).await;
+        assert_matches!(res, Err(KeyTransparencyError::VerificationFailed(_)));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn timestamp_is_passed_to_log() {
+        let alice = get_test_signing_identity(TEST_CIPHER_SUITE, b"alice")
+            .await
+            .0;
+
+        let log = TestLog {
+            keys: vec![alice.signature_key.clone()],
+            require_timestamp: true,
+        };
+
+        let provider = KeyTransparencyIdentityProvider::new(BasicIdentityProvider, log);
+
+        let res = provider.validate_member(&alice, None, None).await;
+        assert_matches!(res, Err(KeyTransparencyError::VerificationFailed(_)));
+
+        let res = provider
+            .validate_member(&alice, Some(MlsTime::now()), None)
+            .await;
+
+        assert_matches!(res, Ok(()));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn inner_provider_is_checked_first() {
+        let (alice, _) = get_test_signing_identity(TEST_CIPHER_SUITE, b"alice").await;
+
+        let custom = SigningIdentity {
+            credential: Credential::Custom(CustomCredential::new(1234.into(), vec![])),
+            ..alice.clone()
+        };
+
+        let log = TestLog {
+            keys: vec![alice.signature_key],
+            ..Default::default()
+        };
+
+        let provider = KeyTransparencyIdentityProvider::new(BasicIdentityProvider, log);
+
+        let res = provider.validate_member(&custom, None, None).await;
+        assert_matches!(res, Err(KeyTransparencyError::IdentityProviderError(_)));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn group_rejects_members_missing_from_log() {
+        let (alice, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"alice").await;
+
+        let log = TestLog {
+            keys: vec![alice.signature_key.clone()],
+            ..Default::default()
+        };
+
+        let client = ClientBuilder::new()
+            .crypto_provider(TestCryptoProvider::new())
+            .identity_provider(KeyTransparencyIdentityProvider::new(
+                BasicIdentityProvider,
+                log,
+            ))
+            .signing_identity(alice, secret_key, TEST_CIPHER_SUITE)
+            .build();
+
+        let mut group = client.create_group(Default::default()).await.unwrap();
+
+        let key_package =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let res = group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::IdentityProviderError(_)));
+    }
+}
@@ -0,0 +1,342 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use mls_rs_core::{
+    error::{AnyError, IntoAnyError},
+    extension::ExtensionList,
+    identity::{CredentialType, IdentityProvider, SigningIdentity},
+    time::MlsTime,
+};
+
+/// Resolver of the tenant that a signing identity belongs to, used by
+/// [`TenantIdentityProvider`].
+///
+/// Implementations would typically read the tenant from the credential,
+/// e.g. from a field of a certificate or a prefix of a basic credential.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+pub trait TenantResolver: Send + Sync {
+    /// Error type that this resolver returns on internal failure or if the
+    /// tenant could not be determined.
+    type Error: IntoAnyError;
+
+    /// Identifier of the tenant of `signing_identity`.
+    async fn tenant(&self, signing_identity: &SigningIdentity) -> Result<Vec<u8>, Self::Error>;
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+/// Error returned by a [`TenantIdentityProvider`].
+pub enum TenantError {
+    #[cfg_attr(feature = "std", error(transparent))]
+    IdentityProviderError(AnyError),
+    #[cfg_attr(feature = "std", error("tenant resolution failed: {0}"))]
+    ResolutionFailed(AnyError),
+    #[cfg_attr(feature = "std", error("identity belongs to a different tenant"))]
+    TenantMismatch,
+}
+
+impl IntoAnyError for TenantError {
+    #[cfg(feature = "std")]
+    fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+        Ok(self.into())
+    }
+}
+
+/// Identity provider for multi-tenant deployments that namespaces the
+/// identities of an inner [`IdentityProvider`] by tenant.
+///
+/// The provider carries the tenant of the client. By default, members and
+/// external senders of other tenants are rejected, so all members of a group
+/// belong to the same tenant and members of other tenants can't be injected
+/// into it. Identities returned by [`IdentityProvider::identity`] are
+/// prefixed with the tenant, so identical identities of different tenants
+/// are never considered to be the same member.
+#[derive(Clone, Debug)]
+pub struct TenantIdentityProvider<I, R> {
+    inner: I,
+    resolver: R,
+    tenant: Vec<u8>,
+    allow_cross_tenant: bool,
+}
+
+impl<I, R> TenantIdentityProvider<I, R>
+where
+    I: IdentityProvider,
+    R: TenantResolver,
+{
+    /// Validate identities with `inner` and then check that `resolver`
+    /// assigns them to `tenant`.
+    pub fn new(inner: I, resolver: R, tenant: Vec<u8>) -> Self {
+        Self {
+            inner,
+            resolver,
+            tenant,
+            allow_cross_tenant: false,
+        }
+    }
+
+    /// Accept members and external senders of other tenants. Identities are
+    /// still namespaced by tenant.
+    pub fn with_cross_tenant_members(self, allow_cross_tenant: bool) -> Self {
+        Self {
+            allow_cross_tenant,
+            ..self
+        }
+    }
+
+    /// The inner identity provider.
+    pub fn inner(&self) -> &I {
+        &self.inner
+    }
+
+    /// The tenant of the client.
+    pub fn tenant(&self) -> &[u8] {
+        &self.tenant
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn resolve(&self, signing_identity: &SigningIdentity) -> Result<Vec<u8>, TenantError> {
+        self.resolver
+            .tenant(signing_identity)
+            .await
+            .map_err(|e| TenantError::ResolutionFailed(e.into_any_error()))
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn check_tenant(&self, signing_identity: &SigningIdentity) -> Result<(), TenantError> {
+        let tenant = self.resolve(signing_identity).await?;
+
+        if self.allow_cross_tenant || tenant == self.tenant {
+            Ok(())
+        } else {
+            Err(TenantError::TenantMismatch)
+        }
+    }
+}
+
+fn inner_error<E: IntoAnyError>(e: E) -> TenantError {
+    TenantError::IdentityProviderError(e.into_any_error())
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl<I, R> IdentityProvider for TenantIdentityProvider<I, R>
+where
+    I: IdentityProvider,
+    R: TenantResolver,
+{
+    type Error = TenantError;
+
+    async fn validate_member(
+        &self,
+        signing_identity: &SigningIdentity,
+        timestamp: Option<MlsTime>,
+        extensions: Option<&ExtensionList>,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .validate_member(signing_identity, timestamp, extensions)
+            .await
+            .map_err(inner_error)?;
+
+        self.check_tenant(signing_identity).await
+    }
+
+    async fn validate_external_sender(
+        &self,
+        signing_identity: &SigningIdentity,
+        timestamp: Option<MlsTime>,
+        extensions: Option<&ExtensionList>,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .validate_external_sender(signing_identity, timestamp, extensions)
+            .await
+            .map_err(inner_error)?;
+
+        self.check_tenant(signing_identity).await
+    }
+
+    /// The identity of the inner provider, prefixed with the tenant and its
+    /// length as a 4 byte big-endian integer.
+    async fn identity(
+        &self,
+        signing_identity: &SigningIdentity,
+        extensions: &ExtensionList,
+    ) -> Result<Vec<u8>, Self::Error> {
+        let tenant = self.resolve(signing_identity).await?;
+
+        let identity = self
+            .inner
+            .identity(signing_identity, extensions)
+            .await
+            .map_err(inner_error)?;
+
+        let mut namespaced = Vec::with_capacity(4 + tenant.len() + identity.len());
+        namespaced.extend_from_slice(&(tenant.len() as u32).to_be_bytes());
+        namespaced.extend_from_slice(&tenant);
+        namespaced.extend_from_slice(&identity);
+
+        Ok(namespaced)
+    }
+
+    async fn valid_successor(
+        &self,
+        predecessor: &SigningIdentity,
+        successor: &SigningIdentity,
+        extensions: &ExtensionList,
+    ) -> Result<bool, Self::Error> {
+        if self.resolve(predecessor).await? != self.resolve(successor).await? {
+            return Ok(false);
+        }
+
+        self.inner
+            .valid_successor(predecessor, successor, extensions)
+            .await
+            .map_err(inner_error)
+    }
+
+    fn supported_types(&self) -> Vec<CredentialType> {
+        self.inner.supported_types()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(mls_build_async)]
+    use alloc::boxed::Box;
+    use alloc::vec::Vec;
+    use assert_matches::assert_matches;
+    use mls_rs_core::identity::{IdentityProvider, SigningIdentity};
+
+    use super::{TenantError, TenantIdentityProvider, TenantResolver};
+
+    use crate::{
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        client_builder::ClientBuilder,
+        crypto::test_utils::TestCryptoProvider,
+        identity::{basic::BasicIdentityProvider, test_utils::get_test_signing_identity},
+        key_package::test_utils::test_key_package_message,
+    };
+
+    /// Reads the tenant from the part of basic credentials before the first
+    /// `/`.
+    #[derive(Clone, Debug)]
+    struct PrefixResolver;
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl TenantResolver for PrefixResolver {
+        type Error = TenantError;
+
+        async fn tenant(&self, signing_identity: &SigningIdentity) -> Result<Vec<u8>, Self::Error> {
+            let identifier = &signing_identity
+                .credential
+                .as_basic()
+                .ok_or(TenantError::TenantMismatch)?
+                .identifier;
+
+            let tenant = identifier.split(|b| *b == b'/').next().unwrap_or_default();
+
+            Ok(tenant.to_vec())
+        }
+    }
+
+    fn provider(tenant: &[u8]) -> TenantIdentityProvider<BasicIdentityProvider, PrefixResolver> {
+        TenantIdentityProvider::new(BasicIdentityProvider, PrefixResolver, tenant.to_vec())
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn members_of_other_tenants_are_rejected() {
+        let alice = get_test_signing_identity(TEST_CIPHER_SUITE, b"a/alice")
+            .await
+            .0;
+
+        let bob = get_test_signing_identity(TEST_CIPHER_SUITE, b"b/bob")
+            .await
+            .0;
+
+        let res = provider(b"a").validate_member(&alice, None, None).await;
+        assert_matches!(res, Ok(()));
+
+        let res = provider(b"a").validate_member(&bob, None, None).await;
+        assert_matches!(res, Err(TenantError::TenantMismatch));
+
+        let res = provider(b"a")
+            .validate_external_sender(&bob, None, None)
+            .await;
+        assert_matches!(res, Err(TenantError::TenantMismatch));
+
+        let res = provider(b"a")
+            .with_cross_tenant_members(true)
+            .validate_member(&bob, None, None)
+            .await;
+
+        assert_matches!(res, Ok(()));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn identities_are_namespaced_by_tenant() {
+        let alice = get_test_signing_identity(TEST_CIPHER_SUITE, b"a/alice")
+            .await
+            .0;
+
+        let other = get_test_signing_identity(TEST_CIPHER_SUITE, b"b/alice")
+            .await
+            .0;
+
+        let provider = provider(b"a").with_cross_tenant_members(true);
+
+        let identity = provider
+            .identity(&alice, &Default::default())
+            .await
+            .unwrap();
+
+        assert!(identity.starts_with(&[0, 0, 0, 1, b'a']));
+
+        let other_identity = provider
+            .identity(&other, &Default::default())
+            .await
+            .unwrap();
+
+        assert_ne!(identity, other_identity);
+
+        let res = provider
+            .valid_successor(&alice, &other, &Default::default())
+            .await;
+
+        assert_matches!(res, Ok(false));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn group_rejects_members_of_other_tenants() {
+        let (alice, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"a/alice").await;
+
+        let client = ClientBuilder::new()
+            .crypto_provider(TestCryptoProvider::new())
+            .identity_provider(provider(b"a"))
+            .signing_identity(alice, secret_key, TEST_CIPHER_SUITE)
+            .build();
+
+        let mut group = client.create_group(Default::default()).await.unwrap();
+
+        let key_package =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "b/bob").await;
+
+        let res = group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::IdentityProviderError(_)));
+    }
+}
@@ -9,7 +9,7 @@ use mls_rs_core::{error::IntoAnyError, identity::IdentityProvider, key_package::
 
 use crate::client::MlsError;
 use crate::{
-    crypto::{HpkeSecretKey, SignatureSecretKey},
+    crypto::{HpkePublicKey, HpkeSecretKey, SignatureSecretKey},
     group::framing::MlsMessagePayload,
     identity::SigningIdentity,
     protocol_version::ProtocolVersion,
@@ -23,6 +23,9 @@ use crate::{
 
 use super::{KeyPackage, KeyPackageRef};
 
+#[cfg(all(not(mls_build_async), feature = "rayon"))]
+use rayon::prelude::*;
+
 #[derive(Clone, Debug)]
 pub struct KeyPackageGenerator<'a, IP, CP>
 where
@@ -95,23 +98,116 @@ where
         key_package_extensions: ExtensionList,
         leaf_node_extensions: ExtensionList,
     ) -> Result<KeyPackageGeneration, MlsError> {
-        let (init_secret_key, public_init) = self
-            .cipher_suite_provider
+        let init_key_pair = self.kem_generate().await?;
+        let leaf_key_pair = self.kem_generate().await?;
+
+        self.generate_with_keys(
+            lifetime,
+            capabilities,
+            key_package_extensions,
+            leaf_node_extensions,
+            init_key_pair,
+            leaf_key_pair,
+        )
+        .await
+    }
+
+    /// Generate `count` key packages.
+    ///
+    /// The HPKE key pairs of all key packages are generated first, in
+    /// parallel if the `rayon` feature is enabled. The key packages are then
+    /// signed one after the other with the same signing key, which allows
+    /// signers backed by hardware to keep a single session open.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn generate_batch(
+        &self,
+        count: usize,
+        lifetime: Lifetime,
+        capabilities: Capabilities,
+        key_package_extensions: ExtensionList,
+        leaf_node_extensions: ExtensionList,
+    ) -> Result<Vec<KeyPackageGeneration>, MlsError> {
+        let mut key_pairs = self.kem_generate_many(2 * count).await?.into_iter();
+        let mut generations = Vec::with_capacity(count);
+
+        while let (Some(init_key_pair), Some(leaf_key_pair)) = (key_pairs.next(), key_pairs.next())
+        {
+            let generation = self
+                .generate_with_keys(
+                    lifetime.clone(),
+                    capabilities.clone(),
+                    key_package_extensions.clone(),
+                    leaf_node_extensions.clone(),
+                    init_key_pair,
+                    leaf_key_pair,
+                )
+                .await?;
+
+            generations.push(generation);
+        }
+
+        Ok(generations)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn kem_generate(&self) -> Result<(HpkeSecretKey, HpkePublicKey), MlsError> {
+        self.cipher_suite_provider
             .kem_generate()
             .await
-            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
+    }
+
+    #[cfg(any(mls_build_async, not(feature = "rayon")))]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn kem_generate_many(
+        &self,
+        count: usize,
+    ) -> Result<Vec<(HpkeSecretKey, HpkePublicKey)>, MlsError> {
+        let mut key_pairs = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            key_pairs.push(self.kem_generate().await?);
+        }
+
+        Ok(key_pairs)
+    }
+
+    #[cfg(all(not(mls_build_async), feature = "rayon"))]
+    fn kem_generate_many(
+        &self,
+        count: usize,
+    ) -> Result<Vec<(HpkeSecretKey, HpkePublicKey)>, MlsError> {
+        (0..count)
+            .into_par_iter()
+            .map(|_| self.kem_generate())
+            .collect()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn generate_with_keys(
+        &self,
+        lifetime: Lifetime,
+        capabilities: Capabilities,
+        key_package_extensions: ExtensionList,
+        leaf_node_extensions: ExtensionList,
+        init_key_pair: (HpkeSecretKey, HpkePublicKey),
+        leaf_key_pair: (HpkeSecretKey, HpkePublicKey),
+    ) -> Result<KeyPackageGeneration, MlsError> {
+        let (init_secret_key, public_init) = init_key_pair;
 
         let properties = ConfigProperties {
             capabilities,
             extensions: leaf_node_extensions,
         };
 
-        let (leaf_node, leaf_node_secret) = LeafNode::generate(
+        let (leaf_node, leaf_node_secret) = LeafNode::generate_with_key(
             self.cipher_suite_provider,
             properties,
             self.signing_identity.clone(),
             self.signing_key,
             lifetime,
+            leaf_key_pair,
         )
         .await?;
 
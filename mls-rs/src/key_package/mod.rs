@@ -8,7 +8,7 @@ use crate::crypto::HpkePublicKey;
 use crate::hash_reference::HashReference;
 use crate::identity::SigningIdentity;
 use crate::protocol_version::ProtocolVersion;
-use crate::signer::Signable;
+use crate::signer::{Signable, SignatureLabel};
 use crate::tree_kem::leaf_node::{LeafNode, LeafNodeSource};
 use crate::CipherSuiteProvider;
 use alloc::vec::Vec;
@@ -23,6 +23,7 @@ use mls_rs_core::extension::ExtensionList;
 
 mod validator;
 pub(crate) use validator::*;
+pub use validator::{validate_key_package, KeyPackageValidation};
 
 pub(crate) mod generator;
 pub(crate) use generator::*;
@@ -140,7 +141,7 @@ impl KeyPackage {
 }
 
 impl<'a> Signable<'a> for KeyPackage {
-    const SIGN_LABEL: &'static str = "KeyPackageTBS";
+    const SIGN_LABEL: &'static str = SignatureLabel::KeyPackage.as_str();
 
     type SigningContext = ();
 
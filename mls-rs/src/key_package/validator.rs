@@ -2,9 +2,20 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
-use mls_rs_core::{crypto::CipherSuiteProvider, protocol_version::ProtocolVersion};
+use alloc::vec::Vec;
+use mls_rs_core::{
+    crypto::{CipherSuite, CipherSuiteProvider, CryptoProvider},
+    extension::ExtensionType,
+    protocol_version::ProtocolVersion,
+};
 
-use crate::{client::MlsError, signer::Signable, KeyPackage};
+use crate::{
+    client::MlsError,
+    signer::Signable,
+    time::MlsTime,
+    tree_kem::leaf_node::{LeafNodeSigningContext, LeafNodeSource},
+    KeyPackage, MlsMessage,
+};
 
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 pub(crate) async fn validate_key_package_properties<CSP: CipherSuiteProvider>(
@@ -37,3 +48,251 @@ pub(crate) async fn validate_key_package_properties<CSP: CipherSuiteProvider>(
 
     Ok(())
 }
+
+/// Findings produced by [`validate_key_package`](crate::validate_key_package).
+///
+/// Each field describes the outcome of an individual check so that a
+/// delivery service can report precisely why an uploaded key package
+/// was rejected.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct KeyPackageValidation {
+    /// The key package is signed by the signature key of its leaf node.
+    pub key_package_signature_valid: bool,
+    /// The leaf node is signed by its own signature key.
+    pub leaf_node_signature_valid: bool,
+    /// The key package uses the expected protocol version.
+    pub protocol_version_matches: bool,
+    /// The key package uses the expected cipher suite.
+    pub cipher_suite_matches: bool,
+    /// The init key is valid for the cipher suite and differs from the
+    /// leaf node encryption key.
+    pub init_key_valid: bool,
+    /// The leaf node was created for a key package and the validation
+    /// time falls within its lifetime.
+    pub within_lifetime: bool,
+    /// The leaf node capabilities list the protocol version, cipher suite
+    /// and credential type used by the key package.
+    pub capabilities_valid: bool,
+    /// Leaf node extensions that are not listed in the leaf node capabilities.
+    pub extensions_not_in_capabilities: Vec<ExtensionType>,
+}
+
+impl KeyPackageValidation {
+    /// Returns true if every check passed.
+    pub fn is_valid(&self) -> bool {
+        self.key_package_signature_valid
+            && self.leaf_node_signature_valid
+            && self.protocol_version_matches
+            && self.cipher_suite_matches
+            && self.init_key_valid
+            && self.within_lifetime
+            && self.capabilities_valid
+            && self.extensions_not_in_capabilities.is_empty()
+    }
+}
+
+/// Validate a serialized key package message without requiring a group.
+///
+/// This is intended for directory servers and delivery services that
+/// want to reject malformed key package uploads. Unlike
+/// [`ExternalClient::validate_key_package`](crate::external_client::ExternalClient::validate_key_package),
+/// no check fails early and the credential is not validated by an
+/// [`IdentityProvider`](crate::IdentityProvider). Instead, the outcome of every check is
+/// returned as a [`KeyPackageValidation`].
+///
+/// The lifetime of the key package is checked against `time`.
+///
+/// An error is returned only if `key_package_data` can not be decoded as a
+/// key package message or if `cipher_suite` is not supported by `crypto_provider`.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub async fn validate_key_package<P: CryptoProvider>(
+    crypto_provider: &P,
+    key_package_data: &[u8],
+    protocol_version: ProtocolVersion,
+    cipher_suite: CipherSuite,
+    time: MlsTime,
+) -> Result<(KeyPackage, KeyPackageValidation), MlsError> {
+    let message = MlsMessage::from_bytes(key_package_data)?;
+    let message_version = message.version;
+
+    let key_package = message
+        .into_key_package()
+        .ok_or(MlsError::UnexpectedMessageType)?;
+
+    let cs = crypto_provider
+        .cipher_suite_provider(cipher_suite)
+        .ok_or(MlsError::UnsupportedCipherSuite(cipher_suite))?;
+
+    let leaf_node = &key_package.leaf_node;
+    let signature_key = &leaf_node.signing_identity.signature_key;
+
+    let key_package_signature_valid = key_package.verify(&cs, signature_key, &()).await.is_ok();
+
+    let leaf_node_signature_valid = leaf_node
+        .verify(&cs, signature_key, &LeafNodeSigningContext::default())
+        .await
+        .is_ok();
+
+    let init_key_valid = cs
+        .kem_public_key_validate(&key_package.hpke_init_key)
+        .is_ok()
+        && key_package.hpke_init_key.as_ref() != leaf_node.public_key.as_ref();
+
+    let within_lifetime = matches!(
+        &leaf_node.leaf_node_source,
+        LeafNodeSource::KeyPackage(lifetime) if lifetime.within_lifetime(time)
+    );
+
+    let capabilities = &leaf_node.capabilities;
+
+    let capabilities_valid = capabilities
+        .protocol_versions
+        .contains(&key_package.version)
+        && capabilities
+            .cipher_suites
+            .contains(&key_package.cipher_suite)
+        && capabilities
+            .credentials
+            .contains(&leaf_node.signing_identity.credential.credential_type());
+
+    let extensions_not_in_capabilities = leaf_node
+        .extensions
+        .iter()
+        .map(|ext| ext.extension_type)
+        .filter(|ext_type| !capabilities.extensions.contains(ext_type))
+        .collect();
+
+    let validation = KeyPackageValidation {
+        key_package_signature_valid,
+        leaf_node_signature_valid,
+        protocol_version_matches: key_package.version == protocol_version
+            && message_version == protocol_version,
+        cipher_suite_matches: key_package.cipher_suite == cipher_suite,
+        init_key_valid,
+        within_lifetime,
+        capabilities_valid,
+        extensions_not_in_capabilities,
+    };
+
+    Ok((key_package, validation))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        crypto::test_utils::TestCryptoProvider,
+        group::framing::MlsMessagePayload,
+        key_package::test_utils::{test_key_package, test_key_package_message},
+    };
+
+    fn test_time() -> MlsTime {
+        #[cfg(feature = "std")]
+        let time = MlsTime::now();
+
+        #[cfg(not(feature = "std"))]
+        let time = MlsTime::from(3600);
+
+        time
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn valid_key_package_passes_all_checks() {
+        let key_package =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "alice").await;
+
+        let (decoded, validation) = validate_key_package(
+            &TestCryptoProvider::new(),
+            &key_package.to_bytes().unwrap(),
+            TEST_PROTOCOL_VERSION,
+            TEST_CIPHER_SUITE,
+            test_time(),
+        )
+        .await
+        .unwrap();
+
+        assert!(validation.is_valid(), "{validation:?}");
+        assert_eq!(key_package.into_key_package().unwrap(), decoded);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn findings_report_each_failed_check() {
+        let mut key_package =
+            test_key_package(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "alice").await;
+
+        key_package.signature = vec![0u8; key_package.signature.len()];
+        key_package.hpke_init_key = key_package.leaf_node.public_key.clone();
+
+        let message = MlsMessage::new(
+            TEST_PROTOCOL_VERSION,
+            MlsMessagePayload::KeyPackage(key_package),
+        );
+
+        let (_, validation) = validate_key_package(
+            &TestCryptoProvider::new(),
+            &message.to_bytes().unwrap(),
+            TEST_PROTOCOL_VERSION,
+            TEST_CIPHER_SUITE,
+            MlsTime::from(u64::MAX),
+        )
+        .await
+        .unwrap();
+
+        assert!(!validation.is_valid());
+        assert!(!validation.key_package_signature_valid);
+        assert!(validation.leaf_node_signature_valid);
+        assert!(!validation.init_key_valid);
+        assert!(!validation.within_lifetime);
+        assert!(validation.capabilities_valid);
+        assert!(validation.extensions_not_in_capabilities.is_empty());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn cipher_suite_mismatch_is_reported() {
+        let key_package =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "alice").await;
+
+        let crypto_provider = TestCryptoProvider::new();
+
+        let Some(other_cipher_suite) = crypto_provider
+            .supported_cipher_suites()
+            .into_iter()
+            .find(|cs| cs != &TEST_CIPHER_SUITE)
+        else {
+            return;
+        };
+
+        let (_, validation) = validate_key_package(
+            &crypto_provider,
+            &key_package.to_bytes().unwrap(),
+            TEST_PROTOCOL_VERSION,
+            other_cipher_suite,
+            test_time(),
+        )
+        .await
+        .unwrap();
+
+        assert!(!validation.cipher_suite_matches);
+        assert!(!validation.is_valid());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn non_key_package_message_is_rejected() {
+        let res = validate_key_package(
+            &TestCryptoProvider::new(),
+            &[0u8; 4],
+            TEST_PROTOCOL_VERSION,
+            CipherSuite::P256_AES128,
+            test_time(),
+        )
+        .await;
+
+        assert_matches!(res, Err(MlsError::SerializationError(_)));
+    }
+}
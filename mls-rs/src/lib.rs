@@ -128,11 +128,22 @@ mod protocol_version {
 
 pub use protocol_version::ProtocolVersion;
 
+/// Helpers to construct protocol-violating messages, for testing that they
+/// are rejected.
+#[cfg(any(test, feature = "adversarial"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "adversarial")))]
+pub mod adversarial;
+/// Blocking wrappers for the async build of the crate.
+#[cfg(all(mls_build_async, feature = "blocking"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
+pub mod blocking;
 pub mod client;
 pub mod client_builder;
 mod client_config;
+mod crate_capabilities;
 /// Dependencies of [`CryptoProvider`] and [`CipherSuiteProvider`]
 pub mod crypto;
+mod error_render;
 /// Extension utilities and built-in extension types.
 pub mod extension;
 /// Tools to observe groups without being a member, useful
@@ -151,6 +162,7 @@ mod key_package;
 pub(crate) mod map;
 /// Pre-shared key support.
 pub mod psk;
+mod shared_bytes;
 mod signer;
 /// Storage providers to use with
 /// [`ClientBuilder`](client_builder::ClientBuilder).
@@ -167,31 +179,43 @@ pub use mls_rs_core::{
 /// Dependencies of [`MlsRules`].
 pub mod mls_rules {
     pub use crate::group::{
+        commit_authorization::{
+            AuthorizingMlsRules, AuthorizingMlsRulesError, CommitAuthorizationPolicy,
+        },
         mls_rules::{
-            CommitDirection, CommitOptions, CommitSource, DefaultMlsRules, EncryptionOptions,
+            CommitDirection, CommitOptions, CommitPathPolicy, CommitSource, DefaultMlsRules,
+            EncryptionOptions,
+        },
+        proposal_filter::{
+            ProposalBundle, ProposalInfo, ProposalSource, PROPOSAL_APPLICATION_ORDER,
         },
-        proposal_filter::{ProposalBundle, ProposalInfo, ProposalSource},
     };
 
     #[cfg(feature = "by_ref_proposal")]
-    pub use crate::group::proposal_ref::ProposalRef;
+    pub use crate::group::{proposal_filter::ProposalRejection, proposal_ref::ProposalRef};
+
+    #[cfg(feature = "private_message")]
+    pub use crate::group::mls_rules::EncryptionPolicy;
 }
 
 pub use mls_rs_core::extension::{Extension, ExtensionList};
 
 pub use crate::{
     client::Client,
+    crate_capabilities::CrateCapabilities,
     group::{
-        framing::{MlsMessage, WireFormat},
+        framing::{MessageDescription, MlsMessage, WireFormat},
         mls_rules::MlsRules,
-        Group,
+        Group, Validated,
     },
-    key_package::{KeyPackage, KeyPackageRef},
+    key_package::{validate_key_package, KeyPackage, KeyPackageRef, KeyPackageValidation},
+    signer::SignatureLabel,
 };
 
 /// Error types.
 pub mod error {
     pub use crate::client::MlsError;
+    pub use crate::error_render::{DefaultErrorRenderer, ErrorRenderer, SharedErrorRenderer};
     pub use mls_rs_core::error::{AnyError, IntoAnyError};
     pub use mls_rs_core::extension::ExtensionError;
 }
@@ -202,6 +226,7 @@ pub mod time {
 }
 
 mod tree_kem;
+pub mod tree_math;
 
 pub use mls_rs_codec;
 
@@ -61,6 +61,10 @@ mod map_impl {
             self.find(key).map(|i| self.0.remove(i).1)
         }
 
+        pub fn keys(&self) -> impl Iterator<Item = &K> {
+            self.0.iter().map(|(k, _)| k)
+        }
+
         fn find(&self, key: &K) -> Option<usize> {
             self.0.iter().position(|(k, _)| k == key)
         }
@@ -28,6 +28,9 @@ pub(crate) mod secret;
 
 pub use mls_rs_core::psk::{ExternalPskId, PreSharedKey};
 
+#[cfg(feature = "psk")]
+pub use secret::{compute_psk_secret, ExternalPskInput};
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, MlsSize, MlsEncode, MlsDecode)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
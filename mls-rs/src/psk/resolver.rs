@@ -19,6 +19,40 @@ use crate::{
 
 use super::{secret::PskSecretInput, JustPreSharedKeyID, PreSharedKeyID, ResumptionPsk};
 
+/// PSK storage falling back to an application provided lookup for the PSKs
+/// missing from `store`.
+pub(crate) struct PskStoreWithLookup<'a, PS, L> {
+    pub store: PS,
+    pub lookup: &'a L,
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl<PS, L> PreSharedKeyStorage for PskStoreWithLookup<'_, PS, L>
+where
+    PS: PreSharedKeyStorage,
+    L: PreSharedKeyStorage,
+{
+    type Error = MlsError;
+
+    async fn get(&self, id: &ExternalPskId) -> Result<Option<PreSharedKey>, Self::Error> {
+        let psk = self
+            .store
+            .get(id)
+            .await
+            .map_err(|e| MlsError::PskStoreError(e.into_any_error()))?;
+
+        match psk {
+            Some(psk) => Ok(Some(psk)),
+            None => self
+                .lookup
+                .get(id)
+                .await
+                .map_err(|e| MlsError::PskStoreError(e.into_any_error())),
+        }
+    }
+}
+
 pub(crate) struct PskResolver<'a, GS, K, PS>
 where
     GS: GroupStateStorage,
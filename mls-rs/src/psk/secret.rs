@@ -15,13 +15,17 @@ use zeroize::Zeroizing;
 use mls_rs_codec::MlsEncode;
 
 #[cfg(feature = "psk")]
-use mls_rs_core::{error::IntoAnyError, psk::PreSharedKey};
+use mls_rs_core::{
+    error::IntoAnyError,
+    psk::{ExternalPskId, PreSharedKey},
+    secret::Secret,
+};
 
 #[cfg(feature = "psk")]
 use crate::{
     client::MlsError,
     group::key_schedule::kdf_expand_with_label,
-    psk::{PSKLabel, PreSharedKeyID},
+    psk::{JustPreSharedKeyID, PSKLabel, PreSharedKeyID, PskNonce},
 };
 
 #[cfg(feature = "psk")]
@@ -31,6 +35,65 @@ pub(crate) struct PskSecretInput {
     pub psk: PreSharedKey,
 }
 
+/// External pre-shared key referenced by a PSK proposal, input of
+/// [`compute_psk_secret`].
+#[cfg(feature = "psk")]
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct ExternalPskInput {
+    /// Identifier of the pre-shared key.
+    pub id: ExternalPskId,
+    /// Nonce included in the proposal referencing the pre-shared key.
+    pub nonce: Vec<u8>,
+    /// Value of the pre-shared key.
+    pub psk: PreSharedKey,
+}
+
+#[cfg(feature = "psk")]
+impl ExternalPskInput {
+    /// Create an input for the pre-shared key `psk` with identifier `id`,
+    /// referenced with `nonce`.
+    pub fn new(id: ExternalPskId, nonce: Vec<u8>, psk: PreSharedKey) -> Self {
+        Self { id, nonce, psk }
+    }
+}
+
+#[cfg(feature = "psk")]
+impl From<ExternalPskInput> for PskSecretInput {
+    fn from(input: ExternalPskInput) -> Self {
+        PskSecretInput {
+            id: PreSharedKeyID {
+                key_id: JustPreSharedKeyID::External(input.id),
+                psk_nonce: PskNonce(input.nonce),
+            },
+            psk: input.psk,
+        }
+    }
+}
+
+/// Compute the `psk_secret` injected into the key schedule of an epoch
+/// whose commit covers PSK proposals for `psks`, in the order of the
+/// proposals, as defined in RFC 9420 section 8.4.
+///
+/// This is the same computation used by groups and can be used by test
+/// harnesses and server-side tooling to check PSK secrets computed by other
+/// implementations.
+#[cfg(feature = "psk")]
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub async fn compute_psk_secret<P: CipherSuiteProvider>(
+    cipher_suite_provider: &P,
+    psks: Vec<ExternalPskInput>,
+) -> Result<Secret, MlsError> {
+    let input = psks
+        .into_iter()
+        .map(PskSecretInput::from)
+        .collect::<Vec<_>>();
+
+    PskSecret::calculate(&input, cipher_suite_provider)
+        .await
+        .map(|secret| secret.0.into())
+}
+
 #[derive(PartialEq, Eq, Clone)]
 pub(crate) struct PskSecret(Zeroizing<Vec<u8>>);
 
@@ -42,7 +105,7 @@ impl Debug for PskSecret {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "fuzz_util"))]
 impl From<Vec<u8>> for PskSecret {
     fn from(value: Vec<u8>) -> Self {
         PskSecret(Zeroizing::new(value))
@@ -117,10 +180,7 @@ mod tests {
     use serde::{Deserialize, Serialize};
 
     use crate::{
-        crypto::test_utils::try_test_cipher_suite_provider,
-        psk::ExternalPskId,
-        psk::{JustPreSharedKeyID, PreSharedKeyID, PskNonce},
-        CipherSuiteProvider,
+        crypto::test_utils::try_test_cipher_suite_provider, psk::ExternalPskId, CipherSuiteProvider,
     };
 
     #[cfg(not(mls_build_async))]
@@ -129,29 +189,23 @@ mod tests {
         CipherSuite,
     };
 
-    use super::{PskSecret, PskSecretInput};
+    use super::{compute_psk_secret, ExternalPskInput};
 
+    // Field names of the RFC 9420 `psk_secret` test vectors are accepted as
+    // aliases, so those vectors can be used in place of `psk_secret.json`.
     #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
     struct PskInfo {
-        #[serde(with = "hex::serde")]
+        #[serde(with = "hex::serde", alias = "psk_id")]
         id: Vec<u8>,
         #[serde(with = "hex::serde")]
         psk: Vec<u8>,
-        #[serde(with = "hex::serde")]
+        #[serde(with = "hex::serde", alias = "psk_nonce")]
         nonce: Vec<u8>,
     }
 
-    impl From<PskInfo> for PskSecretInput {
+    impl From<PskInfo> for ExternalPskInput {
         fn from(info: PskInfo) -> Self {
-            let id = PreSharedKeyID {
-                key_id: JustPreSharedKeyID::External(ExternalPskId::new(info.id)),
-                psk_nonce: PskNonce(info.nonce),
-            };
-
-            PskSecretInput {
-                id,
-                psk: info.psk.into(),
-            }
+            ExternalPskInput::new(ExternalPskId::new(info.id), info.nonce, info.psk.into())
         }
     }
 
@@ -196,7 +250,7 @@ mod tests {
                         TestScenario {
                             cipher_suite: cs.into(),
                             psks: psks.to_vec(),
-                            psk_secret: psk_secret.to_vec(),
+                            psk_secret,
                         }
                     },
                 )
@@ -212,13 +266,10 @@ mod tests {
         async fn compute_psk_secret<P: CipherSuiteProvider>(
             provider: &P,
             psks: Vec<PskInfo>,
-        ) -> PskSecret {
-            let input = psks
-                .into_iter()
-                .map(PskSecretInput::from)
-                .collect::<Vec<_>>();
+        ) -> Vec<u8> {
+            let input = psks.into_iter().map(ExternalPskInput::from).collect();
 
-            PskSecret::calculate(&input, provider).await.unwrap()
+            compute_psk_secret(provider, input).await.unwrap().to_vec()
         }
     }
 
@@ -232,8 +283,30 @@ mod tests {
                 let computed =
                     TestScenario::compute_psk_secret(&provider, scenario.psks.clone()).await;
 
-                assert_eq!(scenario.psk_secret, computed.to_vec());
+                assert_eq!(scenario.psk_secret, computed);
             }
         }
     }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn psk_secret_depends_on_order() {
+        let scenarios: Vec<TestScenario> =
+            load_test_case_json!(psk_secret, TestScenario::generate());
+
+        let scenario = scenarios
+            .into_iter()
+            .find(|scenario| scenario.psks.len() > 1)
+            .unwrap();
+
+        let Some(provider) = try_test_cipher_suite_provider(scenario.cipher_suite) else {
+            return;
+        };
+
+        let mut psks = scenario.psks;
+        psks.reverse();
+
+        let computed = TestScenario::compute_psk_secret(&provider, psks).await;
+
+        assert_ne!(scenario.psk_secret, computed);
+    }
 }
@@ -0,0 +1,39 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Large opaque fields that are decoded without copying with the `bytes`
+//! feature.
+//!
+//! Fields of type [`SharedBytes`] are encoded with
+//! `mls_codec(with = "crate::shared_bytes::codec")`. With the `bytes`
+//! feature, they are slices of the input buffer when a message is decoded
+//! with [`MlsMessage::from_shared_bytes`](crate::MlsMessage::from_shared_bytes).
+//! Otherwise they are owned vectors.
+
+#[cfg(feature = "bytes")]
+pub(crate) type SharedBytes = bytes::Bytes;
+
+#[cfg(feature = "bytes")]
+pub(crate) use mls_rs_codec::shared_bytes as codec;
+
+#[cfg(not(feature = "bytes"))]
+pub(crate) type SharedBytes = alloc::vec::Vec<u8>;
+
+#[cfg(not(feature = "bytes"))]
+pub(crate) use mls_rs_codec::byte_vec as codec;
+
+#[cfg(feature = "bytes")]
+pub(crate) fn from_vec(data: alloc::vec::Vec<u8>) -> SharedBytes {
+    data.into()
+}
+
+#[cfg(not(feature = "bytes"))]
+pub(crate) fn from_vec(data: alloc::vec::Vec<u8>) -> SharedBytes {
+    data
+}
+
+#[cfg(feature = "arbitrary")]
+pub(crate) fn arbitrary(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<SharedBytes> {
+    <alloc::vec::Vec<u8> as arbitrary::Arbitrary>::arbitrary(u).map(from_vec)
+}
@@ -28,14 +28,80 @@ impl Debug for SignContent {
 }
 
 impl SignContent {
-    pub fn new(label: &str, content: Vec<u8>) -> Self {
+    pub fn new(label: &str, suffix: &[u8], content: Vec<u8>) -> Self {
         Self {
-            label: [b"MLS 1.0 ", label.as_bytes()].concat(),
+            label: full_label(label, suffix),
             content,
         }
     }
 }
 
+fn full_label(label: &str, suffix: &[u8]) -> Vec<u8> {
+    [b"MLS 1.0 ", label.as_bytes(), suffix].concat()
+}
+
+/// Label of each struct signed with `SignWithLabel`, as defined by
+/// [RFC 9420 Section 5.1.2](https://www.rfc-editor.org/rfc/rfc9420.html#section-5.1.2).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SignatureLabel {
+    /// Signature of the content of a handshake or application message.
+    FramedContent,
+    /// Signature of a group info.
+    GroupInfo,
+    /// Signature of a key package.
+    KeyPackage,
+    /// Signature of a leaf node.
+    LeafNode,
+    /// Signature of a [`MembershipProof`](crate::group::MembershipProof).
+    /// This label is not defined by RFC 9420.
+    MembershipProof,
+    /// Signature of a [`SignedRoster`](crate::group::SignedRoster).
+    /// This label is not defined by RFC 9420.
+    SignedRoster,
+    /// Signature of a [`CommitReceipt`](crate::group::CommitReceipt).
+    /// This label is not defined by RFC 9420.
+    CommitReceipt,
+}
+
+impl SignatureLabel {
+    /// All labels used by this crate.
+    pub const ALL: [Self; 7] = [
+        Self::FramedContent,
+        Self::GroupInfo,
+        Self::KeyPackage,
+        Self::LeafNode,
+        Self::MembershipProof,
+        Self::SignedRoster,
+        Self::CommitReceipt,
+    ];
+
+    /// The label, without the `"MLS 1.0 "` prefix.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::FramedContent => "FramedContentTBS",
+            Self::GroupInfo => "GroupInfoTBS",
+            Self::KeyPackage => "KeyPackageTBS",
+            Self::LeafNode => "LeafNodeTBS",
+            Self::MembershipProof => "MembershipProofTBS",
+            Self::SignedRoster => "SignedRosterTBS",
+            Self::CommitReceipt => "CommitReceiptTBS",
+        }
+    }
+
+    /// The label as included in signed content, with the `"MLS 1.0 "` prefix.
+    pub fn full_label(&self) -> Vec<u8> {
+        self.full_label_with_suffix(&[])
+    }
+
+    /// The label as included in signed content by a
+    /// [`CipherSuiteProvider`] with the given
+    /// [signature label suffix](CipherSuiteProvider::signature_label_suffix).
+    pub fn full_label_with_suffix(&self, suffix: &[u8]) -> Vec<u8> {
+        full_label(self.as_str(), suffix)
+    }
+}
+
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 #[cfg_attr(all(target_arch = "wasm32", mls_build_async), maybe_async::must_be_async(?Send))]
 #[cfg_attr(
@@ -62,7 +128,11 @@ pub(crate) trait Signable<'a> {
         signer: &SignatureSecretKey,
         context: &Self::SigningContext,
     ) -> Result<(), MlsError> {
-        let sign_content = SignContent::new(Self::SIGN_LABEL, self.signable_content(context)?);
+        let sign_content = SignContent::new(
+            Self::SIGN_LABEL,
+            signature_provider.signature_label_suffix(),
+            self.signable_content(context)?,
+        );
 
         let signature = signature_provider
             .sign(signer, &sign_content.mls_encode_to_vec()?)
@@ -80,7 +150,11 @@ pub(crate) trait Signable<'a> {
         public_key: &SignaturePublicKey,
         context: &Self::SigningContext,
     ) -> Result<(), MlsError> {
-        let sign_content = SignContent::new(Self::SIGN_LABEL, self.signable_content(context)?);
+        let sign_content = SignContent::new(
+            Self::SIGN_LABEL,
+            signature_provider.signature_label_suffix(),
+            self.signable_content(context)?,
+        );
 
         signature_provider
             .verify(
@@ -354,4 +428,100 @@ mod tests {
 
         assert_matches!(res, Err(MlsError::InvalidSignature));
     }
+
+    #[test]
+    fn signature_labels_match_rfc() {
+        assert_eq!(
+            SignatureLabel::FramedContent.full_label(),
+            b"MLS 1.0 FramedContentTBS".to_vec()
+        );
+
+        assert_eq!(SignatureLabel::LeafNode.as_str(), "LeafNodeTBS");
+        assert_eq!(SignatureLabel::KeyPackage.as_str(), "KeyPackageTBS");
+        assert_eq!(SignatureLabel::GroupInfo.as_str(), "GroupInfoTBS");
+    }
+
+    #[cfg(feature = "signature_label_suffix")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn signature_with_label_suffix_round_trips() {
+        use crate::crypto::WithSignatureLabelSuffix;
+        use mls_rs_core::crypto::CryptoProvider;
+
+        let cs = WithSignatureLabelSuffix::new(TestCryptoProvider::new(), b" suffix".to_vec())
+            .cipher_suite_provider(TEST_CIPHER_SUITE)
+            .unwrap();
+
+        let (secret, public) = cs.signature_key_generate().await.unwrap();
+        let context = random_bytes(32);
+
+        let mut test_signable = TestSignable {
+            content: random_bytes(32),
+            signature: vec![],
+        };
+
+        test_signable.sign(&cs, &secret, &context).await.unwrap();
+
+        test_signable.verify(&cs, &public, &context).await.unwrap();
+
+        let sign_content = SignContent {
+            label: b"MLS 1.0 SignWithLabel suffix".to_vec(),
+            content: [context.as_slice(), test_signable.content.as_slice()].concat(),
+        };
+
+        test_cipher_suite_provider(TEST_CIPHER_SUITE)
+            .verify(
+                &public,
+                &test_signable.signature,
+                &sign_content.mls_encode_to_vec().unwrap(),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[cfg(feature = "signature_label_suffix")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn signature_with_other_label_suffix_is_rejected() {
+        use crate::crypto::WithSignatureLabelSuffix;
+        use mls_rs_core::crypto::CryptoProvider;
+
+        let suffixed = |suffix: &[u8]| {
+            WithSignatureLabelSuffix::new(TestCryptoProvider::new(), suffix.to_vec())
+                .cipher_suite_provider(TEST_CIPHER_SUITE)
+                .unwrap()
+        };
+
+        let cs = suffixed(b"deployment");
+        let (secret, public) = cs.signature_key_generate().await.unwrap();
+
+        let mut test_signable = TestSignable {
+            content: random_bytes(32),
+            signature: vec![],
+        };
+
+        test_signable.sign(&cs, &secret, &vec![]).await.unwrap();
+
+        let res = test_signable
+            .verify(&suffixed(b"other deployment"), &public, &vec![])
+            .await;
+
+        assert_matches!(res, Err(MlsError::InvalidSignature));
+
+        let res = test_signable
+            .verify(
+                &test_cipher_suite_provider(TEST_CIPHER_SUITE),
+                &public,
+                &vec![],
+            )
+            .await;
+
+        assert_matches!(res, Err(MlsError::InvalidSignature));
+    }
+
+    #[test]
+    fn full_label_with_suffix_appends_suffix() {
+        assert_eq!(
+            SignatureLabel::GroupInfo.full_label_with_suffix(b" suffix"),
+            b"MLS 1.0 GroupInfoTBS suffix".to_vec()
+        );
+    }
 }
@@ -2,11 +2,15 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+/// Storage wrappers that encrypt data at rest.
+pub mod encrypted;
 /// Storage providers that operate completely in memory.
 pub mod in_memory;
 pub(crate) mod key_package;
+mod snapshot_delta;
 
 pub use key_package::*;
+pub use snapshot_delta::SnapshotDelta;
 
 #[cfg(feature = "sqlite")]
 #[cfg_attr(docsrs, doc(cfg(feature = "sqlite")))]
@@ -0,0 +1,553 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{
+    crypto::CipherSuiteProvider,
+    error::IntoAnyError,
+    group::{EpochRecord, GroupState, GroupStateStorage},
+};
+use zeroize::Zeroizing;
+
+use crate::client::MlsError;
+
+/// Symmetric key used by [`EncryptedGroupStateStorage`] to encrypt group
+/// state at rest.
+///
+/// The `id` is stored alongside each encrypted record so that the key
+/// required for decryption can be found after a key rotation.
+#[derive(Clone)]
+pub struct StorageEncryptionKey {
+    id: u32,
+    key: Zeroizing<Vec<u8>>,
+}
+
+impl StorageEncryptionKey {
+    /// Create a key with identifier `id`. The length of `key` must match the
+    /// AEAD key size of the cipher suite used for encryption.
+    pub fn new(id: u32, key: Vec<u8>) -> Self {
+        Self {
+            id,
+            key: Zeroizing::new(key),
+        }
+    }
+
+    /// Identifier of this key.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+}
+
+impl Debug for StorageEncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StorageEncryptionKey")
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+struct StorageEnvelope {
+    key_id: u32,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    nonce: Vec<u8>,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    ciphertext: Vec<u8>,
+}
+
+#[derive(MlsSize, MlsEncode)]
+struct StorageAad<'a> {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    group_id: &'a [u8],
+    epoch_id: Option<u64>,
+}
+
+/// [`GroupStateStorage`] wrapper that encrypts group states and prior epochs
+/// before passing them to an inner storage.
+///
+/// Each record is sealed with the AEAD of `cipher_suite_provider` under a
+/// fresh random nonce. The group ID and epoch ID of the record are used as
+/// additional authenticated data, which prevents records from being swapped
+/// between groups or epochs.
+///
+/// # Rollback
+///
+/// Protection against rollback is out of scope. The additional
+/// authenticated data does not contain a version of the record, so an
+/// attacker with write access to the inner storage can replace the group
+/// state with an older encrypted state of the same group, which decrypts
+/// successfully and resumes the group at an earlier epoch. Detecting this
+/// requires a monotonic counter kept outside of the inner storage, e.g. the
+/// last epoch of each group in trusted storage, compared by the application
+/// with the epoch of the loaded group.
+///
+/// # Key rotation
+///
+/// To rotate keys, construct the storage with the new key, add the old key
+/// with [`with_previous_key`](Self::with_previous_key), and call
+/// [`reencrypt`](Self::reencrypt) for each stored group. Afterwards, the old
+/// key is no longer needed.
+#[derive(Clone)]
+pub struct EncryptedGroupStateStorage<S, P> {
+    inner: S,
+    cipher_suite_provider: P,
+    key: StorageEncryptionKey,
+    previous_keys: Vec<StorageEncryptionKey>,
+}
+
+impl<S, P> Debug for EncryptedGroupStateStorage<S, P>
+where
+    S: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncryptedGroupStateStorage")
+            .field("inner", &self.inner)
+            .field("key", &self.key)
+            .field("previous_keys", &self.previous_keys)
+            .finish()
+    }
+}
+
+impl<S, P> EncryptedGroupStateStorage<S, P>
+where
+    S: GroupStateStorage,
+    P: CipherSuiteProvider,
+{
+    /// Encrypt records written to `inner` with `key`, using the AEAD of
+    /// `cipher_suite_provider`.
+    pub fn new(
+        inner: S,
+        cipher_suite_provider: P,
+        key: StorageEncryptionKey,
+    ) -> Result<Self, MlsError> {
+        check_key_size(&cipher_suite_provider, &key)?;
+
+        Ok(Self {
+            inner,
+            cipher_suite_provider,
+            key,
+            previous_keys: Vec::new(),
+        })
+    }
+
+    /// Accept `key` for decrypting records that were written before a key
+    /// rotation.
+    pub fn with_previous_key(mut self, key: StorageEncryptionKey) -> Result<Self, MlsError> {
+        check_key_size(&self.cipher_suite_provider, &key)?;
+        self.previous_keys.push(key);
+        Ok(self)
+    }
+
+    /// The wrapped storage.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Re-encrypt the group state and all stored prior epochs of the group
+    /// with ID `group_id` with the current key.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn reencrypt(&mut self, group_id: &[u8]) -> Result<(), MlsError> {
+        let Some(data) = self.state(group_id).await? else {
+            return Ok(());
+        };
+
+        let mut epochs = Vec::new();
+
+        if let Some(max_epoch_id) = self.max_epoch_id(group_id).await? {
+            for epoch_id in (0..=max_epoch_id).rev() {
+                let Some(data) = self.epoch(group_id, epoch_id).await? else {
+                    break;
+                };
+
+                epochs.push(EpochRecord::new(epoch_id, data));
+            }
+        }
+
+        epochs.reverse();
+
+        let state = GroupState {
+            id: group_id.to_vec(),
+            data,
+        };
+
+        self.write(state, Vec::new(), epochs).await
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn seal(
+        &self,
+        group_id: &[u8],
+        epoch_id: Option<u64>,
+        data: &[u8],
+    ) -> Result<Vec<u8>, MlsError> {
+        let aad = StorageAad { group_id, epoch_id }.mls_encode_to_vec()?;
+
+        let nonce = self
+            .cipher_suite_provider
+            .random_bytes_vec(self.cipher_suite_provider.aead_nonce_size())
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        let ciphertext = self
+            .cipher_suite_provider
+            .aead_seal(&self.key.key, data, Some(&aad), &nonce)
+            .await
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        let envelope = StorageEnvelope {
+            key_id: self.key.id,
+            nonce,
+            ciphertext,
+        };
+
+        Ok(envelope.mls_encode_to_vec()?)
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn open(
+        &self,
+        group_id: &[u8],
+        epoch_id: Option<u64>,
+        data: &[u8],
+    ) -> Result<Vec<u8>, MlsError> {
+        let envelope = StorageEnvelope::mls_decode(&mut &*data)?;
+
+        let key = core::iter::once(&self.key)
+            .chain(self.previous_keys.iter())
+            .find(|key| key.id == envelope.key_id)
+            .ok_or(MlsError::StorageKeyNotFound(envelope.key_id))?;
+
+        let aad = StorageAad { group_id, epoch_id }.mls_encode_to_vec()?;
+
+        let plaintext = self
+            .cipher_suite_provider
+            .aead_open(&key.key, &envelope.ciphertext, Some(&aad), &envelope.nonce)
+            .await
+            .map_err(|_| MlsError::StorageDecryptionFailed)?;
+
+        Ok(plaintext.to_vec())
+    }
+}
+
+fn check_key_size<P: CipherSuiteProvider>(
+    cipher_suite_provider: &P,
+    key: &StorageEncryptionKey,
+) -> Result<(), MlsError> {
+    if key.key.len() != cipher_suite_provider.aead_key_size() {
+        return Err(MlsError::InvalidStorageKeyLength(key.key.len()));
+    }
+
+    Ok(())
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl<S, P> GroupStateStorage for EncryptedGroupStateStorage<S, P>
+where
+    S: GroupStateStorage,
+    P: CipherSuiteProvider,
+{
+    type Error = MlsError;
+
+    async fn state(&self, group_id: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        let data = self
+            .inner
+            .state(group_id)
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?;
+
+        match data {
+            Some(data) => Ok(Some(self.open(group_id, None, &data).await?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn epoch(&self, group_id: &[u8], epoch_id: u64) -> Result<Option<Vec<u8>>, Self::Error> {
+        let data = self
+            .inner
+            .epoch(group_id, epoch_id)
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))?;
+
+        match data {
+            Some(data) => Ok(Some(self.open(group_id, Some(epoch_id), &data).await?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn write(
+        &mut self,
+        state: GroupState,
+        epoch_inserts: Vec<EpochRecord>,
+        epoch_updates: Vec<EpochRecord>,
+    ) -> Result<(), Self::Error> {
+        let group_id = state.id;
+
+        let state = GroupState {
+            data: self.seal(&group_id, None, &state.data).await?,
+            id: group_id.clone(),
+        };
+
+        let mut inserts = Vec::with_capacity(epoch_inserts.len());
+
+        for epoch in epoch_inserts {
+            let data = self.seal(&group_id, Some(epoch.id), &epoch.data).await?;
+            inserts.push(EpochRecord::new(epoch.id, data));
+        }
+
+        let mut updates = Vec::with_capacity(epoch_updates.len());
+
+        for epoch in epoch_updates {
+            let data = self.seal(&group_id, Some(epoch.id), &epoch.data).await?;
+            updates.push(EpochRecord::new(epoch.id, data));
+        }
+
+        self.inner
+            .write(state, inserts, updates)
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))
+    }
+
+    async fn max_epoch_id(&self, group_id: &[u8]) -> Result<Option<u64>, Self::Error> {
+        self.inner
+            .max_epoch_id(group_id)
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))
+    }
+
+    async fn delete(&mut self, group_id: &[u8]) -> Result<(), Self::Error> {
+        self.inner
+            .delete(group_id)
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))
+    }
+
+    async fn begin_transaction(&mut self) -> Result<(), Self::Error> {
+        self.inner
+            .begin_transaction()
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))
+    }
+
+    async fn commit_transaction(&mut self) -> Result<(), Self::Error> {
+        self.inner
+            .commit_transaction()
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))
+    }
+
+    async fn rollback_transaction(&mut self) -> Result<(), Self::Error> {
+        self.inner
+            .rollback_transaction()
+            .await
+            .map_err(|e| MlsError::GroupStorageError(e.into_any_error()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use assert_matches::assert_matches;
+    use mls_rs_core::{
+        crypto::CipherSuiteProvider,
+        group::{EpochRecord, GroupState, GroupStateStorage},
+    };
+
+    use super::{EncryptedGroupStateStorage, StorageEncryptionKey};
+
+    use crate::{
+        client::{
+            test_utils::{TestClientBuilder, TEST_CIPHER_SUITE},
+            MlsError,
+        },
+        crypto::test_utils::{test_cipher_suite_provider, TestCryptoProvider},
+        group::test_utils::TEST_GROUP,
+        identity::test_utils::get_test_signing_identity,
+        storage_provider::in_memory::InMemoryGroupStateStorage,
+        ExtensionList,
+    };
+
+    type TestStorage = EncryptedGroupStateStorage<
+        InMemoryGroupStateStorage,
+        <TestCryptoProvider as crate::CryptoProvider>::CipherSuiteProvider,
+    >;
+
+    fn test_key(id: u32) -> StorageEncryptionKey {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        StorageEncryptionKey::new(id, vec![id as u8; cs.aead_key_size()])
+    }
+
+    fn test_storage(inner: &InMemoryGroupStateStorage, key: StorageEncryptionKey) -> TestStorage {
+        EncryptedGroupStateStorage::new(
+            inner.clone(),
+            test_cipher_suite_provider(TEST_CIPHER_SUITE),
+            key,
+        )
+        .unwrap()
+    }
+
+    fn test_state(data: &[u8]) -> GroupState {
+        GroupState {
+            id: TEST_GROUP.to_vec(),
+            data: data.to_vec(),
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn records_are_encrypted_at_rest() {
+        let inner = InMemoryGroupStateStorage::new();
+        let mut storage = test_storage(&inner, test_key(1));
+
+        storage
+            .write(
+                test_state(b"state"),
+                vec![EpochRecord::new(0, b"epoch".to_vec())],
+                vec![],
+            )
+            .await
+            .unwrap();
+
+        let raw_state = inner.state(TEST_GROUP).await.unwrap().unwrap();
+        let raw_epoch = inner.epoch(TEST_GROUP, 0).await.unwrap().unwrap();
+
+        assert_ne!(raw_state, b"state".to_vec());
+        assert_ne!(raw_epoch, b"epoch".to_vec());
+
+        let state = storage.state(TEST_GROUP).await.unwrap();
+        let epoch = storage.epoch(TEST_GROUP, 0).await.unwrap();
+
+        assert_eq!(state, Some(b"state".to_vec()));
+        assert_eq!(epoch, Some(b"epoch".to_vec()));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn records_can_not_be_swapped() {
+        let inner = InMemoryGroupStateStorage::new();
+        let mut storage = test_storage(&inner, test_key(1));
+
+        storage
+            .write(
+                test_state(b"state"),
+                vec![
+                    EpochRecord::new(0, b"epoch 0".to_vec()),
+                    EpochRecord::new(1, b"epoch 1".to_vec()),
+                ],
+                vec![],
+            )
+            .await
+            .unwrap();
+
+        let mut inner_writer = inner.clone();
+        let raw_epoch = inner.epoch(TEST_GROUP, 1).await.unwrap().unwrap();
+        let raw_state = inner.state(TEST_GROUP).await.unwrap().unwrap();
+
+        inner_writer
+            .write(
+                GroupState {
+                    id: TEST_GROUP.to_vec(),
+                    data: raw_epoch.clone(),
+                },
+                vec![],
+                vec![EpochRecord::new(0, raw_epoch)],
+            )
+            .await
+            .unwrap();
+
+        let res = storage.state(TEST_GROUP).await;
+        assert_matches!(res, Err(MlsError::StorageDecryptionFailed));
+
+        let res = storage.epoch(TEST_GROUP, 0).await;
+        assert_matches!(res, Err(MlsError::StorageDecryptionFailed));
+
+        inner_writer
+            .write(
+                GroupState {
+                    id: b"other group".to_vec(),
+                    data: raw_state,
+                },
+                vec![],
+                vec![],
+            )
+            .await
+            .unwrap();
+
+        let res = storage.state(b"other group").await;
+        assert_matches!(res, Err(MlsError::StorageDecryptionFailed));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn keys_can_be_rotated() {
+        let inner = InMemoryGroupStateStorage::new();
+        let mut storage = test_storage(&inner, test_key(1));
+
+        storage
+            .write(
+                test_state(b"state"),
+                vec![
+                    EpochRecord::new(0, b"epoch 0".to_vec()),
+                    EpochRecord::new(1, b"epoch 1".to_vec()),
+                ],
+                vec![],
+            )
+            .await
+            .unwrap();
+
+        let res = test_storage(&inner, test_key(2)).state(TEST_GROUP).await;
+        assert_matches!(res, Err(MlsError::StorageKeyNotFound(1)));
+
+        let mut rotating = test_storage(&inner, test_key(2))
+            .with_previous_key(test_key(1))
+            .unwrap();
+
+        rotating.reencrypt(TEST_GROUP).await.unwrap();
+
+        let storage = test_storage(&inner, test_key(2));
+
+        let state = storage.state(TEST_GROUP).await.unwrap();
+        assert_eq!(state, Some(b"state".to_vec()));
+
+        for (epoch_id, data) in [(0, b"epoch 0"), (1, b"epoch 1")] {
+            let epoch = storage.epoch(TEST_GROUP, epoch_id).await.unwrap();
+            assert_eq!(epoch, Some(data.to_vec()));
+        }
+    }
+
+    #[test]
+    fn invalid_key_size_is_rejected() {
+        let res = EncryptedGroupStateStorage::new(
+            InMemoryGroupStateStorage::new(),
+            test_cipher_suite_provider(TEST_CIPHER_SUITE),
+            StorageEncryptionKey::new(1, vec![0; 3]),
+        );
+
+        assert_matches!(res, Err(MlsError::InvalidStorageKeyLength(3)));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn group_can_be_loaded_from_encrypted_storage() {
+        let (identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"alice").await;
+        let inner = InMemoryGroupStateStorage::new();
+
+        let client = TestClientBuilder::new_for_test()
+            .signing_identity(identity, secret_key, TEST_CIPHER_SUITE)
+            .group_state_storage(test_storage(&inner, test_key(1)))
+            .build();
+
+        let mut group = client.create_group(ExtensionList::default()).await.unwrap();
+
+        group.commit(Vec::new()).await.unwrap();
+        group.apply_pending_commit().await.unwrap();
+        group.write_to_storage().await.unwrap();
+
+        let loaded = client.load_group(group.group_id()).await.unwrap();
+
+        assert_eq!(loaded.current_epoch(), group.current_epoch());
+    }
+}
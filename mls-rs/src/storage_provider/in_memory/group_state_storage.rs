@@ -222,6 +222,11 @@ impl GroupStateStorage for InMemoryGroupStateStorage {
 
         Ok(())
     }
+
+    async fn delete(&mut self, group_id: &[u8]) -> Result<(), Self::Error> {
+        self.delete_group(group_id);
+        Ok(())
+    }
 }
 
 #[cfg(all(test, feature = "prior_epoch"))]
@@ -32,6 +32,7 @@ use crate::map::LargeMap;
 /// All clones of an instance of this type share the same underlying HashMap.
 pub struct InMemoryKeyPackageStorage {
     inner: Arc<Mutex<LargeMap<Vec<u8>, KeyPackageData>>>,
+    consumed: Arc<Mutex<LargeMap<Vec<u8>, u64>>>,
 }
 
 impl Debug for InMemoryKeyPackageStorage {
@@ -72,6 +73,36 @@ impl InMemoryKeyPackageStorage {
     /// Delete key package data by `id`.
     pub fn delete(&self, id: &[u8]) {
         self.lock().remove(id);
+        lock(&self.consumed).remove(id);
+    }
+
+    /// Record that the key package data with `id` was used to join a group at
+    /// `consumed_at`, in seconds since the Unix epoch. The earliest time is
+    /// kept if the key package was already marked consumed.
+    pub fn mark_consumed(&self, id: &[u8], consumed_at: u64) {
+        if self.lock().contains_key(id) {
+            lock(&self.consumed)
+                .entry(id.to_vec())
+                .or_insert(consumed_at);
+        }
+    }
+
+    /// Delete all key package data marked consumed at or before
+    /// `consumed_before`.
+    pub fn delete_consumed(&self, consumed_before: u64) {
+        let expired = lock(&self.consumed)
+            .iter()
+            .filter(|(_, consumed_at)| **consumed_at <= consumed_before)
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>();
+
+        expired.iter().for_each(|id| self.delete(id));
+    }
+
+    /// Time at which the key package data with `id` was marked consumed, if
+    /// it was.
+    pub fn consumed_at(&self, id: &[u8]) -> Option<u64> {
+        lock(&self.consumed).get(id).copied()
     }
 
     /// Get all key packages that are currently stored.
@@ -83,14 +114,18 @@ impl InMemoryKeyPackageStorage {
     }
 
     fn lock(&self) -> MutexGuard<'_, LargeMap<Vec<u8>, KeyPackageData>> {
-        #[cfg(feature = "std")]
-        return self.inner.lock().unwrap();
-
-        #[cfg(not(feature = "std"))]
-        return self.inner.lock();
+        lock(&self.inner)
     }
 }
 
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    #[cfg(feature = "std")]
+    return mutex.lock().unwrap();
+
+    #[cfg(not(feature = "std"))]
+    return mutex.lock();
+}
+
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
 impl KeyPackageStorage for InMemoryKeyPackageStorage {
@@ -109,4 +144,14 @@ impl KeyPackageStorage for InMemoryKeyPackageStorage {
     async fn get(&self, id: &[u8]) -> Result<Option<KeyPackageData>, Self::Error> {
         Ok(self.get(id))
     }
+
+    async fn mark_consumed(&mut self, id: &[u8], consumed_at: u64) -> Result<(), Self::Error> {
+        (*self).mark_consumed(id, consumed_at);
+        Ok(())
+    }
+
+    async fn delete_consumed(&mut self, consumed_before: u64) -> Result<(), Self::Error> {
+        (*self).delete_consumed(consumed_before);
+        Ok(())
+    }
 }
@@ -8,6 +8,7 @@ use alloc::sync::Arc;
 #[cfg(not(target_has_atomic = "ptr"))]
 use portable_atomic_util::Arc;
 
+use alloc::vec::Vec;
 use core::convert::Infallible;
 
 use mls_rs_core::psk::{ExternalPskId, PreSharedKey, PreSharedKeyStorage};
@@ -27,41 +28,100 @@ use crate::map::LargeMap;
 ///
 /// All clones of an instance of this type share the same underlying HashMap.
 pub struct InMemoryPreSharedKeyStorage {
-    inner: Arc<Mutex<LargeMap<ExternalPskId, PreSharedKey>>>,
+    inner: Arc<Mutex<PskStore>>,
+}
+
+#[derive(Debug, Default)]
+struct PskStore {
+    keys: LargeMap<ExternalPskId, PreSharedKey>,
+    successors: LargeMap<ExternalPskId, ExternalPskId>,
 }
 
 impl InMemoryPreSharedKeyStorage {
-    /// Insert a pre-shared key into storage.
+    #[cfg(feature = "std")]
+    fn lock(&self) -> std::sync::MutexGuard<'_, PskStore> {
+        self.inner.lock().unwrap()
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn lock(&self) -> spin::MutexGuard<'_, PskStore> {
+        self.inner.lock()
+    }
+
+    /// Insert a pre-shared key into storage, replacing any key previously
+    /// stored under `id`.
     pub fn insert(&mut self, id: ExternalPskId, psk: PreSharedKey) {
-        #[cfg(feature = "std")]
-        let mut lock = self.inner.lock().unwrap();
+        self.lock().keys.insert(id, psk);
+    }
+
+    /// Register a new pre-shared key.
+    ///
+    /// Unlike [`insert`](Self::insert), an existing key is never replaced.
+    /// Returns `false` if a key is already stored under `id`.
+    pub fn register(&mut self, id: ExternalPskId, psk: PreSharedKey) -> bool {
+        let mut lock = self.lock();
 
-        #[cfg(not(feature = "std"))]
-        let mut lock = self.inner.lock();
+        if lock.keys.contains_key(&id) {
+            return false;
+        }
 
-        lock.insert(id, psk);
+        lock.keys.insert(id, psk);
+        true
+    }
+
+    /// Start rotating the key stored under `old_id` to `psk` stored under
+    /// `new_id`.
+    ///
+    /// Both keys remain valid until the old one is [revoked](Self::revoke),
+    /// so that members can keep processing messages using the old key while
+    /// the new one is being distributed and committed, e.g. with
+    /// [`Group::commit_external_psk_rotation`](crate::Group::commit_external_psk_rotation).
+    /// Returns `false` and leaves the storage unchanged if `old_id` is not
+    /// stored or `new_id` is already in use.
+    pub fn rotate(
+        &mut self,
+        old_id: &ExternalPskId,
+        new_id: ExternalPskId,
+        psk: PreSharedKey,
+    ) -> bool {
+        let mut lock = self.lock();
+
+        if !lock.keys.contains_key(old_id) || lock.keys.contains_key(&new_id) {
+            return false;
+        }
+
+        lock.keys.insert(new_id.clone(), psk);
+        lock.successors.insert(old_id.clone(), new_id);
+        true
+    }
+
+    /// The id of the key replacing `id`, if `id` is being rotated.
+    pub fn successor(&self, id: &ExternalPskId) -> Option<ExternalPskId> {
+        self.lock().successors.get(id).cloned()
+    }
+
+    /// Revoke the key stored under `id`, ending its rotation window if it
+    /// is being rotated. Returns the revoked key.
+    pub fn revoke(&mut self, id: &ExternalPskId) -> Option<PreSharedKey> {
+        let mut lock = self.lock();
+        lock.successors.remove(id);
+        lock.keys.remove(id)
     }
 
     /// Get a pre-shared key by `id`.
     pub fn get(&self, id: &ExternalPskId) -> Option<PreSharedKey> {
-        #[cfg(feature = "std")]
-        let lock = self.inner.lock().unwrap();
-
-        #[cfg(not(feature = "std"))]
-        let lock = self.inner.lock();
+        self.lock().keys.get(id).cloned()
+    }
 
-        lock.get(id).cloned()
+    /// Ids of all stored pre-shared keys, including keys that are being
+    /// rotated.
+    pub fn ids(&self) -> Vec<ExternalPskId> {
+        self.lock().keys.keys().cloned().collect()
     }
 
     /// Delete a pre-shared key from storage.
     pub fn delete(&mut self, id: &ExternalPskId) {
-        #[cfg(feature = "std")]
-        let mut lock = self.inner.lock().unwrap();
-
-        #[cfg(not(feature = "std"))]
-        let mut lock = self.inner.lock();
-
-        lock.remove(id);
+        self.revoke(id);
     }
 }
 
@@ -73,4 +133,42 @@ impl PreSharedKeyStorage for InMemoryPreSharedKeyStorage {
     async fn get(&self, id: &ExternalPskId) -> Result<Option<PreSharedKey>, Self::Error> {
         Ok(self.get(id))
     }
+
+    async fn ids(&self) -> Result<Vec<ExternalPskId>, Self::Error> {
+        Ok(self.ids())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use mls_rs_core::psk::{ExternalPskId, PreSharedKey};
+
+    use super::InMemoryPreSharedKeyStorage;
+
+    #[test]
+    fn rotated_keys_stay_valid_until_revoked() {
+        let mut storage = InMemoryPreSharedKeyStorage::default();
+
+        let old_id = ExternalPskId::new(vec![0]);
+        let new_id = ExternalPskId::new(vec![1]);
+
+        assert!(storage.register(old_id.clone(), PreSharedKey::from(vec![0])));
+        assert!(!storage.register(old_id.clone(), PreSharedKey::from(vec![2])));
+        assert_eq!(storage.get(&old_id), Some(PreSharedKey::from(vec![0])));
+
+        assert!(!storage.rotate(&new_id, old_id.clone(), PreSharedKey::from(vec![1])));
+        assert!(storage.rotate(&old_id, new_id.clone(), PreSharedKey::from(vec![1])));
+
+        let mut ids = storage.ids();
+        ids.sort();
+        assert_eq!(ids, vec![old_id.clone(), new_id.clone()]);
+        assert_eq!(storage.successor(&old_id), Some(new_id.clone()));
+
+        assert_eq!(storage.revoke(&old_id), Some(PreSharedKey::from(vec![0])));
+        assert_eq!(storage.get(&old_id), None);
+        assert_eq!(storage.successor(&old_id), None);
+        assert_eq!(storage.ids(), vec![new_id]);
+    }
 }
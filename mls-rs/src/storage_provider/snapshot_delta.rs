@@ -0,0 +1,281 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use core::mem;
+
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{crypto::CipherSuiteProvider, error::IntoAnyError};
+
+use crate::{
+    client::MlsError,
+    group::snapshot::Snapshot,
+    tree_kem::{
+        node::{Node, NodeIndex},
+        TreeKemPublic,
+    },
+};
+
+/// Difference between two snapshots of the state of a group, as written to
+/// the [`GroupStateStorage`](crate::GroupStateStorage) in
+/// [`GroupState::data`](mls_rs_core::group::GroupState::data).
+///
+/// A commit changes only a few nodes of the ratchet tree, which makes up most
+/// of the state of a large group. A delta contains the changed tree nodes and
+/// the remaining state of the group, such as the secrets and metadata of the
+/// new epoch. Storage implementations can append deltas instead of writing
+/// full snapshots, and [apply](SnapshotDelta::apply) them when the group is
+/// loaded.
+///
+/// Each delta records the hashes of the snapshots it was computed from, so
+/// that it can only be applied to the snapshot it was computed against and
+/// its result can be verified. The hashes are computed over the snapshots
+/// without the data derived from the tree, which is recomputed when the group
+/// is loaded. Deltas contain secrets and must be stored as securely as
+/// snapshots.
+#[derive(Clone, Debug, PartialEq, MlsSize, MlsEncode, MlsDecode)]
+pub struct SnapshotDelta {
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    base_hash: Vec<u8>,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    target_hash: Vec<u8>,
+    node_count: u32,
+    changed_nodes: Vec<(NodeIndex, Option<Node>)>,
+    state: Snapshot,
+}
+
+struct CanonicalSnapshot {
+    snapshot: Snapshot,
+    encoded: Vec<u8>,
+}
+
+impl CanonicalSnapshot {
+    fn decode(bytes: &[u8]) -> Result<Self, MlsError> {
+        let mut snapshot = Snapshot::from_bytes(bytes)?;
+
+        let mut public_tree = TreeKemPublic::new();
+        public_tree.nodes = mem::take(&mut snapshot.state.public_tree.nodes);
+        snapshot.state.public_tree = public_tree;
+
+        let encoded = snapshot.mls_encode_to_vec()?;
+
+        Ok(Self { snapshot, encoded })
+    }
+}
+
+impl SnapshotDelta {
+    /// Compute the delta turning the snapshot `base` into `target`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn between<CP: CipherSuiteProvider>(
+        cipher_suite_provider: &CP,
+        base: &[u8],
+        target: &[u8],
+    ) -> Result<Self, MlsError> {
+        let base = CanonicalSnapshot::decode(base)?;
+        let target = CanonicalSnapshot::decode(target)?;
+
+        let base_hash = hash(cipher_suite_provider, &base.encoded).await?;
+        let target_hash = hash(cipher_suite_provider, &target.encoded).await?;
+
+        let mut state = target.snapshot;
+        let nodes = mem::take(&mut state.state.public_tree.nodes);
+        let base_nodes = &base.snapshot.state.public_tree.nodes;
+
+        let changed_nodes = nodes
+            .iter()
+            .enumerate()
+            .filter(|(i, node)| base_nodes.get(*i) != Some(*node))
+            .map(|(i, node)| (i as NodeIndex, node.clone()))
+            .collect();
+
+        Ok(Self {
+            base_hash,
+            target_hash,
+            node_count: nodes.len() as u32,
+            changed_nodes,
+            state,
+        })
+    }
+
+    /// Apply this delta to the snapshot `base` it was computed against.
+    ///
+    /// Returns the resulting snapshot, which can be loaded like the snapshot
+    /// the delta was computed to. Returns
+    /// [`MlsError::SnapshotDeltaInvalidNodeCount`] if the delta grows the
+    /// tree by more nodes than it contains.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn apply<CP: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &CP,
+        base: &[u8],
+    ) -> Result<Vec<u8>, MlsError> {
+        let base = CanonicalSnapshot::decode(base)?;
+
+        (hash(cipher_suite_provider, &base.encoded).await? == self.base_hash)
+            .then_some(())
+            .ok_or(MlsError::SnapshotDeltaBaseMismatch)?;
+
+        let mut nodes = base.snapshot.state.public_tree.nodes;
+
+        // Nodes beyond the base are all set by the delta, which bounds the
+        // size of the tree by the size of the delta.
+        if self.node_count as usize > nodes.len() + self.changed_nodes.len() {
+            return Err(MlsError::SnapshotDeltaInvalidNodeCount(self.node_count));
+        }
+
+        nodes.resize(self.node_count as usize, None);
+
+        for (i, node) in &self.changed_nodes {
+            *nodes
+                .get_mut(*i as usize)
+                .ok_or(MlsError::InvalidNodeIndex(*i))? = node.clone();
+        }
+
+        let mut target = self.state.clone();
+        target.state.public_tree.nodes = nodes;
+
+        let encoded = target.mls_encode_to_vec()?;
+
+        (hash(cipher_suite_provider, &encoded).await? == self.target_hash)
+            .then_some(())
+            .ok_or(MlsError::SnapshotDeltaTargetMismatch)?;
+
+        Ok(encoded)
+    }
+
+    /// Merge this delta with the delta `next` computed against its target,
+    /// into one delta turning the base of this delta into the target of
+    /// `next`.
+    pub fn merge(self, next: SnapshotDelta) -> Result<Self, MlsError> {
+        (self.target_hash == next.base_hash)
+            .then_some(())
+            .ok_or(MlsError::SnapshotDeltaBaseMismatch)?;
+
+        let overwritten = |i: &NodeIndex| next.changed_nodes.iter().any(|(j, _)| j == i);
+
+        let mut changed_nodes = self
+            .changed_nodes
+            .into_iter()
+            .filter(|(i, _)| *i < next.node_count && !overwritten(i))
+            .collect::<Vec<_>>();
+
+        // Nodes truncated by this delta and not set by `next` are blank, even
+        // if they are not in the base of this delta.
+        let blanked = (self.node_count..next.node_count)
+            .filter(|i| !overwritten(i))
+            .map(|i| (i, None))
+            .collect::<Vec<_>>();
+
+        changed_nodes.extend(blanked);
+        changed_nodes.extend(next.changed_nodes);
+        changed_nodes.sort_by_key(|(i, _)| *i);
+
+        Ok(Self {
+            base_hash: self.base_hash,
+            target_hash: next.target_hash,
+            node_count: next.node_count,
+            changed_nodes,
+            state: next.state,
+        })
+    }
+
+    /// Deserialize a delta from storage.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+
+    /// Serialize a delta for storage.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MlsError> {
+        self.mls_encode_to_vec().map_err(Into::into)
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+async fn hash<CP: CipherSuiteProvider>(
+    cipher_suite_provider: &CP,
+    data: &[u8],
+) -> Result<Vec<u8>, MlsError> {
+    cipher_suite_provider
+        .hash(data)
+        .await
+        .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use assert_matches::assert_matches;
+    use mls_rs_codec::MlsEncode;
+
+    use crate::{
+        client::{
+            test_utils::{TestClientBuilder, TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
+        crypto::test_utils::test_cipher_suite_provider,
+        group::{
+            snapshot::Snapshot,
+            test_utils::{test_group, TestGroup},
+            Group,
+        },
+    };
+
+    use super::SnapshotDelta;
+
+    fn snapshot(group: &TestGroup) -> Vec<u8> {
+        group.group.snapshot().mls_encode_to_vec().unwrap()
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn applied_deltas_restore_group() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let s0 = snapshot(&alice);
+        alice.join("bob").await;
+        let s1 = snapshot(&alice);
+        alice.join("carol").await;
+        let s2 = snapshot(&alice);
+
+        let d01 = SnapshotDelta::between(&cs, &s0, &s1).await.unwrap();
+        let d12 = SnapshotDelta::between(&cs, &s1, &s2).await.unwrap();
+
+        let d12 = SnapshotDelta::from_bytes(&d12.to_bytes().unwrap()).unwrap();
+
+        let res = d12.apply(&cs, &s0).await;
+        assert_matches!(res, Err(MlsError::SnapshotDeltaBaseMismatch));
+
+        let s1_applied = d01.apply(&cs, &s0).await.unwrap();
+        let s2_applied = d12.apply(&cs, &s1_applied).await.unwrap();
+
+        let merged = d01.merge(d12).unwrap();
+        let merged_applied = merged.apply(&cs, &s0).await.unwrap();
+        assert_eq!(merged_applied, s2_applied);
+
+        let snapshot = Snapshot::from_bytes(&s2_applied).unwrap();
+        let config = TestClientBuilder::new_for_test().build().config;
+
+        let restored = Group::from_snapshot(config, snapshot).await.unwrap();
+
+        assert!(Group::equal_group_state(&alice.group, &restored));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn delta_with_too_many_nodes_is_rejected() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let s0 = snapshot(&alice);
+        alice.join("bob").await;
+        let s1 = snapshot(&alice);
+
+        let mut delta = SnapshotDelta::between(&cs, &s0, &s1).await.unwrap();
+        delta.node_count = u32::MAX;
+
+        let delta = SnapshotDelta::from_bytes(&delta.to_bytes().unwrap()).unwrap();
+        let res = delta.apply(&cs, &s0).await;
+
+        assert_matches!(res, Err(MlsError::SnapshotDeltaInvalidNodeCount(u32::MAX)));
+    }
+}
@@ -1,12 +1,21 @@
 use mls_rs_codec::MlsEncode;
-use mls_rs_core::protocol_version::ProtocolVersion;
+use mls_rs_core::{
+    crypto::{CipherSuiteProvider, CryptoProvider},
+    protocol_version::ProtocolVersion,
+};
+use zeroize::Zeroizing;
 
 use crate::{
     cipher_suite::CipherSuite,
     client_builder::{BaseConfig, MlsConfig, WithCryptoProvider, WithIdentityProvider},
-    group::{framing::MlsMessage, Group},
+    group::{
+        framing::MlsMessage,
+        secret_tree::{KeyType, SecretTree},
+        Group, SecretTreeMemoryUsage, Validated,
+    },
     identity::basic::BasicIdentityProvider,
     test_utils::{generate_basic_client, get_test_groups},
+    tree_kem::node::{LeafIndex, NodeIndex},
 };
 
 pub use mls_rs_crypto_openssl::OpensslCryptoProvider as MlsCryptoProvider;
@@ -134,7 +143,32 @@ pub async fn join_group(cs: CipherSuite, group_info: MlsMessage) -> GroupStates<
         .unwrap();
 
     let (receiver, commit) = client.commit_external(group_info).await.unwrap();
-    sender.process_incoming_message(commit).await.unwrap();
+    sender
+        .process_incoming_message_validated(Validated::new(commit).unwrap())
+        .await
+        .unwrap();
 
     GroupStates { sender, receiver }
 }
+
+/// Derive the first application key of every leaf of a secret tree with
+/// `leaf_count` leaves, as a member receiving one message from each other
+/// member would, and report the memory held by the tree afterwards.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub async fn derive_secret_tree_keys(cs: CipherSuite, leaf_count: u32) -> SecretTreeMemoryUsage {
+    let cs = MlsCryptoProvider::new().cipher_suite_provider(cs).unwrap();
+    let secret = Zeroizing::new(vec![0u8; cs.kdf_extract_size()]);
+
+    // Like the ratchet tree, the secret tree always has a power of two leaves.
+    let mut tree = SecretTree::new(leaf_count.next_power_of_two(), secret);
+
+    for leaf in 0..leaf_count {
+        let leaf = NodeIndex::from(LeafIndex(leaf));
+
+        tree.message_key_generation(&cs, leaf, KeyType::Application, 0, u32::MAX)
+            .await
+            .unwrap();
+    }
+
+    tree.memory_usage()
+}
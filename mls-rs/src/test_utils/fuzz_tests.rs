@@ -13,9 +13,10 @@ use crate::{
     client_builder::{BaseConfig, WithCryptoProvider, WithIdentityProvider},
     group::{
         framing::{Content, MlsMessage, Sender, WireFormat},
+        key_schedule::reference::assert_key_schedule_matches_reference,
         message_processor::MessageProcessor,
         message_signature::AuthenticatedContent,
-        Commit, Group,
+        Commit, Group, GroupContext,
     },
     identity::{basic::BasicIdentityProvider, SigningIdentity},
     Client, ExtensionList,
@@ -85,6 +86,37 @@ pub fn create_fuzz_commit_message(
     group.format_for_wire(auth_content)
 }
 
+/// Derive an epoch from the given secrets and `context` and check it against
+/// an independent reference implementation of the key schedule. Secrets are
+/// truncated or zero-padded to the length they have in MLS.
+pub fn check_key_schedule(
+    cipher_suite: CipherSuite,
+    init_secret: &[u8],
+    commit_secret: &[u8],
+    psk_secret: &[u8],
+    context: &GroupContext,
+) {
+    let Some(cs_provider) = MlsCryptoProvider::new().cipher_suite_provider(cipher_suite) else {
+        return;
+    };
+
+    let key_size = cs_provider.kdf_extract_size();
+
+    let fit = |secret: &[u8]| {
+        let mut secret = secret.to_vec();
+        secret.resize(key_size, 0);
+        secret
+    };
+
+    assert_key_schedule_matches_reference(
+        &cs_provider,
+        &fit(init_secret),
+        &fit(commit_secret),
+        &fit(psk_secret),
+        context,
+    );
+}
+
 fn make_client(cipher_suite: CipherSuite, name: &str) -> Client<TestClientConfig> {
     let (secret, signing_identity) = make_identity(cipher_suite, name);
 
@@ -17,6 +17,7 @@ use mls_rs_core::{
 
 use crate::{
     client_builder::{ClientBuilder, MlsConfig},
+    group::Validated,
     identity::basic::BasicIdentityProvider,
     mls_rules::{CommitOptions, DefaultMlsRules},
     tree_kem::Lifetime,
@@ -50,6 +51,43 @@ pub fn is_edwards(cs: u16) -> bool {
     .contains(&cs.into())
 }
 
+/// Protocol version, cipher suite and whether control messages are
+/// encrypted, for each configuration that public API tests run with using
+/// `crypto_provider`. Control messages are only encrypted with the
+/// `private_message` feature.
+///
+/// The parameters are returned as a list instead of being passed to a test
+/// function, so that the same loop over them compiles both in the sync build
+/// and with `--cfg mls_build_async`, including on wasm32.
+pub fn all_test_params<C: CryptoProvider>(
+    crypto_provider: &C,
+) -> Vec<(ProtocolVersion, CipherSuite, bool)> {
+    let encrypt_controls: &[bool] = if cfg!(feature = "private_message") {
+        &[true, false]
+    } else {
+        &[false]
+    };
+
+    ProtocolVersion::all()
+        .flat_map(|version| {
+            crypto_provider
+                .supported_cipher_suites()
+                .into_iter()
+                .flat_map(move |cs| encrypt_controls.iter().map(move |&e| (version, cs, e)))
+        })
+        .collect()
+}
+
+/// Same as [`all_test_params`], restricted to plaintext control messages.
+pub fn plaintext_test_params<C: CryptoProvider>(
+    crypto_provider: &C,
+) -> Vec<(ProtocolVersion, CipherSuite, bool)> {
+    all_test_params(crypto_provider)
+        .into_iter()
+        .filter(|&(_, _, encrypt_controls)| !encrypt_controls)
+        .collect()
+}
+
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 #[cfg_attr(coverage_nightly, coverage(off))]
 pub async fn generate_basic_client<C: CryptoProvider + Clone>(
@@ -153,7 +191,10 @@ pub async fn get_test_groups<C: CryptoProvider + Clone>(
 
     for client in &receiver_clients {
         let (test_client, _info) = client
-            .join_group(Some(tree_data.clone()), &welcome[0])
+            .join_group_validated(
+                Some(tree_data.clone()),
+                &Validated::new(welcome[0].clone()).unwrap(),
+            )
             .await
             .unwrap();
 
@@ -174,7 +215,7 @@ pub async fn all_process_message<C: MlsConfig>(
     for group in groups {
         if sender != group.current_member_index() as usize {
             group
-                .process_incoming_message(message.clone())
+                .process_incoming_message_validated(Validated::new(message.clone()).unwrap())
                 .await
                 .unwrap();
         } else if is_commit {
@@ -75,7 +75,7 @@ impl ValidationTestCase {
 #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
 #[cfg_attr(coverage_nightly, coverage(off))]
 async fn validation() {
-    use crate::group::test_utils::get_test_group_context;
+    use crate::group::{test_utils::get_test_group_context, ExportedTree};
 
     #[cfg(mls_build_async)]
     let test_cases: Vec<ValidationTestCase> = load_test_case_json!(
@@ -92,6 +92,10 @@ async fn validation() {
             continue;
         };
 
+        // The tree is in the RFC 9420 `ratchet_tree` encoding used by exported trees
+        let exported = ExportedTree::from_bytes(&test_case.tree).unwrap();
+        assert_eq!(exported.to_bytes().unwrap(), test_case.tree);
+
         let mut tree = TreeKemPublic::import_node_data(
             NodeVec::mls_decode(&mut &*test_case.tree).unwrap(),
             &BasicIdentityProvider,
@@ -32,7 +32,7 @@ use super::{
     TreeKemPrivate, TreeKemPublic, UpdatePath, UpdatePathNode, ValidatedUpdatePath,
 };
 
-#[cfg(test)]
+#[cfg(any(test, feature = "adversarial"))]
 use crate::{group::CommitModifiers, signer::Signable};
 
 pub struct TreeKem<'a> {
@@ -67,7 +67,7 @@ impl<'a> TreeKem<'a> {
         update_leaf_properties: ConfigProperties,
         signing_identity: Option<SigningIdentity>,
         cipher_suite_provider: &P,
-        #[cfg(test)] commit_modifiers: &CommitModifiers,
+        #[cfg(any(test, feature = "adversarial"))] commit_modifiers: &CommitModifiers,
     ) -> Result<EncapGeneration, MlsError>
     where
         P: CipherSuiteProvider + Send + Sync,
@@ -97,7 +97,7 @@ impl<'a> TreeKem<'a> {
             }
         }
 
-        #[cfg(test)]
+        #[cfg(any(test, feature = "adversarial"))]
         (commit_modifiers.modify_tree)(self.tree_kem_public);
 
         self.tree_kem_public
@@ -120,7 +120,7 @@ impl<'a> TreeKem<'a> {
                     .await?,
             );
 
-            #[cfg(test)]
+            #[cfg(any(test, feature = "adversarial"))]
             if let Some(signer) = (commit_modifiers.modify_leaf)(own_leaf, signer) {
                 let context = &(context.group_id.as_slice(), *self_index).into();
 
@@ -130,6 +130,16 @@ impl<'a> TreeKem<'a> {
                     .unwrap();
             }
 
+            #[cfg(any(test, feature = "adversarial"))]
+            if let Some(public_key) = &commit_modifiers.leaf_encryption_key {
+                own_leaf.public_key = public_key.clone();
+
+                let context = &(context.group_id.as_slice(), *self_index).into();
+                own_leaf
+                    .sign(cipher_suite_provider, signer, context)
+                    .await?;
+            }
+
             own_leaf.clone()
         };
 
@@ -155,7 +165,7 @@ impl<'a> TreeKem<'a> {
             )
             .await?;
 
-        #[cfg(test)]
+        #[cfg(any(test, feature = "adversarial"))]
         let node_updates = (commit_modifiers.modify_path)(node_updates);
 
         // Create an update path with the new node and parent node updates
@@ -5,7 +5,11 @@
 use super::{parent_hash::ParentHash, Capabilities, Lifetime};
 use crate::client::MlsError;
 use crate::crypto::{CipherSuiteProvider, HpkePublicKey, HpkeSecretKey, SignatureSecretKey};
-use crate::{identity::SigningIdentity, signer::Signable, ExtensionList};
+use crate::{
+    identity::SigningIdentity,
+    signer::{Signable, SignatureLabel},
+    ExtensionList,
+};
 use alloc::vec::Vec;
 use core::fmt::{self, Debug};
 use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
@@ -70,11 +74,37 @@ impl LeafNode {
     where
         CSP: CipherSuiteProvider,
     {
-        let (secret_key, public_key) = cipher_suite_provider
+        let key_pair = cipher_suite_provider
             .kem_generate()
             .await
             .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
 
+        Self::generate_with_key(
+            cipher_suite_provider,
+            properties,
+            signing_identity,
+            signer,
+            lifetime,
+            key_pair,
+        )
+        .await
+    }
+
+    /// Same as [`LeafNode::generate`], using the pre-generated HPKE `key_pair`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn generate_with_key<CSP>(
+        cipher_suite_provider: &CSP,
+        properties: ConfigProperties,
+        signing_identity: SigningIdentity,
+        signer: &SignatureSecretKey,
+        lifetime: Lifetime,
+        key_pair: (HpkeSecretKey, HpkePublicKey),
+    ) -> Result<(Self, HpkeSecretKey), MlsError>
+    where
+        CSP: CipherSuiteProvider,
+    {
+        let (secret_key, public_key) = key_pair;
+
         let mut leaf_node = LeafNode {
             public_key,
             signing_identity,
@@ -230,7 +260,7 @@ impl<'a> From<(&'a [u8], u32)> for LeafNodeSigningContext<'a> {
 }
 
 impl<'a> Signable<'a> for LeafNode {
-    const SIGN_LABEL: &'static str = "LeafNodeTBS";
+    const SIGN_LABEL: &'static str = SignatureLabel::LeafNode.as_str();
 
     type SigningContext = LeafNodeSigningContext<'a>;
 
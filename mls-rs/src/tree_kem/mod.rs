@@ -40,7 +40,7 @@ pub mod node;
 pub mod parent_hash;
 pub mod path_secret;
 mod private;
-mod tree_hash;
+pub(crate) mod tree_hash;
 pub mod tree_validator;
 pub mod update_path;
 
@@ -86,6 +86,7 @@ impl TreeKemPublic {
         Default::default()
     }
 
+    #[cfg(any(test, feature = "external_client"))]
     #[cfg_attr(not(feature = "tree_index"), allow(unused))]
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub(crate) async fn import_node_data<IP>(
@@ -343,7 +344,8 @@ impl TreeKemPublic {
         I: IdentityProvider,
         CP: CipherSuiteProvider,
     {
-        // Apply removes (they commute with updates because they don't touch the same leaves)
+        // Apply removes. RFC 9420 applies updates first, but the two commute because an update
+        // from a removed member is rejected below, so the resulting tree is the same.
         for i in (0..proposal_bundle.remove_proposals().len()).rev() {
             let index = proposal_bundle.remove_proposals()[i].proposal.to_remove;
             let res = self.nodes.blank_leaf_node(index);
@@ -364,8 +366,8 @@ impl TreeKemPublic {
 
             if proposal_bundle.remove_proposals()[i].is_by_value() || !filter {
                 res?;
-            } else if res.is_err() {
-                proposal_bundle.remove::<RemoveProposal>(i);
+            } else if let Err(e) = res {
+                proposal_bundle.reject::<RemoveProposal>(i, &e);
             }
         }
 
@@ -401,6 +403,7 @@ impl TreeKemPublic {
         let mut removed_leaves = vec![];
         let mut updated_indices = vec![];
         let mut bad_indices = vec![];
+        let mut revert_error = None;
 
         // Apply updates one by one. If there's an update which we can't apply or revert, we revert
         // all updates.
@@ -412,42 +415,42 @@ impl TreeKemPublic {
             #[cfg(not(feature = "tree_index"))]
             let res = index_insert(&self.nodes, &new_leaf, index, id_provider, extensions).await;
 
-            let err = res.is_err();
-
-            if !filter {
-                res?;
-            }
-
-            if !err {
-                self.nodes.insert_leaf(index, new_leaf);
-                removed_leaves.push(old_leaf);
-                updated_indices.push(index);
-            } else {
-                #[cfg(feature = "tree_index")]
-                let res =
-                    index_insert(&mut self.index, &old_leaf, index, id_provider, extensions).await;
-
-                #[cfg(not(feature = "tree_index"))]
-                let res =
-                    index_insert(&self.nodes, &old_leaf, index, id_provider, extensions).await;
-
-                if res.is_ok() {
-                    self.nodes.insert_leaf(index, old_leaf);
-                    bad_indices.push(i);
-                } else {
-                    // Revert all updates and stop. We're already in the "filter" case, so we don't throw an error.
+            match res {
+                Err(e) if !filter => return Err(e),
+                Err(e) => {
                     #[cfg(feature = "tree_index")]
-                    {
-                        self.index = index_clone;
+                    let res =
+                        index_insert(&mut self.index, &old_leaf, index, id_provider, extensions)
+                            .await;
+
+                    #[cfg(not(feature = "tree_index"))]
+                    let res =
+                        index_insert(&self.nodes, &old_leaf, index, id_provider, extensions).await;
+
+                    if res.is_ok() {
+                        self.nodes.insert_leaf(index, old_leaf);
+                        bad_indices.push((i, e));
+                    } else {
+                        // Revert all updates and stop. We're already in the "filter" case, so we don't throw an error.
+                        #[cfg(feature = "tree_index")]
+                        {
+                            self.index = index_clone;
+                        }
+
+                        removed_leaves
+                            .into_iter()
+                            .zip(updated_indices.iter())
+                            .for_each(|(leaf, index)| self.nodes.insert_leaf(*index, leaf));
+
+                        updated_indices = vec![];
+                        revert_error = Some(e);
+                        break;
                     }
-
-                    removed_leaves
-                        .into_iter()
-                        .zip(updated_indices.iter())
-                        .for_each(|(leaf, index)| self.nodes.insert_leaf(*index, leaf));
-
-                    updated_indices = vec![];
-                    break;
+                }
+                Ok(_) => {
+                    self.nodes.insert_leaf(index, new_leaf);
+                    removed_leaves.push(old_leaf);
+                    updated_indices.push(index);
                 }
             }
         }
@@ -458,16 +461,22 @@ impl TreeKemPublic {
             .try_for_each(|index| self.nodes.blank_direct_path(*index).map(|_| ()))?;
 
         // Remove rejected updates from applied proposals
-        if updated_indices.is_empty() {
+        if let Some(e) = revert_error {
             // This takes care of the "revert all" scenario
-            proposal_bundle.updates = vec![];
+            for i in (0..proposal_bundle.updates.len()).rev() {
+                proposal_bundle.reject::<UpdateProposal>(i, &e);
+            }
         } else {
-            for i in bad_indices.into_iter().rev() {
-                proposal_bundle.remove::<UpdateProposal>(i);
+            for (i, e) in bad_indices.into_iter().rev() {
+                proposal_bundle.reject::<UpdateProposal>(i, &e);
                 proposal_bundle.update_senders.remove(i);
             }
         }
 
+        if updated_indices.is_empty() {
+            proposal_bundle.updates = vec![];
+        }
+
         // Apply adds
         let mut start = LeafIndex(0);
         let mut added = vec![];
@@ -484,18 +493,18 @@ impl TreeKemPublic {
                 .add_leaf(leaf, id_provider, extensions, Some(start))
                 .await;
 
-            if let Ok(index) = res {
-                start = index;
-                added.push(start);
-            } else if proposal_bundle.additions[i].is_by_value() || !filter {
-                res?;
-            } else {
-                bad_indexes.push(i);
+            match res {
+                Ok(index) => {
+                    start = index;
+                    added.push(start);
+                }
+                Err(e) if proposal_bundle.additions[i].is_by_value() || !filter => return Err(e),
+                Err(e) => bad_indexes.push((i, e)),
             }
         }
 
-        for i in bad_indexes.into_iter().rev() {
-            proposal_bundle.remove::<AddProposal>(i);
+        for (i, e) in bad_indexes.into_iter().rev() {
+            proposal_bundle.reject::<AddProposal>(i, &e);
         }
 
         self.nodes.trim();
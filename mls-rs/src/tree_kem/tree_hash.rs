@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
 use super::leaf_node::LeafNode;
-use super::node::{LeafIndex, NodeVec};
+use super::node::{LeafIndex, NodeIndex, NodeVec};
 use super::tree_math::BfsIterTopDown;
 use crate::client::MlsError;
 use crate::crypto::CipherSuiteProvider;
@@ -51,6 +51,14 @@ pub(crate) struct TreeHashes {
     pub current: Vec<TreeHash>,
 }
 
+/// Node on the direct path of a leaf, along with the tree hash of its child
+/// that is not on the direct path.
+#[derive(Clone, Debug, MlsSize, MlsEncode, MlsDecode, PartialEq)]
+pub(crate) struct TreeHashPathNode {
+    pub parent_node: Option<Parent>,
+    pub sibling_hash: TreeHash,
+}
+
 #[derive(Debug, MlsSize, MlsEncode)]
 struct LeafNodeHashInput<'a> {
     leaf_index: LeafIndex,
@@ -85,6 +93,28 @@ impl TreeKemPublic {
         Ok(self.tree_hashes.current[root as usize].to_vec())
     }
 
+    /// Tree hash computed from the nodes, ignoring the cached hashes.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn recomputed_tree_hash<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+    ) -> Result<Vec<u8>, MlsError> {
+        let mut hashes = Vec::new();
+        let num_leaves = self.total_leaf_count();
+
+        tree_hash(
+            &mut hashes,
+            &self.nodes,
+            None,
+            &[],
+            num_leaves,
+            cipher_suite_provider,
+        )
+        .await?;
+
+        Ok(hashes[num_leaves.root() as usize].to_vec())
+    }
+
     // Update hashes after `committer` makes changes to the tree. `path_blank` is the
     // list of leaves whose paths were blanked, i.e. updates and removes.
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -143,6 +173,49 @@ impl TreeKemPublic {
         Ok(())
     }
 
+    // Nodes needed to recompute the tree hash from the leaf at `leaf_index`,
+    // from the bottom of the tree to the root.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn tree_hash_path<P: CipherSuiteProvider>(
+        &self,
+        leaf_index: LeafIndex,
+        cipher_suite_provider: &P,
+    ) -> Result<Vec<TreeHashPathNode>, MlsError> {
+        let num_leaves = self.total_leaf_count();
+
+        let mut computed = Vec::new();
+
+        let hashes = if self.tree_hashes.current.is_empty() {
+            tree_hash(
+                &mut computed,
+                &self.nodes,
+                None,
+                &[],
+                num_leaves,
+                cipher_suite_provider,
+            )
+            .await?;
+
+            &computed
+        } else {
+            &self.tree_hashes.current
+        };
+
+        let mut path = Vec::new();
+        let mut node = NodeIndex::from(leaf_index);
+
+        while let Some(ps) = node.parent_sibling(&num_leaves) {
+            path.push(TreeHashPathNode {
+                parent_node: self.nodes.borrow_as_parent(ps.parent).ok().cloned(),
+                sibling_hash: hashes[ps.sibling as usize].clone(),
+            });
+
+            node = ps.parent;
+        }
+
+        Ok(path)
+    }
+
     pub(crate) fn unmerged_in_subtree(
         &self,
         node_unmerged: u32,
@@ -312,6 +385,52 @@ async fn tree_hash<P: CipherSuiteProvider>(
     Ok(())
 }
 
+// Recompute the tree hash of a tree with `num_leaves` leaves from the leaf
+// node at `leaf_index` and its path computed with `tree_hash_path`.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub(crate) async fn tree_hash_from_path<P: CipherSuiteProvider>(
+    leaf_index: LeafIndex,
+    leaf_node: &LeafNode,
+    num_leaves: u32,
+    path: &[TreeHashPathNode],
+    cipher_suite_provider: &P,
+) -> Result<Vec<u8>, MlsError> {
+    if !num_leaves.is_power_of_two() || *leaf_index >= num_leaves {
+        return Err(MlsError::InvalidTreeIndex);
+    }
+
+    let mut hash = hash_for_leaf(leaf_index, Some(leaf_node), cipher_suite_provider).await?;
+    let mut node = NodeIndex::from(leaf_index);
+    let mut path = path.iter();
+
+    while let Some(ps) = node.parent_sibling(&num_leaves) {
+        let path_node = path.next().ok_or(MlsError::TreeHashMismatch)?;
+
+        let (left_hash, right_hash) = if ps.sibling < node {
+            (&*path_node.sibling_hash, &*hash)
+        } else {
+            (&*hash, &*path_node.sibling_hash)
+        };
+
+        hash = hash_for_parent(
+            path_node.parent_node.as_ref(),
+            cipher_suite_provider,
+            &[],
+            left_hash,
+            right_hash,
+        )
+        .await?;
+
+        node = ps.parent;
+    }
+
+    if path.next().is_some() {
+        return Err(MlsError::TreeHashMismatch);
+    }
+
+    Ok(hash)
+}
+
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 async fn hash_for_leaf<P: CipherSuiteProvider>(
     leaf_index: LeafIndex,
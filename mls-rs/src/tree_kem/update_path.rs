@@ -182,6 +182,8 @@ mod tests {
             indexes_of_added_kpkgs: vec![],
             external_init_index: None,
             #[cfg(feature = "state_update")]
+            applied_order: vec![],
+            #[cfg(feature = "state_update")]
             unused_proposals: vec![],
         }
     }
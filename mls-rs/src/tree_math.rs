@@ -0,0 +1,228 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+//! Index arithmetic for the ratchet tree, as defined in
+//! [RFC 9420 Appendix C](https://www.rfc-editor.org/rfc/rfc9420.html#appendix-C).
+//!
+//! Nodes are identified by their index in the array representation of the
+//! tree, in which leaves have even and parent nodes have odd indices. The
+//! index of a member, e.g. [`Member::index`](crate::group::Member::index), is
+//! a leaf index and converts to a node index with [`leaf_to_node`].
+//!
+//! Ratchet trees always have a power of two leaves. Functions taking the
+//! number of leaves `n_leaves` return `None` or an empty path for other
+//! values and for nodes outside of the tree.
+
+use alloc::vec::Vec;
+
+use crate::tree_kem::math::TreeIndex;
+
+/// Number of nodes in a tree with `n_leaves` leaves.
+pub fn node_width(n_leaves: u32) -> u32 {
+    if n_leaves == 0 {
+        0
+    } else {
+        2 * (n_leaves - 1) + 1
+    }
+}
+
+/// Index of the root node of a tree with `n_leaves` leaves.
+pub fn root(n_leaves: u32) -> u32 {
+    let width = node_width(n_leaves);
+    (1 << log2(width)) - 1
+}
+
+/// Node index of the leaf with index `leaf`.
+pub fn leaf_to_node(leaf: u32) -> u32 {
+    2 * leaf
+}
+
+/// Leaf index of `node`, if it is a leaf.
+pub fn node_to_leaf(node: u32) -> Option<u32> {
+    is_leaf(node).then_some(node / 2)
+}
+
+/// Determines if `node` is a leaf.
+pub fn is_leaf(node: u32) -> bool {
+    node.is_leaf()
+}
+
+/// Level of `node` in the tree, 0 for leaves.
+pub fn level(node: u32) -> u32 {
+    node.trailing_ones()
+}
+
+/// Left child of `node`, if it is a parent node.
+pub fn left(node: u32) -> Option<u32> {
+    (!node.is_leaf()).then(|| node.left_unchecked())
+}
+
+/// Right child of `node`, if it is a parent node.
+pub fn right(node: u32) -> Option<u32> {
+    (!node.is_leaf()).then(|| node.right_unchecked())
+}
+
+/// Parent of `node`, unless it is the root.
+pub fn parent(node: u32, n_leaves: u32) -> Option<u32> {
+    in_tree(node, n_leaves)
+        .then(|| node.parent_sibling(&n_leaves))
+        .flatten()
+        .map(|ps| ps.parent)
+}
+
+/// Other child of the parent of `node`, unless it is the root.
+pub fn sibling(node: u32, n_leaves: u32) -> Option<u32> {
+    in_tree(node, n_leaves)
+        .then(|| node.parent_sibling(&n_leaves))
+        .flatten()
+        .map(|ps| ps.sibling)
+}
+
+/// Ancestors of `node`, from its parent up to the root.
+pub fn direct_path(node: u32, n_leaves: u32) -> Vec<u32> {
+    if !in_tree(node, n_leaves) {
+        return Vec::new();
+    }
+
+    node.direct_copath(&n_leaves)
+        .into_iter()
+        .map(|n| n.path)
+        .collect()
+}
+
+/// Siblings of `node` and of its ancestors below the root.
+pub fn copath(node: u32, n_leaves: u32) -> Vec<u32> {
+    if !in_tree(node, n_leaves) {
+        return Vec::new();
+    }
+
+    node.direct_copath(&n_leaves)
+        .into_iter()
+        .map(|n| n.copath)
+        .collect()
+}
+
+/// Lowest common ancestor of nodes `x` and `y`, which is `x` or `y` if one
+/// is an ancestor of the other.
+pub fn common_ancestor(x: u32, y: u32) -> u32 {
+    let (lx, ly) = (level(x) + 1, level(y) + 1);
+
+    if lx <= ly && x >> ly == y >> ly {
+        return y;
+    }
+
+    if ly <= lx && x >> lx == y >> lx {
+        return x;
+    }
+
+    let (mut xn, mut yn, mut k) = (x, y, 0);
+
+    while xn != yn {
+        xn >>= 1;
+        yn >>= 1;
+        k += 1;
+    }
+
+    (xn << k) + (1 << (k - 1)) - 1
+}
+
+fn in_tree(node: u32, n_leaves: u32) -> bool {
+    n_leaves.is_power_of_two() && node < node_width(n_leaves)
+}
+
+fn log2(x: u32) -> u32 {
+    x.checked_ilog2().unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    #[derive(Serialize, Deserialize)]
+    struct TestCase {
+        n_leaves: u32,
+        n_nodes: u32,
+        root: u32,
+        left: Vec<Option<u32>>,
+        right: Vec<Option<u32>>,
+        parent: Vec<Option<u32>>,
+        sibling: Vec<Option<u32>>,
+    }
+
+    #[cfg_attr(coverage_nightly, coverage(off))]
+    fn generate_test_cases() -> Vec<TestCase> {
+        (0..8)
+            .map(|log_n_leaves| {
+                let n_leaves = 1 << log_n_leaves;
+                let n_nodes = node_width(n_leaves);
+
+                TestCase {
+                    n_leaves,
+                    n_nodes,
+                    root: root(n_leaves),
+                    left: (0..n_nodes).map(left).collect(),
+                    right: (0..n_nodes).map(right).collect(),
+                    parent: (0..n_nodes).map(|x| parent(x, n_leaves)).collect(),
+                    sibling: (0..n_nodes).map(|x| sibling(x, n_leaves)).collect(),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tree_math_test_vectors() {
+        let test_cases: Vec<TestCase> = load_test_case_json!(tree_math, generate_test_cases());
+
+        for case in test_cases {
+            assert_eq!(node_width(case.n_leaves), case.n_nodes);
+            assert_eq!(root(case.n_leaves), case.root);
+
+            for x in 0..case.n_nodes {
+                let i = x as usize;
+
+                assert_eq!(left(x), case.left[i]);
+                assert_eq!(right(x), case.right[i]);
+                assert_eq!(parent(x, case.n_leaves), case.parent[i]);
+                assert_eq!(sibling(x, case.n_leaves), case.sibling[i]);
+            }
+
+            assert_eq!(parent(case.n_nodes, case.n_leaves), None);
+        }
+    }
+
+    #[test]
+    fn paths_and_common_ancestors_are_consistent() {
+        let n_leaves = 16;
+
+        assert_eq!(direct_path(0, n_leaves), [1, 3, 7, 15]);
+        assert_eq!(copath(0, n_leaves), [2, 5, 11, 23]);
+        assert!(direct_path(0, 12).is_empty());
+
+        for x in 0..node_width(n_leaves) {
+            let path = direct_path(x, n_leaves);
+
+            assert_eq!(path.len(), copath(x, n_leaves).len());
+            assert_eq!(path.first().copied(), parent(x, n_leaves));
+
+            for y in 0..node_width(n_leaves) {
+                let ancestor = common_ancestor(x, y);
+
+                assert_eq!(ancestor, common_ancestor(y, x));
+                assert!(ancestor == x || path.contains(&ancestor));
+            }
+        }
+
+        for leaf in 0..n_leaves {
+            assert_eq!(node_to_leaf(leaf_to_node(leaf)), Some(leaf));
+        }
+
+        assert_eq!(node_to_leaf(1), None);
+    }
+}
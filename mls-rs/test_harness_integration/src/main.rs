@@ -28,7 +28,7 @@ use mls_rs::{
     psk::ExternalPskId,
     storage_provider::in_memory::{InMemoryKeyPackageStorage, InMemoryPreSharedKeyStorage},
     CipherSuite, CipherSuiteProvider, Client, CryptoProvider, Extension, ExtensionList, Group,
-    MlsMessage, MlsRules,
+    MlsMessage, MlsRules, Validated,
 };
 
 #[cfg(feature = "by_ref_proposal")]
@@ -263,7 +263,7 @@ impl MlsClient for MlsClientImpl {
         let request = request.into_inner();
         let mut clients = self.clients.lock().await;
 
-        let welcome_msg = MlsMessage::from_bytes(&request.welcome).map_err(abort)?;
+        let welcome_msg = Validated::from_bytes(&request.welcome).map_err(abort)?;
 
         let client = clients
             .get_mut(&request.transaction_id)
@@ -271,7 +271,7 @@ impl MlsClient for MlsClientImpl {
 
         let (group, _) = client
             .client
-            .join_group(get_tree(&request.ratchet_tree)?, &welcome_msg)
+            .join_group_validated(get_tree(&request.ratchet_tree)?, &welcome_msg)
             .map_err(abort)?;
 
         let epoch_authenticator = group.epoch_authenticator().map_err(abort)?.to_vec();
@@ -430,7 +430,7 @@ impl MlsClient for MlsClientImpl {
     ) -> Result<Response<UnprotectResponse>, Status> {
         let request = request.into_inner();
         let mut clients = self.clients.lock().await;
-        let ciphertext = MlsMessage::from_bytes(&request.ciphertext).map_err(abort)?;
+        let ciphertext = Validated::from_bytes(&request.ciphertext).map_err(abort)?;
 
         let message = clients
             .get_mut(&request.state_id)
@@ -438,7 +438,7 @@ impl MlsClient for MlsClientImpl {
             .group
             .as_mut()
             .ok_or_else(|| Status::aborted("no group with such index."))?
-            .process_incoming_message(ciphertext)
+            .process_incoming_message_validated(ciphertext)
             .map_err(abort)?;
 
         let app_msg = match message {
@@ -691,9 +691,9 @@ impl MlsClientImpl {
             .ok_or_else(|| Status::aborted("no group with such index."))?;
 
         for proposal_bytes in &request.by_reference {
-            let proposal = MlsMessage::from_bytes(proposal_bytes).map_err(abort)?;
+            let proposal = Validated::from_bytes(proposal_bytes).map_err(abort)?;
 
-            match group.process_incoming_message(proposal) {
+            match group.process_incoming_message_validated(proposal) {
                 Ok(_) | Err(MlsError::CantProcessMessageFromSelf) => Ok(()),
                 Err(e) => Err(abort(e)),
             }?;
@@ -787,17 +787,19 @@ impl MlsClientImpl {
             .ok_or_else(|| Status::aborted("no group with such index."))?;
 
         for proposal in &request.proposal {
-            let proposal = MlsMessage::from_bytes(proposal).map_err(abort)?;
+            let proposal = Validated::from_bytes(proposal).map_err(abort)?;
 
-            match group.process_incoming_message(proposal) {
+            match group.process_incoming_message_validated(proposal) {
                 Ok(_) | Err(MlsError::CantProcessMessageFromSelf) => Ok(()),
                 Err(e) => Err(abort(e)),
             }?;
         }
 
-        let commit = MlsMessage::from_bytes(&request.commit).map_err(abort)?;
+        let commit = Validated::from_bytes(&request.commit).map_err(abort)?;
 
-        let message = group.process_incoming_message(commit).map_err(abort)?;
+        let message = group
+            .process_incoming_message_validated(commit)
+            .map_err(abort)?;
 
         let resp = HandleCommitResponse {
             state_id: request.state_id,
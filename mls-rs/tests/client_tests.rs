@@ -7,7 +7,7 @@ use cfg_if::cfg_if;
 use mls_rs::client_builder::MlsConfig;
 use mls_rs::error::MlsError;
 use mls_rs::group::proposal::Proposal;
-use mls_rs::group::ReceivedMessage;
+use mls_rs::group::{ReceivedMessage, Validated};
 use mls_rs::identity::SigningIdentity;
 use mls_rs::mls_rules::CommitOptions;
 use mls_rs::ExtensionList;
@@ -21,9 +21,6 @@ use rand::RngCore;
 
 use mls_rs::test_utils::{all_process_message, get_test_basic_credential};
 
-#[cfg(mls_build_async)]
-use futures::Future;
-
 cfg_if! {
     if #[cfg(target_arch = "wasm32")] {
         use mls_rs_crypto_webcrypto::WebCryptoProvider as TestCryptoProvider;
@@ -80,79 +77,13 @@ use wasm_bindgen_test::wasm_bindgen_test as futures_test;
 #[cfg(all(mls_build_async, not(target_arch = "wasm32")))]
 use futures_test::test as futures_test;
 
-#[cfg(feature = "private_message")]
-#[cfg(mls_build_async)]
-async fn test_on_all_params<F, Fut>(test: F)
-where
-    F: Fn(ProtocolVersion, CipherSuite, usize, bool) -> Fut,
-    Fut: Future<Output = ()>,
-{
-    for version in ProtocolVersion::all() {
-        for cs in TestCryptoProvider::all_supported_cipher_suites() {
-            for encrypt_controls in [true, false] {
-                test(version, cs, 10, encrypt_controls).await;
-            }
-        }
-    }
-}
-
-#[cfg(feature = "private_message")]
-#[cfg(not(mls_build_async))]
-fn test_on_all_params<F>(test: F)
-where
-    F: Fn(ProtocolVersion, CipherSuite, usize, bool),
-{
-    for version in ProtocolVersion::all() {
-        for cs in TestCryptoProvider::all_supported_cipher_suites() {
-            for encrypt_controls in [true, false] {
-                test(version, cs, 10, encrypt_controls);
-            }
-        }
-    }
+fn test_params() -> Vec<(ProtocolVersion, CipherSuite, bool)> {
+    mls_rs::test_utils::all_test_params(&TestCryptoProvider::default())
 }
 
-#[cfg(not(feature = "private_message"))]
-#[cfg(mls_build_async)]
-async fn test_on_all_params<F, Fut>(test: F)
-where
-    F: Fn(ProtocolVersion, CipherSuite, usize, bool) -> Fut,
-    Fut: Future<Output = ()>,
-{
-    test_on_all_params_plaintext(test).await;
-}
-
-#[cfg(not(feature = "private_message"))]
-#[cfg(not(mls_build_async))]
-fn test_on_all_params<F>(test: F)
-where
-    F: Fn(ProtocolVersion, CipherSuite, usize, bool),
-{
-    test_on_all_params_plaintext(test);
-}
-
-#[cfg(mls_build_async)]
-async fn test_on_all_params_plaintext<F, Fut>(test: F)
-where
-    F: Fn(ProtocolVersion, CipherSuite, usize, bool) -> Fut,
-    Fut: Future<Output = ()>,
-{
-    for version in ProtocolVersion::all() {
-        for cs in TestCryptoProvider::all_supported_cipher_suites() {
-            test(version, cs, 10, false).await;
-        }
-    }
-}
-
-#[cfg(not(mls_build_async))]
-fn test_on_all_params_plaintext<F>(test: F)
-where
-    F: Fn(ProtocolVersion, CipherSuite, usize, bool),
-{
-    for version in ProtocolVersion::all() {
-        for cs in TestCryptoProvider::all_supported_cipher_suites() {
-            test(version, cs, 10, false);
-        }
-    }
+#[cfg(feature = "by_ref_proposal")]
+fn plaintext_test_params() -> Vec<(ProtocolVersion, CipherSuite, bool)> {
+    mls_rs::test_utils::plaintext_test_params(&TestCryptoProvider::default())
 }
 
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -185,14 +116,19 @@ async fn test_create(
     alice_group.apply_pending_commit().await.unwrap();
 
     // Bob receives the welcome message and joins the group
-    let (bob_group, _) = bob.join_group(None, welcome).await.unwrap();
+    let (bob_group, _) = bob
+        .join_group_validated(None, &Validated::new(welcome.clone()).unwrap())
+        .await
+        .unwrap();
 
     assert!(Group::equal_group_state(&alice_group, &bob_group));
 }
 
 #[maybe_async::test(not(mls_build_async), async(mls_build_async, futures_test))]
 async fn test_create_group() {
-    test_on_all_params(test_create).await;
+    for (version, cs, encrypt_controls) in test_params() {
+        test_create(version, cs, 10, encrypt_controls).await;
+    }
 }
 
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -229,7 +165,9 @@ async fn test_empty_commits(
 
 #[maybe_async::test(not(mls_build_async), async(mls_build_async, futures_test))]
 async fn test_group_path_updates() {
-    test_on_all_params(test_empty_commits).await;
+    for (version, cs, encrypt_controls) in test_params() {
+        test_empty_commits(version, cs, 10, encrypt_controls).await;
+    }
 }
 
 #[cfg(feature = "by_ref_proposal")]
@@ -275,7 +213,9 @@ async fn test_update_proposals(
 #[cfg(feature = "by_ref_proposal")]
 #[maybe_async::test(not(mls_build_async), async(mls_build_async, futures_test))]
 async fn test_group_update_proposals() {
-    test_on_all_params(test_update_proposals).await;
+    for (version, cs, encrypt_controls) in test_params() {
+        test_update_proposals(version, cs, 10, encrypt_controls).await;
+    }
 }
 
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -337,7 +277,9 @@ async fn test_remove_proposals(
 
 #[maybe_async::test(not(mls_build_async), async(mls_build_async, futures_test))]
 async fn test_group_remove_proposals() {
-    test_on_all_params(test_remove_proposals).await;
+    for (version, cs, encrypt_controls) in test_params() {
+        test_remove_proposals(version, cs, 10, encrypt_controls).await;
+    }
 }
 
 #[cfg(feature = "private_message")]
@@ -375,7 +317,9 @@ async fn test_application_messages(
             for g in groups.iter_mut() {
                 if g.current_member_index() != sender_index {
                     let decrypted = g
-                        .process_incoming_message(ciphertext.clone())
+                        .process_incoming_message_validated(
+                            Validated::new(ciphertext.clone()).unwrap(),
+                        )
                         .await
                         .unwrap();
 
@@ -413,7 +357,10 @@ async fn test_out_of_order_application_messages() {
 
     alice_group.apply_pending_commit().await.unwrap();
 
-    bob_group.process_incoming_message(commit).await.unwrap();
+    bob_group
+        .process_incoming_message_validated(Validated::new(commit).unwrap())
+        .await
+        .unwrap();
 
     ciphertexts.push(
         alice_group
@@ -431,7 +378,7 @@ async fn test_out_of_order_application_messages() {
 
     for i in [3, 2, 1, 0] {
         let res = bob_group
-            .process_incoming_message(ciphertexts[i].clone())
+            .process_incoming_message_validated(Validated::new(ciphertexts[i].clone()).unwrap())
             .await
             .unwrap();
 
@@ -445,7 +392,9 @@ async fn test_out_of_order_application_messages() {
 #[cfg(feature = "private_message")]
 #[maybe_async::test(not(mls_build_async), async(mls_build_async, futures_test))]
 async fn test_group_application_messages() {
-    test_on_all_params(test_application_messages).await
+    for (version, cs, encrypt_controls) in test_params() {
+        test_application_messages(version, cs, 10, encrypt_controls).await;
+    }
 }
 
 #[cfg(feature = "private_message")]
@@ -466,7 +415,7 @@ async fn processing_message_from_self_returns_error(
         .unwrap();
 
     let error = creator_group
-        .process_incoming_message(msg)
+        .process_incoming_message_validated(Validated::new(msg).unwrap())
         .await
         .unwrap_err();
 
@@ -476,7 +425,9 @@ async fn processing_message_from_self_returns_error(
 #[cfg(feature = "private_message")]
 #[maybe_async::test(not(mls_build_async), async(mls_build_async, futures_test))]
 async fn test_processing_message_from_self_returns_error() {
-    test_on_all_params(processing_message_from_self_returns_error).await;
+    for (version, cs, encrypt_controls) in test_params() {
+        processing_message_from_self_returns_error(version, cs, 10, encrypt_controls).await;
+    }
 }
 
 #[cfg(feature = "by_ref_proposal")]
@@ -521,7 +472,7 @@ async fn external_commits_work(
 
         for group in groups.iter_mut() {
             group
-                .process_incoming_message(commit.clone())
+                .process_incoming_message_validated(Validated::new(commit.clone()).unwrap())
                 .await
                 .unwrap();
         }
@@ -538,7 +489,7 @@ async fn external_commits_work(
 
         for (_, group) in groups.iter_mut().enumerate().filter(|&(j, _)| i != j) {
             let processed = group
-                .process_incoming_message(message.clone())
+                .process_incoming_message_validated(Validated::new(message.clone()).unwrap())
                 .await
                 .unwrap();
 
@@ -558,7 +509,9 @@ async fn external_commits_work(
 #[cfg(feature = "by_ref_proposal")]
 #[maybe_async::test(not(mls_build_async), async(mls_build_async, futures_test))]
 async fn test_external_commits() {
-    test_on_all_params_plaintext(external_commits_work).await
+    for (version, cs, encrypt_controls) in plaintext_test_params() {
+        external_commits_work(version, cs, 10, encrypt_controls).await;
+    }
 }
 
 #[maybe_async::test(not(mls_build_async), async(mls_build_async, futures_test))]
@@ -613,7 +566,10 @@ async fn reinit_works() {
 
     alice_group.apply_pending_commit().await.unwrap();
 
-    let (mut bob_group, _) = bob1.join_group(None, welcome).await.unwrap();
+    let (mut bob_group, _) = bob1
+        .join_group_validated(None, &Validated::new(welcome.clone()).unwrap())
+        .await
+        .unwrap();
 
     // Alice proposes reinit
     let reinit_proposal_message = alice_group
@@ -629,7 +585,7 @@ async fn reinit_works() {
 
     // Bob commits the reinit
     bob_group
-        .process_incoming_message(reinit_proposal_message)
+        .process_incoming_message_validated(Validated::new(reinit_proposal_message).unwrap())
         .await
         .unwrap();
 
@@ -646,7 +602,10 @@ async fn reinit_works() {
     #[cfg(not(feature = "state_update"))]
     bob_group.apply_pending_commit().await.unwrap();
 
-    let message = alice_group.process_incoming_message(commit).await.unwrap();
+    let message = alice_group
+        .process_incoming_message_validated(Validated::new(commit).unwrap())
+        .await
+        .unwrap();
 
     #[cfg(feature = "state_update")]
     if let ReceivedMessage::Commit(commit_description) = message {
@@ -716,12 +675,15 @@ async fn reinit_works() {
     alice_group.apply_pending_commit().await.unwrap();
 
     bob_group
-        .process_incoming_message(commit_output.commit_message)
+        .process_incoming_message_validated(Validated::new(commit_output.commit_message).unwrap())
         .await
         .unwrap();
 
     carol
-        .join_group(None, &commit_output.welcome_messages[0])
+        .join_group_validated(
+            None,
+            &Validated::new(commit_output.welcome_messages[0].clone()).unwrap(),
+        )
         .await
         .unwrap();
 }
@@ -765,12 +727,18 @@ async fn external_joiner_can_process_siblings_update() {
     // New client's sibling proposes an update to blank their common parent
     let p = groups[0].propose_update(Vec::new()).await.unwrap();
     all_process_message(&mut groups, &p, 0, false).await;
-    group.process_incoming_message(p).await.unwrap();
+    group
+        .process_incoming_message_validated(Validated::new(p).unwrap())
+        .await
+        .unwrap();
 
     // Some other member commits
     let c = groups[1].commit(Vec::new()).await.unwrap().commit_message;
     all_process_message(&mut groups, &c, 2, true).await;
-    group.process_incoming_message(c).await.unwrap();
+    group
+        .process_incoming_message_validated(Validated::new(c).unwrap())
+        .await
+        .unwrap();
 }
 
 #[maybe_async::test(not(mls_build_async), async(mls_build_async, futures_test))]
@@ -843,5 +811,8 @@ async fn external_info_from_commit_allows_to_join() {
         .await
         .unwrap();
 
-    alice.process_incoming_message(commit).await.unwrap();
+    alice
+        .process_incoming_message_validated(Validated::new(commit).unwrap())
+        .await
+        .unwrap();
 }